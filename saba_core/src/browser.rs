@@ -1,10 +1,11 @@
-use crate::renderer::page::Page;
+use crate::renderer::{css::media::MediaEnvironment, page::Page};
 use alloc::{rc::Rc, vec::Vec};
 use core::cell::RefCell;
 
 pub struct Browser {
     active_page_index: usize,
     pages: Vec<Rc<RefCell<Page>>>,
+    media_environment: MediaEnvironment,
 }
 
 impl Browser {
@@ -12,6 +13,7 @@ impl Browser {
         let browser = Rc::new(RefCell::new(Self {
             active_page_index: 0,
             pages: Vec::new(),
+            media_environment: MediaEnvironment::default(),
         }));
 
         let page = Page::new(Rc::downgrade(&browser));
@@ -23,4 +25,15 @@ impl Browser {
     pub fn current_page(&self) -> Rc<RefCell<Page>> {
         self.pages[self.active_page_index].clone()
     }
+
+    pub fn media_environment(&self) -> MediaEnvironment {
+        self.media_environment
+    }
+
+    pub fn set_media_environment(&mut self, media_environment: MediaEnvironment) {
+        self.media_environment = media_environment;
+        for page in &self.pages {
+            page.borrow_mut().set_media_environment(media_environment);
+        }
+    }
 }
@@ -0,0 +1,590 @@
+use crate::renderer::{
+    css::{
+        computed_style::{expand_shorthand, is_supported_declaration, ComputedStyle, Sides},
+        cssom::{ComponentValue, CssRule, Declaration, StyleSheet},
+        custom_property::{contains_var, resolve_custom_properties, substitute_var},
+        media::{parse_media_query_list, MediaEnvironment},
+        parser::CssParser,
+        selector::{SelectorList, Specificity},
+        supports::supports_condition,
+        token::{CssTokenKind, CssTokenizer},
+        ua::USER_AGENT_STYLE_SHEET,
+        value::LengthContext,
+    },
+    dom::{api::children, node::Node},
+};
+use alloc::{
+    collections::BTreeMap,
+    format,
+    rc::Rc,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::cell::RefCell;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Origin {
+    UserAgent,
+    Author,
+}
+
+#[derive(Clone, Debug)]
+struct CollectedRule {
+    selectors: SelectorList,
+    declarations: Vec<Declaration>,
+    origin: Origin,
+    layer: String,
+}
+
+#[derive(Clone, Debug)]
+struct CascadedDeclaration {
+    declaration: Declaration,
+    origin: Origin,
+    inline: bool,
+    layer: usize,
+    specificity: Specificity,
+    order: usize,
+}
+
+// カスケードレイヤーの木。名前はドット区切りの完全な名前で持つ
+#[derive(Clone, Debug, Default)]
+struct LayerTree {
+    children: Vec<(String, LayerTree)>,
+}
+
+impl LayerTree {
+    fn register(&mut self, path: &[&str]) {
+        let (first, rest) = match path.split_first() {
+            Some(split) => split,
+            None => return,
+        };
+        let index = match self.children.iter().position(|(n, _)| n == first) {
+            Some(index) => index,
+            None => {
+                self.children
+                    .push((first.to_string(), LayerTree::default()));
+                self.children.len() - 1
+            }
+        };
+        self.children[index].1.register(rest);
+    }
+
+    // 子レイヤーが親レイヤーに属さない宣言よりも先に来るよう後順で番号を振る
+    fn assign_order(&self, prefix: &str, order: &mut BTreeMap<String, usize>) {
+        for (name, child) in &self.children {
+            let full_name = if prefix.is_empty() {
+                name.clone()
+            } else {
+                format!("{}.{}", prefix, name)
+            };
+            child.assign_order(&full_name, order);
+        }
+        let next = order.len();
+        order.insert(prefix.to_string(), next);
+    }
+}
+
+pub struct StyleResolver {
+    rules: Vec<CollectedRule>,
+    inline_styles: Vec<(Rc<RefCell<Node>>, Vec<Declaration>)>,
+    layer_order: BTreeMap<String, usize>,
+    environment: MediaEnvironment,
+    length_context: LengthContext,
+}
+
+impl StyleResolver {
+    pub fn new(
+        style_sheets: &[StyleSheet],
+        inline_styles: Vec<(Rc<RefCell<Node>>, Vec<Declaration>)>,
+        environment: &MediaEnvironment,
+    ) -> Self {
+        let mut resolver = Self {
+            rules: Vec::new(),
+            inline_styles,
+            layer_order: BTreeMap::new(),
+            environment: *environment,
+            length_context: LengthContext {
+                viewport_width: environment.width,
+                viewport_height: environment.height,
+                ..Default::default()
+            },
+        };
+        let mut layers = LayerTree::default();
+        let mut anonymous_layers = 0;
+
+        let mut parser = CssParser::new(CssTokenizer::new(USER_AGENT_STYLE_SHEET.to_string()));
+        let ua = parser.parse_stylesheet();
+        resolver.collect_rules(
+            &ua.rules,
+            Origin::UserAgent,
+            "",
+            &mut layers,
+            &mut anonymous_layers,
+        );
+        for style_sheet in style_sheets {
+            resolver.collect_rules(
+                &style_sheet.rules,
+                Origin::Author,
+                "",
+                &mut layers,
+                &mut anonymous_layers,
+            );
+        }
+
+        layers.assign_order("", &mut resolver.layer_order);
+        resolver
+    }
+
+    fn collect_rules(
+        &mut self,
+        rules: &[CssRule],
+        origin: Origin,
+        layer: &str,
+        layers: &mut LayerTree,
+        anonymous_layers: &mut usize,
+    ) {
+        let join = |name: &str| {
+            if layer.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}.{}", layer, name)
+            }
+        };
+
+        for rule in rules {
+            match rule {
+                CssRule::Style(style_rule) => self.rules.push(CollectedRule {
+                    selectors: style_rule.selectors.clone(),
+                    declarations: style_rule.declarations.clone(),
+                    origin,
+                    layer: layer.to_string(),
+                }),
+                CssRule::Media(media_rule) => {
+                    if parse_media_query_list(&media_rule.media).matches(&self.environment) {
+                        self.collect_rules(
+                            &media_rule.rules,
+                            origin,
+                            layer,
+                            layers,
+                            anonymous_layers,
+                        );
+                    }
+                }
+                CssRule::Layer(layer_rule) => match &layer_rule.rules {
+                    None => {
+                        for name in &layer_rule.names {
+                            let full_name = join(name);
+                            layers.register(&full_name.split('.').collect::<Vec<_>>());
+                        }
+                    }
+                    Some(rules) => {
+                        // 名前のないレイヤーは他から参照できない一意な名前を付ける
+                        let name = match layer_rule.names.first() {
+                            Some(name) => name.clone(),
+                            None => {
+                                *anonymous_layers += 1;
+                                format!("#anonymous{}", anonymous_layers)
+                            }
+                        };
+                        let full_name = join(&name);
+                        layers.register(&full_name.split('.').collect::<Vec<_>>());
+                        self.collect_rules(rules, origin, &full_name, layers, anonymous_layers);
+                    }
+                },
+                CssRule::Supports(supports_rule) => {
+                    if supports_condition(&supports_rule.condition) {
+                        self.collect_rules(
+                            &supports_rule.rules,
+                            origin,
+                            layer,
+                            layers,
+                            anonymous_layers,
+                        );
+                    }
+                }
+                // @importの取得はまだサポートしていない
+                CssRule::Import(_) | CssRule::FontFace(_) | CssRule::Keyframes(_) => {}
+            }
+        }
+    }
+
+    fn layer_index(&self, layer: &str) -> usize {
+        self.layer_order.get(layer).copied().unwrap_or(0)
+    }
+
+    fn cascaded_declarations(&self, node: &Rc<RefCell<Node>>) -> Vec<CascadedDeclaration> {
+        let mut declarations = Vec::new();
+        let mut order = 0;
+
+        for rule in &self.rules {
+            let specificity = match rule.selectors.matching_specificity(node) {
+                Some(specificity) => specificity,
+                None => {
+                    order += rule.declarations.len();
+                    continue;
+                }
+            };
+            for declaration in &rule.declarations {
+                declarations.push(CascadedDeclaration {
+                    declaration: declaration.clone(),
+                    origin: rule.origin,
+                    inline: false,
+                    layer: self.layer_index(&rule.layer),
+                    specificity,
+                    order,
+                });
+                order += 1;
+            }
+        }
+
+        if let Some((_, inline)) = self.inline_styles.iter().find(|(n, _)| Rc::ptr_eq(n, node)) {
+            for declaration in inline {
+                declarations.push(CascadedDeclaration {
+                    declaration: declaration.clone(),
+                    origin: Origin::Author,
+                    inline: true,
+                    layer: self.layer_index(""),
+                    specificity: Specificity::default(),
+                    order,
+                });
+                order += 1;
+            }
+        }
+
+        let layer_count = self.layer_order.len();
+        declarations.sort_by_key(|d| {
+            // 通常の宣言はUA < 作成者、!importantの宣言はその逆の順で優先される
+            let importance = match (d.origin, d.declaration.important) {
+                (Origin::UserAgent, false) => 0,
+                (Origin::Author, false) => 1,
+                (Origin::Author, true) => 2,
+                (Origin::UserAgent, true) => 3,
+            };
+            // !importantの宣言ではレイヤーの優先順位も逆転する
+            let layer = if d.declaration.important {
+                layer_count - d.layer
+            } else {
+                d.layer
+            };
+            (importance, d.inline, layer, d.specificity, d.order)
+        });
+        declarations
+    }
+
+    // root_font_sizeはルート要素の場合None
+    pub fn compute_style(
+        &self,
+        node: &Rc<RefCell<Node>>,
+        parent_style: &ComputedStyle,
+        root_font_size: Option<f64>,
+    ) -> ComputedStyle {
+        let mut style = ComputedStyle::inherit_from(parent_style);
+        let (custom, declarations): (Vec<Declaration>, Vec<Declaration>) = self
+            .cascaded_declarations(node)
+            .into_iter()
+            .map(|cascaded| cascaded.declaration)
+            .partition(|declaration| declaration.is_custom_property());
+
+        let custom: Vec<(String, Vec<ComponentValue>)> =
+            custom.into_iter().map(|d| (d.name, d.value)).collect();
+        style.custom_properties =
+            resolve_custom_properties(&custom, &parent_style.custom_properties);
+
+        // 優先度の低い順に並んでいるので後の宣言で上書きする
+        let mut longhands: Vec<(String, Vec<ComponentValue>)> = Vec::new();
+        for declaration in declarations {
+            let value = if contains_var(&declaration.value) {
+                // 置換に失敗した宣言は計算値の時点で無効となりunsetとして扱う
+                substitute_var(&declaration.value, &mut |name| style.custom_property(name))
+                    .filter(|value| is_supported_declaration(&declaration.name, value))
+                    .unwrap_or_else(|| {
+                        vec![ComponentValue::Token(CssTokenKind::Ident(
+                            "unset".to_string(),
+                        ))]
+                    })
+            } else if is_supported_declaration(&declaration.name, &declaration.value) {
+                declaration.value
+            } else {
+                // 解釈できない宣言はカスケードに参加しない
+                continue;
+            };
+            for (name, values) in expand_shorthand(&declaration.name, &value) {
+                longhands.retain(|(n, _)| *n != name);
+                longhands.push((name, values));
+            }
+        }
+
+        let find = |name: &str| longhands.iter().find(|(n, _)| n == name);
+
+        // 他のプロパティの計算に使うfont-sizeとcolorを先に決める
+        let mut context = LengthContext {
+            root_font_size: root_font_size.unwrap_or(16.0),
+            ..self.length_context
+        };
+        if let Some((name, values)) = find("font-size") {
+            style.apply_property(name, values, parent_style, &context);
+        }
+        context.root_font_size = root_font_size.unwrap_or(style.font_size);
+        if let Some((name, values)) = find("color") {
+            style.apply_property(name, values, parent_style, &context);
+        }
+        // border-colorの初期値はcurrentcolor
+        style.border_color = Sides::all(style.color);
+
+        for (name, values) in &longhands {
+            if name == "font-size" || name == "color" {
+                continue;
+            }
+            style.apply_property(name, values, parent_style, &context);
+        }
+
+        style.finalize(is_root_element(node));
+        style
+    }
+
+    // 全要素の計算値を求めてノードに保存する
+    pub fn resolve(&self, document: &Rc<RefCell<Node>>) {
+        self.resolve_children(document, &ComputedStyle::default(), None);
+    }
+
+    fn resolve_children(
+        &self,
+        node: &Rc<RefCell<Node>>,
+        parent_style: &ComputedStyle,
+        root_font_size: Option<f64>,
+    ) {
+        for child in children(node) {
+            if child.borrow().get_element().is_none() {
+                continue;
+            }
+            let style = self.compute_style(&child, parent_style, root_font_size);
+            let root_font_size = root_font_size.unwrap_or(style.font_size);
+            child.borrow_mut().set_computed_style(Some(style.clone()));
+            self.resolve_children(&child, &style, Some(root_font_size));
+        }
+    }
+}
+
+fn is_root_element(node: &Rc<RefCell<Node>>) -> bool {
+    node.borrow()
+        .parent()
+        .upgrade()
+        .is_some_and(|p| p.borrow().get_element().is_none())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::{
+        css::{
+            computed_style::{Display, LineHeight},
+            value::{Color, Length},
+        },
+        dom::node::Window,
+        html::{parser::HtmlParser, token::HtmlTokenizer},
+    };
+    use alloc::vec;
+
+    fn resolve(html: &str, css: &str) -> Rc<RefCell<Window>> {
+        let window = HtmlParser::new(HtmlTokenizer::new(html.to_string())).construct_tree();
+        let style_sheet = CssParser::new(CssTokenizer::new(css.to_string())).parse_stylesheet();
+        let document = window.borrow().document();
+        StyleResolver::new(&[style_sheet], Vec::new(), &MediaEnvironment::default())
+            .resolve(&document);
+        window
+    }
+
+    fn elements(window: &Rc<RefCell<Window>>) -> Vec<Rc<RefCell<Node>>> {
+        fn walk(node: &Rc<RefCell<Node>>, result: &mut Vec<Rc<RefCell<Node>>>) {
+            for child in children(node) {
+                if child.borrow().get_element().is_some() {
+                    result.push(child.clone());
+                }
+                walk(&child, result);
+            }
+        }
+        let mut result = Vec::new();
+        walk(&window.borrow().document(), &mut result);
+        result
+    }
+
+    fn style(node: &Rc<RefCell<Node>>) -> ComputedStyle {
+        node.borrow()
+            .computed_style()
+            .expect("computed style is not set")
+    }
+
+    #[test]
+    fn test_user_agent_style() {
+        let window = resolve(
+            "<html><head><style></style></head><body><h1>title</h1><p>text <a href=\"/\">link</a></p></body></html>",
+            "",
+        );
+        let elements = elements(&window);
+        // html, head, style, body, h1, p, a
+        assert_eq!(7, elements.len());
+
+        assert_eq!(Display::Block, style(&elements[0]).display);
+        assert_eq!(Display::None, style(&elements[1]).display);
+        assert_eq!(Sides::all(Length::Px(8.0)), style(&elements[3]).margin);
+
+        let h1 = style(&elements[4]);
+        assert_eq!(Display::Block, h1.display);
+        assert_eq!(32.0, h1.font_size);
+        assert_eq!(700, h1.font_weight);
+        assert_eq!(Length::Px(32.0 * 0.67), h1.margin.top);
+
+        let p = style(&elements[5]);
+        assert_eq!(Length::Px(16.0), p.margin.bottom);
+
+        let a = style(&elements[6]);
+        assert_eq!(Display::Inline, a.display);
+        assert_eq!(Color::rgb(0, 0, 0xee), a.color);
+        assert!(a.text_decoration.underline);
+    }
+
+    #[test]
+    fn test_specificity_and_order() {
+        let window = resolve(
+            "<html><body><p id=\"x\" class=\"c\">a</p><p class=\"c\">b</p></body></html>",
+            "#x { color: red } .c { color: green } p { color: blue } p.c { font-size: 10px } .c { font-size: 20px }",
+        );
+        let elements = elements(&window);
+        let (p1, p2) = (style(&elements[3]), style(&elements[4]));
+        assert_eq!(Color::rgb(255, 0, 0), p1.color);
+        assert_eq!(Color::rgb(0, 128, 0), p2.color);
+        assert_eq!(10.0, p2.font_size);
+    }
+
+    #[test]
+    fn test_important_and_inline() {
+        let window = HtmlParser::new(HtmlTokenizer::new(
+            "<html><body><p id=\"x\">a</p><h1>b</h1></body></html>".to_string(),
+        ))
+        .construct_tree();
+        let document = window.borrow().document();
+        let style_sheet = CssParser::new(CssTokenizer::new(
+            "p { color: red !important; margin-top: 1px } #x { color: blue; margin-top: 2px } h1 { display: inline !important } h1 { display: block }".to_string(),
+        ))
+        .parse_stylesheet();
+        let elements = elements(&window);
+        let inline = CssParser::new(CssTokenizer::new(
+            "margin-top: 3px; color: green".to_string(),
+        ))
+        .parse_declaration_list();
+        StyleResolver::new(
+            &[style_sheet],
+            vec![(elements[3].clone(), inline)],
+            &MediaEnvironment::default(),
+        )
+        .resolve(&document);
+
+        let p = style(&elements[3]);
+        assert_eq!(Color::rgb(255, 0, 0), p.color);
+        assert_eq!(Length::Px(3.0), p.margin.top);
+        assert_eq!(Display::Inline, style(&elements[4]).display);
+    }
+
+    #[test]
+    fn test_inheritance() {
+        let window = resolve(
+            "<html><body><p>a<a href=\"/\">b</a></p></body></html>",
+            "body { color: #123456; font-size: 20px; line-height: 1.5; border: 1px solid; margin: inherit } p { font-size: 2em; border-color: inherit } a { color: inherit; font-weight: bolder }",
+        );
+        let elements = elements(&window);
+        let (body, p, a) = (
+            style(&elements[2]),
+            style(&elements[3]),
+            style(&elements[4]),
+        );
+        assert_eq!(Color::rgb(0x12, 0x34, 0x56), p.color);
+        assert_eq!(Color::rgb(0x12, 0x34, 0x56), body.border_color.top);
+        assert_eq!(Sides::all(Length::Px(0.0)), body.margin);
+        assert_eq!(40.0, p.font_size);
+        assert_eq!(LineHeight::Number(1.5), p.line_height);
+        assert_eq!(0.0, p.border_width.top);
+        assert_eq!(body.border_color, p.border_color);
+        assert_eq!(Color::rgb(0x12, 0x34, 0x56), a.color);
+        assert_eq!(40.0, a.font_size);
+        assert_eq!(700, a.font_weight);
+    }
+
+    #[test]
+    fn test_rem_and_root() {
+        let window = resolve(
+            "<html><body><p>a</p></body></html>",
+            "html { font-size: 10px; display: inline } p { margin-top: 2rem; font-size: 3rem }",
+        );
+        let elements = elements(&window);
+        assert_eq!(Display::Block, style(&elements[0]).display);
+        let p = style(&elements[3]);
+        assert_eq!(Length::Px(20.0), p.margin.top);
+        assert_eq!(30.0, p.font_size);
+    }
+
+    #[test]
+    fn test_layers_and_media() {
+        let window = resolve(
+            "<html><body><p class=\"c\">a</p></body></html>",
+            "@layer base, theme; @layer theme { p { color: red } } @layer base { p.c { color: blue; font-weight: bold !important } } p { font-weight: 100 !important } @media print { p { display: none } } @media screen { p { font-style: italic } }",
+        );
+        let elements = elements(&window);
+        let p = style(&elements[3]);
+        assert_eq!(Color::rgb(255, 0, 0), p.color);
+        assert_eq!(700, p.font_weight);
+        assert_eq!(Display::Block, p.display);
+        assert_eq!(
+            crate::renderer::css::computed_style::FontStyle::Italic,
+            p.font_style
+        );
+    }
+
+    #[test]
+    fn test_custom_properties() {
+        let window = resolve(
+            "<html><body><p>a<a href=\"/\">b</a></p></body></html>",
+            ":root { --main: #00ff00; --gap: 4px; --size: 2em } body { color: var(--main); margin: var(--gap) calc(var(--gap) * 2) } p { --main: blue; color: red; color: var(--missing); font-size: var(--size) } a { color: var(--main, red); --gap: var(--gap) }",
+        );
+        let elements = elements(&window);
+        let (body, p, a) = (
+            style(&elements[2]),
+            style(&elements[3]),
+            style(&elements[4]),
+        );
+        assert_eq!(Color::rgb(0, 255, 0), body.color);
+        assert_eq!(Length::Px(4.0), body.margin.top);
+        assert_eq!(Length::Px(8.0), body.margin.left);
+        // var()の置換に失敗したcolorはunsetとなり親の値を継承する
+        assert_eq!(Color::rgb(0, 255, 0), p.color);
+        assert_eq!(32.0, p.font_size);
+        assert_eq!(Color::rgb(0, 0, 255), a.color);
+        assert!(a.custom_property("--gap").is_none());
+        assert!(a.custom_property("--size").is_some());
+    }
+
+    #[test]
+    fn test_invalid_declaration_is_ignored() {
+        let window = resolve(
+            "<html><body><p>a</p></body></html>",
+            "p { color: red; color: 12px; display: inline; display: bogus }",
+        );
+        let elements = elements(&window);
+        let p = style(&elements[3]);
+        assert_eq!(Color::rgb(255, 0, 0), p.color);
+        assert_eq!(Display::Inline, p.display);
+    }
+
+    #[test]
+    fn test_supports_rule() {
+        let window = resolve(
+            "<html><body><p>a</p></body></html>",
+            "@supports (display: flex) { p { color: red } } @supports (display: bogus) { p { display: none } } @supports not (display: bogus) { p { font-size: 10px } }",
+        );
+        let elements = elements(&window);
+        let p = style(&elements[3]);
+        assert_eq!(Color::rgb(255, 0, 0), p.color);
+        assert_eq!(Display::Block, p.display);
+        assert_eq!(10.0, p.font_size);
+    }
+}
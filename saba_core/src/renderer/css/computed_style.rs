@@ -0,0 +1,2468 @@
+use crate::renderer::css::{
+    cssom::ComponentValue,
+    custom_property::contains_var,
+    gradient::{parse_gradient, parse_position, Gradient},
+    grid::{
+        omitted_grid_line, parse_grid_auto_flow, parse_grid_line, parse_grid_template_areas,
+        parse_track_list, parse_track_sizes, split_slash, GridAutoFlow, GridLine,
+        GridTemplateAreas, TrackListItem, TrackSize,
+    },
+    token::CssTokenKind,
+    value::{
+        non_whitespace, parse_color, parse_keyword, parse_length, parse_number, Color, Length,
+        LengthContext,
+    },
+};
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Display {
+    None,
+    Contents,
+    Block,
+    Inline,
+    InlineBlock,
+    FlowRoot,
+    ListItem,
+    Flex,
+    InlineFlex,
+    Grid,
+    InlineGrid,
+    Table,
+    InlineTable,
+    TableRowGroup,
+    TableHeaderGroup,
+    TableFooterGroup,
+    TableRow,
+    TableCell,
+    TableColumnGroup,
+    TableColumn,
+    TableCaption,
+}
+
+impl Display {
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        let display = match keyword {
+            "none" => Display::None,
+            "contents" => Display::Contents,
+            "block" => Display::Block,
+            "inline" => Display::Inline,
+            "inline-block" => Display::InlineBlock,
+            "flow-root" => Display::FlowRoot,
+            "list-item" => Display::ListItem,
+            "flex" => Display::Flex,
+            "inline-flex" => Display::InlineFlex,
+            "grid" => Display::Grid,
+            "inline-grid" => Display::InlineGrid,
+            "table" => Display::Table,
+            "inline-table" => Display::InlineTable,
+            "table-row-group" => Display::TableRowGroup,
+            "table-header-group" => Display::TableHeaderGroup,
+            "table-footer-group" => Display::TableFooterGroup,
+            "table-row" => Display::TableRow,
+            "table-cell" => Display::TableCell,
+            "table-column-group" => Display::TableColumnGroup,
+            "table-column" => Display::TableColumn,
+            "table-caption" => Display::TableCaption,
+            _ => return None,
+        };
+        Some(display)
+    }
+
+    pub fn is_inline_level(&self) -> bool {
+        matches!(
+            self,
+            Display::Inline
+                | Display::InlineBlock
+                | Display::InlineFlex
+                | Display::InlineGrid
+                | Display::InlineTable
+        )
+    }
+
+    // ルート要素などのブロック化
+    pub fn blockify(self) -> Self {
+        match self {
+            Display::Inline | Display::InlineBlock => Display::Block,
+            Display::InlineFlex => Display::Flex,
+            Display::InlineGrid => Display::Grid,
+            Display::InlineTable => Display::Table,
+            _ => self,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FontStyle {
+    Normal,
+    Italic,
+    Oblique,
+}
+
+impl FontStyle {
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "normal" => Some(FontStyle::Normal),
+            "italic" => Some(FontStyle::Italic),
+            "oblique" => Some(FontStyle::Oblique),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextAlign {
+    Start,
+    End,
+    Left,
+    Right,
+    Center,
+    Justify,
+}
+
+impl TextAlign {
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "start" => Some(TextAlign::Start),
+            "end" => Some(TextAlign::End),
+            "left" => Some(TextAlign::Left),
+            "right" => Some(TextAlign::Right),
+            "center" => Some(TextAlign::Center),
+            "justify" => Some(TextAlign::Justify),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WhiteSpace {
+    Normal,
+    Pre,
+    Nowrap,
+    PreWrap,
+    PreLine,
+    BreakSpaces,
+}
+
+impl WhiteSpace {
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "normal" => Some(WhiteSpace::Normal),
+            "pre" => Some(WhiteSpace::Pre),
+            "nowrap" => Some(WhiteSpace::Nowrap),
+            "pre-wrap" => Some(WhiteSpace::PreWrap),
+            "pre-line" => Some(WhiteSpace::PreLine),
+            "break-spaces" => Some(WhiteSpace::BreakSpaces),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WordBreak {
+    Normal,
+    BreakAll,
+    KeepAll,
+    // 非推奨。normalとoverflow-wrap: anywhereの組み合わせとして扱う
+    BreakWord,
+}
+
+impl WordBreak {
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "normal" => Some(WordBreak::Normal),
+            "break-all" => Some(WordBreak::BreakAll),
+            "keep-all" => Some(WordBreak::KeepAll),
+            "break-word" => Some(WordBreak::BreakWord),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverflowWrap {
+    Normal,
+    Anywhere,
+    BreakWord,
+}
+
+impl OverflowWrap {
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "normal" => Some(OverflowWrap::Normal),
+            "anywhere" => Some(OverflowWrap::Anywhere),
+            "break-word" => Some(OverflowWrap::BreakWord),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum VerticalAlign {
+    Baseline,
+    Sub,
+    Super,
+    TextTop,
+    TextBottom,
+    Middle,
+    Top,
+    Bottom,
+    // 長さは親のベースラインからの持ち上げ量、パーセントはline-heightに対する割合
+    Length(Length),
+}
+
+impl VerticalAlign {
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "baseline" => Some(VerticalAlign::Baseline),
+            "sub" => Some(VerticalAlign::Sub),
+            "super" => Some(VerticalAlign::Super),
+            "text-top" => Some(VerticalAlign::TextTop),
+            "text-bottom" => Some(VerticalAlign::TextBottom),
+            "middle" => Some(VerticalAlign::Middle),
+            "top" => Some(VerticalAlign::Top),
+            "bottom" => Some(VerticalAlign::Bottom),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Visibility {
+    Visible,
+    Hidden,
+    Collapse,
+}
+
+impl Visibility {
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "visible" => Some(Visibility::Visible),
+            "hidden" => Some(Visibility::Hidden),
+            "collapse" => Some(Visibility::Collapse),
+            _ => None,
+        }
+    }
+}
+
+// ポインターのイベントの対象になるかどうか。SVG用の値はautoとして扱う
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PointerEvents {
+    Auto,
+    None,
+}
+
+impl PointerEvents {
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "auto" | "visiblepainted" | "visiblefill" | "visiblestroke" | "visible" | "painted"
+            | "fill" | "stroke" | "all" => Some(PointerEvents::Auto),
+            "none" => Some(PointerEvents::None),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BorderCollapse {
+    Separate,
+    Collapse,
+}
+
+impl BorderCollapse {
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "separate" => Some(BorderCollapse::Separate),
+            "collapse" => Some(BorderCollapse::Collapse),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TableLayout {
+    Auto,
+    Fixed,
+}
+
+impl TableLayout {
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "auto" => Some(TableLayout::Auto),
+            "fixed" => Some(TableLayout::Fixed),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CaptionSide {
+    Top,
+    Bottom,
+}
+
+impl CaptionSide {
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "top" => Some(CaptionSide::Top),
+            "bottom" => Some(CaptionSide::Bottom),
+            _ => None,
+        }
+    }
+}
+
+// 置換要素の内容をボックスに合わせる方法
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ObjectFit {
+    Fill,
+    Contain,
+    Cover,
+    None,
+    ScaleDown,
+}
+
+impl ObjectFit {
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "fill" => Some(ObjectFit::Fill),
+            "contain" => Some(ObjectFit::Contain),
+            "cover" => Some(ObjectFit::Cover),
+            "none" => Some(ObjectFit::None),
+            "scale-down" => Some(ObjectFit::ScaleDown),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Position {
+    Static,
+    Relative,
+    Absolute,
+    Fixed,
+    Sticky,
+}
+
+impl Position {
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "static" => Some(Position::Static),
+            "relative" => Some(Position::Relative),
+            "absolute" => Some(Position::Absolute),
+            "fixed" => Some(Position::Fixed),
+            "sticky" => Some(Position::Sticky),
+            _ => None,
+        }
+    }
+
+    // 通常フローから取り除かれる配置方法
+    pub fn is_absolute(&self) -> bool {
+        matches!(self, Position::Absolute | Position::Fixed)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Float {
+    None,
+    Left,
+    Right,
+}
+
+impl Float {
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "none" => Some(Float::None),
+            "left" => Some(Float::Left),
+            "right" => Some(Float::Right),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Overflow {
+    Visible,
+    Hidden,
+    Clip,
+    Scroll,
+    Auto,
+}
+
+impl Overflow {
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "visible" => Some(Overflow::Visible),
+            "hidden" => Some(Overflow::Hidden),
+            "clip" => Some(Overflow::Clip),
+            "scroll" => Some(Overflow::Scroll),
+            "auto" => Some(Overflow::Auto),
+            _ => None,
+        }
+    }
+
+    // スクロールコンテナを作る値
+    pub fn is_scrollable(&self) -> bool {
+        matches!(self, Overflow::Hidden | Overflow::Scroll | Overflow::Auto)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Clear {
+    None,
+    Left,
+    Right,
+    Both,
+}
+
+impl Clear {
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "none" => Some(Clear::None),
+            "left" => Some(Clear::Left),
+            "right" => Some(Clear::Right),
+            "both" => Some(Clear::Both),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BoxSizing {
+    ContentBox,
+    BorderBox,
+}
+
+impl BoxSizing {
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "content-box" => Some(BoxSizing::ContentBox),
+            "border-box" => Some(BoxSizing::BorderBox),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BorderStyle {
+    None,
+    Hidden,
+    Solid,
+    Dashed,
+    Dotted,
+    Double,
+    Groove,
+    Ridge,
+    Inset,
+    Outset,
+}
+
+impl BorderStyle {
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        let style = match keyword {
+            "none" => BorderStyle::None,
+            "hidden" => BorderStyle::Hidden,
+            "solid" => BorderStyle::Solid,
+            "dashed" => BorderStyle::Dashed,
+            "dotted" => BorderStyle::Dotted,
+            "double" => BorderStyle::Double,
+            "groove" => BorderStyle::Groove,
+            "ridge" => BorderStyle::Ridge,
+            "inset" => BorderStyle::Inset,
+            "outset" => BorderStyle::Outset,
+            _ => return None,
+        };
+        Some(style)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ListStyleType {
+    None,
+    Disc,
+    Circle,
+    Square,
+    Decimal,
+    LowerAlpha,
+    UpperAlpha,
+    LowerRoman,
+    UpperRoman,
+}
+
+impl ListStyleType {
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        let list_style_type = match keyword {
+            "none" => ListStyleType::None,
+            "disc" => ListStyleType::Disc,
+            "circle" => ListStyleType::Circle,
+            "square" => ListStyleType::Square,
+            "decimal" => ListStyleType::Decimal,
+            "lower-alpha" | "lower-latin" => ListStyleType::LowerAlpha,
+            "upper-alpha" | "upper-latin" => ListStyleType::UpperAlpha,
+            "lower-roman" => ListStyleType::LowerRoman,
+            "upper-roman" => ListStyleType::UpperRoman,
+            _ => return None,
+        };
+        Some(list_style_type)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlexDirection {
+    Row,
+    RowReverse,
+    Column,
+    ColumnReverse,
+}
+
+impl FlexDirection {
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "row" => Some(FlexDirection::Row),
+            "row-reverse" => Some(FlexDirection::RowReverse),
+            "column" => Some(FlexDirection::Column),
+            "column-reverse" => Some(FlexDirection::ColumnReverse),
+            _ => None,
+        }
+    }
+
+    pub fn is_column(&self) -> bool {
+        matches!(self, FlexDirection::Column | FlexDirection::ColumnReverse)
+    }
+
+    pub fn is_reverse(&self) -> bool {
+        matches!(
+            self,
+            FlexDirection::RowReverse | FlexDirection::ColumnReverse
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlexWrap {
+    Nowrap,
+    Wrap,
+    WrapReverse,
+}
+
+impl FlexWrap {
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "nowrap" => Some(FlexWrap::Nowrap),
+            "wrap" => Some(FlexWrap::Wrap),
+            "wrap-reverse" => Some(FlexWrap::WrapReverse),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum FlexBasis {
+    // 主軸方向のwidthまたはheightを使う
+    Auto,
+    Content,
+    Length(Length),
+}
+
+// justify-contentとalign-contentの値
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ContentAlign {
+    Normal,
+    Start,
+    End,
+    FlexStart,
+    FlexEnd,
+    Center,
+    SpaceBetween,
+    SpaceAround,
+    SpaceEvenly,
+    Stretch,
+}
+
+impl ContentAlign {
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        let align = match keyword {
+            "normal" => ContentAlign::Normal,
+            "start" | "left" => ContentAlign::Start,
+            "end" | "right" => ContentAlign::End,
+            "flex-start" => ContentAlign::FlexStart,
+            "flex-end" => ContentAlign::FlexEnd,
+            "center" => ContentAlign::Center,
+            "space-between" => ContentAlign::SpaceBetween,
+            "space-around" => ContentAlign::SpaceAround,
+            "space-evenly" => ContentAlign::SpaceEvenly,
+            "stretch" => ContentAlign::Stretch,
+            _ => return None,
+        };
+        Some(align)
+    }
+}
+
+// align-itemsとalign-selfの値。autoはalign-selfでのみ使える
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ItemAlign {
+    Auto,
+    Normal,
+    Stretch,
+    Start,
+    End,
+    FlexStart,
+    FlexEnd,
+    SelfStart,
+    SelfEnd,
+    Center,
+    Baseline,
+}
+
+impl ItemAlign {
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        let align = match keyword {
+            "auto" => ItemAlign::Auto,
+            "normal" => ItemAlign::Normal,
+            "stretch" => ItemAlign::Stretch,
+            "start" | "left" => ItemAlign::Start,
+            "end" | "right" => ItemAlign::End,
+            "flex-start" => ItemAlign::FlexStart,
+            "flex-end" => ItemAlign::FlexEnd,
+            "self-start" => ItemAlign::SelfStart,
+            "self-end" => ItemAlign::SelfEnd,
+            "center" => ItemAlign::Center,
+            "baseline" => ItemAlign::Baseline,
+            _ => return None,
+        };
+        Some(align)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineHeight {
+    Normal,
+    // 単位のない数値は子孫要素でもフォントサイズとの比率として継承される
+    Number(f64),
+    Px(f64),
+}
+
+impl LineHeight {
+    pub fn resolve(&self, font_size: f64) -> f64 {
+        match self {
+            LineHeight::Normal => font_size * 1.2,
+            LineHeight::Number(n) => font_size * n,
+            LineHeight::Px(px) => *px,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TextDecoration {
+    pub underline: bool,
+    pub overline: bool,
+    pub line_through: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sides<T> {
+    pub top: T,
+    pub right: T,
+    pub bottom: T,
+    pub left: T,
+}
+
+impl<T: Clone> Sides<T> {
+    pub fn all(value: T) -> Self {
+        Self {
+            top: value.clone(),
+            right: value.clone(),
+            bottom: value.clone(),
+            left: value,
+        }
+    }
+}
+
+impl Default for Sides<f64> {
+    fn default() -> Self {
+        Sides::all(0.0)
+    }
+}
+
+impl Sides<f64> {
+    pub fn horizontal(&self) -> f64 {
+        self.left + self.right
+    }
+
+    pub fn vertical(&self) -> f64 {
+        self.top + self.bottom
+    }
+}
+
+// 角ごとの値
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Corners<T> {
+    pub top_left: T,
+    pub top_right: T,
+    pub bottom_right: T,
+    pub bottom_left: T,
+}
+
+impl<T: Clone> Corners<T> {
+    pub fn all(value: T) -> Self {
+        Self {
+            top_left: value.clone(),
+            top_right: value.clone(),
+            bottom_right: value.clone(),
+            bottom_left: value,
+        }
+    }
+}
+
+const SIDES: [&str; 4] = ["top", "right", "bottom", "left"];
+
+const CORNERS: [&str; 4] = ["top-left", "top-right", "bottom-right", "bottom-left"];
+
+fn side_mut<'a, T>(sides: &'a mut Sides<T>, side: &str) -> Option<&'a mut T> {
+    match side {
+        "top" => Some(&mut sides.top),
+        "right" => Some(&mut sides.right),
+        "bottom" => Some(&mut sides.bottom),
+        "left" => Some(&mut sides.left),
+        _ => None,
+    }
+}
+
+fn side_of<T: Clone>(sides: &Sides<T>, side: &str) -> Option<T> {
+    match side {
+        "top" => Some(sides.top.clone()),
+        "right" => Some(sides.right.clone()),
+        "bottom" => Some(sides.bottom.clone()),
+        "left" => Some(sides.left.clone()),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComputedStyle {
+    pub display: Display,
+    pub color: Color,
+    pub background_color: Color,
+    pub background_image: Option<Gradient>,
+    pub font_size: f64,
+    pub font_weight: u16,
+    pub font_style: FontStyle,
+    pub font_family: Vec<String>,
+    pub line_height: LineHeight,
+    pub text_align: TextAlign,
+    pub text_decoration: TextDecoration,
+    pub white_space: WhiteSpace,
+    pub word_break: WordBreak,
+    pub overflow_wrap: OverflowWrap,
+    pub vertical_align: VerticalAlign,
+    pub visibility: Visibility,
+    pub pointer_events: PointerEvents,
+    pub list_style_type: ListStyleType,
+    pub margin: Sides<Length>,
+    pub padding: Sides<Length>,
+    pub border_width: Sides<f64>,
+    pub border_style: Sides<BorderStyle>,
+    pub border_color: Sides<Color>,
+    // 角ごとの横と縦の半径
+    pub border_radius: Corners<(Length, Length)>,
+    pub width: Length,
+    pub height: Length,
+    pub min_width: Length,
+    pub min_height: Length,
+    // noneの場合はNone
+    pub max_width: Option<Length>,
+    pub max_height: Option<Length>,
+    pub box_sizing: BoxSizing,
+    pub flex_direction: FlexDirection,
+    pub flex_wrap: FlexWrap,
+    pub flex_grow: f64,
+    pub flex_shrink: f64,
+    pub flex_basis: FlexBasis,
+    pub justify_content: ContentAlign,
+    pub align_content: ContentAlign,
+    pub align_items: ItemAlign,
+    pub align_self: ItemAlign,
+    // normalは0として扱う
+    pub row_gap: Length,
+    pub column_gap: Length,
+    pub order: i32,
+    pub justify_items: ItemAlign,
+    pub justify_self: ItemAlign,
+    pub grid_template_rows: Vec<TrackListItem>,
+    pub grid_template_columns: Vec<TrackListItem>,
+    pub grid_template_areas: GridTemplateAreas,
+    pub grid_auto_rows: Vec<TrackSize>,
+    pub grid_auto_columns: Vec<TrackSize>,
+    pub grid_auto_flow: GridAutoFlow,
+    pub grid_row_start: GridLine,
+    pub grid_row_end: GridLine,
+    pub grid_column_start: GridLine,
+    pub grid_column_end: GridLine,
+    pub border_collapse: BorderCollapse,
+    // 水平方向と垂直方向のセルの間隔
+    pub border_spacing: (f64, f64),
+    pub table_layout: TableLayout,
+    pub caption_side: CaptionSide,
+    pub object_fit: ObjectFit,
+    // 置換要素の内容を置く位置の横と縦
+    pub object_position: (Length, Length),
+    pub position: Position,
+    // top, right, bottom, left
+    pub inset: Sides<Length>,
+    // autoの場合はNone
+    pub z_index: Option<i32>,
+    pub float: Float,
+    pub clear: Clear,
+    pub overflow_x: Overflow,
+    pub overflow_y: Overflow,
+    // 0.0から1.0の範囲
+    pub opacity: f64,
+    // var()を置換済みのカスタムプロパティ
+    pub custom_properties: BTreeMap<String, Vec<ComponentValue>>,
+}
+
+impl Default for ComputedStyle {
+    // 各プロパティの初期値
+    fn default() -> Self {
+        Self {
+            display: Display::Inline,
+            color: Color::BLACK,
+            background_color: Color::TRANSPARENT,
+            background_image: None,
+            font_size: 16.0,
+            font_weight: 400,
+            font_style: FontStyle::Normal,
+            font_family: vec!["serif".to_string()],
+            line_height: LineHeight::Normal,
+            text_align: TextAlign::Start,
+            text_decoration: TextDecoration::default(),
+            white_space: WhiteSpace::Normal,
+            word_break: WordBreak::Normal,
+            overflow_wrap: OverflowWrap::Normal,
+            vertical_align: VerticalAlign::Baseline,
+            visibility: Visibility::Visible,
+            pointer_events: PointerEvents::Auto,
+            list_style_type: ListStyleType::Disc,
+            margin: Sides::all(Length::Px(0.0)),
+            padding: Sides::all(Length::Px(0.0)),
+            border_width: Sides::all(3.0),
+            border_style: Sides::all(BorderStyle::None),
+            border_color: Sides::all(Color::BLACK),
+            border_radius: Corners::all((Length::Px(0.0), Length::Px(0.0))),
+            width: Length::Auto,
+            height: Length::Auto,
+            min_width: Length::Auto,
+            min_height: Length::Auto,
+            max_width: None,
+            max_height: None,
+            box_sizing: BoxSizing::ContentBox,
+            flex_direction: FlexDirection::Row,
+            flex_wrap: FlexWrap::Nowrap,
+            flex_grow: 0.0,
+            flex_shrink: 1.0,
+            flex_basis: FlexBasis::Auto,
+            justify_content: ContentAlign::Normal,
+            align_content: ContentAlign::Normal,
+            align_items: ItemAlign::Normal,
+            align_self: ItemAlign::Auto,
+            row_gap: Length::Px(0.0),
+            column_gap: Length::Px(0.0),
+            order: 0,
+            justify_items: ItemAlign::Normal,
+            justify_self: ItemAlign::Auto,
+            grid_template_rows: Vec::new(),
+            grid_template_columns: Vec::new(),
+            grid_template_areas: GridTemplateAreas::default(),
+            grid_auto_rows: vec![TrackSize::auto()],
+            grid_auto_columns: vec![TrackSize::auto()],
+            grid_auto_flow: GridAutoFlow::default(),
+            grid_row_start: GridLine::Auto,
+            grid_row_end: GridLine::Auto,
+            grid_column_start: GridLine::Auto,
+            grid_column_end: GridLine::Auto,
+            border_collapse: BorderCollapse::Separate,
+            border_spacing: (0.0, 0.0),
+            table_layout: TableLayout::Auto,
+            caption_side: CaptionSide::Top,
+            object_fit: ObjectFit::Fill,
+            object_position: (Length::Percent(50.0), Length::Percent(50.0)),
+            position: Position::Static,
+            inset: Sides::all(Length::Auto),
+            z_index: None,
+            float: Float::None,
+            clear: Clear::None,
+            overflow_x: Overflow::Visible,
+            overflow_y: Overflow::Visible,
+            opacity: 1.0,
+            custom_properties: BTreeMap::new(),
+        }
+    }
+}
+
+pub fn is_inherited_property(name: &str) -> bool {
+    matches!(
+        name,
+        "color"
+            | "font-size"
+            | "font-weight"
+            | "font-style"
+            | "font-family"
+            | "line-height"
+            | "text-align"
+            | "white-space"
+            | "word-break"
+            | "overflow-wrap"
+            | "visibility"
+            | "pointer-events"
+            | "list-style-type"
+            | "border-collapse"
+            | "border-spacing"
+            | "caption-side"
+    )
+}
+
+const PROPERTIES: [&str; 70] = [
+    "display",
+    "color",
+    "background-color",
+    "font-size",
+    "font-weight",
+    "font-style",
+    "font-family",
+    "line-height",
+    "text-align",
+    "text-decoration-line",
+    "white-space",
+    "word-break",
+    "overflow-wrap",
+    "vertical-align",
+    "visibility",
+    "pointer-events",
+    "list-style-type",
+    "box-sizing",
+    "width",
+    "height",
+    "min-width",
+    "min-height",
+    "max-width",
+    "max-height",
+    "flex-direction",
+    "flex-wrap",
+    "flex-grow",
+    "flex-shrink",
+    "flex-basis",
+    "justify-content",
+    "align-content",
+    "align-items",
+    "align-self",
+    "row-gap",
+    "column-gap",
+    "order",
+    "justify-items",
+    "justify-self",
+    "grid-template-rows",
+    "grid-template-columns",
+    "grid-template-areas",
+    "grid-auto-rows",
+    "grid-auto-columns",
+    "grid-auto-flow",
+    "grid-row-start",
+    "grid-row-end",
+    "grid-column-start",
+    "grid-column-end",
+    "border-collapse",
+    "border-spacing",
+    "table-layout",
+    "caption-side",
+    "object-fit",
+    "object-position",
+    "position",
+    "top",
+    "right",
+    "bottom",
+    "left",
+    "z-index",
+    "float",
+    "clear",
+    "overflow-x",
+    "overflow-y",
+    "opacity",
+    "background-image",
+    "border-top-left-radius",
+    "border-top-right-radius",
+    "border-bottom-right-radius",
+    "border-bottom-left-radius",
+];
+
+fn is_known_longhand(name: &str) -> bool {
+    if PROPERTIES.contains(&name) {
+        return true;
+    }
+    matches!(
+        split_side_property(name),
+        Some(("margin", _, ""))
+            | Some(("padding", _, ""))
+            | Some(("border", _, "width" | "style" | "color"))
+    )
+}
+
+// 宣言を解釈できるかどうか。var()を含む値は計算値の時点まで検証できないので有効とみなす
+pub fn is_supported_declaration(name: &str, values: &[ComponentValue]) -> bool {
+    if name.starts_with("--") {
+        return true;
+    }
+    if contains_var(values) {
+        return is_known_longhand(name) || longhand_names(name).is_some();
+    }
+    let longhands = expand_shorthand(name, values);
+    if longhands.is_empty() || !longhands.iter().all(|(n, _)| is_known_longhand(n)) {
+        return false;
+    }
+
+    let parent = ComputedStyle::default();
+    longhands.iter().all(|(name, values)| {
+        ComputedStyle::default().apply_property(name, values, &parent, &LengthContext::default())
+    })
+}
+
+fn is_css_wide_keyword(keyword: &str) -> bool {
+    matches!(keyword, "inherit" | "initial" | "unset" | "revert")
+}
+
+fn single_value(values: &[ComponentValue]) -> Option<&ComponentValue> {
+    let values = non_whitespace(values);
+    if values.len() != 1 {
+        return None;
+    }
+    Some(values[0])
+}
+
+fn ident(value: &str) -> ComponentValue {
+    ComponentValue::Token(CssTokenKind::Ident(value.to_string()))
+}
+
+fn border_width_value(value: &ComponentValue, context: &LengthContext) -> Option<f64> {
+    if let Some(keyword) = value.ident() {
+        return match keyword.to_ascii_lowercase().as_str() {
+            "thin" => Some(1.0),
+            "medium" => Some(3.0),
+            "thick" => Some(5.0),
+            _ => None,
+        };
+    }
+    match parse_length(value, context)? {
+        Length::Px(px) if px >= 0.0 => Some(px),
+        _ => None,
+    }
+}
+
+// 1つなら両方向、2つなら水平方向と垂直方向の間隔
+fn border_spacing_value(values: &[ComponentValue], context: &LengthContext) -> Option<(f64, f64)> {
+    let lengths = non_whitespace(values)
+        .into_iter()
+        .map(|value| match parse_length(value, context)? {
+            Length::Px(px) if px >= 0.0 => Some(px),
+            _ => None,
+        })
+        .collect::<Option<Vec<f64>>>()?;
+    match lengths[..] {
+        [spacing] => Some((spacing, spacing)),
+        [horizontal, vertical] => Some((horizontal, vertical)),
+        _ => None,
+    }
+}
+
+fn is_border_style(value: &ComponentValue) -> bool {
+    value
+        .ident()
+        .is_some_and(|k| BorderStyle::from_keyword(&k.to_ascii_lowercase()).is_some())
+}
+
+// 上右下左の順に1から4個の値を割り当てる
+fn expand_sides(
+    values: &[ComponentValue],
+    name: &dyn Fn(&str) -> String,
+) -> Vec<(String, Vec<ComponentValue>)> {
+    let items = non_whitespace(values);
+    let indices: [usize; 4] = match items.len() {
+        1 => [0, 0, 0, 0],
+        2 => [0, 1, 0, 1],
+        3 => [0, 1, 2, 1],
+        4 => [0, 1, 2, 3],
+        _ => return Vec::new(),
+    };
+    SIDES
+        .iter()
+        .zip(indices)
+        .map(|(side, i)| (name(side), vec![items[i].clone()]))
+        .collect()
+}
+
+// border-radius: <length-percentage>{1,4} [/ <length-percentage>{1,4}]?
+fn expand_border_radius(values: &[ComponentValue]) -> Vec<(String, Vec<ComponentValue>)> {
+    let parts = split_slash(values);
+    if parts.len() > 2 {
+        return Vec::new();
+    }
+    let radii: Vec<Vec<(String, Vec<ComponentValue>)>> = parts
+        .iter()
+        .map(|part| expand_sides(part, &|side| side.to_string()))
+        .collect();
+    if radii.iter().any(|r| r.is_empty()) {
+        return Vec::new();
+    }
+    // 上、右、下、左の順の値を左上、右上、右下、左下の角に割り当てる
+    let vertical = radii.last().unwrap_or(&radii[0]);
+    CORNERS
+        .iter()
+        .enumerate()
+        .map(|(i, corner)| {
+            let mut value = radii[0][i].1.clone();
+            if parts.len() == 2 {
+                value.push(ComponentValue::Token(CssTokenKind::Whitespace));
+                value.extend(vertical[i].1.clone());
+            }
+            (format!("border-{}-radius", corner), value)
+        })
+        .collect()
+}
+
+fn expand_border(values: &[ComponentValue], sides: &[&str]) -> Vec<(String, Vec<ComponentValue>)> {
+    let mut width = None;
+    let mut style = None;
+    let mut color = None;
+    for item in non_whitespace(values) {
+        if style.is_none() && is_border_style(item) {
+            style = Some(item.clone());
+        } else if color.is_none() && parse_color(item, Color::BLACK).is_some() {
+            color = Some(item.clone());
+        } else if width.is_none() && border_width_value(item, &LengthContext::default()).is_some() {
+            width = Some(item.clone());
+        } else {
+            return Vec::new();
+        }
+    }
+
+    let width = width.unwrap_or_else(|| ident("medium"));
+    let style = style.unwrap_or_else(|| ident("none"));
+    let color = color.unwrap_or_else(|| ident("currentcolor"));
+    let mut longhands = Vec::new();
+    for side in sides {
+        longhands.push((format_side("border", side, "width"), vec![width.clone()]));
+        longhands.push((format_side("border", side, "style"), vec![style.clone()]));
+        longhands.push((format_side("border", side, "color"), vec![color.clone()]));
+    }
+    longhands
+}
+
+fn format_side(prefix: &str, side: &str, suffix: &str) -> String {
+    let mut name = String::from(prefix);
+    name.push('-');
+    name.push_str(side);
+    if !suffix.is_empty() {
+        name.push('-');
+        name.push_str(suffix);
+    }
+    name
+}
+
+fn expand_font(values: &[ComponentValue]) -> Vec<(String, Vec<ComponentValue>)> {
+    let mut style = ident("normal");
+    let mut weight = ident("normal");
+    let mut i = 0;
+
+    // font-sizeより前にはfont-style、font-variant、font-weightが任意の順で来る
+    let size = loop {
+        let value = match values.get(i) {
+            Some(value) => value,
+            None => return Vec::new(),
+        };
+        i += 1;
+        if value.is_whitespace() {
+            continue;
+        }
+        match value.ident().map(|k| k.to_ascii_lowercase()).as_deref() {
+            Some("normal") | Some("small-caps") => continue,
+            Some("italic") | Some("oblique") => style = value.clone(),
+            Some("bold") | Some("bolder") | Some("lighter") => weight = value.clone(),
+            _ => {
+                if parse_number(value).is_some_and(|n| n != 0.0) {
+                    weight = value.clone();
+                } else {
+                    break value.clone();
+                }
+            }
+        }
+    };
+
+    let mut rest: Vec<ComponentValue> = values[i..].to_vec();
+    let mut line_height = ident("normal");
+    let first = rest.iter().position(|v| !v.is_whitespace());
+    if let Some(pos) = first {
+        if rest[pos].is_token(&CssTokenKind::Delim('/')) {
+            let next = match rest[pos + 1..].iter().position(|v| !v.is_whitespace()) {
+                Some(next) => pos + 1 + next,
+                None => return Vec::new(),
+            };
+            line_height = rest[next].clone();
+            rest = rest[next + 1..].to_vec();
+        }
+    }
+    if non_whitespace(&rest).is_empty() {
+        return Vec::new();
+    }
+
+    vec![
+        ("font-style".to_string(), vec![style]),
+        ("font-weight".to_string(), vec![weight]),
+        ("font-size".to_string(), vec![size]),
+        ("line-height".to_string(), vec![line_height]),
+        ("font-family".to_string(), rest),
+    ]
+}
+
+// 単位のない0は伸縮係数の後ではflex-basisになる
+fn is_flex_basis(value: &ComponentValue) -> bool {
+    if let Some(n) = parse_number(value) {
+        return n == 0.0;
+    }
+    matches!(
+        value.ident().map(|k| k.to_ascii_lowercase()).as_deref(),
+        Some("auto" | "content")
+    ) || parse_length(value, &LengthContext::default()).is_some()
+}
+
+fn number(value: f64) -> ComponentValue {
+    ComponentValue::Token(CssTokenKind::Number {
+        value,
+        is_integer: false,
+    })
+}
+
+// flex: none | [<flex-grow> <flex-shrink>? || <flex-basis>]
+fn expand_flex(values: &[ComponentValue]) -> Vec<(String, Vec<ComponentValue>)> {
+    let (grow, shrink, basis) = match parse_keyword(values).as_deref() {
+        Some("none") => (number(0.0), number(0.0), ident("auto")),
+        Some("auto") => (number(1.0), number(1.0), ident("auto")),
+        _ => {
+            let items = non_whitespace(values);
+            let mut factors = Vec::new();
+            let mut basis = None;
+            let mut i = 0;
+            while i < items.len() {
+                if factors.is_empty() && parse_number(items[i]).is_some() {
+                    factors.push(items[i].clone());
+                    if let Some(shrink) = items.get(i + 1).filter(|v| parse_number(v).is_some()) {
+                        factors.push((*shrink).clone());
+                        i += 1;
+                    }
+                } else if basis.is_none() && is_flex_basis(items[i]) {
+                    basis = Some(items[i].clone());
+                } else {
+                    return Vec::new();
+                }
+                i += 1;
+            }
+            if factors.is_empty() && basis.is_none() {
+                return Vec::new();
+            }
+            // flex-basisを省略すると0%になる
+            let basis = basis.unwrap_or(ComponentValue::Token(CssTokenKind::Percentage(0.0)));
+            let grow = factors.first().cloned().unwrap_or_else(|| number(1.0));
+            let shrink = factors.get(1).cloned().unwrap_or_else(|| number(1.0));
+            (grow, shrink, basis)
+        }
+    };
+    vec![
+        ("flex-grow".to_string(), vec![grow]),
+        ("flex-shrink".to_string(), vec![shrink]),
+        ("flex-basis".to_string(), vec![basis]),
+    ]
+}
+
+fn expand_flex_flow(values: &[ComponentValue]) -> Vec<(String, Vec<ComponentValue>)> {
+    let mut direction = None;
+    let mut wrap = None;
+    for item in non_whitespace(values) {
+        let keyword = item
+            .ident()
+            .map(|k| k.to_ascii_lowercase())
+            .unwrap_or_default();
+        if direction.is_none() && FlexDirection::from_keyword(&keyword).is_some() {
+            direction = Some(item.clone());
+        } else if wrap.is_none() && FlexWrap::from_keyword(&keyword).is_some() {
+            wrap = Some(item.clone());
+        } else {
+            return Vec::new();
+        }
+    }
+    vec![
+        (
+            "flex-direction".to_string(),
+            vec![direction.unwrap_or_else(|| ident("row"))],
+        ),
+        (
+            "flex-wrap".to_string(),
+            vec![wrap.unwrap_or_else(|| ident("nowrap"))],
+        ),
+    ]
+}
+
+// grid-row: <start> [/ <end>]?
+fn expand_grid_lines(
+    values: &[ComponentValue],
+    prefix: &str,
+) -> Vec<(String, Vec<ComponentValue>)> {
+    let parts = split_slash(values);
+    let end = match parts.as_slice() {
+        [start] => omitted_grid_line(start),
+        [_, end] => end.clone(),
+        _ => return Vec::new(),
+    };
+    vec![
+        (prefix.to_string() + "-start", parts[0].clone()),
+        (prefix.to_string() + "-end", end),
+    ]
+}
+
+// grid-area: <row-start> [/ <column-start> [/ <row-end> [/ <column-end>]?]?]?
+fn expand_grid_area(values: &[ComponentValue]) -> Vec<(String, Vec<ComponentValue>)> {
+    let mut parts = split_slash(values);
+    if parts.len() > 4 {
+        return Vec::new();
+    }
+    // 省略された値は対応する始まりの線から補う
+    for i in parts.len()..4 {
+        let line = omitted_grid_line(&parts[if i == 1 { 0 } else { i - 2 }]);
+        parts.push(line);
+    }
+    [
+        "grid-row-start",
+        "grid-column-start",
+        "grid-row-end",
+        "grid-column-end",
+    ]
+    .iter()
+    .zip(parts)
+    .map(|(name, values)| (name.to_string(), values))
+    .collect()
+}
+
+fn longhand_names(name: &str) -> Option<Vec<String>> {
+    let border_parts = ["width", "style", "color"];
+    let names = match name {
+        "margin" | "padding" => SIDES.iter().map(|s| format_side(name, s, "")).collect(),
+        "border-width" | "border-style" | "border-color" => SIDES
+            .iter()
+            .map(|s| format_side("border", s, &name["border-".len()..]))
+            .collect(),
+        "border" => SIDES
+            .iter()
+            .flat_map(|s| border_parts.map(|p| format_side("border", s, p)))
+            .collect(),
+        "border-top" | "border-right" | "border-bottom" | "border-left" => border_parts
+            .iter()
+            .map(|p| format_side("border", &name["border-".len()..], p))
+            .collect(),
+        "font" => [
+            "font-style",
+            "font-weight",
+            "font-size",
+            "line-height",
+            "font-family",
+        ]
+        .iter()
+        .map(|n| n.to_string())
+        .collect(),
+        "inset" => SIDES.iter().map(|s| s.to_string()).collect(),
+        "overflow" => vec!["overflow-x".to_string(), "overflow-y".to_string()],
+        "background" => vec![
+            "background-color".to_string(),
+            "background-image".to_string(),
+        ],
+        "border-radius" => CORNERS
+            .iter()
+            .map(|c| format!("border-{}-radius", c))
+            .collect(),
+        "list-style" => vec!["list-style-type".to_string()],
+        "text-decoration" => vec!["text-decoration-line".to_string()],
+        // overflow-wrapの古い別名
+        "word-wrap" => vec!["overflow-wrap".to_string()],
+        "flex" => vec![
+            "flex-grow".to_string(),
+            "flex-shrink".to_string(),
+            "flex-basis".to_string(),
+        ],
+        "flex-flow" => vec!["flex-direction".to_string(), "flex-wrap".to_string()],
+        "gap" | "grid-gap" => vec!["row-gap".to_string(), "column-gap".to_string()],
+        // gapの古い別名
+        "grid-row-gap" => vec!["row-gap".to_string()],
+        "grid-column-gap" => vec!["column-gap".to_string()],
+        "grid-row" => vec!["grid-row-start".to_string(), "grid-row-end".to_string()],
+        "grid-column" => vec![
+            "grid-column-start".to_string(),
+            "grid-column-end".to_string(),
+        ],
+        "grid-area" => [
+            "grid-row-start",
+            "grid-column-start",
+            "grid-row-end",
+            "grid-column-end",
+        ]
+        .iter()
+        .map(|n| n.to_string())
+        .collect(),
+        _ => return None,
+    };
+    Some(names)
+}
+
+// 一括指定プロパティを個別のプロパティに展開する
+pub fn expand_shorthand(
+    name: &str,
+    values: &[ComponentValue],
+) -> Vec<(String, Vec<ComponentValue>)> {
+    let names = match longhand_names(name) {
+        Some(names) => names,
+        None => return vec![(name.to_string(), values.to_vec())],
+    };
+
+    // inheritなどはすべての個別プロパティに適用する
+    if let Some(keyword) = parse_keyword(values) {
+        if is_css_wide_keyword(&keyword) {
+            return names.into_iter().map(|n| (n, values.to_vec())).collect();
+        }
+    }
+
+    match name {
+        "margin" | "padding" => expand_sides(values, &|side| format_side(name, side, "")),
+        "border-width" => expand_sides(values, &|side| format_side("border", side, "width")),
+        "border-style" => expand_sides(values, &|side| format_side("border", side, "style")),
+        "border-color" => expand_sides(values, &|side| format_side("border", side, "color")),
+        "border" => expand_border(values, &SIDES),
+        "border-top" => expand_border(values, &["top"]),
+        "border-right" => expand_border(values, &["right"]),
+        "border-bottom" => expand_border(values, &["bottom"]),
+        "border-left" => expand_border(values, &["left"]),
+        "font" => expand_font(values),
+        "inset" => expand_sides(values, &|side| side.to_string()),
+        "background" => {
+            let color = non_whitespace(values)
+                .into_iter()
+                .find(|v| parse_color(v, Color::BLACK).is_some())
+                .cloned()
+                .unwrap_or_else(|| ident("transparent"));
+            let image = non_whitespace(values)
+                .into_iter()
+                .find(|v| parse_gradient(v, &LengthContext::default(), Color::BLACK).is_some())
+                .cloned()
+                .unwrap_or_else(|| ident("none"));
+            vec![
+                ("background-color".to_string(), vec![color]),
+                ("background-image".to_string(), vec![image]),
+            ]
+        }
+        "border-radius" => expand_border_radius(values),
+        "list-style" => {
+            let list_style_type = non_whitespace(values).into_iter().find(|v| {
+                v.ident()
+                    .is_some_and(|k| ListStyleType::from_keyword(&k.to_ascii_lowercase()).is_some())
+            });
+            match list_style_type {
+                Some(v) => vec![("list-style-type".to_string(), vec![v.clone()])],
+                None => Vec::new(),
+            }
+        }
+        "text-decoration" => {
+            let lines: Vec<ComponentValue> = non_whitespace(values)
+                .into_iter()
+                .filter(|v| {
+                    matches!(
+                        v.ident().map(|k| k.to_ascii_lowercase()).as_deref(),
+                        Some("none" | "underline" | "overline" | "line-through")
+                    )
+                })
+                .cloned()
+                .collect();
+            if lines.is_empty() {
+                return Vec::new();
+            }
+            vec![("text-decoration-line".to_string(), lines)]
+        }
+        "word-wrap" => vec![("overflow-wrap".to_string(), values.to_vec())],
+        "flex" => expand_flex(values),
+        "flex-flow" => expand_flex_flow(values),
+        "gap" | "grid-gap" => {
+            let items = non_whitespace(values);
+            let (row, column) = match items.len() {
+                1 => (items[0], items[0]),
+                2 => (items[0], items[1]),
+                _ => return Vec::new(),
+            };
+            vec![
+                ("row-gap".to_string(), vec![row.clone()]),
+                ("column-gap".to_string(), vec![column.clone()]),
+            ]
+        }
+        "overflow" => {
+            let items = non_whitespace(values);
+            let (x, y) = match items.len() {
+                1 => (items[0], items[0]),
+                2 => (items[0], items[1]),
+                _ => return Vec::new(),
+            };
+            vec![
+                ("overflow-x".to_string(), vec![x.clone()]),
+                ("overflow-y".to_string(), vec![y.clone()]),
+            ]
+        }
+        "grid-row-gap" => vec![("row-gap".to_string(), values.to_vec())],
+        "grid-column-gap" => vec![("column-gap".to_string(), values.to_vec())],
+        "grid-row" => expand_grid_lines(values, "grid-row"),
+        "grid-column" => expand_grid_lines(values, "grid-column"),
+        "grid-area" => expand_grid_area(values),
+        _ => Vec::new(),
+    }
+}
+
+fn font_size_keyword(keyword: &str, parent_font_size: f64) -> Option<f64> {
+    let size = match keyword {
+        "xx-small" => 16.0 * 3.0 / 5.0,
+        "x-small" => 16.0 * 3.0 / 4.0,
+        "small" => 16.0 * 8.0 / 9.0,
+        "medium" => 16.0,
+        "large" => 16.0 * 6.0 / 5.0,
+        "x-large" => 16.0 * 3.0 / 2.0,
+        "xx-large" => 16.0 * 2.0,
+        "xxx-large" => 16.0 * 3.0,
+        "larger" => parent_font_size * 1.2,
+        "smaller" => parent_font_size / 1.2,
+        _ => return None,
+    };
+    Some(size)
+}
+
+fn font_weight_value(value: &ComponentValue, parent_weight: u16) -> Option<u16> {
+    if let Some(n) = parse_number(value) {
+        if (1.0..=1000.0).contains(&n) {
+            return Some(n as u16);
+        }
+        return None;
+    }
+    let weight = match value.ident()?.to_ascii_lowercase().as_str() {
+        "normal" => 400,
+        "bold" => 700,
+        "bolder" => match parent_weight {
+            0..=349 => 400,
+            350..=549 => 700,
+            _ => 900,
+        },
+        "lighter" => match parent_weight {
+            0..=549 => 100,
+            550..=749 => 400,
+            _ => 700,
+        },
+        _ => return None,
+    };
+    Some(weight)
+}
+
+pub fn font_family_value(values: &[ComponentValue]) -> Option<Vec<String>> {
+    let mut families = Vec::new();
+    for part in values.split(|v| v.is_token(&CssTokenKind::Comma)) {
+        let mut words = Vec::new();
+        for value in non_whitespace(part) {
+            match value {
+                ComponentValue::Token(CssTokenKind::Ident(name))
+                | ComponentValue::Token(CssTokenKind::String(name)) => words.push(name.clone()),
+                _ => return None,
+            }
+        }
+        if words.is_empty() {
+            return None;
+        }
+        families.push(words.join(" "));
+    }
+    Some(families)
+}
+
+impl ComputedStyle {
+    // 継承プロパティは親の値を、それ以外は初期値を持つスタイルを作る
+    pub fn inherit_from(parent: &ComputedStyle) -> Self {
+        let mut style = ComputedStyle::default();
+        for name in [
+            "color",
+            "font-size",
+            "font-weight",
+            "font-style",
+            "font-family",
+            "line-height",
+            "text-align",
+            "white-space",
+            "word-break",
+            "overflow-wrap",
+            "visibility",
+            "pointer-events",
+            "list-style-type",
+            "border-collapse",
+            "border-spacing",
+            "caption-side",
+        ] {
+            style.copy_property(name, parent);
+        }
+        style.border_color = Sides::all(style.color);
+        style.custom_properties = parent.custom_properties.clone();
+        style
+    }
+
+    pub fn custom_property(&self, name: &str) -> Option<Vec<ComponentValue>> {
+        self.custom_properties.get(name).cloned()
+    }
+
+    fn copy_property(&mut self, name: &str, from: &ComputedStyle) {
+        match name {
+            "display" => self.display = from.display,
+            "color" => self.color = from.color,
+            "background-color" => self.background_color = from.background_color,
+            "background-image" => self.background_image = from.background_image.clone(),
+            "border-top-left-radius" => {
+                self.border_radius.top_left = from.border_radius.top_left.clone()
+            }
+            "border-top-right-radius" => {
+                self.border_radius.top_right = from.border_radius.top_right.clone()
+            }
+            "border-bottom-right-radius" => {
+                self.border_radius.bottom_right = from.border_radius.bottom_right.clone()
+            }
+            "border-bottom-left-radius" => {
+                self.border_radius.bottom_left = from.border_radius.bottom_left.clone()
+            }
+            "font-size" => self.font_size = from.font_size,
+            "font-weight" => self.font_weight = from.font_weight,
+            "font-style" => self.font_style = from.font_style,
+            "font-family" => self.font_family = from.font_family.clone(),
+            "line-height" => self.line_height = from.line_height,
+            "text-align" => self.text_align = from.text_align,
+            "text-decoration-line" => self.text_decoration = from.text_decoration,
+            "white-space" => self.white_space = from.white_space,
+            "word-break" => self.word_break = from.word_break,
+            "overflow-wrap" => self.overflow_wrap = from.overflow_wrap,
+            "vertical-align" => self.vertical_align = from.vertical_align.clone(),
+            "visibility" => self.visibility = from.visibility,
+            "pointer-events" => self.pointer_events = from.pointer_events,
+            "list-style-type" => self.list_style_type = from.list_style_type,
+            "width" => self.width = from.width.clone(),
+            "height" => self.height = from.height.clone(),
+            "min-width" => self.min_width = from.min_width.clone(),
+            "min-height" => self.min_height = from.min_height.clone(),
+            "max-width" => self.max_width = from.max_width.clone(),
+            "max-height" => self.max_height = from.max_height.clone(),
+            "box-sizing" => self.box_sizing = from.box_sizing,
+            "flex-direction" => self.flex_direction = from.flex_direction,
+            "flex-wrap" => self.flex_wrap = from.flex_wrap,
+            "flex-grow" => self.flex_grow = from.flex_grow,
+            "flex-shrink" => self.flex_shrink = from.flex_shrink,
+            "flex-basis" => self.flex_basis = from.flex_basis.clone(),
+            "justify-content" => self.justify_content = from.justify_content,
+            "align-content" => self.align_content = from.align_content,
+            "align-items" => self.align_items = from.align_items,
+            "align-self" => self.align_self = from.align_self,
+            "row-gap" => self.row_gap = from.row_gap.clone(),
+            "column-gap" => self.column_gap = from.column_gap.clone(),
+            "order" => self.order = from.order,
+            "justify-items" => self.justify_items = from.justify_items,
+            "justify-self" => self.justify_self = from.justify_self,
+            "grid-template-rows" => self.grid_template_rows = from.grid_template_rows.clone(),
+            "grid-template-columns" => {
+                self.grid_template_columns = from.grid_template_columns.clone()
+            }
+            "grid-template-areas" => self.grid_template_areas = from.grid_template_areas.clone(),
+            "grid-auto-rows" => self.grid_auto_rows = from.grid_auto_rows.clone(),
+            "grid-auto-columns" => self.grid_auto_columns = from.grid_auto_columns.clone(),
+            "grid-auto-flow" => self.grid_auto_flow = from.grid_auto_flow,
+            "grid-row-start" => self.grid_row_start = from.grid_row_start.clone(),
+            "grid-row-end" => self.grid_row_end = from.grid_row_end.clone(),
+            "grid-column-start" => self.grid_column_start = from.grid_column_start.clone(),
+            "grid-column-end" => self.grid_column_end = from.grid_column_end.clone(),
+            "border-collapse" => self.border_collapse = from.border_collapse,
+            "border-spacing" => self.border_spacing = from.border_spacing,
+            "table-layout" => self.table_layout = from.table_layout,
+            "caption-side" => self.caption_side = from.caption_side,
+            "object-fit" => self.object_fit = from.object_fit,
+            "object-position" => self.object_position = from.object_position.clone(),
+            "position" => self.position = from.position,
+            "top" => self.inset.top = from.inset.top.clone(),
+            "right" => self.inset.right = from.inset.right.clone(),
+            "bottom" => self.inset.bottom = from.inset.bottom.clone(),
+            "left" => self.inset.left = from.inset.left.clone(),
+            "z-index" => self.z_index = from.z_index,
+            "float" => self.float = from.float,
+            "clear" => self.clear = from.clear,
+            "overflow-x" => self.overflow_x = from.overflow_x,
+            "overflow-y" => self.overflow_y = from.overflow_y,
+            "opacity" => self.opacity = from.opacity,
+            _ => {
+                let (prefix, side, suffix) = match split_side_property(name) {
+                    Some(parts) => parts,
+                    None => return,
+                };
+                match (prefix, suffix) {
+                    ("margin", "") => copy_side(&mut self.margin, &from.margin, side),
+                    ("padding", "") => copy_side(&mut self.padding, &from.padding, side),
+                    ("border", "width") => {
+                        copy_side(&mut self.border_width, &from.border_width, side)
+                    }
+                    ("border", "style") => {
+                        copy_side(&mut self.border_style, &from.border_style, side)
+                    }
+                    ("border", "color") => {
+                        copy_side(&mut self.border_color, &from.border_color, side)
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    // 宣言された値を計算値にして設定する。不正な値は無視してfalseを返す
+    pub fn apply_property(
+        &mut self,
+        name: &str,
+        values: &[ComponentValue],
+        parent: &ComputedStyle,
+        context: &LengthContext,
+    ) -> bool {
+        if let Some(keyword) = parse_keyword(values) {
+            if is_css_wide_keyword(&keyword) {
+                let inherit =
+                    keyword == "inherit" || (keyword != "initial" && is_inherited_property(name));
+                if inherit {
+                    self.copy_property(name, parent);
+                } else {
+                    let initial = ComputedStyle {
+                        border_color: Sides::all(self.color),
+                        ..Default::default()
+                    };
+                    self.copy_property(name, &initial);
+                }
+                return true;
+            }
+        }
+
+        let context = LengthContext {
+            font_size: self.font_size,
+            ..*context
+        };
+        let keyword = parse_keyword(values);
+        let keyword = keyword.as_deref().unwrap_or("");
+
+        match name {
+            "display" => set(&mut self.display, Display::from_keyword(keyword)),
+            "color" => {
+                let color = single_value(values).and_then(|v| parse_color(v, parent.color));
+                set(&mut self.color, color)
+            }
+            "background-color" => {
+                let color = single_value(values).and_then(|v| parse_color(v, self.color));
+                set(&mut self.background_color, color)
+            }
+            "background-image" => {
+                if keyword == "none" {
+                    self.background_image = None;
+                    return true;
+                }
+                let gradient = single_value(values)
+                    .and_then(|v| parse_gradient(v, &context, self.color))
+                    .map(Some);
+                set(&mut self.background_image, gradient)
+            }
+            "border-top-left-radius"
+            | "border-top-right-radius"
+            | "border-bottom-right-radius"
+            | "border-bottom-left-radius" => {
+                let radius = |value: &ComponentValue| {
+                    parse_length(value, &context).filter(|l| !l.is_auto() && !l.is_negative())
+                };
+                // 値が1つなら横と縦の半径が同じになる
+                let radii = match non_whitespace(values)[..] {
+                    [both] => radius(both).map(|r| (r.clone(), r)),
+                    [horizontal, vertical] => radius(horizontal).zip(radius(vertical)),
+                    _ => None,
+                };
+                let corner = match name {
+                    "border-top-left-radius" => &mut self.border_radius.top_left,
+                    "border-top-right-radius" => &mut self.border_radius.top_right,
+                    "border-bottom-right-radius" => &mut self.border_radius.bottom_right,
+                    _ => &mut self.border_radius.bottom_left,
+                };
+                set(corner, radii)
+            }
+            "font-size" => {
+                if let Some(size) = font_size_keyword(keyword, parent.font_size) {
+                    self.font_size = size;
+                    return true;
+                }
+                // font-sizeのemと%は親のフォントサイズに対する値
+                let context = LengthContext {
+                    font_size: parent.font_size,
+                    ..context
+                };
+                match single_value(values).and_then(|v| parse_length(v, &context)) {
+                    Some(Length::Auto) | None => false,
+                    Some(length) => {
+                        self.font_size = length.resolve_or_zero(parent.font_size).max(0.0);
+                        true
+                    }
+                }
+            }
+            "font-weight" => {
+                let weight =
+                    single_value(values).and_then(|v| font_weight_value(v, parent.font_weight));
+                set(&mut self.font_weight, weight)
+            }
+            "font-style" => set(&mut self.font_style, FontStyle::from_keyword(keyword)),
+            "font-family" => set(&mut self.font_family, font_family_value(values)),
+            "line-height" => {
+                if keyword == "normal" {
+                    self.line_height = LineHeight::Normal;
+                    return true;
+                }
+                let value = match single_value(values) {
+                    Some(value) => value,
+                    None => return false,
+                };
+                if let Some(n) = parse_number(value) {
+                    return set(
+                        &mut self.line_height,
+                        Some(LineHeight::Number(n)).filter(|_| n >= 0.0),
+                    );
+                }
+                match parse_length(value, &context) {
+                    Some(length) if !length.is_auto() && !length.is_negative() => {
+                        let px = length.resolve_or_zero(self.font_size).max(0.0);
+                        self.line_height = LineHeight::Px(px);
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            "text-align" => set(&mut self.text_align, TextAlign::from_keyword(keyword)),
+            "text-decoration-line" => {
+                let mut decoration = TextDecoration::default();
+                for value in non_whitespace(values) {
+                    match value.ident().map(|k| k.to_ascii_lowercase()).as_deref() {
+                        Some("none") => {}
+                        Some("underline") => decoration.underline = true,
+                        Some("overline") => decoration.overline = true,
+                        Some("line-through") => decoration.line_through = true,
+                        _ => return false,
+                    }
+                }
+                self.text_decoration = decoration;
+                true
+            }
+            "white-space" => set(&mut self.white_space, WhiteSpace::from_keyword(keyword)),
+            "word-break" => set(&mut self.word_break, WordBreak::from_keyword(keyword)),
+            "overflow-wrap" => set(&mut self.overflow_wrap, OverflowWrap::from_keyword(keyword)),
+            "vertical-align" => {
+                if let Some(align) = VerticalAlign::from_keyword(keyword) {
+                    self.vertical_align = align;
+                    return true;
+                }
+                let length = single_value(values)
+                    .and_then(|v| parse_length(v, &context))
+                    .filter(|length| !length.is_auto());
+                set(&mut self.vertical_align, length.map(VerticalAlign::Length))
+            }
+            "visibility" => set(&mut self.visibility, Visibility::from_keyword(keyword)),
+            "pointer-events" => set(
+                &mut self.pointer_events,
+                PointerEvents::from_keyword(keyword),
+            ),
+            "list-style-type" => set(
+                &mut self.list_style_type,
+                ListStyleType::from_keyword(keyword),
+            ),
+            "box-sizing" => set(&mut self.box_sizing, BoxSizing::from_keyword(keyword)),
+            "width" | "height" | "min-width" | "min-height" => {
+                let length = single_value(values)
+                    .and_then(|v| parse_length(v, &context))
+                    .filter(|length| !length.is_negative());
+                match name {
+                    "width" => set(&mut self.width, length),
+                    "height" => set(&mut self.height, length),
+                    "min-width" => set(&mut self.min_width, length),
+                    _ => set(&mut self.min_height, length),
+                }
+            }
+            "max-width" | "max-height" => {
+                let length = if keyword == "none" {
+                    None
+                } else {
+                    match single_value(values).and_then(|v| parse_length(v, &context)) {
+                        Some(length) if !length.is_auto() && !length.is_negative() => Some(length),
+                        _ => return false,
+                    }
+                };
+                if name == "max-width" {
+                    self.max_width = length;
+                } else {
+                    self.max_height = length;
+                }
+                true
+            }
+            "flex-direction" => set(
+                &mut self.flex_direction,
+                FlexDirection::from_keyword(keyword),
+            ),
+            "flex-wrap" => set(&mut self.flex_wrap, FlexWrap::from_keyword(keyword)),
+            "flex-grow" | "flex-shrink" => {
+                let factor = single_value(values)
+                    .and_then(parse_number)
+                    .filter(|n| *n >= 0.0);
+                if name == "flex-grow" {
+                    set(&mut self.flex_grow, factor)
+                } else {
+                    set(&mut self.flex_shrink, factor)
+                }
+            }
+            "flex-basis" => {
+                let basis = match keyword {
+                    "auto" => Some(FlexBasis::Auto),
+                    "content" => Some(FlexBasis::Content),
+                    _ => single_value(values)
+                        .and_then(|v| parse_length(v, &context))
+                        .filter(|length| !length.is_auto() && !length.is_negative())
+                        .map(FlexBasis::Length),
+                };
+                set(&mut self.flex_basis, basis)
+            }
+            "justify-content" | "align-content" => {
+                let align = ContentAlign::from_keyword(keyword);
+                if name == "justify-content" {
+                    set(&mut self.justify_content, align)
+                } else {
+                    set(&mut self.align_content, align)
+                }
+            }
+            "align-items" => {
+                let align = ItemAlign::from_keyword(keyword).filter(|a| *a != ItemAlign::Auto);
+                set(&mut self.align_items, align)
+            }
+            "align-self" => set(&mut self.align_self, ItemAlign::from_keyword(keyword)),
+            "row-gap" | "column-gap" => {
+                let gap = if keyword == "normal" {
+                    Some(Length::Px(0.0))
+                } else {
+                    single_value(values)
+                        .and_then(|v| parse_length(v, &context))
+                        .filter(|length| !length.is_auto() && !length.is_negative())
+                };
+                if name == "row-gap" {
+                    set(&mut self.row_gap, gap)
+                } else {
+                    set(&mut self.column_gap, gap)
+                }
+            }
+            "order" => {
+                let order = single_value(values)
+                    .and_then(parse_number)
+                    .filter(|n| *n as i32 as f64 == *n)
+                    .map(|n| n as i32);
+                set(&mut self.order, order)
+            }
+            "justify-items" => {
+                let align = ItemAlign::from_keyword(keyword).filter(|a| *a != ItemAlign::Auto);
+                set(&mut self.justify_items, align)
+            }
+            "justify-self" => set(&mut self.justify_self, ItemAlign::from_keyword(keyword)),
+            "grid-template-rows" => set(
+                &mut self.grid_template_rows,
+                parse_track_list(values, &context),
+            ),
+            "grid-template-columns" => set(
+                &mut self.grid_template_columns,
+                parse_track_list(values, &context),
+            ),
+            "grid-template-areas" => set(
+                &mut self.grid_template_areas,
+                parse_grid_template_areas(values),
+            ),
+            "grid-auto-rows" => set(
+                &mut self.grid_auto_rows,
+                parse_track_sizes(values, &context),
+            ),
+            "grid-auto-columns" => set(
+                &mut self.grid_auto_columns,
+                parse_track_sizes(values, &context),
+            ),
+            "grid-auto-flow" => set(&mut self.grid_auto_flow, parse_grid_auto_flow(values)),
+            "grid-row-start" => set(&mut self.grid_row_start, parse_grid_line(values)),
+            "grid-row-end" => set(&mut self.grid_row_end, parse_grid_line(values)),
+            "grid-column-start" => set(&mut self.grid_column_start, parse_grid_line(values)),
+            "grid-column-end" => set(&mut self.grid_column_end, parse_grid_line(values)),
+            "border-collapse" => set(
+                &mut self.border_collapse,
+                BorderCollapse::from_keyword(keyword),
+            ),
+            "border-spacing" => set(
+                &mut self.border_spacing,
+                border_spacing_value(values, &context),
+            ),
+            "table-layout" => set(&mut self.table_layout, TableLayout::from_keyword(keyword)),
+            "caption-side" => set(&mut self.caption_side, CaptionSide::from_keyword(keyword)),
+            "object-fit" => set(&mut self.object_fit, ObjectFit::from_keyword(keyword)),
+            "object-position" => set(
+                &mut self.object_position,
+                parse_position(&non_whitespace(values), &context),
+            ),
+            "position" => set(&mut self.position, Position::from_keyword(keyword)),
+            "top" | "right" | "bottom" | "left" => {
+                let length = single_value(values).and_then(|v| parse_length(v, &context));
+                set_side(&mut self.inset, name, length)
+            }
+            "z-index" => {
+                if keyword == "auto" {
+                    self.z_index = None;
+                    return true;
+                }
+                let z_index = single_value(values)
+                    .and_then(parse_number)
+                    .filter(|n| *n as i32 as f64 == *n)
+                    .map(|n| Some(n as i32));
+                set(&mut self.z_index, z_index)
+            }
+            "float" => set(&mut self.float, Float::from_keyword(keyword)),
+            "clear" => set(&mut self.clear, Clear::from_keyword(keyword)),
+            "overflow-x" => set(&mut self.overflow_x, Overflow::from_keyword(keyword)),
+            "overflow-y" => set(&mut self.overflow_y, Overflow::from_keyword(keyword)),
+            "opacity" => {
+                // 範囲外の値は範囲に収める
+                let opacity = single_value(values)
+                    .and_then(|v| match v {
+                        ComponentValue::Token(CssTokenKind::Percentage(p)) => Some(p / 100.0),
+                        _ => parse_number(v),
+                    })
+                    .map(|o| o.clamp(0.0, 1.0));
+                set(&mut self.opacity, opacity)
+            }
+            _ => self.apply_side_property(name, values, &context),
+        }
+    }
+
+    fn apply_side_property(
+        &mut self,
+        name: &str,
+        values: &[ComponentValue],
+        context: &LengthContext,
+    ) -> bool {
+        let (prefix, side, suffix) = match split_side_property(name) {
+            Some(parts) => parts,
+            None => return false,
+        };
+        let value = match single_value(values) {
+            Some(value) => value,
+            None => return false,
+        };
+
+        match (prefix, suffix) {
+            ("margin", "") => {
+                let length = parse_length(value, context);
+                set_side(&mut self.margin, side, length)
+            }
+            ("padding", "") => {
+                let length = parse_length(value, context)
+                    .filter(|length| !length.is_auto() && !length.is_negative());
+                set_side(&mut self.padding, side, length)
+            }
+            ("border", "width") => {
+                let width = border_width_value(value, context);
+                set_side(&mut self.border_width, side, width)
+            }
+            ("border", "style") => {
+                let style = value
+                    .ident()
+                    .and_then(|k| BorderStyle::from_keyword(&k.to_ascii_lowercase()));
+                set_side(&mut self.border_style, side, style)
+            }
+            ("border", "color") => {
+                let color = parse_color(value, self.color);
+                set_side(&mut self.border_color, side, color)
+            }
+            _ => false,
+        }
+    }
+
+    // すべての宣言を適用した後に値を確定させる
+    pub fn finalize(&mut self, is_root: bool) {
+        for side in SIDES {
+            if let Some(style) = side_of(&self.border_style, side) {
+                if matches!(style, BorderStyle::None | BorderStyle::Hidden) {
+                    set_side(&mut self.border_width, side, Some(0.0));
+                }
+            }
+        }
+        // 絶対配置の要素はフロートにならない。CSS 2.1 9.7
+        if self.position.is_absolute() {
+            self.float = Float::None;
+        }
+        if is_root || self.position.is_absolute() || self.float != Float::None {
+            self.display = self.display.blockify();
+        }
+        // 片方の軸だけがスクロールする場合、もう片方のvisibleとclipはautoとhiddenになる。CSS Overflow 3 3.1
+        let scrollable = self.overflow_x.is_scrollable() || self.overflow_y.is_scrollable();
+        for overflow in [&mut self.overflow_x, &mut self.overflow_y] {
+            match *overflow {
+                Overflow::Visible if scrollable => *overflow = Overflow::Auto,
+                Overflow::Clip if scrollable => *overflow = Overflow::Hidden,
+                _ => {}
+            }
+        }
+    }
+}
+
+// margin-topなどを("margin", "top", "")に、border-top-widthを("border", "top", "width")に分ける
+fn split_side_property(name: &str) -> Option<(&str, &str, &str)> {
+    let mut parts = name.splitn(3, '-');
+    let prefix = parts.next()?;
+    let side = parts.next()?;
+    let suffix = parts.next().unwrap_or("");
+    if !SIDES.contains(&side) {
+        return None;
+    }
+    Some((prefix, side, suffix))
+}
+
+fn set_side<T>(sides: &mut Sides<T>, side: &str, value: Option<T>) -> bool {
+    match (side_mut(sides, side), value) {
+        (Some(s), Some(value)) => {
+            *s = value;
+            true
+        }
+        _ => false,
+    }
+}
+
+// 値が正しい場合のみ設定する
+fn set<T>(property: &mut T, value: Option<T>) -> bool {
+    match value {
+        Some(value) => {
+            *property = value;
+            true
+        }
+        None => false,
+    }
+}
+
+fn copy_side<T: Clone>(sides: &mut Sides<T>, from: &Sides<T>, side: &str) {
+    if let Some(value) = side_of(from, side) {
+        set_side(sides, side, Some(value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::css::{parser::CssParser, token::CssTokenizer};
+
+    fn values(s: &str) -> Vec<ComponentValue> {
+        CssParser::new(CssTokenizer::new(s.to_string())).parse_component_values()
+    }
+
+    fn expand(name: &str, value: &str) -> Vec<(String, String)> {
+        expand_shorthand(name, &values(value))
+            .into_iter()
+            .map(|(n, v)| {
+                let v = v.iter().map(|v| v.to_string()).collect::<String>();
+                (n, v.trim().to_string())
+            })
+            .collect()
+    }
+
+    fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(n, v)| (n.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn apply(style: &mut ComputedStyle, name: &str, value: &str, parent: &ComputedStyle) {
+        for (name, values) in expand_shorthand(name, &values(value)) {
+            style.apply_property(&name, &values, parent, &LengthContext::default());
+        }
+    }
+
+    #[test]
+    fn test_expand_shorthand() {
+        assert_eq!(
+            pairs(&[
+                ("margin-top", "1px"),
+                ("margin-right", "2px"),
+                ("margin-bottom", "1px"),
+                ("margin-left", "2px"),
+            ]),
+            expand("margin", "1px 2px")
+        );
+        assert_eq!(
+            pairs(&[
+                ("border-left-width", "medium"),
+                ("border-left-style", "dashed"),
+                ("border-left-color", "red"),
+            ]),
+            expand("border-left", "red dashed")
+        );
+        assert_eq!(
+            pairs(&[
+                ("font-style", "italic"),
+                ("font-weight", "bold"),
+                ("font-size", "12px"),
+                ("line-height", "1.5"),
+                ("font-family", "\"Helvetica Neue\", sans-serif"),
+            ]),
+            expand(
+                "font",
+                "italic bold 12px/1.5 \"Helvetica Neue\", sans-serif"
+            )
+        );
+        assert_eq!(12, expand("border", "inherit").len());
+        assert!(expand("margin", "1px 2px 3px 4px 5px").is_empty());
+        assert!(expand("font", "bold").is_empty());
+        assert_eq!(pairs(&[("color", "red")]), expand("color", "red"));
+        assert_eq!(
+            pairs(&[
+                ("flex-grow", "2"),
+                ("flex-shrink", "1"),
+                ("flex-basis", "0%"),
+            ]),
+            expand("flex", "2")
+        );
+        assert_eq!(
+            pairs(&[
+                ("flex-grow", "1"),
+                ("flex-shrink", "1"),
+                ("flex-basis", "10px"),
+            ]),
+            expand("flex", "10px")
+        );
+        assert_eq!(
+            pairs(&[
+                ("flex-grow", "0"),
+                ("flex-shrink", "0"),
+                ("flex-basis", "0"),
+            ]),
+            expand("flex", "0 0 0")
+        );
+        assert_eq!(
+            pairs(&[("flex-direction", "column"), ("flex-wrap", "wrap")]),
+            expand("flex-flow", "wrap column")
+        );
+        assert!(expand("flex", "1 2 3").is_empty());
+        assert_eq!(
+            pairs(&[("grid-row-start", "2"), ("grid-row-end", "span 3")]),
+            expand("grid-row", "2 / span 3")
+        );
+        assert_eq!(
+            pairs(&[
+                ("grid-row-start", "main"),
+                ("grid-column-start", "main"),
+                ("grid-row-end", "main"),
+                ("grid-column-end", "main"),
+            ]),
+            expand("grid-area", "main")
+        );
+        assert_eq!(
+            pairs(&[
+                ("grid-row-start", "1"),
+                ("grid-column-start", "a"),
+                ("grid-row-end", "auto"),
+                ("grid-column-end", "a"),
+            ]),
+            expand("grid-area", "1 / a")
+        );
+        assert!(expand("grid-column", "1 / 2 / 3").is_empty());
+    }
+
+    #[test]
+    fn test_apply_property() {
+        let parent = ComputedStyle {
+            font_size: 20.0,
+            color: Color::rgb(255, 0, 0),
+            ..Default::default()
+        };
+        let mut style = ComputedStyle::inherit_from(&parent);
+        assert_eq!(20.0, style.font_size);
+        assert_eq!(Sides::all(Color::rgb(255, 0, 0)), style.border_color);
+
+        apply(&mut style, "font-size", "2em", &parent);
+        assert_eq!(40.0, style.font_size);
+        apply(&mut style, "font-size", "150%", &parent);
+        assert_eq!(30.0, style.font_size);
+        apply(&mut style, "margin", "1em auto", &parent);
+        assert_eq!(Length::Px(30.0), style.margin.top);
+        assert_eq!(Length::Auto, style.margin.left);
+        apply(&mut style, "padding", "-1px", &parent);
+        assert_eq!(Sides::all(Length::Px(0.0)), style.padding);
+        apply(&mut style, "font-weight", "bolder", &parent);
+        assert_eq!(700, style.font_weight);
+        apply(&mut style, "line-height", "150%", &parent);
+        assert_eq!(LineHeight::Px(45.0), style.line_height);
+        apply(&mut style, "display", "inline-flex", &parent);
+        assert_eq!(Display::InlineFlex, style.display);
+        apply(&mut style, "display", "bogus", &parent);
+        assert_eq!(Display::InlineFlex, style.display);
+        apply(&mut style, "max-width", "50%", &parent);
+        assert_eq!(Some(Length::Percent(50.0)), style.max_width);
+        apply(
+            &mut style,
+            "text-decoration",
+            "underline line-through",
+            &parent,
+        );
+        assert!(style.text_decoration.underline && style.text_decoration.line_through);
+        apply(&mut style, "vertical-align", "super", &parent);
+        assert_eq!(VerticalAlign::Super, style.vertical_align);
+        apply(&mut style, "vertical-align", "-2px", &parent);
+        assert_eq!(
+            VerticalAlign::Length(Length::Px(-2.0)),
+            style.vertical_align
+        );
+        apply(&mut style, "word-wrap", "break-word", &parent);
+        assert_eq!(OverflowWrap::BreakWord, style.overflow_wrap);
+        apply(&mut style, "word-break", "keep-all", &parent);
+        assert_eq!(WordBreak::KeepAll, style.word_break);
+
+        apply(&mut style, "flex", "none", &parent);
+        assert_eq!((0.0, 0.0), (style.flex_grow, style.flex_shrink));
+        assert_eq!(FlexBasis::Auto, style.flex_basis);
+        apply(&mut style, "flex", "3 content", &parent);
+        assert_eq!(FlexBasis::Content, style.flex_basis);
+        apply(&mut style, "flex-grow", "-1", &parent);
+        assert_eq!(3.0, style.flex_grow);
+        apply(&mut style, "gap", "1px 2em", &parent);
+        assert_eq!(Length::Px(1.0), style.row_gap);
+        assert_eq!(Length::Px(60.0), style.column_gap);
+        apply(&mut style, "order", "-2", &parent);
+        assert_eq!(-2, style.order);
+        apply(&mut style, "align-items", "auto", &parent);
+        assert_eq!(ItemAlign::Normal, style.align_items);
+        apply(&mut style, "justify-content", "space-between", &parent);
+        assert_eq!(ContentAlign::SpaceBetween, style.justify_content);
+        apply(&mut style, "grid-column", "span 2", &parent);
+        assert_eq!(GridLine::Span(2, None), style.grid_column_start);
+        assert_eq!(GridLine::Auto, style.grid_column_end);
+        apply(
+            &mut style,
+            "grid-template-columns",
+            "repeat(2, 1em)",
+            &parent,
+        );
+        assert_eq!(1, style.grid_template_columns.len());
+        apply(&mut style, "grid-auto-rows", "10px 1fr", &parent);
+        assert_eq!(2, style.grid_auto_rows.len());
+        apply(&mut style, "grid-auto-flow", "dense", &parent);
+        assert!(style.grid_auto_flow.dense && !style.grid_auto_flow.column);
+        apply(&mut style, "justify-self", "center", &parent);
+        assert_eq!(ItemAlign::Center, style.justify_self);
+        apply(&mut style, "border-spacing", "2px 1em", &parent);
+        assert_eq!((2.0, 30.0), style.border_spacing);
+        apply(&mut style, "border-spacing", "-1px", &parent);
+        assert_eq!((2.0, 30.0), style.border_spacing);
+        apply(&mut style, "border-collapse", "collapse", &parent);
+        assert_eq!(BorderCollapse::Collapse, style.border_collapse);
+        apply(&mut style, "table-layout", "fixed", &parent);
+        assert_eq!(TableLayout::Fixed, style.table_layout);
+        apply(&mut style, "inset", "1px 10%", &parent);
+        assert_eq!(Length::Px(1.0), style.inset.bottom);
+        assert_eq!(Length::Percent(10.0), style.inset.left);
+        apply(&mut style, "z-index", "-3", &parent);
+        assert_eq!(Some(-3), style.z_index);
+        apply(&mut style, "z-index", "1.5", &parent);
+        assert_eq!(Some(-3), style.z_index);
+        apply(&mut style, "z-index", "auto", &parent);
+        assert_eq!(None, style.z_index);
+
+        apply(&mut style, "color", "blue", &parent);
+        apply(&mut style, "font-size", "initial", &parent);
+        assert_eq!(16.0, style.font_size);
+        apply(&mut style, "color", "unset", &parent);
+        assert_eq!(parent.color, style.color);
+        apply(&mut style, "border", "2px solid", &parent);
+        assert_eq!(Sides::all(2.0), style.border_width);
+        assert_eq!(Sides::all(BorderStyle::Solid), style.border_style);
+
+        apply(&mut style, "border-top", "thick", &parent);
+        style.finalize(false);
+        assert_eq!(0.0, style.border_width.top);
+        assert_eq!(2.0, style.border_width.left);
+
+        // フロートと絶対配置の要素はブロック化される
+        let mut style = ComputedStyle::inherit_from(&parent);
+        apply(&mut style, "float", "left", &parent);
+        style.finalize(false);
+        assert_eq!(Display::Block, style.display);
+        apply(&mut style, "position", "absolute", &parent);
+        style.finalize(false);
+        assert_eq!(Float::None, style.float);
+
+        let mut style = ComputedStyle::inherit_from(&parent);
+        apply(&mut style, "overflow", "clip scroll", &parent);
+        assert_eq!(Overflow::Clip, style.overflow_x);
+        style.finalize(false);
+        assert_eq!(Overflow::Hidden, style.overflow_x);
+        assert_eq!(Overflow::Scroll, style.overflow_y);
+        let mut style = ComputedStyle::inherit_from(&parent);
+        apply(&mut style, "overflow-y", "clip", &parent);
+        style.finalize(false);
+        assert_eq!(Overflow::Visible, style.overflow_x);
+        assert_eq!(Overflow::Clip, style.overflow_y);
+        apply(&mut style, "opacity", "40%", &parent);
+        assert_eq!(0.4, style.opacity);
+        apply(&mut style, "opacity", "1.5", &parent);
+        assert_eq!(1.0, style.opacity);
+        apply(&mut style, "border-radius", "1px 10% / 2px", &parent);
+        assert_eq!(
+            (Length::Percent(10.0), Length::Px(2.0)),
+            style.border_radius.top_right
+        );
+        assert_eq!(
+            (Length::Px(1.0), Length::Px(2.0)),
+            style.border_radius.bottom_right
+        );
+        apply(&mut style, "border-bottom-left-radius", "-1px", &parent);
+        assert_eq!(
+            (Length::Percent(10.0), Length::Px(2.0)),
+            style.border_radius.bottom_left
+        );
+        apply(
+            &mut style,
+            "background",
+            "linear-gradient(red, blue) green",
+            &parent,
+        );
+        assert_eq!(Color::rgb(0, 128, 0), style.background_color);
+        assert!(style.background_image.is_some());
+        apply(&mut style, "background", "red", &parent);
+        assert_eq!(None, style.background_image);
+        apply(&mut style, "pointer-events", "none", &parent);
+        assert_eq!(PointerEvents::None, style.pointer_events);
+        apply(&mut style, "pointer-events", "visiblePainted", &parent);
+        assert_eq!(PointerEvents::Auto, style.pointer_events);
+        apply(&mut style, "object-fit", "scale-down", &parent);
+        assert_eq!(ObjectFit::ScaleDown, style.object_fit);
+        apply(&mut style, "object-position", "bottom 2em", &parent);
+        assert_eq!(
+            (Length::Px(40.0), Length::Percent(100.0)),
+            style.object_position
+        );
+        apply(&mut style, "object-position", "left 1px 2px", &parent);
+        assert_eq!(
+            (Length::Px(40.0), Length::Percent(100.0)),
+            style.object_position
+        );
+    }
+}
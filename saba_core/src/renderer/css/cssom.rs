@@ -0,0 +1,370 @@
+use crate::renderer::css::{selector::SelectorList, token::CssTokenKind};
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ComponentValue {
+    Token(CssTokenKind),
+    Function {
+        name: String,
+        value: Vec<ComponentValue>,
+    },
+    SimpleBlock {
+        associated: char,
+        value: Vec<ComponentValue>,
+    },
+}
+
+impl ComponentValue {
+    pub fn is_whitespace(&self) -> bool {
+        matches!(self, ComponentValue::Token(CssTokenKind::Whitespace))
+    }
+
+    pub fn is_token(&self, kind: &CssTokenKind) -> bool {
+        matches!(self, ComponentValue::Token(k) if k == kind)
+    }
+
+    pub fn ident(&self) -> Option<&str> {
+        match self {
+            ComponentValue::Token(CssTokenKind::Ident(name)) => Some(name),
+            _ => None,
+        }
+    }
+}
+
+fn closing_char(associated: char) -> char {
+    match associated {
+        '(' => ')',
+        '[' => ']',
+        _ => '}',
+    }
+}
+
+fn write_escaped_string(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for ch in s.chars() {
+        match ch {
+            '"' | '\\' => write!(f, "\\{}", ch)?,
+            '\n' => write!(f, "\\a ")?,
+            _ => write!(f, "{}", ch)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+impl fmt::Display for CssTokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CssTokenKind::Ident(name) => write!(f, "{}", name),
+            CssTokenKind::Function(name) => write!(f, "{}(", name),
+            CssTokenKind::AtKeyword(name) => write!(f, "@{}", name),
+            CssTokenKind::Hash { value, is_id: _ } => write!(f, "#{}", value),
+            CssTokenKind::String(s) => write_escaped_string(f, s),
+            CssTokenKind::BadString => Ok(()),
+            CssTokenKind::Url(url) => write!(f, "url({})", url),
+            CssTokenKind::BadUrl => Ok(()),
+            CssTokenKind::Delim(ch) => write!(f, "{}", ch),
+            CssTokenKind::Number {
+                value,
+                is_integer: _,
+            } => write!(f, "{}", value),
+            CssTokenKind::Percentage(value) => write!(f, "{}%", value),
+            CssTokenKind::Dimension {
+                value,
+                is_integer: _,
+                unit,
+            } => write!(f, "{}{}", value, unit),
+            CssTokenKind::UnicodeRange { start, end } => {
+                if start == end {
+                    write!(f, "U+{:X}", start)
+                } else {
+                    write!(f, "U+{:X}-{:X}", start, end)
+                }
+            }
+            CssTokenKind::Whitespace => write!(f, " "),
+            CssTokenKind::Cdo => write!(f, "<!--"),
+            CssTokenKind::Cdc => write!(f, "-->"),
+            CssTokenKind::Colon => write!(f, ":"),
+            CssTokenKind::Semicolon => write!(f, ";"),
+            CssTokenKind::Comma => write!(f, ","),
+            CssTokenKind::OpenSquare => write!(f, "["),
+            CssTokenKind::CloseSquare => write!(f, "]"),
+            CssTokenKind::OpenParen => write!(f, "("),
+            CssTokenKind::CloseParen => write!(f, ")"),
+            CssTokenKind::OpenCurly => write!(f, "{{"),
+            CssTokenKind::CloseCurly => write!(f, "}}"),
+        }
+    }
+}
+
+impl fmt::Display for ComponentValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComponentValue::Token(token) => write!(f, "{}", token),
+            ComponentValue::Function { name, value } => {
+                write!(f, "{}(", name)?;
+                write_component_values(f, value)?;
+                write!(f, ")")
+            }
+            ComponentValue::SimpleBlock { associated, value } => {
+                write!(f, "{}", associated)?;
+                write_component_values(f, value)?;
+                write!(f, "{}", closing_char(*associated))
+            }
+        }
+    }
+}
+
+pub fn write_component_values(
+    f: &mut fmt::Formatter<'_>,
+    values: &[ComponentValue],
+) -> fmt::Result {
+    for value in values {
+        write!(f, "{}", value)?;
+    }
+    Ok(())
+}
+
+pub struct ComponentValues<'a>(pub &'a [ComponentValue]);
+
+impl fmt::Display for ComponentValues<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_component_values(f, self.0)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Declaration {
+    pub name: String,
+    pub value: Vec<ComponentValue>,
+    pub important: bool,
+}
+
+impl Declaration {
+    pub fn new(name: String, value: Vec<ComponentValue>, important: bool) -> Self {
+        Self {
+            name,
+            value,
+            important,
+        }
+    }
+
+    pub fn is_custom_property(&self) -> bool {
+        self.name.starts_with("--")
+    }
+}
+
+impl fmt::Display for Declaration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.name)?;
+        write_component_values(f, &self.value)?;
+        if self.important {
+            write!(f, " !important")?;
+        }
+        write!(f, ";")
+    }
+}
+
+fn write_declarations(f: &mut fmt::Formatter<'_>, declarations: &[Declaration]) -> fmt::Result {
+    write!(f, "{{")?;
+    for declaration in declarations {
+        write!(f, " {}", declaration)?;
+    }
+    write!(f, " }}")
+}
+
+fn write_rules(f: &mut fmt::Formatter<'_>, rules: &[CssRule]) -> fmt::Result {
+    write!(f, "{{")?;
+    for rule in rules {
+        write!(f, "\n  {}", rule)?;
+    }
+    write!(f, "\n}}")
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct StyleRule {
+    pub selectors: SelectorList,
+    pub declarations: Vec<Declaration>,
+}
+
+impl fmt::Display for StyleRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ", self.selectors)?;
+        write_declarations(f, &self.declarations)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MediaRule {
+    pub media: Vec<ComponentValue>,
+    pub rules: Vec<CssRule>,
+}
+
+impl fmt::Display for MediaRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "@media {} ", ComponentValues(&self.media))?;
+        write_rules(f, &self.rules)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImportRule {
+    pub url: String,
+    pub layer: Option<String>,
+    pub supports: Option<Vec<ComponentValue>>,
+    pub media: Vec<ComponentValue>,
+}
+
+impl fmt::Display for ImportRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "@import url(\"{}\")", self.url)?;
+        match &self.layer {
+            Some(layer) if layer.is_empty() => write!(f, " layer")?,
+            Some(layer) => write!(f, " layer({})", layer)?,
+            None => {}
+        }
+        if let Some(supports) = &self.supports {
+            write!(f, " supports({})", ComponentValues(supports))?;
+        }
+        if !self.media.is_empty() {
+            write!(f, " {}", ComponentValues(&self.media))?;
+        }
+        write!(f, ";")
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FontFaceRule {
+    pub declarations: Vec<Declaration>,
+}
+
+impl fmt::Display for FontFaceRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "@font-face ")?;
+        write_declarations(f, &self.declarations)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SupportsRule {
+    pub condition: Vec<ComponentValue>,
+    pub rules: Vec<CssRule>,
+}
+
+impl fmt::Display for SupportsRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "@supports {} ", ComponentValues(&self.condition))?;
+        write_rules(f, &self.rules)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Keyframe {
+    // 0.0から1.0の範囲で表したキーフレームの位置
+    pub offsets: Vec<f64>,
+    pub declarations: Vec<Declaration>,
+}
+
+impl fmt::Display for Keyframe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, offset) in self.offsets.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}%", offset * 100.0)?;
+        }
+        write!(f, " ")?;
+        write_declarations(f, &self.declarations)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyframesRule {
+    pub name: String,
+    pub keyframes: Vec<Keyframe>,
+}
+
+impl fmt::Display for KeyframesRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "@keyframes {} {{", self.name)?;
+        for keyframe in &self.keyframes {
+            write!(f, "\n  {}", keyframe)?;
+        }
+        write!(f, "\n}}")
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LayerRule {
+    pub names: Vec<String>,
+    // `@layer a, b;`のような文形式の場合はNone
+    pub rules: Option<Vec<CssRule>>,
+}
+
+impl fmt::Display for LayerRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "@layer")?;
+        for (i, name) in self.names.iter().enumerate() {
+            if i == 0 {
+                write!(f, " {}", name)?;
+            } else {
+                write!(f, ", {}", name)?;
+            }
+        }
+        match &self.rules {
+            Some(rules) => {
+                write!(f, " ")?;
+                write_rules(f, rules)
+            }
+            None => write!(f, ";"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CssRule {
+    Style(StyleRule),
+    Media(MediaRule),
+    Import(ImportRule),
+    FontFace(FontFaceRule),
+    Supports(SupportsRule),
+    Keyframes(KeyframesRule),
+    Layer(LayerRule),
+}
+
+impl fmt::Display for CssRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CssRule::Style(rule) => write!(f, "{}", rule),
+            CssRule::Media(rule) => write!(f, "{}", rule),
+            CssRule::Import(rule) => write!(f, "{}", rule),
+            CssRule::FontFace(rule) => write!(f, "{}", rule),
+            CssRule::Supports(rule) => write!(f, "{}", rule),
+            CssRule::Keyframes(rule) => write!(f, "{}", rule),
+            CssRule::Layer(rule) => write!(f, "{}", rule),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StyleSheet {
+    pub rules: Vec<CssRule>,
+}
+
+impl StyleSheet {
+    pub fn new() -> Self {
+        Self { rules: Vec::new() }
+    }
+}
+
+impl fmt::Display for StyleSheet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, rule) in self.rules.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", rule)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,240 @@
+use crate::renderer::css::{cssom::ComponentValue, token::CssTokenKind, value::parse_keyword};
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    string::{String, ToString},
+    vec::Vec,
+};
+
+pub fn contains_var(values: &[ComponentValue]) -> bool {
+    values.iter().any(|value| match value {
+        ComponentValue::Function { name, value } => {
+            name.eq_ignore_ascii_case("var") || contains_var(value)
+        }
+        ComponentValue::SimpleBlock {
+            associated: _,
+            value,
+        } => contains_var(value),
+        ComponentValue::Token(_) => false,
+    })
+}
+
+// var(--name, fallback)の引数を(名前, フォールバック)に分ける
+fn parse_var_arguments(values: &[ComponentValue]) -> Option<(String, Option<Vec<ComponentValue>>)> {
+    let mut rest = values.iter().skip_while(|v| v.is_whitespace());
+    let name = rest.next()?.ident()?;
+    if !name.starts_with("--") {
+        return None;
+    }
+
+    let mut rest = rest.skip_while(|v| v.is_whitespace());
+    match rest.next() {
+        None => Some((name.to_string(), None)),
+        Some(ComponentValue::Token(CssTokenKind::Comma)) => {
+            let mut fallback: Vec<ComponentValue> =
+                rest.skip_while(|v| v.is_whitespace()).cloned().collect();
+            while fallback.last().is_some_and(|v| v.is_whitespace()) {
+                fallback.pop();
+            }
+            Some((name.to_string(), Some(fallback)))
+        }
+        Some(_) => None,
+    }
+}
+
+// var()を参照先の値で置き換える。参照先が無効でフォールバックもない場合はNoneを返す
+pub fn substitute_var(
+    values: &[ComponentValue],
+    lookup: &mut dyn FnMut(&str) -> Option<Vec<ComponentValue>>,
+) -> Option<Vec<ComponentValue>> {
+    let mut result = Vec::new();
+    for value in values {
+        match value {
+            ComponentValue::Function { name, value } if name.eq_ignore_ascii_case("var") => {
+                let (name, fallback) = parse_var_arguments(value)?;
+                match (lookup(&name), fallback) {
+                    (Some(values), _) => result.extend(values),
+                    (None, Some(fallback)) => result.extend(substitute_var(&fallback, lookup)?),
+                    (None, None) => return None,
+                }
+            }
+            ComponentValue::Function { name, value } => result.push(ComponentValue::Function {
+                name: name.clone(),
+                value: substitute_var(value, lookup)?,
+            }),
+            ComponentValue::SimpleBlock { associated, value } => {
+                result.push(ComponentValue::SimpleBlock {
+                    associated: *associated,
+                    value: substitute_var(value, lookup)?,
+                })
+            }
+            ComponentValue::Token(_) => result.push(value.clone()),
+        }
+    }
+    Some(result)
+}
+
+struct CustomPropertyResolver<'a> {
+    declared: BTreeMap<&'a str, &'a [ComponentValue]>,
+    inherited: &'a BTreeMap<String, Vec<ComponentValue>>,
+    // Noneは無効が保証された値
+    resolved: BTreeMap<String, Option<Vec<ComponentValue>>>,
+    stack: Vec<String>,
+    cyclic: BTreeSet<String>,
+}
+
+impl<'a> CustomPropertyResolver<'a> {
+    fn resolve(&mut self, name: &str) -> Option<Vec<ComponentValue>> {
+        if let Some(resolved) = self.resolved.get(name) {
+            return resolved.clone();
+        }
+        let values: &'a [ComponentValue] = match self.declared.get(name) {
+            Some(values) => values,
+            None => return self.inherited.get(name).cloned(),
+        };
+
+        if let Some(pos) = self.stack.iter().position(|n| n == name) {
+            // 循環に含まれるプロパティはフォールバックの有無にかかわらずすべて無効
+            for n in &self.stack[pos..] {
+                self.cyclic.insert(n.clone());
+            }
+            return None;
+        }
+
+        self.stack.push(name.to_string());
+        let result = match parse_keyword(values).as_deref() {
+            // カスタムプロパティは継承プロパティなのでunsetも継承になる
+            Some("inherit") | Some("unset") | Some("revert") => self.inherited.get(name).cloned(),
+            Some("initial") => None,
+            _ => {
+                if contains_var(values) {
+                    substitute_var(values, &mut |n| self.resolve(n))
+                } else {
+                    Some(values.to_vec())
+                }
+            }
+        };
+        self.stack.pop();
+
+        let result = if self.cyclic.contains(name) {
+            None
+        } else {
+            result
+        };
+        self.resolved.insert(name.to_string(), result.clone());
+        result
+    }
+}
+
+// 要素で宣言されたカスタムプロパティの計算値を求める。declaredは優先度の低い順
+pub fn resolve_custom_properties(
+    declared: &[(String, Vec<ComponentValue>)],
+    inherited: &BTreeMap<String, Vec<ComponentValue>>,
+) -> BTreeMap<String, Vec<ComponentValue>> {
+    let mut resolver = CustomPropertyResolver {
+        declared: declared
+            .iter()
+            .map(|(name, values)| (name.as_str(), values.as_slice()))
+            .collect(),
+        inherited,
+        resolved: BTreeMap::new(),
+        stack: Vec::new(),
+        cyclic: BTreeSet::new(),
+    };
+
+    let mut result = inherited.clone();
+    for (name, _) in declared {
+        match resolver.resolve(name) {
+            Some(values) => result.insert(name.clone(), values),
+            None => result.remove(name),
+        };
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::css::{cssom::ComponentValues, parser::CssParser, token::CssTokenizer};
+    use alloc::vec;
+
+    fn values(s: &str) -> Vec<ComponentValue> {
+        CssParser::new(CssTokenizer::new(s.to_string())).parse_component_values()
+    }
+
+    fn declared(pairs: &[(&str, &str)]) -> Vec<(String, Vec<ComponentValue>)> {
+        pairs
+            .iter()
+            .map(|(n, v)| (n.to_string(), values(v)))
+            .collect()
+    }
+
+    fn get(properties: &BTreeMap<String, Vec<ComponentValue>>, name: &str) -> Option<String> {
+        properties.get(name).map(|v| ComponentValues(v).to_string())
+    }
+
+    #[test]
+    fn test_substitute_var() {
+        let mut lookup = |name: &str| match name {
+            "--a" => Some(values("10px")),
+            _ => None,
+        };
+        let substituted =
+            substitute_var(&values("calc(var(--a) * 2) var(--b, 1px 2px)"), &mut lookup);
+        assert_eq!(
+            Some("calc(10px * 2) 1px 2px".to_string()),
+            substituted.map(|v| ComponentValues(&v).to_string())
+        );
+        assert_eq!(None, substitute_var(&values("var(--b)"), &mut lookup));
+        assert_eq!(None, substitute_var(&values("var(b, 1px)"), &mut lookup));
+        assert_eq!(
+            Some(vec![]),
+            substitute_var(&values("var(--b,)"), &mut lookup)
+        );
+        assert!(contains_var(&values("a (b var(--c))")));
+        assert!(!contains_var(&values("a (b c)")));
+    }
+
+    #[test]
+    fn test_resolve_custom_properties() {
+        let mut inherited = BTreeMap::new();
+        inherited.insert("--parent".to_string(), values("red"));
+        inherited.insert("--overridden".to_string(), values("1"));
+        inherited.insert("--reset".to_string(), values("2"));
+
+        let resolved = resolve_custom_properties(
+            &declared(&[
+                ("--a", "var(--b) var(--parent)"),
+                ("--b", "blue"),
+                ("--overridden", "3"),
+                ("--overridden", "4"),
+                ("--reset", "initial"),
+                ("--missing", "var(--nothing)"),
+                ("--fallback", "var(--nothing, var(--b))"),
+            ]),
+            &inherited,
+        );
+        assert_eq!(Some("blue red".to_string()), get(&resolved, "--a"));
+        assert_eq!(Some("red".to_string()), get(&resolved, "--parent"));
+        assert_eq!(Some("4".to_string()), get(&resolved, "--overridden"));
+        assert_eq!(None, get(&resolved, "--reset"));
+        assert_eq!(None, get(&resolved, "--missing"));
+        assert_eq!(Some("blue".to_string()), get(&resolved, "--fallback"));
+    }
+
+    #[test]
+    fn test_cycle() {
+        let resolved = resolve_custom_properties(
+            &declared(&[
+                ("--a", "var(--b, 1px)"),
+                ("--b", "var(--a, 2px)"),
+                ("--c", "var(--a, 3px)"),
+                ("--self", "var(--self, 4px)"),
+            ]),
+            &BTreeMap::new(),
+        );
+        assert_eq!(None, get(&resolved, "--a"));
+        assert_eq!(None, get(&resolved, "--b"));
+        assert_eq!(Some("3px".to_string()), get(&resolved, "--c"));
+        assert_eq!(None, get(&resolved, "--self"));
+    }
+}
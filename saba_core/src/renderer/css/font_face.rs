@@ -0,0 +1,195 @@
+use crate::renderer::css::{
+    computed_style::font_family_value,
+    cssom::{ComponentValue, CssRule, FontFaceRule, StyleSheet},
+    media::{parse_media_query_list, MediaEnvironment},
+    supports::supports_condition,
+    token::CssTokenKind,
+    value::non_whitespace,
+};
+use alloc::{string::String, vec::Vec};
+
+// 読み込めるフォントファイルの形式。format()がなければ取得して確かめる
+const SUPPORTED_FORMATS: [&str; 3] = ["truetype", "opentype", "collection"];
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum FontFaceSource {
+    Url { url: String, format: Option<String> },
+    Local(String),
+}
+
+// @font-faceで宣言されたWebフォント
+#[derive(Clone, Debug, PartialEq)]
+pub struct FontFace {
+    pub family: String,
+    pub sources: Vec<FontFaceSource>,
+}
+
+impl FontFace {
+    // 取得を試みるURL。srcに書かれた順に、読み込めない形式のものを除いて並べる
+    pub fn urls(&self) -> Vec<String> {
+        self.sources
+            .iter()
+            .filter_map(|source| match source {
+                FontFaceSource::Url { url, format } => {
+                    let supported = format.as_ref().map_or(true, |format| {
+                        SUPPORTED_FORMATS
+                            .iter()
+                            .any(|f| f.eq_ignore_ascii_case(format))
+                    });
+                    supported.then(|| url.clone())
+                }
+                FontFaceSource::Local(_) => None,
+            })
+            .collect()
+    }
+}
+
+fn string_argument(values: &[ComponentValue]) -> Option<String> {
+    match non_whitespace(values)[..] {
+        [ComponentValue::Token(CssTokenKind::String(s))]
+        | [ComponentValue::Token(CssTokenKind::Ident(s))] => Some(s.clone()),
+        _ => None,
+    }
+}
+
+// `url(a.ttf) format("truetype")`や`local(Name)`の並び
+fn parse_source(values: &[ComponentValue]) -> Option<FontFaceSource> {
+    let values = non_whitespace(values);
+    let (first, rest) = values.split_first()?;
+    let url = match first {
+        ComponentValue::Token(CssTokenKind::Url(url)) => url.clone(),
+        ComponentValue::Function { name, value } if name.eq_ignore_ascii_case("url") => {
+            string_argument(value)?
+        }
+        ComponentValue::Function { name, value } if name.eq_ignore_ascii_case("local") => {
+            if !rest.is_empty() {
+                return None;
+            }
+            let names = font_family_value(value)?;
+            return match &names[..] {
+                [name] => Some(FontFaceSource::Local(name.clone())),
+                _ => None,
+            };
+        }
+        _ => return None,
+    };
+    let mut format = None;
+    for value in rest {
+        match value {
+            ComponentValue::Function { name, value } if name.eq_ignore_ascii_case("format") => {
+                format = Some(string_argument(value)?);
+            }
+            // tech()などの指定は読み込みに影響しない
+            ComponentValue::Function { .. } => {}
+            _ => return None,
+        }
+    }
+    Some(FontFaceSource::Url { url, format })
+}
+
+// font-familyとsrcの両方がなければ無効になる。不正なsrcの項目は読み飛ばす
+pub fn parse_font_face(rule: &FontFaceRule) -> Option<FontFace> {
+    let mut family = None;
+    let mut sources = None;
+    for declaration in &rule.declarations {
+        match declaration.name.to_ascii_lowercase().as_str() {
+            "font-family" => {
+                family = match font_family_value(&declaration.value)?.as_slice() {
+                    [name] => Some(name.clone()),
+                    _ => None,
+                }
+            }
+            "src" => {
+                sources = Some(
+                    declaration
+                        .value
+                        .split(|v| v.is_token(&CssTokenKind::Comma))
+                        .filter_map(parse_source)
+                        .collect::<Vec<_>>(),
+                )
+            }
+            _ => {}
+        }
+    }
+    Some(FontFace {
+        family: family?,
+        sources: sources.filter(|sources| !sources.is_empty())?,
+    })
+}
+
+fn collect_rules(rules: &[CssRule], environment: &MediaEnvironment, faces: &mut Vec<FontFace>) {
+    for rule in rules {
+        match rule {
+            CssRule::FontFace(rule) => faces.extend(parse_font_face(rule)),
+            CssRule::Media(rule) => {
+                if parse_media_query_list(&rule.media).matches(environment) {
+                    collect_rules(&rule.rules, environment, faces);
+                }
+            }
+            CssRule::Supports(rule) => {
+                if supports_condition(&rule.condition) {
+                    collect_rules(&rule.rules, environment, faces);
+                }
+            }
+            CssRule::Layer(rule) => {
+                if let Some(rules) = &rule.rules {
+                    collect_rules(rules, environment, faces);
+                }
+            }
+            CssRule::Style(_) | CssRule::Import(_) | CssRule::Keyframes(_) => {}
+        }
+    }
+}
+
+// 文書の順に並べた、有効な@font-face
+pub fn collect_font_faces(
+    style_sheets: &[StyleSheet],
+    environment: &MediaEnvironment,
+) -> Vec<FontFace> {
+    let mut faces = Vec::new();
+    for style_sheet in style_sheets {
+        collect_rules(&style_sheet.rules, environment, &mut faces);
+    }
+    faces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::css::{parser::CssParser, token::CssTokenizer};
+    use alloc::{string::ToString, vec};
+
+    #[test]
+    fn test_collect_font_faces() {
+        let css = r#"
+            @font-face { font-family: "My Font"; src: local(My Font), url(a.woff2) format("woff2"), url("a.ttf") format(truetype), url(b.otf) }
+            @font-face { font-family: Missing Source }
+            @font-face { font-family: a, b; src: url(c.ttf) }
+            @media print { @font-face { font-family: Print; src: url(p.ttf) } }
+            @layer base { @font-face { font-family: Layered; src: bad, url(l.ttf) } }
+        "#;
+        let style_sheet = CssParser::new(CssTokenizer::new(css.to_string())).parse_stylesheet();
+        let faces = collect_font_faces(&[style_sheet], &MediaEnvironment::default());
+        assert_eq!(2, faces.len());
+
+        assert_eq!("My Font", faces[0].family);
+        assert_eq!(
+            FontFaceSource::Local("My Font".to_string()),
+            faces[0].sources[0]
+        );
+        assert_eq!(
+            FontFaceSource::Url {
+                url: "a.woff2".to_string(),
+                format: Some("woff2".to_string())
+            },
+            faces[0].sources[1]
+        );
+        assert_eq!(
+            vec!["a.ttf".to_string(), "b.otf".to_string()],
+            faces[0].urls()
+        );
+
+        assert_eq!("Layered", faces[1].family);
+        assert_eq!(vec!["l.ttf".to_string()], faces[1].urls());
+    }
+}
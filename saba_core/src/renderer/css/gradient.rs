@@ -0,0 +1,393 @@
+use crate::renderer::css::{
+    cssom::ComponentValue,
+    token::CssTokenKind,
+    value::{non_whitespace, parse_angle, parse_color, parse_length, Color, Length, LengthContext},
+};
+use alloc::{string::String, vec::Vec};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GradientDirection {
+    // 上向きを0度として時計回りに測った角度
+    Angle(f64),
+    // `to top right`のように角に向かう。横と縦の向きを-1, 0, 1で表す
+    Corner(i8, i8),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RadialShape {
+    Circle,
+    Ellipse,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RadialSize {
+    ClosestSide,
+    ClosestCorner,
+    FarthestSide,
+    FarthestCorner,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorStop {
+    pub color: Color,
+    // 省略された場合は前後の位置から補う
+    pub position: Option<Length>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Gradient {
+    Linear {
+        direction: GradientDirection,
+        stops: Vec<ColorStop>,
+    },
+    Radial {
+        shape: RadialShape,
+        size: RadialSize,
+        // 背景の領域に対する中心の位置
+        center: (Length, Length),
+        stops: Vec<ColorStop>,
+    },
+}
+
+impl Gradient {
+    pub fn stops(&self) -> &[ColorStop] {
+        match self {
+            Gradient::Linear { stops, .. } | Gradient::Radial { stops, .. } => stops,
+        }
+    }
+}
+
+fn keyword(value: &ComponentValue) -> Option<String> {
+    value.ident().map(|k| k.to_ascii_lowercase())
+}
+
+fn length_percentage(value: &ComponentValue, context: &LengthContext) -> Option<Length> {
+    parse_length(value, context).filter(|length| !length.is_auto())
+}
+
+// <color> <length-percentage>{0,2}。位置が2つあれば同じ色の2つの点になる
+fn parse_color_stop(
+    values: &[&ComponentValue],
+    context: &LengthContext,
+    current_color: Color,
+    stops: &mut Vec<ColorStop>,
+) -> Option<()> {
+    let (first, rest) = values.split_first()?;
+    let color = parse_color(first, current_color)?;
+    if rest.len() > 2 {
+        return None;
+    }
+    if rest.is_empty() {
+        stops.push(ColorStop {
+            color,
+            position: None,
+        });
+    }
+    for value in rest {
+        stops.push(ColorStop {
+            color,
+            position: Some(length_percentage(value, context)?),
+        });
+    }
+    Some(())
+}
+
+fn parse_direction(values: &[&ComponentValue]) -> Option<GradientDirection> {
+    if let [value] = values {
+        // 単位のない数値は0だけを角度として認める
+        return match value {
+            ComponentValue::Token(CssTokenKind::Number { value, .. }) if *value != 0.0 => None,
+            ComponentValue::Token(CssTokenKind::Ident(_)) => None,
+            _ => parse_angle(value).map(GradientDirection::Angle),
+        };
+    }
+    if values.first().and_then(|v| keyword(v)).as_deref() != Some("to") {
+        return None;
+    }
+    let mut x = 0;
+    let mut y = 0;
+    for value in &values[1..] {
+        match keyword(value)?.as_str() {
+            "left" if x == 0 => x = -1,
+            "right" if x == 0 => x = 1,
+            "top" if y == 0 => y = -1,
+            "bottom" if y == 0 => y = 1,
+            _ => return None,
+        }
+    }
+    match (x, y, values.len()) {
+        (0, -1, 2) => Some(GradientDirection::Angle(0.0)),
+        (1, 0, 2) => Some(GradientDirection::Angle(90.0)),
+        (0, 1, 2) => Some(GradientDirection::Angle(180.0)),
+        (-1, 0, 2) => Some(GradientDirection::Angle(270.0)),
+        (x, y, 3) if x != 0 && y != 0 => Some(GradientDirection::Corner(x, y)),
+        _ => None,
+    }
+}
+
+// 1つか2つの値で表した位置。キーワードは割合に直す
+pub fn parse_position(
+    values: &[&ComponentValue],
+    context: &LengthContext,
+) -> Option<(Length, Length)> {
+    let horizontal = |value: &ComponentValue| match keyword(value).as_deref() {
+        Some("left") => Some(Length::Percent(0.0)),
+        Some("center") => Some(Length::Percent(50.0)),
+        Some("right") => Some(Length::Percent(100.0)),
+        Some(_) => None,
+        None => length_percentage(value, context),
+    };
+    let vertical = |value: &ComponentValue| match keyword(value).as_deref() {
+        Some("top") => Some(Length::Percent(0.0)),
+        Some("center") => Some(Length::Percent(50.0)),
+        Some("bottom") => Some(Length::Percent(100.0)),
+        Some(_) => None,
+        None => length_percentage(value, context),
+    };
+    match values {
+        [value] => match keyword(value).as_deref() {
+            Some("top" | "bottom") => Some((Length::Percent(50.0), vertical(value)?)),
+            _ => Some((horizontal(value)?, Length::Percent(50.0))),
+        },
+        [first, second] => match (horizontal(first), vertical(second)) {
+            (Some(x), Some(y)) => Some((x, y)),
+            // `top left`のように縦が先に書かれた場合
+            _ => Some((horizontal(second)?, vertical(first)?)),
+        },
+        _ => None,
+    }
+}
+
+// [<shape> || <size>]? [at <position>]?
+fn parse_radial_shape(
+    values: &[&ComponentValue],
+    context: &LengthContext,
+) -> Option<(RadialShape, RadialSize, (Length, Length))> {
+    let mut shape = RadialShape::Ellipse;
+    let mut size = RadialSize::FarthestCorner;
+    let mut center = (Length::Percent(50.0), Length::Percent(50.0));
+    let mut i = 0;
+    while i < values.len() {
+        match keyword(values[i])?.as_str() {
+            "circle" => shape = RadialShape::Circle,
+            "ellipse" => shape = RadialShape::Ellipse,
+            "closest-side" => size = RadialSize::ClosestSide,
+            "closest-corner" => size = RadialSize::ClosestCorner,
+            "farthest-side" => size = RadialSize::FarthestSide,
+            "farthest-corner" => size = RadialSize::FarthestCorner,
+            "at" => {
+                center = parse_position(&values[i + 1..], context)?;
+                break;
+            }
+            _ => return None,
+        }
+        i += 1;
+    }
+    Some((shape, size, center))
+}
+
+// linear-gradient()とradial-gradient()。CSS Images 3 3
+pub fn parse_gradient(
+    value: &ComponentValue,
+    context: &LengthContext,
+    current_color: Color,
+) -> Option<Gradient> {
+    let (name, arguments) = match value {
+        ComponentValue::Function { name, value } => (name.to_ascii_lowercase(), value),
+        _ => return None,
+    };
+    let parts: Vec<Vec<&ComponentValue>> = arguments
+        .split(|v| v.is_token(&CssTokenKind::Comma))
+        .map(non_whitespace)
+        .collect();
+    let mut stops = Vec::new();
+    let gradient = match name.as_str() {
+        "linear-gradient" => {
+            let (direction, rest) = match parse_direction(parts.first()?) {
+                Some(direction) => (direction, &parts[1..]),
+                None => (GradientDirection::Angle(180.0), &parts[..]),
+            };
+            for part in rest {
+                parse_color_stop(part, context, current_color, &mut stops)?;
+            }
+            Gradient::Linear { direction, stops }
+        }
+        "radial-gradient" => {
+            let ((shape, size, center), rest) =
+                match parse_color_stop(parts.first()?, context, current_color, &mut Vec::new()) {
+                    Some(()) => (
+                        (
+                            RadialShape::Ellipse,
+                            RadialSize::FarthestCorner,
+                            (Length::Percent(50.0), Length::Percent(50.0)),
+                        ),
+                        &parts[..],
+                    ),
+                    None => (parse_radial_shape(&parts[0], context)?, &parts[1..]),
+                };
+            for part in rest {
+                parse_color_stop(part, context, current_color, &mut stops)?;
+            }
+            Gradient::Radial {
+                shape,
+                size,
+                center,
+                stops,
+            }
+        }
+        _ => return None,
+    };
+    if gradient.stops().len() < 2 {
+        return None;
+    }
+    Some(gradient)
+}
+
+// グラデーションの線の長さlengthに対して各点の位置を0.0から1.0の割合で決める。CSS Images 3 3.5.3
+pub fn resolve_color_stops(stops: &[ColorStop], length: f64) -> Vec<(f64, Color)> {
+    let mut positions: Vec<Option<f64>> = stops
+        .iter()
+        .map(|stop| {
+            stop.position
+                .as_ref()
+                .and_then(|p| p.resolve(length))
+                .map(|p| if length > 0.0 { p / length } else { 0.0 })
+        })
+        .collect();
+    if let Some(first) = positions.first_mut() {
+        first.get_or_insert(0.0);
+    }
+    if let Some(last) = positions.last_mut() {
+        last.get_or_insert(1.0);
+    }
+    // 前の点より手前にある点は前の点の位置に揃える
+    let mut max = f64::MIN;
+    for position in positions.iter_mut().flatten() {
+        max = max.max(*position);
+        *position = max;
+    }
+    // 位置のない点は前後の点の間に均等に並べる
+    let mut i = 0;
+    while i < positions.len() {
+        if positions[i].is_some() {
+            i += 1;
+            continue;
+        }
+        let start = i - 1;
+        let end = (i..positions.len())
+            .find(|j| positions[*j].is_some())
+            .unwrap_or(positions.len() - 1);
+        let from = positions[start].unwrap_or(0.0);
+        let to = positions[end].unwrap_or(1.0);
+        for (k, position) in positions.iter_mut().enumerate().take(end).skip(i) {
+            *position = Some(from + (to - from) * (k - start) as f64 / (end - start) as f64);
+        }
+        i = end;
+    }
+    positions
+        .into_iter()
+        .zip(stops)
+        .map(|(position, stop)| (position.unwrap_or(0.0), stop.color))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::css::{parser::CssParser, token::CssTokenizer};
+    use alloc::{string::ToString, vec};
+
+    fn gradient(css: &str) -> Option<Gradient> {
+        let values = CssParser::new(CssTokenizer::new(css.to_string())).parse_component_values();
+        let values = non_whitespace(&values);
+        parse_gradient(values[0], &LengthContext::default(), Color::BLACK)
+    }
+
+    #[test]
+    fn test_parse_gradient() {
+        assert_eq!(
+            Some(Gradient::Linear {
+                direction: GradientDirection::Angle(90.0),
+                stops: vec![
+                    ColorStop {
+                        color: Color::rgb(255, 0, 0),
+                        position: None,
+                    },
+                    ColorStop {
+                        color: Color::rgb(0, 0, 255),
+                        position: Some(Length::Percent(30.0)),
+                    },
+                    ColorStop {
+                        color: Color::rgb(0, 0, 255),
+                        position: Some(Length::Px(100.0)),
+                    },
+                ],
+            }),
+            gradient("linear-gradient(to right, red, blue 30% 100px)")
+        );
+        assert!(matches!(
+            gradient("linear-gradient(to top left, red, blue)"),
+            Some(Gradient::Linear {
+                direction: GradientDirection::Corner(-1, -1),
+                ..
+            })
+        ));
+        assert!(matches!(
+            gradient("linear-gradient(0.25turn, red, blue)"),
+            Some(Gradient::Linear {
+                direction: GradientDirection::Angle(angle),
+                ..
+            }) if angle == 90.0
+        ));
+        assert_eq!(None, gradient("linear-gradient(red)"));
+        assert_eq!(None, gradient("linear-gradient(to middle, red, blue)"));
+
+        let radial = gradient("radial-gradient(circle closest-side at top 10px, red, blue)");
+        assert_eq!(
+            Some((
+                RadialShape::Circle,
+                RadialSize::ClosestSide,
+                (Length::Px(10.0), Length::Percent(0.0))
+            )),
+            radial.map(|g| match g {
+                Gradient::Radial {
+                    shape,
+                    size,
+                    center,
+                    ..
+                } => (shape, size, center),
+                _ => unreachable!(),
+            })
+        );
+        assert!(matches!(
+            gradient("radial-gradient(red, blue)"),
+            Some(Gradient::Radial {
+                shape: RadialShape::Ellipse,
+                size: RadialSize::FarthestCorner,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_resolve_color_stops() {
+        let stop = |position: Option<Length>| ColorStop {
+            color: Color::BLACK,
+            position,
+        };
+        let stops = [
+            stop(None),
+            stop(Some(Length::Px(50.0))),
+            stop(None),
+            stop(None),
+            stop(Some(Length::Percent(20.0))),
+            stop(None),
+        ];
+        let positions: Vec<f64> = resolve_color_stops(&stops, 100.0)
+            .into_iter()
+            .map(|(p, _)| p)
+            .collect();
+        // 手前に戻る点は前の点の位置になる
+        assert_eq!(vec![0.0, 0.5, 0.5, 0.5, 0.5, 1.0], positions);
+    }
+}
@@ -0,0 +1,570 @@
+use crate::renderer::css::{
+    cssom::ComponentValue,
+    token::CssTokenKind,
+    value::{non_whitespace, parse_keyword, parse_length, Length, LengthContext},
+};
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+// トラックの大きさの最小値または最大値
+#[derive(Clone, Debug, PartialEq)]
+pub enum TrackBreadth {
+    Length(Length),
+    // frの値
+    Flex(f64),
+    MinContent,
+    MaxContent,
+    Auto,
+}
+
+impl TrackBreadth {
+    pub fn is_intrinsic(&self) -> bool {
+        matches!(
+            self,
+            TrackBreadth::MinContent | TrackBreadth::MaxContent | TrackBreadth::Auto
+        )
+    }
+}
+
+// minmax(min, max)。単独の値は最小値と最大値が同じになる
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrackSize {
+    pub min: TrackBreadth,
+    pub max: TrackBreadth,
+}
+
+impl TrackSize {
+    pub fn new(breadth: TrackBreadth) -> Self {
+        match breadth {
+            // 1frはminmax(auto, 1fr)と同じ
+            TrackBreadth::Flex(_) => Self {
+                min: TrackBreadth::Auto,
+                max: breadth,
+            },
+            breadth => Self {
+                min: breadth.clone(),
+                max: breadth,
+            },
+        }
+    }
+
+    pub fn auto() -> Self {
+        Self::new(TrackBreadth::Auto)
+    }
+
+    pub fn flex_factor(&self) -> Option<f64> {
+        match self.max {
+            TrackBreadth::Flex(fr) => Some(fr),
+            _ => None,
+        }
+    }
+
+    fn is_fixed(&self) -> bool {
+        matches!(self.min, TrackBreadth::Length(_)) || matches!(self.max, TrackBreadth::Length(_))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RepeatCount {
+    Count(usize),
+    AutoFill,
+    AutoFit,
+}
+
+impl RepeatCount {
+    pub fn is_auto(&self) -> bool {
+        matches!(self, RepeatCount::AutoFill | RepeatCount::AutoFit)
+    }
+}
+
+// grid-template-rows/columnsの要素
+#[derive(Clone, Debug, PartialEq)]
+pub enum TrackListItem {
+    // [name1 name2]
+    LineNames(Vec<String>),
+    Track(TrackSize),
+    // repeat()の中には線の名前とトラックだけが入る
+    Repeat(RepeatCount, Vec<TrackListItem>),
+}
+
+// grid-row-startなどの値
+#[derive(Clone, Debug, PartialEq)]
+pub enum GridLine {
+    Auto,
+    // <integer> <custom-ident>?
+    Line(i32, Option<String>),
+    // span <integer> <custom-ident>?
+    Span(u32, Option<String>),
+    // <custom-ident>のみ
+    Name(String),
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GridAutoFlow {
+    pub column: bool,
+    pub dense: bool,
+}
+
+// grid-template-areasで名前を付けた領域。線の番号は0から数え、終わりの線は含まない
+#[derive(Clone, Debug, PartialEq)]
+pub struct NamedArea {
+    pub name: String,
+    pub rows: (usize, usize),
+    pub columns: (usize, usize),
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GridTemplateAreas {
+    pub rows: usize,
+    pub columns: usize,
+    pub areas: Vec<NamedArea>,
+}
+
+impl GridTemplateAreas {
+    pub fn area(&self, name: &str) -> Option<&NamedArea> {
+        self.areas.iter().find(|a| a.name == name)
+    }
+}
+
+// spanやautoは線の名前に使えない
+fn custom_ident(value: &ComponentValue) -> Option<String> {
+    let name = value.ident()?;
+    let lower = name.to_ascii_lowercase();
+    if matches!(
+        lower.as_str(),
+        "span" | "auto" | "inherit" | "initial" | "unset" | "revert" | "default"
+    ) {
+        return None;
+    }
+    Some(name.to_string())
+}
+
+fn integer(value: &ComponentValue) -> Option<i32> {
+    match value {
+        ComponentValue::Token(CssTokenKind::Number {
+            value,
+            is_integer: true,
+        }) => Some(*value as i32),
+        _ => None,
+    }
+}
+
+fn parse_breadth(value: &ComponentValue, context: &LengthContext) -> Option<TrackBreadth> {
+    match value {
+        ComponentValue::Token(CssTokenKind::Dimension { value, unit, .. })
+            if unit.eq_ignore_ascii_case("fr") =>
+        {
+            Some(TrackBreadth::Flex(*value)).filter(|_| *value >= 0.0)
+        }
+        ComponentValue::Token(CssTokenKind::Ident(name)) => {
+            match name.to_ascii_lowercase().as_str() {
+                "auto" => Some(TrackBreadth::Auto),
+                "min-content" => Some(TrackBreadth::MinContent),
+                "max-content" => Some(TrackBreadth::MaxContent),
+                _ => None,
+            }
+        }
+        _ => parse_length(value, context)
+            .filter(|length| !length.is_negative())
+            .map(TrackBreadth::Length),
+    }
+}
+
+// カンマで区切られた関数の引数
+fn split_arguments(values: &[ComponentValue]) -> Vec<Vec<&ComponentValue>> {
+    let mut arguments = vec![Vec::new()];
+    for value in values {
+        if value.is_token(&CssTokenKind::Comma) {
+            arguments.push(Vec::new());
+        } else if !value.is_whitespace() {
+            if let Some(last) = arguments.last_mut() {
+                last.push(value);
+            }
+        }
+    }
+    arguments
+}
+
+pub fn parse_track_size(value: &ComponentValue, context: &LengthContext) -> Option<TrackSize> {
+    match value {
+        ComponentValue::Function { name, value } if name.eq_ignore_ascii_case("minmax") => {
+            let arguments = split_arguments(value);
+            match arguments.as_slice() {
+                [min, max] if min.len() == 1 && max.len() == 1 => {
+                    let min = parse_breadth(min[0], context)?;
+                    // 最小値にfrは使えない
+                    if matches!(min, TrackBreadth::Flex(_)) {
+                        return None;
+                    }
+                    let max = parse_breadth(max[0], context)?;
+                    Some(TrackSize { min, max })
+                }
+                _ => None,
+            }
+        }
+        _ => parse_breadth(value, context).map(TrackSize::new),
+    }
+}
+
+fn line_names(value: &ComponentValue) -> Option<Vec<String>> {
+    match value {
+        ComponentValue::SimpleBlock {
+            associated: '[',
+            value,
+        } => non_whitespace(value)
+            .into_iter()
+            .map(custom_ident)
+            .collect(),
+        _ => None,
+    }
+}
+
+fn parse_repeat(values: &[ComponentValue], context: &LengthContext) -> Option<TrackListItem> {
+    let arguments = split_arguments(values);
+    if arguments.len() != 2 || arguments[0].len() != 1 {
+        return None;
+    }
+    let count = match arguments[0][0].ident().map(|k| k.to_ascii_lowercase()) {
+        Some(k) if k == "auto-fill" => RepeatCount::AutoFill,
+        Some(k) if k == "auto-fit" => RepeatCount::AutoFit,
+        _ => RepeatCount::Count(integer(arguments[0][0]).filter(|n| *n >= 1)? as usize),
+    };
+
+    let mut items = Vec::new();
+    for value in &arguments[1] {
+        match line_names(value) {
+            Some(names) => items.push(TrackListItem::LineNames(names)),
+            None => items.push(TrackListItem::Track(parse_track_size(value, context)?)),
+        }
+    }
+    let tracks: Vec<&TrackSize> = items
+        .iter()
+        .filter_map(|item| match item {
+            TrackListItem::Track(track) => Some(track),
+            _ => None,
+        })
+        .collect();
+    if tracks.is_empty() {
+        return None;
+    }
+    // 自動の繰り返しは大きさが決まっているトラックだけを含む
+    if count.is_auto() && !tracks.iter().all(|t| t.is_fixed()) {
+        return None;
+    }
+    Some(TrackListItem::Repeat(count, items))
+}
+
+// noneは空のリストになる
+pub fn parse_track_list(
+    values: &[ComponentValue],
+    context: &LengthContext,
+) -> Option<Vec<TrackListItem>> {
+    if parse_keyword(values).as_deref() == Some("none") {
+        return Some(Vec::new());
+    }
+    let mut items = Vec::new();
+    let mut has_track = false;
+    let mut auto_repeat = false;
+    for value in non_whitespace(values) {
+        if let Some(names) = line_names(value) {
+            items.push(TrackListItem::LineNames(names));
+            continue;
+        }
+        let item = match value {
+            ComponentValue::Function { name, value } if name.eq_ignore_ascii_case("repeat") => {
+                let item = parse_repeat(value, context)?;
+                // 自動の繰り返しは1つだけ
+                if matches!(&item, TrackListItem::Repeat(count, _) if count.is_auto()) {
+                    if auto_repeat {
+                        return None;
+                    }
+                    auto_repeat = true;
+                }
+                item
+            }
+            _ => TrackListItem::Track(parse_track_size(value, context)?),
+        };
+        has_track = true;
+        items.push(item);
+    }
+    if !has_track {
+        return None;
+    }
+    Some(items)
+}
+
+// grid-auto-rows/columns
+pub fn parse_track_sizes(
+    values: &[ComponentValue],
+    context: &LengthContext,
+) -> Option<Vec<TrackSize>> {
+    let sizes: Option<Vec<TrackSize>> = non_whitespace(values)
+        .into_iter()
+        .map(|v| parse_track_size(v, context))
+        .collect();
+    sizes.filter(|s| !s.is_empty())
+}
+
+// auto | <custom-ident> | [<integer> && <custom-ident>?] | [span && [<integer> || <custom-ident>]]
+pub fn parse_grid_line(values: &[ComponentValue]) -> Option<GridLine> {
+    let items = non_whitespace(values);
+    if items.len() == 1 && parse_keyword(values).as_deref() == Some("auto") {
+        return Some(GridLine::Auto);
+    }
+    let mut span = false;
+    let mut number = None;
+    let mut name = None;
+    for item in items.iter() {
+        if item.ident().is_some_and(|k| k.eq_ignore_ascii_case("span")) && !span {
+            span = true;
+        } else if let Some(n) = integer(item).filter(|_| number.is_none()) {
+            number = Some(n);
+        } else if let Some(ident) = custom_ident(item).filter(|_| name.is_none()) {
+            name = Some(ident);
+        } else {
+            return None;
+        }
+    }
+    match (span, number, name) {
+        (true, Some(n), _) if n <= 0 => None,
+        (true, None, None) => None,
+        (true, n, name) => Some(GridLine::Span(n.unwrap_or(1) as u32, name)),
+        (false, Some(0), _) => None,
+        (false, Some(n), name) => Some(GridLine::Line(n, name)),
+        (false, None, Some(name)) => Some(GridLine::Name(name)),
+        (false, None, None) => None,
+    }
+}
+
+// [row | column] || dense
+pub fn parse_grid_auto_flow(values: &[ComponentValue]) -> Option<GridAutoFlow> {
+    let mut direction = None;
+    let mut dense = false;
+    for item in non_whitespace(values) {
+        match item.ident().map(|k| k.to_ascii_lowercase()).as_deref() {
+            Some("row") if direction.is_none() => direction = Some(false),
+            Some("column") if direction.is_none() => direction = Some(true),
+            Some("dense") if !dense => dense = true,
+            _ => return None,
+        }
+    }
+    if direction.is_none() && !dense {
+        return None;
+    }
+    Some(GridAutoFlow {
+        column: direction.unwrap_or(false),
+        dense,
+    })
+}
+
+// 各行の文字列を空白で区切ってセルにする。ピリオドの並びは名前のないセル
+pub fn parse_grid_template_areas(values: &[ComponentValue]) -> Option<GridTemplateAreas> {
+    if parse_keyword(values).as_deref() == Some("none") {
+        return Some(GridTemplateAreas::default());
+    }
+    let mut cells: Vec<Vec<Option<String>>> = Vec::new();
+    for item in non_whitespace(values) {
+        let row = match item {
+            ComponentValue::Token(CssTokenKind::String(row)) => row,
+            _ => return None,
+        };
+        let row: Vec<Option<String>> = row
+            .split_ascii_whitespace()
+            .map(|cell| {
+                if cell.chars().all(|c| c == '.') {
+                    None
+                } else {
+                    Some(cell.to_string())
+                }
+            })
+            .collect();
+        if row.is_empty() || cells.first().is_some_and(|first| first.len() != row.len()) {
+            return None;
+        }
+        cells.push(row);
+    }
+    if cells.is_empty() {
+        return None;
+    }
+
+    let rows = cells.len();
+    let columns = cells[0].len();
+    let mut areas: Vec<NamedArea> = Vec::new();
+    for (r, row) in cells.iter().enumerate() {
+        for (c, cell) in row.iter().enumerate() {
+            let name = match cell {
+                Some(name) => name,
+                None => continue,
+            };
+            match areas.iter_mut().find(|a| &a.name == name) {
+                Some(area) => {
+                    area.rows.1 = area.rows.1.max(r + 1);
+                    area.columns.0 = area.columns.0.min(c);
+                    area.columns.1 = area.columns.1.max(c + 1);
+                }
+                None => areas.push(NamedArea {
+                    name: name.clone(),
+                    rows: (r, r + 1),
+                    columns: (c, c + 1),
+                }),
+            }
+        }
+    }
+    // 名前付き領域は長方形でなければならない
+    for area in &areas {
+        let cell_count = cells
+            .iter()
+            .flatten()
+            .filter(|cell| cell.as_ref() == Some(&area.name))
+            .count();
+        let filled = (area.rows.0..area.rows.1).all(|r| {
+            (area.columns.0..area.columns.1).all(|c| cells[r][c].as_ref() == Some(&area.name))
+        });
+        let size = (area.rows.1 - area.rows.0) * (area.columns.1 - area.columns.0);
+        if !filled || cell_count != size {
+            return None;
+        }
+    }
+    Some(GridTemplateAreas {
+        rows,
+        columns,
+        areas,
+    })
+}
+
+// "a / b"のようにスラッシュで区切られた値
+pub fn split_slash(values: &[ComponentValue]) -> Vec<Vec<ComponentValue>> {
+    let mut parts = vec![Vec::new()];
+    for value in values {
+        if value.is_token(&CssTokenKind::Delim('/')) {
+            parts.push(Vec::new());
+        } else if let Some(last) = parts.last_mut() {
+            last.push(value.clone());
+        }
+    }
+    parts
+}
+
+// 省略された終わりの線は始まりが名前であれば同じ名前、そうでなければauto
+pub fn omitted_grid_line(start: &[ComponentValue]) -> Vec<ComponentValue> {
+    match parse_grid_line(start) {
+        Some(GridLine::Name(_)) => start.to_vec(),
+        _ => vec![ComponentValue::Token(CssTokenKind::Ident(
+            "auto".to_string(),
+        ))],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::css::{parser::CssParser, token::CssTokenizer};
+
+    fn values(s: &str) -> Vec<ComponentValue> {
+        CssParser::new(CssTokenizer::new(s.to_string())).parse_component_values()
+    }
+
+    fn px(value: f64) -> TrackSize {
+        TrackSize::new(TrackBreadth::Length(Length::Px(value)))
+    }
+
+    #[test]
+    fn test_parse_track_list() {
+        let context = LengthContext::default();
+        assert_eq!(
+            Some(vec![
+                TrackListItem::LineNames(vec!["a".to_string(), "b".to_string()]),
+                TrackListItem::Track(px(100.0)),
+                TrackListItem::Track(TrackSize::new(TrackBreadth::Flex(2.0))),
+                TrackListItem::Track(TrackSize {
+                    min: TrackBreadth::Length(Length::Px(32.0)),
+                    max: TrackBreadth::MaxContent,
+                }),
+                TrackListItem::LineNames(vec!["c".to_string()]),
+            ]),
+            parse_track_list(
+                &values("[a b] 100px 2fr minmax(2em, max-content) [c]"),
+                &context
+            )
+        );
+        assert_eq!(
+            Some(vec![TrackListItem::Repeat(
+                RepeatCount::AutoFill,
+                vec![
+                    TrackListItem::LineNames(vec!["x".to_string()]),
+                    TrackListItem::Track(px(50.0))
+                ]
+            )]),
+            parse_track_list(&values("repeat(auto-fill, [x] 50px)"), &context)
+        );
+        assert_eq!(
+            Some(Vec::new()),
+            parse_track_list(&values("none"), &context)
+        );
+        assert_eq!(
+            None,
+            parse_track_list(&values("minmax(1fr, 10px)"), &context)
+        );
+        assert_eq!(
+            None,
+            parse_track_list(&values("repeat(auto-fit, 1fr)"), &context)
+        );
+        assert_eq!(None, parse_track_list(&values("repeat(0, 10px)"), &context));
+        assert_eq!(None, parse_track_list(&values("[a]"), &context));
+        assert_eq!(None, parse_track_list(&values("-10px"), &context));
+    }
+
+    #[test]
+    fn test_parse_grid_line() {
+        assert_eq!(Some(GridLine::Auto), parse_grid_line(&values("auto")));
+        assert_eq!(
+            Some(GridLine::Line(-1, None)),
+            parse_grid_line(&values("-1"))
+        );
+        assert_eq!(
+            Some(GridLine::Line(2, Some("a".to_string()))),
+            parse_grid_line(&values("a 2"))
+        );
+        assert_eq!(
+            Some(GridLine::Span(3, None)),
+            parse_grid_line(&values("span 3"))
+        );
+        assert_eq!(
+            Some(GridLine::Span(1, Some("a".to_string()))),
+            parse_grid_line(&values("span a"))
+        );
+        assert_eq!(
+            Some(GridLine::Name("main".to_string())),
+            parse_grid_line(&values("main"))
+        );
+        assert_eq!(None, parse_grid_line(&values("0")));
+        assert_eq!(None, parse_grid_line(&values("span 0")));
+        assert_eq!(None, parse_grid_line(&values("span")));
+        assert_eq!(None, parse_grid_line(&values("1.5")));
+    }
+
+    #[test]
+    fn test_parse_grid_template_areas() {
+        let areas =
+            parse_grid_template_areas(&values("\"head head\" \"side main\" \". main\"")).unwrap();
+        assert_eq!((3, 2), (areas.rows, areas.columns));
+        let main = areas.area("main").unwrap();
+        assert_eq!(((1, 3), (1, 2)), (main.rows, main.columns));
+        assert_eq!((0, 2), areas.area("head").unwrap().columns);
+
+        // 長方形でない領域や列の数が揃わない行は不正
+        assert_eq!(None, parse_grid_template_areas(&values("\"a a\" \"a b\"")));
+        assert_eq!(None, parse_grid_template_areas(&values("\"a b\" \"c\"")));
+        assert_eq!(None, parse_grid_template_areas(&values("\"a b a\"")));
+        assert_eq!(
+            Some(GridAutoFlow {
+                column: true,
+                dense: true
+            }),
+            parse_grid_auto_flow(&values("dense column"))
+        );
+    }
+}
@@ -0,0 +1,669 @@
+use crate::renderer::css::{cssom::ComponentValue, token::CssTokenKind, value::non_whitespace};
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorScheme {
+    Light,
+    Dark,
+}
+
+// メディアクエリを評価するためのビューポートとユーザー設定
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MediaEnvironment {
+    pub width: f64,
+    pub height: f64,
+    // 1pxあたりのデバイスピクセル数(dppx)
+    pub resolution: f64,
+    pub color_scheme: ColorScheme,
+    pub reduced_motion: bool,
+}
+
+impl MediaEnvironment {
+    pub fn new(width: f64, height: f64) -> Self {
+        Self {
+            width,
+            height,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for MediaEnvironment {
+    fn default() -> Self {
+        Self {
+            width: 600.0,
+            height: 400.0,
+            resolution: 1.0,
+            color_scheme: ColorScheme::Light,
+            reduced_motion: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Comparison {
+    Lt,
+    Le,
+    Eq,
+    Ge,
+    Gt,
+}
+
+impl Comparison {
+    // `400px < width`を`width > 400px`に変えるための反転
+    fn flip(self) -> Self {
+        match self {
+            Comparison::Lt => Comparison::Gt,
+            Comparison::Le => Comparison::Ge,
+            Comparison::Eq => Comparison::Eq,
+            Comparison::Ge => Comparison::Le,
+            Comparison::Gt => Comparison::Lt,
+        }
+    }
+
+    fn compare(self, left: f64, right: f64) -> bool {
+        match self {
+            Comparison::Lt => left < right,
+            Comparison::Le => left <= right,
+            Comparison::Eq => left == right,
+            Comparison::Ge => left >= right,
+            Comparison::Gt => left > right,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MediaValue {
+    Number(f64),
+    // pxに変換した長さ
+    Length(f64),
+    // dppxに変換した解像度
+    Resolution(f64),
+    Ratio(f64, f64),
+    Ident(String),
+}
+
+impl MediaValue {
+    fn as_number(&self) -> Option<f64> {
+        match self {
+            MediaValue::Number(n) | MediaValue::Length(n) | MediaValue::Resolution(n) => Some(*n),
+            MediaValue::Ratio(a, b) => Some(a / b),
+            MediaValue::Ident(_) => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MediaFeature {
+    Boolean(String),
+    Plain {
+        name: String,
+        value: MediaValue,
+    },
+    // 特性名を左辺にした比較の並び
+    Range {
+        name: String,
+        comparisons: Vec<(Comparison, MediaValue)>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MediaCondition {
+    Feature(MediaFeature),
+    Not(Box<MediaCondition>),
+    And(Vec<MediaCondition>),
+    Or(Vec<MediaCondition>),
+    // 解釈できない括弧は常に不明として扱う
+    GeneralEnclosed,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MediaQuery {
+    pub negated: bool,
+    pub media_type: Option<String>,
+    pub condition: Option<MediaCondition>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MediaQueryList {
+    // パースに失敗したクエリは`not all`としてNoneになる
+    pub queries: Vec<Option<MediaQuery>>,
+}
+
+fn keyword(value: &ComponentValue) -> Option<String> {
+    value.ident().map(|k| k.to_ascii_lowercase())
+}
+
+fn parse_media_value(values: &[&ComponentValue]) -> Option<MediaValue> {
+    match values {
+        [ComponentValue::Token(CssTokenKind::Number { value, .. })] => {
+            Some(MediaValue::Number(*value))
+        }
+        [ComponentValue::Token(CssTokenKind::Dimension { value, unit, .. })] => {
+            let value = *value;
+            let unit = unit.to_ascii_lowercase();
+            let resolution = match unit.as_str() {
+                "dppx" | "x" => Some(value),
+                "dpi" => Some(value / 96.0),
+                "dpcm" => Some(value * 2.54 / 96.0),
+                _ => None,
+            };
+            if let Some(resolution) = resolution {
+                return Some(MediaValue::Resolution(resolution));
+            }
+            // メディアクエリの相対単位は初期値のフォントサイズを基準にする
+            let px = match unit.as_str() {
+                "px" => value,
+                "em" | "rem" => value * 16.0,
+                "pt" => value * 96.0 / 72.0,
+                "pc" => value * 16.0,
+                "in" => value * 96.0,
+                "cm" => value * 96.0 / 2.54,
+                "mm" => value * 96.0 / 25.4,
+                _ => return None,
+            };
+            Some(MediaValue::Length(px))
+        }
+        [ComponentValue::Token(CssTokenKind::Ident(ident))] => {
+            Some(MediaValue::Ident(ident.to_ascii_lowercase()))
+        }
+        [a, slash, b] if slash.is_token(&CssTokenKind::Delim('/')) => {
+            let a = match a {
+                ComponentValue::Token(CssTokenKind::Number { value, .. }) => *value,
+                _ => return None,
+            };
+            let b = match b {
+                ComponentValue::Token(CssTokenKind::Number { value, .. }) => *value,
+                _ => return None,
+            };
+            Some(MediaValue::Ratio(a, b))
+        }
+        _ => None,
+    }
+}
+
+// <, <=, >, >=, =を読み取り、消費したトークン数とともに返す
+fn parse_comparison(values: &[&ComponentValue]) -> Option<(Comparison, usize)> {
+    let first = match values.first()? {
+        ComponentValue::Token(CssTokenKind::Delim(ch)) => *ch,
+        _ => return None,
+    };
+    let with_equal = values
+        .get(1)
+        .is_some_and(|v| v.is_token(&CssTokenKind::Delim('=')));
+    match (first, with_equal) {
+        ('=', _) => Some((Comparison::Eq, 1)),
+        ('<', true) => Some((Comparison::Le, 2)),
+        ('<', false) => Some((Comparison::Lt, 1)),
+        ('>', true) => Some((Comparison::Ge, 2)),
+        ('>', false) => Some((Comparison::Gt, 1)),
+        _ => None,
+    }
+}
+
+fn parse_range(values: &[&ComponentValue]) -> Option<MediaFeature> {
+    // トークン列を値と比較演算子に分ける
+    let mut parts: Vec<Vec<&ComponentValue>> = Vec::new();
+    let mut comparisons = Vec::new();
+    let mut current = Vec::new();
+    let mut i = 0;
+    while i < values.len() {
+        if let Some((comparison, consumed)) = parse_comparison(&values[i..]) {
+            parts.push(core::mem::take(&mut current));
+            comparisons.push(comparison);
+            i += consumed;
+            continue;
+        }
+        current.push(values[i]);
+        i += 1;
+    }
+    parts.push(current);
+
+    match (parts.as_slice(), comparisons.as_slice()) {
+        // width > 400px または 400px < width
+        ([left, right], [comparison]) => {
+            if let [name] = left.as_slice() {
+                if let Some(name) = keyword(name) {
+                    if let Some(value) = parse_media_value(right) {
+                        return Some(MediaFeature::Range {
+                            name,
+                            comparisons: vec![(*comparison, value)],
+                        });
+                    }
+                }
+            }
+            let name = match right.as_slice() {
+                [name] => keyword(name)?,
+                _ => return None,
+            };
+            Some(MediaFeature::Range {
+                name,
+                comparisons: vec![(comparison.flip(), parse_media_value(left)?)],
+            })
+        }
+        // 400px <= width <= 800px
+        ([left, name, right], [first, second]) => {
+            let name = match name.as_slice() {
+                [name] => keyword(name)?,
+                _ => return None,
+            };
+            let ascending = matches!(first, Comparison::Lt | Comparison::Le)
+                && matches!(second, Comparison::Lt | Comparison::Le);
+            let descending = matches!(first, Comparison::Gt | Comparison::Ge)
+                && matches!(second, Comparison::Gt | Comparison::Ge);
+            if !ascending && !descending {
+                return None;
+            }
+            Some(MediaFeature::Range {
+                name,
+                comparisons: vec![
+                    (first.flip(), parse_media_value(left)?),
+                    (*second, parse_media_value(right)?),
+                ],
+            })
+        }
+        _ => None,
+    }
+}
+
+fn parse_media_feature(values: &[ComponentValue]) -> Option<MediaFeature> {
+    let values = non_whitespace(values);
+    match values.as_slice() {
+        [name] => Some(MediaFeature::Boolean(keyword(name)?)),
+        [name, colon, rest @ ..] if colon.is_token(&CssTokenKind::Colon) => {
+            let name = keyword(name)?;
+            let value = parse_media_value(rest)?;
+            // min-/max-の接頭辞は範囲の比較に変換する
+            if let Some(name) = name.strip_prefix("min-") {
+                return Some(MediaFeature::Range {
+                    name: name.to_string(),
+                    comparisons: vec![(Comparison::Ge, value)],
+                });
+            }
+            if let Some(name) = name.strip_prefix("max-") {
+                return Some(MediaFeature::Range {
+                    name: name.to_string(),
+                    comparisons: vec![(Comparison::Le, value)],
+                });
+            }
+            Some(MediaFeature::Plain { name, value })
+        }
+        _ => parse_range(&values),
+    }
+}
+
+fn parse_in_parens(value: &ComponentValue) -> Option<MediaCondition> {
+    match value {
+        ComponentValue::SimpleBlock {
+            associated: '(',
+            value,
+        } => {
+            if let Some(condition) = parse_media_condition(value) {
+                return Some(condition);
+            }
+            if let Some(feature) = parse_media_feature(value) {
+                return Some(MediaCondition::Feature(feature));
+            }
+            Some(MediaCondition::GeneralEnclosed)
+        }
+        ComponentValue::Function { .. } => Some(MediaCondition::GeneralEnclosed),
+        _ => None,
+    }
+}
+
+fn parse_condition_items(items: &[&ComponentValue], allow_or: bool) -> Option<MediaCondition> {
+    let first = items.first()?;
+    if keyword(first).as_deref() == Some("not") {
+        if items.len() != 2 {
+            return None;
+        }
+        return Some(MediaCondition::Not(Box::new(parse_in_parens(items[1])?)));
+    }
+
+    let mut conditions = vec![parse_in_parens(first)?];
+    let mut operator: Option<String> = None;
+    for chunk in items[1..].chunks(2) {
+        let (op, operand) = match chunk {
+            [op, operand] => (keyword(op)?, parse_in_parens(operand)?),
+            _ => return None,
+        };
+        if op != "and" && !(allow_or && op == "or") {
+            return None;
+        }
+        // andとorを括弧なしで混在させることはできない
+        if operator.as_ref().is_some_and(|o| *o != op) {
+            return None;
+        }
+        operator = Some(op);
+        conditions.push(operand);
+    }
+
+    match operator.as_deref() {
+        None => conditions.pop(),
+        Some("and") => Some(MediaCondition::And(conditions)),
+        _ => Some(MediaCondition::Or(conditions)),
+    }
+}
+
+fn parse_media_condition(values: &[ComponentValue]) -> Option<MediaCondition> {
+    parse_condition_items(&non_whitespace(values), true)
+}
+
+fn parse_media_query(values: &[ComponentValue]) -> Option<MediaQuery> {
+    let items = non_whitespace(values);
+    if items.is_empty() {
+        return None;
+    }
+
+    // 括弧で始まる場合は<media-condition>のみ
+    let starts_with_condition = match keyword(items[0]).as_deref() {
+        None => true,
+        Some("not") => items.get(1).is_some_and(|v| keyword(v).is_none()),
+        _ => false,
+    };
+    if starts_with_condition {
+        return Some(MediaQuery {
+            negated: false,
+            media_type: None,
+            condition: Some(parse_condition_items(&items, true)?),
+        });
+    }
+
+    let mut i = 0;
+    let mut negated = false;
+    match keyword(items[0]).as_deref() {
+        Some("not") => {
+            negated = true;
+            i += 1;
+        }
+        Some("only") => i += 1,
+        _ => {}
+    }
+
+    let media_type = keyword(items.get(i)?)?;
+    if matches!(media_type.as_str(), "not" | "only" | "and" | "or" | "layer") {
+        return None;
+    }
+    i += 1;
+
+    if i == items.len() {
+        return Some(MediaQuery {
+            negated,
+            media_type: Some(media_type),
+            condition: None,
+        });
+    }
+
+    // メディアタイプの後は`and`で始まる条件が続き、orは使えない
+    if keyword(items[i]).as_deref() != Some("and") {
+        return None;
+    }
+    let condition = parse_condition_items(&items[i + 1..], false)?;
+    Some(MediaQuery {
+        negated,
+        media_type: Some(media_type),
+        condition: Some(condition),
+    })
+}
+
+pub fn parse_media_query_list(values: &[ComponentValue]) -> MediaQueryList {
+    if values.iter().all(|v| v.is_whitespace()) {
+        return MediaQueryList::default();
+    }
+    let queries = values
+        .split(|v| v.is_token(&CssTokenKind::Comma))
+        .map(parse_media_query)
+        .collect();
+    MediaQueryList { queries }
+}
+
+fn evaluate_boolean(name: &str, environment: &MediaEnvironment) -> Option<bool> {
+    let result = match name {
+        "width" | "device-width" => environment.width != 0.0,
+        "height" | "device-height" => environment.height != 0.0,
+        "orientation" | "aspect-ratio" | "resolution" | "color" | "hover" | "pointer" => true,
+        "prefers-color-scheme" => true,
+        "prefers-reduced-motion" => environment.reduced_motion,
+        "monochrome" | "grid" => false,
+        _ => return None,
+    };
+    Some(result)
+}
+
+fn feature_value(name: &str, environment: &MediaEnvironment) -> Option<MediaValue> {
+    let value = match name {
+        "width" | "device-width" => MediaValue::Length(environment.width),
+        "height" | "device-height" => MediaValue::Length(environment.height),
+        "aspect-ratio" | "device-aspect-ratio" => {
+            MediaValue::Ratio(environment.width, environment.height)
+        }
+        "resolution" => MediaValue::Resolution(environment.resolution),
+        "color" => MediaValue::Number(8.0),
+        "monochrome" => MediaValue::Number(0.0),
+        "orientation" => {
+            if environment.height >= environment.width {
+                MediaValue::Ident("portrait".to_string())
+            } else {
+                MediaValue::Ident("landscape".to_string())
+            }
+        }
+        "prefers-color-scheme" => match environment.color_scheme {
+            ColorScheme::Light => MediaValue::Ident("light".to_string()),
+            ColorScheme::Dark => MediaValue::Ident("dark".to_string()),
+        },
+        "prefers-reduced-motion" => {
+            if environment.reduced_motion {
+                MediaValue::Ident("reduce".to_string())
+            } else {
+                MediaValue::Ident("no-preference".to_string())
+            }
+        }
+        "hover" => MediaValue::Ident("hover".to_string()),
+        "pointer" => MediaValue::Ident("fine".to_string()),
+        _ => return None,
+    };
+    Some(value)
+}
+
+fn compare_values(
+    actual: &MediaValue,
+    comparison: Comparison,
+    expected: &MediaValue,
+) -> Option<bool> {
+    match (actual, expected) {
+        (MediaValue::Ident(a), MediaValue::Ident(b)) => match comparison {
+            Comparison::Eq => Some(a == b),
+            _ => None,
+        },
+        (MediaValue::Length(_), MediaValue::Length(_))
+        | (MediaValue::Resolution(_), MediaValue::Resolution(_))
+        | (MediaValue::Number(_), MediaValue::Number(_))
+        | (MediaValue::Ratio(..), MediaValue::Ratio(..) | MediaValue::Number(_)) => {
+            Some(comparison.compare(actual.as_number()?, expected.as_number()?))
+        }
+        // 0は単位なしでも長さとして扱う
+        (MediaValue::Length(a), MediaValue::Number(b)) if *b == 0.0 => {
+            Some(comparison.compare(*a, 0.0))
+        }
+        _ => None,
+    }
+}
+
+// 不明な特性を含む場合はNoneを返す
+fn evaluate_feature(feature: &MediaFeature, environment: &MediaEnvironment) -> Option<bool> {
+    match feature {
+        MediaFeature::Boolean(name) => evaluate_boolean(name, environment),
+        MediaFeature::Plain { name, value } => {
+            compare_values(&feature_value(name, environment)?, Comparison::Eq, value)
+        }
+        MediaFeature::Range { name, comparisons } => {
+            let actual = feature_value(name, environment)?;
+            if matches!(actual, MediaValue::Ident(_)) {
+                return None;
+            }
+            let mut result = true;
+            for (comparison, value) in comparisons {
+                result &= compare_values(&actual, *comparison, value)?;
+            }
+            Some(result)
+        }
+    }
+}
+
+// 3値論理で評価する。Noneは不明
+fn evaluate_condition(condition: &MediaCondition, environment: &MediaEnvironment) -> Option<bool> {
+    match condition {
+        MediaCondition::Feature(feature) => evaluate_feature(feature, environment),
+        MediaCondition::Not(condition) => evaluate_condition(condition, environment).map(|r| !r),
+        MediaCondition::And(conditions) => {
+            let mut result = Some(true);
+            for condition in conditions {
+                match evaluate_condition(condition, environment) {
+                    Some(false) => return Some(false),
+                    Some(true) => {}
+                    None => result = None,
+                }
+            }
+            result
+        }
+        MediaCondition::Or(conditions) => {
+            let mut result = Some(false);
+            for condition in conditions {
+                match evaluate_condition(condition, environment) {
+                    Some(true) => return Some(true),
+                    Some(false) => {}
+                    None => result = None,
+                }
+            }
+            result
+        }
+        MediaCondition::GeneralEnclosed => None,
+    }
+}
+
+impl MediaQuery {
+    pub fn matches(&self, environment: &MediaEnvironment) -> bool {
+        let type_matches = matches!(
+            self.media_type.as_deref(),
+            None | Some("all") | Some("screen")
+        );
+        let condition = match &self.condition {
+            Some(condition) => evaluate_condition(condition, environment),
+            None => Some(true),
+        };
+        // 不明な結果はnotの有無にかかわらず一致しない
+        match condition {
+            Some(condition) => (type_matches && condition) != self.negated,
+            None => false,
+        }
+    }
+}
+
+impl MediaQueryList {
+    // 空のリストはすべてのメディアに一致する
+    pub fn matches(&self, environment: &MediaEnvironment) -> bool {
+        if self.queries.is_empty() {
+            return true;
+        }
+        self.queries
+            .iter()
+            .any(|query| query.as_ref().is_some_and(|q| q.matches(environment)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::css::{parser::CssParser, token::CssTokenizer};
+
+    fn matches(media: &str, environment: &MediaEnvironment) -> bool {
+        let values = CssParser::new(CssTokenizer::new(media.to_string())).parse_component_values();
+        parse_media_query_list(&values).matches(environment)
+    }
+
+    #[test]
+    fn test_media_types() {
+        let environment = MediaEnvironment::default();
+        assert!(matches("", &environment));
+        assert!(matches("all", &environment));
+        assert!(matches("only screen", &environment));
+        assert!(!matches("print", &environment));
+        assert!(matches("not print", &environment));
+        assert!(matches("print, screen", &environment));
+        assert!(!matches("screen and", &environment));
+        assert!(!matches("not", &environment));
+    }
+
+    #[test]
+    fn test_range_features() {
+        let environment = MediaEnvironment::new(600.0, 400.0);
+        assert!(matches("(min-width: 600px)", &environment));
+        assert!(!matches("(max-width: 599px)", &environment));
+        assert!(matches("(width > 30em)", &environment));
+        assert!(matches("(400px <= width <= 800px)", &environment));
+        assert!(!matches("(800px >= width > 600px)", &environment));
+        assert!(matches("(height = 400px)", &environment));
+        assert!(matches("(aspect-ratio: 3/2)", &environment));
+        assert!(matches("(min-aspect-ratio: 1/1)", &environment));
+        assert!(matches("(width)", &environment));
+        assert!(!matches("(400px < width < 500px < height)", &environment));
+    }
+
+    #[test]
+    fn test_discrete_features() {
+        let mut environment = MediaEnvironment::new(400.0, 800.0);
+        assert!(matches("(orientation: portrait)", &environment));
+        assert!(matches("(prefers-color-scheme: light)", &environment));
+        assert!(matches(
+            "(prefers-reduced-motion: no-preference)",
+            &environment
+        ));
+        assert!(!matches("(prefers-reduced-motion)", &environment));
+        assert!(matches("(resolution: 96dpi)", &environment));
+
+        environment.color_scheme = ColorScheme::Dark;
+        environment.reduced_motion = true;
+        environment.resolution = 2.0;
+        assert!(matches("(prefers-color-scheme: dark)", &environment));
+        assert!(matches("(prefers-reduced-motion: reduce)", &environment));
+        assert!(matches("(min-resolution: 2x)", &environment));
+        assert!(!matches("(resolution < 1.5dppx)", &environment));
+    }
+
+    #[test]
+    fn test_conditions() {
+        let environment = MediaEnvironment::new(600.0, 400.0);
+        assert!(matches(
+            "screen and (min-width: 500px) and (orientation: landscape)",
+            &environment
+        ));
+        assert!(matches(
+            "(max-width: 100px) or (min-width: 500px)",
+            &environment
+        ));
+        assert!(!matches(
+            "screen and (max-width: 100px) or (min-width: 500px)",
+            &environment
+        ));
+        assert!(!matches(
+            "(max-width: 100px) and (min-width: 500px) or (width)",
+            &environment
+        ));
+        assert!(matches("not (max-width: 100px)", &environment));
+        assert!(matches("not screen and (max-width: 100px)", &environment));
+        assert!(matches(
+            "((width > 500px) and (height < 500px)) or (color)",
+            &environment
+        ));
+        // 不明な特性は否定しても一致しない
+        assert!(!matches("(unknown-feature)", &environment));
+        assert!(!matches("not (unknown-feature)", &environment));
+        assert!(matches("(unknown-feature) or (width)", &environment));
+        assert!(!matches("(orientation > portrait)", &environment));
+    }
+}
@@ -0,0 +1,14 @@
+pub mod cascade;
+pub mod computed_style;
+pub mod cssom;
+pub mod custom_property;
+pub mod font_face;
+pub mod gradient;
+pub mod grid;
+pub mod media;
+pub mod parser;
+pub mod selector;
+pub mod supports;
+pub mod token;
+pub mod ua;
+pub mod value;
@@ -0,0 +1,734 @@
+use crate::renderer::css::{
+    cssom::{
+        ComponentValue, CssRule, Declaration, FontFaceRule, ImportRule, Keyframe, KeyframesRule,
+        LayerRule, MediaRule, StyleRule, StyleSheet, SupportsRule,
+    },
+    selector::parse_selector_list,
+    token::{CssToken, CssTokenKind, CssTokenizer},
+};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+enum RawRule {
+    At {
+        name: String,
+        prelude: Vec<ComponentValue>,
+        block: Option<Vec<ComponentValue>>,
+    },
+    Qualified {
+        prelude: Vec<ComponentValue>,
+        block: Vec<ComponentValue>,
+    },
+}
+
+pub struct CssParser {
+    tokens: Vec<CssToken>,
+    pos: usize,
+}
+
+impl CssParser {
+    pub fn new(tokenizer: CssTokenizer) -> Self {
+        Self {
+            tokens: tokenizer.collect(),
+            pos: 0,
+        }
+    }
+
+    pub fn parse_stylesheet(&mut self) -> StyleSheet {
+        let values = self.consume_component_values();
+        StyleSheet {
+            rules: parse_rule_list(&values, true),
+        }
+    }
+
+    // style属性のようなブロックを持たない宣言のリストを解析する
+    pub fn parse_declaration_list(&mut self) -> Vec<Declaration> {
+        let values = self.consume_component_values();
+        parse_declaration_list(&values)
+    }
+
+    pub fn parse_component_values(&mut self) -> Vec<ComponentValue> {
+        self.consume_component_values()
+    }
+
+    fn consume_component_values(&mut self) -> Vec<ComponentValue> {
+        let mut values = Vec::new();
+        while let Some(value) = self.consume_component_value() {
+            values.push(value);
+        }
+        values
+    }
+
+    fn consume_component_value(&mut self) -> Option<ComponentValue> {
+        let token = self.tokens.get(self.pos)?.kind.clone();
+        self.pos += 1;
+
+        let value = match token {
+            CssTokenKind::OpenCurly => self.consume_simple_block('{', CssTokenKind::CloseCurly),
+            CssTokenKind::OpenSquare => self.consume_simple_block('[', CssTokenKind::CloseSquare),
+            CssTokenKind::OpenParen => self.consume_simple_block('(', CssTokenKind::CloseParen),
+            CssTokenKind::Function(name) => ComponentValue::Function {
+                name,
+                value: self.consume_until(&CssTokenKind::CloseParen),
+            },
+            token => ComponentValue::Token(token),
+        };
+
+        Some(value)
+    }
+
+    fn consume_simple_block(&mut self, associated: char, ending: CssTokenKind) -> ComponentValue {
+        ComponentValue::SimpleBlock {
+            associated,
+            value: self.consume_until(&ending),
+        }
+    }
+
+    fn consume_until(&mut self, ending: &CssTokenKind) -> Vec<ComponentValue> {
+        let mut values = Vec::new();
+        loop {
+            match self.tokens.get(self.pos) {
+                // 閉じられていないブロックはEOFで閉じる
+                None => return values,
+                Some(token) if &token.kind == ending => {
+                    self.pos += 1;
+                    return values;
+                }
+                Some(_) => {
+                    if let Some(value) = self.consume_component_value() {
+                        values.push(value);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn is_curly_block(value: &ComponentValue) -> bool {
+    matches!(
+        value,
+        ComponentValue::SimpleBlock {
+            associated: '{',
+            value: _
+        }
+    )
+}
+
+fn block_contents(value: ComponentValue) -> Vec<ComponentValue> {
+    match value {
+        ComponentValue::SimpleBlock {
+            associated: _,
+            value,
+        } => value,
+        _ => Vec::new(),
+    }
+}
+
+fn consume_raw_rules(values: &[ComponentValue], top_level: bool) -> Vec<RawRule> {
+    let mut rules = Vec::new();
+    let mut i = 0;
+
+    while i < values.len() {
+        match &values[i] {
+            ComponentValue::Token(CssTokenKind::Whitespace) => i += 1,
+            ComponentValue::Token(CssTokenKind::Cdo | CssTokenKind::Cdc) if top_level => i += 1,
+            ComponentValue::Token(CssTokenKind::AtKeyword(name)) => {
+                i += 1;
+                let mut prelude = Vec::new();
+                let mut block = None;
+                while i < values.len() {
+                    let value = &values[i];
+                    i += 1;
+                    if value.is_token(&CssTokenKind::Semicolon) {
+                        break;
+                    }
+                    if is_curly_block(value) {
+                        block = Some(block_contents(value.clone()));
+                        break;
+                    }
+                    prelude.push(value.clone());
+                }
+                rules.push(RawRule::At {
+                    name: name.to_ascii_lowercase(),
+                    prelude,
+                    block,
+                });
+            }
+            _ => {
+                let mut prelude = Vec::new();
+                let mut block = None;
+                while i < values.len() {
+                    let value = &values[i];
+                    i += 1;
+                    if is_curly_block(value) {
+                        block = Some(block_contents(value.clone()));
+                        break;
+                    }
+                    prelude.push(value.clone());
+                }
+                // ブロックのないqualified ruleは不正なので捨てる
+                if let Some(block) = block {
+                    rules.push(RawRule::Qualified { prelude, block });
+                }
+            }
+        }
+    }
+
+    rules
+}
+
+fn trim_whitespace(values: &[ComponentValue]) -> Vec<ComponentValue> {
+    let start = values
+        .iter()
+        .position(|v| !v.is_whitespace())
+        .unwrap_or(values.len());
+    let end = values
+        .iter()
+        .rposition(|v| !v.is_whitespace())
+        .map(|i| i + 1)
+        .unwrap_or(start);
+    values[start..end].to_vec()
+}
+
+fn parse_declaration(values: &[ComponentValue]) -> Option<Declaration> {
+    let mut iter = values.iter().filter(|v| !v.is_whitespace());
+    let name = iter.next()?.ident()?.to_string();
+
+    let colon = values
+        .iter()
+        .position(|v| v.is_token(&CssTokenKind::Colon))?;
+    // 名前とコロンの間には空白しか置けない
+    if values[1..colon].iter().any(|v| !v.is_whitespace()) {
+        return None;
+    }
+
+    let mut value = trim_whitespace(&values[colon + 1..]);
+    let mut important = false;
+
+    let len = value.len();
+    if len >= 2 {
+        let last = value[len - 1].ident();
+        let mut bang = len - 2;
+        while bang > 0 && value[bang].is_whitespace() {
+            bang -= 1;
+        }
+        if last.is_some_and(|l| l.eq_ignore_ascii_case("important"))
+            && value[bang].is_token(&CssTokenKind::Delim('!'))
+        {
+            important = true;
+            value = trim_whitespace(&value[..bang]);
+        }
+    }
+
+    // カスタムプロパティ以外は大文字小文字を区別しない
+    let name = if name.starts_with("--") {
+        name
+    } else {
+        name.to_ascii_lowercase()
+    };
+
+    if value.is_empty() && !name.starts_with("--") {
+        return None;
+    }
+
+    Some(Declaration::new(name, value, important))
+}
+
+pub fn parse_declaration_list(values: &[ComponentValue]) -> Vec<Declaration> {
+    let mut declarations = Vec::new();
+    let mut i = 0;
+
+    while i < values.len() {
+        match &values[i] {
+            ComponentValue::Token(CssTokenKind::Whitespace | CssTokenKind::Semicolon) => i += 1,
+            ComponentValue::Token(CssTokenKind::AtKeyword(_)) => {
+                // ネストされたat-ruleはサポートしないので読み飛ばす
+                i += 1;
+                while i < values.len() {
+                    let value = &values[i];
+                    i += 1;
+                    if value.is_token(&CssTokenKind::Semicolon) || is_curly_block(value) {
+                        break;
+                    }
+                }
+            }
+            _ => {
+                let start = i;
+                while i < values.len() && !values[i].is_token(&CssTokenKind::Semicolon) {
+                    i += 1;
+                }
+                // 不正な宣言は次のセミコロンまで捨てる
+                if let Some(declaration) = parse_declaration(&values[start..i]) {
+                    declarations.push(declaration);
+                }
+            }
+        }
+    }
+
+    declarations
+}
+
+fn parse_keyframe_selector(values: &[ComponentValue]) -> Option<Vec<f64>> {
+    let mut offsets = Vec::new();
+    for part in values.split(|v| v.is_token(&CssTokenKind::Comma)) {
+        let part = trim_whitespace(part);
+        if part.len() != 1 {
+            return None;
+        }
+        let offset = match &part[0] {
+            ComponentValue::Token(CssTokenKind::Ident(name))
+                if name.eq_ignore_ascii_case("from") =>
+            {
+                0.0
+            }
+            ComponentValue::Token(CssTokenKind::Ident(name)) if name.eq_ignore_ascii_case("to") => {
+                1.0
+            }
+            ComponentValue::Token(CssTokenKind::Percentage(value))
+                if (0.0..=100.0).contains(value) =>
+            {
+                value / 100.0
+            }
+            _ => return None,
+        };
+        offsets.push(offset);
+    }
+    Some(offsets)
+}
+
+fn parse_keyframes(name: String, block: &[ComponentValue]) -> KeyframesRule {
+    let keyframes = consume_raw_rules(block, false)
+        .into_iter()
+        .filter_map(|rule| match rule {
+            RawRule::Qualified { prelude, block } => Some(Keyframe {
+                offsets: parse_keyframe_selector(&prelude)?,
+                declarations: parse_declaration_list(&block),
+            }),
+            RawRule::At { .. } => None,
+        })
+        .collect();
+
+    KeyframesRule { name, keyframes }
+}
+
+fn parse_layer_names(prelude: &[ComponentValue]) -> Option<Vec<String>> {
+    let mut names = Vec::new();
+    let prelude = trim_whitespace(prelude);
+    if prelude.is_empty() {
+        return Some(names);
+    }
+
+    for part in prelude.split(|v| v.is_token(&CssTokenKind::Comma)) {
+        let mut name = String::new();
+        for value in trim_whitespace(part) {
+            match value {
+                ComponentValue::Token(CssTokenKind::Ident(ident)) => name.push_str(&ident),
+                ComponentValue::Token(CssTokenKind::Delim('.')) => name.push('.'),
+                _ => return None,
+            }
+        }
+        if name.is_empty() {
+            return None;
+        }
+        names.push(name);
+    }
+
+    Some(names)
+}
+
+fn parse_import(prelude: &[ComponentValue]) -> Option<ImportRule> {
+    let prelude = trim_whitespace(prelude);
+    let mut iter = prelude.iter().peekable();
+
+    let url = match iter.next()? {
+        ComponentValue::Token(CssTokenKind::Url(url))
+        | ComponentValue::Token(CssTokenKind::String(url)) => url.clone(),
+        ComponentValue::Function { name, value } if name.eq_ignore_ascii_case("url") => {
+            match trim_whitespace(value).first()? {
+                ComponentValue::Token(CssTokenKind::String(url)) => url.clone(),
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+
+    let mut layer = None;
+    let mut supports = None;
+    let rest: Vec<ComponentValue> = iter.cloned().collect();
+    let mut rest = trim_whitespace(&rest);
+
+    if let Some(first) = rest.first() {
+        match first {
+            ComponentValue::Token(CssTokenKind::Ident(name))
+                if name.eq_ignore_ascii_case("layer") =>
+            {
+                layer = Some(String::new());
+                rest = trim_whitespace(&rest[1..]);
+            }
+            ComponentValue::Function { name, value } if name.eq_ignore_ascii_case("layer") => {
+                layer = Some(parse_layer_names(value)?.join(", "));
+                rest = trim_whitespace(&rest[1..]);
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(ComponentValue::Function { name, value }) = rest.first() {
+        if name.eq_ignore_ascii_case("supports") {
+            supports = Some(trim_whitespace(value));
+            rest = trim_whitespace(&rest[1..]);
+        }
+    }
+
+    Some(ImportRule {
+        url,
+        layer,
+        supports,
+        media: rest,
+    })
+}
+
+fn parse_at_rule(
+    name: String,
+    prelude: Vec<ComponentValue>,
+    block: Option<Vec<ComponentValue>>,
+    allow_import: bool,
+) -> Option<CssRule> {
+    let rule = match name.as_str() {
+        "media" => CssRule::Media(MediaRule {
+            media: trim_whitespace(&prelude),
+            rules: parse_rule_list(&block?, false),
+        }),
+        "supports" => CssRule::Supports(SupportsRule {
+            condition: trim_whitespace(&prelude),
+            rules: parse_rule_list(&block?, false),
+        }),
+        "font-face" => {
+            if !trim_whitespace(&prelude).is_empty() {
+                return None;
+            }
+            CssRule::FontFace(FontFaceRule {
+                declarations: parse_declaration_list(&block?),
+            })
+        }
+        "keyframes" | "-webkit-keyframes" => {
+            let prelude = trim_whitespace(&prelude);
+            if prelude.len() != 1 {
+                return None;
+            }
+            let name = match &prelude[0] {
+                ComponentValue::Token(CssTokenKind::Ident(name))
+                | ComponentValue::Token(CssTokenKind::String(name)) => name.clone(),
+                _ => return None,
+            };
+            CssRule::Keyframes(parse_keyframes(name, &block?))
+        }
+        "layer" => {
+            let names = parse_layer_names(&prelude)?;
+            match block {
+                Some(block) => {
+                    // ブロック形式の@layerは名前を1つまでしか持てない
+                    if names.len() > 1 {
+                        return None;
+                    }
+                    CssRule::Layer(LayerRule {
+                        names,
+                        rules: Some(parse_rule_list(&block, false)),
+                    })
+                }
+                None => {
+                    if names.is_empty() {
+                        return None;
+                    }
+                    CssRule::Layer(LayerRule { names, rules: None })
+                }
+            }
+        }
+        "import" => {
+            // @importはスタイルシートの先頭でのみ有効
+            if !allow_import || block.is_some() {
+                return None;
+            }
+            CssRule::Import(parse_import(&prelude)?)
+        }
+        _ => return None,
+    };
+
+    Some(rule)
+}
+
+pub fn parse_rule_list(values: &[ComponentValue], top_level: bool) -> Vec<CssRule> {
+    let mut rules = Vec::new();
+    // @importより前に置けるのは@layer文のみ
+    let mut allow_import = top_level;
+
+    for raw in consume_raw_rules(values, top_level) {
+        match raw {
+            RawRule::At {
+                name,
+                prelude,
+                block,
+            } => {
+                let keeps_import_allowed = name == "import" || (name == "layer" && block.is_none());
+                if let Some(rule) = parse_at_rule(name, prelude, block, allow_import) {
+                    rules.push(rule);
+                }
+                allow_import &= keeps_import_allowed;
+            }
+            RawRule::Qualified { prelude, block } => {
+                allow_import = false;
+                let Some(selectors) = parse_selector_list(&trim_whitespace(&prelude)) else {
+                    // 不正なセレクタを持つルールは丸ごと捨てる
+                    continue;
+                };
+                rules.push(CssRule::Style(StyleRule {
+                    selectors,
+                    declarations: parse_declaration_list(&block),
+                }));
+            }
+        }
+    }
+
+    rules
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::css::selector::{
+        AttributeOperator, AttributeSelector, Combinator, CompoundSelector, PseudoClass,
+        SimpleSelector,
+    };
+    use alloc::{format, string::ToString, vec};
+
+    fn parse(css: &str) -> StyleSheet {
+        CssParser::new(CssTokenizer::new(css.to_string())).parse_stylesheet()
+    }
+
+    fn style_rule(rule: &CssRule) -> &StyleRule {
+        match rule {
+            CssRule::Style(rule) => rule,
+            _ => panic!("expected a style rule but got {:?}", rule),
+        }
+    }
+
+    fn ident(s: &str) -> ComponentValue {
+        ComponentValue::Token(CssTokenKind::Ident(s.to_string()))
+    }
+
+    #[test]
+    fn test_empty() {
+        assert_eq!(StyleSheet::new(), parse(""));
+        assert_eq!(StyleSheet::new(), parse("  <!-- -->  "));
+    }
+
+    #[test]
+    fn test_style_rule() {
+        let style_sheet = parse("p { color: red; margin : 0 auto !IMPORTANT }");
+        assert_eq!(1, style_sheet.rules.len());
+
+        let rule = style_rule(&style_sheet.rules[0]);
+        assert_eq!(
+            vec![CompoundSelector {
+                simple_selectors: vec![SimpleSelector::Type("p".to_string())]
+            }],
+            rule.selectors.selectors[0].compounds
+        );
+        assert_eq!(
+            vec![
+                Declaration::new("color".to_string(), vec![ident("red")], false),
+                Declaration::new(
+                    "margin".to_string(),
+                    vec![
+                        ComponentValue::Token(CssTokenKind::Number {
+                            value: 0.0,
+                            is_integer: true
+                        }),
+                        ComponentValue::Token(CssTokenKind::Whitespace),
+                        ident("auto"),
+                    ],
+                    true
+                ),
+            ],
+            rule.declarations
+        );
+    }
+
+    #[test]
+    fn test_selectors() {
+        let style_sheet = parse(
+            "h1.title#main > a[href^='http' i]:hover, ul li + li ~ p::before, :not(.a, .b) {}",
+        );
+        let selectors = &style_rule(&style_sheet.rules[0]).selectors.selectors;
+        assert_eq!(3, selectors.len());
+
+        assert_eq!(vec![Combinator::Child], selectors[0].combinators);
+        assert_eq!(
+            vec![
+                SimpleSelector::Type("h1".to_string()),
+                SimpleSelector::Class("title".to_string()),
+                SimpleSelector::Id("main".to_string()),
+            ],
+            selectors[0].compounds[0].simple_selectors
+        );
+        assert_eq!(
+            vec![
+                SimpleSelector::Type("a".to_string()),
+                SimpleSelector::Attribute(AttributeSelector {
+                    name: "href".to_string(),
+                    operator: AttributeOperator::Prefix,
+                    value: "http".to_string(),
+                    case_insensitive: true,
+                }),
+                SimpleSelector::PseudoClass(PseudoClass::Named("hover".to_string())),
+            ],
+            selectors[0].compounds[1].simple_selectors
+        );
+
+        assert_eq!(
+            vec![
+                Combinator::Descendant,
+                Combinator::NextSibling,
+                Combinator::SubsequentSibling
+            ],
+            selectors[1].combinators
+        );
+        assert_eq!(
+            Some(&SimpleSelector::PseudoElement("before".to_string())),
+            selectors[1].compounds[3].simple_selectors.last()
+        );
+
+        match &selectors[2].compounds[0].simple_selectors[0] {
+            SimpleSelector::PseudoClass(PseudoClass::Selector { name, selectors }) => {
+                assert_eq!("not", name);
+                assert_eq!(2, selectors.selectors.len());
+            }
+            s => panic!("unexpected selector {:?}", s),
+        }
+    }
+
+    #[test]
+    fn test_invalid_selector_drops_rule() {
+        let style_sheet = parse("p > { color: red } a, ..b { color: blue } h1 { color: green }");
+        assert_eq!(1, style_sheet.rules.len());
+        assert_eq!("h1 { color: green; }", format!("{}", style_sheet.rules[0]));
+    }
+
+    #[test]
+    fn test_declaration_error_recovery() {
+        let style_sheet = parse("p { color red; 12: x; @foo bar; background: blue; ;; width: }");
+        let rule = style_rule(&style_sheet.rules[0]);
+        assert_eq!(1, rule.declarations.len());
+        assert_eq!("background", rule.declarations[0].name);
+    }
+
+    #[test]
+    fn test_unclosed_block() {
+        let style_sheet = parse("p { color: red");
+        let rule = style_rule(&style_sheet.rules[0]);
+        assert_eq!("color", rule.declarations[0].name);
+    }
+
+    #[test]
+    fn test_custom_property() {
+        let style_sheet = parse("p { --Main-Color: { a; b } ; --empty:; }");
+        let rule = style_rule(&style_sheet.rules[0]);
+        assert_eq!(2, rule.declarations.len());
+        assert_eq!("--Main-Color", rule.declarations[0].name);
+        assert!(rule.declarations[0].is_custom_property());
+        assert!(rule.declarations[1].value.is_empty());
+    }
+
+    #[test]
+    fn test_at_rules() {
+        let style_sheet = parse(
+            "@layer base, components;
+            @import url(\"a.css\") layer(base) supports(display: grid) screen;
+            @media screen and (min-width: 400px) { p { color: red } }
+            @supports (display: flex) { div { display: flex } }
+            @font-face { font-family: Foo; src: url(foo.ttf) }
+            @keyframes spin { from { opacity: 0 } 50%, to { opacity: 1 } }
+            @layer base { h1 { color: blue } }
+            @unknown { p { color: green } }
+            @import \"late.css\";",
+        );
+
+        let rules = &style_sheet.rules;
+        assert_eq!(7, rules.len());
+
+        match &rules[0] {
+            CssRule::Layer(layer) => {
+                assert_eq!(
+                    vec!["base".to_string(), "components".to_string()],
+                    layer.names
+                );
+                assert!(layer.rules.is_none());
+            }
+            r => panic!("unexpected rule {:?}", r),
+        }
+        match &rules[1] {
+            CssRule::Import(import) => {
+                assert_eq!("a.css", import.url);
+                assert_eq!(Some("base".to_string()), import.layer);
+                assert!(import.supports.is_some());
+                assert_eq!(vec![ident("screen")], import.media);
+            }
+            r => panic!("unexpected rule {:?}", r),
+        }
+        match &rules[2] {
+            CssRule::Media(media) => {
+                assert_eq!(
+                    "screen and (min-width: 400px)",
+                    format!(
+                        "{}",
+                        crate::renderer::css::cssom::ComponentValues(&media.media)
+                    )
+                );
+                assert_eq!(1, media.rules.len());
+            }
+            r => panic!("unexpected rule {:?}", r),
+        }
+        assert!(matches!(&rules[3], CssRule::Supports(s) if s.rules.len() == 1));
+        assert!(matches!(&rules[4], CssRule::FontFace(f) if f.declarations.len() == 2));
+        match &rules[5] {
+            CssRule::Keyframes(keyframes) => {
+                assert_eq!("spin", keyframes.name);
+                assert_eq!(vec![0.0], keyframes.keyframes[0].offsets);
+                assert_eq!(vec![0.5, 1.0], keyframes.keyframes[1].offsets);
+            }
+            r => panic!("unexpected rule {:?}", r),
+        }
+        assert!(matches!(&rules[6], CssRule::Layer(l) if l.rules.is_some()));
+    }
+
+    #[test]
+    fn test_declaration_list() {
+        let declarations = CssParser::new(CssTokenizer::new(
+            "color: red; font-size: 12px !important".to_string(),
+        ))
+        .parse_declaration_list();
+        assert_eq!(2, declarations.len());
+        assert!(declarations[1].important);
+    }
+
+    #[test]
+    fn test_serialize() {
+        let css =
+            "h1.title > a[href=\"x\"]:hover, p::before { color: red; margin: 0 auto !important; }
+@media screen and (max-width: 600px) {
+  p { font-size: 1.5em; }
+}
+@keyframes fade {
+  0% { opacity: 0; }
+  100% { opacity: 1; }
+}
+@layer a, b;";
+        let style_sheet = parse(css);
+        assert_eq!(css, format!("{}", style_sheet));
+
+        // シリアライズ結果を再度パースしても同じ結果になる
+        assert_eq!(style_sheet, parse(&format!("{}", style_sheet)));
+    }
+}
@@ -0,0 +1,949 @@
+use crate::renderer::{
+    css::{
+        cssom::{ComponentValue, ComponentValues},
+        token::CssTokenKind,
+    },
+    dom::node::{Element, Node, NodeKind},
+};
+use alloc::{
+    format,
+    rc::Rc,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{cell::RefCell, fmt};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Combinator {
+    Descendant,
+    Child,
+    NextSibling,
+    SubsequentSibling,
+}
+
+impl fmt::Display for Combinator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Combinator::Descendant => write!(f, " "),
+            Combinator::Child => write!(f, " > "),
+            Combinator::NextSibling => write!(f, " + "),
+            Combinator::SubsequentSibling => write!(f, " ~ "),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttributeOperator {
+    Exists,
+    Equals,
+    Includes,
+    DashMatch,
+    Prefix,
+    Suffix,
+    Substring,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AttributeSelector {
+    pub name: String,
+    pub operator: AttributeOperator,
+    pub value: String,
+    pub case_insensitive: bool,
+}
+
+impl fmt::Display for AttributeSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let operator = match self.operator {
+            AttributeOperator::Exists => return write!(f, "[{}]", self.name),
+            AttributeOperator::Equals => "=",
+            AttributeOperator::Includes => "~=",
+            AttributeOperator::DashMatch => "|=",
+            AttributeOperator::Prefix => "^=",
+            AttributeOperator::Suffix => "$=",
+            AttributeOperator::Substring => "*=",
+        };
+        write!(f, "[{}{}\"{}\"", self.name, operator, self.value)?;
+        if self.case_insensitive {
+            write!(f, " i")?;
+        }
+        write!(f, "]")
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PseudoClass {
+    Named(String),
+    Function {
+        name: String,
+        arguments: Vec<ComponentValue>,
+    },
+    Selector {
+        name: String,
+        selectors: SelectorList,
+    },
+}
+
+impl fmt::Display for PseudoClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PseudoClass::Named(name) => write!(f, ":{}", name),
+            PseudoClass::Function { name, arguments } => {
+                write!(f, ":{}({})", name, ComponentValues(arguments))
+            }
+            PseudoClass::Selector { name, selectors } => write!(f, ":{}({})", name, selectors),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SimpleSelector {
+    Universal,
+    Type(String),
+    Id(String),
+    Class(String),
+    Attribute(AttributeSelector),
+    PseudoClass(PseudoClass),
+    PseudoElement(String),
+}
+
+impl fmt::Display for SimpleSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimpleSelector::Universal => write!(f, "*"),
+            SimpleSelector::Type(name) => write!(f, "{}", name),
+            SimpleSelector::Id(id) => write!(f, "#{}", id),
+            SimpleSelector::Class(class) => write!(f, ".{}", class),
+            SimpleSelector::Attribute(attribute) => write!(f, "{}", attribute),
+            SimpleSelector::PseudoClass(pseudo) => write!(f, "{}", pseudo),
+            SimpleSelector::PseudoElement(name) => write!(f, "::{}", name),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CompoundSelector {
+    pub simple_selectors: Vec<SimpleSelector>,
+}
+
+impl fmt::Display for CompoundSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for simple in &self.simple_selectors {
+            write!(f, "{}", simple)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComplexSelector {
+    pub compounds: Vec<CompoundSelector>,
+    // combinators[i]はcompounds[i]とcompounds[i + 1]の間の結合子
+    pub combinators: Vec<Combinator>,
+}
+
+impl fmt::Display for ComplexSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, compound) in self.compounds.iter().enumerate() {
+            if i > 0 {
+                write!(f, "{}", self.combinators[i - 1])?;
+            }
+            write!(f, "{}", compound)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SelectorList {
+    pub selectors: Vec<ComplexSelector>,
+}
+
+impl fmt::Display for SelectorList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, selector) in self.selectors.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", selector)?;
+        }
+        Ok(())
+    }
+}
+
+fn split_by_comma(values: &[ComponentValue]) -> Vec<&[ComponentValue]> {
+    values.split(|v| v.is_token(&CssTokenKind::Comma)).collect()
+}
+
+fn trim_whitespace(mut values: &[ComponentValue]) -> &[ComponentValue] {
+    while values.first().is_some_and(|v| v.is_whitespace()) {
+        values = &values[1..];
+    }
+    while values.last().is_some_and(|v| v.is_whitespace()) {
+        values = &values[..values.len() - 1];
+    }
+    values
+}
+
+pub fn parse_selector_list(values: &[ComponentValue]) -> Option<SelectorList> {
+    let mut selectors = Vec::new();
+    for part in split_by_comma(values) {
+        selectors.push(parse_complex_selector(trim_whitespace(part))?);
+    }
+    Some(SelectorList { selectors })
+}
+
+// :is()や:where()では不正なセレクタを無視する
+fn parse_forgiving_selector_list(values: &[ComponentValue]) -> SelectorList {
+    let selectors = split_by_comma(values)
+        .into_iter()
+        .filter_map(|part| parse_complex_selector(trim_whitespace(part)))
+        .collect();
+    SelectorList { selectors }
+}
+
+fn parse_attribute_selector(values: &[ComponentValue]) -> Option<AttributeSelector> {
+    let values: Vec<&ComponentValue> = values.iter().filter(|v| !v.is_whitespace()).collect();
+    let name = values.first()?.ident()?.to_ascii_lowercase();
+
+    if values.len() == 1 {
+        return Some(AttributeSelector {
+            name,
+            operator: AttributeOperator::Exists,
+            value: String::new(),
+            case_insensitive: false,
+        });
+    }
+
+    let (operator, rest) = match values.get(1)? {
+        ComponentValue::Token(CssTokenKind::Delim('=')) => (AttributeOperator::Equals, 2),
+        ComponentValue::Token(CssTokenKind::Delim(ch)) => {
+            if !values.get(2)?.is_token(&CssTokenKind::Delim('=')) {
+                return None;
+            }
+            let operator = match ch {
+                '~' => AttributeOperator::Includes,
+                '|' => AttributeOperator::DashMatch,
+                '^' => AttributeOperator::Prefix,
+                '$' => AttributeOperator::Suffix,
+                '*' => AttributeOperator::Substring,
+                _ => return None,
+            };
+            (operator, 3)
+        }
+        _ => return None,
+    };
+
+    let value = match values.get(rest)? {
+        ComponentValue::Token(CssTokenKind::Ident(value))
+        | ComponentValue::Token(CssTokenKind::String(value)) => value.clone(),
+        _ => return None,
+    };
+
+    let case_insensitive = match values.get(rest + 1) {
+        None => false,
+        Some(modifier) => match modifier.ident() {
+            Some(m) if m.eq_ignore_ascii_case("i") => true,
+            Some(m) if m.eq_ignore_ascii_case("s") => false,
+            _ => return None,
+        },
+    };
+    if values.len() > rest + 2 {
+        return None;
+    }
+
+    Some(AttributeSelector {
+        name,
+        operator,
+        value,
+        case_insensitive,
+    })
+}
+
+fn parse_pseudo_class(value: &ComponentValue) -> Option<SimpleSelector> {
+    match value {
+        ComponentValue::Token(CssTokenKind::Ident(name)) => {
+            let name = name.to_ascii_lowercase();
+            // CSS2の疑似要素は:1つでも書ける
+            if matches!(
+                name.as_str(),
+                "before" | "after" | "first-line" | "first-letter"
+            ) {
+                return Some(SimpleSelector::PseudoElement(name));
+            }
+            Some(SimpleSelector::PseudoClass(PseudoClass::Named(name)))
+        }
+        ComponentValue::Function { name, value } => {
+            let name = name.to_ascii_lowercase();
+            let pseudo = match name.as_str() {
+                "not" | "has" => PseudoClass::Selector {
+                    name,
+                    selectors: parse_selector_list(value)?,
+                },
+                "is" | "where" => PseudoClass::Selector {
+                    name,
+                    selectors: parse_forgiving_selector_list(value),
+                },
+                _ => PseudoClass::Function {
+                    name,
+                    arguments: trim_whitespace(value).to_vec(),
+                },
+            };
+            Some(SimpleSelector::PseudoClass(pseudo))
+        }
+        _ => None,
+    }
+}
+
+fn parse_complex_selector(values: &[ComponentValue]) -> Option<ComplexSelector> {
+    let mut compounds = Vec::new();
+    let mut combinators = Vec::new();
+    let mut current = CompoundSelector::default();
+    let mut pending_combinator: Option<Combinator> = None;
+    let mut i = 0;
+
+    while i < values.len() {
+        let value = &values[i];
+        i += 1;
+
+        let combinator = match value {
+            ComponentValue::Token(CssTokenKind::Whitespace) => Some(Combinator::Descendant),
+            ComponentValue::Token(CssTokenKind::Delim('>')) => Some(Combinator::Child),
+            ComponentValue::Token(CssTokenKind::Delim('+')) => Some(Combinator::NextSibling),
+            ComponentValue::Token(CssTokenKind::Delim('~')) => Some(Combinator::SubsequentSibling),
+            _ => None,
+        };
+        if let Some(combinator) = combinator {
+            if current.simple_selectors.is_empty() && compounds.is_empty() {
+                // 先頭の結合子は不正
+                return None;
+            }
+            // 空白よりも明示的な結合子を優先する
+            pending_combinator = match (pending_combinator, combinator) {
+                (Some(Combinator::Descendant), c) | (None, c) => Some(c),
+                (Some(c), Combinator::Descendant) => Some(c),
+                (Some(_), _) => return None,
+            };
+            continue;
+        }
+
+        if let Some(combinator) = pending_combinator.take() {
+            if current.simple_selectors.is_empty() {
+                return None;
+            }
+            compounds.push(core::mem::take(&mut current));
+            combinators.push(combinator);
+        }
+
+        let simple = match value {
+            ComponentValue::Token(CssTokenKind::Ident(name)) => {
+                if !current.simple_selectors.is_empty() {
+                    return None;
+                }
+                SimpleSelector::Type(name.to_ascii_lowercase())
+            }
+            ComponentValue::Token(CssTokenKind::Delim('*')) => {
+                if !current.simple_selectors.is_empty() {
+                    return None;
+                }
+                SimpleSelector::Universal
+            }
+            ComponentValue::Token(CssTokenKind::Hash { value, is_id }) => {
+                if !is_id {
+                    return None;
+                }
+                SimpleSelector::Id(value.clone())
+            }
+            ComponentValue::Token(CssTokenKind::Delim('.')) => {
+                let class = values.get(i)?.ident()?;
+                i += 1;
+                SimpleSelector::Class(class.to_string())
+            }
+            ComponentValue::SimpleBlock {
+                associated: '[',
+                value,
+            } => SimpleSelector::Attribute(parse_attribute_selector(value)?),
+            ComponentValue::Token(CssTokenKind::Colon) => {
+                let next = values.get(i)?;
+                i += 1;
+                if next.is_token(&CssTokenKind::Colon) {
+                    let name = values.get(i)?.ident()?;
+                    i += 1;
+                    SimpleSelector::PseudoElement(name.to_ascii_lowercase())
+                } else {
+                    parse_pseudo_class(next)?
+                }
+            }
+            _ => return None,
+        };
+        current.simple_selectors.push(simple);
+    }
+
+    // 末尾の結合子は不正
+    if matches!(
+        pending_combinator,
+        Some(Combinator::Child | Combinator::NextSibling | Combinator::SubsequentSibling)
+    ) {
+        return None;
+    }
+    if current.simple_selectors.is_empty() {
+        return None;
+    }
+    compounds.push(current);
+
+    Some(ComplexSelector {
+        compounds,
+        combinators,
+    })
+}
+
+// (id, class, type)の順で比較する詳細度
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Specificity(pub u32, pub u32, pub u32);
+
+impl Specificity {
+    fn add(self, other: Specificity) -> Specificity {
+        Specificity(self.0 + other.0, self.1 + other.1, self.2 + other.2)
+    }
+}
+
+fn element_of(node: &Rc<RefCell<Node>>) -> Option<Element> {
+    node.borrow().get_element()
+}
+
+fn parent_element(node: &Rc<RefCell<Node>>) -> Option<Rc<RefCell<Node>>> {
+    let parent = node.borrow().parent().upgrade()?;
+    if element_of(&parent).is_some() {
+        Some(parent)
+    } else {
+        None
+    }
+}
+
+fn previous_element_sibling(node: &Rc<RefCell<Node>>) -> Option<Rc<RefCell<Node>>> {
+    let mut sibling = node.borrow().previous_sibling().upgrade();
+    while let Some(s) = sibling {
+        if element_of(&s).is_some() {
+            return Some(s);
+        }
+        sibling = s.borrow().previous_sibling().upgrade();
+    }
+    None
+}
+
+fn next_element_sibling(node: &Rc<RefCell<Node>>) -> Option<Rc<RefCell<Node>>> {
+    let mut sibling = node.borrow().next_sibling();
+    while let Some(s) = sibling {
+        if element_of(&s).is_some() {
+            return Some(s);
+        }
+        sibling = s.borrow().next_sibling();
+    }
+    None
+}
+
+fn has_same_type(a: &Rc<RefCell<Node>>, b: &Rc<RefCell<Node>>) -> bool {
+    a.borrow().element_kind() == b.borrow().element_kind()
+}
+
+// 兄弟要素の中での1から始まる位置
+fn element_index(
+    node: &Rc<RefCell<Node>>,
+    from_end: bool,
+    filter: &dyn Fn(&Rc<RefCell<Node>>) -> bool,
+) -> i32 {
+    let mut index = 1;
+    let mut sibling = if from_end {
+        next_element_sibling(node)
+    } else {
+        previous_element_sibling(node)
+    };
+    while let Some(s) = sibling {
+        if filter(&s) {
+            index += 1;
+        }
+        sibling = if from_end {
+            next_element_sibling(&s)
+        } else {
+            previous_element_sibling(&s)
+        };
+    }
+    index
+}
+
+fn is_empty_element(node: &Rc<RefCell<Node>>) -> bool {
+    let mut child = node.borrow().first_child();
+    while let Some(c) = child {
+        match c.borrow().kind() {
+            NodeKind::Element(_) => return false,
+            NodeKind::Text(text) if !text.is_empty() => return false,
+            _ => {}
+        }
+        child = c.borrow().next_sibling();
+    }
+    true
+}
+
+fn has_matching_descendant(node: &Rc<RefCell<Node>>, selectors: &SelectorList) -> bool {
+    let mut child = node.borrow().first_child();
+    while let Some(c) = child {
+        if element_of(&c).is_some()
+            && (selectors.matches(&c) || has_matching_descendant(&c, selectors))
+        {
+            return true;
+        }
+        child = c.borrow().next_sibling();
+    }
+    false
+}
+
+// An+Bの形式を(A, B)として解釈する
+fn parse_an_plus_b(values: &[ComponentValue]) -> Option<(i32, i32)> {
+    let mut s = String::new();
+    for value in values {
+        match value {
+            ComponentValue::Token(CssTokenKind::Whitespace) => {}
+            // `n+1`の`+1`は符号付きの数値としてトークン化される
+            ComponentValue::Token(CssTokenKind::Number { value, .. })
+                if s.ends_with('n') && *value >= 0.0 =>
+            {
+                s.push_str(&format!("+{}", value))
+            }
+            _ => s.push_str(&value.to_string()),
+        }
+    }
+    let s = s.to_ascii_lowercase();
+
+    match s.as_str() {
+        "odd" => return Some((2, 1)),
+        "even" => return Some((2, 0)),
+        _ => {}
+    }
+
+    match s.find('n') {
+        Some(pos) => {
+            let a = match &s[..pos] {
+                "" | "+" => 1,
+                "-" => -1,
+                a => a.parse().ok()?,
+            };
+            let b = match &s[pos + 1..] {
+                "" => 0,
+                b => b.parse().ok()?,
+            };
+            Some((a, b))
+        }
+        None => Some((0, s.parse().ok()?)),
+    }
+}
+
+// :nth-child(An+B of S)の引数を分解する
+fn parse_nth_arguments(arguments: &[ComponentValue]) -> Option<(i32, i32, Option<SelectorList>)> {
+    let of = arguments
+        .iter()
+        .position(|v| v.ident().is_some_and(|i| i.eq_ignore_ascii_case("of")));
+    match of {
+        Some(pos) => {
+            let (a, b) = parse_an_plus_b(&arguments[..pos])?;
+            let selectors = parse_selector_list(trim_whitespace(&arguments[pos + 1..]))?;
+            Some((a, b, Some(selectors)))
+        }
+        None => {
+            let (a, b) = parse_an_plus_b(arguments)?;
+            Some((a, b, None))
+        }
+    }
+}
+
+fn matches_nth(a: i32, b: i32, index: i32) -> bool {
+    if a == 0 {
+        return index == b;
+    }
+    let diff = index - b;
+    diff % a == 0 && diff / a >= 0
+}
+
+impl AttributeSelector {
+    pub fn matches(&self, element: &Element) -> bool {
+        let value = match element.get_attribute(&self.name) {
+            Some(value) => value,
+            None => return false,
+        };
+        let (value, expected) = if self.case_insensitive {
+            (value.to_ascii_lowercase(), self.value.to_ascii_lowercase())
+        } else {
+            (value, self.value.clone())
+        };
+
+        match self.operator {
+            AttributeOperator::Exists => true,
+            AttributeOperator::Equals => value == expected,
+            AttributeOperator::Includes => value.split_ascii_whitespace().any(|v| v == expected),
+            AttributeOperator::DashMatch => {
+                value == expected || value.starts_with(&format!("{}-", expected))
+            }
+            AttributeOperator::Prefix => !expected.is_empty() && value.starts_with(&expected),
+            AttributeOperator::Suffix => !expected.is_empty() && value.ends_with(&expected),
+            AttributeOperator::Substring => !expected.is_empty() && value.contains(&expected),
+        }
+    }
+}
+
+impl PseudoClass {
+    pub fn specificity(&self) -> Specificity {
+        match self {
+            PseudoClass::Named(_) => Specificity(0, 1, 0),
+            PseudoClass::Function { name, arguments } => {
+                let base = Specificity(0, 1, 0);
+                if name.starts_with("nth-") {
+                    if let Some((_, _, Some(selectors))) = parse_nth_arguments(arguments) {
+                        return base.add(selectors.max_specificity());
+                    }
+                }
+                base
+            }
+            PseudoClass::Selector { name, selectors } => {
+                if name == "where" {
+                    Specificity::default()
+                } else {
+                    selectors.max_specificity()
+                }
+            }
+        }
+    }
+
+    pub fn matches(&self, node: &Rc<RefCell<Node>>, element: &Element) -> bool {
+        match self {
+            PseudoClass::Named(name) => match name.as_str() {
+                "root" | "scope" => node
+                    .borrow()
+                    .parent()
+                    .upgrade()
+                    .is_some_and(|p| p.borrow().kind() == NodeKind::Document),
+                "first-child" => previous_element_sibling(node).is_none(),
+                "last-child" => next_element_sibling(node).is_none(),
+                "only-child" => {
+                    previous_element_sibling(node).is_none() && next_element_sibling(node).is_none()
+                }
+                "first-of-type" => element_index(node, false, &|s| has_same_type(s, node)) == 1,
+                "last-of-type" => element_index(node, true, &|s| has_same_type(s, node)) == 1,
+                "only-of-type" => {
+                    element_index(node, false, &|s| has_same_type(s, node)) == 1
+                        && element_index(node, true, &|s| has_same_type(s, node)) == 1
+                }
+                "empty" => is_empty_element(node),
+                "link" | "any-link" => element.get_attribute("href").is_some(),
+                // ユーザー操作に関する状態はまだ持っていない
+                _ => false,
+            },
+            PseudoClass::Function { name, arguments } => {
+                let (a, b, of) = match parse_nth_arguments(arguments) {
+                    Some(nth) => nth,
+                    None => return false,
+                };
+                let index = match name.as_str() {
+                    "nth-child" | "nth-last-child" => {
+                        if let Some(selectors) = &of {
+                            if !selectors.matches(node) {
+                                return false;
+                            }
+                        }
+                        let filter = |s: &Rc<RefCell<Node>>| match &of {
+                            Some(selectors) => selectors.matches(s),
+                            None => true,
+                        };
+                        element_index(node, name == "nth-last-child", &filter)
+                    }
+                    "nth-of-type" => element_index(node, false, &|s| has_same_type(s, node)),
+                    "nth-last-of-type" => element_index(node, true, &|s| has_same_type(s, node)),
+                    _ => return false,
+                };
+                matches_nth(a, b, index)
+            }
+            PseudoClass::Selector { name, selectors } => match name.as_str() {
+                "is" | "where" => selectors.matches(node),
+                "not" => !selectors.matches(node),
+                "has" => has_matching_descendant(node, selectors),
+                _ => false,
+            },
+        }
+    }
+}
+
+impl SimpleSelector {
+    // @supports selector()で使う。照合を実装しているかどうか
+    fn is_supported(&self) -> bool {
+        match self {
+            SimpleSelector::PseudoClass(PseudoClass::Named(name)) => matches!(
+                name.as_str(),
+                "root"
+                    | "scope"
+                    | "first-child"
+                    | "last-child"
+                    | "only-child"
+                    | "first-of-type"
+                    | "last-of-type"
+                    | "only-of-type"
+                    | "empty"
+                    | "link"
+                    | "any-link"
+                    | "visited"
+                    | "hover"
+                    | "active"
+                    | "focus"
+            ),
+            SimpleSelector::PseudoClass(PseudoClass::Function { name, arguments }) => {
+                matches!(
+                    name.as_str(),
+                    "nth-child" | "nth-last-child" | "nth-of-type" | "nth-last-of-type"
+                ) && parse_nth_arguments(arguments).is_some()
+            }
+            SimpleSelector::PseudoClass(PseudoClass::Selector { selectors, .. }) => {
+                selectors.is_supported()
+            }
+            SimpleSelector::PseudoElement(_) => false,
+            _ => true,
+        }
+    }
+
+    pub fn specificity(&self) -> Specificity {
+        match self {
+            SimpleSelector::Universal => Specificity::default(),
+            SimpleSelector::Id(_) => Specificity(1, 0, 0),
+            SimpleSelector::Class(_) | SimpleSelector::Attribute(_) => Specificity(0, 1, 0),
+            SimpleSelector::PseudoClass(pseudo) => pseudo.specificity(),
+            SimpleSelector::Type(_) | SimpleSelector::PseudoElement(_) => Specificity(0, 0, 1),
+        }
+    }
+
+    pub fn matches(&self, node: &Rc<RefCell<Node>>, element: &Element) -> bool {
+        match self {
+            SimpleSelector::Universal => true,
+            SimpleSelector::Type(name) => element.kind().to_string() == *name,
+            SimpleSelector::Id(id) => element.get_attribute("id").is_some_and(|v| v == *id),
+            SimpleSelector::Class(class) => element
+                .get_attribute("class")
+                .is_some_and(|v| v.split_ascii_whitespace().any(|c| c == class)),
+            SimpleSelector::Attribute(attribute) => attribute.matches(element),
+            SimpleSelector::PseudoClass(pseudo) => pseudo.matches(node, element),
+            // 疑似要素は要素そのものには一致しない
+            SimpleSelector::PseudoElement(_) => false,
+        }
+    }
+}
+
+impl CompoundSelector {
+    pub fn specificity(&self) -> Specificity {
+        self.simple_selectors
+            .iter()
+            .fold(Specificity::default(), |acc, s| acc.add(s.specificity()))
+    }
+
+    pub fn matches(&self, node: &Rc<RefCell<Node>>) -> bool {
+        let element = match element_of(node) {
+            Some(element) => element,
+            None => return false,
+        };
+        self.simple_selectors
+            .iter()
+            .all(|s| s.matches(node, &element))
+    }
+}
+
+impl ComplexSelector {
+    pub fn specificity(&self) -> Specificity {
+        self.compounds
+            .iter()
+            .fold(Specificity::default(), |acc, c| acc.add(c.specificity()))
+    }
+
+    pub fn matches(&self, node: &Rc<RefCell<Node>>) -> bool {
+        match self.compounds.len() {
+            0 => false,
+            len => self.matches_at(len - 1, node),
+        }
+    }
+
+    // 右から左へ照合し、子孫結合子と後続兄弟結合子ではバックトラックする
+    fn matches_at(&self, index: usize, node: &Rc<RefCell<Node>>) -> bool {
+        if !self.compounds[index].matches(node) {
+            return false;
+        }
+        if index == 0 {
+            return true;
+        }
+
+        match self.combinators[index - 1] {
+            Combinator::Descendant => {
+                let mut ancestor = parent_element(node);
+                while let Some(a) = ancestor {
+                    if self.matches_at(index - 1, &a) {
+                        return true;
+                    }
+                    ancestor = parent_element(&a);
+                }
+                false
+            }
+            Combinator::Child => {
+                parent_element(node).is_some_and(|p| self.matches_at(index - 1, &p))
+            }
+            Combinator::NextSibling => {
+                previous_element_sibling(node).is_some_and(|s| self.matches_at(index - 1, &s))
+            }
+            Combinator::SubsequentSibling => {
+                let mut sibling = previous_element_sibling(node);
+                while let Some(s) = sibling {
+                    if self.matches_at(index - 1, &s) {
+                        return true;
+                    }
+                    sibling = previous_element_sibling(&s);
+                }
+                false
+            }
+        }
+    }
+}
+
+impl SelectorList {
+    pub fn is_supported(&self) -> bool {
+        self.selectors.iter().all(|selector| {
+            selector
+                .compounds
+                .iter()
+                .flat_map(|c| c.simple_selectors.iter())
+                .all(|s| s.is_supported())
+        })
+    }
+
+    pub fn matches(&self, node: &Rc<RefCell<Node>>) -> bool {
+        self.selectors.iter().any(|s| s.matches(node))
+    }
+
+    // 一致したセレクタのうち最も高い詳細度を返す
+    pub fn matching_specificity(&self, node: &Rc<RefCell<Node>>) -> Option<Specificity> {
+        self.selectors
+            .iter()
+            .filter(|s| s.matches(node))
+            .map(|s| s.specificity())
+            .max()
+    }
+
+    fn max_specificity(&self) -> Specificity {
+        self.selectors
+            .iter()
+            .map(|s| s.specificity())
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::{
+        css::{parser::CssParser, token::CssTokenizer},
+        dom::{api::children, node::Window},
+        html::{parser::HtmlParser, token::HtmlTokenizer},
+    };
+
+    fn selector(s: &str) -> SelectorList {
+        let values = CssParser::new(CssTokenizer::new(s.to_string())).parse_component_values();
+        parse_selector_list(&values).expect("failed to parse selector")
+    }
+
+    fn create_window(html: &str) -> Rc<RefCell<Window>> {
+        HtmlParser::new(HtmlTokenizer::new(html.to_string())).construct_tree()
+    }
+
+    fn body_children(window: &Rc<RefCell<Window>>) -> Vec<Rc<RefCell<Node>>> {
+        let document = window.borrow().document();
+        let html = document.borrow().first_child().unwrap();
+        let body = html.borrow().last_child().upgrade().unwrap();
+        children(&body)
+            .into_iter()
+            .filter(|n| n.borrow().get_element().is_some())
+            .collect()
+    }
+
+    #[test]
+    fn test_specificity() {
+        assert_eq!(
+            Specificity(0, 0, 1),
+            selector("p").selectors[0].specificity()
+        );
+        assert_eq!(
+            Specificity(1, 2, 1),
+            selector("#a p.b:first-child").selectors[0].specificity()
+        );
+        assert_eq!(
+            Specificity(1, 0, 1),
+            selector("p:is(#a, .b)").selectors[0].specificity()
+        );
+        assert_eq!(
+            Specificity(0, 0, 1),
+            selector("p:where(#a, .b)").selectors[0].specificity()
+        );
+        assert_eq!(
+            Specificity(0, 2, 0),
+            selector(":nth-child(2n+1 of .a)").selectors[0].specificity()
+        );
+        assert_eq!(
+            Specificity::default(),
+            selector("*").selectors[0].specificity()
+        );
+    }
+
+    #[test]
+    fn test_match_selectors() {
+        let window = create_window(
+            "<html><head></head><body><p id=\"first\" class=\"x y\" lang=\"en-US\">one</p><p class=\"y\">two<a href=\"/\">link</a></p><h1>title</h1><p></p></body></html>",
+        );
+        let elements = body_children(&window);
+        assert_eq!(4, elements.len());
+        let (p1, p2, h1, p3) = (&elements[0], &elements[1], &elements[2], &elements[3]);
+        let a = children(p2).last().unwrap().clone();
+
+        assert!(selector("p").matches(p1));
+        assert!(!selector("p").matches(h1));
+        assert!(selector("#first.x.y").matches(p1));
+        assert!(!selector(".x").matches(p2));
+        assert!(selector("[lang|=en]").matches(p1));
+        assert!(selector("[class~=\"y\"]").matches(p2));
+        assert!(selector("[lang^=EN i]").matches(p1));
+        assert!(!selector("[lang^=EN]").matches(p1));
+
+        assert!(selector("body > p > a").matches(&a));
+        assert!(selector("html a").matches(&a));
+        assert!(!selector("h1 a").matches(&a));
+        assert!(selector("p + p").matches(p2));
+        assert!(!selector("p + p").matches(p3));
+        assert!(selector("#first ~ p").matches(p3));
+        assert!(selector("a:any-link").matches(&a));
+
+        assert!(
+            selector(":root").matches(&window.borrow().document().borrow().first_child().unwrap())
+        );
+        assert!(selector("p:first-child").matches(p1));
+        assert!(selector("p:last-child").matches(p3));
+        assert!(selector("h1:only-of-type").matches(h1));
+        assert!(selector("p:last-of-type").matches(p3));
+        assert!(selector("p:empty").matches(p3));
+        assert!(!selector("p:empty").matches(p1));
+        assert!(selector(":nth-child(odd)").matches(h1));
+        assert!(selector(":nth-child(2n + 1)").matches(p1));
+        assert!(!selector(":nth-child(2n+1)").matches(p2));
+        assert!(selector(":nth-child(-n+2)").matches(p2));
+        assert!(!selector(":nth-child(-n+2)").matches(h1));
+        assert!(selector(":nth-last-child(1)").matches(p3));
+        assert!(selector("p:nth-of-type(3)").matches(p3));
+        assert!(selector(":nth-child(2 of p)").matches(p2));
+        assert!(selector(":nth-child(3 of p)").matches(p3));
+
+        assert!(selector("p:not(.x)").matches(p2));
+        assert!(!selector("p:not(.x)").matches(p1));
+        assert!(selector(":is(h1, .x)").matches(h1));
+        assert!(selector("p:has(a)").matches(p2));
+        assert!(!selector("p:has(a)").matches(p1));
+        assert!(!selector("p::before").matches(p1));
+        assert!(!selector("a:hover").matches(&a));
+    }
+}
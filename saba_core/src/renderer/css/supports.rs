@@ -0,0 +1,144 @@
+use crate::renderer::css::{
+    computed_style::is_supported_declaration, cssom::ComponentValue, selector::parse_selector_list,
+    token::CssTokenKind, value::non_whitespace,
+};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+// <supports-in-parens>を評価する。構文が不正な場合はNone
+fn evaluate_in_parens(value: &ComponentValue) -> Option<bool> {
+    match value {
+        ComponentValue::SimpleBlock {
+            associated: '(',
+            value,
+        } => {
+            if let Some(result) = evaluate_condition(value) {
+                return Some(result);
+            }
+            if let Some(result) = evaluate_declaration(value) {
+                return Some(result);
+            }
+            // 解釈できない括弧は<general-enclosed>としてfalseになる
+            Some(false)
+        }
+        ComponentValue::Function { name, value } => {
+            if name.eq_ignore_ascii_case("selector") {
+                return Some(
+                    parse_selector_list(value).is_some_and(|selectors| selectors.is_supported()),
+                );
+            }
+            Some(false)
+        }
+        _ => None,
+    }
+}
+
+fn evaluate_declaration(values: &[ComponentValue]) -> Option<bool> {
+    let mut rest = values.iter().skip_while(|v| v.is_whitespace());
+    let name = rest.next()?.ident()?.to_string();
+    let mut rest = rest.skip_while(|v| v.is_whitespace());
+    if !rest.next()?.is_token(&CssTokenKind::Colon) {
+        return None;
+    }
+
+    let value: Vec<ComponentValue> = rest.cloned().collect();
+    let name = if name.starts_with("--") {
+        name
+    } else {
+        name.to_ascii_lowercase()
+    };
+    Some(is_supported_declaration(&name, &value))
+}
+
+fn evaluate_condition(values: &[ComponentValue]) -> Option<bool> {
+    let items = non_whitespace(values);
+    let keyword = |v: &ComponentValue| v.ident().map(|k| k.to_ascii_lowercase());
+
+    let first = items.first()?;
+    if keyword(first).as_deref() == Some("not") {
+        if items.len() != 2 {
+            return None;
+        }
+        return Some(!evaluate_in_parens(items[1])?);
+    }
+
+    let mut result = evaluate_in_parens(first)?;
+    let mut operator: Option<String> = None;
+    let mut rest = items[1..].chunks(2);
+    for chunk in &mut rest {
+        let (op, operand) = match chunk {
+            [op, operand] => (keyword(op)?, evaluate_in_parens(operand)?),
+            _ => return None,
+        };
+        // andとorを括弧なしで混在させることはできない
+        if operator.as_ref().is_some_and(|o| *o != op) {
+            return None;
+        }
+        match op.as_str() {
+            "and" => result = result && operand,
+            "or" => result = result || operand,
+            _ => return None,
+        }
+        operator = Some(op);
+    }
+    Some(result)
+}
+
+// @supportsの条件を評価する。構文が不正な条件はfalseになる
+pub fn supports_condition(condition: &[ComponentValue]) -> bool {
+    evaluate_condition(condition).unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::css::{parser::CssParser, token::CssTokenizer};
+
+    fn supports(s: &str) -> bool {
+        let values = CssParser::new(CssTokenizer::new(s.to_string())).parse_component_values();
+        supports_condition(&values)
+    }
+
+    #[test]
+    fn test_declarations() {
+        assert!(supports("(display: flex)"));
+        assert!(supports("(display:block)"));
+        assert!(supports("(COLOR: rgb(1 2 3))"));
+        assert!(supports("(margin: 1px auto)"));
+        assert!(supports("(--anything: { whatever })"));
+        assert!(supports("(width: calc(100% - var(--x)))"));
+        assert!(!supports("(display: bogus)"));
+        assert!(!supports("(unknown-property: 1px)"));
+        assert!(!supports("(width: 10px 20px)"));
+    }
+
+    #[test]
+    fn test_operators() {
+        assert!(supports("not (display: bogus)"));
+        assert!(supports("(display: flex) and (color: red)"));
+        assert!(!supports("(display: flex) and (display: bogus)"));
+        assert!(supports("(display: bogus) or (color: red)"));
+        assert!(supports(
+            "((display: bogus) or (color: red)) and (width: 1px)"
+        ));
+        assert!(!supports(
+            "(display: flex) and (color: red) or (width: 1px)"
+        ));
+        assert!(!supports("not (display: flex) and (color: red)"));
+        assert!(!supports("display: flex"));
+        assert!(!supports("(foo bar)"));
+        assert!(supports("(foo bar) or (color: red)"));
+    }
+
+    #[test]
+    fn test_selector() {
+        assert!(supports("selector(p > a:first-child)"));
+        assert!(supports("selector(:is(h1, h2))"));
+        assert!(!supports("selector(p::unknown)"));
+        assert!(!supports("selector(:unknown-pseudo)"));
+        assert!(!supports("selector(p >)"));
+        assert!(!supports("unknown(p)"));
+    }
+}
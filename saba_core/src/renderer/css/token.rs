@@ -0,0 +1,873 @@
+use alloc::{string::String, vec::Vec};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CssTokenKind {
+    Ident(String),
+    Function(String),
+    AtKeyword(String),
+    Hash {
+        value: String,
+        is_id: bool,
+    },
+    String(String),
+    BadString,
+    Url(String),
+    BadUrl,
+    Delim(char),
+    Number {
+        value: f64,
+        is_integer: bool,
+    },
+    Percentage(f64),
+    Dimension {
+        value: f64,
+        is_integer: bool,
+        unit: String,
+    },
+    UnicodeRange {
+        start: u32,
+        end: u32,
+    },
+    Whitespace,
+    Cdo,
+    Cdc,
+    Colon,
+    Semicolon,
+    Comma,
+    OpenSquare,
+    CloseSquare,
+    OpenParen,
+    CloseParen,
+    OpenCurly,
+    CloseCurly,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SourcePosition {
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CssToken {
+    pub kind: CssTokenKind,
+    pub start: SourcePosition,
+    pub end: SourcePosition,
+}
+
+impl CssToken {
+    pub fn new(kind: CssTokenKind) -> Self {
+        Self {
+            kind,
+            start: SourcePosition::default(),
+            end: SourcePosition::default(),
+        }
+    }
+}
+
+const REPLACEMENT_CHARACTER: char = '\u{FFFD}';
+const MAX_CODE_POINT: u32 = 0x10FFFF;
+
+fn is_name_start(ch: char) -> bool {
+    ch.is_ascii_alphabetic() || !ch.is_ascii() || ch == '_'
+}
+
+fn is_name(ch: char) -> bool {
+    is_name_start(ch) || ch.is_ascii_digit() || ch == '-'
+}
+
+fn is_non_printable(ch: char) -> bool {
+    matches!(ch, '\u{0}'..='\u{8}' | '\u{B}' | '\u{E}'..='\u{1F}' | '\u{7F}')
+}
+
+fn is_whitespace(ch: char) -> bool {
+    matches!(ch, '\n' | '\t' | ' ')
+}
+
+pub struct CssTokenizer {
+    pos: usize,
+    input: Vec<char>,
+    line_starts: Vec<usize>,
+}
+
+impl CssTokenizer {
+    pub fn new(css: String) -> Self {
+        // 入力の前処理: 改行を\nにそろえ、NULLを置換文字にする
+        let mut input = Vec::new();
+        let mut chars = css.chars().peekable();
+        while let Some(ch) = chars.next() {
+            match ch {
+                '\r' => {
+                    if chars.peek() == Some(&'\n') {
+                        chars.next();
+                    }
+                    input.push('\n');
+                }
+                '\u{C}' => input.push('\n'),
+                '\0' => input.push(REPLACEMENT_CHARACTER),
+                _ => input.push(ch),
+            }
+        }
+
+        let mut line_starts = Vec::new();
+        line_starts.push(0);
+        for (i, ch) in input.iter().enumerate() {
+            if *ch == '\n' {
+                line_starts.push(i + 1);
+            }
+        }
+
+        Self {
+            pos: 0,
+            input,
+            line_starts,
+        }
+    }
+
+    fn source_position(&self, offset: usize) -> SourcePosition {
+        let line = match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(line) => line - 1,
+        };
+        SourcePosition {
+            offset,
+            line: line + 1,
+            column: offset - self.line_starts[line] + 1,
+        }
+    }
+
+    fn peek(&self, n: usize) -> Option<char> {
+        self.input.get(self.pos + n).copied()
+    }
+
+    fn consume(&mut self) -> Option<char> {
+        let ch = self.peek(0);
+        if ch.is_some() {
+            self.pos += 1;
+        }
+        ch
+    }
+
+    fn is_valid_escape(first: Option<char>, second: Option<char>) -> bool {
+        first == Some('\\') && second.is_some() && second != Some('\n')
+    }
+
+    fn starts_identifier(&self, n: usize) -> bool {
+        let (first, second, third) = (self.peek(n), self.peek(n + 1), self.peek(n + 2));
+        match first {
+            Some('-') => {
+                matches!(second, Some(ch) if is_name_start(ch) || ch == '-')
+                    || Self::is_valid_escape(second, third)
+            }
+            Some('\\') => Self::is_valid_escape(first, second),
+            Some(ch) => is_name_start(ch),
+            None => false,
+        }
+    }
+
+    fn starts_number(&self) -> bool {
+        match self.peek(0) {
+            Some('+') | Some('-') => match self.peek(1) {
+                Some(ch) if ch.is_ascii_digit() => true,
+                Some('.') => matches!(self.peek(2), Some(ch) if ch.is_ascii_digit()),
+                _ => false,
+            },
+            Some('.') => matches!(self.peek(1), Some(ch) if ch.is_ascii_digit()),
+            Some(ch) => ch.is_ascii_digit(),
+            None => false,
+        }
+    }
+
+    fn consume_comments(&mut self) {
+        while self.peek(0) == Some('/') && self.peek(1) == Some('*') {
+            self.pos += 2;
+            loop {
+                match self.consume() {
+                    Some('*') if self.peek(0) == Some('/') => {
+                        self.pos += 1;
+                        break;
+                    }
+                    Some(_) => {}
+                    // 閉じられていないコメントはEOFまで読み飛ばす
+                    None => return,
+                }
+            }
+        }
+    }
+
+    fn consume_escaped_code_point(&mut self) -> char {
+        let Some(ch) = self.consume() else {
+            return REPLACEMENT_CHARACTER;
+        };
+
+        if !ch.is_ascii_hexdigit() {
+            return ch;
+        }
+
+        let mut hex = String::new();
+        hex.push(ch);
+        while hex.len() < 6 {
+            match self.peek(0) {
+                Some(c) if c.is_ascii_hexdigit() => {
+                    hex.push(c);
+                    self.pos += 1;
+                }
+                _ => break,
+            }
+        }
+        if matches!(self.peek(0), Some(c) if is_whitespace(c)) {
+            self.pos += 1;
+        }
+
+        let value = u32::from_str_radix(&hex, 16).unwrap_or(0);
+        if value == 0 || (0xD800..=0xDFFF).contains(&value) || value > MAX_CODE_POINT {
+            return REPLACEMENT_CHARACTER;
+        }
+        char::from_u32(value).unwrap_or(REPLACEMENT_CHARACTER)
+    }
+
+    fn consume_name(&mut self) -> String {
+        let mut name = String::new();
+        loop {
+            match self.peek(0) {
+                Some(ch) if is_name(ch) => {
+                    name.push(ch);
+                    self.pos += 1;
+                }
+                first if Self::is_valid_escape(first, self.peek(1)) => {
+                    self.pos += 1;
+                    name.push(self.consume_escaped_code_point());
+                }
+                _ => return name,
+            }
+        }
+    }
+
+    fn consume_digits(&mut self, repr: &mut String) {
+        while let Some(ch) = self.peek(0) {
+            if !ch.is_ascii_digit() {
+                break;
+            }
+            repr.push(ch);
+            self.pos += 1;
+        }
+    }
+
+    fn consume_number(&mut self) -> (f64, bool) {
+        let mut repr = String::new();
+        let mut is_integer = true;
+
+        if let Some(ch @ ('+' | '-')) = self.peek(0) {
+            repr.push(ch);
+            self.pos += 1;
+        }
+        self.consume_digits(&mut repr);
+
+        if self.peek(0) == Some('.') && matches!(self.peek(1), Some(ch) if ch.is_ascii_digit()) {
+            repr.push('.');
+            self.pos += 1;
+            self.consume_digits(&mut repr);
+            is_integer = false;
+        }
+
+        if let Some('e' | 'E') = self.peek(0) {
+            let has_exponent = match self.peek(1) {
+                Some('+' | '-') => matches!(self.peek(2), Some(ch) if ch.is_ascii_digit()),
+                Some(ch) => ch.is_ascii_digit(),
+                None => false,
+            };
+            if has_exponent {
+                repr.push('e');
+                self.pos += 1;
+                if let Some(ch @ ('+' | '-')) = self.peek(0) {
+                    repr.push(ch);
+                    self.pos += 1;
+                }
+                self.consume_digits(&mut repr);
+                is_integer = false;
+            }
+        }
+
+        (repr.parse::<f64>().unwrap_or(0.0), is_integer)
+    }
+
+    fn consume_numeric_token(&mut self) -> CssTokenKind {
+        let (value, is_integer) = self.consume_number();
+
+        if self.starts_identifier(0) {
+            let unit = self.consume_name();
+            return CssTokenKind::Dimension {
+                value,
+                is_integer,
+                unit,
+            };
+        }
+
+        if self.peek(0) == Some('%') {
+            self.pos += 1;
+            return CssTokenKind::Percentage(value);
+        }
+
+        CssTokenKind::Number { value, is_integer }
+    }
+
+    fn consume_string_token(&mut self, ending: char) -> CssTokenKind {
+        let mut value = String::new();
+        loop {
+            match self.consume() {
+                Some(ch) if ch == ending => return CssTokenKind::String(value),
+                None => return CssTokenKind::String(value),
+                Some('\n') => {
+                    // 改行は再消費してbad-stringを返す
+                    self.pos -= 1;
+                    return CssTokenKind::BadString;
+                }
+                Some('\\') => match self.peek(0) {
+                    None => {}
+                    Some('\n') => self.pos += 1,
+                    Some(_) => value.push(self.consume_escaped_code_point()),
+                },
+                Some(ch) => value.push(ch),
+            }
+        }
+    }
+
+    fn consume_bad_url_remnants(&mut self) {
+        loop {
+            match self.consume() {
+                Some(')') | None => return,
+                first if Self::is_valid_escape(first, self.peek(0)) => {
+                    self.consume_escaped_code_point();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn consume_url_token(&mut self) -> CssTokenKind {
+        let mut value = String::new();
+        while matches!(self.peek(0), Some(ch) if is_whitespace(ch)) {
+            self.pos += 1;
+        }
+
+        loop {
+            match self.consume() {
+                Some(')') | None => return CssTokenKind::Url(value),
+                Some(ch) if is_whitespace(ch) => {
+                    while matches!(self.peek(0), Some(ch) if is_whitespace(ch)) {
+                        self.pos += 1;
+                    }
+                    match self.peek(0) {
+                        Some(')') => {
+                            self.pos += 1;
+                            return CssTokenKind::Url(value);
+                        }
+                        None => return CssTokenKind::Url(value),
+                        _ => {
+                            self.consume_bad_url_remnants();
+                            return CssTokenKind::BadUrl;
+                        }
+                    }
+                }
+                Some(ch) if ch == '"' || ch == '\'' || ch == '(' || is_non_printable(ch) => {
+                    self.consume_bad_url_remnants();
+                    return CssTokenKind::BadUrl;
+                }
+                Some('\\') => {
+                    if Self::is_valid_escape(Some('\\'), self.peek(0)) {
+                        value.push(self.consume_escaped_code_point());
+                    } else {
+                        self.consume_bad_url_remnants();
+                        return CssTokenKind::BadUrl;
+                    }
+                }
+                Some(ch) => value.push(ch),
+            }
+        }
+    }
+
+    fn consume_ident_like_token(&mut self) -> CssTokenKind {
+        let name = self.consume_name();
+
+        if name.eq_ignore_ascii_case("url") && self.peek(0) == Some('(') {
+            self.pos += 1;
+            while matches!(self.peek(0), Some(ch) if is_whitespace(ch))
+                && matches!(self.peek(1), Some(ch) if is_whitespace(ch))
+            {
+                self.pos += 1;
+            }
+
+            // url("...")の形式は関数トークンとして扱う
+            let next = match self.peek(0) {
+                Some(ch) if is_whitespace(ch) => self.peek(1),
+                ch => ch,
+            };
+            if next == Some('"') || next == Some('\'') {
+                return CssTokenKind::Function(name);
+            }
+            return self.consume_url_token();
+        }
+
+        if self.peek(0) == Some('(') {
+            self.pos += 1;
+            return CssTokenKind::Function(name);
+        }
+
+        CssTokenKind::Ident(name)
+    }
+
+    fn starts_unicode_range(&self) -> bool {
+        matches!(self.peek(0), Some('u' | 'U'))
+            && self.peek(1) == Some('+')
+            && matches!(self.peek(2), Some(ch) if ch.is_ascii_hexdigit() || ch == '?')
+    }
+
+    fn consume_unicode_range(&mut self) -> CssTokenKind {
+        // "u+"を読み飛ばす
+        self.pos += 2;
+
+        let mut first = String::new();
+        while first.len() < 6 {
+            match self.peek(0) {
+                Some(ch) if ch.is_ascii_hexdigit() => {
+                    first.push(ch);
+                    self.pos += 1;
+                }
+                _ => break,
+            }
+        }
+
+        let mut wildcards = 0;
+        while first.len() + wildcards < 6 && self.peek(0) == Some('?') {
+            wildcards += 1;
+            self.pos += 1;
+        }
+
+        if wildcards > 0 {
+            let start = first.clone() + &"0".repeat(wildcards);
+            let end = first + &"F".repeat(wildcards);
+            return CssTokenKind::UnicodeRange {
+                start: u32::from_str_radix(&start, 16).unwrap_or(0),
+                end: u32::from_str_radix(&end, 16).unwrap_or(0),
+            };
+        }
+
+        let start = u32::from_str_radix(&first, 16).unwrap_or(0);
+
+        if self.peek(0) == Some('-') && matches!(self.peek(1), Some(ch) if ch.is_ascii_hexdigit()) {
+            self.pos += 1;
+            let mut second = String::new();
+            while second.len() < 6 {
+                match self.peek(0) {
+                    Some(ch) if ch.is_ascii_hexdigit() => {
+                        second.push(ch);
+                        self.pos += 1;
+                    }
+                    _ => break,
+                }
+            }
+            return CssTokenKind::UnicodeRange {
+                start,
+                end: u32::from_str_radix(&second, 16).unwrap_or(0),
+            };
+        }
+
+        CssTokenKind::UnicodeRange { start, end: start }
+    }
+
+    fn consume_token(&mut self) -> Option<CssTokenKind> {
+        self.consume_comments();
+
+        let ch = self.peek(0)?;
+
+        let kind = match ch {
+            ch if is_whitespace(ch) => {
+                while matches!(self.peek(0), Some(ch) if is_whitespace(ch)) {
+                    self.pos += 1;
+                }
+                CssTokenKind::Whitespace
+            }
+            '"' | '\'' => {
+                self.pos += 1;
+                self.consume_string_token(ch)
+            }
+            '#' => {
+                self.pos += 1;
+                let is_name_char = matches!(self.peek(0), Some(ch) if is_name(ch));
+                if is_name_char || Self::is_valid_escape(self.peek(0), self.peek(1)) {
+                    let is_id = self.starts_identifier(0);
+                    let value = self.consume_name();
+                    CssTokenKind::Hash { value, is_id }
+                } else {
+                    CssTokenKind::Delim('#')
+                }
+            }
+            '(' => {
+                self.pos += 1;
+                CssTokenKind::OpenParen
+            }
+            ')' => {
+                self.pos += 1;
+                CssTokenKind::CloseParen
+            }
+            '+' | '.' if self.starts_number() => self.consume_numeric_token(),
+            ',' => {
+                self.pos += 1;
+                CssTokenKind::Comma
+            }
+            '-' if self.starts_number() => self.consume_numeric_token(),
+            '-' if self.peek(1) == Some('-') && self.peek(2) == Some('>') => {
+                self.pos += 3;
+                CssTokenKind::Cdc
+            }
+            '-' if self.starts_identifier(0) => self.consume_ident_like_token(),
+            ':' => {
+                self.pos += 1;
+                CssTokenKind::Colon
+            }
+            ';' => {
+                self.pos += 1;
+                CssTokenKind::Semicolon
+            }
+            '<' if self.peek(1) == Some('!')
+                && self.peek(2) == Some('-')
+                && self.peek(3) == Some('-') =>
+            {
+                self.pos += 4;
+                CssTokenKind::Cdo
+            }
+            '@' if self.starts_identifier(1) => {
+                self.pos += 1;
+                CssTokenKind::AtKeyword(self.consume_name())
+            }
+            '[' => {
+                self.pos += 1;
+                CssTokenKind::OpenSquare
+            }
+            '\\' if Self::is_valid_escape(Some('\\'), self.peek(1)) => {
+                self.consume_ident_like_token()
+            }
+            ']' => {
+                self.pos += 1;
+                CssTokenKind::CloseSquare
+            }
+            '{' => {
+                self.pos += 1;
+                CssTokenKind::OpenCurly
+            }
+            '}' => {
+                self.pos += 1;
+                CssTokenKind::CloseCurly
+            }
+            ch if ch.is_ascii_digit() => self.consume_numeric_token(),
+            'u' | 'U' if self.starts_unicode_range() => self.consume_unicode_range(),
+            ch if is_name_start(ch) => self.consume_ident_like_token(),
+            ch => {
+                self.pos += 1;
+                CssTokenKind::Delim(ch)
+            }
+        };
+
+        Some(kind)
+    }
+}
+
+impl Iterator for CssTokenizer {
+    type Item = CssToken;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // コメントはトークンを生成しないので、開始位置はコメントの後ろにする
+        self.consume_comments();
+        let start = self.source_position(self.pos);
+        let kind = self.consume_token()?;
+        let end = self.source_position(self.pos);
+
+        Some(CssToken { kind, start, end })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{string::ToString, vec};
+
+    fn tokenize(css: &str) -> Vec<CssTokenKind> {
+        CssTokenizer::new(css.to_string())
+            .map(|token| token.kind)
+            .collect()
+    }
+
+    fn ident(s: &str) -> CssTokenKind {
+        CssTokenKind::Ident(s.to_string())
+    }
+
+    #[test]
+    fn test_empty() {
+        assert!(tokenize("").is_empty());
+        assert!(tokenize("/* only comment */").is_empty());
+    }
+
+    #[test]
+    fn test_rule() {
+        let expected = vec![
+            ident("p"),
+            CssTokenKind::Whitespace,
+            CssTokenKind::OpenCurly,
+            CssTokenKind::Whitespace,
+            ident("color"),
+            CssTokenKind::Colon,
+            CssTokenKind::Whitespace,
+            ident("red"),
+            CssTokenKind::Semicolon,
+            CssTokenKind::Whitespace,
+            CssTokenKind::CloseCurly,
+        ];
+        assert_eq!(expected, tokenize("p { color: red; }"));
+    }
+
+    #[test]
+    fn test_comment() {
+        assert_eq!(
+            vec![ident("a"), ident("b")],
+            tokenize("a/* comment */b/* unterminated")
+        );
+    }
+
+    #[test]
+    fn test_hash() {
+        assert_eq!(
+            vec![
+                CssTokenKind::Hash {
+                    value: "id".to_string(),
+                    is_id: true
+                },
+                CssTokenKind::Hash {
+                    value: "123".to_string(),
+                    is_id: false
+                },
+                CssTokenKind::Delim('#'),
+            ],
+            tokenize("#id#123#")
+        );
+    }
+
+    #[test]
+    fn test_at_keyword_and_function() {
+        assert_eq!(
+            vec![
+                CssTokenKind::AtKeyword("media".to_string()),
+                CssTokenKind::Whitespace,
+                CssTokenKind::Function("rgb".to_string()),
+                CssTokenKind::CloseParen,
+                CssTokenKind::Delim('@'),
+            ],
+            tokenize("@media rgb()@")
+        );
+    }
+
+    #[test]
+    fn test_string() {
+        assert_eq!(
+            vec![
+                CssTokenKind::String("double".to_string()),
+                CssTokenKind::String("single".to_string()),
+                CssTokenKind::String("esc\"aped\u{263A}x".to_string()),
+                CssTokenKind::String("line continued".to_string()),
+            ],
+            tokenize("\"double\"'single'\"esc\\\"aped\\263A x\"'line \\\ncontinued'")
+        );
+    }
+
+    #[test]
+    fn test_bad_string() {
+        assert_eq!(
+            vec![
+                CssTokenKind::BadString,
+                CssTokenKind::Whitespace,
+                ident("a"),
+            ],
+            tokenize("\"unterminated\na")
+        );
+        assert_eq!(
+            vec![CssTokenKind::String("eof".to_string())],
+            tokenize("'eof")
+        );
+    }
+
+    #[test]
+    fn test_escape() {
+        // エスケープ直後の空白1つはエスケープの一部として消費される
+        assert_eq!(
+            vec![
+                ident("a\u{FFFD}b"),
+                CssTokenKind::Whitespace,
+                ident("\u{FFFD}x.y")
+            ],
+            tokenize("a\\0 b \\110000 x\\.y")
+        );
+    }
+
+    #[test]
+    fn test_url() {
+        assert_eq!(
+            vec![
+                CssTokenKind::Url("image.png".to_string()),
+                CssTokenKind::Url("a b".to_string()),
+                CssTokenKind::Function("url".to_string()),
+                CssTokenKind::String("quoted.png".to_string()),
+                CssTokenKind::CloseParen,
+            ],
+            tokenize("url( image.png )url(a\\ b)url(\"quoted.png\")")
+        );
+    }
+
+    #[test]
+    fn test_bad_url() {
+        assert_eq!(
+            vec![CssTokenKind::BadUrl, ident("a")],
+            tokenize("url(a b)a")
+        );
+        assert_eq!(
+            vec![CssTokenKind::BadUrl, ident("a")],
+            tokenize("url(a'b)a")
+        );
+    }
+
+    #[test]
+    fn test_numeric() {
+        assert_eq!(
+            vec![
+                CssTokenKind::Number {
+                    value: 12.0,
+                    is_integer: true
+                },
+                CssTokenKind::Whitespace,
+                CssTokenKind::Number {
+                    value: -0.5,
+                    is_integer: false
+                },
+                CssTokenKind::Whitespace,
+                CssTokenKind::Percentage(50.0),
+                CssTokenKind::Whitespace,
+                CssTokenKind::Dimension {
+                    value: 1.5,
+                    is_integer: false,
+                    unit: "em".to_string()
+                },
+                CssTokenKind::Whitespace,
+                CssTokenKind::Number {
+                    value: 100.0,
+                    is_integer: false
+                },
+                CssTokenKind::Whitespace,
+                CssTokenKind::Dimension {
+                    value: 10.0,
+                    is_integer: true,
+                    unit: "e".to_string()
+                },
+                CssTokenKind::Whitespace,
+                CssTokenKind::Number {
+                    value: 1.0,
+                    is_integer: true
+                },
+                CssTokenKind::Delim('.'),
+            ],
+            tokenize("12 -.5 50% 1.5em 1e2 10e 1.")
+        );
+    }
+
+    #[test]
+    fn test_unicode_range() {
+        assert_eq!(
+            vec![
+                CssTokenKind::UnicodeRange {
+                    start: 0x26,
+                    end: 0x26
+                },
+                CssTokenKind::Comma,
+                CssTokenKind::UnicodeRange {
+                    start: 0x0,
+                    end: 0x7F
+                },
+                CssTokenKind::Comma,
+                CssTokenKind::UnicodeRange {
+                    start: 0x4E00,
+                    end: 0x4EFF
+                },
+            ],
+            tokenize("U+26,u+0-7F,U+4E??")
+        );
+    }
+
+    #[test]
+    fn test_cdo_cdc() {
+        assert_eq!(
+            vec![
+                CssTokenKind::Cdo,
+                CssTokenKind::Whitespace,
+                ident("a"),
+                CssTokenKind::Whitespace,
+                CssTokenKind::Cdc,
+                ident("--custom"),
+            ],
+            tokenize("<!-- a -->--custom")
+        );
+    }
+
+    #[test]
+    fn test_delimiters() {
+        assert_eq!(
+            vec![
+                CssTokenKind::OpenSquare,
+                CssTokenKind::CloseSquare,
+                CssTokenKind::OpenParen,
+                CssTokenKind::CloseParen,
+                CssTokenKind::Delim('>'),
+                CssTokenKind::Delim('~'),
+                CssTokenKind::Delim('*'),
+                CssTokenKind::Delim('+'),
+                CssTokenKind::Delim('-'),
+            ],
+            tokenize("[]()>~*+-")
+        );
+    }
+
+    #[test]
+    fn test_preprocess() {
+        assert_eq!(
+            vec![ident("a"), CssTokenKind::Whitespace, ident("b\u{FFFD}")],
+            tokenize("a\r\n\u{C}b\0")
+        );
+    }
+
+    #[test]
+    fn test_position() {
+        let tokens: Vec<CssToken> = CssTokenizer::new("a {\n  b: c;\n}".to_string()).collect();
+        let b = tokens
+            .iter()
+            .find(|t| t.kind == ident("b"))
+            .expect("failed to find b");
+        assert_eq!(
+            SourcePosition {
+                offset: 6,
+                line: 2,
+                column: 3
+            },
+            b.start
+        );
+        assert_eq!(
+            SourcePosition {
+                offset: 7,
+                line: 2,
+                column: 4
+            },
+            b.end
+        );
+
+        let last = tokens.last().unwrap();
+        assert_eq!(CssTokenKind::CloseCurly, last.kind);
+        assert_eq!(3, last.start.line);
+        assert_eq!(1, last.start.column);
+    }
+}
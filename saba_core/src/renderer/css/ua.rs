@@ -0,0 +1,166 @@
+// ユーザーエージェントスタイルシート
+pub const USER_AGENT_STYLE_SHEET: &str = r#"
+html, body, address, article, aside, blockquote, center, dd, details, div, dl, dt,
+fieldset, figcaption, figure, footer, form, header, hr, legend, main, nav, ol, p,
+pre, section, summary, ul, h1, h2, h3, h4, h5, h6 {
+  display: block;
+}
+
+head, link, meta, script, style, template, title {
+  display: none;
+}
+
+body {
+  margin: 8px;
+}
+
+p, blockquote, dl, figure, pre {
+  margin-top: 1em;
+  margin-bottom: 1em;
+}
+
+h1 {
+  font-size: 2em;
+  font-weight: bold;
+  margin-top: 0.67em;
+  margin-bottom: 0.67em;
+}
+
+h2 {
+  font-size: 1.5em;
+  font-weight: bold;
+  margin-top: 0.83em;
+  margin-bottom: 0.83em;
+}
+
+h3 {
+  font-size: 1.17em;
+  font-weight: bold;
+  margin-top: 1em;
+  margin-bottom: 1em;
+}
+
+h4 {
+  font-weight: bold;
+  margin-top: 1.33em;
+  margin-bottom: 1.33em;
+}
+
+h5 {
+  font-size: 0.83em;
+  font-weight: bold;
+  margin-top: 1.67em;
+  margin-bottom: 1.67em;
+}
+
+h6 {
+  font-size: 0.67em;
+  font-weight: bold;
+  margin-top: 2.33em;
+  margin-bottom: 2.33em;
+}
+
+a:any-link {
+  color: #0000ee;
+  text-decoration: underline;
+}
+
+b, strong {
+  font-weight: bolder;
+}
+
+i, em, cite, var {
+  font-style: italic;
+}
+
+pre, code, kbd, samp, tt {
+  font-family: monospace;
+}
+
+pre {
+  white-space: pre;
+}
+
+blockquote, figure {
+  margin-left: 40px;
+  margin-right: 40px;
+}
+
+ul, ol {
+  margin-top: 1em;
+  margin-bottom: 1em;
+  padding-left: 40px;
+}
+
+ol {
+  list-style-type: decimal;
+}
+
+ul ul, ul ol, ol ul, ol ol {
+  margin-top: 0;
+  margin-bottom: 0;
+}
+
+ul ul, ol ul {
+  list-style-type: circle;
+}
+
+ul ul ul, ul ol ul, ol ul ul, ol ol ul {
+  list-style-type: square;
+}
+
+li {
+  display: list-item;
+}
+
+table {
+  display: table;
+  box-sizing: border-box;
+  border-collapse: separate;
+  border-spacing: 2px;
+}
+
+caption {
+  display: table-caption;
+  text-align: center;
+}
+
+colgroup {
+  display: table-column-group;
+}
+
+col {
+  display: table-column;
+}
+
+thead {
+  display: table-header-group;
+  vertical-align: middle;
+}
+
+tbody {
+  display: table-row-group;
+  vertical-align: middle;
+}
+
+tfoot {
+  display: table-footer-group;
+  vertical-align: middle;
+}
+
+tr {
+  display: table-row;
+  vertical-align: inherit;
+}
+
+td, th {
+  display: table-cell;
+  vertical-align: inherit;
+  padding: 1px;
+}
+
+th {
+  font-weight: bold;
+  text-align: center;
+}
+"#;
@@ -0,0 +1,884 @@
+use crate::renderer::css::{cssom::ComponentValue, token::CssTokenKind};
+use alloc::{boxed::Box, rc::Rc, string::String, vec, vec::Vec};
+
+// CSS Color Module Level 4で定義された名前付きの色
+const NAMED_COLORS: [(&str, u32); 148] = [
+    ("aliceblue", 0xf0f8ff),
+    ("antiquewhite", 0xfaebd7),
+    ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff),
+    ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0),
+    ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50),
+    ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222),
+    ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff),
+    ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xadff2f),
+    ("grey", 0x808080),
+    ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4),
+    ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd),
+    ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff),
+    ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90),
+    ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0),
+    ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6),
+    ("magenta", 0xff00ff),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd),
+    ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db),
+    ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a),
+    ("mediumturquoise", 0x48d1cc),
+    ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xf5fffa),
+    ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead),
+    ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500),
+    ("orangered", 0xff4500),
+    ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa),
+    ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f),
+    ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f),
+    ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072),
+    ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57),
+    ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0),
+    ("skyblue", 0x87ceeb),
+    ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4),
+    ("tan", 0xd2b48c),
+    ("teal", 0x008080),
+    ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0),
+    ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3),
+    ("white", 0xffffff),
+    ("whitesmoke", 0xf5f5f5),
+    ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Color {
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b, a: 255 }
+    }
+
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+
+    pub const BLACK: Color = Color::rgb(0, 0, 0);
+    pub const WHITE: Color = Color::rgb(255, 255, 255);
+    pub const TRANSPARENT: Color = Color::rgba(0, 0, 0, 0);
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase();
+        if name == "transparent" {
+            return Some(Color::TRANSPARENT);
+        }
+        NAMED_COLORS
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, rgb)| Color::rgb((rgb >> 16) as u8, (rgb >> 8) as u8, *rgb as u8))
+    }
+
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+
+        let digit = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).ok();
+        let pair = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+
+        match hex.len() {
+            3 | 4 => {
+                let r = digit(0)? * 17;
+                let g = digit(1)? * 17;
+                let b = digit(2)? * 17;
+                let a = if hex.len() == 4 { digit(3)? * 17 } else { 255 };
+                Some(Color::rgba(r, g, b, a))
+            }
+            6 | 8 => {
+                let a = if hex.len() == 8 { pair(6)? } else { 255 };
+                Some(Color::rgba(pair(0)?, pair(2)?, pair(4)?, a))
+            }
+            _ => None,
+        }
+    }
+
+    // hueは度数、saturationとlightnessは0.0から1.0の範囲
+    pub fn from_hsl(hue: f64, saturation: f64, lightness: f64, alpha: f64) -> Self {
+        let hue = modulo(hue, 360.0);
+        let saturation = saturation.clamp(0.0, 1.0);
+        let lightness = lightness.clamp(0.0, 1.0);
+
+        let f = |n: f64| {
+            let k = modulo(n + hue / 30.0, 12.0);
+            let a = saturation * lightness.min(1.0 - lightness);
+            lightness - a * (k - 3.0).min(9.0 - k).clamp(-1.0, 1.0)
+        };
+        Color::rgba(
+            to_channel(f(0.0) * 255.0),
+            to_channel(f(8.0) * 255.0),
+            to_channel(f(4.0) * 255.0),
+            to_channel(alpha * 255.0),
+        )
+    }
+}
+
+// no_stdではrem_euclidが使えないため負の剰余を補正する
+fn modulo(value: f64, divisor: f64) -> f64 {
+    let rem = value % divisor;
+    if rem < 0.0 {
+        rem + divisor
+    } else {
+        rem
+    }
+}
+
+fn to_channel(value: f64) -> u8 {
+    let value = value.clamp(0.0, 255.0);
+    // no_stdではf64::roundが使えないので0.5を足して切り捨てる
+    (value + 0.5) as u8
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Length {
+    Auto,
+    Px(f64),
+    Percent(f64),
+    // パーセントを含むため計算値の段階では解決できないcalc()
+    Calc(Rc<CalcExpression>),
+}
+
+impl Length {
+    // autoの場合はNoneを返す
+    pub fn resolve(&self, base: f64) -> Option<f64> {
+        match self {
+            Length::Auto => None,
+            Length::Px(px) => Some(*px),
+            Length::Percent(percent) => Some(base * percent / 100.0),
+            Length::Calc(expression) => Some(expression.evaluate(base)),
+        }
+    }
+
+    pub fn resolve_or_zero(&self, base: f64) -> f64 {
+        self.resolve(base).unwrap_or(0.0)
+    }
+
+    pub fn is_auto(&self) -> bool {
+        matches!(self, Length::Auto)
+    }
+
+    pub fn is_negative(&self) -> bool {
+        match self {
+            Length::Px(v) | Length::Percent(v) => *v < 0.0,
+            Length::Auto | Length::Calc(_) => false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LengthContext {
+    pub font_size: f64,
+    pub root_font_size: f64,
+    pub viewport_width: f64,
+    pub viewport_height: f64,
+}
+
+impl Default for LengthContext {
+    fn default() -> Self {
+        Self {
+            font_size: 16.0,
+            root_font_size: 16.0,
+            viewport_width: 600.0,
+            viewport_height: 400.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CalcType {
+    Number,
+    Length,
+}
+
+// calc()などの数式。長さの単位はパース時にpxへ変換する
+#[derive(Clone, Debug, PartialEq)]
+pub enum CalcExpression {
+    Number(f64),
+    Px(f64),
+    Percent(f64),
+    Sum(Vec<CalcExpression>),
+    Negate(Box<CalcExpression>),
+    Product(Box<CalcExpression>, Box<CalcExpression>),
+    Quotient(Box<CalcExpression>, Box<CalcExpression>),
+    Min(Vec<CalcExpression>),
+    Max(Vec<CalcExpression>),
+    Clamp(
+        Box<CalcExpression>,
+        Box<CalcExpression>,
+        Box<CalcExpression>,
+    ),
+}
+
+impl CalcExpression {
+    // パーセントはpercent_baseに対する割合として評価する
+    // 途中の無限大はそのまま計算し、最後にNaNは0、無限大は有限の最大値にする
+    pub fn evaluate(&self, percent_base: f64) -> f64 {
+        let value = self.evaluate_term(percent_base);
+        if value.is_nan() {
+            0.0
+        } else {
+            value.clamp(f64::MIN, f64::MAX)
+        }
+    }
+
+    fn evaluate_term(&self, percent_base: f64) -> f64 {
+        match self {
+            CalcExpression::Number(n) => *n,
+            CalcExpression::Px(px) => *px,
+            CalcExpression::Percent(p) => percent_base * p / 100.0,
+            CalcExpression::Sum(terms) => terms.iter().map(|t| t.evaluate_term(percent_base)).sum(),
+            CalcExpression::Negate(e) => -e.evaluate_term(percent_base),
+            CalcExpression::Product(a, b) => {
+                a.evaluate_term(percent_base) * b.evaluate_term(percent_base)
+            }
+            CalcExpression::Quotient(a, b) => {
+                a.evaluate_term(percent_base) / b.evaluate_term(percent_base)
+            }
+            CalcExpression::Min(args) => args
+                .iter()
+                .map(|a| a.evaluate_term(percent_base))
+                .fold(f64::INFINITY, f64::min),
+            CalcExpression::Max(args) => args
+                .iter()
+                .map(|a| a.evaluate_term(percent_base))
+                .fold(f64::NEG_INFINITY, f64::max),
+            CalcExpression::Clamp(min, value, max) => {
+                let min = min.evaluate_term(percent_base);
+                let max = max.evaluate_term(percent_base);
+                // minとmaxが逆転している場合はminを優先する
+                value.evaluate_term(percent_base).min(max).max(min)
+            }
+        }
+    }
+
+    fn contains_percent(&self) -> bool {
+        match self {
+            CalcExpression::Number(_) | CalcExpression::Px(_) => false,
+            CalcExpression::Percent(_) => true,
+            CalcExpression::Negate(e) => e.contains_percent(),
+            CalcExpression::Product(a, b) | CalcExpression::Quotient(a, b) => {
+                a.contains_percent() || b.contains_percent()
+            }
+            CalcExpression::Sum(args) | CalcExpression::Min(args) | CalcExpression::Max(args) => {
+                args.iter().any(|a| a.contains_percent())
+            }
+            CalcExpression::Clamp(a, b, c) => {
+                a.contains_percent() || b.contains_percent() || c.contains_percent()
+            }
+        }
+    }
+}
+
+fn is_math_function(name: &str) -> bool {
+    matches!(
+        name.to_ascii_lowercase().as_str(),
+        "calc" | "min" | "max" | "clamp"
+    )
+}
+
+struct CalcParser<'a> {
+    values: Vec<&'a ComponentValue>,
+    position: usize,
+    context: &'a LengthContext,
+}
+
+impl<'a> CalcParser<'a> {
+    fn new(values: &'a [ComponentValue], context: &'a LengthContext) -> Self {
+        Self {
+            values: non_whitespace(values),
+            position: 0,
+            context,
+        }
+    }
+
+    // 入力をすべて消費した場合のみ成功とする
+    fn parse(mut self) -> Option<(CalcExpression, CalcType)> {
+        let result = self.parse_sum()?;
+        if self.position != self.values.len() {
+            return None;
+        }
+        Some(result)
+    }
+
+    fn next_delim(&self) -> Option<char> {
+        match self.values.get(self.position) {
+            Some(ComponentValue::Token(CssTokenKind::Delim(ch))) => Some(*ch),
+            _ => None,
+        }
+    }
+
+    fn parse_sum(&mut self) -> Option<(CalcExpression, CalcType)> {
+        let (first, calc_type) = self.parse_product()?;
+        let mut terms = vec![first];
+        while let Some(op @ ('+' | '-')) = self.next_delim() {
+            self.position += 1;
+            let (term, term_type) = self.parse_product()?;
+            if term_type != calc_type {
+                return None;
+            }
+            if op == '-' {
+                terms.push(CalcExpression::Negate(Box::new(term)));
+            } else {
+                terms.push(term);
+            }
+        }
+
+        if terms.len() == 1 {
+            return Some((terms.remove(0), calc_type));
+        }
+        Some((CalcExpression::Sum(terms), calc_type))
+    }
+
+    fn parse_product(&mut self) -> Option<(CalcExpression, CalcType)> {
+        let (mut expression, mut calc_type) = self.parse_value()?;
+        while let Some(op @ ('*' | '/')) = self.next_delim() {
+            self.position += 1;
+            let (rhs, rhs_type) = self.parse_value()?;
+            if op == '*' {
+                // 長さ同士の掛け算はできない
+                calc_type = match (calc_type, rhs_type) {
+                    (CalcType::Number, t) | (t, CalcType::Number) => t,
+                    _ => return None,
+                };
+                expression = CalcExpression::Product(Box::new(expression), Box::new(rhs));
+            } else {
+                if rhs_type != CalcType::Number {
+                    return None;
+                }
+                expression = CalcExpression::Quotient(Box::new(expression), Box::new(rhs));
+            }
+        }
+        Some((expression, calc_type))
+    }
+
+    fn parse_value(&mut self) -> Option<(CalcExpression, CalcType)> {
+        let value = *self.values.get(self.position)?;
+        self.position += 1;
+
+        match value {
+            ComponentValue::Token(CssTokenKind::Number { value, .. }) => {
+                Some((CalcExpression::Number(*value), CalcType::Number))
+            }
+            ComponentValue::Token(CssTokenKind::Percentage(value)) => {
+                Some((CalcExpression::Percent(*value), CalcType::Length))
+            }
+            ComponentValue::Token(CssTokenKind::Dimension { value, unit, .. }) => {
+                let px = absolute_length(*value, unit, self.context)?;
+                Some((CalcExpression::Px(px), CalcType::Length))
+            }
+            ComponentValue::Token(CssTokenKind::Ident(name)) => {
+                let number = match name.to_ascii_lowercase().as_str() {
+                    "pi" => core::f64::consts::PI,
+                    "e" => core::f64::consts::E,
+                    "infinity" => f64::INFINITY,
+                    "-infinity" => f64::NEG_INFINITY,
+                    "nan" => f64::NAN,
+                    _ => return None,
+                };
+                Some((CalcExpression::Number(number), CalcType::Number))
+            }
+            ComponentValue::SimpleBlock {
+                associated: '(',
+                value,
+            } => CalcParser::new(value, self.context).parse(),
+            ComponentValue::Function { name, value } => {
+                parse_math_function(name, value, self.context)
+            }
+            _ => None,
+        }
+    }
+}
+
+fn parse_math_function(
+    name: &str,
+    arguments: &[ComponentValue],
+    context: &LengthContext,
+) -> Option<(CalcExpression, CalcType)> {
+    let name = name.to_ascii_lowercase();
+    if name == "calc" {
+        return CalcParser::new(arguments, context).parse();
+    }
+
+    let mut args = Vec::new();
+    let mut calc_type = None;
+    for argument in arguments.split(|v| v.is_token(&CssTokenKind::Comma)) {
+        let (expression, t) = CalcParser::new(argument, context).parse()?;
+        if calc_type.is_some_and(|calc_type| calc_type != t) {
+            return None;
+        }
+        calc_type = Some(t);
+        args.push(expression);
+    }
+    let calc_type = calc_type?;
+
+    let expression = match name.as_str() {
+        "min" => CalcExpression::Min(args),
+        "max" => CalcExpression::Max(args),
+        "clamp" if args.len() == 3 => {
+            let max = args.pop()?;
+            let value = args.pop()?;
+            let min = args.pop()?;
+            CalcExpression::Clamp(Box::new(min), Box::new(value), Box::new(max))
+        }
+        _ => return None,
+    };
+    Some((expression, calc_type))
+}
+
+pub fn non_whitespace(values: &[ComponentValue]) -> Vec<&ComponentValue> {
+    values.iter().filter(|v| !v.is_whitespace()).collect()
+}
+
+pub fn parse_keyword(values: &[ComponentValue]) -> Option<String> {
+    let values = non_whitespace(values);
+    if values.len() != 1 {
+        return None;
+    }
+    values[0].ident().map(|s| s.to_ascii_lowercase())
+}
+
+pub fn parse_angle(value: &ComponentValue) -> Option<f64> {
+    match value {
+        ComponentValue::Token(CssTokenKind::Number { value, .. }) => Some(*value),
+        ComponentValue::Token(CssTokenKind::Dimension { value, unit, .. }) => {
+            let degrees = match unit.to_ascii_lowercase().as_str() {
+                "deg" => *value,
+                "rad" => value.to_degrees(),
+                "grad" => value * 0.9,
+                "turn" => value * 360.0,
+                _ => return None,
+            };
+            Some(degrees)
+        }
+        ComponentValue::Token(CssTokenKind::Ident(name)) if name.eq_ignore_ascii_case("none") => {
+            Some(0.0)
+        }
+        _ => None,
+    }
+}
+
+// 数値はそのまま、パーセントはscaleに対する割合として返す
+fn parse_number_or_percentage(value: &ComponentValue, scale: f64) -> Option<f64> {
+    match value {
+        ComponentValue::Token(CssTokenKind::Number { value, .. }) => Some(*value),
+        ComponentValue::Token(CssTokenKind::Percentage(value)) => Some(scale * value / 100.0),
+        ComponentValue::Token(CssTokenKind::Ident(name)) if name.eq_ignore_ascii_case("none") => {
+            Some(0.0)
+        }
+        ComponentValue::Function { name, value } if is_math_function(name) => {
+            match parse_math_function(name, value, &LengthContext::default())? {
+                (expression, CalcType::Number) => Some(expression.evaluate(0.0)),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+// rgb(1, 2, 3)のようなカンマ区切りとrgb(1 2 3 / 50%)のような空白区切りの両方を受け付ける
+fn color_function_arguments(
+    arguments: &[ComponentValue],
+) -> Option<(Vec<&ComponentValue>, Option<&ComponentValue>)> {
+    let values = non_whitespace(arguments);
+    if values.iter().any(|v| v.is_token(&CssTokenKind::Comma)) {
+        let mut channels = Vec::new();
+        for (i, value) in values.iter().enumerate() {
+            let is_comma = value.is_token(&CssTokenKind::Comma);
+            if is_comma != (i % 2 == 1) {
+                return None;
+            }
+            if !is_comma {
+                channels.push(*value);
+            }
+        }
+        if values.len() % 2 == 0 {
+            return None;
+        }
+        return match channels.len() {
+            3 => Some((channels, None)),
+            4 => {
+                let alpha = channels.pop();
+                Some((channels, alpha))
+            }
+            _ => None,
+        };
+    }
+
+    match values.as_slice() {
+        [a, b, c] => Some((vec![*a, *b, *c], None)),
+        [a, b, c, slash, alpha] if slash.is_token(&CssTokenKind::Delim('/')) => {
+            Some((vec![*a, *b, *c], Some(*alpha)))
+        }
+        _ => None,
+    }
+}
+
+fn parse_alpha(alpha: Option<&ComponentValue>) -> Option<f64> {
+    match alpha {
+        Some(alpha) => Some(parse_number_or_percentage(alpha, 1.0)?.clamp(0.0, 1.0)),
+        None => Some(1.0),
+    }
+}
+
+fn parse_color_function(name: &str, arguments: &[ComponentValue]) -> Option<Color> {
+    let (channels, alpha) = color_function_arguments(arguments)?;
+    let alpha = parse_alpha(alpha)?;
+
+    match name.to_ascii_lowercase().as_str() {
+        "rgb" | "rgba" => {
+            let r = parse_number_or_percentage(channels[0], 255.0)?;
+            let g = parse_number_or_percentage(channels[1], 255.0)?;
+            let b = parse_number_or_percentage(channels[2], 255.0)?;
+            Some(Color::rgba(
+                to_channel(r),
+                to_channel(g),
+                to_channel(b),
+                to_channel(alpha * 255.0),
+            ))
+        }
+        "hsl" | "hsla" => {
+            let hue = parse_angle(channels[0])?;
+            let saturation = parse_number_or_percentage(channels[1], 100.0)?;
+            let lightness = parse_number_or_percentage(channels[2], 100.0)?;
+            Some(Color::from_hsl(
+                hue,
+                saturation / 100.0,
+                lightness / 100.0,
+                alpha,
+            ))
+        }
+        _ => None,
+    }
+}
+
+pub fn parse_color(value: &ComponentValue, current_color: Color) -> Option<Color> {
+    match value {
+        ComponentValue::Token(CssTokenKind::Ident(name)) => {
+            if name.eq_ignore_ascii_case("currentcolor") {
+                return Some(current_color);
+            }
+            Color::from_name(name)
+        }
+        ComponentValue::Token(CssTokenKind::Hash { value, is_id: _ }) => Color::from_hex(value),
+        ComponentValue::Function { name, value } => parse_color_function(name, value),
+        _ => None,
+    }
+}
+
+fn absolute_length(value: f64, unit: &str, context: &LengthContext) -> Option<f64> {
+    let px = match unit.to_ascii_lowercase().as_str() {
+        "px" => value,
+        "em" => value * context.font_size,
+        "rem" => value * context.root_font_size,
+        // 字形を測れないため0.5emとして扱う
+        "ch" | "ex" => value * context.font_size * 0.5,
+        "vw" => value * context.viewport_width / 100.0,
+        "vh" => value * context.viewport_height / 100.0,
+        "vmin" => value * context.viewport_width.min(context.viewport_height) / 100.0,
+        "vmax" => value * context.viewport_width.max(context.viewport_height) / 100.0,
+        "pt" => value * 96.0 / 72.0,
+        "pc" => value * 16.0,
+        "in" => value * 96.0,
+        "cm" => value * 96.0 / 2.54,
+        "mm" => value * 96.0 / 25.4,
+        "q" => value * 96.0 / 101.6,
+        _ => return None,
+    };
+    Some(px)
+}
+
+pub fn parse_length(value: &ComponentValue, context: &LengthContext) -> Option<Length> {
+    match value {
+        ComponentValue::Token(CssTokenKind::Dimension {
+            value,
+            is_integer: _,
+            unit,
+        }) => Some(Length::Px(absolute_length(*value, unit, context)?)),
+        ComponentValue::Token(CssTokenKind::Percentage(value)) => Some(Length::Percent(*value)),
+        // 単位のない0のみ長さとして扱う
+        ComponentValue::Token(CssTokenKind::Number {
+            value,
+            is_integer: _,
+        }) if *value == 0.0 => Some(Length::Px(0.0)),
+        ComponentValue::Token(CssTokenKind::Ident(name)) if name.eq_ignore_ascii_case("auto") => {
+            Some(Length::Auto)
+        }
+        ComponentValue::Function { name, value } if is_math_function(name) => {
+            let (expression, calc_type) = parse_math_function(name, value, context)?;
+            if calc_type != CalcType::Length {
+                return None;
+            }
+            if expression.contains_percent() {
+                Some(Length::Calc(Rc::new(expression)))
+            } else {
+                Some(Length::Px(expression.evaluate(0.0)))
+            }
+        }
+        _ => None,
+    }
+}
+
+pub fn parse_number(value: &ComponentValue) -> Option<f64> {
+    match value {
+        ComponentValue::Token(CssTokenKind::Number {
+            value,
+            is_integer: _,
+        }) => Some(*value),
+        ComponentValue::Function { name, value } if is_math_function(name) => {
+            match parse_math_function(name, value, &LengthContext::default())? {
+                (expression, CalcType::Number) => Some(expression.evaluate(0.0)),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::css::{parser::CssParser, token::CssTokenizer};
+    use alloc::string::ToString;
+
+    fn value(s: &str) -> ComponentValue {
+        let values = CssParser::new(CssTokenizer::new(s.to_string())).parse_component_values();
+        let values = non_whitespace(&values);
+        assert_eq!(1, values.len());
+        values[0].clone()
+    }
+
+    fn color(s: &str) -> Option<Color> {
+        parse_color(&value(s), Color::rgb(1, 2, 3))
+    }
+
+    fn length(s: &str) -> Option<Length> {
+        let context = LengthContext {
+            font_size: 20.0,
+            root_font_size: 10.0,
+            viewport_width: 800.0,
+            viewport_height: 600.0,
+        };
+        parse_length(&value(s), &context)
+    }
+
+    #[test]
+    fn test_named_and_hex_colors() {
+        assert_eq!(Some(Color::rgb(102, 51, 153)), color("RebeccaPurple"));
+        assert_eq!(Some(Color::rgb(240, 248, 255)), color("aliceblue"));
+        assert_eq!(Some(Color::TRANSPARENT), color("transparent"));
+        assert_eq!(Some(Color::rgb(1, 2, 3)), color("currentColor"));
+        assert_eq!(None, color("notacolor"));
+        assert_eq!(Some(Color::rgb(0xff, 0x00, 0xcc)), color("#f0c"));
+        assert_eq!(Some(Color::rgba(0xff, 0x00, 0xcc, 0x88)), color("#f0c8"));
+        assert_eq!(
+            Some(Color::rgba(0x12, 0x34, 0x56, 0x78)),
+            color("#12345678")
+        );
+        assert_eq!(None, color("#12345"));
+    }
+
+    #[test]
+    fn test_color_functions() {
+        assert_eq!(Some(Color::rgb(255, 0, 128)), color("rgb(255, 0, 128)"));
+        assert_eq!(
+            Some(Color::rgba(255, 0, 128, 128)),
+            color("rgba(255, 0, 128, 0.5)")
+        );
+        assert_eq!(
+            Some(Color::rgba(255, 128, 0, 64)),
+            color("rgb(100% 50% 0% / 25%)")
+        );
+        assert_eq!(Some(Color::rgb(255, 255, 0)), color("rgb(300 255 -10)"));
+        assert_eq!(None, color("rgb(1, 2)"));
+        assert_eq!(None, color("rgb(1, 2 3)"));
+
+        assert_eq!(Some(Color::rgb(255, 0, 0)), color("hsl(0, 100%, 50%)"));
+        assert_eq!(Some(Color::rgb(0, 255, 0)), color("hsl(120deg 100% 50%)"));
+        assert_eq!(
+            Some(Color::rgb(0, 0, 255)),
+            color("hsl(0.6667turn 100% 50%)")
+        );
+        assert_eq!(
+            Some(Color::rgba(128, 128, 128, 51)),
+            color("hsla(-30, 0%, 50%, .2)")
+        );
+        assert_eq!(Some(Color::rgb(255, 255, 255)), color("hsl(0 0% 100%)"));
+    }
+
+    #[test]
+    fn test_lengths() {
+        assert_eq!(Some(Length::Px(12.0)), length("12px"));
+        assert_eq!(Some(Length::Px(30.0)), length("1.5em"));
+        assert_eq!(Some(Length::Px(20.0)), length("2rem"));
+        assert_eq!(Some(Length::Px(10.0)), length("1ch"));
+        assert_eq!(Some(Length::Px(80.0)), length("10vw"));
+        assert_eq!(Some(Length::Px(60.0)), length("10vh"));
+        assert_eq!(Some(Length::Px(96.0)), length("1in"));
+        assert_eq!(Some(Length::Percent(50.0)), length("50%"));
+        assert_eq!(Some(Length::Px(0.0)), length("0"));
+        assert_eq!(Some(Length::Auto), length("auto"));
+        assert_eq!(None, length("12"));
+        assert_eq!(None, length("12foo"));
+        assert_eq!(Some(40.0), Length::Percent(50.0).resolve(80.0));
+        assert_eq!(None, Length::Auto.resolve(80.0));
+    }
+
+    #[test]
+    fn test_calc() {
+        assert_eq!(Some(Length::Px(30.0)), length("calc(10px + 1em)"));
+        assert_eq!(Some(Length::Px(5.0)), length("calc((10px + 20px) / 6)"));
+        assert_eq!(Some(Length::Px(70.0)), length("calc(10px + 2 * 3 * 10px)"));
+        assert_eq!(Some(Length::Px(-10.0)), length("calc(10px - 20px)"));
+        assert_eq!(Some(Length::Px(10.0)), length("min(10px, 2em, 1rem)"));
+        assert_eq!(Some(Length::Px(20.0)), length("max(10px, 1em)"));
+        assert_eq!(Some(Length::Px(15.0)), length("clamp(15px, 10px, 20px)"));
+        assert_eq!(
+            Some(Length::Px(20.0)),
+            length("clamp(15px, calc(5em), 20px)")
+        );
+
+        let calc = length("calc(100% - 2 * 10px)").unwrap();
+        assert!(matches!(calc, Length::Calc(_)));
+        assert_eq!(Some(180.0), calc.resolve(200.0));
+        let clamp = length("clamp(100px, 50%, 300px)").unwrap();
+        assert_eq!(Some(100.0), clamp.resolve(100.0));
+        assert_eq!(Some(150.0), clamp.resolve(300.0));
+        assert_eq!(Some(300.0), clamp.resolve(1000.0));
+
+        // 結果の無限大は有限の範囲に収め、NaNは0にする
+        assert_eq!(Some(Length::Px(f64::MAX)), length("calc(1px / 0)"));
+        assert_eq!(Some(Length::Px(f64::MIN)), length("calc(-1px / 0)"));
+        assert_eq!(Some(Length::Px(f64::MAX)), length("calc(1e308px * 10)"));
+        assert_eq!(Some(Length::Px(0.0)), length("calc(1px / (1 / 0))"));
+        assert_eq!(Some(Length::Px(0.0)), length("calc((1px / 0) - (1px / 0))"));
+        let infinite = length("calc(100% / 0)").unwrap();
+        assert_eq!(Some(f64::MAX), infinite.resolve(100.0));
+
+        assert_eq!(None, length("calc(10px * 10px)"));
+        assert_eq!(None, length("calc(10px + 2)"));
+        assert_eq!(None, length("calc(10px / 2px)"));
+        assert_eq!(None, length("calc(10px 20px)"));
+        assert_eq!(None, length("calc(2 * 3)"));
+        assert_eq!(None, length("clamp(1px, 2px)"));
+
+        assert_eq!(Some(6.0), parse_number(&value("calc(2 * 3)")));
+        assert_eq!(Some(1.5), parse_number(&value("max(1, 1.5)")));
+        assert_eq!(None, parse_number(&value("calc(2px * 3)")));
+    }
+}
@@ -0,0 +1,687 @@
+use crate::{
+    error::Error,
+    renderer::{
+        css::computed_style::{ComputedStyle, Display, Visibility, WhiteSpace},
+        dom::{
+            mutation::{
+                queue_attribute_record, queue_character_data_record, queue_child_list_record,
+            },
+            node::{ElementKind, Node, NodeKind},
+        },
+    },
+};
+use alloc::{
+    format,
+    rc::{Rc, Weak},
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::cell::RefCell;
+
+fn is_inclusive_ancestor(ancestor: &Rc<RefCell<Node>>, node: &Rc<RefCell<Node>>) -> bool {
+    let mut current = Some(node.clone());
+    while let Some(n) = current {
+        if Rc::ptr_eq(&n, ancestor) {
+            return true;
+        }
+        current = n.borrow().parent().upgrade();
+    }
+    false
+}
+
+fn detach(node: &Rc<RefCell<Node>>) {
+    let Some(parent) = node.borrow().parent().upgrade() else {
+        return;
+    };
+
+    let previous = node.borrow().previous_sibling().upgrade();
+    let next = node.borrow().next_sibling();
+
+    match &previous {
+        Some(previous) => previous.borrow_mut().set_next_sibling(next.clone()),
+        None => parent.borrow_mut().set_first_child(next.clone()),
+    }
+    match &next {
+        Some(next) => next
+            .borrow_mut()
+            .set_previous_sibling(previous.as_ref().map(Rc::downgrade).unwrap_or_default()),
+        None => parent
+            .borrow_mut()
+            .set_last_child(previous.as_ref().map(Rc::downgrade).unwrap_or_default()),
+    }
+
+    let mut node = node.borrow_mut();
+    node.set_parent(Weak::new());
+    node.set_previous_sibling(Weak::new());
+    node.set_next_sibling(None);
+}
+
+fn attach(
+    parent: &Rc<RefCell<Node>>,
+    child: &Rc<RefCell<Node>>,
+    reference: Option<&Rc<RefCell<Node>>>,
+) {
+    let previous = match reference {
+        Some(reference) => reference.borrow().previous_sibling().upgrade(),
+        None => parent.borrow().last_child().upgrade(),
+    };
+
+    match &previous {
+        Some(previous) => previous.borrow_mut().set_next_sibling(Some(child.clone())),
+        None => parent.borrow_mut().set_first_child(Some(child.clone())),
+    }
+    match reference {
+        Some(reference) => reference
+            .borrow_mut()
+            .set_previous_sibling(Rc::downgrade(child)),
+        None => parent.borrow_mut().set_last_child(Rc::downgrade(child)),
+    }
+
+    let mut child_mut = child.borrow_mut();
+    child_mut.set_parent(Rc::downgrade(parent));
+    child_mut.set_previous_sibling(previous.as_ref().map(Rc::downgrade).unwrap_or_default());
+    child_mut.set_next_sibling(reference.cloned());
+    child_mut.set_window(parent.borrow().window());
+}
+
+pub fn children(node: &Rc<RefCell<Node>>) -> Vec<Rc<RefCell<Node>>> {
+    let mut children = Vec::new();
+    let mut child = node.borrow().first_child();
+    while let Some(c) = child {
+        child = c.borrow().next_sibling();
+        children.push(c);
+    }
+    children
+}
+
+pub fn insert_before(
+    parent: &Rc<RefCell<Node>>,
+    child: &Rc<RefCell<Node>>,
+    reference: Option<&Rc<RefCell<Node>>>,
+) -> bool {
+    if is_inclusive_ancestor(child, parent) {
+        return false;
+    }
+    if let Some(reference) = reference {
+        let is_child = match reference.borrow().parent().upgrade() {
+            Some(p) => Rc::ptr_eq(&p, parent),
+            None => false,
+        };
+        if !is_child || Rc::ptr_eq(reference, child) {
+            return false;
+        }
+    }
+
+    if child.borrow().parent().upgrade().is_some() {
+        remove_child_internal(child);
+    }
+
+    attach(parent, child, reference);
+
+    let previous_sibling = child.borrow().previous_sibling().upgrade();
+    let next_sibling = child.borrow().next_sibling();
+    queue_child_list_record(
+        parent,
+        vec![child.clone()],
+        Vec::new(),
+        previous_sibling,
+        next_sibling,
+    );
+
+    true
+}
+
+pub fn append_child(parent: &Rc<RefCell<Node>>, child: &Rc<RefCell<Node>>) -> bool {
+    insert_before(parent, child, None)
+}
+
+fn remove_child_internal(child: &Rc<RefCell<Node>>) {
+    let Some(parent) = child.borrow().parent().upgrade() else {
+        return;
+    };
+    let previous_sibling = child.borrow().previous_sibling().upgrade();
+    let next_sibling = child.borrow().next_sibling();
+
+    detach(child);
+
+    queue_child_list_record(
+        &parent,
+        Vec::new(),
+        vec![child.clone()],
+        previous_sibling,
+        next_sibling,
+    );
+}
+
+pub fn remove_child(parent: &Rc<RefCell<Node>>, child: &Rc<RefCell<Node>>) -> bool {
+    match child.borrow().parent().upgrade() {
+        Some(p) if Rc::ptr_eq(&p, parent) => {}
+        _ => return false,
+    }
+
+    remove_child_internal(child);
+    true
+}
+
+pub fn set_attribute(node: &Rc<RefCell<Node>>, name: &str, value: &str) -> bool {
+    let old_value = match &mut node.borrow_mut().kind {
+        NodeKind::Element(element) => element.set_attribute(name, value),
+        _ => return false,
+    };
+
+    queue_attribute_record(node, name, old_value);
+    true
+}
+
+pub fn remove_attribute(node: &Rc<RefCell<Node>>, name: &str) -> bool {
+    let old_value = match &mut node.borrow_mut().kind {
+        NodeKind::Element(element) => element.remove_attribute(name),
+        _ => return false,
+    };
+
+    // 存在しない属性の削除は変更として扱わない
+    if old_value.is_none() {
+        return false;
+    }
+
+    queue_attribute_record(node, name, old_value);
+    true
+}
+
+pub fn set_character_data(node: &Rc<RefCell<Node>>, data: &str) -> bool {
+    let old_value = match &mut node.borrow_mut().kind {
+        NodeKind::Text(text) => core::mem::replace(text, String::from(data)),
+        _ => return false,
+    };
+
+    queue_character_data_record(node, old_value);
+    true
+}
+
+fn character_data(node: &Rc<RefCell<Node>>) -> Result<String, Error> {
+    match &node.borrow().kind {
+        NodeKind::Text(text) => Ok(text.clone()),
+        kind => Err(Error::UnexpectedInput(format!(
+            "{:?} is not a character data node",
+            kind
+        ))),
+    }
+}
+
+pub fn length(node: &Rc<RefCell<Node>>) -> usize {
+    match &node.borrow().kind {
+        NodeKind::Text(text) => text.chars().count(),
+        _ => children(node).len(),
+    }
+}
+
+pub fn substring_data(
+    node: &Rc<RefCell<Node>>,
+    offset: usize,
+    count: usize,
+) -> Result<String, Error> {
+    let data = character_data(node)?;
+    let length = data.chars().count();
+    if offset > length {
+        return Err(Error::UnexpectedInput(format!(
+            "offset {} is greater than the length {}",
+            offset, length
+        )));
+    }
+
+    Ok(data.chars().skip(offset).take(count).collect())
+}
+
+pub fn replace_data(
+    node: &Rc<RefCell<Node>>,
+    offset: usize,
+    count: usize,
+    data: &str,
+) -> Result<(), Error> {
+    let old_data = character_data(node)?;
+    let length = old_data.chars().count();
+    if offset > length {
+        return Err(Error::UnexpectedInput(format!(
+            "offset {} is greater than the length {}",
+            offset, length
+        )));
+    }
+
+    let count = count.min(length - offset);
+    let mut new_data: String = old_data.chars().take(offset).collect();
+    new_data.push_str(data);
+    new_data.extend(old_data.chars().skip(offset + count));
+
+    set_character_data(node, &new_data);
+    Ok(())
+}
+
+pub fn append_data(node: &Rc<RefCell<Node>>, data: &str) -> Result<(), Error> {
+    let length = length(node);
+    replace_data(node, length, 0, data)
+}
+
+pub fn insert_data(node: &Rc<RefCell<Node>>, offset: usize, data: &str) -> Result<(), Error> {
+    replace_data(node, offset, 0, data)
+}
+
+pub fn delete_data(node: &Rc<RefCell<Node>>, offset: usize, count: usize) -> Result<(), Error> {
+    replace_data(node, offset, count, "")
+}
+
+pub fn split_text(node: &Rc<RefCell<Node>>, offset: usize) -> Result<Rc<RefCell<Node>>, Error> {
+    let length = length(node);
+    let new_data = substring_data(node, offset, length - offset.min(length))?;
+    let new_node = Rc::new(RefCell::new(Node::new(NodeKind::Text(new_data))));
+
+    let parent = node.borrow().parent().upgrade();
+    if let Some(parent) = parent {
+        let next_sibling = node.borrow().next_sibling();
+        insert_before(&parent, &new_node, next_sibling.as_ref());
+    }
+
+    replace_data(node, offset, length - offset, "")?;
+    Ok(new_node)
+}
+
+fn is_text(node: &Rc<RefCell<Node>>) -> bool {
+    matches!(node.borrow().kind, NodeKind::Text(_))
+}
+
+pub fn normalize(node: &Rc<RefCell<Node>>) {
+    let mut child = node.borrow().first_child();
+
+    while let Some(current) = child {
+        if !is_text(&current) {
+            normalize(&current);
+            child = current.borrow().next_sibling();
+            continue;
+        }
+
+        if length(&current) == 0 {
+            child = current.borrow().next_sibling();
+            remove_child(node, &current);
+            continue;
+        }
+
+        // 連続するテキストノードを1つにまとめる
+        let mut data = String::new();
+        let mut next = current.borrow().next_sibling();
+        while let Some(sibling) = next.clone() {
+            if !is_text(&sibling) {
+                break;
+            }
+            if let NodeKind::Text(text) = &sibling.borrow().kind {
+                data.push_str(text);
+            }
+            next = sibling.borrow().next_sibling();
+            remove_child(node, &sibling);
+        }
+
+        if !data.is_empty() {
+            // append_dataは文字データノードに対して失敗しない
+            let _ = append_data(&current, &data);
+        }
+        child = next;
+    }
+}
+
+pub fn set_text_content(node: &Rc<RefCell<Node>>, value: &str) {
+    if is_text(node) {
+        set_character_data(node, value);
+        return;
+    }
+
+    if node.borrow().element_kind().is_none() {
+        return;
+    }
+
+    for child in children(node) {
+        remove_child(node, &child);
+    }
+
+    if !value.is_empty() {
+        let text = Rc::new(RefCell::new(Node::new(NodeKind::Text(value.to_string()))));
+        append_child(node, &text);
+    }
+}
+
+enum RenderedTextItem {
+    Text(String),
+    // white-spaceで空白を保つテキスト
+    PreservedText(String),
+    // 表の最後ではないセルの後に置くタブ
+    Tab,
+    RequiredLineBreak(usize),
+}
+
+// スタイルが計算されていない木では要素の種類から判断する
+fn required_line_breaks(kind: ElementKind) -> usize {
+    match kind {
+        ElementKind::P => 2,
+        ElementKind::H1 | ElementKind::H2 | ElementKind::H3 | ElementKind::Body => 1,
+        _ => 0,
+    }
+}
+
+fn is_rendered(kind: ElementKind) -> bool {
+    !matches!(
+        kind,
+        ElementKind::Head | ElementKind::Style | ElementKind::Script
+    )
+}
+
+// 計算済みのdisplayから求める、要素の前後に必要な改行の数。HTML 3.2.7 rendered text collection steps
+fn required_line_breaks_for_display(kind: ElementKind, display: Display) -> usize {
+    match display {
+        _ if kind == ElementKind::P => 2,
+        Display::Block
+        | Display::FlowRoot
+        | Display::ListItem
+        | Display::Flex
+        | Display::Grid
+        | Display::Table
+        | Display::TableCaption
+        | Display::TableRow => 1,
+        _ => 0,
+    }
+}
+
+// 後ろの兄弟にtable-cellのボックスがあるセル
+fn has_next_cell(node: &Rc<RefCell<Node>>) -> bool {
+    let mut sibling = node.borrow().next_sibling();
+    while let Some(current) = sibling {
+        if current
+            .borrow()
+            .computed_style()
+            .is_some_and(|style| style.display == Display::TableCell)
+        {
+            return true;
+        }
+        sibling = current.borrow().next_sibling();
+    }
+    false
+}
+
+fn collect_rendered_text(
+    node: &Rc<RefCell<Node>>,
+    parent_style: Option<&ComputedStyle>,
+    items: &mut Vec<RenderedTextItem>,
+) {
+    let kind = node.borrow().kind();
+    match kind {
+        NodeKind::Text(text) => {
+            let white_space = match parent_style {
+                Some(style) if style.visibility != Visibility::Visible => return,
+                Some(style) => style.white_space,
+                None => WhiteSpace::Normal,
+            };
+            match white_space {
+                WhiteSpace::Pre | WhiteSpace::PreWrap | WhiteSpace::BreakSpaces => {
+                    items.push(RenderedTextItem::PreservedText(text));
+                    return;
+                }
+                _ => {}
+            }
+            let mut collapsed = String::new();
+            for ch in text.chars() {
+                if ch == '\n' && white_space == WhiteSpace::PreLine {
+                    while collapsed.ends_with(' ') {
+                        collapsed.pop();
+                    }
+                    collapsed.push('\n');
+                } else if ch.is_ascii_whitespace() {
+                    if !collapsed.ends_with(' ') && !collapsed.ends_with('\n') {
+                        collapsed.push(' ');
+                    }
+                } else {
+                    collapsed.push(ch);
+                }
+            }
+            items.push(RenderedTextItem::Text(collapsed));
+        }
+        NodeKind::Element(element) => {
+            let style = node.borrow().computed_style();
+            let breaks = match &style {
+                Some(style) if style.display == Display::None => return,
+                Some(style) => required_line_breaks_for_display(element.kind(), style.display),
+                None if !is_rendered(element.kind()) => return,
+                None => required_line_breaks(element.kind()),
+            };
+            if breaks > 0 {
+                items.push(RenderedTextItem::RequiredLineBreak(breaks));
+            }
+            // スタイルのない要素の中では親のスタイルを引き継ぐ
+            let style = style.as_ref().or(parent_style);
+            for child in children(node) {
+                collect_rendered_text(&child, style, items);
+            }
+            if style.is_some_and(|style| style.display == Display::TableCell) && has_next_cell(node)
+            {
+                items.push(RenderedTextItem::Tab);
+            }
+            if breaks > 0 {
+                items.push(RenderedTextItem::RequiredLineBreak(breaks));
+            }
+        }
+        NodeKind::Document => {
+            for child in children(node) {
+                collect_rendered_text(&child, None, items);
+            }
+        }
+    }
+}
+
+pub fn inner_text(node: &Rc<RefCell<Node>>) -> String {
+    if is_text(node) {
+        return character_data(node).unwrap_or_default();
+    }
+
+    let style = node.borrow().computed_style();
+    let mut items = Vec::new();
+    for child in children(node) {
+        collect_rendered_text(&child, style.as_ref(), &mut items);
+    }
+
+    let mut result = String::new();
+    let mut pending_breaks = 0;
+    for item in items {
+        let (text, collapsible) = match &item {
+            RenderedTextItem::RequiredLineBreak(count) => {
+                pending_breaks = pending_breaks.max(*count);
+                continue;
+            }
+            RenderedTextItem::Text(text) => (text.as_str(), true),
+            RenderedTextItem::PreservedText(text) => (text.as_str(), false),
+            RenderedTextItem::Tab => {
+                // セルの末尾の空白は描画されない
+                while result.ends_with(' ') {
+                    result.pop();
+                }
+                ("\t", false)
+            }
+        };
+        let mut text = text;
+        if pending_breaks > 0 {
+            // 先頭と末尾の改行は出力しない
+            if !result.is_empty() {
+                while result.ends_with(' ') {
+                    result.pop();
+                }
+                result.push_str(&"\n".repeat(pending_breaks));
+            }
+            pending_breaks = 0;
+        }
+        if collapsible
+            && (result.is_empty()
+                || result.ends_with(' ')
+                || result.ends_with('\n')
+                || result.ends_with('\t'))
+        {
+            text = text.trim_start_matches(' ');
+        }
+        result.push_str(text);
+    }
+
+    while result.ends_with(' ') {
+        result.pop();
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::{
+        dom::{create_body, node::Element},
+        html::{parser::HtmlParser, token::HtmlTokenizer},
+        layout::style_document,
+    };
+
+    fn new_text(data: &str) -> Rc<RefCell<Node>> {
+        Rc::new(RefCell::new(Node::new(NodeKind::Text(data.to_string()))))
+    }
+
+    fn data(node: &Rc<RefCell<Node>>) -> String {
+        node.borrow().text_content().unwrap()
+    }
+
+    #[test]
+    fn test_character_data_operations() {
+        let text = new_text("hello");
+
+        append_data(&text, " world").unwrap();
+        assert_eq!("hello world", data(&text));
+
+        insert_data(&text, 5, ",").unwrap();
+        assert_eq!("hello, world", data(&text));
+
+        delete_data(&text, 0, 7).unwrap();
+        assert_eq!("world", data(&text));
+
+        replace_data(&text, 1, 100, "ide").unwrap();
+        assert_eq!("wide", data(&text));
+
+        assert_eq!("id", substring_data(&text, 1, 2).unwrap());
+        assert_eq!("", substring_data(&text, 4, 2).unwrap());
+        assert!(substring_data(&text, 5, 1).is_err());
+        assert!(insert_data(&text, 10, "x").is_err());
+    }
+
+    #[test]
+    fn test_multibyte_offsets() {
+        let text = new_text("あいう");
+        insert_data(&text, 1, "x").unwrap();
+        assert_eq!("あxいう", data(&text));
+        assert_eq!(4, length(&text));
+    }
+
+    #[test]
+    fn test_character_data_on_element() {
+        let body = create_body("<html><head></head><body></body></html>");
+        assert!(append_data(&body, "x").is_err());
+    }
+
+    #[test]
+    fn test_split_text() {
+        let body = create_body("<html><head></head><body><p>foobar</p></body></html>");
+        let p = body.borrow().first_child().unwrap();
+        let text = p.borrow().first_child().unwrap();
+
+        let new_node = split_text(&text, 3).unwrap();
+        assert_eq!("foo", data(&text));
+        assert_eq!("bar", data(&new_node));
+        assert!(Rc::ptr_eq(
+            &new_node,
+            &text.borrow().next_sibling().unwrap()
+        ));
+        assert!(Rc::ptr_eq(
+            &new_node,
+            &p.borrow().last_child().upgrade().unwrap()
+        ));
+
+        assert!(split_text(&text, 4).is_err());
+    }
+
+    #[test]
+    fn test_normalize() {
+        let body = create_body("<html><head></head><body><p>foo</p></body></html>");
+        let p = body.borrow().first_child().unwrap();
+        append_child(&p, &new_text(""));
+        append_child(&p, &new_text("bar"));
+        let a = Rc::new(RefCell::new(Node::new(NodeKind::Element(Element::new(
+            "a",
+            Vec::new(),
+        )))));
+        append_child(&p, &a);
+        append_child(&a, &new_text("x"));
+        append_child(&a, &new_text("y"));
+        append_child(&p, &new_text("baz"));
+
+        normalize(&body);
+
+        let children = children(&p);
+        assert_eq!(3, children.len());
+        assert_eq!("foobar", data(&children[0]));
+        assert_eq!(1, super::children(&a).len());
+        assert_eq!("xy", data(&a));
+        assert_eq!("baz", data(&children[2]));
+    }
+
+    #[test]
+    fn test_text_content() {
+        let body = create_body(
+            "<html><head></head><body><p>foo<a href=x>bar</a></p><h1>baz</h1></body></html>",
+        );
+        assert_eq!(Some("foobarbaz".to_string()), body.borrow().text_content());
+
+        let p = body.borrow().first_child().unwrap();
+        set_text_content(&p, "replaced");
+        assert_eq!(1, children(&p).len());
+        assert_eq!("replaced", data(&p));
+
+        set_text_content(&p, "");
+        assert!(p.borrow().first_child().is_none());
+
+        let document = Node::new(NodeKind::Document);
+        assert_eq!(None, document.text_content());
+
+        // 要素の前後や先頭の空白も元の文書のまま残る
+        let body = create_body(
+            "<html><head></head><body><p>Hello <a>link</a> world</p><p>  indented</p></body></html>",
+        );
+        assert_eq!(
+            Some("Hello link world  indented".to_string()),
+            body.borrow().text_content()
+        );
+        assert_eq!("Hello link world\n\nindented", inner_text(&body));
+    }
+
+    #[test]
+    fn test_inner_text() {
+        let html = "<html><head><style>p { color: red; }</style></head><body><h1>Title</h1><p>Hello   world</p><p>second</p></body></html>";
+        let window = HtmlParser::new(HtmlTokenizer::new(html.to_string())).construct_tree();
+        let document = window.borrow().document();
+        let html = document.borrow().first_child().unwrap();
+        let body = html.borrow().last_child().upgrade().unwrap();
+
+        assert_eq!("Title\n\nHello world\n\nsecond", inner_text(&body));
+        assert_eq!("Title\n\nHello world\n\nsecond", inner_text(&html));
+    }
+
+    #[test]
+    fn test_inner_text_with_style() {
+        let html = "<html><body><h1>Title</h1><p class=\"hidden\">hidden</p><p class=\"invisible\">invisible</p>\
+             <ul><li>one</li><li>two  </li></ul><table><tr><td>a</td><td>b </td></tr><tr><td> c</td></tr></table>\
+             <p class=\"pre\">x   y</p></body></html>";
+        let css =
+            ".hidden { display: none } .invisible { visibility: hidden } .pre { white-space: pre }";
+        let document = style_document(html, css);
+        let html = document.borrow().first_child().unwrap();
+        let body = html.borrow().last_child().upgrade().unwrap();
+
+        // 段落の前後は2行、ブロックやリスト項目、表の行の前後は1行空け、セルの間はタブで区切る
+        assert_eq!("Title\n\none\ntwo\na\tb\nc\n\nx   y", inner_text(&body));
+    }
+}
@@ -0,0 +1,635 @@
+use crate::renderer::dom::node::{ElementKind, Node, Window};
+use alloc::{
+    rc::Rc,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{cell::RefCell, fmt};
+
+pub type EventListenerCallback = Rc<dyn Fn(&mut Event)>;
+
+#[derive(Clone)]
+pub struct EventListener {
+    event_type: String,
+    callback: EventListenerCallback,
+    capture: bool,
+}
+
+impl EventListener {
+    pub fn new(event_type: &str, callback: EventListenerCallback, capture: bool) -> Self {
+        Self {
+            event_type: event_type.to_string(),
+            callback,
+            capture,
+        }
+    }
+
+    pub fn event_type(&self) -> String {
+        self.event_type.clone()
+    }
+
+    pub fn capture(&self) -> bool {
+        self.capture
+    }
+
+    fn is_same(&self, other: &EventListener) -> bool {
+        self.event_type == other.event_type
+            && self.capture == other.capture
+            && Rc::ptr_eq(&self.callback, &other.callback)
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct EventTarget {
+    listeners: Vec<EventListener>,
+}
+
+impl EventTarget {
+    pub fn new() -> Self {
+        Self {
+            listeners: Vec::new(),
+        }
+    }
+
+    pub fn add_event_listener(
+        &mut self,
+        event_type: &str,
+        callback: EventListenerCallback,
+        capture: bool,
+    ) {
+        let listener = EventListener::new(event_type, callback, capture);
+
+        // 同じリスナーは重複して登録しない
+        if self.listeners.iter().any(|l| l.is_same(&listener)) {
+            return;
+        }
+
+        self.listeners.push(listener);
+    }
+
+    pub fn remove_event_listener(
+        &mut self,
+        event_type: &str,
+        callback: &EventListenerCallback,
+        capture: bool,
+    ) {
+        let listener = EventListener::new(event_type, callback.clone(), capture);
+        self.listeners.retain(|l| !l.is_same(&listener));
+    }
+
+    pub fn listeners(&self) -> Vec<EventListener> {
+        self.listeners.clone()
+    }
+
+    fn contains(&self, listener: &EventListener) -> bool {
+        self.listeners.iter().any(|l| l.is_same(listener))
+    }
+}
+
+impl fmt::Debug for EventTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventTarget")
+            .field("listeners", &self.listeners.len())
+            .finish()
+    }
+}
+
+#[derive(Clone)]
+pub enum EventTargetHandle {
+    Node(Rc<RefCell<Node>>),
+    Window(Rc<RefCell<Window>>),
+}
+
+impl EventTargetHandle {
+    pub fn node(&self) -> Option<Rc<RefCell<Node>>> {
+        match self {
+            EventTargetHandle::Node(node) => Some(node.clone()),
+            EventTargetHandle::Window(_) => None,
+        }
+    }
+
+    fn listeners(&self) -> Vec<EventListener> {
+        match self {
+            EventTargetHandle::Node(node) => node.borrow().event_target().listeners(),
+            EventTargetHandle::Window(window) => window.borrow().event_target().listeners(),
+        }
+    }
+
+    fn contains(&self, listener: &EventListener) -> bool {
+        match self {
+            EventTargetHandle::Node(node) => node.borrow().event_target().contains(listener),
+            EventTargetHandle::Window(window) => window.borrow().event_target().contains(listener),
+        }
+    }
+}
+
+impl PartialEq for EventTargetHandle {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (EventTargetHandle::Node(a), EventTargetHandle::Node(b)) => Rc::ptr_eq(a, b),
+            (EventTargetHandle::Window(a), EventTargetHandle::Window(b)) => Rc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Debug for EventTargetHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EventTargetHandle::Node(node) => write!(f, "Node({:?})", node.borrow().kind()),
+            EventTargetHandle::Window(_) => write!(f, "Window"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventPhase {
+    None,
+    Capturing,
+    AtTarget,
+    Bubbling,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Modifiers {
+    pub alt: bool,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub meta: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MouseEventData {
+    pub client_x: i64,
+    pub client_y: i64,
+    pub button: i16,
+    pub buttons: u16,
+    pub detail: i64,
+    pub modifiers: Modifiers,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct KeyboardEventData {
+    pub key: String,
+    pub code: String,
+    pub repeat: bool,
+    pub modifiers: Modifiers,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InputEventData {
+    pub data: Option<String>,
+    pub input_type: String,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct FocusEventData {
+    pub related_target: Option<Rc<RefCell<Node>>>,
+}
+
+#[derive(Clone, Debug)]
+pub enum EventKind {
+    Basic,
+    Mouse(MouseEventData),
+    Keyboard(KeyboardEventData),
+    Focus(FocusEventData),
+    Input(InputEventData),
+    Load,
+}
+
+#[derive(Clone, Debug)]
+pub struct Event {
+    event_type: String,
+    kind: EventKind,
+    bubbles: bool,
+    cancelable: bool,
+    phase: EventPhase,
+    target: Option<EventTargetHandle>,
+    current_target: Option<EventTargetHandle>,
+    propagation_stopped: bool,
+    immediate_propagation_stopped: bool,
+    default_prevented: bool,
+}
+
+impl Event {
+    pub fn new(event_type: &str, bubbles: bool, cancelable: bool) -> Self {
+        Self {
+            event_type: event_type.to_string(),
+            kind: EventKind::Basic,
+            bubbles,
+            cancelable,
+            phase: EventPhase::None,
+            target: None,
+            current_target: None,
+            propagation_stopped: false,
+            immediate_propagation_stopped: false,
+            default_prevented: false,
+        }
+    }
+
+    pub fn new_mouse_event(event_type: &str, data: MouseEventData) -> Self {
+        // mouseenter/mouseleaveのみバブリングしない
+        let bubbles = !matches!(event_type, "mouseenter" | "mouseleave");
+        let cancelable = bubbles;
+        let mut event = Self::new(event_type, bubbles, cancelable);
+        event.kind = EventKind::Mouse(data);
+        event
+    }
+
+    pub fn new_keyboard_event(event_type: &str, data: KeyboardEventData) -> Self {
+        let mut event = Self::new(event_type, true, true);
+        event.kind = EventKind::Keyboard(data);
+        event
+    }
+
+    pub fn new_focus_event(event_type: &str, data: FocusEventData) -> Self {
+        // focus/blurはバブリングせず、focusin/focusoutはバブリングする
+        let bubbles = matches!(event_type, "focusin" | "focusout");
+        let mut event = Self::new(event_type, bubbles, false);
+        event.kind = EventKind::Focus(data);
+        event
+    }
+
+    pub fn new_input_event(event_type: &str, data: InputEventData) -> Self {
+        let cancelable = event_type == "beforeinput";
+        let mut event = Self::new(event_type, true, cancelable);
+        event.kind = EventKind::Input(data);
+        event
+    }
+
+    pub fn new_load_event() -> Self {
+        let mut event = Self::new("load", false, false);
+        event.kind = EventKind::Load;
+        event
+    }
+
+    pub fn event_type(&self) -> String {
+        self.event_type.clone()
+    }
+
+    pub fn kind(&self) -> EventKind {
+        self.kind.clone()
+    }
+
+    pub fn bubbles(&self) -> bool {
+        self.bubbles
+    }
+
+    pub fn cancelable(&self) -> bool {
+        self.cancelable
+    }
+
+    pub fn phase(&self) -> EventPhase {
+        self.phase
+    }
+
+    pub fn target(&self) -> Option<EventTargetHandle> {
+        self.target.clone()
+    }
+
+    pub fn current_target(&self) -> Option<EventTargetHandle> {
+        self.current_target.clone()
+    }
+
+    pub fn stop_propagation(&mut self) {
+        self.propagation_stopped = true;
+    }
+
+    pub fn stop_immediate_propagation(&mut self) {
+        self.propagation_stopped = true;
+        self.immediate_propagation_stopped = true;
+    }
+
+    pub fn prevent_default(&mut self) {
+        if self.cancelable {
+            self.default_prevented = true;
+        }
+    }
+
+    pub fn default_prevented(&self) -> bool {
+        self.default_prevented
+    }
+
+    fn invoke(&mut self, target: &EventTargetHandle, phase: EventPhase, capture: bool) {
+        if self.propagation_stopped {
+            return;
+        }
+
+        self.phase = phase;
+        self.current_target = Some(target.clone());
+
+        // コールバック中にリスナーが変更されても良いように、先に複製しておく
+        let listeners = target.listeners();
+        for listener in listeners {
+            if listener.event_type != self.event_type || listener.capture != capture {
+                continue;
+            }
+
+            // 呼び出し前に削除されたリスナーは呼ばない
+            if !target.contains(&listener) {
+                continue;
+            }
+
+            (listener.callback)(self);
+
+            if self.immediate_propagation_stopped {
+                return;
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DefaultAction {
+    Navigate(String),
+}
+
+fn event_path(target: &Rc<RefCell<Node>>) -> Vec<EventTargetHandle> {
+    let mut path = Vec::new();
+    let mut current = Some(target.clone());
+
+    while let Some(node) = current {
+        path.push(EventTargetHandle::Node(node.clone()));
+
+        let parent = node.borrow().parent().upgrade();
+        if parent.is_none() {
+            // ドキュメントノードの場合はWindowまで伝播させる
+            if let Some(window) = node.borrow().window().upgrade() {
+                path.push(EventTargetHandle::Window(window));
+            }
+        }
+        current = parent;
+    }
+
+    path
+}
+
+fn dispatch_along_path(path: &[EventTargetHandle], event: &mut Event) -> bool {
+    let Some(target) = path.first() else {
+        return true;
+    };
+
+    event.target = Some(target.clone());
+    event.propagation_stopped = false;
+    event.immediate_propagation_stopped = false;
+    event.default_prevented = false;
+
+    for handle in path.iter().skip(1).rev() {
+        event.invoke(handle, EventPhase::Capturing, true);
+    }
+
+    // ターゲットではキャプチャのリスナーを先に呼び出す
+    event.invoke(target, EventPhase::AtTarget, true);
+    event.invoke(target, EventPhase::AtTarget, false);
+
+    if event.bubbles {
+        for handle in path.iter().skip(1) {
+            event.invoke(handle, EventPhase::Bubbling, false);
+        }
+    }
+
+    event.phase = EventPhase::None;
+    event.current_target = None;
+
+    !event.default_prevented
+}
+
+pub fn dispatch_event(target: &Rc<RefCell<Node>>, event: &mut Event) -> bool {
+    let path = event_path(target);
+    dispatch_along_path(&path, event)
+}
+
+pub fn dispatch_window_event(window: &Rc<RefCell<Window>>, event: &mut Event) -> bool {
+    let path = [EventTargetHandle::Window(window.clone())];
+    dispatch_along_path(&path, event)
+}
+
+pub fn default_action(event: &Event) -> Option<DefaultAction> {
+    if event.default_prevented || event.event_type != "click" {
+        return None;
+    }
+
+    let mut current = event.target.as_ref().and_then(|target| target.node());
+    while let Some(node) = current {
+        if node.borrow().element_kind() == Some(ElementKind::A) {
+            let href = node
+                .borrow()
+                .get_element()
+                .and_then(|element| element.get_attribute("href"));
+            if let Some(href) = href {
+                return Some(DefaultAction::Navigate(href));
+            }
+        }
+        current = node.borrow().parent().upgrade();
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::html::{parser::HtmlParser, token::HtmlTokenizer};
+    use alloc::{format, string::ToString, vec};
+
+    fn create_window(html: &str) -> Rc<RefCell<Window>> {
+        let tokenizer = HtmlTokenizer::new(html.to_string());
+        HtmlParser::new(tokenizer).construct_tree()
+    }
+
+    fn find_element(node: &Rc<RefCell<Node>>, kind: ElementKind) -> Option<Rc<RefCell<Node>>> {
+        if node.borrow().element_kind() == Some(kind) {
+            return Some(node.clone());
+        }
+
+        let mut child = node.borrow().first_child();
+        while let Some(c) = child {
+            if let Some(found) = find_element(&c, kind) {
+                return Some(found);
+            }
+            child = c.borrow().next_sibling();
+        }
+
+        None
+    }
+
+    fn recorder(log: &Rc<RefCell<Vec<String>>>, name: &str) -> EventListenerCallback {
+        let log = log.clone();
+        let name = name.to_string();
+        Rc::new(move |event: &mut Event| {
+            log.borrow_mut()
+                .push(format!("{}:{:?}", name, event.phase()));
+        })
+    }
+
+    #[test]
+    fn test_capture_target_bubble_order() {
+        let window = create_window("<html><head></head><body><p>text</p></body></html>");
+        let document = window.borrow().document();
+        let body = find_element(&document, ElementKind::Body).unwrap();
+        let p = find_element(&document, ElementKind::P).unwrap();
+        let log = Rc::new(RefCell::new(Vec::new()));
+
+        window
+            .borrow_mut()
+            .add_event_listener("click", recorder(&log, "window"), true);
+        body.borrow_mut()
+            .add_event_listener("click", recorder(&log, "body-capture"), true);
+        body.borrow_mut()
+            .add_event_listener("click", recorder(&log, "body"), false);
+        p.borrow_mut()
+            .add_event_listener("click", recorder(&log, "p"), false);
+        p.borrow_mut()
+            .add_event_listener("click", recorder(&log, "p-capture"), true);
+
+        let mut event = Event::new_mouse_event("click", MouseEventData::default());
+        assert!(dispatch_event(&p, &mut event));
+
+        assert_eq!(
+            vec![
+                "window:Capturing",
+                "body-capture:Capturing",
+                "p-capture:AtTarget",
+                "p:AtTarget",
+                "body:Bubbling",
+            ],
+            *log.borrow()
+        );
+        assert_eq!(EventPhase::None, event.phase());
+        assert_eq!(Some(EventTargetHandle::Node(p.clone())), event.target());
+    }
+
+    #[test]
+    fn test_stop_propagation() {
+        let window = create_window("<html><head></head><body><p>text</p></body></html>");
+        let document = window.borrow().document();
+        let body = find_element(&document, ElementKind::Body).unwrap();
+        let p = find_element(&document, ElementKind::P).unwrap();
+        let log = Rc::new(RefCell::new(Vec::new()));
+
+        p.borrow_mut().add_event_listener(
+            "click",
+            Rc::new(|event: &mut Event| event.stop_propagation()),
+            false,
+        );
+        p.borrow_mut()
+            .add_event_listener("click", recorder(&log, "p"), false);
+        body.borrow_mut()
+            .add_event_listener("click", recorder(&log, "body"), false);
+
+        let mut event = Event::new_mouse_event("click", MouseEventData::default());
+        dispatch_event(&p, &mut event);
+
+        assert_eq!(vec!["p:AtTarget"], *log.borrow());
+    }
+
+    #[test]
+    fn test_stop_immediate_propagation() {
+        let window = create_window("<html><head></head><body><p>text</p></body></html>");
+        let document = window.borrow().document();
+        let p = find_element(&document, ElementKind::P).unwrap();
+        let log = Rc::new(RefCell::new(Vec::new()));
+
+        p.borrow_mut().add_event_listener(
+            "click",
+            Rc::new(|event: &mut Event| event.stop_immediate_propagation()),
+            false,
+        );
+        p.borrow_mut()
+            .add_event_listener("click", recorder(&log, "p"), false);
+
+        let mut event = Event::new_mouse_event("click", MouseEventData::default());
+        dispatch_event(&p, &mut event);
+
+        assert!(log.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_non_bubbling_event() {
+        let window = create_window("<html><head></head><body><p>text</p></body></html>");
+        let document = window.borrow().document();
+        let body = find_element(&document, ElementKind::Body).unwrap();
+        let p = find_element(&document, ElementKind::P).unwrap();
+        let log = Rc::new(RefCell::new(Vec::new()));
+
+        body.borrow_mut()
+            .add_event_listener("focus", recorder(&log, "body"), false);
+        body.borrow_mut()
+            .add_event_listener("focus", recorder(&log, "body-capture"), true);
+
+        let mut event = Event::new_focus_event("focus", FocusEventData::default());
+        dispatch_event(&p, &mut event);
+
+        assert_eq!(vec!["body-capture:Capturing"], *log.borrow());
+    }
+
+    #[test]
+    fn test_remove_event_listener() {
+        let window = create_window("<html><head></head><body><p>text</p></body></html>");
+        let document = window.borrow().document();
+        let p = find_element(&document, ElementKind::P).unwrap();
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let callback = recorder(&log, "p");
+
+        p.borrow_mut()
+            .add_event_listener("click", callback.clone(), false);
+        p.borrow_mut()
+            .add_event_listener("click", callback.clone(), false);
+        assert_eq!(1, p.borrow().event_target().listeners().len());
+
+        p.borrow_mut()
+            .remove_event_listener("click", &callback, true);
+        assert_eq!(1, p.borrow().event_target().listeners().len());
+
+        p.borrow_mut()
+            .remove_event_listener("click", &callback, false);
+        let mut event = Event::new_mouse_event("click", MouseEventData::default());
+        dispatch_event(&p, &mut event);
+
+        assert!(log.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_prevent_default() {
+        let window = create_window(
+            "<html><head></head><body><p><a href=http://example.com>link</a></p></body></html>",
+        );
+        let document = window.borrow().document();
+        let a = find_element(&document, ElementKind::A).unwrap();
+        let text = a.borrow().first_child().unwrap();
+
+        let mut event = Event::new_mouse_event("click", MouseEventData::default());
+        assert!(dispatch_event(&text, &mut event));
+        assert_eq!(
+            Some(DefaultAction::Navigate("http://example.com".to_string())),
+            default_action(&event)
+        );
+
+        a.borrow_mut().add_event_listener(
+            "click",
+            Rc::new(|event: &mut Event| event.prevent_default()),
+            false,
+        );
+        let mut event = Event::new_mouse_event("click", MouseEventData::default());
+        assert!(!dispatch_event(&text, &mut event));
+        assert_eq!(None, default_action(&event));
+    }
+
+    #[test]
+    fn test_prevent_default_not_cancelable() {
+        let window = create_window("<html><head></head><body></body></html>");
+        let mut event = Event::new_load_event();
+        let log = Rc::new(RefCell::new(Vec::new()));
+        window
+            .borrow_mut()
+            .add_event_listener("load", recorder(&log, "window"), false);
+        window.borrow_mut().add_event_listener(
+            "load",
+            Rc::new(|event: &mut Event| event.prevent_default()),
+            false,
+        );
+
+        assert!(dispatch_window_event(&window, &mut event));
+        assert_eq!(vec!["window:AtTarget"], *log.borrow());
+    }
+}
@@ -1 +1,25 @@
+pub mod api;
+pub mod event;
+pub mod mutation;
 pub mod node;
+
+#[cfg(test)]
+use crate::renderer::{
+    dom::node::{ElementKind, Node},
+    html::{parser::HtmlParser, token::HtmlTokenizer},
+};
+#[cfg(test)]
+use alloc::{rc::Rc, string::ToString};
+#[cfg(test)]
+use core::cell::RefCell;
+
+// テスト用に、HTMLから文書を作りbody要素を返す
+#[cfg(test)]
+pub(crate) fn create_body(html: &str) -> Rc<RefCell<Node>> {
+    let window = HtmlParser::new(HtmlTokenizer::new(html.to_string())).construct_tree();
+    let document = window.borrow().document();
+    let html = document.borrow().first_child().unwrap();
+    let body = html.borrow().last_child().upgrade().unwrap();
+    assert_eq!(Some(ElementKind::Body), body.borrow().element_kind());
+    body
+}
@@ -0,0 +1,454 @@
+use crate::renderer::dom::node::Node;
+use alloc::{
+    rc::{Rc, Weak},
+    string::String,
+    vec::Vec,
+};
+use core::cell::RefCell;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MutationType {
+    ChildList,
+    Attributes,
+    CharacterData,
+}
+
+#[derive(Clone, Debug)]
+pub struct MutationRecord {
+    record_type: MutationType,
+    target: Rc<RefCell<Node>>,
+    added_nodes: Vec<Rc<RefCell<Node>>>,
+    removed_nodes: Vec<Rc<RefCell<Node>>>,
+    previous_sibling: Option<Rc<RefCell<Node>>>,
+    next_sibling: Option<Rc<RefCell<Node>>>,
+    attribute_name: Option<String>,
+    old_value: Option<String>,
+}
+
+impl MutationRecord {
+    fn new(record_type: MutationType, target: Rc<RefCell<Node>>) -> Self {
+        Self {
+            record_type,
+            target,
+            added_nodes: Vec::new(),
+            removed_nodes: Vec::new(),
+            previous_sibling: None,
+            next_sibling: None,
+            attribute_name: None,
+            old_value: None,
+        }
+    }
+
+    pub fn record_type(&self) -> MutationType {
+        self.record_type
+    }
+
+    pub fn target(&self) -> Rc<RefCell<Node>> {
+        self.target.clone()
+    }
+
+    pub fn added_nodes(&self) -> Vec<Rc<RefCell<Node>>> {
+        self.added_nodes.clone()
+    }
+
+    pub fn removed_nodes(&self) -> Vec<Rc<RefCell<Node>>> {
+        self.removed_nodes.clone()
+    }
+
+    pub fn previous_sibling(&self) -> Option<Rc<RefCell<Node>>> {
+        self.previous_sibling.clone()
+    }
+
+    pub fn next_sibling(&self) -> Option<Rc<RefCell<Node>>> {
+        self.next_sibling.clone()
+    }
+
+    pub fn attribute_name(&self) -> Option<String> {
+        self.attribute_name.clone()
+    }
+
+    pub fn old_value(&self) -> Option<String> {
+        self.old_value.clone()
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MutationObserverInit {
+    pub child_list: bool,
+    pub attributes: bool,
+    pub character_data: bool,
+    pub subtree: bool,
+    pub attribute_old_value: bool,
+    pub character_data_old_value: bool,
+    pub attribute_filter: Option<Vec<String>>,
+}
+
+impl MutationObserverInit {
+    fn is_valid(&self) -> bool {
+        self.child_list || self.attributes || self.character_data
+    }
+
+    fn normalized(&self) -> Self {
+        let mut options = self.clone();
+        // attributeOldValueやattributeFilterが指定されていればattributesも監視する
+        if options.attribute_old_value || options.attribute_filter.is_some() {
+            options.attributes = true;
+        }
+        if options.character_data_old_value {
+            options.character_data = true;
+        }
+        options
+    }
+}
+
+#[derive(Debug, Default)]
+struct MutationObserverState {
+    records: Vec<MutationRecord>,
+    observed_nodes: Vec<Weak<RefCell<Node>>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct RegisteredObserver {
+    observer: Weak<RefCell<MutationObserverState>>,
+    options: MutationObserverInit,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct MutationObserver {
+    state: Rc<RefCell<MutationObserverState>>,
+}
+
+impl MutationObserver {
+    pub fn new() -> Self {
+        Self {
+            state: Rc::new(RefCell::new(MutationObserverState::default())),
+        }
+    }
+
+    pub fn observe(&self, target: &Rc<RefCell<Node>>, options: MutationObserverInit) -> bool {
+        let options = options.normalized();
+        if !options.is_valid() {
+            return false;
+        }
+
+        let mut node = target.borrow_mut();
+        let registered = node.registered_observers_mut();
+
+        // 既に登録されている場合はオプションを置き換える
+        if let Some(existing) = registered
+            .iter_mut()
+            .find(|r| self.is_same_state(&r.observer))
+        {
+            existing.options = options;
+            return true;
+        }
+
+        registered.push(RegisteredObserver {
+            observer: Rc::downgrade(&self.state),
+            options,
+        });
+        self.state
+            .borrow_mut()
+            .observed_nodes
+            .push(Rc::downgrade(target));
+
+        true
+    }
+
+    pub fn disconnect(&self) {
+        let observed_nodes = core::mem::take(&mut self.state.borrow_mut().observed_nodes);
+        for node in observed_nodes.iter().filter_map(|n| n.upgrade()) {
+            node.borrow_mut()
+                .registered_observers_mut()
+                .retain(|r| !self.is_same_state(&r.observer));
+        }
+
+        self.state.borrow_mut().records.clear();
+    }
+
+    pub fn take_records(&self) -> Vec<MutationRecord> {
+        core::mem::take(&mut self.state.borrow_mut().records)
+    }
+
+    pub fn has_pending_records(&self) -> bool {
+        !self.state.borrow().records.is_empty()
+    }
+
+    fn is_same_state(&self, other: &Weak<RefCell<MutationObserverState>>) -> bool {
+        match other.upgrade() {
+            Some(state) => Rc::ptr_eq(&state, &self.state),
+            None => false,
+        }
+    }
+}
+
+fn interested_observers(
+    target: &Rc<RefCell<Node>>,
+    record_type: MutationType,
+    attribute_name: Option<&str>,
+) -> Vec<(Rc<RefCell<MutationObserverState>>, bool)> {
+    let mut interested: Vec<(Rc<RefCell<MutationObserverState>>, bool)> = Vec::new();
+    let mut current = Some(target.clone());
+
+    while let Some(node) = current {
+        let is_target = Rc::ptr_eq(&node, target);
+
+        for registered in node.borrow().registered_observers().iter() {
+            let options = &registered.options;
+            if !is_target && !options.subtree {
+                continue;
+            }
+
+            let (matched, wants_old_value) = match record_type {
+                MutationType::ChildList => (options.child_list, false),
+                MutationType::Attributes => {
+                    let filtered = match (&options.attribute_filter, attribute_name) {
+                        (Some(filter), Some(name)) => filter.iter().any(|f| f == name),
+                        _ => true,
+                    };
+                    (options.attributes && filtered, options.attribute_old_value)
+                }
+                MutationType::CharacterData => {
+                    (options.character_data, options.character_data_old_value)
+                }
+            };
+            if !matched {
+                continue;
+            }
+
+            let Some(state) = registered.observer.upgrade() else {
+                continue;
+            };
+
+            // 同じオブザーバーには1つのレコードのみ追加する
+            match interested.iter_mut().find(|(s, _)| Rc::ptr_eq(s, &state)) {
+                Some((_, old_value)) => *old_value |= wants_old_value,
+                None => interested.push((state, wants_old_value)),
+            }
+        }
+
+        current = node.borrow().parent().upgrade();
+    }
+
+    interested
+}
+
+fn queue_record(record: MutationRecord, attribute_name: Option<&str>) {
+    let observers = interested_observers(&record.target, record.record_type, attribute_name);
+
+    for (state, wants_old_value) in observers {
+        let mut record = record.clone();
+        if !wants_old_value {
+            record.old_value = None;
+        }
+        state.borrow_mut().records.push(record);
+    }
+}
+
+pub fn queue_child_list_record(
+    target: &Rc<RefCell<Node>>,
+    added_nodes: Vec<Rc<RefCell<Node>>>,
+    removed_nodes: Vec<Rc<RefCell<Node>>>,
+    previous_sibling: Option<Rc<RefCell<Node>>>,
+    next_sibling: Option<Rc<RefCell<Node>>>,
+) {
+    let mut record = MutationRecord::new(MutationType::ChildList, target.clone());
+    record.added_nodes = added_nodes;
+    record.removed_nodes = removed_nodes;
+    record.previous_sibling = previous_sibling;
+    record.next_sibling = next_sibling;
+    queue_record(record, None);
+}
+
+pub fn queue_attribute_record(
+    target: &Rc<RefCell<Node>>,
+    attribute_name: &str,
+    old_value: Option<String>,
+) {
+    let mut record = MutationRecord::new(MutationType::Attributes, target.clone());
+    record.attribute_name = Some(String::from(attribute_name));
+    record.old_value = old_value;
+    queue_record(record, Some(attribute_name));
+}
+
+pub fn queue_character_data_record(target: &Rc<RefCell<Node>>, old_value: String) {
+    let mut record = MutationRecord::new(MutationType::CharacterData, target.clone());
+    record.old_value = Some(old_value);
+    queue_record(record, None);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::dom::{
+        api::{
+            append_child, insert_before, remove_attribute, remove_child, set_attribute,
+            set_character_data,
+        },
+        create_body,
+        node::{Element, NodeKind},
+    };
+    use alloc::{string::ToString, vec, vec::Vec};
+
+    fn new_element(name: &str) -> Rc<RefCell<Node>> {
+        Rc::new(RefCell::new(Node::new(NodeKind::Element(Element::new(
+            name,
+            Vec::new(),
+        )))))
+    }
+
+    #[test]
+    fn test_child_list() {
+        let body = create_body("<html><head></head><body><p>text</p></body></html>");
+        let p = body.borrow().first_child().unwrap();
+        let observer = MutationObserver::new();
+        assert!(observer.observe(
+            &body,
+            MutationObserverInit {
+                child_list: true,
+                ..Default::default()
+            }
+        ));
+
+        let h1 = new_element("h1");
+        assert!(insert_before(&body, &h1, Some(&p)));
+        assert!(remove_child(&body, &p));
+
+        let records = observer.take_records();
+        assert_eq!(2, records.len());
+
+        assert_eq!(MutationType::ChildList, records[0].record_type());
+        assert!(Rc::ptr_eq(&body, &records[0].target()));
+        assert!(Rc::ptr_eq(&h1, &records[0].added_nodes()[0]));
+        assert!(records[0].previous_sibling().is_none());
+        assert!(Rc::ptr_eq(&p, &records[0].next_sibling().unwrap()));
+
+        assert!(Rc::ptr_eq(&p, &records[1].removed_nodes()[0]));
+        assert!(Rc::ptr_eq(&h1, &records[1].previous_sibling().unwrap()));
+
+        assert!(observer.take_records().is_empty());
+    }
+
+    #[test]
+    fn test_subtree() {
+        let body = create_body("<html><head></head><body><p>text</p></body></html>");
+        let p = body.borrow().first_child().unwrap();
+        let observer = MutationObserver::new();
+        observer.observe(
+            &body,
+            MutationObserverInit {
+                child_list: true,
+                ..Default::default()
+            },
+        );
+
+        append_child(&p, &new_element("a"));
+        assert!(observer.take_records().is_empty());
+
+        observer.observe(
+            &body,
+            MutationObserverInit {
+                child_list: true,
+                subtree: true,
+                ..Default::default()
+            },
+        );
+        append_child(&p, &new_element("a"));
+        let records = observer.take_records();
+        assert_eq!(1, records.len());
+        assert!(Rc::ptr_eq(&p, &records[0].target()));
+    }
+
+    #[test]
+    fn test_attributes() {
+        let body = create_body("<html><head></head><body><p>text</p></body></html>");
+        let p = body.borrow().first_child().unwrap();
+        let observer = MutationObserver::new();
+        observer.observe(
+            &p,
+            MutationObserverInit {
+                attribute_old_value: true,
+                attribute_filter: Some(vec!["id".to_string()]),
+                ..Default::default()
+            },
+        );
+
+        set_attribute(&p, "id", "first");
+        set_attribute(&p, "class", "ignored");
+        set_attribute(&p, "id", "second");
+        remove_attribute(&p, "id");
+
+        let records = observer.take_records();
+        let old_values: Vec<Option<String>> = records.iter().map(|r| r.old_value()).collect();
+        assert_eq!(
+            vec![None, Some("first".to_string()), Some("second".to_string())],
+            old_values
+        );
+        assert!(records
+            .iter()
+            .all(|r| r.attribute_name() == Some("id".to_string())));
+    }
+
+    #[test]
+    fn test_character_data() {
+        let body = create_body("<html><head></head><body><p>text</p></body></html>");
+        let p = body.borrow().first_child().unwrap();
+        let text = p.borrow().first_child().unwrap();
+        let observer = MutationObserver::new();
+        let observer_with_old_value = MutationObserver::new();
+        observer.observe(
+            &body,
+            MutationObserverInit {
+                character_data: true,
+                subtree: true,
+                ..Default::default()
+            },
+        );
+        observer_with_old_value.observe(
+            &text,
+            MutationObserverInit {
+                character_data_old_value: true,
+                ..Default::default()
+            },
+        );
+
+        set_character_data(&text, "changed");
+
+        let records = observer.take_records();
+        assert_eq!(1, records.len());
+        assert_eq!(MutationType::CharacterData, records[0].record_type());
+        assert_eq!(None, records[0].old_value());
+
+        let records = observer_with_old_value.take_records();
+        assert_eq!(Some("text".to_string()), records[0].old_value());
+    }
+
+    #[test]
+    fn test_disconnect() {
+        let body = create_body("<html><head></head><body><p>text</p></body></html>");
+        let observer = MutationObserver::new();
+        observer.observe(
+            &body,
+            MutationObserverInit {
+                child_list: true,
+                ..Default::default()
+            },
+        );
+        append_child(&body, &new_element("p"));
+        assert!(observer.has_pending_records());
+
+        observer.disconnect();
+        assert!(!observer.has_pending_records());
+        assert!(body.borrow().registered_observers().is_empty());
+
+        append_child(&body, &new_element("p"));
+        assert!(observer.take_records().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_options() {
+        let body = create_body("<html><head></head><body><p>text</p></body></html>");
+        let observer = MutationObserver::new();
+        assert!(!observer.observe(&body, MutationObserverInit::default()));
+    }
+}
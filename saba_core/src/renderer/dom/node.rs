@@ -1,11 +1,19 @@
-use crate::renderer::html::attribute::Attribute;
+use crate::renderer::{
+    css::computed_style::ComputedStyle,
+    dom::{
+        event::{EventListenerCallback, EventTarget},
+        mutation::RegisteredObserver,
+    },
+    html::attribute::Attribute,
+    image::source::ImageState,
+};
 use alloc::{
     format,
     rc::{Rc, Weak},
     string::String,
     vec::Vec,
 };
-use core::{cell::RefCell, str::FromStr};
+use core::{cell::RefCell, fmt, str::FromStr};
 
 #[derive(Clone, Debug)]
 pub struct Node {
@@ -16,6 +24,11 @@ pub struct Node {
     last_child: Weak<RefCell<Node>>,
     previous_sibling: Weak<RefCell<Node>>,
     next_sibling: Option<Rc<RefCell<Node>>>,
+    event_target: EventTarget,
+    registered_observers: Vec<RegisteredObserver>,
+    computed_style: Option<ComputedStyle>,
+    // img要素の場合の画像の読み込み状態
+    image_state: Option<ImageState>,
 }
 
 impl Node {
@@ -28,6 +41,10 @@ impl Node {
             last_child: Weak::new(),
             previous_sibling: Weak::new(),
             next_sibling: None,
+            event_target: EventTarget::new(),
+            registered_observers: Vec::new(),
+            computed_style: None,
+            image_state: None,
         }
     }
 
@@ -35,6 +52,10 @@ impl Node {
         self.window = window;
     }
 
+    pub fn window(&self) -> Weak<RefCell<Window>> {
+        self.window.clone()
+    }
+
     pub fn set_parent(&mut self, parent: Weak<RefCell<Node>>) {
         self.parent = parent;
     }
@@ -92,6 +113,72 @@ impl Node {
             NodeKind::Element(elem) => Some(elem.kind()),
         }
     }
+
+    pub fn text_content(&self) -> Option<String> {
+        match &self.kind {
+            NodeKind::Document => None,
+            NodeKind::Text(text) => Some(text.clone()),
+            NodeKind::Element(_) => {
+                let mut content = String::new();
+                let mut child = self.first_child();
+                while let Some(c) = child {
+                    if let Some(text) = c.borrow().text_content() {
+                        content.push_str(&text);
+                    }
+                    child = c.borrow().next_sibling();
+                }
+                Some(content)
+            }
+        }
+    }
+
+    pub fn event_target(&self) -> &EventTarget {
+        &self.event_target
+    }
+
+    pub fn add_event_listener(
+        &mut self,
+        event_type: &str,
+        callback: EventListenerCallback,
+        capture: bool,
+    ) {
+        self.event_target
+            .add_event_listener(event_type, callback, capture);
+    }
+
+    pub fn remove_event_listener(
+        &mut self,
+        event_type: &str,
+        callback: &EventListenerCallback,
+        capture: bool,
+    ) {
+        self.event_target
+            .remove_event_listener(event_type, callback, capture);
+    }
+
+    pub fn registered_observers(&self) -> &Vec<RegisteredObserver> {
+        &self.registered_observers
+    }
+
+    pub fn registered_observers_mut(&mut self) -> &mut Vec<RegisteredObserver> {
+        &mut self.registered_observers
+    }
+
+    pub fn set_computed_style(&mut self, computed_style: Option<ComputedStyle>) {
+        self.computed_style = computed_style;
+    }
+
+    pub fn computed_style(&self) -> Option<ComputedStyle> {
+        self.computed_style.clone()
+    }
+
+    pub fn set_image_state(&mut self, image_state: Option<ImageState>) {
+        self.image_state = image_state;
+    }
+
+    pub fn image_state(&self) -> Option<ImageState> {
+        self.image_state.clone()
+    }
 }
 
 impl PartialEq for Node {
@@ -123,12 +210,14 @@ impl PartialEq for NodeKind {
 #[derive(Clone)]
 pub struct Window {
     document: Rc<RefCell<Node>>,
+    event_target: EventTarget,
 }
 
 impl Window {
     pub fn new() -> Self {
         let window = Self {
             document: Rc::new(RefCell::new(Node::new(NodeKind::Document))),
+            event_target: EventTarget::new(),
         };
 
         window
@@ -142,6 +231,30 @@ impl Window {
     pub fn document(&self) -> Rc<RefCell<Node>> {
         self.document.clone()
     }
+
+    pub fn event_target(&self) -> &EventTarget {
+        &self.event_target
+    }
+
+    pub fn add_event_listener(
+        &mut self,
+        event_type: &str,
+        callback: EventListenerCallback,
+        capture: bool,
+    ) {
+        self.event_target
+            .add_event_listener(event_type, callback, capture);
+    }
+
+    pub fn remove_event_listener(
+        &mut self,
+        event_type: &str,
+        callback: &EventListenerCallback,
+        capture: bool,
+    ) {
+        self.event_target
+            .remove_event_listener(event_type, callback, capture);
+    }
 }
 
 impl Default for Window {
@@ -168,12 +281,44 @@ impl Element {
     pub fn kind(&self) -> ElementKind {
         self.kind
     }
+
+    pub fn attributes(&self) -> Vec<Attribute> {
+        self.attributes.clone()
+    }
+
+    pub fn get_attribute(&self, name: &str) -> Option<String> {
+        self.attributes
+            .iter()
+            .find(|attr| attr.name() == name)
+            .map(|attr| attr.value())
+    }
+
+    pub fn set_attribute(&mut self, name: &str, value: &str) -> Option<String> {
+        if let Some(attr) = self.attributes.iter_mut().find(|attr| attr.name() == name) {
+            let old_value = attr.value();
+            attr.set_value(value);
+            return Some(old_value);
+        }
+
+        self.attributes
+            .push(Attribute::with_name_and_value(name, value));
+        None
+    }
+
+    pub fn remove_attribute(&mut self, name: &str) -> Option<String> {
+        let index = self
+            .attributes
+            .iter()
+            .position(|attr| attr.name() == name)?;
+        Some(self.attributes.remove(index).value())
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ElementKind {
     Html,
     Head,
+    Link,
     Style,
     Script,
     Body,
@@ -182,6 +327,20 @@ pub enum ElementKind {
     H2,
     H3,
     A,
+    Img,
+    Ul,
+    Ol,
+    Li,
+    Table,
+    Caption,
+    Colgroup,
+    Col,
+    Thead,
+    Tbody,
+    Tfoot,
+    Tr,
+    Td,
+    Th,
 }
 
 impl FromStr for ElementKind {
@@ -190,6 +349,7 @@ impl FromStr for ElementKind {
         match s {
             "html" => Ok(ElementKind::Html),
             "head" => Ok(ElementKind::Head),
+            "link" => Ok(ElementKind::Link),
             "style" => Ok(ElementKind::Style),
             "script" => Ok(ElementKind::Script),
             "body" => Ok(ElementKind::Body),
@@ -198,7 +358,54 @@ impl FromStr for ElementKind {
             "h2" => Ok(ElementKind::H2),
             "h3" => Ok(ElementKind::H3),
             "a" => Ok(ElementKind::A),
+            "img" => Ok(ElementKind::Img),
+            "ul" => Ok(ElementKind::Ul),
+            "ol" => Ok(ElementKind::Ol),
+            "li" => Ok(ElementKind::Li),
+            "table" => Ok(ElementKind::Table),
+            "caption" => Ok(ElementKind::Caption),
+            "colgroup" => Ok(ElementKind::Colgroup),
+            "col" => Ok(ElementKind::Col),
+            "thead" => Ok(ElementKind::Thead),
+            "tbody" => Ok(ElementKind::Tbody),
+            "tfoot" => Ok(ElementKind::Tfoot),
+            "tr" => Ok(ElementKind::Tr),
+            "td" => Ok(ElementKind::Td),
+            "th" => Ok(ElementKind::Th),
             _ => Err(format!("unimplemented element name {}", s)),
         }
     }
 }
+
+impl fmt::Display for ElementKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ElementKind::Html => "html",
+            ElementKind::Head => "head",
+            ElementKind::Link => "link",
+            ElementKind::Style => "style",
+            ElementKind::Script => "script",
+            ElementKind::Body => "body",
+            ElementKind::P => "p",
+            ElementKind::H1 => "h1",
+            ElementKind::H2 => "h2",
+            ElementKind::H3 => "h3",
+            ElementKind::A => "a",
+            ElementKind::Img => "img",
+            ElementKind::Ul => "ul",
+            ElementKind::Ol => "ol",
+            ElementKind::Li => "li",
+            ElementKind::Table => "table",
+            ElementKind::Caption => "caption",
+            ElementKind::Colgroup => "colgroup",
+            ElementKind::Col => "col",
+            ElementKind::Thead => "thead",
+            ElementKind::Tbody => "tbody",
+            ElementKind::Tfoot => "tfoot",
+            ElementKind::Tr => "tr",
+            ElementKind::Td => "td",
+            ElementKind::Th => "th",
+        };
+        write!(f, "{}", name)
+    }
+}
@@ -0,0 +1,215 @@
+use crate::{
+    error::Error,
+    renderer::font::bitmap::{BitmapFont, BitmapGlyph},
+};
+use alloc::{format, string::ToString, vec::Vec};
+use core::str::Lines;
+
+fn numbers(words: core::str::SplitWhitespace) -> Result<Vec<i32>, Error> {
+    words
+        .map(|word| {
+            word.parse::<i32>()
+                .map_err(|_| Error::UnexpectedInput(format!("invalid BDF number: {}", word)))
+        })
+        .collect()
+}
+
+// 文字ごとの外接矩形。幅、高さ、原点からの横と縦のずれ
+type BoundingBox = (i32, i32, i32, i32);
+
+// STARTCHARからENDCHARまでを読む。ENCODINGが負の文字は番号を持たない
+fn parse_char(
+    lines: &mut Lines,
+    font_box: Option<BoundingBox>,
+) -> Result<(Option<char>, BitmapGlyph), Error> {
+    let mut encoding = None;
+    let mut advance = None;
+    let mut bounding_box = font_box;
+    while let Some(line) = lines.next() {
+        let mut words = line.split_whitespace();
+        match words.next() {
+            Some("ENCODING") => {
+                encoding = numbers(words)?
+                    .first()
+                    .and_then(|n| u32::try_from(*n).ok())
+                    .and_then(char::from_u32);
+            }
+            Some("DWIDTH") => advance = numbers(words)?.first().copied(),
+            Some("BBX") => {
+                if let [width, height, x, y] = numbers(words)?[..] {
+                    bounding_box = Some((width, height, x, y));
+                }
+            }
+            Some("BITMAP") => {
+                let (width, height, x, y) = bounding_box.ok_or_else(|| {
+                    Error::UnexpectedInput("BDF glyph has no bounding box".to_string())
+                })?;
+                let (width, height) = (width.max(0) as usize, height.max(0) as usize);
+                let mut data = Vec::new();
+                let mut ended = false;
+                for row in lines.by_ref().take(height) {
+                    let row = row.trim();
+                    // 行が足りない分は消灯とする
+                    if row == "ENDCHAR" {
+                        ended = true;
+                        break;
+                    }
+                    for i in (0..row.len()).step_by(2) {
+                        let byte = row
+                            .get(i..(i + 2).min(row.len()))
+                            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                            .ok_or_else(|| {
+                                Error::UnexpectedInput(format!("invalid BDF bitmap: {}", row))
+                            })?;
+                        data.push(byte);
+                    }
+                }
+                let glyph = BitmapGlyph::from_packed_rows(
+                    width,
+                    height,
+                    x,
+                    y + height as i32,
+                    advance.unwrap_or(width as i32),
+                    &data,
+                );
+                // BITMAPの後はENDCHARだけが続く
+                if !ended {
+                    for line in lines.by_ref() {
+                        if line.trim() == "ENDCHAR" {
+                            break;
+                        }
+                    }
+                }
+                return Ok((encoding, glyph));
+            }
+            _ => {}
+        }
+    }
+    Err(Error::UnexpectedInput("BDF glyph is truncated".to_string()))
+}
+
+// X11で使われるGlyph Bitmap Distribution Format
+pub fn parse_bdf(text: &str) -> Result<BitmapFont, Error> {
+    let mut lines = text.lines();
+    if !lines
+        .next()
+        .is_some_and(|line| line.starts_with("STARTFONT"))
+    {
+        return Err(Error::UnexpectedInput("not a BDF font".to_string()));
+    }
+    let mut font_box = None;
+    let mut ascent = None;
+    let mut descent = None;
+    let mut default_char = None;
+    let mut glyphs = Vec::new();
+    while let Some(line) = lines.next() {
+        let mut words = line.split_whitespace();
+        match words.next() {
+            Some("FONTBOUNDINGBOX") => {
+                if let [width, height, x, y] = numbers(words)?[..] {
+                    font_box = Some((width, height, x, y));
+                }
+            }
+            Some("FONT_ASCENT") => ascent = numbers(words)?.first().copied(),
+            Some("FONT_DESCENT") => descent = numbers(words)?.first().copied(),
+            Some("DEFAULT_CHAR") => {
+                default_char = numbers(words)?
+                    .first()
+                    .and_then(|n| u32::try_from(*n).ok())
+                    .and_then(char::from_u32);
+            }
+            Some("STARTCHAR") => glyphs.push(parse_char(&mut lines, font_box)?),
+            Some("ENDFONT") => break,
+            _ => {}
+        }
+    }
+
+    // 高さの情報がなければフォント全体の外接矩形から決める
+    let (box_height, box_y) = font_box.map_or((0, 0), |(_, height, _, y)| (height, y));
+    let mut font = BitmapFont::new(
+        ascent.unwrap_or(box_height + box_y),
+        descent.unwrap_or(-box_y),
+    );
+    for (c, glyph) in glyphs {
+        if let Some(c) = c {
+            font.insert(c, glyph);
+        }
+    }
+    if let Some(glyph) = default_char.and_then(|c| font.bitmap_glyph(c).cloned()) {
+        font.set_default_glyph(glyph);
+    }
+    Ok(font)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::{
+        css::computed_style::ComputedStyle, font::glyph::Font, layout::font_metrics::FontMetrics,
+    };
+
+    const FONT: &str = "STARTFONT 2.1
+FONT -misc-test
+SIZE 6 75 75
+FONTBOUNDINGBOX 4 6 0 -1
+STARTPROPERTIES 2
+FONT_ASCENT 5
+FONT_DESCENT 1
+DEFAULT_CHAR 63
+ENDPROPERTIES
+CHARS 3
+STARTCHAR question
+ENCODING 63
+SWIDTH 500 0
+DWIDTH 4 0
+BBX 3 3 0 2
+BITMAP
+E0
+20
+40
+ENDCHAR
+STARTCHAR g
+ENCODING 103
+DWIDTH 5 0
+BBX 4 2 1 -1
+BITMAP
+F0
+10
+ENDCHAR
+STARTCHAR unencoded
+ENCODING -1
+BITMAP
+00
+ENDCHAR
+ENDFONT
+";
+
+    #[test]
+    fn test_parse_bdf() {
+        let font = parse_bdf(FONT).unwrap();
+        assert_eq!(2, font.glyph_count());
+        assert_eq!(6, font.pixel_size());
+
+        let g = font.bitmap_glyph('g').unwrap();
+        assert_eq!(
+            (4, 2, 1, 1, 5),
+            (g.width, g.height, g.left, g.top, g.advance)
+        );
+        let glyph = font.glyph('g', 6.0).unwrap();
+        assert_eq!(255, glyph.coverage_at(3, 1));
+        assert_eq!(0, glyph.coverage_at(2, 1));
+
+        // ない文字はDEFAULT_CHARで描く
+        assert!(!font.has_glyph('z'));
+        assert_eq!(5, font.glyph('z', 6.0).unwrap().top);
+        let style = ComputedStyle {
+            font_size: 12.0,
+            ..Default::default()
+        };
+        assert_eq!(8.0, font.advance('z', &style));
+        assert_eq!(10.0, font.ascent(&style));
+
+        assert!(parse_bdf("STARTFONT 2.1\nSTARTCHAR a\nBITMAP\nzz\n").is_err());
+        assert!(parse_bdf("hello").is_err());
+    }
+}
@@ -0,0 +1,211 @@
+use crate::{
+    renderer::{
+        css::computed_style::ComputedStyle,
+        font::glyph::{Font, Glyph},
+        layout::font_metrics::FontMetrics,
+    },
+    utils::{ceil, floor},
+};
+use alloc::{collections::BTreeMap, vec::Vec};
+
+// 拡大縮小するときの1画素あたりの縦横の標本数
+const SAMPLES: usize = 4;
+
+// ビットマップフォントの1文字
+#[derive(Clone, Debug, PartialEq)]
+pub struct BitmapGlyph {
+    pub width: usize,
+    pub height: usize,
+    // 原点から左端までの距離と、ベースラインから上端までの高さ
+    pub left: i32,
+    pub top: i32,
+    pub advance: i32,
+    // 行ごとに並べた、画素が点灯しているかどうか
+    pub bits: Vec<bool>,
+}
+
+impl BitmapGlyph {
+    // 1行を(width + 7) / 8バイトにまとめ、上位ビットから左の画素を表すデータから作る。足りない部分は消灯とする
+    pub fn from_packed_rows(
+        width: usize,
+        height: usize,
+        left: i32,
+        top: i32,
+        advance: i32,
+        data: &[u8],
+    ) -> Self {
+        let stride = width.div_ceil(8);
+        let mut bits = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let byte = data.get(y * stride + x / 8).copied().unwrap_or(0);
+                bits.push(byte & (0x80 >> (x % 8)) != 0);
+            }
+        }
+        Self {
+            width,
+            height,
+            left,
+            top,
+            advance,
+            bits,
+        }
+    }
+
+    fn bit(&self, x: f64, y: f64) -> bool {
+        if x < 0.0 || y < 0.0 || x >= self.width as f64 || y >= self.height as f64 {
+            return false;
+        }
+        self.bits[y as usize * self.width + x as usize]
+    }
+}
+
+// PSFやBDFから読み込んだ、決まった大きさの画素で文字を持つフォント
+#[derive(Clone, Debug, PartialEq)]
+pub struct BitmapFont {
+    // ベースラインから上端と下端までの画素数。合わせたものがフォントの大きさになる
+    ascent: i32,
+    descent: i32,
+    glyphs: BTreeMap<char, BitmapGlyph>,
+    // フォントにない文字の代わりに描く形
+    default_glyph: Option<BitmapGlyph>,
+}
+
+impl BitmapFont {
+    pub fn new(ascent: i32, descent: i32) -> Self {
+        Self {
+            ascent,
+            descent,
+            glyphs: BTreeMap::new(),
+            default_glyph: None,
+        }
+    }
+
+    pub fn insert(&mut self, c: char, glyph: BitmapGlyph) {
+        self.glyphs.insert(c, glyph);
+    }
+
+    pub fn set_default_glyph(&mut self, glyph: BitmapGlyph) {
+        self.default_glyph = Some(glyph);
+    }
+
+    pub fn pixel_size(&self) -> i32 {
+        self.ascent + self.descent
+    }
+
+    pub fn glyph_count(&self) -> usize {
+        self.glyphs.len()
+    }
+
+    pub fn bitmap_glyph(&self, c: char) -> Option<&BitmapGlyph> {
+        self.glyphs.get(&c).or(self.default_glyph.as_ref())
+    }
+
+    // フォントサイズsizeで描くときの倍率
+    fn scale(&self, size: f64) -> f64 {
+        if self.pixel_size() <= 0 {
+            return 1.0;
+        }
+        size / self.pixel_size() as f64
+    }
+}
+
+impl FontMetrics for BitmapFont {
+    fn advance(&self, c: char, style: &ComputedStyle) -> f64 {
+        self.bitmap_glyph(c)
+            .map_or(0.0, |g| g.advance as f64 * self.scale(style.font_size))
+    }
+
+    fn ascent(&self, style: &ComputedStyle) -> f64 {
+        self.ascent as f64 * self.scale(style.font_size)
+    }
+
+    fn descent(&self, style: &ComputedStyle) -> f64 {
+        self.descent as f64 * self.scale(style.font_size)
+    }
+}
+
+impl Font for BitmapFont {
+    fn has_glyph(&self, c: char) -> bool {
+        self.glyphs.contains_key(&c)
+    }
+
+    // 大きさが違うときは、拡大縮小した画素が元の画素と重なる割合を被覆率にする
+    fn glyph(&self, c: char, size: f64) -> Option<Glyph> {
+        let source = self.bitmap_glyph(c)?;
+        let scale = self.scale(size);
+        let left = floor(source.left as f64 * scale);
+        let right = ceil((source.left + source.width as i32) as f64 * scale);
+        let top = ceil(source.top as f64 * scale);
+        let bottom = floor((source.top - source.height as i32) as f64 * scale);
+        let width = (right - left).max(0.0) as usize;
+        let height = (top - bottom).max(0.0) as usize;
+        let mut coverage = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let mut count = 0;
+                for j in 0..SAMPLES {
+                    for i in 0..SAMPLES {
+                        // 上向きを正とするベースラインからの位置を元の画素の位置に戻す
+                        let px = left + x as f64 + (i as f64 + 0.5) / SAMPLES as f64;
+                        let py = top - y as f64 - (j as f64 + 0.5) / SAMPLES as f64;
+                        let sx = px / scale - source.left as f64;
+                        let sy = source.top as f64 - py / scale;
+                        if source.bit(sx, sy) {
+                            count += 1;
+                        }
+                    }
+                }
+                coverage.push((count * 255 / (SAMPLES * SAMPLES)) as u8);
+            }
+        }
+        Some(Glyph {
+            width,
+            height,
+            left: left as i32,
+            top: top as i32,
+            coverage,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_scale_glyph() {
+        let mut font = BitmapFont::new(2, 1);
+        // 左上と右下が点灯した2x2の形
+        font.insert(
+            'a',
+            BitmapGlyph::from_packed_rows(2, 2, 0, 2, 3, &[0b1000_0000, 0b0100_0000]),
+        );
+        let glyph = font.glyph('a', 3.0).unwrap();
+        assert_eq!(
+            (2, 2, 0, 2),
+            (glyph.width, glyph.height, glyph.left, glyph.top)
+        );
+        assert_eq!(vec![255, 0, 0, 255], glyph.coverage);
+
+        let glyph = font.glyph('a', 6.0).unwrap();
+        assert_eq!((4, 4, 4), (glyph.width, glyph.height, glyph.top));
+        assert_eq!(255, glyph.coverage_at(1, 1));
+        assert_eq!(0, glyph.coverage_at(2, 1));
+
+        let glyph = font.glyph('a', 4.5).unwrap();
+        assert_eq!((3, 3), (glyph.width, glyph.height));
+        assert!(glyph.coverage_at(1, 1) > 0 && glyph.coverage_at(1, 1) < 255);
+
+        let style = ComputedStyle {
+            font_size: 6.0,
+            ..Default::default()
+        };
+        assert_eq!(6.0, font.advance('a', &style));
+        assert_eq!(4.0, font.ascent(&style));
+        // 代わりの形がなければ描けない
+        assert_eq!(0.0, font.advance('b', &style));
+        assert_eq!(None, font.glyph('b', 6.0));
+    }
+}
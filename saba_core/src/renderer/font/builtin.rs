@@ -0,0 +1,142 @@
+use crate::renderer::font::bitmap::{BitmapFont, BitmapGlyph};
+
+// 外部のファイルがなくても文字を描けるように持っておく8x16のASCIIフォント。
+// X11のmisc-fixed 8x13(パブリックドメイン)の形を上に1行、下に2行空けて置いたもの
+const WIDTH: usize = 8;
+const HEIGHT: usize = 16;
+const ASCENT: i32 = 12;
+const DESCENT: i32 = 4;
+
+// 1つの値が1文字で、上位のバイトから順に上の行を表す
+const NOTDEF: u128 = 0x000000aa_00820082_008200aa_00000000;
+const GLYPHS: [u128; 95] = [
+    0x00000000_00000000_00000000_00000000, // ' '
+    0x00000010_10101010_10100010_00000000, // '!'
+    0x00000024_24240000_00000000_00000000, // '"'
+    0x00000000_24247e24_7e242400_00000000, // '#'
+    0x00000010_3c505038_14147810_00000000, // '$'
+    0x00000022_52240808_10242a44_00000000, // '%'
+    0x00000000_00304848_304a443a_00000000, // '&'
+    0x00000010_10100000_00000000_00000000, // '\''
+    0x00000004_08081010_10080804_00000000, // '('
+    0x00000020_10100808_08101020_00000000, // ')'
+    0x00000024_187e1824_00000000_00000000, // '*'
+    0x00000000_0010107c_10100000_00000000, // '+'
+    0x00000000_00000000_00003830_40000000, // ','
+    0x00000000_0000007c_00000000_00000000, // '-'
+    0x00000000_00000000_00001038_10000000, // '.'
+    0x00000002_02040810_20408080_00000000, // '/'
+    0x00000018_24424242_42422418_00000000, // '0'
+    0x00000010_30501010_1010107c_00000000, // '1'
+    0x0000003c_42420204_1820407e_00000000, // '2'
+    0x0000007e_0204081c_0202423c_00000000, // '3'
+    0x00000004_0c142444_447e0404_00000000, // '4'
+    0x0000007e_40405c62_0202423c_00000000, // '5'
+    0x0000001c_2040405c_6242423c_00000000, // '6'
+    0x0000007e_02040808_10102020_00000000, // '7'
+    0x0000003c_4242423c_4242423c_00000000, // '8'
+    0x0000003c_4242463a_02020438_00000000, // '9'
+    0x00000000_00103810_00001038_10000000, // ':'
+    0x00000000_00103810_00003830_40000000, // ';'
+    0x00000002_04081020_10080402_00000000, // '<'
+    0x00000000_00007e00_007e0000_00000000, // '='
+    0x00000040_20100804_08102040_00000000, // '>'
+    0x0000003c_42420204_08080008_00000000, // '?'
+    0x0000003c_42424e52_564a403c_00000000, // '@'
+    0x00000018_24424242_7e424242_00000000, // 'A'
+    0x00000078_44424478_44424478_00000000, // 'B'
+    0x0000003c_42404040_4040423c_00000000, // 'C'
+    0x00000078_44424242_42424478_00000000, // 'D'
+    0x0000007e_40404078_4040407e_00000000, // 'E'
+    0x0000007e_40404078_40404040_00000000, // 'F'
+    0x0000003c_42404040_4e42463a_00000000, // 'G'
+    0x00000042_4242427e_42424242_00000000, // 'H'
+    0x0000007c_10101010_1010107c_00000000, // 'I'
+    0x0000001f_04040404_04044438_00000000, // 'J'
+    0x00000042_44485060_50484442_00000000, // 'K'
+    0x00000040_40404040_4040407e_00000000, // 'L'
+    0x00000082_82c6aa92_92828282_00000000, // 'M'
+    0x00000042_4262524a_46424242_00000000, // 'N'
+    0x0000003c_42424242_4242423c_00000000, // 'O'
+    0x0000007c_4242427c_40404040_00000000, // 'P'
+    0x0000003c_42424242_42524a3c_02000000, // 'Q'
+    0x0000007c_4242427c_50484442_00000000, // 'R'
+    0x0000003c_4240403c_0202423c_00000000, // 'S'
+    0x000000fe_10101010_10101010_00000000, // 'T'
+    0x00000042_42424242_4242423c_00000000, // 'U'
+    0x00000082_82444444_28282810_00000000, // 'V'
+    0x00000082_82828292_9292aa44_00000000, // 'W'
+    0x00000082_82442810_28448282_00000000, // 'X'
+    0x00000082_82442810_10101010_00000000, // 'Y'
+    0x0000007e_02040810_2040407e_00000000, // 'Z'
+    0x0000003c_20202020_2020203c_00000000, // '['
+    0x00000080_80402010_08040202_00000000, // '\\'
+    0x00000078_08080808_08080878_00000000, // ']'
+    0x00000010_28440000_00000000_00000000, // '^'
+    0x00000000_00000000_00000000_fe000000, // '_'
+    0x00001008_00000000_00000000_00000000, // '`'
+    0x00000000_00003c02_3e42463a_00000000, // 'a'
+    0x00000040_40405c62_4242625c_00000000, // 'b'
+    0x00000000_00003c42_4040423c_00000000, // 'c'
+    0x00000002_02023a46_4242463a_00000000, // 'd'
+    0x00000000_00003c42_7e40423c_00000000, // 'e'
+    0x0000001c_2220207c_20202020_00000000, // 'f'
+    0x00000000_00003a44_4438403c_423c0000, // 'g'
+    0x00000040_40405c62_42424242_00000000, // 'h'
+    0x00000000_10003010_1010107c_00000000, // 'i'
+    0x00000000_04000c04_04040444_44380000, // 'j'
+    0x00000040_40404448_70484442_00000000, // 'k'
+    0x00000030_10101010_1010107c_00000000, // 'l'
+    0x00000000_0000ec92_92929282_00000000, // 'm'
+    0x00000000_00005c62_42424242_00000000, // 'n'
+    0x00000000_00003c42_4242423c_00000000, // 'o'
+    0x00000000_00005c62_42625c40_40400000, // 'p'
+    0x00000000_00003a46_42463a02_02020000, // 'q'
+    0x00000000_00005c22_20202020_00000000, // 'r'
+    0x00000000_00003c42_300c423c_00000000, // 's'
+    0x00000000_20207c20_2020221c_00000000, // 't'
+    0x00000000_00004444_4444443a_00000000, // 'u'
+    0x00000000_00004444_44282810_00000000, // 'v'
+    0x00000000_00008282_9292aa44_00000000, // 'w'
+    0x00000000_00004224_18182442_00000000, // 'x'
+    0x00000000_00004242_42463a02_423c0000, // 'y'
+    0x00000000_00007e04_0810207e_00000000, // 'z'
+    0x0000000e_10100830_0810100e_00000000, // '{'
+    0x00000010_10101010_10101010_00000000, // '|'
+    0x00000070_0808100c_10080870_00000000, // '}'
+    0x00000024_54480000_00000000_00000000, // '~'
+];
+
+fn glyph(rows: u128) -> BitmapGlyph {
+    BitmapGlyph::from_packed_rows(WIDTH, HEIGHT, 0, ASCENT, WIDTH as i32, &rows.to_be_bytes())
+}
+
+pub fn default_font() -> BitmapFont {
+    let mut font = BitmapFont::new(ASCENT, DESCENT);
+    for (c, rows) in (' '..='~').zip(GLYPHS) {
+        font.insert(c, glyph(rows));
+    }
+    font.set_default_glyph(glyph(NOTDEF));
+    font
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::font::glyph::Font;
+
+    #[test]
+    fn test_default_font() {
+        let font = default_font();
+        assert_eq!(95, font.glyph_count());
+        assert_eq!(16, font.pixel_size());
+        let glyph = font.glyph('A', 16.0).unwrap();
+        assert_eq!((8, 16, 12), (glyph.width, glyph.height, glyph.top));
+        // Aの横棒
+        assert!((1..7).all(|x| glyph.coverage_at(x, 8) == 255));
+        assert_eq!(0, glyph.coverage_at(3, 12));
+        // ないASCII以外の文字は枠の形で描く
+        assert!(!font.has_glyph('あ'));
+        assert_eq!(255, font.glyph('あ', 16.0).unwrap().coverage_at(0, 3));
+    }
+}
@@ -0,0 +1,162 @@
+use crate::{
+    error::Error,
+    renderer::{
+        css::computed_style::ComputedStyle,
+        font::{
+            bdf::parse_bdf,
+            builtin::default_font,
+            glyph::{Font, Glyph, GlyphCache},
+            psf::parse_psf2,
+            truetype::TrueTypeFont,
+        },
+        layout::font_metrics::{is_zero_width, FontMetrics},
+    },
+};
+use alloc::{
+    rc::Rc,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::cell::RefCell;
+
+// 文書で使うフォントの集まり。font-familyの名前で選び、選んだフォントにない文字は既定のフォントで描く
+pub struct FontSystem {
+    // ファミリー名とフォント。同じ名前なら後から加えたものを優先する
+    fonts: RefCell<Vec<(String, Rc<dyn Font>)>>,
+    fallback: Rc<dyn Font>,
+    cache: RefCell<GlyphCache>,
+}
+
+impl Default for FontSystem {
+    fn default() -> Self {
+        Self::new(Rc::new(default_font()))
+    }
+}
+
+impl FontSystem {
+    pub fn new(fallback: Rc<dyn Font>) -> Self {
+        Self {
+            fonts: RefCell::new(Vec::new()),
+            fallback,
+            cache: RefCell::new(GlyphCache::new()),
+        }
+    }
+
+    pub fn add_font(&self, family: &str, font: Rc<dyn Font>) {
+        self.fonts
+            .borrow_mut()
+            .push((family.to_ascii_lowercase(), font));
+        self.cache.borrow_mut().clear();
+    }
+
+    pub fn families(&self) -> Vec<String> {
+        self.fonts
+            .borrow()
+            .iter()
+            .map(|(family, _)| family.clone())
+            .collect()
+    }
+
+    // font-familyの順に探したフォントとその番号。0は既定のフォントを表す
+    fn select(&self, style: &ComputedStyle, c: Option<char>) -> (usize, Rc<dyn Font>) {
+        let fonts = self.fonts.borrow();
+        for family in &style.font_family {
+            let found = fonts.iter().enumerate().rev().find(|(_, (name, font))| {
+                name.eq_ignore_ascii_case(family) && c.map_or(true, |c| font.has_glyph(c))
+            });
+            if let Some((i, (_, font))) = found {
+                return (i + 1, font.clone());
+            }
+        }
+        (0, self.fallback.clone())
+    }
+
+    // 文字の形。描いたものはキャッシュしておく
+    pub fn glyph(&self, c: char, style: &ComputedStyle) -> Option<Rc<Glyph>> {
+        if is_zero_width(c) {
+            return None;
+        }
+        let (id, font) = self.select(style, Some(c));
+        self.cache
+            .borrow_mut()
+            .get_or_insert(id, c, style.font_size, font.as_ref())
+    }
+
+    pub fn cached_glyphs(&self) -> usize {
+        self.cache.borrow().len()
+    }
+}
+
+impl FontMetrics for FontSystem {
+    fn advance(&self, c: char, style: &ComputedStyle) -> f64 {
+        if is_zero_width(c) {
+            return 0.0;
+        }
+        self.select(style, Some(c)).1.advance(c, style)
+    }
+
+    fn ascent(&self, style: &ComputedStyle) -> f64 {
+        self.select(style, None).1.ascent(style)
+    }
+
+    fn descent(&self, style: &ComputedStyle) -> f64 {
+        self.select(style, None).1.descent(style)
+    }
+
+    // 2つの文字が同じフォントで描かれるときだけ調整する
+    fn kerning(&self, left: char, right: char, style: &ComputedStyle) -> f64 {
+        let (left_id, font) = self.select(style, Some(left));
+        if self.select(style, Some(right)).0 != left_id {
+            return 0.0;
+        }
+        font.kerning(left, right, style)
+    }
+}
+
+// 先頭のバイトから形式を判断してフォントファイルを読み込む
+pub fn parse_font(data: Vec<u8>) -> Result<Rc<dyn Font>, Error> {
+    if data.starts_with(&[0x72, 0xb5, 0x4a, 0x86]) {
+        return Ok(Rc::new(parse_psf2(&data)?));
+    }
+    if data.starts_with(b"STARTFONT") {
+        let text = core::str::from_utf8(&data)
+            .map_err(|_| Error::UnexpectedInput("BDF font is not UTF-8".to_string()))?;
+        return Ok(Rc::new(parse_bdf(text)?));
+    }
+    Ok(Rc::new(TrueTypeFont::parse(data)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::font::bitmap::{BitmapFont, BitmapGlyph};
+    use alloc::vec;
+
+    #[test]
+    fn test_select_font() {
+        let fonts = FontSystem::default();
+        let mut wide = BitmapFont::new(8, 2);
+        wide.insert('a', BitmapGlyph::from_packed_rows(1, 1, 0, 1, 20, &[0x80]));
+        fonts.add_font("Wide", Rc::new(wide));
+
+        let mut style = ComputedStyle {
+            font_size: 10.0,
+            font_family: vec!["unknown".to_string(), "wide".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(20.0, fonts.advance('a', &style));
+        assert_eq!(8.0, fonts.ascent(&style));
+        // フォントにない文字は既定のフォントで測る
+        assert_eq!(5.0, fonts.advance('b', &style));
+        assert_eq!(0.0, fonts.advance('\u{200b}', &style));
+        assert_eq!(25.0, fonts.text_width("ab", &style));
+
+        style.font_family = vec!["serif".to_string()];
+        assert_eq!(7.5, fonts.ascent(&style));
+        assert_eq!(Some(5), fonts.glyph('a', &style).map(|g| g.width));
+        fonts.glyph('a', &style);
+        fonts.glyph('b', &style);
+        assert_eq!(2, fonts.cached_glyphs());
+        assert_eq!(vec!["wide".to_string()], fonts.families());
+    }
+}
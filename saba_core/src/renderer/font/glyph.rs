@@ -0,0 +1,79 @@
+use crate::renderer::layout::font_metrics::FontMetrics;
+use alloc::{collections::BTreeMap, rc::Rc, vec::Vec};
+
+// キャッシュに置いておく文字の形の数
+const CACHE_CAPACITY: usize = 1024;
+
+// 描画する1文字分の形。coverageは左上から行ごとに並べた0から255の被覆率
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Glyph {
+    pub width: usize,
+    pub height: usize,
+    // ペンの位置から左端までの距離と、ベースラインから上端までの高さ
+    pub left: i32,
+    pub top: i32,
+    pub coverage: Vec<u8>,
+}
+
+impl Glyph {
+    pub fn coverage_at(&self, x: usize, y: usize) -> u8 {
+        if x >= self.width || y >= self.height {
+            return 0;
+        }
+        self.coverage[y * self.width + x]
+    }
+}
+
+// 文字の形を描けるフォント
+pub trait Font: FontMetrics {
+    fn has_glyph(&self, c: char) -> bool;
+
+    // 大きさsize(px)の文字の形。フォントにない文字は代わりの形になる
+    fn glyph(&self, c: char, size: f64) -> Option<Glyph>;
+}
+
+// 一度描いた文字の形を、フォントの番号、文字、大きさで引けるように覚えておく
+#[derive(Debug, Default)]
+pub struct GlyphCache {
+    glyphs: BTreeMap<(usize, char, u64), Option<Rc<Glyph>>>,
+}
+
+impl GlyphCache {
+    pub fn new() -> Self {
+        Self {
+            glyphs: BTreeMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.glyphs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.glyphs.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.glyphs.clear();
+    }
+
+    pub fn get_or_insert(
+        &mut self,
+        font_id: usize,
+        c: char,
+        size: f64,
+        font: &dyn Font,
+    ) -> Option<Rc<Glyph>> {
+        let key = (font_id, c, size.to_bits());
+        if let Some(glyph) = self.glyphs.get(&key) {
+            return glyph.clone();
+        }
+        // いっぱいになったらまとめて捨てる
+        if self.glyphs.len() >= CACHE_CAPACITY {
+            self.glyphs.clear();
+        }
+        let glyph = font.glyph(c, size).map(Rc::new);
+        self.glyphs.insert(key, glyph.clone());
+        glyph
+    }
+}
@@ -0,0 +1,8 @@
+pub mod bdf;
+pub mod bitmap;
+pub mod builtin;
+pub mod font_system;
+pub mod glyph;
+pub mod outline;
+pub mod psf;
+pub mod truetype;
@@ -0,0 +1,282 @@
+use crate::{
+    renderer::font::glyph::Glyph,
+    utils::{ceil, floor, sqrt},
+};
+use alloc::{vec, vec::Vec};
+
+// 曲線を直線に分けるときに許す、曲線からのずれ(px)
+const TOLERANCE: f64 = 0.2;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Point {
+    pub fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+
+    fn lerp(&self, other: &Point, t: f64) -> Point {
+        Point::new(
+            self.x + (other.x - self.x) * t,
+            self.y + (other.y - self.y) * t,
+        )
+    }
+}
+
+// 上向きを正とするフォントの座標で表した文字の輪郭
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Outline {
+    // 閉じた輪郭ごとの直線の端点
+    contours: Vec<Vec<Point>>,
+}
+
+impl Outline {
+    pub fn new() -> Self {
+        Self {
+            contours: Vec::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.contours.is_empty()
+    }
+
+    pub fn contours(&self) -> &[Vec<Point>] {
+        &self.contours
+    }
+
+    pub fn move_to(&mut self, p: Point) {
+        self.contours.push(vec![p]);
+    }
+
+    pub fn line_to(&mut self, p: Point) {
+        if let Some(contour) = self.contours.last_mut() {
+            contour.push(p);
+        }
+    }
+
+    // 2次ベジェ曲線。フォントの単位で分けると大きく描いたときに角張るため、scaleを掛けた大きさで分割数を決める
+    pub fn quad_to(&mut self, control: Point, p: Point, scale: f64) {
+        let Some(&start) = self.contours.last().and_then(|c| c.last()) else {
+            return;
+        };
+        let dx = (start.x - 2.0 * control.x + p.x) * scale;
+        let dy = (start.y - 2.0 * control.y + p.y) * scale;
+        let deviation = sqrt(dx * dx + dy * dy) / 4.0;
+        let segments = ceil(sqrt(deviation / TOLERANCE)).clamp(1.0, 64.0) as usize;
+        for i in 1..=segments {
+            let t = i as f64 / segments as f64;
+            let a = start.lerp(&control, t);
+            let b = control.lerp(&p, t);
+            self.line_to(a.lerp(&b, t));
+        }
+    }
+
+    // 座標をa, b, c, dの行列で変換してから(dx, dy)だけずらした輪郭を加える
+    pub fn append(&mut self, other: &Outline, transform: [f64; 6]) {
+        let [a, b, c, d, dx, dy] = transform;
+        for contour in &other.contours {
+            self.contours.push(
+                contour
+                    .iter()
+                    .map(|p| Point::new(a * p.x + c * p.y + dx, b * p.x + d * p.y + dy))
+                    .collect(),
+            );
+        }
+    }
+
+    // scale倍した輪郭を、画素ごとの面積を積み上げて塗る
+    pub fn rasterize(&self, scale: f64) -> Glyph {
+        let points = || self.contours.iter().flatten();
+        if points().next().is_none() {
+            return Glyph::default();
+        }
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (f64::MAX, f64::MAX, f64::MIN, f64::MIN);
+        for p in points() {
+            min_x = min_x.min(p.x * scale);
+            min_y = min_y.min(p.y * scale);
+            max_x = max_x.max(p.x * scale);
+            max_y = max_y.max(p.y * scale);
+        }
+        let left = floor(min_x);
+        let top = ceil(max_y);
+        let width = (ceil(max_x) - left) as usize;
+        let height = (top - floor(min_y)) as usize;
+
+        let mut accumulator = Accumulator::new(width, height);
+        for contour in &self.contours {
+            for (i, p) in contour.iter().enumerate() {
+                let next = &contour[(i + 1) % contour.len()];
+                accumulator.line(
+                    Point::new(p.x * scale - left, top - p.y * scale),
+                    Point::new(next.x * scale - left, top - next.y * scale),
+                );
+            }
+        }
+        Glyph {
+            width,
+            height,
+            left: left as i32,
+            top: top as i32,
+            coverage: accumulator.coverage(),
+        }
+    }
+}
+
+// 線分が画素の左側に作る面積の変化を記録し、行ごとに足し合わせると覆う割合になる
+struct Accumulator {
+    width: usize,
+    height: usize,
+    area: Vec<f64>,
+}
+
+impl Accumulator {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            // 右端の画素の次にはみ出す分を受ける
+            area: vec![0.0; width * height + 2],
+        }
+    }
+
+    fn add(&mut self, index: usize, value: f64) {
+        if let Some(a) = self.area.get_mut(index) {
+            *a += value;
+        }
+    }
+
+    fn line(&mut self, p0: Point, p1: Point) {
+        if p0.y == p1.y {
+            return;
+        }
+        // 上から下へ向かう線分に揃え、向きは符号で表す
+        let (direction, p0, p1) = if p0.y < p1.y {
+            (1.0, p0, p1)
+        } else {
+            (-1.0, p1, p0)
+        };
+        let slope = (p1.x - p0.x) / (p1.y - p0.y);
+        let width = self.width as f64;
+        let mut x = p0.x;
+        let (y0, y1) = (p0.y.max(0.0), p1.y.min(self.height as f64));
+        if p0.y < 0.0 {
+            x -= p0.y * slope;
+        }
+        let mut y = floor(y0);
+        while y < y1 {
+            let row = y as usize * self.width;
+            let dy = (y + 1.0).min(y1) - y.max(y0);
+            let next_x = x + slope * dy;
+            let d = dy * direction;
+            let (x0, x1) = if x < next_x { (x, next_x) } else { (next_x, x) };
+            let (x0, x1) = (x0.clamp(0.0, width), x1.clamp(0.0, width));
+            let x0_floor = floor(x0);
+            let x0_index = x0_floor as usize;
+            let x1_ceil = ceil(x1);
+            let x1_index = x1_ceil as usize;
+            if x1_index <= x0_index + 1 {
+                // 1つの画素の中で終わる線分は、横の中点で左右に分ける
+                let middle = (x0 + x1) / 2.0 - x0_floor;
+                self.add(row + x0_index, d - d * middle);
+                self.add(row + x0_index + 1, d * middle);
+            } else {
+                // 複数の画素にまたがる線分は、画素ごとに台形の面積を求める
+                let s = 1.0 / (x1 - x0);
+                let x0_fraction = x0 - x0_floor;
+                let first = 0.5 * s * (1.0 - x0_fraction) * (1.0 - x0_fraction);
+                let x1_fraction = x1 - x1_ceil + 1.0;
+                let last = 0.5 * s * x1_fraction * x1_fraction;
+                self.add(row + x0_index, d * first);
+                if x1_index == x0_index + 2 {
+                    self.add(row + x0_index + 1, d * (1.0 - first - last));
+                } else {
+                    let second = s * (1.5 - x0_fraction);
+                    self.add(row + x0_index + 1, d * (second - first));
+                    for index in x0_index + 2..x1_index - 1 {
+                        self.add(row + index, d * s);
+                    }
+                    let before_last = second + (x1_index - x0_index - 3) as f64 * s;
+                    self.add(row + x1_index - 1, d * (1.0 - before_last - last));
+                }
+                self.add(row + x1_index, d * last);
+            }
+            x = next_x;
+            y += 1.0;
+        }
+    }
+
+    // 重なった輪郭は巻き数によらず塗る
+    fn coverage(&self) -> Vec<u8> {
+        let mut total = 0.0;
+        self.area[..self.width * self.height]
+            .iter()
+            .map(|a| {
+                total += a;
+                (total.max(-total).min(1.0) * 255.0 + 0.5) as u8
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rectangle(x0: f64, y0: f64, x1: f64, y1: f64) -> Outline {
+        let mut outline = Outline::new();
+        outline.move_to(Point::new(x0, y0));
+        outline.line_to(Point::new(x1, y0));
+        outline.line_to(Point::new(x1, y1));
+        outline.line_to(Point::new(x0, y1));
+        outline
+    }
+
+    #[test]
+    fn test_rasterize() {
+        let glyph = rectangle(1.0, -1.0, 3.0, 2.0).rasterize(1.0);
+        assert_eq!(
+            (2, 3, 1, 2),
+            (glyph.width, glyph.height, glyph.left, glyph.top)
+        );
+        assert!(glyph.coverage.iter().all(|c| *c == 255));
+
+        // 半分だけ覆う画素
+        let glyph = rectangle(0.0, 0.0, 1.5, 1.0).rasterize(1.0);
+        assert_eq!(vec![255, 128], glyph.coverage);
+
+        // 内側の輪郭を逆向きに回すと穴になる
+        let mut outline = rectangle(0.0, 0.0, 3.0, 3.0);
+        outline.move_to(Point::new(1.0, 1.0));
+        outline.line_to(Point::new(1.0, 2.0));
+        outline.line_to(Point::new(2.0, 2.0));
+        outline.line_to(Point::new(2.0, 1.0));
+        let glyph = outline.rasterize(1.0);
+        assert_eq!(0, glyph.coverage_at(1, 1));
+        assert_eq!(255, glyph.coverage_at(0, 1));
+
+        // 斜めの辺を持つ三角形は面積の分だけ塗る
+        let mut outline = Outline::new();
+        outline.move_to(Point::new(0.0, 0.0));
+        outline.line_to(Point::new(4.0, 0.0));
+        outline.line_to(Point::new(0.0, 4.0));
+        let glyph = outline.rasterize(1.0);
+        let area: f64 = glyph.coverage.iter().map(|c| *c as f64 / 255.0).sum();
+        assert!((area - 8.0) * (area - 8.0) < 0.01);
+        assert_eq!(255, glyph.coverage_at(1, 2));
+        assert_eq!(128, glyph.coverage_at(2, 2));
+
+        // 曲線は拡大に合わせて細かく分ける
+        let mut outline = Outline::new();
+        outline.move_to(Point::new(0.0, 0.0));
+        outline.quad_to(Point::new(1.0, 2.0), Point::new(2.0, 0.0), 10.0);
+        assert!(outline.contours()[0].len() > 4);
+        assert_eq!(
+            Point::new(1.0, 1.0),
+            outline.contours()[0][outline.contours()[0].len() / 2]
+        );
+    }
+}
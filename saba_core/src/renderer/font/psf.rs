@@ -0,0 +1,150 @@
+use crate::{
+    error::Error,
+    renderer::font::bitmap::{BitmapFont, BitmapGlyph},
+};
+use alloc::{format, string::ToString};
+
+const PSF2_MAGIC: [u8; 4] = [0x72, 0xb5, 0x4a, 0x86];
+const PSF2_HEADER_SIZE: usize = 32;
+// 文字の形の後にUnicodeの対応表がある
+const PSF2_HAS_UNICODE_TABLE: u32 = 0x01;
+const PSF2_SEPARATOR: u8 = 0xff;
+const PSF2_START_SEQUENCE: u8 = 0xfe;
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+    ])
+}
+
+// UTF-8の先頭のバイトから1文字のバイト数を求める
+fn utf8_length(byte: u8) -> usize {
+    match byte {
+        0x00..=0x7f => 1,
+        0xc0..=0xdf => 2,
+        0xe0..=0xef => 3,
+        _ => 4,
+    }
+}
+
+// Linuxのコンソールで使われるPC Screen Font version 2
+pub fn parse_psf2(data: &[u8]) -> Result<BitmapFont, Error> {
+    if data.len() < PSF2_HEADER_SIZE || data[..4] != PSF2_MAGIC {
+        return Err(Error::UnexpectedInput("not a PSF2 font".to_string()));
+    }
+    let header_size = read_u32(data, 8) as usize;
+    let flags = read_u32(data, 12);
+    let count = read_u32(data, 16) as usize;
+    let glyph_size = read_u32(data, 20) as usize;
+    let height = read_u32(data, 24) as usize;
+    let width = read_u32(data, 28) as usize;
+    if glyph_size < width.div_ceil(8) * height {
+        return Err(Error::UnexpectedInput(format!(
+            "PSF2 glyph size {} is too small for {}x{}",
+            glyph_size, width, height
+        )));
+    }
+    let table_start = count
+        .checked_mul(glyph_size)
+        .and_then(|size| size.checked_add(header_size))
+        .filter(|end| *end <= data.len())
+        .ok_or_else(|| Error::UnexpectedInput("PSF2 glyph data is truncated".to_string()))?;
+
+    // 高さの4分の3をベースラインより上に置く
+    let ascent = (height * 3).div_ceil(4) as i32;
+    let mut font = BitmapFont::new(ascent, height as i32 - ascent);
+    let glyph = |index: usize| {
+        let start = header_size + index * glyph_size;
+        BitmapGlyph::from_packed_rows(
+            width,
+            height,
+            0,
+            ascent,
+            width as i32,
+            &data[start..start + glyph_size],
+        )
+    };
+
+    if flags & PSF2_HAS_UNICODE_TABLE == 0 {
+        for index in 0..count {
+            if let Some(c) = char::from_u32(index as u32) {
+                font.insert(c, glyph(index));
+            }
+        }
+    } else {
+        let mut position = table_start;
+        for index in 0..count {
+            // 結合文字の並びは1文字として扱えないため読み飛ばす
+            let mut in_sequence = false;
+            while let Some(&byte) = data.get(position) {
+                if byte == PSF2_SEPARATOR {
+                    position += 1;
+                    break;
+                }
+                if byte == PSF2_START_SEQUENCE {
+                    in_sequence = true;
+                    position += 1;
+                    continue;
+                }
+                let end = (position + utf8_length(byte)).min(data.len());
+                let c = core::str::from_utf8(&data[position..end])
+                    .ok()
+                    .and_then(|s| s.chars().next());
+                position = end;
+                if let (Some(c), false) = (c, in_sequence) {
+                    font.insert(c, glyph(index));
+                }
+            }
+        }
+    }
+
+    // 置換文字があればそれを、なければ最初の形を代わりに使う
+    let replacement = font.bitmap_glyph('\u{fffd}').cloned();
+    if count > 0 {
+        font.set_default_glyph(replacement.unwrap_or_else(|| glyph(0)));
+    }
+    Ok(font)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::font::glyph::Font;
+    use alloc::vec::Vec;
+
+    fn psf2(flags: u32, glyphs: &[[u8; 4]], table: &[u8]) -> Vec<u8> {
+        let mut data = PSF2_MAGIC.to_vec();
+        for value in [0, 32, flags, glyphs.len() as u32, 4, 4, 4] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        for glyph in glyphs {
+            data.extend_from_slice(glyph);
+        }
+        data.extend_from_slice(table);
+        data
+    }
+
+    #[test]
+    fn test_parse_psf2() {
+        let glyphs = [[0xf0, 0, 0, 0], [0, 0, 0, 0xf0]];
+        // 1つ目はAとÅ、2つ目はBと結合文字の並び
+        let table = [b'A', 0xc3, 0x85, 0xff, b'B', 0xfe, b'x', 0xcc, 0x8a, 0xff];
+        let font = parse_psf2(&psf2(PSF2_HAS_UNICODE_TABLE, &glyphs, &table)).unwrap();
+        assert_eq!(3, font.glyph_count());
+        assert_eq!(font.bitmap_glyph('A'), font.bitmap_glyph('Å'));
+        assert!(font.has_glyph('B') && !font.has_glyph('x'));
+        let glyph = font.glyph('B', 4.0).unwrap();
+        assert_eq!((4, 4, 3), (glyph.width, glyph.height, glyph.top));
+        assert_eq!(255, glyph.coverage_at(0, 3));
+
+        // 対応表がなければ位置がそのまま文字の番号になる
+        let font = parse_psf2(&psf2(0, &glyphs, &[])).unwrap();
+        assert!(font.has_glyph('\u{1}') && !font.has_glyph('A'));
+
+        assert!(parse_psf2(&psf2(0, &glyphs, &[])[..39]).is_err());
+        assert!(parse_psf2(b"not a font").is_err());
+    }
+}
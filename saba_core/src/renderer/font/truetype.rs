@@ -0,0 +1,819 @@
+use crate::{
+    error::Error,
+    renderer::{
+        css::computed_style::ComputedStyle,
+        font::{
+            glyph::{Font, Glyph},
+            outline::{Outline, Point},
+        },
+        layout::font_metrics::FontMetrics,
+    },
+};
+use alloc::{format, string::ToString, vec::Vec};
+
+// 複合グリフが参照をたどる深さの上限
+const MAX_COMPONENT_DEPTH: usize = 8;
+
+// glyfテーブルの点のフラグ
+const ON_CURVE: u8 = 0x01;
+const X_SHORT: u8 = 0x02;
+const Y_SHORT: u8 = 0x04;
+const REPEAT: u8 = 0x08;
+const X_SAME_OR_POSITIVE: u8 = 0x10;
+const Y_SAME_OR_POSITIVE: u8 = 0x20;
+
+// 複合グリフの部品のフラグ
+const ARGS_ARE_WORDS: u16 = 0x0001;
+const ARGS_ARE_XY_VALUES: u16 = 0x0002;
+const HAS_SCALE: u16 = 0x0008;
+const MORE_COMPONENTS: u16 = 0x0020;
+const HAS_XY_SCALE: u16 = 0x0040;
+const HAS_TWO_BY_TWO: u16 = 0x0080;
+
+// GPOSのValueRecordでXAdvanceがあることを表すビット
+const X_ADVANCE: u16 = 0x0004;
+
+// 範囲外を読んだときは0とみなし、壊れたフォントでも描画を止めない
+fn u8_at(data: &[u8], offset: usize) -> u8 {
+    data.get(offset).copied().unwrap_or(0)
+}
+
+fn u16_at(data: &[u8], offset: usize) -> u16 {
+    u16::from_be_bytes([u8_at(data, offset), u8_at(data, offset + 1)])
+}
+
+fn i16_at(data: &[u8], offset: usize) -> i16 {
+    u16_at(data, offset) as i16
+}
+
+fn u32_at(data: &[u8], offset: usize) -> u32 {
+    (u16_at(data, offset) as u32) << 16 | u16_at(data, offset + 2) as u32
+}
+
+// 2.14形式の固定小数点数
+fn f2dot14_at(data: &[u8], offset: usize) -> f64 {
+    i16_at(data, offset) as f64 / 16384.0
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum CharacterMap {
+    // セグメントごとに文字の範囲を対応付ける形式4
+    Segments(usize),
+    // 32ビットの文字を範囲ごとに対応付ける形式12
+    Groups(usize),
+}
+
+// TrueTypeの輪郭を持つTrueType/OpenTypeフォント。CFFの輪郭は扱わない
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrueTypeFont {
+    data: Vec<u8>,
+    units_per_em: u16,
+    ascender: i16,
+    descender: i16,
+    glyph_count: u16,
+    long_loca: bool,
+    horizontal_metrics: u16,
+    cmap: CharacterMap,
+    loca: usize,
+    glyf: usize,
+    hmtx: usize,
+    kern: Option<usize>,
+    // GPOSのkern機能が使うペア調整のサブテーブル
+    pair_adjustments: Vec<usize>,
+}
+
+impl TrueTypeFont {
+    pub fn parse(data: Vec<u8>) -> Result<Self, Error> {
+        // フォントコレクションは最初のフォントを使う
+        let start = if data.get(..4) == Some(b"ttcf") {
+            u32_at(&data, 12) as usize
+        } else {
+            0
+        };
+        match u32_at(&data, start) {
+            0x0001_0000 | 0x7472_7565 => {}
+            0x4f54_544f => {
+                return Err(Error::UnexpectedInput(
+                    "CFF outlines are not supported".to_string(),
+                ))
+            }
+            _ => return Err(Error::UnexpectedInput("not a TrueType font".to_string())),
+        }
+
+        let table_count = u16_at(&data, start + 4) as usize;
+        let find = |tag: &[u8; 4]| {
+            (0..table_count)
+                .map(|i| start + 12 + i * 16)
+                .find(|record| data.get(*record..*record + 4) == Some(tag))
+                .map(|record| u32_at(&data, record + 8) as usize)
+                .filter(|offset| *offset < data.len())
+        };
+        let require = |tag: &[u8; 4]| {
+            find(tag).ok_or_else(|| {
+                Error::UnexpectedInput(format!(
+                    "TrueType font has no {} table",
+                    core::str::from_utf8(tag).unwrap_or_default()
+                ))
+            })
+        };
+
+        let head = require(b"head")?;
+        let hhea = require(b"hhea")?;
+        let maxp = require(b"maxp")?;
+        let cmap = parse_cmap(&data, require(b"cmap")?)?;
+        let units_per_em = u16_at(&data, head + 18);
+        if units_per_em == 0 {
+            return Err(Error::UnexpectedInput(
+                "TrueType font has no units per em".to_string(),
+            ));
+        }
+        let pair_adjustments = find(b"GPOS")
+            .map(|gpos| kerning_subtables(&data, gpos))
+            .unwrap_or_default();
+
+        Ok(Self {
+            units_per_em,
+            ascender: i16_at(&data, hhea + 4),
+            descender: i16_at(&data, hhea + 6),
+            glyph_count: u16_at(&data, maxp + 4),
+            long_loca: i16_at(&data, head + 50) != 0,
+            horizontal_metrics: u16_at(&data, hhea + 34),
+            cmap,
+            loca: require(b"loca")?,
+            glyf: require(b"glyf")?,
+            hmtx: require(b"hmtx")?,
+            kern: find(b"kern"),
+            pair_adjustments,
+            data,
+        })
+    }
+
+    pub fn units_per_em(&self) -> u16 {
+        self.units_per_em
+    }
+
+    pub fn glyph_count(&self) -> u16 {
+        self.glyph_count
+    }
+
+    // 文字に対応するグリフの番号。ない文字は0(.notdef)になる
+    pub fn glyph_index(&self, c: char) -> u16 {
+        let data = &self.data;
+        let code = c as u32;
+        match self.cmap {
+            CharacterMap::Segments(table) => {
+                if code > 0xffff {
+                    return 0;
+                }
+                let segments = u16_at(data, table + 6) as usize / 2;
+                let end_codes = table + 14;
+                let start_codes = end_codes + segments * 2 + 2;
+                let deltas = start_codes + segments * 2;
+                let range_offsets = deltas + segments * 2;
+                for i in 0..segments {
+                    if code > u16_at(data, end_codes + i * 2) as u32 {
+                        continue;
+                    }
+                    let start = u16_at(data, start_codes + i * 2) as u32;
+                    if code < start {
+                        return 0;
+                    }
+                    let delta = u16_at(data, deltas + i * 2);
+                    let range_offset = u16_at(data, range_offsets + i * 2) as usize;
+                    if range_offset == 0 {
+                        return (code as u16).wrapping_add(delta);
+                    }
+                    // idRangeOffsetは自身の位置からglyphIdArrayまでのバイト数
+                    let offset = range_offsets + i * 2 + range_offset + (code - start) as usize * 2;
+                    return match u16_at(data, offset) {
+                        0 => 0,
+                        glyph => glyph.wrapping_add(delta),
+                    };
+                }
+                0
+            }
+            CharacterMap::Groups(table) => {
+                let groups = u32_at(data, table + 12) as usize;
+                (0..groups)
+                    .map(|i| table + 16 + i * 12)
+                    .find(|group| (u32_at(data, *group)..=u32_at(data, group + 4)).contains(&code))
+                    .map_or(0, |group| {
+                        (u32_at(data, group + 8) + code - u32_at(data, group)) as u16
+                    })
+            }
+        }
+    }
+
+    // フォントの単位での送り幅
+    pub fn advance_width(&self, glyph: u16) -> u16 {
+        let last = self.horizontal_metrics.max(1) - 1;
+        u16_at(&self.data, self.hmtx + glyph.min(last) as usize * 4)
+    }
+
+    // フォントの単位での2つのグリフの間の調整量。GPOSのkern機能があればkernテーブルは使わない
+    pub fn kerning_units(&self, left: u16, right: u16) -> i16 {
+        if !self.pair_adjustments.is_empty() {
+            return self
+                .pair_adjustments
+                .iter()
+                .find_map(|subtable| pair_adjustment(&self.data, *subtable, left, right))
+                .unwrap_or(0);
+        }
+        self.kern
+            .and_then(|kern| kern_table(&self.data, kern, left, right))
+            .unwrap_or(0)
+    }
+
+    // glyfテーブル内のグリフの範囲。輪郭を持たないグリフはNone
+    fn glyph_range(&self, glyph: u16) -> Option<(usize, usize)> {
+        if glyph >= self.glyph_count {
+            return None;
+        }
+        let index = glyph as usize;
+        let (start, end) = if self.long_loca {
+            (
+                u32_at(&self.data, self.loca + index * 4) as usize,
+                u32_at(&self.data, self.loca + index * 4 + 4) as usize,
+            )
+        } else {
+            (
+                u16_at(&self.data, self.loca + index * 2) as usize * 2,
+                u16_at(&self.data, self.loca + index * 2 + 2) as usize * 2,
+            )
+        };
+        if start >= end || self.glyf + end > self.data.len() {
+            return None;
+        }
+        Some((self.glyf + start, self.glyf + end))
+    }
+
+    // フォントの単位でのグリフの輪郭。scaleは曲線を分割する細かさを決める
+    pub fn outline(&self, glyph: u16, scale: f64) -> Outline {
+        let mut outline = Outline::new();
+        self.append_outline(&mut outline, glyph, scale, 0);
+        outline
+    }
+
+    fn append_outline(&self, outline: &mut Outline, glyph: u16, scale: f64, depth: usize) {
+        let Some((start, end)) = self.glyph_range(glyph) else {
+            return;
+        };
+        let contours = i16_at(&self.data, start);
+        if contours >= 0 {
+            self.append_simple(outline, &self.data[start..end], contours as usize, scale);
+        } else if depth < MAX_COMPONENT_DEPTH {
+            self.append_composite(outline, &self.data[start..end], scale, depth);
+        }
+    }
+
+    fn append_simple(&self, outline: &mut Outline, data: &[u8], contours: usize, scale: f64) {
+        let end_points: Vec<usize> = (0..contours)
+            .map(|i| u16_at(data, 10 + i * 2) as usize)
+            .collect();
+        let point_count = end_points.last().map_or(0, |last| last + 1);
+        let instructions = 10 + contours * 2;
+        let mut offset = instructions + 2 + u16_at(data, instructions) as usize;
+
+        let mut flags = Vec::with_capacity(point_count);
+        while flags.len() < point_count {
+            let flag = u8_at(data, offset);
+            offset += 1;
+            flags.push(flag);
+            if flag & REPEAT != 0 {
+                for _ in 0..u8_at(data, offset) {
+                    flags.push(flag);
+                }
+                offset += 1;
+            }
+        }
+        flags.truncate(point_count);
+
+        // 座標は前の点からの差で、短い形式では符号をフラグで表す
+        let mut read_coordinates = |short: u8, same_or_positive: u8| {
+            let mut value = 0i32;
+            let mut coordinates = Vec::with_capacity(point_count);
+            for flag in &flags {
+                if flag & short != 0 {
+                    let delta = u8_at(data, offset) as i32;
+                    offset += 1;
+                    value += if flag & same_or_positive != 0 {
+                        delta
+                    } else {
+                        -delta
+                    };
+                } else if flag & same_or_positive == 0 {
+                    value += i16_at(data, offset) as i32;
+                    offset += 2;
+                }
+                coordinates.push(value as f64);
+            }
+            coordinates
+        };
+        let xs = read_coordinates(X_SHORT, X_SAME_OR_POSITIVE);
+        let ys = read_coordinates(Y_SHORT, Y_SAME_OR_POSITIVE);
+
+        let mut first = 0;
+        for last in end_points {
+            if last < first || last >= point_count {
+                break;
+            }
+            let points: Vec<(Point, bool)> = (first..=last)
+                .map(|i| (Point::new(xs[i], ys[i]), flags[i] & ON_CURVE != 0))
+                .collect();
+            append_contour(outline, &points, scale);
+            first = last + 1;
+        }
+    }
+
+    fn append_composite(&self, outline: &mut Outline, data: &[u8], scale: f64, depth: usize) {
+        let mut offset = 10;
+        loop {
+            let flags = u16_at(data, offset);
+            let component = u16_at(data, offset + 2);
+            offset += 4;
+            let (dx, dy) = if flags & ARGS_ARE_WORDS != 0 {
+                offset += 4;
+                (
+                    i16_at(data, offset - 4) as f64,
+                    i16_at(data, offset - 2) as f64,
+                )
+            } else {
+                offset += 2;
+                (
+                    u8_at(data, offset - 2) as i8 as f64,
+                    u8_at(data, offset - 1) as i8 as f64,
+                )
+            };
+            let (mut a, mut b, mut c, mut d) = (1.0, 0.0, 0.0, 1.0);
+            if flags & HAS_SCALE != 0 {
+                a = f2dot14_at(data, offset);
+                d = a;
+                offset += 2;
+            } else if flags & HAS_XY_SCALE != 0 {
+                a = f2dot14_at(data, offset);
+                d = f2dot14_at(data, offset + 2);
+                offset += 4;
+            } else if flags & HAS_TWO_BY_TWO != 0 {
+                a = f2dot14_at(data, offset);
+                b = f2dot14_at(data, offset + 2);
+                c = f2dot14_at(data, offset + 4);
+                d = f2dot14_at(data, offset + 6);
+                offset += 8;
+            }
+
+            let mut part = Outline::new();
+            self.append_outline(&mut part, component, scale, depth + 1);
+            // 点どうしを合わせる指定は扱わず、ずらさずに置く
+            let (dx, dy) = if flags & ARGS_ARE_XY_VALUES != 0 {
+                (dx, dy)
+            } else {
+                (0.0, 0.0)
+            };
+            outline.append(&part, [a, b, c, d, dx, dy]);
+
+            if flags & MORE_COMPONENTS == 0 || offset >= data.len() {
+                break;
+            }
+        }
+    }
+
+    fn scale(&self, size: f64) -> f64 {
+        size / self.units_per_em as f64
+    }
+}
+
+// 曲線外の点が続くときは、その中点を曲線上の点として補う
+fn append_contour(outline: &mut Outline, points: &[(Point, bool)], scale: f64) {
+    let midpoint = |a: &Point, b: &Point| Point::new((a.x + b.x) / 2.0, (a.y + b.y) / 2.0);
+    let Some(start_index) = points.iter().position(|(_, on_curve)| *on_curve) else {
+        // すべて曲線外の点なら最初の2点の中点から始める
+        if let [(a, _), (b, _), ..] = points {
+            let start = midpoint(a, b);
+            outline.move_to(start);
+            for i in 1..=points.len() {
+                let control = points[i % points.len()].0;
+                let next = points[(i + 1) % points.len()].0;
+                outline.quad_to(control, midpoint(&control, &next), scale);
+            }
+        }
+        return;
+    };
+
+    let start = points[start_index].0;
+    outline.move_to(start);
+    let mut control: Option<Point> = None;
+    for i in 1..=points.len() {
+        let (point, on_curve) = points[(start_index + i) % points.len()];
+        match (on_curve, control) {
+            (true, None) => outline.line_to(point),
+            (true, Some(c)) => {
+                outline.quad_to(c, point, scale);
+                control = None;
+            }
+            (false, None) => control = Some(point),
+            (false, Some(c)) => {
+                outline.quad_to(c, midpoint(&c, &point), scale);
+                control = Some(point);
+            }
+        }
+    }
+}
+
+// Unicodeの表を、32ビットの形式12、BMPの形式4の順に探す
+fn parse_cmap(data: &[u8], cmap: usize) -> Result<CharacterMap, Error> {
+    let records = u16_at(data, cmap + 2) as usize;
+    let mut segments = None;
+    for i in 0..records {
+        let record = cmap + 4 + i * 8;
+        let platform = u16_at(data, record);
+        let encoding = u16_at(data, record + 2);
+        let table = cmap + u32_at(data, record + 4) as usize;
+        let is_unicode = platform == 0 || (platform == 3 && (encoding == 1 || encoding == 10));
+        if !is_unicode {
+            continue;
+        }
+        match u16_at(data, table) {
+            12 => return Ok(CharacterMap::Groups(table)),
+            4 => segments = segments.or(Some(CharacterMap::Segments(table))),
+            _ => {}
+        }
+    }
+    segments.ok_or_else(|| {
+        Error::UnexpectedInput("TrueType font has no supported Unicode cmap".to_string())
+    })
+}
+
+// kernテーブルの横書き用の形式0のサブテーブルから、並んだ2つのグリフの調整量を探す
+fn kern_table(data: &[u8], kern: usize, left: u16, right: u16) -> Option<i16> {
+    let tables = u16_at(data, kern + 2) as usize;
+    let mut subtable = kern + 4;
+    for _ in 0..tables {
+        let length = u16_at(data, subtable + 2) as usize;
+        let coverage = u16_at(data, subtable + 4);
+        let is_horizontal = coverage & 0x01 != 0;
+        if coverage >> 8 == 0 && is_horizontal {
+            let pairs = u16_at(data, subtable + 6) as usize;
+            let key = (left as u32) << 16 | right as u32;
+            let (mut low, mut high) = (0, pairs);
+            while low < high {
+                let middle = (low + high) / 2;
+                let pair = subtable + 14 + middle * 6;
+                match u32_at(data, pair).cmp(&key) {
+                    core::cmp::Ordering::Less => low = middle + 1,
+                    core::cmp::Ordering::Greater => high = middle,
+                    core::cmp::Ordering::Equal => return Some(i16_at(data, pair + 4)),
+                }
+            }
+        }
+        if length == 0 {
+            break;
+        }
+        subtable += length;
+    }
+    None
+}
+
+// GPOSのkern機能から参照されるペア調整(タイプ2)のサブテーブルの位置
+fn kerning_subtables(data: &[u8], gpos: usize) -> Vec<usize> {
+    let features = gpos + u16_at(data, gpos + 6) as usize;
+    let lookups = gpos + u16_at(data, gpos + 8) as usize;
+    let mut lookup_indices = Vec::new();
+    for i in 0..u16_at(data, features) as usize {
+        let record = features + 2 + i * 6;
+        if data.get(record..record + 4) != Some(b"kern") {
+            continue;
+        }
+        let feature = features + u16_at(data, record + 4) as usize;
+        for j in 0..u16_at(data, feature + 2) as usize {
+            let index = u16_at(data, feature + 4 + j * 2);
+            if !lookup_indices.contains(&index) {
+                lookup_indices.push(index);
+            }
+        }
+    }
+    lookup_indices.sort_unstable();
+
+    let mut subtables = Vec::new();
+    for index in lookup_indices {
+        let lookup = lookups + u16_at(data, lookups + 2 + index as usize * 2) as usize;
+        let lookup_type = u16_at(data, lookup);
+        for i in 0..u16_at(data, lookup + 4) as usize {
+            let subtable = lookup + u16_at(data, lookup + 6 + i * 2) as usize;
+            match lookup_type {
+                2 => subtables.push(subtable),
+                // 拡張サブテーブルは32ビットの位置で実際のサブテーブルを指す
+                9 if u16_at(data, subtable + 2) == 2 => {
+                    subtables.push(subtable + u32_at(data, subtable + 4) as usize)
+                }
+                _ => {}
+            }
+        }
+    }
+    subtables
+}
+
+// Coverageテーブルでのグリフの番号
+fn coverage_index(data: &[u8], coverage: usize, glyph: u16) -> Option<usize> {
+    match u16_at(data, coverage) {
+        1 => {
+            let count = u16_at(data, coverage + 2) as usize;
+            (0..count).find(|i| u16_at(data, coverage + 4 + i * 2) == glyph)
+        }
+        2 => {
+            let count = u16_at(data, coverage + 2) as usize;
+            (0..count).map(|i| coverage + 4 + i * 6).find_map(|range| {
+                let start = u16_at(data, range);
+                (start..=u16_at(data, range + 2))
+                    .contains(&glyph)
+                    .then(|| u16_at(data, range + 4) as usize + (glyph - start) as usize)
+            })
+        }
+        _ => None,
+    }
+}
+
+// ClassDefテーブルでのグリフのクラス。含まれないグリフはクラス0
+fn glyph_class(data: &[u8], class_def: usize, glyph: u16) -> u16 {
+    match u16_at(data, class_def) {
+        1 => {
+            let start = u16_at(data, class_def + 2);
+            let count = u16_at(data, class_def + 4);
+            if glyph >= start && glyph - start < count {
+                u16_at(data, class_def + 6 + (glyph - start) as usize * 2)
+            } else {
+                0
+            }
+        }
+        2 => {
+            let count = u16_at(data, class_def + 2) as usize;
+            (0..count)
+                .map(|i| class_def + 4 + i * 6)
+                .find(|range| (u16_at(data, *range)..=u16_at(data, range + 2)).contains(&glyph))
+                .map_or(0, |range| u16_at(data, range + 4))
+        }
+        _ => 0,
+    }
+}
+
+// ValueRecordの大きさと、その中のXAdvanceの位置
+fn value_record_layout(format: u16) -> (usize, Option<usize>) {
+    let size = format.count_ones() as usize * 2;
+    let x_advance = (format & X_ADVANCE != 0).then(|| (format & 0x03).count_ones() as usize * 2);
+    (size, x_advance)
+}
+
+// ペア調整のサブテーブルでの、1つ目のグリフの送り幅の調整量
+fn pair_adjustment(data: &[u8], subtable: usize, left: u16, right: u16) -> Option<i16> {
+    let format = u16_at(data, subtable);
+    let coverage = subtable + u16_at(data, subtable + 2) as usize;
+    let index = coverage_index(data, coverage, left)?;
+    let (first_size, x_advance) = value_record_layout(u16_at(data, subtable + 4));
+    let (second_size, _) = value_record_layout(u16_at(data, subtable + 6));
+    match format {
+        1 => {
+            if index >= u16_at(data, subtable + 8) as usize {
+                return None;
+            }
+            let pair_set = subtable + u16_at(data, subtable + 10 + index * 2) as usize;
+            let record_size = 2 + first_size + second_size;
+            let record = (0..u16_at(data, pair_set) as usize)
+                .map(|i| pair_set + 2 + i * record_size)
+                .find(|record| u16_at(data, *record) == right)?;
+            Some(x_advance.map_or(0, |offset| i16_at(data, record + 2 + offset)))
+        }
+        2 => {
+            let first_class =
+                glyph_class(data, subtable + u16_at(data, subtable + 8) as usize, left);
+            let second_class =
+                glyph_class(data, subtable + u16_at(data, subtable + 10) as usize, right);
+            let first_count = u16_at(data, subtable + 12);
+            let second_count = u16_at(data, subtable + 14);
+            if first_class >= first_count || second_class >= second_count {
+                return None;
+            }
+            let record_size = first_size + second_size;
+            let record = subtable
+                + 16
+                + (first_class as usize * second_count as usize + second_class as usize)
+                    * record_size;
+            Some(x_advance.map_or(0, |offset| i16_at(data, record + offset)))
+        }
+        _ => None,
+    }
+}
+
+impl FontMetrics for TrueTypeFont {
+    fn advance(&self, c: char, style: &ComputedStyle) -> f64 {
+        self.advance_width(self.glyph_index(c)) as f64 * self.scale(style.font_size)
+    }
+
+    fn ascent(&self, style: &ComputedStyle) -> f64 {
+        self.ascender as f64 * self.scale(style.font_size)
+    }
+
+    fn descent(&self, style: &ComputedStyle) -> f64 {
+        -(self.descender as f64) * self.scale(style.font_size)
+    }
+
+    fn kerning(&self, left: char, right: char, style: &ComputedStyle) -> f64 {
+        let units = self.kerning_units(self.glyph_index(left), self.glyph_index(right));
+        units as f64 * self.scale(style.font_size)
+    }
+}
+
+impl Font for TrueTypeFont {
+    fn has_glyph(&self, c: char) -> bool {
+        self.glyph_index(c) != 0
+    }
+
+    fn glyph(&self, c: char, size: f64) -> Option<Glyph> {
+        let scale = self.scale(size);
+        Some(self.outline(self.glyph_index(c), scale).rasterize(scale))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{vec, vec::Vec};
+
+    fn push16(data: &mut Vec<u8>, values: &[u16]) {
+        for value in values {
+            data.extend_from_slice(&value.to_be_bytes());
+        }
+    }
+
+    // 表を並べたフォントファイルを作る
+    fn sfnt(tables: &[(&[u8; 4], Vec<u8>)]) -> Vec<u8> {
+        let mut data = Vec::new();
+        push16(&mut data, &[1, 0, tables.len() as u16, 0, 0, 0]);
+        let mut offset = 12 + tables.len() * 16;
+        for (tag, table) in tables {
+            data.extend_from_slice(tag.as_slice());
+            data.extend_from_slice(&[0; 4]);
+            data.extend_from_slice(&(offset as u32).to_be_bytes());
+            data.extend_from_slice(&(table.len() as u32).to_be_bytes());
+            offset += table.len();
+        }
+        for (_, table) in tables {
+            data.extend_from_slice(table);
+        }
+        data
+    }
+
+    // 'A'(1)は四角、'B'(2)は曲線を持つ形、'C'(3)は'A'をずらした複合グリフで、1000単位を1emとする
+    fn test_font(gpos: Option<Vec<u8>>) -> Vec<u8> {
+        let mut head = vec![0; 54];
+        head[18..20].copy_from_slice(&1000u16.to_be_bytes());
+        let mut hhea = vec![0; 36];
+        hhea[4..6].copy_from_slice(&800i16.to_be_bytes());
+        hhea[6..8].copy_from_slice(&(-200i16).to_be_bytes());
+        hhea[34..36].copy_from_slice(&3u16.to_be_bytes());
+        let mut maxp = vec![0; 6];
+        maxp[4..6].copy_from_slice(&4u16.to_be_bytes());
+
+        // 'A'から'C'を1から3に対応付ける形式4
+        let mut cmap = Vec::new();
+        push16(&mut cmap, &[0, 1, 3, 1, 0, 12]);
+        push16(&mut cmap, &[4, 32, 0, 4, 0, 0, 0]);
+        push16(&mut cmap, &[0x43, 0xffff, 0, 0x41, 0xffff]);
+        push16(&mut cmap, &[(1u16).wrapping_sub(0x41), 1, 0, 0]);
+
+        let mut glyf = Vec::new();
+        // 'A': (100, 0)から(500, 700)の四角
+        push16(&mut glyf, &[1, 100, 0, 500, 700, 3, 0]);
+        glyf.extend_from_slice(&[ON_CURVE, ON_CURVE, ON_CURVE, ON_CURVE]);
+        push16(&mut glyf, &[100, 400, 0, (-400i16) as u16]);
+        push16(&mut glyf, &[0, 0, 700, 0]);
+        let a_end = glyf.len();
+        // 'B': 曲線外の点が続く三角形に近い形
+        push16(&mut glyf, &[1, 0, 0, 600, 600, 2, 0]);
+        glyf.extend_from_slice(&[
+            ON_CURVE | X_SHORT | Y_SHORT | X_SAME_OR_POSITIVE | Y_SAME_OR_POSITIVE,
+            X_SHORT | Y_SHORT | X_SAME_OR_POSITIVE | Y_SAME_OR_POSITIVE,
+            X_SHORT | Y_SHORT | Y_SAME_OR_POSITIVE,
+        ]);
+        glyf.extend_from_slice(&[0, 200, 200, 0, 0, 200]);
+        glyf.push(0);
+        let b_end = glyf.len();
+        // 'C': 'A'を(200, 100)だけずらす
+        push16(&mut glyf, &[(-1i16) as u16, 0, 0, 0, 0]);
+        push16(
+            &mut glyf,
+            &[ARGS_ARE_WORDS | ARGS_ARE_XY_VALUES, 1, 200, 100],
+        );
+        let c_end = glyf.len();
+
+        let mut loca = Vec::new();
+        for offset in [0, 0, a_end, b_end, c_end] {
+            push16(&mut loca, &[offset as u16 / 2]);
+        }
+        let mut hmtx = Vec::new();
+        push16(&mut hmtx, &[500, 0, 400, 0, 500, 0, 600, 0]);
+
+        // 'A'と'B'の間を-100詰める
+        let mut kern = Vec::new();
+        push16(
+            &mut kern,
+            &[0, 1, 0, 20, 1, 1, 0, 0, 0, 1, 2, (-100i16) as u16],
+        );
+
+        let mut tables: Vec<(&[u8; 4], Vec<u8>)> = vec![
+            (b"cmap", cmap),
+            (b"glyf", glyf),
+            (b"head", head),
+            (b"hhea", hhea),
+            (b"hmtx", hmtx),
+            (b"kern", kern),
+            (b"loca", loca),
+            (b"maxp", maxp),
+        ];
+        if let Some(gpos) = gpos {
+            tables.push((b"GPOS", gpos));
+        }
+        sfnt(&tables)
+    }
+
+    #[test]
+    fn test_parse_truetype() {
+        let font = TrueTypeFont::parse(test_font(None)).unwrap();
+        assert_eq!((1000, 4), (font.units_per_em(), font.glyph_count()));
+        assert_eq!(
+            (1, 3, 0),
+            (
+                font.glyph_index('A'),
+                font.glyph_index('C'),
+                font.glyph_index('a')
+            )
+        );
+        assert!(font.has_glyph('B') && !font.has_glyph('D'));
+
+        let style = ComputedStyle {
+            font_size: 20.0,
+            ..Default::default()
+        };
+        assert_eq!(10.0, font.advance('B', &style));
+        // hmtxの最後の送り幅を後のグリフも使う
+        assert_eq!(10.0, font.advance('C', &style));
+        assert_eq!((16.0, 4.0), (font.ascent(&style), font.descent(&style)));
+        assert_eq!(-2.0, font.kerning('A', 'B', &style));
+        assert_eq!(0.0, font.kerning('B', 'A', &style));
+        assert_eq!(16.0, font.text_width("AB", &style));
+
+        // 20pxでは'A'は(2, 0)から(10, 14)の四角になる
+        let glyph = font.glyph('A', 20.0).unwrap();
+        assert_eq!(
+            (8, 14, 2, 14),
+            (glyph.width, glyph.height, glyph.left, glyph.top)
+        );
+        assert!(glyph.coverage.iter().all(|c| *c == 255));
+        let glyph = font.glyph('A', 15.0).unwrap();
+        assert_eq!((1, 11), (glyph.left, glyph.top));
+        assert_eq!(128, glyph.coverage_at(0, 5));
+
+        let glyph = font.glyph('C', 20.0).unwrap();
+        assert_eq!((6, 16), (glyph.left, glyph.top));
+
+        // 曲線外の点は塗る範囲を曲線の内側に狭める
+        let glyph = font.glyph('B', 20.0).unwrap();
+        assert_eq!((3, 3), (glyph.width, glyph.height));
+        assert!(glyph.coverage_at(0, 2) > 200);
+        assert_eq!(0, glyph.coverage_at(2, 0));
+
+        // 輪郭のないグリフは空の形になる
+        assert_eq!(0, font.glyph('a', 20.0).unwrap().width);
+
+        assert!(TrueTypeFont::parse(b"OTTO\0\0\0\0".to_vec()).is_err());
+        assert!(TrueTypeFont::parse(test_font(None)[..60].to_vec()).is_err());
+    }
+
+    #[test]
+    fn test_gpos_kerning() {
+        let mut gpos = Vec::new();
+        // ヘッダと、2つのルックアップを使うkern機能だけを持つFeatureList
+        push16(&mut gpos, &[1, 0, 0, 10, 26, 1]);
+        gpos.extend_from_slice(b"kern");
+        push16(&mut gpos, &[8, 0, 2, 0, 1]);
+        // LookupList
+        push16(&mut gpos, &[2, 6, 38]);
+        // 形式1: 'A'の後の'B'を-50
+        push16(&mut gpos, &[2, 0, 1, 8]);
+        push16(&mut gpos, &[1, 12, X_ADVANCE, 0, 1, 18]);
+        push16(&mut gpos, &[1, 1, 1]);
+        push16(&mut gpos, &[1, 2, (-50i16) as u16]);
+        // 形式2: クラス1の'B'の後のクラス1の'A'を+30
+        push16(&mut gpos, &[2, 0, 1, 8]);
+        push16(&mut gpos, &[2, 24, X_ADVANCE, 0, 30, 38, 2, 2]);
+        push16(&mut gpos, &[0, 0, 0, 30]);
+        push16(&mut gpos, &[1, 1, 2]);
+        push16(&mut gpos, &[1, 2, 1, 1]);
+        push16(&mut gpos, &[2, 1, 1, 1, 1]);
+
+        let font = TrueTypeFont::parse(test_font(Some(gpos))).unwrap();
+        // GPOSがあればkernテーブルは使わない
+        assert_eq!(-50, font.kerning_units(1, 2));
+        assert_eq!(30, font.kerning_units(2, 1));
+        assert_eq!(0, font.kerning_units(1, 1));
+        assert_eq!(0, font.kerning_units(3, 1));
+    }
+}
@@ -1,4 +1,4 @@
-use alloc::string::String;
+use alloc::string::{String, ToString};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Attribute {
@@ -14,6 +14,13 @@ impl Attribute {
         }
     }
 
+    pub fn with_name_and_value(name: &str, value: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            value: value.to_string(),
+        }
+    }
+
     pub fn add_name_char(&mut self, ch: char) {
         self.name.push(ch);
     }
@@ -22,6 +29,10 @@ impl Attribute {
         self.value.push(ch);
     }
 
+    pub fn set_value(&mut self, value: &str) {
+        self.value = value.to_string();
+    }
+
     pub fn name(&self) -> String {
         self.name.clone()
     }
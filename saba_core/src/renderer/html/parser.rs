@@ -2,7 +2,10 @@ use super::{
     attribute::Attribute,
     token::{HtmlToken, HtmlTokenizer},
 };
-use crate::renderer::dom::node::{Element, ElementKind, Node, NodeKind, Window};
+use crate::renderer::dom::{
+    api::insert_before,
+    node::{Element, ElementKind, Node, NodeKind, Window},
+};
 use alloc::{rc::Rc, string::String, vec::Vec};
 use core::{cell::RefCell, str::FromStr};
 
@@ -12,16 +15,75 @@ pub struct HtmlParser {
     original_insertion_mode: InsertionMode,
     stack_of_open_elements: Vec<Rc<RefCell<Node>>>,
     tokenizer: HtmlTokenizer,
+    // テーブルの中に置けないノードをテーブルの直前に挿入する
+    foster_parenting: bool,
+    // InTableTextで溜めているテーブル内の文字
+    pending_table_characters: String,
+}
+
+// テーブル関連の要素のうち、テーブルやセルなどを閉じる開始タグ
+const TABLE_START_TAGS: [&str; 9] = [
+    "caption", "col", "colgroup", "tbody", "td", "tfoot", "th", "thead", "tr",
+];
+
+// テーブル関連の挿入モードが自身で処理する終了タグ
+const TABLE_END_TAGS: [&str; 12] = [
+    "body", "caption", "col", "colgroup", "html", "table", "tbody", "td", "tfoot", "th", "thead",
+    "tr",
+];
+
+const SCOPE_BOUNDARIES: [ElementKind; 5] = [
+    ElementKind::Html,
+    ElementKind::Table,
+    ElementKind::Td,
+    ElementKind::Th,
+    ElementKind::Caption,
+];
+
+// リスト項目のスコープ。入れ子のリストの外側の項目は閉じない
+const LIST_ITEM_SCOPE_BOUNDARIES: [ElementKind; 7] = [
+    ElementKind::Html,
+    ElementKind::Table,
+    ElementKind::Td,
+    ElementKind::Th,
+    ElementKind::Caption,
+    ElementKind::Ul,
+    ElementKind::Ol,
+];
+
+const TABLE_SCOPE_BOUNDARIES: [ElementKind; 2] = [ElementKind::Html, ElementKind::Table];
+
+fn is_start_tag(token: &HtmlToken, names: &[&str]) -> bool {
+    match token {
+        HtmlToken::StartTag { tag, .. } => names.contains(&tag.as_str()),
+        _ => false,
+    }
+}
+
+fn is_end_tag(token: &HtmlToken, names: &[&str]) -> bool {
+    match token {
+        HtmlToken::EndTag { tag } => names.contains(&tag.as_str()),
+        _ => false,
+    }
 }
 
 impl HtmlParser {
     pub fn new(tokenizer: HtmlTokenizer) -> Self {
+        let window = Rc::new(RefCell::new(Window::new()));
+        window
+            .borrow()
+            .document()
+            .borrow_mut()
+            .set_window(Rc::downgrade(&window));
+
         Self {
-            window: Rc::new(RefCell::new(Window::new())),
+            window,
             mode: InsertionMode::Initial,
             original_insertion_mode: InsertionMode::Initial,
             stack_of_open_elements: Vec::new(),
             tokenizer,
+            foster_parenting: false,
+            pending_table_characters: String::new(),
         }
     }
 
@@ -29,7 +91,7 @@ impl HtmlParser {
         let mut token = self.tokenizer.next();
 
         while let Some(token_ref) = &token {
-            match self.mode {
+            match self.rules_for(token_ref) {
                 InsertionMode::Initial => {
                     if let HtmlToken::Char(_) = token_ref {
                         token = self.tokenizer.next();
@@ -111,6 +173,15 @@ impl HtmlParser {
                                 self.insert_element(tag, attributes.clone());
                                 self.original_insertion_mode = self.mode;
                                 self.mode = InsertionMode::Text;
+                                self.tokenizer.switch_to_raw_text(tag);
+                                token = self.tokenizer.next();
+                                continue;
+                            }
+
+                            // linkは子を持たない要素なので挿入後すぐに取り出す
+                            if tag == "link" {
+                                self.insert_element(tag, attributes.clone());
+                                self.pop_current_node(ElementKind::Link);
                                 token = self.tokenizer.next();
                                 continue;
                             }
@@ -201,6 +272,50 @@ impl HtmlParser {
                                 self.insert_element(tag, attributes.clone());
                                 token = self.tokenizer.next();
                             }
+                            // imgは子を持たない要素なので挿入後すぐに取り出す
+                            "img" => {
+                                self.insert_element(tag, attributes.clone());
+                                self.pop_current_node(ElementKind::Img);
+                                token = self.tokenizer.next();
+                                continue;
+                            }
+                            "ul" | "ol" => {
+                                if self.in_scope(ElementKind::P, &SCOPE_BOUNDARIES) {
+                                    self.pop_until(ElementKind::P);
+                                }
+                                self.insert_element(tag, attributes.clone());
+                                token = self.tokenizer.next();
+                                continue;
+                            }
+                            // 開いているリスト項目があれば閉じてから次の項目を始める
+                            "li" => {
+                                if self.in_scope(ElementKind::Li, &LIST_ITEM_SCOPE_BOUNDARIES) {
+                                    self.pop_until(ElementKind::Li);
+                                }
+                                if self.in_scope(ElementKind::P, &SCOPE_BOUNDARIES) {
+                                    self.pop_until(ElementKind::P);
+                                }
+                                self.insert_element(tag, attributes.clone());
+                                token = self.tokenizer.next();
+                                continue;
+                            }
+                            "style" | "script" => {
+                                self.insert_element(tag, attributes.clone());
+                                self.original_insertion_mode = self.mode;
+                                self.mode = InsertionMode::Text;
+                                self.tokenizer.switch_to_raw_text(tag);
+                                token = self.tokenizer.next();
+                                continue;
+                            }
+                            "table" => {
+                                if self.in_scope(ElementKind::P, &SCOPE_BOUNDARIES) {
+                                    self.pop_until(ElementKind::P);
+                                }
+                                self.insert_element(tag, attributes.clone());
+                                self.mode = InsertionMode::InTable;
+                                token = self.tokenizer.next();
+                                continue;
+                            }
                             _ => {
                                 token = self.tokenizer.next();
                             }
@@ -231,21 +346,47 @@ impl HtmlParser {
                                 "p" => {
                                     let element_kind = ElementKind::from_str(tag)
                                         .expect("failed to convert string to ElementKind");
-                                    self.pop_until(element_kind);
+                                    // セルの外で開かれた要素は閉じない
+                                    if self.in_scope(element_kind, &SCOPE_BOUNDARIES) {
+                                        self.pop_until(element_kind);
+                                    }
                                     token = self.tokenizer.next();
                                     continue;
                                 }
                                 "h1" | "h2" | "h3" => {
                                     let element_kind = ElementKind::from_str(tag)
                                         .expect("failed to convert string to ElementKind");
-                                    self.pop_until(element_kind);
+                                    // セルの外で開かれた要素は閉じない
+                                    if self.in_scope(element_kind, &SCOPE_BOUNDARIES) {
+                                        self.pop_until(element_kind);
+                                    }
                                     token = self.tokenizer.next();
                                     continue;
                                 }
                                 "a" => {
                                     let element_kind = ElementKind::from_str(tag)
                                         .expect("failed to convert string to ElementKind");
-                                    self.pop_until(element_kind);
+                                    // セルの外で開かれた要素は閉じない
+                                    if self.in_scope(element_kind, &SCOPE_BOUNDARIES) {
+                                        self.pop_until(element_kind);
+                                    }
+                                    token = self.tokenizer.next();
+                                    continue;
+                                }
+                                "ul" | "ol" => {
+                                    let element_kind = ElementKind::from_str(tag)
+                                        .expect("failed to convert string to ElementKind");
+                                    // セルの外で開かれた要素は閉じない
+                                    if self.in_scope(element_kind, &SCOPE_BOUNDARIES) {
+                                        self.pop_until(element_kind);
+                                    }
+                                    token = self.tokenizer.next();
+                                    continue;
+                                }
+                                "li" => {
+                                    if self.in_scope(ElementKind::Li, &LIST_ITEM_SCOPE_BOUNDARIES) {
+                                        self.pop_until(ElementKind::Li);
+                                    }
                                     token = self.tokenizer.next();
                                     continue;
                                 }
@@ -294,6 +435,317 @@ impl HtmlParser {
 
                     self.mode = self.original_insertion_mode;
                 }
+                InsertionMode::InTable => {
+                    match token_ref {
+                        HtmlToken::Char(_) => {
+                            self.pending_table_characters = String::new();
+                            self.original_insertion_mode = self.mode;
+                            self.mode = InsertionMode::InTableText;
+                            continue;
+                        }
+                        HtmlToken::StartTag {
+                            tag,
+                            self_closing: _,
+                            attributes,
+                        } => match tag.as_str() {
+                            "caption" => {
+                                self.clear_stack_back_to(&[ElementKind::Table]);
+                                self.insert_element(tag, attributes.clone());
+                                self.mode = InsertionMode::InCaption;
+                                token = self.tokenizer.next();
+                                continue;
+                            }
+                            "colgroup" => {
+                                self.clear_stack_back_to(&[ElementKind::Table]);
+                                self.insert_element(tag, attributes.clone());
+                                self.mode = InsertionMode::InColumnGroup;
+                                token = self.tokenizer.next();
+                                continue;
+                            }
+                            "col" => {
+                                self.clear_stack_back_to(&[ElementKind::Table]);
+                                self.insert_element("colgroup", Vec::new());
+                                self.mode = InsertionMode::InColumnGroup;
+                                continue;
+                            }
+                            "tbody" | "tfoot" | "thead" => {
+                                self.clear_stack_back_to(&[ElementKind::Table]);
+                                self.insert_element(tag, attributes.clone());
+                                self.mode = InsertionMode::InTableBody;
+                                token = self.tokenizer.next();
+                                continue;
+                            }
+                            "td" | "th" | "tr" => {
+                                self.clear_stack_back_to(&[ElementKind::Table]);
+                                self.insert_element("tbody", Vec::new());
+                                self.mode = InsertionMode::InTableBody;
+                                continue;
+                            }
+                            "table" => {
+                                // 開いているテーブルを閉じてから新しいテーブルとして処理し直す
+                                if self.in_scope(ElementKind::Table, &TABLE_SCOPE_BOUNDARIES) {
+                                    self.pop_until(ElementKind::Table);
+                                    self.reset_insertion_mode();
+                                    continue;
+                                }
+                            }
+                            "style" | "script" => {
+                                self.insert_element(tag, attributes.clone());
+                                self.original_insertion_mode = self.mode;
+                                self.mode = InsertionMode::Text;
+                                self.tokenizer.switch_to_raw_text(tag);
+                                token = self.tokenizer.next();
+                                continue;
+                            }
+                            _ => {}
+                        },
+                        HtmlToken::EndTag { tag } => {
+                            if tag == "table"
+                                && self.in_scope(ElementKind::Table, &TABLE_SCOPE_BOUNDARIES)
+                            {
+                                self.pop_until(ElementKind::Table);
+                                self.reset_insertion_mode();
+                            }
+                        }
+                        HtmlToken::Eof => {
+                            return self.window.clone();
+                        }
+                    }
+
+                    // パース失敗のためトークンを無視
+                    token = self.tokenizer.next();
+                    continue;
+                }
+                InsertionMode::InTableText => {
+                    if let &HtmlToken::Char(ch) = token_ref {
+                        self.pending_table_characters.push(ch);
+                        token = self.tokenizer.next();
+                        continue;
+                    }
+
+                    // 空白以外を含む場合はテーブルの前に移す
+                    let characters = core::mem::take(&mut self.pending_table_characters);
+                    self.foster_parenting = characters
+                        .chars()
+                        .any(|c| !matches!(c, ' ' | '\n' | '\t' | '\r' | '\x0c'));
+                    for ch in characters.chars() {
+                        self.insert_char(ch);
+                    }
+                    self.foster_parenting = false;
+                    self.mode = self.original_insertion_mode;
+                    continue;
+                }
+                InsertionMode::InCaption => {
+                    let closes_caption = matches!(token_ref, HtmlToken::StartTag { .. })
+                        || is_end_tag(token_ref, &["caption", "table"]);
+                    if closes_caption
+                        && self.in_scope(ElementKind::Caption, &TABLE_SCOPE_BOUNDARIES)
+                    {
+                        self.pop_until(ElementKind::Caption);
+                        self.mode = InsertionMode::InTable;
+                        if is_end_tag(token_ref, &["caption"]) {
+                            token = self.tokenizer.next();
+                        }
+                        continue;
+                    }
+
+                    token = self.tokenizer.next();
+                    continue;
+                }
+                InsertionMode::InColumnGroup => {
+                    match token_ref {
+                        &HtmlToken::Char(ch) => {
+                            if ch == ' ' || ch == '\n' {
+                                self.insert_char(ch);
+                                token = self.tokenizer.next();
+                                continue;
+                            }
+                        }
+                        HtmlToken::StartTag {
+                            tag,
+                            self_closing: _,
+                            attributes,
+                        } => {
+                            // colは子を持たない要素なので挿入後すぐに取り出す
+                            if tag == "col" {
+                                self.insert_element(tag, attributes.clone());
+                                self.pop_current_node(ElementKind::Col);
+                                token = self.tokenizer.next();
+                                continue;
+                            }
+                        }
+                        HtmlToken::EndTag { tag } => {
+                            if tag == "col" {
+                                token = self.tokenizer.next();
+                                continue;
+                            }
+                        }
+                        HtmlToken::Eof => {
+                            return self.window.clone();
+                        }
+                    }
+
+                    // colgroupを閉じてInTableで処理し直す
+                    if !self.pop_current_node(ElementKind::Colgroup) {
+                        token = self.tokenizer.next();
+                        continue;
+                    }
+                    self.mode = InsertionMode::InTable;
+                    if is_end_tag(token_ref, &["colgroup"]) {
+                        token = self.tokenizer.next();
+                    }
+                    continue;
+                }
+                InsertionMode::InTableBody => {
+                    match token_ref {
+                        HtmlToken::StartTag {
+                            tag,
+                            self_closing: _,
+                            attributes,
+                        } => {
+                            if tag == "tr" {
+                                self.clear_stack_back_to(&[
+                                    ElementKind::Tbody,
+                                    ElementKind::Tfoot,
+                                    ElementKind::Thead,
+                                ]);
+                                self.insert_element(tag, attributes.clone());
+                                self.mode = InsertionMode::InRow;
+                                token = self.tokenizer.next();
+                                continue;
+                            }
+
+                            if tag == "td" || tag == "th" {
+                                self.clear_stack_back_to(&[
+                                    ElementKind::Tbody,
+                                    ElementKind::Tfoot,
+                                    ElementKind::Thead,
+                                ]);
+                                self.insert_element("tr", Vec::new());
+                                self.mode = InsertionMode::InRow;
+                                continue;
+                            }
+                        }
+                        HtmlToken::EndTag { tag } => {
+                            if tag == "tbody" || tag == "tfoot" || tag == "thead" {
+                                let element_kind = ElementKind::from_str(tag)
+                                    .expect("failed to convert string to ElementKind");
+                                if self.in_scope(element_kind, &TABLE_SCOPE_BOUNDARIES) {
+                                    self.pop_until(element_kind);
+                                    self.mode = InsertionMode::InTable;
+                                }
+                                token = self.tokenizer.next();
+                                continue;
+                            }
+                        }
+                        _ => {}
+                    }
+
+                    // 行グループを閉じてInTableで処理し直す
+                    let closes_section = matches!(token_ref, HtmlToken::StartTag { .. })
+                        || is_end_tag(token_ref, &["table"]);
+                    let in_section = [ElementKind::Tbody, ElementKind::Tfoot, ElementKind::Thead]
+                        .into_iter()
+                        .any(|kind| self.in_scope(kind, &TABLE_SCOPE_BOUNDARIES));
+                    if closes_section && in_section {
+                        self.clear_stack_back_to(&[
+                            ElementKind::Tbody,
+                            ElementKind::Tfoot,
+                            ElementKind::Thead,
+                        ]);
+                        self.stack_of_open_elements.pop();
+                        self.mode = InsertionMode::InTable;
+                        continue;
+                    }
+
+                    token = self.tokenizer.next();
+                    continue;
+                }
+                InsertionMode::InRow => {
+                    match token_ref {
+                        HtmlToken::StartTag {
+                            tag,
+                            self_closing: _,
+                            attributes,
+                        } => {
+                            if tag == "td" || tag == "th" {
+                                self.clear_stack_back_to(&[ElementKind::Tr]);
+                                self.insert_element(tag, attributes.clone());
+                                self.mode = InsertionMode::InCell;
+                                token = self.tokenizer.next();
+                                continue;
+                            }
+                        }
+                        HtmlToken::EndTag { tag } => {
+                            if tag == "tr" {
+                                if self.in_scope(ElementKind::Tr, &TABLE_SCOPE_BOUNDARIES) {
+                                    self.pop_until(ElementKind::Tr);
+                                    self.mode = InsertionMode::InTableBody;
+                                }
+                                token = self.tokenizer.next();
+                                continue;
+                            }
+                        }
+                        _ => {}
+                    }
+
+                    // 行を閉じてInTableBodyで処理し直す
+                    let closes_row = match token_ref {
+                        HtmlToken::StartTag { .. } => true,
+                        HtmlToken::EndTag { tag } => match tag.as_str() {
+                            "table" => true,
+                            "tbody" | "tfoot" | "thead" => ElementKind::from_str(tag)
+                                .is_ok_and(|kind| self.in_scope(kind, &TABLE_SCOPE_BOUNDARIES)),
+                            _ => false,
+                        },
+                        _ => false,
+                    };
+                    if closes_row && self.in_scope(ElementKind::Tr, &TABLE_SCOPE_BOUNDARIES) {
+                        self.pop_until(ElementKind::Tr);
+                        self.mode = InsertionMode::InTableBody;
+                        continue;
+                    }
+
+                    token = self.tokenizer.next();
+                    continue;
+                }
+                InsertionMode::InCell => {
+                    if let HtmlToken::EndTag { tag } = token_ref {
+                        if tag == "td" || tag == "th" {
+                            let element_kind = ElementKind::from_str(tag)
+                                .expect("failed to convert string to ElementKind");
+                            if self.in_scope(element_kind, &TABLE_SCOPE_BOUNDARIES) {
+                                self.pop_until(element_kind);
+                                self.mode = InsertionMode::InRow;
+                            }
+                            token = self.tokenizer.next();
+                            continue;
+                        }
+                    }
+
+                    // セルを閉じてInRowで処理し直す
+                    let closes_cell = match token_ref {
+                        HtmlToken::StartTag { .. } => {
+                            self.in_scope(ElementKind::Td, &TABLE_SCOPE_BOUNDARIES)
+                                || self.in_scope(ElementKind::Th, &TABLE_SCOPE_BOUNDARIES)
+                        }
+                        HtmlToken::EndTag { tag } => match tag.as_str() {
+                            "table" | "tbody" | "tfoot" | "thead" | "tr" => {
+                                ElementKind::from_str(tag)
+                                    .is_ok_and(|kind| self.in_scope(kind, &TABLE_SCOPE_BOUNDARIES))
+                            }
+                            _ => false,
+                        },
+                        _ => false,
+                    };
+                    if closes_cell {
+                        self.close_cell();
+                        continue;
+                    }
+
+                    token = self.tokenizer.next();
+                    continue;
+                }
                 InsertionMode::AfterBody => {
                     match token_ref {
                         HtmlToken::EndTag { tag } => {
@@ -334,17 +786,160 @@ impl HtmlParser {
         self.window.clone()
     }
 
-    fn create_element(&self, tag: &str, attributes: Vec<Attribute>) -> Node {
-        Node::new(NodeKind::Element(Element::new(tag, attributes)))
+    // 現在の挿入モードでトークンを処理するときに従う規則。テーブル関連のモードは扱わないトークンを他のモードの規則で処理する
+    fn rules_for(&mut self, token: &HtmlToken) -> InsertionMode {
+        self.foster_parenting = false;
+        let handles_table_tag =
+            is_start_tag(token, &TABLE_START_TAGS) || is_end_tag(token, &TABLE_END_TAGS);
+        match self.mode {
+            InsertionMode::InCaption | InsertionMode::InCell => {
+                if handles_table_tag {
+                    self.mode
+                } else {
+                    InsertionMode::InBody
+                }
+            }
+            InsertionMode::InColumnGroup => {
+                if is_start_tag(token, &["style", "script"]) {
+                    InsertionMode::InBody
+                } else {
+                    self.mode
+                }
+            }
+            InsertionMode::InTableBody | InsertionMode::InRow if handles_table_tag => self.mode,
+            InsertionMode::InTable | InsertionMode::InTableBody | InsertionMode::InRow => {
+                let handles = match token {
+                    HtmlToken::Char(_) => self.is_table_context(),
+                    HtmlToken::Eof => true,
+                    _ => handles_table_tag || is_start_tag(token, &["table", "style", "script"]),
+                };
+                if handles {
+                    return InsertionMode::InTable;
+                }
+                self.foster_parenting = true;
+                InsertionMode::InBody
+            }
+            mode => mode,
+        }
     }
 
-    fn insert_element(&mut self, tag: &str, attributes: Vec<Attribute>) {
-        let new_child = Rc::new(RefCell::new(self.create_element(tag, attributes)));
+    // 現在のノードがテーブルの構造を表す要素か
+    fn is_table_context(&self) -> bool {
+        self.stack_of_open_elements.last().is_some_and(|node| {
+            matches!(
+                node.borrow().element_kind(),
+                Some(
+                    ElementKind::Table
+                        | ElementKind::Tbody
+                        | ElementKind::Tfoot
+                        | ElementKind::Thead
+                        | ElementKind::Tr
+                )
+            )
+        })
+    }
 
+    // ノードを挿入する親と、その中で直後に来るノード
+    fn insertion_location(&self) -> (Rc<RefCell<Node>>, Option<Rc<RefCell<Node>>>) {
         let current_node = match self.stack_of_open_elements.last() {
             Some(node) => node.clone(),
             None => self.window.borrow().document(),
         };
+        if !self.foster_parenting || !self.is_table_context() {
+            return (current_node, None);
+        }
+
+        // 最後に開いたテーブルの直前に挿入する
+        let index = match self
+            .stack_of_open_elements
+            .iter()
+            .rposition(|node| node.borrow().element_kind() == Some(ElementKind::Table))
+        {
+            Some(index) => index,
+            None => return (current_node, None),
+        };
+        let table = self.stack_of_open_elements[index].clone();
+        let parent = table.borrow().parent().upgrade();
+        match parent {
+            Some(parent) => (parent, Some(table)),
+            None => (self.stack_of_open_elements[index - 1].clone(), None),
+        }
+    }
+
+    // 要素がスコープ内にあるか。境界となる要素に達したらスコープ外とする
+    fn in_scope(&self, element_kind: ElementKind, boundaries: &[ElementKind]) -> bool {
+        for node in self.stack_of_open_elements.iter().rev() {
+            let kind = match node.borrow().element_kind() {
+                Some(kind) => kind,
+                None => continue,
+            };
+            if kind == element_kind {
+                return true;
+            }
+            if boundaries.contains(&kind) {
+                return false;
+            }
+        }
+        false
+    }
+
+    // 現在のノードが指定した要素になるまで取り出す
+    fn clear_stack_back_to(&mut self, element_kinds: &[ElementKind]) {
+        while let Some(node) = self.stack_of_open_elements.last() {
+            let kind = node.borrow().element_kind();
+            if kind == Some(ElementKind::Html) || kind.is_some_and(|k| element_kinds.contains(&k)) {
+                return;
+            }
+            self.stack_of_open_elements.pop();
+        }
+    }
+
+    fn close_cell(&mut self) {
+        while let Some(node) = self.stack_of_open_elements.pop() {
+            if matches!(
+                node.borrow().element_kind(),
+                Some(ElementKind::Td | ElementKind::Th)
+            ) {
+                break;
+            }
+        }
+        self.mode = InsertionMode::InRow;
+    }
+
+    // スタックに残っている要素から挿入モードを決め直す
+    fn reset_insertion_mode(&mut self) {
+        for node in self.stack_of_open_elements.iter().rev() {
+            let mode = match node.borrow().element_kind() {
+                Some(ElementKind::Td | ElementKind::Th) => InsertionMode::InCell,
+                Some(ElementKind::Tr) => InsertionMode::InRow,
+                Some(ElementKind::Tbody | ElementKind::Tfoot | ElementKind::Thead) => {
+                    InsertionMode::InTableBody
+                }
+                Some(ElementKind::Caption) => InsertionMode::InCaption,
+                Some(ElementKind::Colgroup) => InsertionMode::InColumnGroup,
+                Some(ElementKind::Table) => InsertionMode::InTable,
+                Some(ElementKind::Body | ElementKind::Html) => InsertionMode::InBody,
+                _ => continue,
+            };
+            self.mode = mode;
+            return;
+        }
+        self.mode = InsertionMode::InBody;
+    }
+
+    fn create_element(&self, tag: &str, attributes: Vec<Attribute>) -> Node {
+        Node::new(NodeKind::Element(Element::new(tag, attributes)))
+    }
+
+    fn insert_element(&mut self, tag: &str, attributes: Vec<Attribute>) {
+        let new_child = Rc::new(RefCell::new(self.create_element(tag, attributes)));
+
+        let (current_node, reference) = self.insertion_location();
+        if let Some(reference) = reference {
+            insert_before(&current_node, &new_child, Some(&reference));
+            self.stack_of_open_elements.push(new_child);
+            return;
+        }
 
         // let first_child = current_node.borrow().first_child();
         // if let Some(mut last_sibling) = first_child {
@@ -441,21 +1036,46 @@ impl HtmlParser {
 
     fn insert_char(&mut self, ch: char) {
         // ルートノードの場合何もしない
-        let current_node = match self.stack_of_open_elements.last() {
-            Some(node) => node,
-            None => return,
-        };
-
-        if let NodeKind::Text(s) = &mut current_node.borrow_mut().kind {
-            s.push(ch);
+        if self.stack_of_open_elements.is_empty() {
             return;
         }
+        let (current_node, reference) = self.insertion_location();
+
+        // テキストノードはスタックに積まず、直前の子がテキストノードであれば連結する
+        let last_child = match &reference {
+            Some(reference) => reference.borrow().previous_sibling().upgrade(),
+            None => current_node.borrow().last_child().upgrade(),
+        };
+        if let Some(last_child) = last_child {
+            if let NodeKind::Text(s) = &mut last_child.borrow_mut().kind {
+                s.push(ch);
+                return;
+            }
+        }
 
-        if ch == '\n' || ch == ' ' {
+        // 本文の外や表の構造の中にある要素間の空白はテキストノードにしない
+        let structural = matches!(
+            current_node.borrow().element_kind(),
+            Some(
+                ElementKind::Html
+                    | ElementKind::Head
+                    | ElementKind::Table
+                    | ElementKind::Colgroup
+                    | ElementKind::Thead
+                    | ElementKind::Tbody
+                    | ElementKind::Tfoot
+                    | ElementKind::Tr
+            )
+        );
+        if structural && (ch == '\n' || ch == ' ') {
             return;
         }
 
         let new_child = Rc::new(RefCell::new(self.create_text_node_from_char(ch)));
+        if let Some(reference) = reference {
+            insert_before(&current_node, &new_child, Some(&reference));
+            return;
+        }
 
         // なぜfirst_childの後に追加するのだろうか...?
         // let first_child_option = current_node.borrow().first_child();
@@ -491,9 +1111,7 @@ impl HtmlParser {
             .set_last_child(Rc::downgrade(&new_child));
         new_child
             .borrow_mut()
-            .set_parent(Rc::downgrade(current_node));
-
-        self.stack_of_open_elements.push(new_child);
+            .set_parent(Rc::downgrade(&current_node));
     }
 
     fn create_text_node_from_char(&self, ch: char) -> Node {
@@ -512,6 +1130,13 @@ pub enum InsertionMode {
     AfterHead,
     InBody,
     Text,
+    InTable,
+    InTableText,
+    InCaption,
+    InColumnGroup,
+    InTableBody,
+    InRow,
+    InCell,
     AfterBody,
     AfterAfterBody,
 }
@@ -520,10 +1145,14 @@ pub enum InsertionMode {
 mod tests {
     use super::HtmlParser;
     use crate::renderer::{
-        dom::node::{Element, Node, NodeKind},
+        dom::{
+            api::children,
+            node::{Element, ElementKind, Node, NodeKind},
+        },
         html::{attribute::Attribute, token::HtmlTokenizer},
     };
     use alloc::{
+        format,
         rc::Rc,
         string::{String, ToString},
         vec,
@@ -531,6 +1160,31 @@ mod tests {
     };
     use core::cell::RefCell;
 
+    // bodyの中身を"要素名(子, ...)"の形の文字列にする
+    fn body_tree(html: &str) -> String {
+        fn serialize(node: &Rc<RefCell<Node>>) -> String {
+            let kind = node.borrow().kind();
+            match kind {
+                NodeKind::Text(text) => format!("{:?}", text),
+                NodeKind::Element(element) => {
+                    let children = children(node).iter().map(serialize).collect::<Vec<_>>();
+                    if children.is_empty() {
+                        element.kind().to_string()
+                    } else {
+                        format!("{}({})", element.kind(), children.join(", "))
+                    }
+                }
+                NodeKind::Document => String::new(),
+            }
+        }
+
+        let window = HtmlParser::new(HtmlTokenizer::new(html.to_string())).construct_tree();
+        let document = window.borrow().document();
+        let html = document.borrow().first_child().expect("no html element");
+        let body = children(&html).pop().expect("no body element");
+        serialize(&body)
+    }
+
     #[test]
     fn test_empty() {
         let html = String::new();
@@ -697,4 +1351,154 @@ mod tests {
             text
         );
     }
+
+    #[test]
+    fn test_text_is_not_open_element() {
+        // テキストノードを開いている要素のスタックに積まないので、続く要素はテキストノードの兄弟になり、
+        // 終了タグの後のテキストは新しいテキストノードになる
+        let html = "<html><head></head><body><p>a<a>b</a>c</p></body></html>".to_string();
+        let window = HtmlParser::new(HtmlTokenizer::new(html)).construct_tree();
+        let document = window.borrow().document();
+        let body = document
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of document")
+            .borrow()
+            .last_child()
+            .upgrade()
+            .expect("failed to get a last child of html");
+        let p = body
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of body");
+        let a = p
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of p")
+            .borrow()
+            .next_sibling()
+            .expect("failed to get a next sibling of text");
+        assert_eq!(
+            Rc::new(RefCell::new(Node::new(NodeKind::Element(Element::new(
+                "a",
+                Vec::new()
+            ))))),
+            a
+        );
+        let texts: Vec<String> = [
+            p.borrow().first_child(),
+            a.borrow().first_child(),
+            a.borrow().next_sibling(),
+        ]
+        .iter()
+        .map(
+            |node| match node.as_ref().map(|node| node.borrow().kind()) {
+                Some(NodeKind::Text(s)) => s,
+                _ => String::new(),
+            },
+        )
+        .collect();
+        assert_eq!(vec!["a", "b", "c"], texts);
+        assert!(a
+            .borrow()
+            .next_sibling()
+            .unwrap()
+            .borrow()
+            .next_sibling()
+            .is_none());
+    }
+
+    #[test]
+    fn test_link() {
+        let html = "<html><head><link rel=stylesheet href=a.css><style></style></head><body></body></html>".to_string();
+        let t = HtmlTokenizer::new(html);
+        let window = HtmlParser::new(t).construct_tree();
+        let document = window.borrow().document();
+
+        let head = document
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of document")
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of html");
+        let link = head
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of head");
+        assert_eq!(
+            Some("a.css".to_string()),
+            link.borrow()
+                .get_element()
+                .expect("link should be an element")
+                .get_attribute("href")
+        );
+        assert!(link.borrow().first_child().is_none());
+
+        let style = link
+            .borrow()
+            .next_sibling()
+            .expect("failed to get a next sibling of link");
+        assert_eq!(
+            ElementKind::Style,
+            style.borrow().get_element().unwrap().kind()
+        );
+    }
+
+    #[test]
+    fn test_table_implied_elements() {
+        assert_eq!(
+            "body(table(tbody(tr(td(\"a\"), td(\"b\")), tr(th(\"c\")))))",
+            body_tree("<html><body><table><tr><td>a<td>b<tr><th>c</table></body></html>")
+        );
+        assert_eq!(
+            "body(table(caption(\"c\"), colgroup(col, col), thead(tr(th(\"h\"))), tbody(tr(td(\"1\")))))",
+            body_tree(
+                "<html><body><table><caption>c</caption><col><col><thead><tr><th>h\
+                 </thead><tbody><tr><td>1</td></tr></tbody></table></body></html>"
+            )
+        );
+    }
+
+    #[test]
+    fn test_table_foster_parenting() {
+        assert_eq!(
+            "body(\"x\", p(\"y\"), \"z\", table(tbody(tr(td(\"a\")))))",
+            body_tree("<html><body><table>x<tr><td>a</td></tr><p>y</p>z</table></body></html>")
+        );
+        // 空白だけのテキストはテーブルの前に移さない
+        assert_eq!(
+            "body(table(tbody(tr(td(\"a\")))))",
+            body_tree("<html><body><table><tbody> <tr><td>a</table></body></html>")
+        );
+    }
+
+    #[test]
+    fn test_nested_table() {
+        assert_eq!(
+            "body(p(\"a\"), table(tbody(tr(td(table(tbody(tr(td(\"b\")))), \"c\")))), \"d\")",
+            body_tree(
+                "<html><body><p>a<table><tr><td><table><tr><td>b</table>c</p></td></table>d\
+                 </body></html>"
+            )
+        );
+    }
+
+    #[test]
+    fn test_img() {
+        assert_eq!(
+            "body(p(\"a\", img, \"b\", img))",
+            body_tree("<html><body><p>a<img src=\"x.png\">b<img src=\"y.png\"/></p></body></html>")
+        );
+    }
+
+    #[test]
+    fn test_list() {
+        assert_eq!(
+            "body(p(\"a\"), ul(li(\"b\"), li(\"c\", ol(li(\"d\"), li(\"e\"))), li(\"f\")), \"g\")",
+            body_tree(
+                "<html><body><p>a<ul><li>b</li><li>c<ol><li>d<li>e</ol><li>f</ul>g</body></html>"
+            )
+        );
+    }
 }
@@ -8,6 +8,8 @@ pub struct HtmlTokenizer {
     latest_token: Option<HtmlToken>,
     input: Vec<char>,
     buf: String,
+    // 文字のまま読んでいる要素の名前。同じ名前の終了タグで通常の状態に戻る
+    raw_text_tag: String,
 }
 
 impl HtmlTokenizer {
@@ -19,9 +21,16 @@ impl HtmlTokenizer {
             latest_token: None,
             input: html.chars().collect(),
             buf: String::new(),
+            raw_text_tag: String::new(),
         }
     }
 
+    // styleやscriptの中身はタグとして解釈せず、文字のまま読む
+    pub fn switch_to_raw_text(&mut self, tag: &str) {
+        self.state = State::ScriptData;
+        self.raw_text_tag = String::from(tag);
+    }
+
     fn re_consume_input(&mut self) -> char {
         self.re_consume = false;
         self.input[self.pos - 1]
@@ -393,7 +402,7 @@ impl Iterator for HtmlTokenizer {
                     return Some(HtmlToken::Char('<'));
                 }
                 State::ScriptDataEndTagName => {
-                    if ch == '>' {
+                    if ch == '>' && self.buf.eq_ignore_ascii_case(&self.raw_text_tag) {
                         self.state = State::Data;
                         return self.take_latest_token();
                     }
@@ -0,0 +1,136 @@
+use crate::{error::Error, renderer::css::value::Color};
+use alloc::{format, vec, vec::Vec};
+
+// 壊れたヘッダで巨大な領域を確保しないように、デコードする画像の画素数を制限する
+const MAX_PIXELS: usize = 1 << 26;
+
+// 左上から行ごとに並べたRGBAの画素を持つ、デコードした画像。アルファは色に掛けていない
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Bitmap {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Bitmap {
+    // 透明な画素で埋めた画像を作る
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width * height * 4],
+        }
+    }
+
+    // 大きすぎる画像はエラーにする
+    pub fn try_new(width: usize, height: usize) -> Result<Self, Error> {
+        match width.checked_mul(height) {
+            Some(pixels) if pixels <= MAX_PIXELS => Ok(Self::new(width, height)),
+            _ => Err(Error::UnexpectedInput(format!(
+                "image size {}x{} is too large",
+                width, height
+            ))),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> Color {
+        let i = (y * self.width + x) * 4;
+        Color::rgba(
+            self.pixels[i],
+            self.pixels[i + 1],
+            self.pixels[i + 2],
+            self.pixels[i + 3],
+        )
+    }
+
+    // 範囲外の画素は無視する
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: Color) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let i = (y * self.width + x) * 4;
+        self.pixels[i..i + 4].copy_from_slice(&[color.r, color.g, color.b, color.a]);
+    }
+}
+
+// アニメーションの1コマ。delayは次のコマまでのミリ秒
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frame {
+    pub bitmap: Bitmap,
+    pub delay: u32,
+}
+
+// 1つ以上のコマを持つ画像。静止画は1コマだけを持つ
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    frames: Vec<Frame>,
+    // 繰り返す回数。0は無限に繰り返す
+    loop_count: u32,
+}
+
+impl Image {
+    pub fn new(frames: Vec<Frame>, loop_count: u32) -> Self {
+        Self { frames, loop_count }
+    }
+
+    pub fn still(bitmap: Bitmap) -> Self {
+        Self::new(vec![Frame { bitmap, delay: 0 }], 1)
+    }
+
+    pub fn width(&self) -> usize {
+        self.frames.first().map_or(0, |f| f.bitmap.width())
+    }
+
+    pub fn height(&self) -> usize {
+        self.frames.first().map_or(0, |f| f.bitmap.height())
+    }
+
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    pub fn loop_count(&self) -> u32 {
+        self.loop_count
+    }
+
+    pub fn is_animated(&self) -> bool {
+        self.frames.len() > 1
+    }
+
+    // 最初のコマ。静止画として描くときに使う
+    pub fn bitmap(&self) -> &Bitmap {
+        &self.frames[0].bitmap
+    }
+
+    // 始まってからtimeミリ秒後に表示するコマ
+    pub fn frame_at(&self, time: u64) -> &Bitmap {
+        let total: u64 = self.frames.iter().map(|f| f.delay as u64).sum();
+        if !self.is_animated() || total == 0 {
+            return self.bitmap();
+        }
+        let cycle = time / total;
+        if self.loop_count != 0 && cycle >= self.loop_count as u64 {
+            return &self.frames[self.frames.len() - 1].bitmap;
+        }
+        let mut remaining = time % total;
+        for frame in &self.frames {
+            if remaining < frame.delay as u64 {
+                return &frame.bitmap;
+            }
+            remaining -= frame.delay as u64;
+        }
+        self.bitmap()
+    }
+}
@@ -0,0 +1,354 @@
+use crate::{
+    error::Error,
+    renderer::{css::value::Color, image::bitmap::Bitmap},
+};
+use alloc::{format, string::ToString, vec::Vec};
+
+const BI_RGB: u32 = 0;
+const BI_RLE8: u32 = 1;
+const BI_RLE4: u32 = 2;
+const BI_BITFIELDS: u32 = 3;
+// OS/2のBITMAPCOREHEADERの大きさ。大きさが16ビットで、パレットが3バイトずつになる
+const CORE_HEADER_SIZE: usize = 12;
+
+fn error(message: &str) -> Error {
+    Error::UnexpectedInput(message.to_string())
+}
+
+fn u16_at(data: &[u8], offset: usize) -> u32 {
+    u16::from_le_bytes([data[offset], data[offset + 1]]) as u32
+}
+
+fn u32_at(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+    ])
+}
+
+struct Header {
+    size: usize,
+    width: usize,
+    height: usize,
+    // 上の行から並んでいるか。高さが負の画像だけが上から並ぶ
+    top_down: bool,
+    bit_count: u32,
+    compression: u32,
+    colors_used: usize,
+}
+
+fn read_header(data: &[u8]) -> Option<Header> {
+    if data.len() < 26 || &data[..2] != b"BM" {
+        return None;
+    }
+    let size = u32_at(data, 14) as usize;
+    if size == CORE_HEADER_SIZE {
+        return Some(Header {
+            size,
+            width: u16_at(data, 18) as usize,
+            height: u16_at(data, 20) as usize,
+            top_down: false,
+            bit_count: u16_at(data, 24),
+            compression: BI_RGB,
+            colors_used: 0,
+        });
+    }
+    if size < 40 || data.len() < 14 + 40 {
+        return None;
+    }
+    let height = u32_at(data, 22) as i32;
+    Some(Header {
+        size,
+        width: (u32_at(data, 18) as i32).unsigned_abs() as usize,
+        height: height.unsigned_abs() as usize,
+        top_down: height < 0,
+        bit_count: u16_at(data, 28),
+        compression: u32_at(data, 30),
+        colors_used: u32_at(data, 46) as usize,
+    })
+}
+
+// 情報ヘッダから読む画像の大きさ。画素のデータを読まずに分かる
+pub fn bmp_size(data: &[u8]) -> Option<(usize, usize)> {
+    read_header(data).map(|header| (header.width, header.height))
+}
+
+// ビットマスクで取り出した値を8ビットに揃える
+struct Channel {
+    mask: u32,
+    shift: u32,
+}
+
+impl Channel {
+    fn new(mask: u32) -> Self {
+        Self {
+            mask,
+            shift: if mask == 0 { 0 } else { mask.trailing_zeros() },
+        }
+    }
+
+    fn value(&self, pixel: u32) -> Option<u8> {
+        if self.mask == 0 {
+            return None;
+        }
+        let max = (self.mask >> self.shift) as u64;
+        Some((((pixel & self.mask) >> self.shift) as u64 * 255 / max) as u8)
+    }
+}
+
+// RLEで圧縮された画素の色の番号を、下の行から並んだ配列に展開する
+fn decode_rle(data: &[u8], header: &Header) -> Result<Vec<Option<u8>>, Error> {
+    let (width, height) = (header.width, header.height);
+    // 書かれなかった画素は透明にする
+    let mut indices = alloc::vec![None; width * height];
+    let (mut x, mut y) = (0usize, 0usize);
+    let mut offset = 0;
+    let four_bits = header.compression == BI_RLE4;
+    let mut put = |x: &mut usize, y: usize, index: u8| {
+        if *x < width && y < height {
+            indices[y * width + *x] = Some(index);
+        }
+        *x += 1;
+    };
+    while offset + 1 < data.len() {
+        let (count, value) = (data[offset] as usize, data[offset + 1]);
+        offset += 2;
+        if count > 0 {
+            for i in 0..count {
+                let index = match (four_bits, i % 2) {
+                    (true, 0) => value >> 4,
+                    (true, _) => value & 0x0f,
+                    (false, _) => value,
+                };
+                put(&mut x, y, index);
+            }
+            continue;
+        }
+        match value {
+            0 => {
+                x = 0;
+                y += 1;
+            }
+            1 => break,
+            2 => {
+                let delta = data
+                    .get(offset..offset + 2)
+                    .ok_or_else(|| error("BMP RLE data is truncated"))?;
+                x += delta[0] as usize;
+                y += delta[1] as usize;
+                offset += 2;
+            }
+            count => {
+                // 絶対モードの画素は2バイト単位に揃えて置かれる
+                let count = count as usize;
+                let bytes = if four_bits { count.div_ceil(2) } else { count };
+                let run = data
+                    .get(offset..offset + bytes)
+                    .ok_or_else(|| error("BMP RLE data is truncated"))?;
+                for i in 0..count {
+                    let index = if four_bits {
+                        if i % 2 == 0 {
+                            run[i / 2] >> 4
+                        } else {
+                            run[i / 2] & 0x0f
+                        }
+                    } else {
+                        run[i]
+                    };
+                    put(&mut x, y, index);
+                }
+                offset += bytes + bytes % 2;
+            }
+        }
+    }
+    Ok(indices)
+}
+
+pub fn decode_bmp(data: &[u8]) -> Result<Bitmap, Error> {
+    let header = read_header(data).ok_or_else(|| error("not a BMP image"))?;
+    let pixel_offset = u32_at(data, 10) as usize;
+    let pixels = data
+        .get(pixel_offset..)
+        .ok_or_else(|| error("BMP pixel data is missing"))?;
+    let table_offset = 14 + header.size;
+
+    // パレットを持つ形式
+    let mut palette = Vec::new();
+    if header.bit_count <= 8 {
+        let entry_size = if header.size == CORE_HEADER_SIZE {
+            3
+        } else {
+            4
+        };
+        let count = match header.colors_used {
+            0 => 1 << header.bit_count,
+            count => count.min(256),
+        };
+        for i in 0..count {
+            let entry = table_offset + i * entry_size;
+            let Some(c) = data.get(entry..entry + 3) else {
+                break;
+            };
+            palette.push(Color::rgb(c[2], c[1], c[0]));
+        }
+    }
+
+    // ビットマスクはV4以降のヘッダの中か、情報ヘッダの直後にある
+    let (red, green, blue, alpha) = match (header.compression, header.bit_count) {
+        (BI_BITFIELDS, _) => {
+            let masks = if header.size >= 52 {
+                14 + 40
+            } else {
+                table_offset
+            };
+            let mask = |i: usize| {
+                data.get(masks + i * 4..masks + i * 4 + 4)
+                    .map_or(0, |_| u32_at(data, masks + i * 4))
+            };
+            let alpha = if header.size >= 56 { mask(3) } else { 0 };
+            (mask(0), mask(1), mask(2), alpha)
+        }
+        (BI_RGB, 16) => (0x7c00, 0x03e0, 0x001f, 0),
+        _ => (0x00ff_0000, 0x0000_ff00, 0x0000_00ff, 0),
+    };
+    let channels = [
+        Channel::new(red),
+        Channel::new(green),
+        Channel::new(blue),
+        Channel::new(alpha),
+    ];
+
+    let (width, height) = (header.width, header.height);
+    let mut bitmap = Bitmap::try_new(width, height)?;
+    let row_of = |y: usize| if header.top_down { y } else { height - 1 - y };
+
+    if header.compression == BI_RLE8 || header.compression == BI_RLE4 {
+        let indices = decode_rle(pixels, &header)?;
+        for y in 0..height {
+            for x in 0..width {
+                if let Some(color) = indices[y * width + x].and_then(|i| palette.get(i as usize)) {
+                    bitmap.set_pixel(x, height - 1 - y, *color);
+                }
+            }
+        }
+        return Ok(bitmap);
+    }
+    if header.compression != BI_RGB && header.compression != BI_BITFIELDS {
+        return Err(Error::UnexpectedInput(format!(
+            "unsupported BMP compression {}",
+            header.compression
+        )));
+    }
+
+    let bits = header.bit_count as usize;
+    if ![1, 4, 8, 16, 24, 32].contains(&bits) {
+        return Err(Error::UnexpectedInput(format!(
+            "unsupported BMP bit count {}",
+            bits
+        )));
+    }
+    // 各行は4バイト単位に揃えられている
+    let stride = (width * bits).div_ceil(32) * 4;
+    for y in 0..height {
+        let row = pixels
+            .get(y * stride..(y + 1) * stride)
+            .ok_or_else(|| error("BMP pixel data is truncated"))?;
+        for x in 0..width {
+            let color = match bits {
+                1 | 4 | 8 => {
+                    let bit = x * bits;
+                    let index = (row[bit / 8] >> (8 - bits - bit % 8)) & ((1 << bits) - 1) as u8;
+                    palette.get(index as usize).copied().unwrap_or(Color::BLACK)
+                }
+                24 => Color::rgb(row[x * 3 + 2], row[x * 3 + 1], row[x * 3]),
+                _ => {
+                    let pixel = if bits == 16 {
+                        u16_at(row, x * 2)
+                    } else {
+                        u32_at(row, x * 4)
+                    };
+                    let [r, g, b, a] = &channels;
+                    Color::rgba(
+                        r.value(pixel).unwrap_or(0),
+                        g.value(pixel).unwrap_or(0),
+                        b.value(pixel).unwrap_or(0),
+                        a.value(pixel).unwrap_or(255),
+                    )
+                }
+            };
+            bitmap.set_pixel(x, row_of(y), color);
+        }
+    }
+    Ok(bitmap)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_bmp() {
+        // 8ビットのパレットを持つ、下の行から並んだ3x2の画像
+        let bmp = [
+            0x42, 0x4d, 0x4a, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x42, 0x00, 0x00, 0x00,
+            0x28, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x01, 0x00,
+            0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0xff, 0x00, 0x00, 0xff, 0x00, 0x00, 0xff, 0x00, 0x00, 0x00, 0x00, 0x01, 0x02, 0x00,
+            0x02, 0x01, 0x00, 0x00,
+        ];
+        assert_eq!(Some((3, 2)), bmp_size(&bmp));
+        let bitmap = decode_bmp(&bmp).expect("failed to decode");
+        let red = Color::rgb(255, 0, 0);
+        let green = Color::rgb(0, 255, 0);
+        let blue = Color::rgb(0, 0, 255);
+        assert_eq!(blue, bitmap.pixel(0, 0));
+        assert_eq!(green, bitmap.pixel(1, 0));
+        assert_eq!(red, bitmap.pixel(2, 0));
+        assert_eq!(red, bitmap.pixel(0, 1));
+        assert_eq!(blue, bitmap.pixel(2, 1));
+
+        // 24ビットで上の行から並んだ画像を組み立てる
+        let mut top_down = Vec::new();
+        top_down.extend_from_slice(b"BM");
+        top_down.extend_from_slice(&[0; 8]);
+        top_down.extend_from_slice(&54u32.to_le_bytes());
+        top_down.extend_from_slice(&40u32.to_le_bytes());
+        top_down.extend_from_slice(&2i32.to_le_bytes());
+        top_down.extend_from_slice(&(-2i32).to_le_bytes());
+        top_down.extend_from_slice(&[1, 0, 24, 0]);
+        top_down.extend_from_slice(&[0; 24]);
+        top_down.extend_from_slice(&[255, 0, 0, 0, 255, 0, 0, 0]);
+        top_down.extend_from_slice(&[0, 0, 255, 255, 255, 255, 0, 0]);
+        let decoded = decode_bmp(&top_down).expect("failed to decode");
+        assert_eq!(blue, decoded.pixel(0, 0));
+        assert_eq!(green, decoded.pixel(1, 0));
+        assert_eq!(red, decoded.pixel(0, 1));
+        assert_eq!(Color::WHITE, decoded.pixel(1, 1));
+    }
+
+    #[test]
+    fn test_decode_rle4() {
+        // 下の行は連続する4画素、上の行は絶対モードの3画素で、残りは書かれない
+        let bmp = [
+            0x42, 0x4d, 0x48, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x3e, 0x00, 0x00, 0x00,
+            0x28, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x01, 0x00,
+            0x04, 0x00, 0x02, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0xff, 0xff, 0xff, 0x00, 0x04, 0x01, 0x00, 0x00, 0x00, 0x03, 0x10, 0x10,
+            0x00, 0x01,
+        ];
+        let bitmap = decode_bmp(&bmp).expect("failed to decode");
+        let (black, white) = (Color::BLACK, Color::WHITE);
+        assert_eq!(black, bitmap.pixel(0, 1));
+        assert_eq!(white, bitmap.pixel(1, 1));
+        assert_eq!(black, bitmap.pixel(2, 1));
+        assert_eq!(white, bitmap.pixel(3, 1));
+        assert_eq!(white, bitmap.pixel(0, 0));
+        assert_eq!(black, bitmap.pixel(1, 0));
+        assert_eq!(white, bitmap.pixel(2, 0));
+        assert_eq!(Color::TRANSPARENT, bitmap.pixel(3, 0));
+    }
+}
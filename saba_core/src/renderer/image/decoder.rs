@@ -0,0 +1,82 @@
+use crate::{
+    error::Error,
+    renderer::{
+        image::{
+            bitmap::Image,
+            bmp::{bmp_size, decode_bmp},
+            gif::{decode_gif, gif_size},
+            jpeg::{decode_jpeg, jpeg_size},
+            png::{decode_png, png_size},
+        },
+        paint::encoder::PNG_SIGNATURE,
+    },
+};
+use alloc::string::ToString;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Gif,
+    Bmp,
+    Jpeg,
+}
+
+// Content-Typeではなく先頭のバイト列から形式を判定する
+pub fn sniff(data: &[u8]) -> Option<ImageFormat> {
+    if data.starts_with(&PNG_SIGNATURE) {
+        Some(ImageFormat::Png)
+    } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        Some(ImageFormat::Gif)
+    } else if data.starts_with(b"BM") {
+        Some(ImageFormat::Bmp)
+    } else if data.starts_with(&[0xff, 0xd8, 0xff]) {
+        Some(ImageFormat::Jpeg)
+    } else {
+        None
+    }
+}
+
+// 画素を展開せずにヘッダから分かる画像の大きさ
+pub fn intrinsic_size(data: &[u8]) -> Option<(usize, usize)> {
+    match sniff(data)? {
+        ImageFormat::Png => png_size(data),
+        ImageFormat::Gif => gif_size(data),
+        ImageFormat::Bmp => bmp_size(data),
+        ImageFormat::Jpeg => jpeg_size(data),
+    }
+}
+
+pub fn decode_image(data: &[u8]) -> Result<Image, Error> {
+    match sniff(data) {
+        Some(ImageFormat::Png) => Ok(Image::still(decode_png(data)?)),
+        Some(ImageFormat::Gif) => decode_gif(data),
+        Some(ImageFormat::Bmp) => Ok(Image::still(decode_bmp(data)?)),
+        Some(ImageFormat::Jpeg) => Ok(Image::still(decode_jpeg(data)?)),
+        None => Err(Error::UnexpectedInput("unknown image format".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::{
+        css::value::Color,
+        paint::{encoder::encode_png, rasterizer::Framebuffer},
+    };
+
+    #[test]
+    fn test_decode_image() {
+        let mut framebuffer = Framebuffer::new(3, 2);
+        framebuffer.clear(Color::rgb(0, 128, 0));
+        let png = encode_png(&framebuffer);
+        assert_eq!(Some(ImageFormat::Png), sniff(&png));
+        assert_eq!(Some((3, 2)), intrinsic_size(&png));
+        let image = decode_image(&png).expect("failed to decode");
+        assert!(!image.is_animated());
+        assert_eq!(Color::rgb(0, 128, 0), image.bitmap().pixel(2, 1));
+
+        assert_eq!(Some(ImageFormat::Gif), sniff(b"GIF89a"));
+        assert_eq!(None, intrinsic_size(b"<svg></svg>"));
+        assert!(decode_image(b"<svg></svg>").is_err());
+    }
+}
@@ -0,0 +1,345 @@
+use crate::{
+    error::Error,
+    renderer::{
+        css::value::Color,
+        image::bitmap::{Bitmap, Frame, Image},
+    },
+};
+use alloc::{string::ToString, vec, vec::Vec};
+
+const MAX_CODE_SIZE: u32 = 12;
+// これより短い表示時間は多くのブラウザと同じく100ミリ秒として扱う
+const MIN_DELAY: u32 = 20;
+const DEFAULT_DELAY: u32 = 100;
+
+fn error(message: &str) -> Error {
+    Error::UnexpectedInput(message.to_string())
+}
+
+fn u16_at(data: &[u8], offset: usize) -> usize {
+    u16::from_le_bytes([data[offset], data[offset + 1]]) as usize
+}
+
+// 論理画面の大きさ。コマを展開せずに分かる
+pub fn gif_size(data: &[u8]) -> Option<(usize, usize)> {
+    // シグネチャと論理画面記述子の13バイトが揃っている必要がある
+    if data.len() < 13 || (&data[..6] != b"GIF87a" && &data[..6] != b"GIF89a") {
+        return None;
+    }
+    Some((u16_at(data, 6), u16_at(data, 8)))
+}
+
+// 長さの付いたサブブロックの並びを読み、0のブロックの後の位置を返す
+fn read_sub_blocks(data: &[u8], mut offset: usize) -> Result<(Vec<u8>, usize), Error> {
+    let mut output = Vec::new();
+    loop {
+        let length = *data
+            .get(offset)
+            .ok_or_else(|| error("GIF data is truncated"))? as usize;
+        offset += 1;
+        if length == 0 {
+            return Ok((output, offset));
+        }
+        output.extend_from_slice(
+            data.get(offset..offset + length)
+                .ok_or_else(|| error("GIF data is truncated"))?,
+        );
+        offset += length;
+    }
+}
+
+fn read_color_table(data: &[u8], offset: usize, size: usize) -> Result<Vec<Color>, Error> {
+    let table = data
+        .get(offset..offset + size * 3)
+        .ok_or_else(|| error("GIF color table is truncated"))?;
+    Ok(table
+        .chunks_exact(3)
+        .map(|c| Color::rgb(c[0], c[1], c[2]))
+        .collect())
+}
+
+// 可変長の符号で表されたLZW圧縮を展開して色の番号の並びにする
+fn lzw_decode(data: &[u8], min_code_size: u32, pixels: usize) -> Result<Vec<u8>, Error> {
+    if !(1..MAX_CODE_SIZE).contains(&min_code_size) {
+        return Err(error("invalid GIF LZW code size"));
+    }
+    let clear = 1u16 << min_code_size;
+    let end = clear + 1;
+    // 各符号が表す列を、前の符号と最後の値で持つ
+    let mut prefix = vec![0u16; 1 << MAX_CODE_SIZE];
+    let mut suffix = vec![0u8; 1 << MAX_CODE_SIZE];
+    let mut length = vec![0u16; 1 << MAX_CODE_SIZE];
+    for code in 0..clear {
+        suffix[code as usize] = code as u8;
+        length[code as usize] = 1;
+    }
+
+    let mut output = Vec::with_capacity(pixels);
+    let mut code_size = min_code_size + 1;
+    let mut next = end + 1;
+    let mut previous: Option<u16> = None;
+    let (mut bits, mut bit_count, mut position) = (0u32, 0u32, 0usize);
+    let mut buffer = Vec::new();
+    while output.len() < pixels {
+        while bit_count < code_size {
+            let Some(byte) = data.get(position) else {
+                return Ok(output);
+            };
+            bits |= (*byte as u32) << bit_count;
+            bit_count += 8;
+            position += 1;
+        }
+        let code = (bits & ((1 << code_size) - 1)) as u16;
+        bits >>= code_size;
+        bit_count -= code_size;
+
+        if code == clear {
+            code_size = min_code_size + 1;
+            next = end + 1;
+            previous = None;
+            continue;
+        }
+        if code == end {
+            break;
+        }
+        let Some(previous_code) = previous else {
+            if code >= clear {
+                return Err(error("invalid GIF LZW code"));
+            }
+            output.push(code as u8);
+            previous = Some(code);
+            continue;
+        };
+
+        // まだ表にない符号は、前の列にその先頭を加えたもの
+        let known = code < next;
+        if !known && code != next {
+            return Err(error("invalid GIF LZW code"));
+        }
+        let start = if known { code } else { previous_code };
+        buffer.clear();
+        let mut c = start;
+        loop {
+            buffer.push(suffix[c as usize]);
+            if length[c as usize] <= 1 {
+                break;
+            }
+            c = prefix[c as usize];
+        }
+        buffer.reverse();
+        let first = buffer[0];
+        if !known {
+            buffer.push(first);
+        }
+        output.extend_from_slice(&buffer);
+
+        if (next as usize) < (1 << MAX_CODE_SIZE) {
+            prefix[next as usize] = previous_code;
+            suffix[next as usize] = first;
+            length[next as usize] = length[previous_code as usize] + 1;
+            next += 1;
+            if next == 1 << code_size && code_size < MAX_CODE_SIZE {
+                code_size += 1;
+            }
+        }
+        previous = Some(code);
+    }
+    output.truncate(pixels);
+    Ok(output)
+}
+
+// インターレースされた画像のy行目のデータが実際に置かれる行
+fn interlaced_rows(height: usize) -> Vec<usize> {
+    let mut rows = Vec::with_capacity(height);
+    for (start, step) in [(0, 8), (4, 8), (2, 4), (1, 2)] {
+        rows.extend((start..height).step_by(step));
+    }
+    rows
+}
+
+#[derive(Clone, Copy, Default)]
+struct GraphicControl {
+    disposal: u8,
+    delay: u32,
+    transparent: Option<u8>,
+}
+
+// コマを論理画面に重ねて合成し、コマごとの全体の画像にする
+pub fn decode_gif(data: &[u8]) -> Result<Image, Error> {
+    let (width, height) = gif_size(data).ok_or_else(|| error("not a GIF image"))?;
+    let flags = data[10];
+    let mut offset = 13;
+    let global_colors = if flags & 0x80 != 0 {
+        let size = 2 << (flags & 0x07);
+        let table = read_color_table(data, offset, size)?;
+        offset += size * 3;
+        table
+    } else {
+        Vec::new()
+    };
+
+    let mut canvas = Bitmap::try_new(width, height)?;
+    let mut frames = Vec::new();
+    let mut control = GraphicControl::default();
+    // NETSCAPE2.0拡張がなければ1回だけ表示する
+    let mut loop_count = 1;
+    loop {
+        let Some(&introducer) = data.get(offset) else {
+            break;
+        };
+        offset += 1;
+        match introducer {
+            0x21 => {
+                let label = *data
+                    .get(offset)
+                    .ok_or_else(|| error("GIF data is truncated"))?;
+                let (block, next) = read_sub_blocks(data, offset + 1)?;
+                match label {
+                    0xf9 if block.len() >= 4 => {
+                        let delay = u16_at(&block, 1) as u32 * 10;
+                        control = GraphicControl {
+                            disposal: (block[0] >> 2) & 0x07,
+                            delay: if delay < MIN_DELAY {
+                                DEFAULT_DELAY
+                            } else {
+                                delay
+                            },
+                            transparent: (block[0] & 0x01 != 0).then_some(block[3]),
+                        };
+                    }
+                    // 識別子の後のサブブロックに繰り返す回数がある。0は無限に繰り返す
+                    0xff if block.starts_with(b"NETSCAPE2.0") && block.len() >= 14 => {
+                        loop_count = match u16_at(&block, 12) {
+                            0 => 0,
+                            count => count as u32 + 1,
+                        };
+                    }
+                    _ => {}
+                }
+                offset = next;
+            }
+            0x2c => {
+                let descriptor = data
+                    .get(offset..offset + 9)
+                    .ok_or_else(|| error("GIF image descriptor is truncated"))?;
+                let (left, top) = (u16_at(descriptor, 0), u16_at(descriptor, 2));
+                let (frame_width, frame_height) = (u16_at(descriptor, 4), u16_at(descriptor, 6));
+                let frame_flags = descriptor[8];
+                offset += 9;
+                let local_colors;
+                let colors = if frame_flags & 0x80 != 0 {
+                    let size = 2 << (frame_flags & 0x07);
+                    local_colors = read_color_table(data, offset, size)?;
+                    offset += size * 3;
+                    &local_colors
+                } else {
+                    &global_colors
+                };
+                let min_code_size = *data
+                    .get(offset)
+                    .ok_or_else(|| error("GIF data is truncated"))?
+                    as u32;
+                let (compressed, next) = read_sub_blocks(data, offset + 1)?;
+                offset = next;
+                let indices = lzw_decode(&compressed, min_code_size, frame_width * frame_height)?;
+
+                let previous = (control.disposal == 3).then(|| canvas.clone());
+                let rows = if frame_flags & 0x40 != 0 {
+                    interlaced_rows(frame_height)
+                } else {
+                    (0..frame_height).collect()
+                };
+                for (i, index) in indices.iter().enumerate() {
+                    if Some(*index) == control.transparent {
+                        continue;
+                    }
+                    let Some(color) = colors.get(*index as usize) else {
+                        continue;
+                    };
+                    let (x, y) = (i % frame_width, rows[i / frame_width]);
+                    canvas.set_pixel(left + x, top + y, *color);
+                }
+                frames.push(Frame {
+                    bitmap: canvas.clone(),
+                    delay: control.delay,
+                });
+
+                // 次のコマの前にこのコマの範囲を片付ける
+                match (control.disposal, previous) {
+                    (2, _) => {
+                        for y in top..top + frame_height {
+                            for x in left..left + frame_width {
+                                canvas.set_pixel(x, y, Color::TRANSPARENT);
+                            }
+                        }
+                    }
+                    (3, Some(previous)) => canvas = previous,
+                    _ => {}
+                }
+                control = GraphicControl::default();
+            }
+            0x3b => break,
+            _ => return Err(error("invalid GIF block")),
+        }
+    }
+
+    if frames.is_empty() {
+        return Err(error("GIF image has no frames"));
+    }
+    if frames.len() == 1 {
+        frames[0].delay = 0;
+    }
+    Ok(Image::new(frames, loop_count))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_animated_gif() {
+        // 2x2の2コマのアニメーション。1コマ目は100ミリ秒で、色0を透明にして消去方法2で片付ける。
+        // 2コマ目は50ミリ秒で、右下の1画素だけを描く
+        let gif = [
+            0x47, 0x49, 0x46, 0x38, 0x39, 0x61, 0x02, 0x00, 0x02, 0x00, 0x81, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0xff, 0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0x21, 0xff, 0x0b,
+            0x4e, 0x45, 0x54, 0x53, 0x43, 0x41, 0x50, 0x45, 0x32, 0x2e, 0x30, 0x03, 0x01, 0x00,
+            0x00, 0x00, 0x21, 0xf9, 0x04, 0x09, 0x0a, 0x00, 0x00, 0x00, 0x2c, 0x00, 0x00, 0x00,
+            0x00, 0x02, 0x00, 0x02, 0x00, 0x00, 0x02, 0x03, 0x4c, 0x04, 0x05, 0x00, 0x21, 0xf9,
+            0x04, 0x04, 0x05, 0x00, 0x00, 0x00, 0x2c, 0x01, 0x00, 0x01, 0x00, 0x01, 0x00, 0x01,
+            0x00, 0x00, 0x02, 0x02, 0x5c, 0x01, 0x00, 0x3b,
+        ];
+        assert_eq!(Some((2, 2)), gif_size(&gif));
+        let image = decode_gif(&gif).expect("failed to decode");
+        assert!(image.is_animated());
+        assert_eq!(0, image.loop_count());
+        assert_eq!(100, image.frames()[0].delay);
+        assert_eq!(50, image.frames()[1].delay);
+
+        let first = &image.frames()[0].bitmap;
+        assert_eq!(Color::rgb(255, 0, 0), first.pixel(0, 0));
+        assert_eq!(Color::rgb(255, 0, 0), first.pixel(1, 0));
+        assert_eq!(Color::rgb(0, 0, 255), first.pixel(0, 1));
+        assert_eq!(Color::TRANSPARENT, first.pixel(1, 1));
+
+        let second = &image.frames()[1].bitmap;
+        assert_eq!(Color::TRANSPARENT, second.pixel(0, 0));
+        assert_eq!(Color::rgb(255, 255, 255), second.pixel(1, 1));
+
+        assert_eq!(first, image.frame_at(99));
+        assert_eq!(second, image.frame_at(120));
+        assert_eq!(first, image.frame_at(160));
+    }
+
+    #[test]
+    fn test_truncated_header() {
+        assert_eq!(None, gif_size(b"GIF89a\x01\x00\x01\x00"));
+        assert!(decode_gif(b"GIF89a\x01\x00\x01\x00").is_err());
+        assert!(decode_gif(b"GIF89a\x01\x00\x01\x00\x80\x00").is_err());
+        // 論理画面記述子だけでも1x1の画像になる
+        assert_eq!(
+            Some((1, 1)),
+            gif_size(b"GIF89a\x01\x00\x01\x00\x00\x00\x00")
+        );
+    }
+}
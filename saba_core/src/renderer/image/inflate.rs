@@ -0,0 +1,290 @@
+use crate::{error::Error, renderer::paint::encoder::adler32};
+use alloc::{string::ToString, vec, vec::Vec};
+
+// 長さと距離の符号に続く追加ビットの数と基準値
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+// 符号長の符号の長さが並ぶ順
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+fn error(message: &str) -> Error {
+    Error::UnexpectedInput(message.to_string())
+}
+
+// 下位のビットから読むビット列
+pub struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    bit: u32,
+}
+
+impl<'a> BitReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            position: 0,
+            bit: 0,
+        }
+    }
+
+    pub fn bits(&mut self, count: u32) -> Result<u32, Error> {
+        let mut value = 0;
+        for i in 0..count {
+            let byte = *self
+                .data
+                .get(self.position)
+                .ok_or_else(|| error("compressed data is truncated"))?;
+            value |= ((byte >> self.bit) as u32 & 1) << i;
+            self.bit += 1;
+            if self.bit == 8 {
+                self.bit = 0;
+                self.position += 1;
+            }
+        }
+        Ok(value)
+    }
+
+    fn align_to_byte(&mut self) {
+        if self.bit > 0 {
+            self.bit = 0;
+            self.position += 1;
+        }
+    }
+}
+
+// 符号長から作るハフマン符号。長さごとの符号の数と、符号順に並べた記号を持つ
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Result<Self, Error> {
+        let mut counts = [0u16; 16];
+        for length in lengths {
+            counts[*length as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut offsets = [0u16; 16];
+        for i in 1..15 {
+            offsets[i + 1] = offsets[i] + counts[i];
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, length) in lengths.iter().enumerate() {
+            if *length != 0 {
+                symbols[offsets[*length as usize] as usize] = symbol as u16;
+                offsets[*length as usize] += 1;
+            }
+        }
+        Ok(Self { counts, symbols })
+    }
+
+    // 符号は上位のビットから1ビットずつ読む
+    fn decode(&self, reader: &mut BitReader) -> Result<u16, Error> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for length in 1..16 {
+            code |= reader.bits(1)? as i32;
+            let count = self.counts[length] as i32;
+            if code - first < count {
+                return self
+                    .symbols
+                    .get((index + code - first) as usize)
+                    .copied()
+                    .ok_or_else(|| error("invalid Huffman code"));
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(error("invalid Huffman code"))
+    }
+}
+
+fn fixed_tables() -> Result<(Huffman, Huffman), Error> {
+    let mut lengths = [0u8; 288];
+    for (i, length) in lengths.iter_mut().enumerate() {
+        *length = match i {
+            0..=143 => 8,
+            144..=255 => 9,
+            256..=279 => 7,
+            _ => 8,
+        };
+    }
+    Ok((Huffman::new(&lengths)?, Huffman::new(&[5; 30])?))
+}
+
+fn dynamic_tables(reader: &mut BitReader) -> Result<(Huffman, Huffman), Error> {
+    let literals = reader.bits(5)? as usize + 257;
+    let distances = reader.bits(5)? as usize + 1;
+    let code_lengths = reader.bits(4)? as usize + 4;
+    let mut lengths = [0u8; 19];
+    for i in CODE_LENGTH_ORDER.iter().take(code_lengths) {
+        lengths[*i] = reader.bits(3)? as u8;
+    }
+    let code_length_huffman = Huffman::new(&lengths)?;
+
+    let mut lengths = Vec::with_capacity(literals + distances);
+    while lengths.len() < literals + distances {
+        let symbol = code_length_huffman.decode(reader)?;
+        let (value, repeat) = match symbol {
+            0..=15 => (symbol as u8, 1),
+            16 => {
+                let previous = *lengths
+                    .last()
+                    .ok_or_else(|| error("no code length to repeat"))?;
+                (previous, 3 + reader.bits(2)?)
+            }
+            17 => (0, 3 + reader.bits(3)?),
+            _ => (0, 11 + reader.bits(7)?),
+        };
+        for _ in 0..repeat {
+            lengths.push(value);
+        }
+    }
+    if lengths.len() > literals + distances {
+        return Err(error("too many code lengths"));
+    }
+    Ok((
+        Huffman::new(&lengths[..literals])?,
+        Huffman::new(&lengths[literals..])?,
+    ))
+}
+
+// DEFLATE形式の圧縮データを展開する
+pub fn inflate(data: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut reader = BitReader::new(data);
+    let mut output = Vec::new();
+    loop {
+        let last = reader.bits(1)? == 1;
+        match reader.bits(2)? {
+            0 => {
+                reader.align_to_byte();
+                let start = reader.position;
+                let header = data
+                    .get(start..start + 4)
+                    .ok_or_else(|| error("stored block is truncated"))?;
+                let length = u16::from_le_bytes([header[0], header[1]]) as usize;
+                let block = data
+                    .get(start + 4..start + 4 + length)
+                    .ok_or_else(|| error("stored block is truncated"))?;
+                output.extend_from_slice(block);
+                reader.position = start + 4 + length;
+            }
+            kind @ (1 | 2) => {
+                let (literal, distance) = if kind == 1 {
+                    fixed_tables()?
+                } else {
+                    dynamic_tables(&mut reader)?
+                };
+                loop {
+                    let symbol = literal.decode(&mut reader)? as usize;
+                    if symbol < 256 {
+                        output.push(symbol as u8);
+                        continue;
+                    }
+                    if symbol == 256 {
+                        break;
+                    }
+                    let index = symbol - 257;
+                    if index >= LENGTH_BASE.len() {
+                        return Err(error("invalid length code"));
+                    }
+                    let length = LENGTH_BASE[index] as usize
+                        + reader.bits(LENGTH_EXTRA[index] as u32)? as usize;
+                    let index = distance.decode(&mut reader)? as usize;
+                    if index >= DISTANCE_BASE.len() {
+                        return Err(error("invalid distance code"));
+                    }
+                    let distance = DISTANCE_BASE[index] as usize
+                        + reader.bits(DISTANCE_EXTRA[index] as u32)? as usize;
+                    if distance > output.len() {
+                        return Err(error("distance is too far back"));
+                    }
+                    // 重なる範囲のコピーは1バイトずつ行う
+                    let start = output.len() - distance;
+                    for i in 0..length {
+                        output.push(output[start + i]);
+                    }
+                }
+            }
+            _ => return Err(error("invalid block type")),
+        }
+        if last {
+            return Ok(output);
+        }
+    }
+}
+
+// zlib形式のヘッダとAdler-32のチェックサムを確かめて展開する
+pub fn zlib_decompress(data: &[u8]) -> Result<Vec<u8>, Error> {
+    if data.len() < 6 {
+        return Err(error("zlib stream is truncated"));
+    }
+    let (method, flags) = (data[0], data[1]);
+    if method & 0x0f != 8 || ((method as u16) << 8 | flags as u16) % 31 != 0 || flags & 0x20 != 0 {
+        return Err(error("invalid zlib header"));
+    }
+    let output = inflate(&data[2..])?;
+    let checksum = &data[data.len() - 4..];
+    if u32::from_be_bytes([checksum[0], checksum[1], checksum[2], checksum[3]]) != adler32(&output)
+    {
+        return Err(error("zlib checksum mismatch"));
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zlib_decompress() {
+        // 固定ハフマン符号
+        let fixed = [
+            0x78, 0xda, 0x4b, 0x4c, 0x2a, 0x4a, 0x4c, 0x4e, 0x4c, 0x49, 0x04, 0x52, 0x0a, 0x89,
+            0xd8, 0xd9, 0x00, 0xee, 0x28, 0x0d, 0x3d,
+        ];
+        assert_eq!(
+            b"abracadabra abracadabra abracadabra".to_vec(),
+            zlib_decompress(&fixed).expect("failed to decompress")
+        );
+        // 動的ハフマン符号
+        let dynamic = [
+            0x78, 0xda, 0x1d, 0x8a, 0xc1, 0x11, 0x00, 0x00, 0x0c, 0xc1, 0x66, 0x0d, 0xf6, 0x9f,
+            0xa1, 0xd4, 0x47, 0x72, 0xa0, 0xd8, 0x80, 0x2c, 0x89, 0x92, 0xc8, 0xcc, 0xb8, 0xea,
+            0xea, 0xd2, 0x69, 0x34, 0xfd, 0x1f, 0x0f, 0xad, 0x1c, 0x1b, 0x9f, 0x18, 0x74,
+        ];
+        assert_eq!(
+            b"abdccaaabcbbbacaabadaabccacbaccabaaaaabbaabaacabacbbbaaabacbaaad".to_vec(),
+            zlib_decompress(&dynamic).expect("failed to decompress")
+        );
+        // 無圧縮のブロック
+        let stored = [
+            0x78, 0x01, 0x01, 0x05, 0x00, 0xfa, 0xff, 0x68, 0x65, 0x6c, 0x6c, 0x6f, 0x06, 0x2c,
+            0x02, 0x15,
+        ];
+        assert_eq!(
+            b"hello".to_vec(),
+            zlib_decompress(&stored).expect("failed to decompress")
+        );
+
+        let mut broken = stored;
+        broken[15] ^= 1;
+        assert!(zlib_decompress(&broken).is_err());
+    }
+}
@@ -0,0 +1,892 @@
+use crate::{
+    error::Error,
+    renderer::{css::value::Color, image::bitmap::Bitmap},
+    utils::{cos, sqrt},
+};
+use alloc::{format, string::ToString, vec, vec::Vec};
+use core::f64::consts::PI;
+
+// ジグザグ順のk番目の係数が置かれる、8x8ブロック内の位置
+const ZIGZAG: [usize; 64] = [
+    0, 1, 8, 16, 9, 2, 3, 10, 17, 24, 32, 25, 18, 11, 4, 5, 12, 19, 26, 33, 40, 48, 41, 34, 27, 20,
+    13, 6, 7, 14, 21, 28, 35, 42, 49, 56, 57, 50, 43, 36, 29, 22, 15, 23, 30, 37, 44, 51, 58, 59,
+    52, 45, 38, 31, 39, 46, 53, 60, 61, 54, 47, 55, 62, 63,
+];
+
+const SOF0: u8 = 0xc0;
+const SOF1: u8 = 0xc1;
+const SOF2: u8 = 0xc2;
+const DHT: u8 = 0xc4;
+const SOI: u8 = 0xd8;
+const EOI: u8 = 0xd9;
+const SOS: u8 = 0xda;
+const DQT: u8 = 0xdb;
+const DRI: u8 = 0xdd;
+const APP14: u8 = 0xee;
+
+// 8ビットの標本で係数の差分と係数が取りうる最大のビット数、逐次近似で落とす最大のビット数
+const MAX_DC_SIZE: u32 = 11;
+const MAX_AC_SIZE: u32 = 10;
+const MAX_APPROXIMATION: u8 = 13;
+
+fn error(message: &str) -> Error {
+    Error::UnexpectedInput(message.to_string())
+}
+
+fn u16_at(data: &[u8], offset: usize) -> Result<usize, Error> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]) as usize)
+        .ok_or_else(|| error("JPEG data is truncated"))
+}
+
+// SOFセグメントに書かれた画像の大きさ。スキャンを展開せずに分かる
+pub fn jpeg_size(data: &[u8]) -> Option<(usize, usize)> {
+    if data.get(..2) != Some(&[0xff, SOI]) {
+        return None;
+    }
+    let mut offset = 2;
+    while offset + 4 <= data.len() {
+        if data[offset] != 0xff {
+            return None;
+        }
+        let marker = data[offset + 1];
+        // 連続した0xffは詰め物
+        if marker == 0xff {
+            offset += 1;
+            continue;
+        }
+        let length = u16_at(data, offset + 2).ok()?;
+        if matches!(marker, 0xc0..=0xcf) && !matches!(marker, DHT | 0xc8 | 0xcc) {
+            let height = u16_at(data, offset + 5).ok()?;
+            let width = u16_at(data, offset + 7).ok()?;
+            return Some((width, height));
+        }
+        offset += 2 + length;
+    }
+    None
+}
+
+// 符号の長さごとの最小と最大の符号から記号を引くハフマン表
+#[derive(Clone, Default)]
+struct Huffman {
+    max_code: [i32; 18],
+    offsets: [i32; 17],
+    symbols: Vec<u8>,
+}
+
+impl Huffman {
+    fn new(counts: &[u8], symbols: &[u8]) -> Self {
+        let mut huffman = Self {
+            max_code: [-1; 18],
+            offsets: [0; 17],
+            symbols: symbols.to_vec(),
+        };
+        let (mut code, mut index) = (0i32, 0i32);
+        for length in 1..=16 {
+            let count = counts[length - 1] as i32;
+            // 符号から記号の位置を求めるための差
+            huffman.offsets[length] = index - code;
+            if count > 0 {
+                code += count;
+                index += count;
+                huffman.max_code[length] = code - 1;
+            }
+            code <<= 1;
+        }
+        // 番兵
+        huffman.max_code[17] = i32::MAX;
+        huffman
+    }
+}
+
+// 上位のビットから読み、0xffの後の詰め物の0を取り除くビット列
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+    bits: u32,
+    count: u32,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8], position: usize) -> Self {
+        Self {
+            data,
+            position,
+            bits: 0,
+            count: 0,
+        }
+    }
+
+    // マーカーに達したら0を返し続ける
+    fn bit(&mut self) -> u32 {
+        if self.count == 0 {
+            let byte = self.data.get(self.position).copied().unwrap_or(0);
+            if byte == 0xff {
+                match self.data.get(self.position + 1) {
+                    Some(0) => self.position += 2,
+                    _ => {
+                        self.bits = 0;
+                        self.count = 8;
+                        return 0;
+                    }
+                }
+            } else {
+                self.position += 1;
+            }
+            self.bits = byte as u32;
+            self.count = 8;
+        }
+        self.count -= 1;
+        (self.bits >> self.count) & 1
+    }
+
+    fn receive(&mut self, length: u32) -> i32 {
+        let mut value = 0;
+        for _ in 0..length {
+            value = (value << 1) | self.bit() as i32;
+        }
+        value
+    }
+
+    // 上位のビットが0なら負の値を表す。ビット数がmaxを超えるのは壊れたハフマン表による
+    fn receive_extend(&mut self, length: u32, max: u32) -> Result<i32, Error> {
+        if length > max {
+            return Err(error("invalid JPEG coefficient size"));
+        }
+        if length == 0 {
+            return Ok(0);
+        }
+        let value = self.receive(length);
+        Ok(if value < 1 << (length - 1) {
+            value - (1 << length) + 1
+        } else {
+            value
+        })
+    }
+
+    fn decode(&mut self, huffman: &Huffman) -> Result<u8, Error> {
+        let mut code = 0i32;
+        for length in 1..=16 {
+            code = (code << 1) | self.bit() as i32;
+            if code <= huffman.max_code[length] {
+                return huffman
+                    .symbols
+                    .get((code + huffman.offsets[length]) as usize)
+                    .copied()
+                    .ok_or_else(|| error("invalid JPEG Huffman code"));
+            }
+        }
+        Err(error("invalid JPEG Huffman code"))
+    }
+
+    // 残りのビットを捨ててRSTマーカーの後に進む
+    fn restart(&mut self) {
+        self.count = 0;
+        while self.position + 1 < self.data.len() {
+            if self.data[self.position] == 0xff
+                && (0xd0..=0xd7).contains(&self.data[self.position + 1])
+            {
+                self.position += 2;
+                return;
+            }
+            self.position += 1;
+        }
+    }
+
+    // スキャンの後にある次のマーカーの位置
+    fn next_marker(&self) -> usize {
+        let mut position = self.position;
+        while position + 1 < self.data.len() {
+            let next = self.data[position + 1];
+            if self.data[position] == 0xff && next != 0 && !(0xd0..=0xd7).contains(&next) {
+                return position;
+            }
+            position += 1;
+        }
+        self.data.len()
+    }
+}
+
+#[derive(Clone, Default)]
+struct Component {
+    id: u8,
+    h: usize,
+    v: usize,
+    quantization: usize,
+    // MCUの大きさに揃えたブロックの数
+    blocks_per_line: usize,
+    blocks_per_column: usize,
+    // 画像の大きさに必要なブロックの数。1つの成分だけのスキャンはこの範囲を読む
+    used_blocks_per_line: usize,
+    used_blocks_per_column: usize,
+    coefficients: Vec<i32>,
+    dc_table: usize,
+    ac_table: usize,
+    prediction: i32,
+}
+
+impl Component {
+    fn block(&mut self, row: usize, column: usize) -> &mut [i32] {
+        let start = (row * self.blocks_per_line + column) * 64;
+        &mut self.coefficients[start..start + 64]
+    }
+}
+
+struct Scan {
+    components: Vec<usize>,
+    start: usize,
+    end: usize,
+    high: u32,
+    low: u32,
+}
+
+struct Decoder {
+    width: usize,
+    height: usize,
+    progressive: bool,
+    components: Vec<Component>,
+    max_h: usize,
+    max_v: usize,
+    mcus_per_line: usize,
+    mcus_per_column: usize,
+    quantization: [[u16; 64]; 4],
+    dc_tables: [Huffman; 4],
+    ac_tables: [Huffman; 4],
+    restart_interval: usize,
+    // プログレッシブのAC係数で、残りのブロックがすべて0であることを表す数
+    end_of_band_run: u32,
+    adobe_transform: Option<u8>,
+}
+
+impl Decoder {
+    fn new() -> Self {
+        Self {
+            width: 0,
+            height: 0,
+            progressive: false,
+            components: Vec::new(),
+            max_h: 1,
+            max_v: 1,
+            mcus_per_line: 0,
+            mcus_per_column: 0,
+            quantization: [[0; 64]; 4],
+            dc_tables: Default::default(),
+            ac_tables: Default::default(),
+            restart_interval: 0,
+            end_of_band_run: 0,
+            adobe_transform: None,
+        }
+    }
+
+    fn read_frame(&mut self, segment: &[u8]) -> Result<(), Error> {
+        if segment.len() < 6 || segment[0] != 8 {
+            return Err(error("only 8-bit JPEG images are supported"));
+        }
+        self.height = u16_at(segment, 1)?;
+        self.width = u16_at(segment, 3)?;
+        let count = segment[5] as usize;
+        for i in 0..count {
+            let c = segment
+                .get(6 + i * 3..9 + i * 3)
+                .ok_or_else(|| error("JPEG frame header is truncated"))?;
+            let (h, v) = ((c[1] >> 4) as usize, (c[1] & 0x0f) as usize);
+            if !(1..=4).contains(&h) || !(1..=4).contains(&v) {
+                return Err(error("invalid JPEG sampling factor"));
+            }
+            self.components.push(Component {
+                id: c[0],
+                h,
+                v,
+                quantization: (c[2] & 0x03) as usize,
+                ..Default::default()
+            });
+        }
+        if self.width == 0 || self.height == 0 || !(1..=4).contains(&count) {
+            return Err(error("invalid JPEG frame header"));
+        }
+        Bitmap::try_new(self.width, self.height)?;
+
+        self.max_h = self.components.iter().map(|c| c.h).max().unwrap_or(1);
+        self.max_v = self.components.iter().map(|c| c.v).max().unwrap_or(1);
+        self.mcus_per_line = self.width.div_ceil(8 * self.max_h);
+        self.mcus_per_column = self.height.div_ceil(8 * self.max_v);
+        for component in &mut self.components {
+            let width = (self.width * component.h).div_ceil(self.max_h);
+            let height = (self.height * component.v).div_ceil(self.max_v);
+            component.used_blocks_per_line = width.div_ceil(8);
+            component.used_blocks_per_column = height.div_ceil(8);
+            component.blocks_per_line = self.mcus_per_line * component.h;
+            component.blocks_per_column = self.mcus_per_column * component.v;
+            component.coefficients =
+                vec![0; component.blocks_per_line * component.blocks_per_column * 64];
+        }
+        Ok(())
+    }
+
+    fn read_huffman_tables(&mut self, mut segment: &[u8]) -> Result<(), Error> {
+        while segment.len() >= 17 {
+            let (class, id) = (segment[0] >> 4, (segment[0] & 0x03) as usize);
+            let counts = &segment[1..17];
+            let total: usize = counts.iter().map(|c| *c as usize).sum();
+            let symbols = segment
+                .get(17..17 + total)
+                .ok_or_else(|| error("JPEG Huffman table is truncated"))?;
+            let table = Huffman::new(counts, symbols);
+            if class == 0 {
+                self.dc_tables[id] = table;
+            } else {
+                self.ac_tables[id] = table;
+            }
+            segment = &segment[17 + total..];
+        }
+        Ok(())
+    }
+
+    fn read_quantization_tables(&mut self, mut segment: &[u8]) -> Result<(), Error> {
+        while !segment.is_empty() {
+            let (precision, id) = (segment[0] >> 4, (segment[0] & 0x03) as usize);
+            let size = if precision == 0 { 64 } else { 128 };
+            let values = segment
+                .get(1..1 + size)
+                .ok_or_else(|| error("JPEG quantization table is truncated"))?;
+            // 表はジグザグ順に並んでいる
+            for (k, position) in ZIGZAG.iter().enumerate() {
+                self.quantization[id][*position] = if precision == 0 {
+                    values[k] as u16
+                } else {
+                    u16::from_be_bytes([values[k * 2], values[k * 2 + 1]])
+                };
+            }
+            segment = &segment[1 + size..];
+        }
+        Ok(())
+    }
+
+    // スキャンの成分と係数の範囲を読み、成分が使うハフマン表を設定する
+    fn read_scan(&mut self, segment: &[u8]) -> Result<Scan, Error> {
+        let count = *segment
+            .first()
+            .ok_or_else(|| error("JPEG scan header is truncated"))? as usize;
+        let header = segment
+            .get(1..1 + count * 2 + 3)
+            .ok_or_else(|| error("JPEG scan header is truncated"))?;
+        let mut components = Vec::new();
+        for i in 0..count {
+            let index = self
+                .components
+                .iter()
+                .position(|c| c.id == header[i * 2])
+                .ok_or_else(|| error("JPEG scan refers to an unknown component"))?;
+            let tables = header[i * 2 + 1];
+            self.components[index].dc_table = (tables >> 4) as usize & 0x03;
+            self.components[index].ac_table = (tables & 0x03) as usize;
+            components.push(index);
+        }
+        let rest = &header[count * 2..];
+        // 逐次近似で落とすビット数は13まで
+        if rest[2] >> 4 > MAX_APPROXIMATION || rest[2] & 0x0f > MAX_APPROXIMATION {
+            return Err(error("invalid JPEG successive approximation"));
+        }
+        Ok(Scan {
+            components,
+            start: rest[0] as usize,
+            end: (rest[1] as usize).min(63),
+            high: (rest[2] >> 4) as u32,
+            low: (rest[2] & 0x0f) as u32,
+        })
+    }
+
+    fn decode_block(
+        &mut self,
+        reader: &mut Reader,
+        scan: &Scan,
+        c: usize,
+        row: usize,
+        column: usize,
+    ) -> Result<(), Error> {
+        let component = &mut self.components[c];
+        let dc = &self.dc_tables[component.dc_table];
+        let ac = &self.ac_tables[component.ac_table];
+        let low = scan.low;
+        let mut prediction = component.prediction;
+        let block = component.block(row, column);
+
+        if !self.progressive {
+            let length = reader.decode(dc)? as u32;
+            prediction += reader.receive_extend(length, MAX_DC_SIZE)?;
+            block[0] = prediction;
+            let mut k = 1;
+            while k < 64 {
+                let rs = reader.decode(ac)?;
+                let (r, s) = ((rs >> 4) as usize, (rs & 0x0f) as u32);
+                if s == 0 {
+                    if r < 15 {
+                        break;
+                    }
+                    k += 16;
+                    continue;
+                }
+                k += r;
+                if k > 63 {
+                    break;
+                }
+                block[ZIGZAG[k]] = reader.receive_extend(s, MAX_AC_SIZE)?;
+                k += 1;
+            }
+        } else if scan.start == 0 {
+            // DC係数の最初のスキャンと、1ビットずつ精度を上げるスキャン
+            if scan.high == 0 {
+                let length = reader.decode(dc)? as u32;
+                prediction += reader.receive_extend(length, MAX_DC_SIZE)?;
+                block[0] = prediction << low;
+            } else if reader.bit() == 1 {
+                block[0] |= 1 << low;
+            }
+        } else if scan.high == 0 {
+            if self.end_of_band_run > 0 {
+                self.end_of_band_run -= 1;
+            } else {
+                let mut k = scan.start;
+                while k <= scan.end {
+                    let rs = reader.decode(ac)?;
+                    let (r, s) = ((rs >> 4) as u32, (rs & 0x0f) as u32);
+                    if s == 0 {
+                        if r < 15 {
+                            self.end_of_band_run = (1 << r) - 1;
+                            if r > 0 {
+                                self.end_of_band_run += reader.receive(r) as u32;
+                            }
+                            break;
+                        }
+                        k += 16;
+                        continue;
+                    }
+                    k += r as usize;
+                    if k > 63 {
+                        break;
+                    }
+                    block[ZIGZAG[k]] = reader.receive_extend(s, MAX_AC_SIZE)? * (1 << low);
+                    k += 1;
+                }
+            }
+        } else {
+            refine_ac(reader, ac, block, scan, &mut self.end_of_band_run)?;
+        }
+        self.components[c].prediction = prediction;
+        Ok(())
+    }
+
+    fn decode_scan(&mut self, data: &[u8], position: usize, scan: &Scan) -> Result<usize, Error> {
+        let mut reader = Reader::new(data, position);
+        self.end_of_band_run = 0;
+        for c in &scan.components {
+            self.components[*c].prediction = 0;
+        }
+        // 1つの成分だけのスキャンはMCUではなくブロックごとに並ぶ
+        let single = scan.components.len() == 1;
+        let (columns, rows) = if single {
+            let component = &self.components[scan.components[0]];
+            (
+                component.used_blocks_per_line,
+                component.used_blocks_per_column,
+            )
+        } else {
+            (self.mcus_per_line, self.mcus_per_column)
+        };
+        let total = columns * rows;
+        for unit in 0..total {
+            if self.restart_interval > 0 && unit > 0 && unit % self.restart_interval == 0 {
+                reader.restart();
+                self.end_of_band_run = 0;
+                for c in &scan.components {
+                    self.components[*c].prediction = 0;
+                }
+            }
+            let (row, column) = (unit / columns, unit % columns);
+            if single {
+                self.decode_block(&mut reader, scan, scan.components[0], row, column)?;
+                continue;
+            }
+            for c in &scan.components {
+                let (h, v) = (self.components[*c].h, self.components[*c].v);
+                for y in 0..v {
+                    for x in 0..h {
+                        self.decode_block(&mut reader, scan, *c, row * v + y, column * h + x)?;
+                    }
+                }
+            }
+        }
+        Ok(reader.next_marker())
+    }
+
+    // 逆量子化と逆DCTで成分ごとの画素の平面にする
+    fn component_planes(&self) -> Vec<(usize, Vec<u8>)> {
+        let mut table = [[0.0; 8]; 8];
+        for (x, row) in table.iter_mut().enumerate() {
+            for (u, value) in row.iter_mut().enumerate() {
+                let scale = if u == 0 { 1.0 / sqrt(2.0) } else { 1.0 };
+                *value = scale * cos((2 * x + 1) as f64 * u as f64 * PI / 16.0) / 2.0;
+            }
+        }
+        self.components
+            .iter()
+            .map(|component| {
+                let width = component.blocks_per_line * 8;
+                let mut plane = vec![0u8; width * component.blocks_per_column * 8];
+                let quantization = &self.quantization[component.quantization];
+                for (index, block) in component.coefficients.chunks_exact(64).enumerate() {
+                    let (row, column) = (
+                        index / component.blocks_per_line,
+                        index % component.blocks_per_line,
+                    );
+                    let mut coefficients = [0.0; 64];
+                    for i in 0..64 {
+                        coefficients[i] = block[i] as f64 * quantization[i] as f64;
+                    }
+                    // 行と列に分けて1次元の逆DCTを行う
+                    let mut temporary = [0.0; 64];
+                    for v in 0..8 {
+                        for x in 0..8 {
+                            temporary[v * 8 + x] =
+                                (0..8).map(|u| table[x][u] * coefficients[v * 8 + u]).sum();
+                        }
+                    }
+                    for y in 0..8 {
+                        for x in 0..8 {
+                            let value: f64 =
+                                (0..8).map(|v| table[y][v] * temporary[v * 8 + x]).sum();
+                            let sample = (value + 128.5).clamp(0.0, 255.0) as u8;
+                            plane[(row * 8 + y) * width + column * 8 + x] = sample;
+                        }
+                    }
+                }
+                (width, plane)
+            })
+            .collect()
+    }
+
+    fn to_bitmap(&self) -> Result<Bitmap, Error> {
+        let planes = self.component_planes();
+        let mut bitmap = Bitmap::try_new(self.width, self.height)?;
+        // 間引かれた成分は近い画素の値を使う
+        let sample = |c: usize, x: usize, y: usize| -> f64 {
+            let component = &self.components[c];
+            let (width, plane) = &planes[c];
+            let sx = x * component.h / self.max_h;
+            let sy = y * component.v / self.max_v;
+            plane[sy * width + sx] as f64
+        };
+        let ycc_to_rgb = |y: f64, cb: f64, cr: f64| {
+            let clamp = |v: f64| (v + 0.5).clamp(0.0, 255.0) as u8;
+            (
+                clamp(y + 1.402 * (cr - 128.0)),
+                clamp(y - 0.344136 * (cb - 128.0) - 0.714136 * (cr - 128.0)),
+                clamp(y + 1.772 * (cb - 128.0)),
+            )
+        };
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let color = match self.components.len() {
+                    1 | 2 => {
+                        let gray = sample(0, x, y) as u8;
+                        Color::rgb(gray, gray, gray)
+                    }
+                    3 => {
+                        let (a, b, c) = (sample(0, x, y), sample(1, x, y), sample(2, x, y));
+                        // Adobeの変換指定が0ならRGBのまま
+                        if self.adobe_transform == Some(0) {
+                            Color::rgb(a as u8, b as u8, c as u8)
+                        } else {
+                            let (r, g, b) = ycc_to_rgb(a, b, c);
+                            Color::rgb(r, g, b)
+                        }
+                    }
+                    _ => {
+                        // AdobeのCMYKは反転して保存されている。変換指定が2ならYCCKから戻す
+                        let (c, m, ye) = if self.adobe_transform == Some(2) {
+                            let (r, g, b) =
+                                ycc_to_rgb(sample(0, x, y), sample(1, x, y), sample(2, x, y));
+                            (r as f64, g as f64, b as f64)
+                        } else {
+                            (sample(0, x, y), sample(1, x, y), sample(2, x, y))
+                        };
+                        let k = sample(3, x, y);
+                        Color::rgb(
+                            (c * k / 255.0) as u8,
+                            (m * k / 255.0) as u8,
+                            (ye * k / 255.0) as u8,
+                        )
+                    }
+                };
+                bitmap.set_pixel(x, y, color);
+            }
+        }
+        Ok(bitmap)
+    }
+}
+
+// プログレッシブのAC係数の精度を1ビット上げる。すでに0でない係数には補正のビットが続く
+fn refine_ac(
+    reader: &mut Reader,
+    ac: &Huffman,
+    block: &mut [i32],
+    scan: &Scan,
+    end_of_band_run: &mut u32,
+) -> Result<(), Error> {
+    let positive = 1 << scan.low;
+    let negative = -1 << scan.low;
+    let refine = |reader: &mut Reader, coefficient: &mut i32| {
+        if reader.bit() == 1 && *coefficient & positive == 0 {
+            *coefficient += if *coefficient >= 0 {
+                positive
+            } else {
+                negative
+            };
+        }
+    };
+    let mut k = scan.start;
+    if *end_of_band_run == 0 {
+        while k <= scan.end {
+            let rs = reader.decode(ac)?;
+            let (mut r, s) = ((rs >> 4) as i32, rs & 0x0f);
+            let mut value = 0;
+            if s != 0 {
+                value = if reader.bit() == 1 {
+                    positive
+                } else {
+                    negative
+                };
+            } else if r != 15 {
+                *end_of_band_run = 1 << r;
+                if r > 0 {
+                    *end_of_band_run += reader.receive(r as u32) as u32;
+                }
+                break;
+            }
+            // r個の0の係数を飛ばした位置に新しい係数を置く
+            while k <= scan.end {
+                let coefficient = &mut block[ZIGZAG[k]];
+                if *coefficient != 0 {
+                    refine(reader, coefficient);
+                } else {
+                    if r == 0 {
+                        break;
+                    }
+                    r -= 1;
+                }
+                k += 1;
+            }
+            if value != 0 && k <= scan.end {
+                block[ZIGZAG[k]] = value;
+            }
+            k += 1;
+        }
+    }
+    if *end_of_band_run > 0 {
+        while k <= scan.end {
+            let coefficient = &mut block[ZIGZAG[k]];
+            if *coefficient != 0 {
+                refine(reader, coefficient);
+            }
+            k += 1;
+        }
+        *end_of_band_run -= 1;
+    }
+    Ok(())
+}
+
+// ベースラインとプログレッシブのハフマン符号化されたJPEG
+pub fn decode_jpeg(data: &[u8]) -> Result<Bitmap, Error> {
+    if data.get(..2) != Some(&[0xff, SOI]) {
+        return Err(error("not a JPEG image"));
+    }
+    let mut decoder = Decoder::new();
+    let mut has_frame = false;
+    let mut offset = 2;
+    while offset + 1 < data.len() {
+        if data[offset] != 0xff {
+            return Err(error("invalid JPEG marker"));
+        }
+        let marker = data[offset + 1];
+        match marker {
+            0xff => {
+                offset += 1;
+                continue;
+            }
+            EOI => break,
+            0xd0..=0xd7 | 0x01 => {
+                offset += 2;
+                continue;
+            }
+            _ => {}
+        }
+        let length = u16_at(data, offset + 2)?;
+        let segment = data
+            .get(offset + 4..offset + 2 + length)
+            .ok_or_else(|| error("JPEG segment is truncated"))?;
+        offset += 2 + length;
+        match marker {
+            SOF0 | SOF1 | SOF2 => {
+                decoder.progressive = marker == SOF2;
+                decoder.read_frame(segment)?;
+                has_frame = true;
+            }
+            0xc3 | 0xc5..=0xc7 | 0xc9..=0xcb | 0xcd..=0xcf => {
+                return Err(Error::UnexpectedInput(format!(
+                    "unsupported JPEG process {:#x}",
+                    marker
+                )));
+            }
+            DHT => decoder.read_huffman_tables(segment)?,
+            DQT => decoder.read_quantization_tables(segment)?,
+            DRI => decoder.restart_interval = u16_at(segment, 0)?,
+            APP14 if segment.starts_with(b"Adobe") && segment.len() >= 12 => {
+                decoder.adobe_transform = Some(segment[11]);
+            }
+            SOS => {
+                if !has_frame {
+                    return Err(error("JPEG scan appears before the frame header"));
+                }
+                let scan = decoder.read_scan(segment)?;
+                offset = decoder.decode_scan(data, offset, &scan)?;
+            }
+            _ => {}
+        }
+    }
+    if !has_frame {
+        return Err(error("JPEG image has no frame header"));
+    }
+    decoder.to_bitmap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(expected: Color, actual: Color) {
+        let near = |a: u8, b: u8| (a as i32 - b as i32).abs() <= 2;
+        assert!(
+            near(expected.r, actual.r) && near(expected.g, actual.g) && near(expected.b, actual.b),
+            "expected {:?} but got {:?}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn test_decode_baseline() {
+        // 32x16で色差を2x2に間引き、MCUごとにリスタートマーカーを置いた画像。
+        // 左上の8x8は横に明るくなるグレー、右上は赤、左下は青、その右は白、右半分は緑
+        let jpeg = [
+            0xff, 0xd8, 0xff, 0xdb, 0x00, 0x43, 0x00, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01,
+            0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01,
+            0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01,
+            0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01,
+            0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01,
+            0x01, 0xff, 0xc0, 0x00, 0x11, 0x08, 0x00, 0x10, 0x00, 0x20, 0x03, 0x01, 0x22, 0x00,
+            0x02, 0x11, 0x00, 0x03, 0x11, 0x00, 0xff, 0xdd, 0x00, 0x04, 0x00, 0x01, 0xff, 0xc4,
+            0x00, 0x18, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x08, 0x09, 0x0a, 0x0b, 0xff, 0xc4, 0x00, 0x28,
+            0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x15, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x05, 0x06, 0x09, 0x0a, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18,
+            0x23, 0x27, 0x43, 0x44, 0x46, 0x47, 0x64, 0x66, 0x85, 0xc3, 0xff, 0xda, 0x00, 0x0c,
+            0x03, 0x01, 0x00, 0x02, 0x00, 0x03, 0x00, 0x00, 0x3f, 0x00, 0x01, 0x47, 0x04, 0x77,
+            0x03, 0xc6, 0x13, 0x70, 0xa3, 0x00, 0x01, 0x19, 0x00, 0x02, 0x43, 0x00, 0x02, 0x70,
+            0xf0, 0x00, 0x1a, 0x90, 0x39, 0xa8, 0x1b, 0x28, 0x29, 0xd0, 0x24, 0x90, 0x66, 0xd0,
+            0x8c, 0x42, 0x31, 0x09, 0x92, 0x20, 0xd8, 0x21, 0xe0, 0x77, 0x04, 0x3c, 0x10, 0x10,
+            0x97, 0xa0, 0x3c, 0x06, 0xb0, 0x6b, 0x07, 0xe0, 0x8a, 0x83, 0x38, 0x32, 0x86, 0xe0,
+            0x5c, 0x16, 0x80, 0x75, 0x80, 0xde, 0x40, 0x70, 0xd0, 0xa5, 0x00, 0x9b, 0xc1, 0x88,
+            0x42, 0x3e, 0x08, 0xf8, 0x20, 0x00, 0xff, 0x00, 0xc2, 0x16, 0x07, 0x48, 0x42, 0xc1,
+            0x1a, 0x89, 0x14, 0x05, 0x18, 0x37, 0x03, 0x70, 0x44, 0x62, 0x26, 0x0c, 0xa0, 0xc6,
+            0x1d, 0x00, 0xd0, 0x16, 0x3f, 0xff, 0xd0, 0x02, 0x77, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x01, 0x15, 0xc0, 0x00, 0xde, 0x70, 0x0f, 0xff, 0xd9,
+        ];
+        assert_eq!(Some((32, 16)), jpeg_size(&jpeg));
+        let bitmap = decode_jpeg(&jpeg).expect("failed to decode");
+        assert_eq!((32, 16), (bitmap.width(), bitmap.height()));
+        assert_near(Color::rgb(0, 0, 0), bitmap.pixel(0, 0));
+        assert_near(Color::rgb(210, 210, 210), bitmap.pixel(7, 3));
+        assert_near(Color::rgb(255, 0, 0), bitmap.pixel(12, 4));
+        assert_near(Color::rgb(0, 0, 255), bitmap.pixel(3, 12));
+        assert_near(Color::rgb(255, 255, 255), bitmap.pixel(15, 15));
+        assert_near(Color::rgb(0, 160, 0), bitmap.pixel(20, 5));
+        assert_near(Color::rgb(0, 160, 0), bitmap.pixel(31, 15));
+    }
+
+    #[test]
+    fn test_decode_progressive() {
+        // 16x8のグレースケール。DCとACを1ビット落として送り、後のスキャンで精度を上げる
+        let jpeg = [
+            0xff, 0xd8, 0xff, 0xdb, 0x00, 0x43, 0x00, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01,
+            0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01,
+            0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01,
+            0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01,
+            0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01,
+            0x01, 0xff, 0xc2, 0x00, 0x0b, 0x08, 0x00, 0x08, 0x00, 0x10, 0x01, 0x01, 0x11, 0x00,
+            0xff, 0xc4, 0x00, 0x15, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x08, 0x09, 0xff, 0xc4, 0x00, 0x14, 0x10,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0xff, 0xda, 0x00, 0x08, 0x01, 0x01, 0x00, 0x00, 0x00, 0x01, 0x00,
+            0x17, 0x01, 0xff, 0x00, 0x7f, 0xff, 0xc4, 0x00, 0x14, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff,
+            0xc4, 0x00, 0x20, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x0d, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x02, 0x04, 0x06, 0x08, 0x11, 0x21,
+            0x23, 0x34, 0x41, 0x53, 0x71, 0xff, 0xda, 0x00, 0x08, 0x01, 0x01, 0x00, 0x01, 0x3f,
+            0x01, 0x05, 0x6d, 0x04, 0x6c, 0x24, 0x02, 0x18, 0x5b, 0x0a, 0x06, 0x00, 0x01, 0x5c,
+            0x01, 0x1d, 0x01, 0x01, 0x00, 0x48, 0x19, 0x01, 0x40, 0x30, 0x20, 0x1c, 0x04, 0x80,
+            0x80, 0x80, 0x28, 0x06, 0x0e, 0x0a, 0x00, 0x80, 0x40, 0xd0, 0x18, 0x07, 0xff, 0xc4,
+            0x00, 0x14, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0xc4, 0x00, 0x14, 0x10, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0xff, 0xda, 0x00, 0x08, 0x01, 0x01, 0x00, 0x00, 0x00, 0x10, 0x3f, 0xff, 0xc4, 0x00,
+            0x14, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0xc4, 0x00, 0x1a, 0x10, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x07, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+            0x11, 0x41, 0x91, 0xb1, 0xf0, 0xff, 0xda, 0x00, 0x08, 0x01, 0x01, 0x00, 0x01, 0x3f,
+            0x10, 0x06, 0x48, 0x14, 0x00, 0x02, 0x65, 0xc0, 0x7c, 0x81, 0x80, 0x24, 0x03, 0x01,
+            0x00, 0x20, 0x18, 0x07, 0xff, 0xd9,
+        ];
+        let bitmap = decode_jpeg(&jpeg).expect("failed to decode");
+        for (x, y) in [(0, 0), (3, 2), (7, 7), (8, 0), (12, 5), (15, 7)] {
+            let gray = (x * 16 + y * 4).min(255) as u8;
+            assert_near(Color::rgb(gray, gray, gray), bitmap.pixel(x, y));
+        }
+    }
+
+    #[test]
+    fn test_corrupt_huffman_table() {
+        // 1x1のグレースケールで、DCの符号0に大きすぎるビット数を割り当てたハフマン表
+        let jpeg = |symbol: u8, scan: &[u8]| {
+            let mut data = vec![0xff, 0xd8, 0xff, 0xdb, 0x00, 0x43, 0x00];
+            data.extend([0x01; 64]);
+            data.extend([
+                0xff, 0xc0, 0x00, 0x0b, 0x08, 0x00, 0x01, 0x00, 0x01, 0x01, 0x01, 0x11, 0x00,
+            ]);
+            for class in [0x00, 0x10] {
+                data.extend([0xff, 0xc4, 0x00, 0x14, class, 0x01]);
+                data.extend([0x00; 15]);
+                data.push(symbol);
+            }
+            data.extend([0xff, 0xda, 0x00, 0x08, 0x01, 0x01, 0x00]);
+            data.extend(scan);
+            data.extend([0xff, 0xff, 0xff, 0xff, 0xff, 0xd9]);
+            data
+        };
+        assert!(decode_jpeg(&jpeg(0x00, &[0x00, 0x3f, 0x00])).is_ok());
+        for symbol in [0x0c, 0x20, 0x21, 0x3f, 0xff] {
+            assert!(decode_jpeg(&jpeg(symbol, &[0x00, 0x3f, 0x00])).is_err());
+        }
+        // 逐次近似のビット数が大きすぎるスキャン
+        assert!(decode_jpeg(&jpeg(0x00, &[0x00, 0x3f, 0x0f])).is_err());
+
+        // どのバイトを壊しても、失敗するだけでパニックしない
+        let valid = jpeg(0x00, &[0x00, 0x3f, 0x00]);
+        for i in 0..valid.len() {
+            for byte in [0x00, 0x0b, 0x20, 0x7f, 0xff] {
+                let mut data = valid.clone();
+                data[i] = byte;
+                let _ = decode_jpeg(&data);
+            }
+        }
+    }
+}
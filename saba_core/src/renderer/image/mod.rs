@@ -0,0 +1,8 @@
+pub mod bitmap;
+pub mod bmp;
+pub mod decoder;
+pub mod gif;
+pub mod inflate;
+pub mod jpeg;
+pub mod png;
+pub mod source;
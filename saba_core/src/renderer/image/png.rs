@@ -0,0 +1,370 @@
+use crate::{
+    error::Error,
+    renderer::{
+        css::value::Color,
+        image::{bitmap::Bitmap, inflate::zlib_decompress},
+        paint::encoder::{crc32, PNG_SIGNATURE},
+    },
+};
+use alloc::{format, string::ToString, vec, vec::Vec};
+
+// Adam7の各パスの開始位置と間隔(x, y, dx, dy)
+const ADAM7: [(usize, usize, usize, usize); 7] = [
+    (0, 0, 8, 8),
+    (4, 0, 8, 8),
+    (0, 4, 4, 8),
+    (2, 0, 4, 4),
+    (0, 2, 2, 4),
+    (1, 0, 2, 2),
+    (0, 1, 1, 2),
+];
+
+fn error(message: &str) -> Error {
+    Error::UnexpectedInput(message.to_string())
+}
+
+fn u32_at(data: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes([
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+    ])
+}
+
+// IHDRから読む画像の大きさ。画素のデータを展開せずに分かる
+pub fn png_size(data: &[u8]) -> Option<(usize, usize)> {
+    if data.len() < 24 || data[..8] != PNG_SIGNATURE || &data[12..16] != b"IHDR" {
+        return None;
+    }
+    Some((u32_at(data, 16) as usize, u32_at(data, 20) as usize))
+}
+
+struct Header {
+    width: usize,
+    height: usize,
+    bit_depth: u8,
+    color_type: u8,
+    interlaced: bool,
+}
+
+impl Header {
+    fn channels(&self) -> usize {
+        match self.color_type {
+            2 => 3,
+            4 => 2,
+            6 => 4,
+            _ => 1,
+        }
+    }
+
+    fn bits_per_pixel(&self) -> usize {
+        self.channels() * self.bit_depth as usize
+    }
+
+    // フィルタで左隣とみなす画素までのバイト数。1バイトに満たない場合は1
+    fn filter_distance(&self) -> usize {
+        self.bits_per_pixel().div_ceil(8)
+    }
+
+    fn row_bytes(&self, width: usize) -> usize {
+        (width * self.bits_per_pixel()).div_ceil(8)
+    }
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let pa = (p - a as i16).abs();
+    let pb = (p - b as i16).abs();
+    let pc = (p - c as i16).abs();
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+// 行ごとのフィルタを元に戻し、フィルタの種類を除いた行を並べて返す
+fn unfilter(data: &[u8], row_bytes: usize, rows: usize, distance: usize) -> Result<Vec<u8>, Error> {
+    let mut output = vec![0u8; row_bytes * rows];
+    for y in 0..rows {
+        let start = y * (row_bytes + 1);
+        let filter = *data
+            .get(start)
+            .ok_or_else(|| error("PNG image data is truncated"))?;
+        let line = data
+            .get(start + 1..start + 1 + row_bytes)
+            .ok_or_else(|| error("PNG image data is truncated"))?;
+        let (previous, current) = output.split_at_mut(y * row_bytes);
+        let previous = if y == 0 {
+            None
+        } else {
+            Some(&previous[(y - 1) * row_bytes..])
+        };
+        let current = &mut current[..row_bytes];
+        for x in 0..row_bytes {
+            let a = if x >= distance {
+                current[x - distance]
+            } else {
+                0
+            };
+            let b = previous.map_or(0, |p| p[x]);
+            let c = match previous {
+                Some(p) if x >= distance => p[x - distance],
+                _ => 0,
+            };
+            current[x] = line[x].wrapping_add(match filter {
+                0 => 0,
+                1 => a,
+                2 => b,
+                3 => ((a as u16 + b as u16) / 2) as u8,
+                4 => paeth(a, b, c),
+                _ => return Err(error("invalid PNG filter type")),
+            });
+        }
+    }
+    Ok(output)
+}
+
+struct Palette {
+    colors: Vec<Color>,
+    // tRNSで指定された透明にするグレーまたはRGBの値
+    transparent: Option<[u16; 3]>,
+}
+
+// 1つの画素の色。16ビットの値は上位8ビットを使う
+fn pixel_color(header: &Header, palette: &Palette, row: &[u8], x: usize) -> Result<Color, Error> {
+    let depth = header.bit_depth as usize;
+    let sample = |index: usize| -> u16 {
+        let bit = (x * header.channels() + index) * depth;
+        match depth {
+            16 => u16::from_be_bytes([row[bit / 8], row[bit / 8 + 1]]),
+            8 => row[bit / 8] as u16,
+            _ => (row[bit / 8] >> (8 - depth - bit % 8)) as u16 & ((1 << depth) - 1),
+        }
+    };
+    // 8ビットに揃える
+    let scale = |value: u16| -> u8 {
+        match depth {
+            16 => (value >> 8) as u8,
+            8 => value as u8,
+            _ => (value as u32 * 255 / ((1 << depth) - 1)) as u8,
+        }
+    };
+    let color = match header.color_type {
+        0 => {
+            let gray = sample(0);
+            let alpha = if palette.transparent == Some([gray, gray, gray]) {
+                0
+            } else {
+                255
+            };
+            let gray = scale(gray);
+            Color::rgba(gray, gray, gray, alpha)
+        }
+        2 => {
+            let rgb = [sample(0), sample(1), sample(2)];
+            let alpha = if palette.transparent == Some(rgb) {
+                0
+            } else {
+                255
+            };
+            Color::rgba(scale(rgb[0]), scale(rgb[1]), scale(rgb[2]), alpha)
+        }
+        3 => *palette
+            .colors
+            .get(sample(0) as usize)
+            .ok_or_else(|| error("PNG palette index is out of range"))?,
+        4 => {
+            let gray = scale(sample(0));
+            Color::rgba(gray, gray, gray, scale(sample(1)))
+        }
+        _ => Color::rgba(
+            scale(sample(0)),
+            scale(sample(1)),
+            scale(sample(2)),
+            scale(sample(3)),
+        ),
+    };
+    Ok(color)
+}
+
+pub fn decode_png(data: &[u8]) -> Result<Bitmap, Error> {
+    if data.len() < 8 || data[..8] != PNG_SIGNATURE {
+        return Err(error("not a PNG image"));
+    }
+    let mut header = None;
+    let mut palette = Palette {
+        colors: Vec::new(),
+        transparent: None,
+    };
+    let mut compressed = Vec::new();
+    let mut offset = 8;
+    while offset + 12 <= data.len() {
+        let length = u32_at(data, offset) as usize;
+        let kind = &data[offset + 4..offset + 8];
+        let end = offset + 8 + length;
+        if end + 4 > data.len() {
+            return Err(error("PNG chunk is truncated"));
+        }
+        let body = &data[offset + 8..end];
+        if crc32(&data[offset + 4..end]) != u32_at(data, end) {
+            return Err(Error::UnexpectedInput(format!(
+                "PNG chunk {} has a wrong CRC",
+                core::str::from_utf8(kind).unwrap_or_default()
+            )));
+        }
+        match kind {
+            b"IHDR" if body.len() >= 13 => {
+                let h = Header {
+                    width: u32_at(body, 0) as usize,
+                    height: u32_at(body, 4) as usize,
+                    bit_depth: body[8],
+                    color_type: body[9],
+                    interlaced: body[12] == 1,
+                };
+                let valid_depths: &[u8] = match h.color_type {
+                    0 => &[1, 2, 4, 8, 16],
+                    3 => &[1, 2, 4, 8],
+                    2 | 4 | 6 => &[8, 16],
+                    _ => &[],
+                };
+                if !valid_depths.contains(&h.bit_depth) {
+                    return Err(Error::UnexpectedInput(format!(
+                        "unsupported PNG color type {} with bit depth {}",
+                        h.color_type, h.bit_depth
+                    )));
+                }
+                header = Some(h);
+            }
+            b"PLTE" => {
+                palette.colors = body
+                    .chunks_exact(3)
+                    .map(|c| Color::rgb(c[0], c[1], c[2]))
+                    .collect();
+            }
+            b"tRNS" => match header.as_ref().map(|h| h.color_type) {
+                Some(3) => {
+                    for (color, alpha) in palette.colors.iter_mut().zip(body) {
+                        color.a = *alpha;
+                    }
+                }
+                Some(0) if body.len() >= 2 => {
+                    let gray = u16::from_be_bytes([body[0], body[1]]);
+                    palette.transparent = Some([gray; 3]);
+                }
+                Some(2) if body.len() >= 6 => {
+                    palette.transparent = Some([
+                        u16::from_be_bytes([body[0], body[1]]),
+                        u16::from_be_bytes([body[2], body[3]]),
+                        u16::from_be_bytes([body[4], body[5]]),
+                    ]);
+                }
+                _ => {}
+            },
+            b"IDAT" => compressed.extend_from_slice(body),
+            b"IEND" => break,
+            _ => {}
+        }
+        offset = end + 4;
+    }
+
+    let header = header.ok_or_else(|| error("PNG image has no IHDR chunk"))?;
+    let raw = zlib_decompress(&compressed)?;
+    let mut bitmap = Bitmap::try_new(header.width, header.height)?;
+    // インターレースでなければ全体を1つのパスとして扱う
+    let passes: &[(usize, usize, usize, usize)] = if header.interlaced {
+        &ADAM7
+    } else {
+        &[(0, 0, 1, 1)]
+    };
+    let mut position = 0;
+    for (x0, y0, dx, dy) in passes {
+        let width = (header.width + dx - 1 - x0) / dx;
+        let height = (header.height + dy - 1 - y0) / dy;
+        if width == 0 || height == 0 {
+            continue;
+        }
+        let row_bytes = header.row_bytes(width);
+        let pass = raw
+            .get(position..)
+            .ok_or_else(|| error("PNG image data is truncated"))?;
+        let rows = unfilter(pass, row_bytes, height, header.filter_distance())?;
+        position += (row_bytes + 1) * height;
+        for y in 0..height {
+            let row = &rows[y * row_bytes..(y + 1) * row_bytes];
+            for x in 0..width {
+                let color = pixel_color(&header, &palette, row, x)?;
+                bitmap.set_pixel(x0 + x * dx, y0 + y * dy, color);
+            }
+        }
+    }
+    Ok(bitmap)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::paint::{encoder::encode_png, rasterizer::Framebuffer};
+
+    #[test]
+    fn test_decode_png() {
+        let mut framebuffer = Framebuffer::new(2, 2);
+        framebuffer.clear(Color::rgb(10, 20, 30));
+        framebuffer.blend(1, 1, Color::rgb(255, 0, 0), 1.0);
+        let png = encode_png(&framebuffer);
+        assert_eq!(Some((2, 2)), png_size(&png));
+        let bitmap = decode_png(&png).expect("failed to decode");
+        assert_eq!(Color::rgb(10, 20, 30), bitmap.pixel(0, 0));
+        assert_eq!(Color::rgb(255, 0, 0), bitmap.pixel(1, 1));
+
+        let mut broken = png.clone();
+        broken[20] ^= 1;
+        assert!(decode_png(&broken).is_err());
+    }
+
+    #[test]
+    fn test_decode_interlaced_palette() {
+        // 2ビットのパレット、Adam7、4番目の色をtRNSで透明にした3x3の画像
+        let png = [
+            0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48,
+            0x44, 0x52, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x03, 0x02, 0x03, 0x00, 0x00,
+            0x01, 0x5c, 0x41, 0x6d, 0xba, 0x00, 0x00, 0x00, 0x0c, 0x50, 0x4c, 0x54, 0x45, 0xff,
+            0x00, 0x00, 0x00, 0xff, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xfb, 0x00, 0x60,
+            0xf6, 0x00, 0x00, 0x00, 0x04, 0x74, 0x52, 0x4e, 0x53, 0xff, 0xff, 0xff, 0x00, 0x40,
+            0x2a, 0xa9, 0xf4, 0x00, 0x00, 0x00, 0x12, 0x49, 0x44, 0x41, 0x54, 0x78, 0xda, 0x63,
+            0x60, 0x60, 0x68, 0x00, 0x42, 0x07, 0x86, 0x03, 0x0c, 0x47, 0x00, 0x0c, 0x50, 0x02,
+            0xc5, 0x86, 0x2a, 0xa0, 0xa9, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4e, 0x44, 0xae,
+            0x42, 0x60, 0x82,
+        ];
+        let bitmap = decode_png(&png).expect("failed to decode");
+        let red = Color::rgb(255, 0, 0);
+        let green = Color::rgb(0, 255, 0);
+        let blue = Color::rgb(0, 0, 255);
+        let clear = Color::rgba(255, 255, 255, 0);
+        let expected = [[red, green, blue], [clear, red, green], [blue, clear, red]];
+        for (y, row) in expected.iter().enumerate() {
+            for (x, color) in row.iter().enumerate() {
+                assert_eq!(*color, bitmap.pixel(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_16bit() {
+        // 16ビットのRGBで、行にSubフィルタをかけた2x1の画像
+        let png = [
+            0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48,
+            0x44, 0x52, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x01, 0x10, 0x02, 0x00, 0x00,
+            0x00, 0x2b, 0xd0, 0x34, 0x9e, 0x00, 0x00, 0x00, 0x15, 0x49, 0x44, 0x41, 0x54, 0x78,
+            0x9c, 0x63, 0xfc, 0xcf, 0xd0, 0xc0, 0xc0, 0xc8, 0xc0, 0xc4, 0xc0, 0xc0, 0xf0, 0x8f,
+            0x01, 0x00, 0x13, 0x1e, 0x02, 0x82, 0xc9, 0xe8, 0x24, 0x67, 0x00, 0x00, 0x00, 0x00,
+            0x49, 0x45, 0x4e, 0x44, 0xae, 0x42, 0x60, 0x82,
+        ];
+        let bitmap = decode_png(&png).expect("failed to decode");
+        assert_eq!(Color::rgb(255, 128, 1), bitmap.pixel(0, 0));
+        assert_eq!(Color::rgb(1, 128, 255), bitmap.pixel(1, 0));
+    }
+}
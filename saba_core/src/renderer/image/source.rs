@@ -0,0 +1,265 @@
+use crate::renderer::{
+    css::{
+        cssom::ComponentValue,
+        media::{parse_media_query_list, MediaEnvironment},
+        parser::CssParser,
+        token::{CssTokenKind, CssTokenizer},
+        value::{parse_length, Length, LengthContext},
+    },
+    image::bitmap::Image,
+};
+use alloc::{
+    rc::Rc,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+// srcsetの候補に付けられた記述子
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Descriptor {
+    // 1xや2xのような画素密度
+    Density(f64),
+    // 480wのような画像の幅
+    Width(f64),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImageCandidate {
+    pub url: String,
+    pub descriptor: Descriptor,
+}
+
+// 選んだ画像のURLと、画像の1画素をCSSの何pxとして表示するかの画素密度
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImageSource {
+    pub url: String,
+    pub density: f64,
+}
+
+// img要素の画像の読み込み状態
+#[derive(Clone, Debug, PartialEq)]
+pub enum ImageState {
+    // 取得を待っている
+    Pending,
+    Loaded { image: Rc<Image>, density: f64 },
+    // srcがないか、取得やデコードに失敗した
+    Broken,
+}
+
+fn parse_descriptor(descriptor: &str) -> Option<Descriptor> {
+    let (value, unit) = descriptor.split_at(descriptor.len().checked_sub(1)?);
+    let value = value.parse::<f64>().ok().filter(|v| *v > 0.0)?;
+    match unit {
+        "x" => Some(Descriptor::Density(value)),
+        "w" if value as u64 as f64 == value => Some(Descriptor::Width(value)),
+        _ => None,
+    }
+}
+
+// srcset属性を候補の列にする。解釈できない記述子を持つ候補は捨てる。HTML 4.8.4.3.10
+pub fn parse_srcset(srcset: &str) -> Vec<ImageCandidate> {
+    let mut candidates = Vec::new();
+    let mut rest = srcset;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_ascii_whitespace() || c == ',');
+        if rest.is_empty() {
+            return candidates;
+        }
+        let end = rest
+            .find(|c: char| c.is_ascii_whitespace())
+            .unwrap_or(rest.len());
+        let (url, after) = rest.split_at(end);
+        // URLの末尾のカンマは候補の区切りで、記述子はない
+        let (url, descriptors) = if url.ends_with(',') {
+            rest = after;
+            (url.trim_end_matches(','), "")
+        } else {
+            let end = after.find(',').unwrap_or(after.len());
+            rest = &after[end..];
+            (url, &after[..end])
+        };
+
+        let descriptors: Vec<&str> = descriptors.split_ascii_whitespace().collect();
+        let descriptor = match descriptors[..] {
+            [] => Some(Descriptor::Density(1.0)),
+            [descriptor] => parse_descriptor(descriptor),
+            _ => None,
+        };
+        if let Some(descriptor) = descriptor {
+            candidates.push(ImageCandidate {
+                url: url.to_string(),
+                descriptor,
+            });
+        }
+    }
+}
+
+// sizes属性から画像を表示する幅を求める。最初に条件が一致した長さを使い、なければ100vw
+pub fn source_size(sizes: &str, environment: &MediaEnvironment) -> f64 {
+    let context = LengthContext {
+        viewport_width: environment.width,
+        viewport_height: environment.height,
+        ..LengthContext::default()
+    };
+    let values = CssParser::new(CssTokenizer::new(sizes.to_string())).parse_component_values();
+    for size in values.split(|v| *v == ComponentValue::Token(CssTokenKind::Comma)) {
+        let Some(last) = size.iter().rposition(|v| !v.is_whitespace()) else {
+            continue;
+        };
+        // パーセントは使えない
+        let length = match parse_length(&size[last], &context) {
+            Some(Length::Px(px)) if px >= 0.0 => px,
+            _ => continue,
+        };
+        let condition = &size[..last];
+        if condition.iter().all(|v| v.is_whitespace())
+            || parse_media_query_list(condition).matches(environment)
+        {
+            return length;
+        }
+    }
+    environment.width
+}
+
+// 表示する環境の画素密度に最も合う候補を選ぶ。srcは1xの候補として加える
+pub fn select_source(
+    src: Option<&str>,
+    srcset: Option<&str>,
+    sizes: Option<&str>,
+    environment: &MediaEnvironment,
+) -> Option<ImageSource> {
+    let mut candidates = srcset.map(parse_srcset).unwrap_or_default();
+    let has_width = candidates
+        .iter()
+        .any(|c| matches!(c.descriptor, Descriptor::Width(_)));
+    let has_1x = candidates
+        .iter()
+        .any(|c| c.descriptor == Descriptor::Density(1.0));
+    if let Some(src) = src.filter(|s| !s.is_empty()) {
+        if !has_width && !has_1x {
+            candidates.push(ImageCandidate {
+                url: src.to_string(),
+                descriptor: Descriptor::Density(1.0),
+            });
+        }
+    }
+
+    let size = match sizes {
+        Some(sizes) if has_width => source_size(sizes, environment),
+        _ => environment.width,
+    };
+    let sources = candidates.into_iter().filter_map(|c| {
+        let density = match c.descriptor {
+            Descriptor::Density(density) => density,
+            // 記述子の種類が混在する場合は幅の記述子を使う
+            Descriptor::Width(_) if size <= 0.0 => return None,
+            Descriptor::Width(width) => width / size,
+        };
+        Some(ImageSource {
+            url: c.url,
+            density,
+        })
+    });
+
+    // 環境の画素密度以上で最も小さいもの。なければ最も大きいもの
+    let mut best: Option<ImageSource> = None;
+    for source in sources {
+        let better = match &best {
+            None => true,
+            Some(best) => {
+                let enough = source.density >= environment.resolution;
+                let best_enough = best.density >= environment.resolution;
+                match (enough, best_enough) {
+                    (true, true) => source.density < best.density,
+                    (true, false) => true,
+                    (false, true) => false,
+                    (false, false) => source.density > best.density,
+                }
+            }
+        };
+        if better {
+            best = Some(source);
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_parse_srcset() {
+        let candidates =
+            parse_srcset(" a.png, b.png 2x,c.png,, d,e.png 100w, f.png 1.5q, g.png 2x 3x");
+        let summary: Vec<(&str, Descriptor)> = candidates
+            .iter()
+            .map(|c| (c.url.as_str(), c.descriptor))
+            .collect();
+        assert_eq!(
+            vec![
+                ("a.png", Descriptor::Density(1.0)),
+                ("b.png", Descriptor::Density(2.0)),
+                ("c.png", Descriptor::Density(1.0)),
+                ("d,e.png", Descriptor::Width(100.0)),
+            ],
+            summary
+        );
+    }
+
+    #[test]
+    fn test_select_source() {
+        let mut environment = MediaEnvironment::new(800.0, 600.0);
+        let select = |src, srcset, sizes, environment: &MediaEnvironment| {
+            select_source(src, srcset, sizes, environment).map(|s| (s.url, s.density))
+        };
+        assert_eq!(None, select(None, None, None, &environment));
+        assert_eq!(None, select(Some(""), Some(""), None, &environment));
+        assert_eq!(
+            Some(("a.png".to_string(), 1.0)),
+            select(Some("a.png"), Some("b.png 2x"), None, &environment)
+        );
+        environment.resolution = 1.5;
+        assert_eq!(
+            Some(("b.png".to_string(), 2.0)),
+            select(
+                Some("a.png"),
+                Some("b.png 2x, c.png 3x"),
+                None,
+                &environment
+            )
+        );
+        environment.resolution = 4.0;
+        assert_eq!(
+            Some(("c.png".to_string(), 3.0)),
+            select(
+                Some("a.png"),
+                Some("b.png 2x, c.png 3x"),
+                None,
+                &environment
+            )
+        );
+
+        // 幅の記述子があるときはsrcを使わず、sizesの幅で割って画素密度にする
+        environment.resolution = 1.0;
+        let srcset = Some("small.png 400w, large.png 1200w");
+        let sizes = Some("(max-width: 600px) 100vw, 50vw");
+        assert_eq!(
+            Some(("small.png".to_string(), 1.0)),
+            select(Some("a.png"), srcset, sizes, &environment)
+        );
+        environment.resolution = 2.0;
+        assert_eq!(
+            Some(("large.png".to_string(), 3.0)),
+            select(Some("a.png"), srcset, sizes, &environment)
+        );
+        environment.width = 500.0;
+        assert_eq!(
+            Some(("large.png".to_string(), 2.4)),
+            select(Some("a.png"), srcset, sizes, &environment)
+        );
+        assert_eq!(250.0, source_size("bogus, 10%, 250px", &environment));
+        assert_eq!(500.0, source_size("(min-width: 900px) 10px", &environment));
+    }
+}
@@ -0,0 +1,696 @@
+use crate::renderer::{
+    css::{
+        computed_style::{BoxSizing, Clear, Display},
+        value::Length,
+    },
+    dom::node::NodeKind,
+    layout::{
+        float::FloatContext,
+        layout_box::{BoxKind, LayoutBox},
+        layout_view::LayoutContext,
+    },
+};
+
+// 子ボックスのサイズやパーセント値の基準となる包含ブロック
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ContainingBlock {
+    pub x: f64,
+    pub width: f64,
+    // 高さが内容に依存する場合はNone
+    pub height: Option<f64>,
+}
+
+// 隣接して相殺されるマージン。正の最大値と負の最小値の和になる
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CollapsibleMargin {
+    positive: f64,
+    negative: f64,
+}
+
+impl CollapsibleMargin {
+    pub fn new(margin: f64) -> Self {
+        let mut collapsible = Self::default();
+        collapsible.adjoin_margin(margin);
+        collapsible
+    }
+
+    fn adjoin_margin(&mut self, margin: f64) {
+        self.positive = self.positive.max(margin);
+        self.negative = self.negative.min(margin);
+    }
+
+    pub fn adjoin(&mut self, other: CollapsibleMargin) {
+        self.adjoin_margin(other.positive);
+        self.adjoin_margin(other.negative);
+    }
+
+    pub fn resolve(&self) -> f64 {
+        self.positive + self.negative
+    }
+}
+
+// ブロックボックスのレイアウト結果のうち、親がマージンを相殺するために必要な情報
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct BlockMargins {
+    // 最初の子と相殺されて上に抜けるマージン
+    pub top: CollapsibleMargin,
+    // 最後の子と相殺されて下に抜けるマージン
+    pub bottom: CollapsibleMargin,
+    // 内容がなく上下のマージンが相殺される
+    pub collapsed_through: bool,
+}
+
+// フレックスアイテムのように親が決めた内容領域の大きさ。Noneの方向は通常どおり求める
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SizeOverride {
+    pub width: Option<f64>,
+    pub height: Option<f64>,
+}
+
+pub(super) fn resolve_height(length: &Length, containing_block: &ContainingBlock) -> Option<f64> {
+    match length {
+        Length::Auto => None,
+        Length::Px(px) => Some(*px),
+        _ => containing_block.height.and_then(|h| length.resolve(h)),
+    }
+}
+
+// min-width/max-widthで制限した値
+pub(super) fn clamp_size(value: f64, min: f64, max: Option<f64>) -> f64 {
+    let value = match max {
+        Some(max) => value.min(max),
+        None => value,
+    };
+    value.max(min)
+}
+
+impl LayoutBox {
+    pub(super) fn is_root_element(&self) -> bool {
+        self.node
+            .as_ref()
+            .and_then(|node| node.borrow().parent().upgrade())
+            .is_some_and(|parent| matches!(parent.borrow().kind(), NodeKind::Document))
+    }
+
+    // フレックスアイテムかグリッドアイテム
+    pub(super) fn is_container_item(&self) -> bool {
+        self.node
+            .as_ref()
+            .and_then(|node| node.borrow().parent().upgrade())
+            .and_then(|parent| parent.borrow().computed_style())
+            .is_some_and(|style| {
+                matches!(
+                    style.display,
+                    Display::Flex | Display::InlineFlex | Display::Grid | Display::InlineGrid
+                )
+            })
+    }
+
+    // 新しいブロック整形文脈を作るボックスでは子のマージンが外に抜けず、フロートが外に影響しない
+    pub fn establishes_block_formatting_context(&self) -> bool {
+        self.kind == BoxKind::InlineBlock
+            || self.is_root_element()
+            || self.is_out_of_flow()
+            || self.is_container_item()
+            || self.is_scroll_container()
+            || matches!(
+                self.style.display,
+                Display::FlowRoot
+                    | Display::Flex
+                    | Display::Grid
+                    | Display::Table
+                    | Display::TableCell
+                    | Display::TableCaption
+            )
+    }
+
+    // box-sizingに応じて指定値を内容領域の幅に変換する
+    pub(super) fn content_size(&self, specified: f64, horizontal: bool) -> f64 {
+        if self.style.box_sizing == BoxSizing::ContentBox {
+            return specified;
+        }
+        let edges = if horizontal {
+            self.dimensions.padding.horizontal() + self.dimensions.border.horizontal()
+        } else {
+            self.dimensions.padding.vertical() + self.dimensions.border.vertical()
+        };
+        (specified - edges).max(0.0)
+    }
+
+    pub(super) fn min_max_width(&self, containing_width: f64) -> (f64, Option<f64>) {
+        let min = self
+            .style
+            .min_width
+            .resolve(containing_width)
+            .map(|w| self.content_size(w, true))
+            .unwrap_or(0.0);
+        let max = self
+            .style
+            .max_width
+            .as_ref()
+            .and_then(|w| w.resolve(containing_width))
+            .map(|w| self.content_size(w, true));
+        (min, max)
+    }
+
+    // CSS 2.1 10.3.3 通常フロー内のブロックレベルの非置換要素の幅
+    fn solve_width(&mut self, containing_width: f64, width: Option<f64>) {
+        let style = &self.style;
+        let mut margin_left = style.margin.left.resolve(containing_width);
+        let mut margin_right = style.margin.right.resolve(containing_width);
+        let edges = self.dimensions.padding.horizontal() + self.dimensions.border.horizontal();
+
+        let mut width = width;
+        if let Some(w) = width {
+            let total = margin_left.unwrap_or(0.0) + margin_right.unwrap_or(0.0) + edges + w;
+            if total > containing_width {
+                margin_left = margin_left.or(Some(0.0));
+                margin_right = margin_right.or(Some(0.0));
+            }
+        }
+
+        let (width, left, right) = match (width.take(), margin_left, margin_right) {
+            (None, left, right) => {
+                let left = left.unwrap_or(0.0);
+                let right = right.unwrap_or(0.0);
+                let width = containing_width - left - right - edges;
+                if width >= 0.0 {
+                    (width, left, right)
+                } else {
+                    // 幅が負になる場合は0とし、右マージンで調整する
+                    (0.0, left, containing_width - left - edges)
+                }
+            }
+            (Some(width), None, None) => {
+                let margin = (containing_width - width - edges) / 2.0;
+                (width, margin, margin)
+            }
+            (Some(width), None, Some(right)) => {
+                (width, containing_width - width - edges - right, right)
+            }
+            (Some(width), Some(left), None) => {
+                (width, left, containing_width - width - edges - left)
+            }
+            // 制約過多の場合は右マージンを無視する
+            (Some(width), Some(left), Some(_)) => {
+                (width, left, containing_width - width - edges - left)
+            }
+        };
+
+        self.dimensions.content.width = width;
+        self.dimensions.margin.left = left;
+        self.dimensions.margin.right = right;
+    }
+
+    pub(super) fn resolve_edges(&mut self, containing_width: f64) {
+        let style = &self.style;
+        self.dimensions.padding.top = style.padding.top.resolve_or_zero(containing_width);
+        self.dimensions.padding.right = style.padding.right.resolve_or_zero(containing_width);
+        self.dimensions.padding.bottom = style.padding.bottom.resolve_or_zero(containing_width);
+        self.dimensions.padding.left = style.padding.left.resolve_or_zero(containing_width);
+        self.dimensions.border = style.border_width;
+        self.dimensions.margin.top = style.margin.top.resolve_or_zero(containing_width);
+        self.dimensions.margin.bottom = style.margin.bottom.resolve_or_zero(containing_width);
+    }
+
+    // 内容の幅に合わせる幅(shrink-to-fit)。CSS 2.1 10.3.5
+    pub(super) fn shrink_to_fit_width(&self, available: f64, context: &LayoutContext) -> f64 {
+        let (min_content, max_content) = self.intrinsic_widths(context);
+        max_content.min(available.max(min_content))
+    }
+
+    fn resolve_width(&mut self, containing_width: f64, context: &LayoutContext) {
+        if self.is_table_wrapper() {
+            let width = self.table_wrapper_width(containing_width, context);
+            self.solve_width(containing_width, Some(width));
+            return;
+        }
+        let specified_width = self
+            .style
+            .width
+            .resolve(containing_width)
+            .map(|w| self.content_size(w, true));
+        let (min_width, max_width) = self.min_max_width(containing_width);
+        let width = if self.kind == BoxKind::InlineBlock || self.is_float() {
+            let available = containing_width
+                - self.style.margin.left.resolve_or_zero(containing_width)
+                - self.style.margin.right.resolve_or_zero(containing_width)
+                - self.dimensions.padding.horizontal()
+                - self.dimensions.border.horizontal();
+            Some(specified_width.unwrap_or_else(|| self.shrink_to_fit_width(available, context)))
+        } else {
+            specified_width
+        };
+        match width {
+            Some(w) => {
+                self.solve_width(containing_width, Some(clamp_size(w, min_width, max_width)))
+            }
+            None => {
+                self.solve_width(containing_width, None);
+                let w = self.dimensions.content.width;
+                let clamped = clamp_size(w, min_width, max_width);
+                if clamped != w {
+                    self.solve_width(containing_width, Some(clamped));
+                }
+            }
+        }
+    }
+
+    // 左上を(containing_block.x, 0)としてブロックレベルのボックスをレイアウトする
+    pub fn layout_block(
+        &mut self,
+        containing_block: &ContainingBlock,
+        context: &LayoutContext,
+    ) -> BlockMargins {
+        self.layout_block_with(containing_block, SizeOverride::default(), context)
+    }
+
+    pub(super) fn layout_block_with(
+        &mut self,
+        containing_block: &ContainingBlock,
+        size: SizeOverride,
+        context: &LayoutContext,
+    ) -> BlockMargins {
+        let containing_width = containing_block.width;
+        self.resolve_edges(containing_width);
+
+        let replaced_size = self
+            .replaced
+            .is_some()
+            .then(|| self.replaced_size(Some(containing_width), containing_block.height));
+        match size.width.or(replaced_size.map(|(w, _)| w)) {
+            Some(w) => self.solve_width(containing_width, Some(w)),
+            None => self.resolve_width(containing_width, context),
+        }
+        if self.kind == BoxKind::InlineBlock || self.is_float() || size.width.is_some() {
+            // インラインレベルのボックスやフロート、幅が決められたボックスのautoマージンは0
+            self.dimensions.margin.left = self.style.margin.left.resolve_or_zero(containing_width);
+            self.dimensions.margin.right =
+                self.style.margin.right.resolve_or_zero(containing_width);
+        }
+
+        let dimensions = &mut self.dimensions;
+        dimensions.content.x = containing_block.x
+            + dimensions.margin.left
+            + dimensions.border.left
+            + dimensions.padding.left;
+        dimensions.content.y = dimensions.border.top + dimensions.padding.top;
+
+        let specified_height = resolve_height(&self.style.height, containing_block)
+            .map(|h| self.content_size(h, false));
+        let min_height = resolve_height(&self.style.min_height, containing_block)
+            .map(|h| self.content_size(h, false))
+            .unwrap_or(0.0);
+        let max_height = self
+            .style
+            .max_height
+            .as_ref()
+            .and_then(|h| resolve_height(h, containing_block))
+            .map(|h| self.content_size(h, false));
+
+        let definite_height = match (size.height, replaced_size) {
+            (Some(h), _) => Some(h),
+            (None, Some((_, h))) => Some(h),
+            // テーブルの高さの指定は行の高さの合計より小さければ無視されるので、行を広げる高さとして渡す
+            (None, None) if self.kind == BoxKind::Table => None,
+            (None, None) => specified_height.map(|h| clamp_size(h, min_height, max_height)),
+        };
+        let child_containing_block = ContainingBlock {
+            x: self.dimensions.content.x,
+            width: self.dimensions.content.width,
+            height: match self.kind {
+                BoxKind::Table => specified_height.map(|h| clamp_size(h, min_height, max_height)),
+                _ => definite_height,
+            },
+        };
+
+        let is_bfc = self.establishes_block_formatting_context();
+        let edges = self.dimensions.padding;
+        let border = self.dimensions.border;
+        let collapse_top = !is_bfc && edges.top == 0.0 && border.top == 0.0;
+        let collapse_bottom =
+            !is_bfc && edges.bottom == 0.0 && border.bottom == 0.0 && definite_height.is_none();
+
+        let mut margins = BlockMargins {
+            top: CollapsibleMargin::new(self.dimensions.margin.top),
+            bottom: CollapsibleMargin::new(self.dimensions.margin.bottom),
+            collapsed_through: false,
+        };
+
+        // 新しいブロック整形文脈の中のフロートは外のフロートと独立に置く
+        let outer_floats = is_bfc.then(|| context.floats.replace(FloatContext::default()));
+        let (content_height, has_content) = if self.is_flex_container() {
+            self.layout_flex_children(&child_containing_block, context)
+        } else if self.is_grid_container() {
+            self.layout_grid_children(&child_containing_block, context)
+        } else if self.kind == BoxKind::Table {
+            self.layout_table_children(&child_containing_block, context)
+        } else if self.is_table_wrapper() {
+            self.layout_table_wrapper_children(&child_containing_block, context)
+        } else if self.has_inline_children() {
+            self.layout_inline_children(context)
+        } else {
+            self.layout_block_children(
+                &child_containing_block,
+                collapse_top,
+                collapse_bottom,
+                &mut margins,
+                context,
+            )
+        };
+        // ブロック整形文脈のルートの高さはフロートを含む。CSS 2.1 10.6.7
+        let content_height = match outer_floats {
+            Some(outer) => {
+                let floats = context.floats.replace(outer);
+                let content_y = self.dimensions.content.y;
+                floats.bottom().map_or(content_height, |bottom| {
+                    content_height.max(bottom - content_y)
+                })
+            }
+            None => content_height,
+        };
+
+        let height = match definite_height {
+            Some(h) => h,
+            None => clamp_size(content_height, min_height, max_height),
+        };
+        self.dimensions.content.height = height;
+
+        if collapse_top && collapse_bottom && !has_content && height == 0.0 {
+            margins.collapsed_through = true;
+        }
+        margins
+    }
+
+    // 子のブロックボックスを上から順に積み、隣接するマージンを相殺する
+    fn layout_block_children(
+        &mut self,
+        containing_block: &ContainingBlock,
+        collapse_top: bool,
+        collapse_bottom: bool,
+        margins: &mut BlockMargins,
+        context: &LayoutContext,
+    ) -> (f64, bool) {
+        let content_y = self.dimensions.content.y;
+        // ブロック整形文脈のルートから見た内容領域の上端
+        let origin = context.floats.borrow().origin() + content_y;
+        let mut cursor = 0.0;
+        let mut pending = CollapsibleMargin::default();
+        // まだ最初の子の上マージンが親の上マージンと相殺されうる
+        let mut at_top = collapse_top;
+        let mut has_content = false;
+
+        for child in &mut self.children {
+            // 通常フローの外にあるボックスは次のボックスが来る位置に置く
+            let static_y = if at_top {
+                cursor
+            } else {
+                cursor + pending.resolve()
+            };
+            if child.is_absolutely_positioned() {
+                child.place_at_static_position(containing_block, content_y + static_y, context);
+                continue;
+            }
+            if child.is_float() {
+                child.layout_float(containing_block, origin + static_y, context);
+                continue;
+            }
+
+            // レイアウトの前に子の上端の位置を見積もり、中のフロートや行の位置の基準にする
+            let mut top_margin = pending;
+            top_margin.adjoin(child.estimated_top_margin(containing_block.width));
+            let estimated_y = if at_top {
+                cursor
+            } else {
+                cursor + top_margin.resolve()
+            };
+            // フロートの下端より上にあればクリアランスで下げる
+            let clearance = match child.style.clear {
+                Clear::None => None,
+                clear => context.floats.borrow().clearance(clear),
+            }
+            .map(|bottom| bottom - origin)
+            .filter(|y| *y > estimated_y);
+            let y = clearance.unwrap_or(estimated_y);
+            // 新しいブロック整形文脈はフロートと重ならないように狭める
+            let mut child_containing_block = *containing_block;
+            if child.establishes_block_formatting_context() {
+                let (left, right) = context.floats.borrow().available(
+                    origin + y,
+                    0.0,
+                    containing_block.x,
+                    containing_block.x + containing_block.width,
+                );
+                child_containing_block.x = left;
+                child_containing_block.width = (right - left).max(0.0);
+            }
+
+            let parent_origin = context.floats.borrow().origin();
+            context.floats.borrow_mut().set_origin(origin + y);
+            let child_margins = child.layout_block(&child_containing_block, context);
+            context.floats.borrow_mut().set_origin(parent_origin);
+
+            if let Some(y) = clearance {
+                // クリアランスがあるとマージンは前のマージンと相殺されない
+                if at_top {
+                    margins.top.adjoin(pending);
+                    at_top = false;
+                }
+                pending = child_margins.bottom;
+                has_content = true;
+                cursor = y;
+                child.translate(0.0, content_y + cursor);
+                cursor += child.dimensions.border_box().height;
+                continue;
+            }
+            if child_margins.collapsed_through {
+                pending.adjoin(child_margins.top);
+                let y = if at_top {
+                    cursor
+                } else {
+                    cursor + pending.resolve()
+                };
+                child.translate(0.0, content_y + y);
+                pending.adjoin(child_margins.bottom);
+                continue;
+            }
+
+            if at_top {
+                margins.top.adjoin(pending);
+                margins.top.adjoin(child_margins.top);
+                at_top = false;
+            } else {
+                pending.adjoin(child_margins.top);
+                cursor += pending.resolve();
+            }
+            pending = child_margins.bottom;
+            has_content = true;
+
+            child.translate(0.0, content_y + cursor);
+            cursor += child.dimensions.border_box().height;
+        }
+
+        if at_top {
+            margins.top.adjoin(pending);
+        } else if collapse_bottom {
+            margins.bottom.adjoin(pending);
+        } else {
+            cursor += pending.resolve();
+        }
+        (cursor, has_content)
+    }
+
+    // 子と相殺された後の上マージンの見積もり
+    fn estimated_top_margin(&self, containing_width: f64) -> CollapsibleMargin {
+        let style = &self.style;
+        let mut margin = CollapsibleMargin::new(style.margin.top.resolve_or_zero(containing_width));
+        if self.establishes_block_formatting_context()
+            || self.has_inline_children()
+            || style.padding.top.resolve_or_zero(containing_width) != 0.0
+            || style.border_width.top != 0.0
+        {
+            return margin;
+        }
+        if let Some(first) = self.children.iter().find(|child| !child.is_out_of_flow()) {
+            margin.adjoin(first.estimated_top_margin(containing_width));
+        }
+        margin
+    }
+
+    // 最小内容幅と最大内容幅。CSS Sizing 3
+    pub fn intrinsic_widths(&self, context: &LayoutContext) -> (f64, f64) {
+        if self.replaced.is_some() {
+            let (width, _) = self.replaced_size(None, None);
+            return (width, width);
+        }
+        if self.is_flex_container() {
+            return self.flex_intrinsic_widths(context);
+        }
+        if self.is_grid_container() {
+            return self.grid_intrinsic_widths(context);
+        }
+        if self.is_table_wrapper() {
+            return self.table_wrapper_intrinsic_widths(context);
+        }
+        if self.has_inline_children() {
+            return self.inline_intrinsic_widths(context);
+        }
+
+        let mut min_content: f64 = 0.0;
+        let mut max_content: f64 = 0.0;
+        for child in self
+            .children
+            .iter()
+            .filter(|child| !child.is_absolutely_positioned())
+        {
+            let (child_min, child_max) = child.intrinsic_contribution(context);
+            min_content = min_content.max(child_min);
+            max_content = max_content.max(child_max);
+        }
+        (min_content, max_content)
+    }
+
+    // 親の内容幅に対するマージンボックスの最小幅と最大幅
+    pub(super) fn intrinsic_contribution(&self, context: &LayoutContext) -> (f64, f64) {
+        let style = &self.style;
+        let edges = style.margin.left.resolve_or_zero(0.0)
+            + style.margin.right.resolve_or_zero(0.0)
+            + style.padding.left.resolve_or_zero(0.0)
+            + style.padding.right.resolve_or_zero(0.0)
+            + style.border_width.horizontal();
+        let (min, max) = match style.width {
+            Length::Px(width) => {
+                let width = self.content_size_for_intrinsic(width);
+                (width, width)
+            }
+            _ => self.intrinsic_widths(context),
+        };
+        (min + edges, max + edges)
+    }
+
+    pub(super) fn content_size_for_intrinsic(&self, specified: f64) -> f64 {
+        if self.style.box_sizing == BoxSizing::ContentBox {
+            return specified;
+        }
+        let edges = self.style.padding.left.resolve_or_zero(0.0)
+            + self.style.padding.right.resolve_or_zero(0.0)
+            + self.style.border_width.horizontal();
+        (specified - edges).max(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::layout::{geometry::Rect, layout_document, layout_view::LayoutView};
+    use alloc::{vec, vec::Vec};
+
+    fn body(view: &LayoutView) -> &LayoutBox {
+        &view.root().expect("failed to get root box").children()[0]
+    }
+
+    #[test]
+    fn test_block_width() {
+        let (_, view) = layout_document(
+            "<html><body><p class=\"a\">a</p><p class=\"b\">b</p><p class=\"c\">c</p><p class=\"d\">d</p></body></html>",
+            "body { margin: 0 } p { margin: 0 } .a { width: 200px; margin: 0 auto; padding: 5px; border: 1px solid } .b { margin-left: 10%; padding: 0 20px } .c { width: 50%; box-sizing: border-box; padding: 0 10px; margin-left: auto } .d { width: 1000px; max-width: 300px; min-width: 400px }",
+        );
+        let body = body(&view);
+        let a = body.children()[0].dimensions();
+        assert_eq!(200.0, a.content.width);
+        assert_eq!(194.0, a.margin.left);
+        assert_eq!(Rect::new(194.0, 0.0, 212.0, 31.2), a.border_box());
+
+        let b = body.children()[1].dimensions();
+        assert_eq!(60.0, b.margin.left);
+        assert_eq!(500.0, b.content.width);
+        assert_eq!(80.0, b.content.x);
+
+        let c = body.children()[2].dimensions();
+        assert_eq!(280.0, c.content.width);
+        assert_eq!(300.0, c.margin.left);
+
+        let d = body.children()[3].dimensions();
+        assert_eq!(400.0, d.content.width);
+    }
+
+    #[test]
+    fn test_block_height() {
+        let (_, view) = layout_document(
+            "<html><body><p class=\"a\">a</p><p class=\"b\"></p><p class=\"c\">c</p><p class=\"d\"></p></body></html>",
+            "html, body, p { margin: 0 } html { height: 100% } body { height: 50% } .a { height: 50px; padding: 10px; box-sizing: border-box } .b { min-height: 15px } .c { max-height: 10px } .d { height: 10% }",
+        );
+        let body = body(&view);
+        assert_eq!(200.0, body.dimensions().content.height);
+        let heights: Vec<f64> = body
+            .children()
+            .iter()
+            .map(|c| c.dimensions().border_box().height)
+            .collect();
+        assert_eq!(vec![50.0, 15.0, 10.0, 20.0], heights);
+        let ys: Vec<f64> = body
+            .children()
+            .iter()
+            .map(|c| c.dimensions().border_box().y)
+            .collect();
+        assert_eq!(vec![0.0, 50.0, 65.0, 75.0], ys);
+    }
+
+    #[test]
+    fn test_margin_collapsing() {
+        let (_, view) = layout_document(
+            "<html><body><p class=\"a\">a</p><p class=\"empty\"></p><p class=\"b\">b</p><p class=\"c\">c</p></body></html>",
+            "body { margin: 0 } p { line-height: 20px } .a { margin: 30px 0 10px } .empty { margin: 40px 0 -5px } .b { margin: -10px 0 20px } .c { margin: 5px 0 50px }",
+        );
+        let body = body(&view);
+        // 最初の子の上マージンはbodyを抜けてルートの内容の先頭に置かれる
+        assert_eq!(30.0, body.dimensions().border_box().y);
+        let ys: Vec<f64> = body
+            .children()
+            .iter()
+            .map(|c| c.dimensions().border_box().y)
+            .collect();
+        // a(30..50) + 空のブロックを挟む10, 40, -5, -10の相殺で40-10=30
+        assert_eq!(30.0, ys[0]);
+        assert_eq!(80.0, ys[2]);
+        assert_eq!(120.0, ys[3]);
+        // 最後の子の下マージンはbodyの高さに含まれない
+        assert_eq!(110.0, body.dimensions().content.height);
+        let root = view.root().unwrap();
+        assert_eq!(190.0, root.dimensions().content.height);
+    }
+
+    #[test]
+    fn test_parent_with_border_does_not_collapse() {
+        let (_, view) = layout_document(
+            "<html><body><p>a</p></body></html>",
+            "body { margin: 0; padding-top: 1px; border-bottom: 2px solid } p { margin: 20px 0; line-height: 10px }",
+        );
+        let body = body(&view);
+        assert_eq!(0.0, body.dimensions().border_box().y);
+        let p = body.children()[0].dimensions();
+        assert_eq!(21.0, p.border_box().y);
+        assert_eq!(50.0, body.dimensions().content.height);
+    }
+
+    #[test]
+    fn test_flow_root() {
+        let (_, view) = layout_document(
+            "<html><body><p>a</p></body></html>",
+            "body { margin: 0; display: flow-root } p { margin: 20px 0; line-height: 10px }",
+        );
+        let body = body(&view);
+        assert_eq!(0.0, body.dimensions().border_box().y);
+        assert_eq!(20.0, body.children()[0].dimensions().border_box().y);
+        assert_eq!(50.0, body.dimensions().content.height);
+    }
+
+    #[test]
+    fn test_collapsible_margin() {
+        let mut margin = CollapsibleMargin::new(10.0);
+        margin.adjoin(CollapsibleMargin::new(-4.0));
+        margin.adjoin(CollapsibleMargin::new(6.0));
+        margin.adjoin(CollapsibleMargin::new(-7.0));
+        assert_eq!(3.0, margin.resolve());
+    }
+}
@@ -0,0 +1,880 @@
+use crate::renderer::{
+    css::{
+        computed_style::{ComputedStyle, ContentAlign, FlexBasis, FlexWrap, ItemAlign, Sides},
+        value::Length,
+    },
+    layout::{
+        block::{clamp_size, resolve_height, ContainingBlock, SizeOverride},
+        layout_box::LayoutBox,
+        layout_view::LayoutContext,
+    },
+};
+use alloc::vec::Vec;
+
+// フレックスアイテムの主軸と交差軸での大きさ。大きさは内容領域のもの
+#[derive(Clone, Debug)]
+struct FlexItem {
+    // 子ボックスの位置
+    index: usize,
+    grow: f64,
+    shrink: f64,
+    base_size: f64,
+    hypothetical_main: f64,
+    min_main: f64,
+    max_main: Option<f64>,
+    // 軸方向のマージン、ボーダー、パディングの和。autoマージンは0とする
+    main_edges: f64,
+    cross_edges: f64,
+    target_main: f64,
+    frozen: bool,
+    cross: f64,
+    // マージンボックスの上端からベースラインまでの距離
+    baseline: f64,
+    align: ItemAlign,
+    // autoマージンに割り当てた大きさ
+    auto_margin: Sides<f64>,
+    main_position: f64,
+    cross_position: f64,
+}
+
+impl FlexItem {
+    fn outer_main(&self) -> f64 {
+        self.target_main + self.main_edges
+    }
+
+    fn outer_cross(&self) -> f64 {
+        self.cross + self.cross_edges
+    }
+
+    fn flex_factor(&self, growing: bool) -> f64 {
+        if growing {
+            self.grow
+        } else {
+            self.shrink
+        }
+    }
+}
+
+struct FlexLine {
+    // itemsの中の位置
+    items: Vec<usize>,
+    cross: f64,
+    // 行の中で最も高いベースラインの位置
+    baseline: f64,
+    position: f64,
+}
+
+// 反転した軸では書字方向のstartとendがflex-endとflex-startに対応する
+fn flex_relative(align: ContentAlign, reverse: bool) -> ContentAlign {
+    match (align, reverse) {
+        (ContentAlign::Start, true) | (ContentAlign::End, false) => ContentAlign::FlexEnd,
+        (ContentAlign::End, true) | (ContentAlign::Start, false) => ContentAlign::FlexStart,
+        (align, _) => align,
+    }
+}
+
+// 空き領域の配置。(先頭の余白, アイテムの間に加える余白)
+pub(super) fn distribute(align: ContentAlign, free: f64, count: usize) -> (f64, f64) {
+    let count = count.max(1) as f64;
+    match align {
+        ContentAlign::FlexEnd | ContentAlign::End => (free, 0.0),
+        ContentAlign::Center => (free / 2.0, 0.0),
+        ContentAlign::SpaceBetween if free > 0.0 && count > 1.0 => (0.0, free / (count - 1.0)),
+        ContentAlign::SpaceAround if free > 0.0 => (free / count / 2.0, free / count),
+        ContentAlign::SpaceEvenly if free > 0.0 => (free / (count + 1.0), free / (count + 1.0)),
+        // 空きが負の場合は中央に寄せる
+        ContentAlign::SpaceAround | ContentAlign::SpaceEvenly => (free / 2.0, 0.0),
+        _ => (0.0, 0.0),
+    }
+}
+
+// 交差軸の始端からのずれ。wrap-reverseではflex-startが下端になる
+pub(super) fn item_offset(align: ItemAlign, free: f64, wrap_reverse: bool) -> f64 {
+    let at_end = match align {
+        ItemAlign::Center => return free / 2.0,
+        ItemAlign::Start | ItemAlign::SelfStart => false,
+        ItemAlign::End | ItemAlign::SelfEnd => true,
+        ItemAlign::FlexEnd => !wrap_reverse,
+        _ => wrap_reverse,
+    };
+    if at_end {
+        free
+    } else {
+        0.0
+    }
+}
+
+// NaNや無限大の大きさは配分の計算を壊すので有限の値にする
+fn finite(size: f64) -> f64 {
+    if size.is_nan() {
+        0.0
+    } else {
+        size.clamp(f64::MIN, f64::MAX)
+    }
+}
+
+// CSS Flexbox 9.7 伸縮可能な長さの解決
+fn resolve_flexible_lengths(items: &mut [FlexItem], line: &[usize], main_size: f64, gap: f64) {
+    let gaps = gap * line.len().saturating_sub(1) as f64;
+    for i in line {
+        let item = &mut items[*i];
+        item.base_size = finite(item.base_size);
+        item.hypothetical_main = finite(item.hypothetical_main);
+    }
+    let hypothetical: f64 = line
+        .iter()
+        .map(|i| items[*i].hypothetical_main + items[*i].main_edges)
+        .sum();
+    let growing = hypothetical + gaps < main_size;
+
+    for i in line {
+        let item = &mut items[*i];
+        item.target_main = item.hypothetical_main;
+        item.frozen = item.flex_factor(growing) == 0.0
+            || (growing && item.base_size > item.hypothetical_main)
+            || (!growing && item.base_size < item.hypothetical_main);
+    }
+
+    let free_space = |items: &[FlexItem]| -> f64 {
+        let used: f64 = line
+            .iter()
+            .map(|i| {
+                let item = &items[*i];
+                let size = if item.frozen {
+                    item.target_main
+                } else {
+                    item.base_size
+                };
+                size + item.main_edges
+            })
+            .sum();
+        main_size - gaps - used
+    };
+    let initial_free_space = free_space(items);
+
+    // 各回で少なくとも1つのアイテムが固定されるので、アイテムの数だけ繰り返せば終わる
+    for _ in 0..line.len() {
+        let unfrozen: Vec<usize> = line.iter().copied().filter(|i| !items[*i].frozen).collect();
+        if unfrozen.is_empty() {
+            break;
+        }
+
+        let mut remaining = free_space(items);
+        let factor_sum: f64 = unfrozen
+            .iter()
+            .map(|i| items[*i].flex_factor(growing))
+            .sum();
+        if factor_sum < 1.0 {
+            let scaled = initial_free_space * factor_sum;
+            // no_stdではf64::absが使えないので2乗で比べる
+            if scaled * scaled < remaining * remaining {
+                remaining = scaled;
+            }
+        }
+
+        let scaled_shrink_sum: f64 = unfrozen
+            .iter()
+            .map(|i| items[*i].shrink * items[*i].base_size)
+            .sum();
+        for i in &unfrozen {
+            let item = &mut items[*i];
+            item.target_main = if growing {
+                item.base_size + remaining * item.grow / factor_sum
+            } else if scaled_shrink_sum > 0.0 {
+                // 縮小は基本サイズに比例させる
+                item.base_size + remaining * item.shrink * item.base_size / scaled_shrink_sum
+            } else {
+                item.base_size
+            };
+        }
+
+        // 最小値と最大値の違反を直し、違反の向きに応じてアイテムを固定する
+        let mut total_violation = 0.0;
+        let mut violations = Vec::new();
+        for i in &unfrozen {
+            let item = &mut items[*i];
+            let clamped = clamp_size(item.target_main, item.min_main, item.max_main).max(0.0);
+            let violation = clamped - item.target_main;
+            total_violation += violation;
+            violations.push(violation);
+            item.target_main = clamped;
+        }
+        for (i, violation) in unfrozen.iter().zip(violations) {
+            // 巨大な大きさで違反の合計が無限大やNaNになった場合は、残りをすべて固定する
+            items[*i].frozen = if total_violation == 0.0 || !total_violation.is_finite() {
+                true
+            } else if total_violation > 0.0 {
+                violation > 0.0
+            } else {
+                violation < 0.0
+            };
+        }
+    }
+}
+
+impl LayoutBox {
+    fn cross_auto_margins(&self, column: bool) -> (bool, bool) {
+        let margin = &self.style.margin;
+        if column {
+            (margin.left.is_auto(), margin.right.is_auto())
+        } else {
+            (margin.top.is_auto(), margin.bottom.is_auto())
+        }
+    }
+
+    fn main_auto_margins(&self, column: bool) -> (bool, bool) {
+        self.cross_auto_margins(!column)
+    }
+
+    pub(super) fn min_max_height(&self, containing_block: &ContainingBlock) -> (f64, Option<f64>) {
+        let min = resolve_height(&self.style.min_height, containing_block)
+            .map(|h| self.content_size(h, false))
+            .unwrap_or(0.0);
+        let max = self
+            .style
+            .max_height
+            .as_ref()
+            .and_then(|h| resolve_height(h, containing_block))
+            .map(|h| self.content_size(h, false));
+        (min, max)
+    }
+
+    // フレックスベースサイズと仮の主軸の大きさを求める。CSS Flexbox 9.2
+    fn flex_item(
+        &mut self,
+        index: usize,
+        container_style: &ComputedStyle,
+        containing_block: &ContainingBlock,
+        main_available: Option<f64>,
+        context: &LayoutContext,
+    ) -> FlexItem {
+        let column = container_style.flex_direction.is_column();
+        let containing_width = containing_block.width;
+        self.resolve_edges(containing_width);
+        self.dimensions.margin.left = self.style.margin.left.resolve_or_zero(containing_width);
+        self.dimensions.margin.right = self.style.margin.right.resolve_or_zero(containing_width);
+        let d = self.dimensions;
+        let horizontal_edges =
+            d.margin.horizontal() + d.padding.horizontal() + d.border.horizontal();
+        let vertical_edges = d.margin.vertical() + d.padding.vertical() + d.border.vertical();
+
+        let align = match self.style.align_self {
+            ItemAlign::Auto => container_style.align_items,
+            align => align,
+        };
+        let align = if align == ItemAlign::Normal {
+            ItemAlign::Stretch
+        } else {
+            align
+        };
+
+        let (min_width, max_width) = self.min_max_width(containing_width);
+        let mut cross = 0.0;
+        let (specified, content, min_content, min_main, max_main, min_is_auto) = if column {
+            // 交差軸の幅を先に決めてから内容の高さを求める
+            let available = (containing_width - horizontal_edges).max(0.0);
+            let (start_auto, end_auto) = self.cross_auto_margins(column);
+            let width = match self.style.width.resolve(containing_width) {
+                Some(w) => self.content_size(w, true),
+                None if align == ItemAlign::Stretch && !start_auto && !end_auto => available,
+                None => self.shrink_to_fit_width(available, context),
+            };
+            cross = clamp_size(width, min_width, max_width);
+            self.layout_block_with(
+                containing_block,
+                SizeOverride {
+                    width: Some(cross),
+                    height: None,
+                },
+                context,
+            );
+            let content = self.dimensions.content.height;
+            let specified = resolve_height(&self.style.height, containing_block)
+                .map(|h| self.content_size(h, false));
+            let (min, max) = self.min_max_height(containing_block);
+            let min_is_auto = self.style.min_height.is_auto();
+            (specified, content, content, min, max, min_is_auto)
+        } else {
+            let (min_content, max_content) = self.intrinsic_widths(context);
+            let specified = self
+                .style
+                .width
+                .resolve(containing_width)
+                .map(|w| self.content_size(w, true));
+            let min_is_auto = self.style.min_width.is_auto();
+            (
+                specified,
+                max_content,
+                min_content,
+                min_width,
+                max_width,
+                min_is_auto,
+            )
+        };
+
+        let basis = match &self.style.flex_basis {
+            FlexBasis::Auto => specified,
+            FlexBasis::Content => None,
+            FlexBasis::Length(Length::Px(px)) => Some(self.content_size(*px, !column)),
+            // パーセントは主軸の大きさが不定であれば内容に合わせる
+            FlexBasis::Length(length) => main_available
+                .and_then(|size| length.resolve(size))
+                .map(|b| self.content_size(b, !column)),
+        };
+        let base_size = basis.unwrap_or(content);
+
+        // min-width: autoの場合は内容に基づく最小サイズを使う。CSS Flexbox 4.5
+        let min_main = if min_is_auto {
+            let automatic = specified.map_or(min_content, |s| s.min(min_content));
+            match max_main {
+                Some(max) => automatic.min(max),
+                None => automatic,
+            }
+        } else {
+            min_main
+        };
+
+        FlexItem {
+            index,
+            grow: self.style.flex_grow,
+            shrink: self.style.flex_shrink,
+            base_size,
+            hypothetical_main: clamp_size(base_size, min_main, max_main),
+            min_main,
+            max_main,
+            main_edges: if column {
+                vertical_edges
+            } else {
+                horizontal_edges
+            },
+            cross_edges: if column {
+                horizontal_edges
+            } else {
+                vertical_edges
+            },
+            target_main: 0.0,
+            frozen: false,
+            cross,
+            baseline: 0.0,
+            align,
+            auto_margin: Sides::default(),
+            main_position: 0.0,
+            cross_position: 0.0,
+        }
+    }
+
+    // フレックスアイテムを配置し、内容の高さとアイテムがあるかどうかを返す。CSS Flexbox 9
+    pub(super) fn layout_flex_children(
+        &mut self,
+        containing_block: &ContainingBlock,
+        context: &LayoutContext,
+    ) -> (f64, bool) {
+        let style = self.style.clone();
+        let column = style.flex_direction.is_column();
+        let reverse = style.flex_direction.is_reverse();
+        let wrap_reverse = style.flex_wrap == FlexWrap::WrapReverse;
+        let container_width = containing_block.width;
+        let (main_available, cross_definite) = if column {
+            (containing_block.height, Some(container_width))
+        } else {
+            (Some(container_width), containing_block.height)
+        };
+        let row_gap = style
+            .row_gap
+            .resolve_or_zero(containing_block.height.unwrap_or(0.0));
+        let column_gap = style.column_gap.resolve_or_zero(container_width);
+        let (main_gap, cross_gap) = if column {
+            (row_gap, column_gap)
+        } else {
+            (column_gap, row_gap)
+        };
+        let item_containing_block = ContainingBlock {
+            x: 0.0,
+            width: container_width,
+            height: containing_block.height,
+        };
+
+        // orderの順に並べる。同じ値であれば文書順を保つ。絶対配置の子はアイテムにならない
+        self.place_absolute_children(containing_block, context);
+        let mut order: Vec<usize> = (0..self.children.len())
+            .filter(|i| !self.children[*i].is_absolutely_positioned())
+            .collect();
+        order.sort_by_key(|i| self.children[*i].style.order);
+        let mut items: Vec<FlexItem> = order
+            .into_iter()
+            .map(|index| {
+                self.children[index].flex_item(
+                    index,
+                    &style,
+                    &item_containing_block,
+                    main_available,
+                    context,
+                )
+            })
+            .collect();
+
+        // アイテムを行に分ける。CSS Flexbox 9.3
+        let single_line = style.flex_wrap == FlexWrap::Nowrap || main_available.is_none();
+        let mut lines: Vec<Vec<usize>> = Vec::new();
+        let mut current = Vec::new();
+        let mut used = 0.0;
+        for (i, item) in items.iter().enumerate() {
+            let outer = item.hypothetical_main + item.main_edges;
+            if let Some(available) = main_available {
+                if !single_line && !current.is_empty() && used + main_gap + outer > available {
+                    lines.push(core::mem::take(&mut current));
+                    used = 0.0;
+                }
+            }
+            used += if current.is_empty() {
+                outer
+            } else {
+                main_gap + outer
+            };
+            current.push(i);
+        }
+        if !current.is_empty() {
+            lines.push(current);
+        }
+        let mut lines: Vec<FlexLine> = lines
+            .into_iter()
+            .map(|items| FlexLine {
+                items,
+                cross: 0.0,
+                baseline: 0.0,
+                position: 0.0,
+            })
+            .collect();
+
+        let main_size = main_available.unwrap_or_else(|| {
+            lines
+                .iter()
+                .map(|line| {
+                    line.items
+                        .iter()
+                        .map(|i| items[*i].hypothetical_main + items[*i].main_edges)
+                        .sum::<f64>()
+                        + main_gap * line.items.len().saturating_sub(1) as f64
+                })
+                .fold(0.0, f64::max)
+        });
+        for line in &lines {
+            resolve_flexible_lengths(&mut items, &line.items, main_size, main_gap);
+        }
+
+        // 主軸の大きさでレイアウトして仮の交差軸の大きさを求める
+        for item in &mut items {
+            let child = &mut self.children[item.index];
+            let size = if column {
+                SizeOverride {
+                    width: Some(item.cross),
+                    height: Some(item.target_main),
+                }
+            } else {
+                SizeOverride {
+                    width: Some(item.target_main),
+                    height: None,
+                }
+            };
+            child.layout_block_with(&item_containing_block, size, context);
+            if !column {
+                item.cross = child.dimensions.content.height;
+            }
+            let border_box = child.dimensions.border_box();
+            item.baseline =
+                child.dimensions.margin.top + child.first_baseline().unwrap_or(border_box.height);
+        }
+
+        // 行の交差軸の大きさ。CSS Flexbox 9.4
+        for line in &mut lines {
+            if single_line {
+                if let Some(cross_size) = cross_definite {
+                    line.cross = cross_size;
+                }
+            }
+            let mut above: f64 = 0.0;
+            let mut below: f64 = 0.0;
+            let mut largest: f64 = 0.0;
+            for i in &line.items {
+                let item = &items[*i];
+                if !column && item.align == ItemAlign::Baseline {
+                    above = above.max(item.baseline);
+                    below = below.max(item.outer_cross() - item.baseline);
+                } else {
+                    largest = largest.max(item.outer_cross());
+                }
+            }
+            line.baseline = above;
+            if !(single_line && cross_definite.is_some()) {
+                line.cross = largest.max(above + below);
+            }
+        }
+        let line_gaps = cross_gap * lines.len().saturating_sub(1) as f64;
+        if !single_line {
+            if let Some(cross_size) = cross_definite {
+                let free = cross_size - lines.iter().map(|l| l.cross).sum::<f64>() - line_gaps;
+                if free > 0.0
+                    && matches!(
+                        style.align_content,
+                        ContentAlign::Normal | ContentAlign::Stretch
+                    )
+                {
+                    let extra = free / lines.len() as f64;
+                    for line in &mut lines {
+                        line.cross += extra;
+                    }
+                }
+            }
+        }
+
+        // align-self: stretchのアイテムを行の大きさに伸ばす
+        for line in &lines {
+            for i in &line.items {
+                let item = &mut items[*i];
+                let child = &mut self.children[item.index];
+                let cross_is_auto = if column {
+                    child.style.width.is_auto()
+                } else {
+                    child.style.height.is_auto()
+                };
+                let (start_auto, end_auto) = child.cross_auto_margins(column);
+                if item.align != ItemAlign::Stretch || !cross_is_auto || start_auto || end_auto {
+                    continue;
+                }
+                let (min, max) = if column {
+                    child.min_max_width(container_width)
+                } else {
+                    child.min_max_height(&item_containing_block)
+                };
+                item.cross = clamp_size((line.cross - item.cross_edges).max(0.0), min, max);
+                let size = if column {
+                    SizeOverride {
+                        width: Some(item.cross),
+                        height: Some(item.target_main),
+                    }
+                } else {
+                    SizeOverride {
+                        width: Some(item.target_main),
+                        height: Some(item.cross),
+                    }
+                };
+                child.layout_block_with(&item_containing_block, size, context);
+            }
+        }
+
+        // 主軸方向の配置。CSS Flexbox 9.5
+        for line in &lines {
+            let count = line.items.len();
+            let used: f64 = line
+                .items
+                .iter()
+                .map(|i| items[*i].outer_main())
+                .sum::<f64>()
+                + main_gap * count.saturating_sub(1) as f64;
+            let free = main_size - used;
+            let auto_count: usize = line
+                .items
+                .iter()
+                .map(|i| {
+                    let (start, end) = self.children[items[*i].index].main_auto_margins(column);
+                    start as usize + end as usize
+                })
+                .sum();
+            let (mut position, between) = if auto_count > 0 && free > 0.0 {
+                (0.0, 0.0)
+            } else {
+                let justify = flex_relative(style.justify_content, reverse);
+                distribute(justify, free, count)
+            };
+            let auto_size = if auto_count > 0 && free > 0.0 {
+                free / auto_count as f64
+            } else {
+                0.0
+            };
+            for i in &line.items {
+                let item = &mut items[*i];
+                let (start_auto, end_auto) = self.children[item.index].main_auto_margins(column);
+                let (start, end) = (
+                    if start_auto { auto_size } else { 0.0 },
+                    if end_auto { auto_size } else { 0.0 },
+                );
+                if column {
+                    item.auto_margin.top = start;
+                    item.auto_margin.bottom = end;
+                } else {
+                    item.auto_margin.left = start;
+                    item.auto_margin.right = end;
+                }
+                let outer = item.outer_main() + start + end;
+                item.main_position = if reverse {
+                    main_size - position - outer
+                } else {
+                    position
+                };
+                position += outer + main_gap + between;
+            }
+        }
+
+        // 行と交差軸方向の配置。CSS Flexbox 9.6
+        let total_cross = lines.iter().map(|l| l.cross).sum::<f64>() + line_gaps;
+        let cross_size = cross_definite.unwrap_or(total_cross);
+        let (mut position, between) = if single_line {
+            (0.0, 0.0)
+        } else {
+            let align = flex_relative(style.align_content, wrap_reverse);
+            distribute(align, cross_size - total_cross, lines.len())
+        };
+        for line in &mut lines {
+            line.position = if wrap_reverse {
+                cross_size - position - line.cross
+            } else {
+                position
+            };
+            position += line.cross + cross_gap + between;
+
+            for i in &line.items {
+                let item = &mut items[*i];
+                let free = line.cross - item.outer_cross();
+                let (start_auto, end_auto) = self.children[item.index].cross_auto_margins(column);
+                let (offset, start, end) = if (start_auto || end_auto) && free > 0.0 {
+                    match (start_auto, end_auto) {
+                        (true, true) => (0.0, free / 2.0, free / 2.0),
+                        (true, false) => (0.0, free, 0.0),
+                        _ => (0.0, 0.0, free),
+                    }
+                } else if !column && item.align == ItemAlign::Baseline {
+                    (line.baseline - item.baseline, 0.0, 0.0)
+                } else {
+                    (item_offset(item.align, free, wrap_reverse), 0.0, 0.0)
+                };
+                if column {
+                    item.auto_margin.left = start;
+                    item.auto_margin.right = end;
+                } else {
+                    item.auto_margin.top = start;
+                    item.auto_margin.bottom = end;
+                }
+                item.cross_position = line.position + offset;
+            }
+        }
+
+        // マージンボックスの左上が求めた位置になるように移動する
+        let content_x = containing_block.x;
+        let content_y = self.dimensions.content.y;
+        for item in &items {
+            let child = &mut self.children[item.index];
+            let margin = &mut child.dimensions.margin;
+            margin.top += item.auto_margin.top;
+            margin.right += item.auto_margin.right;
+            margin.bottom += item.auto_margin.bottom;
+            margin.left += item.auto_margin.left;
+            let (x, y) = if column {
+                (item.cross_position, item.main_position)
+            } else {
+                (item.main_position, item.cross_position)
+            };
+            let border_box = child.dimensions.border_box();
+            child.translate(
+                content_x + x + child.dimensions.margin.left - border_box.x,
+                content_y + y + child.dimensions.margin.top - border_box.y,
+            );
+        }
+
+        let height = if column { main_size } else { cross_size };
+        (height, !items.is_empty())
+    }
+
+    // フレックスコンテナの最小内容幅と最大内容幅
+    pub(super) fn flex_intrinsic_widths(&self, context: &LayoutContext) -> (f64, f64) {
+        let contributions: Vec<(f64, f64)> = self
+            .children
+            .iter()
+            .filter(|child| !child.is_absolutely_positioned())
+            .map(|child| child.intrinsic_contribution(context))
+            .collect();
+        let largest = |values: &mut dyn Iterator<Item = f64>| values.fold(0.0, f64::max);
+        if self.style.flex_direction.is_column() {
+            return (
+                largest(&mut contributions.iter().map(|c| c.0)),
+                largest(&mut contributions.iter().map(|c| c.1)),
+            );
+        }
+
+        let gaps = self.style.column_gap.resolve_or_zero(0.0)
+            * contributions.len().saturating_sub(1) as f64;
+        let max_content = contributions.iter().map(|c| c.1).sum::<f64>() + gaps;
+        let min_content = if self.style.flex_wrap == FlexWrap::Nowrap {
+            contributions.iter().map(|c| c.0).sum::<f64>() + gaps
+        } else {
+            largest(&mut contributions.iter().map(|c| c.0))
+        };
+        (min_content, max_content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::layout::{geometry::Rect, layout_document};
+    use alloc::{string::ToString, vec};
+
+    // 3つのアイテムを持つフレックスコンテナのアイテムのボーダーボックス
+    fn item_boxes(css: &str) -> Vec<Rect> {
+        let (_, view) = layout_document(
+            "<html><body><p><a class=\"a\">x</a><a class=\"b\">y</a><a class=\"c\">z</a></p></body></html>",
+            &("body, p { margin: 0 } p { display: flex } ".to_string() + css),
+        );
+        let body = &view.root().unwrap().children()[0];
+        let p = &body.children()[0];
+        p.children()
+            .iter()
+            .map(|item| item.dimensions().border_box())
+            .collect()
+    }
+
+    fn widths(boxes: &[Rect]) -> Vec<f64> {
+        boxes.iter().map(|b| b.width).collect()
+    }
+
+    fn positions(boxes: &[Rect]) -> Vec<(f64, f64)> {
+        boxes.iter().map(|b| (b.x, b.y)).collect()
+    }
+
+    #[test]
+    fn test_flexible_lengths() {
+        let boxes = item_boxes(
+            "p { width: 300px } .a { flex: 1 } .b { flex: 2 } .c { flex: none; width: 12px }",
+        );
+        assert_eq!(vec![96.0, 192.0, 12.0], widths(&boxes));
+        assert_eq!(
+            vec![(0.0, 0.0), (96.0, 0.0), (288.0, 0.0)],
+            positions(&boxes)
+        );
+
+        // 縮小は基本サイズとflex-shrinkの積に比例する
+        let boxes = item_boxes(
+            "p { width: 300px } a { width: 200px } .b { flex-shrink: 3 } .c { flex-shrink: 0; width: 50px }",
+        );
+        assert_eq!(vec![162.5, 87.5, 50.0], widths(&boxes));
+
+        // 最大値で固定されたアイテムの分は他のアイテムに配分される
+        let boxes = item_boxes(
+            "p { width: 300px } a { flex: 1 } .a { max-width: 50px } .c { min-width: 200px }",
+        );
+        assert_eq!(vec![50.0, 50.0, 200.0], widths(&boxes));
+    }
+
+    #[test]
+    fn test_infinite_sizes() {
+        // 無限大になる大きさでも配分が終わり、有限の大きさになる
+        let boxes = item_boxes(
+            "p { width: 300px } .a { width: calc(1px / 0) } .b { width: calc(1e308px * 10) } .c { flex-grow: 1; width: calc(-1px / 0) }",
+        );
+        assert!(widths(&boxes).iter().all(|w| w.is_finite()));
+        let boxes = item_boxes(".a, .b { flex-basis: calc(1px / 0); flex-grow: 1 }");
+        assert!(widths(&boxes).iter().all(|w| w.is_finite()));
+    }
+
+    #[test]
+    fn test_wrap_and_justify_content() {
+        let css =
+            "p { flex-wrap: wrap; width: 100px; gap: 5px 10px } a { width: 40px; height: 20px }";
+        let boxes = item_boxes(css);
+        assert_eq!(
+            vec![(0.0, 0.0), (50.0, 0.0), (0.0, 25.0)],
+            positions(&boxes)
+        );
+
+        let boxes = item_boxes(&(css.to_string() + " p { justify-content: center }"));
+        assert_eq!(
+            vec![(5.0, 0.0), (55.0, 0.0), (30.0, 25.0)],
+            positions(&boxes)
+        );
+        let boxes = item_boxes(&(css.to_string() + " p { justify-content: space-between }"));
+        assert_eq!(
+            vec![(0.0, 0.0), (60.0, 0.0), (0.0, 25.0)],
+            positions(&boxes)
+        );
+        let boxes = item_boxes(&(css.to_string() + " p { flex-wrap: wrap-reverse }"));
+        assert_eq!(
+            vec![(0.0, 25.0), (50.0, 25.0), (0.0, 0.0)],
+            positions(&boxes)
+        );
+        let boxes = item_boxes(&(css.to_string() + " .b { margin-left: auto }"));
+        assert_eq!(
+            vec![(0.0, 0.0), (60.0, 0.0), (0.0, 25.0)],
+            positions(&boxes)
+        );
+    }
+
+    #[test]
+    fn test_align_items() {
+        let css = "p { height: 100px; line-height: 20px } a { width: 10px } ";
+        let boxes = item_boxes(css);
+        assert_eq!(
+            vec![100.0, 100.0, 100.0],
+            boxes.iter().map(|b| b.height).collect::<Vec<_>>()
+        );
+
+        let boxes = item_boxes(
+            &(css.to_string()
+                + "p { align-items: center } .b { align-self: flex-end } .c { align-self: stretch; height: 30px }"),
+        );
+        assert_eq!(
+            vec![(0.0, 40.0), (10.0, 80.0), (20.0, 0.0)],
+            positions(&boxes)
+        );
+        assert_eq!(30.0, boxes[2].height);
+
+        let boxes = item_boxes(
+            &(css.to_string()
+                + "p { align-items: baseline } .b { padding-top: 15px } .c { margin-top: 5px }"),
+        );
+        assert_eq!(
+            vec![(0.0, 15.0), (10.0, 0.0), (20.0, 15.0)],
+            positions(&boxes)
+        );
+    }
+
+    #[test]
+    fn test_column_and_order() {
+        let css = "p { flex-direction: column; line-height: 20px } .c { height: 30px }";
+        let boxes = item_boxes(css);
+        assert_eq!(
+            vec![(0.0, 0.0), (0.0, 20.0), (0.0, 40.0)],
+            positions(&boxes)
+        );
+        assert_eq!(600.0, boxes[0].width);
+
+        let boxes = item_boxes(&(css.to_string() + " p { align-items: flex-end } .a { order: 1 }"));
+        assert_eq!(
+            vec![(592.0, 50.0), (592.0, 0.0), (592.0, 20.0)],
+            positions(&boxes)
+        );
+
+        let boxes =
+            item_boxes(&(css.to_string() + " p { flex-direction: column-reverse; height: 100px }"));
+        assert_eq!(
+            vec![(0.0, 80.0), (0.0, 60.0), (0.0, 30.0)],
+            positions(&boxes)
+        );
+    }
+
+    #[test]
+    fn test_inline_flex_intrinsic_width() {
+        let (_, view) = layout_document(
+            "<html><body><p><a>aa bb<h1>cc</h1><h2>d</h2></a></p></body></html>",
+            "body, p, h1, h2 { margin: 0 } p, h1, h2 { font-size: 10px } a { display: inline-flex; column-gap: 5px } h1, h2 { display: block }",
+        );
+        let body = &view.root().unwrap().children()[0];
+        let a = &body.children()[0].children()[0];
+        assert_eq!(50.0, a.dimensions().content.width);
+        assert_eq!(
+            vec![25.0, 10.0, 5.0],
+            a.children()
+                .iter()
+                .map(|item| item.dimensions().border_box().width)
+                .collect::<Vec<_>>()
+        );
+    }
+}
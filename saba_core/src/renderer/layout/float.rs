@@ -0,0 +1,265 @@
+use crate::renderer::{
+    css::computed_style::{Clear, Float},
+    layout::{
+        block::ContainingBlock, geometry::Rect, layout_box::LayoutBox, layout_view::LayoutContext,
+    },
+};
+use alloc::vec::Vec;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct PlacedFloat {
+    side: Float,
+    // マージンボックス
+    rect: Rect,
+}
+
+// ブロック整形文脈に置かれたフロート。y座標は整形文脈のルートのボーダーボックスの上端を0とする
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FloatContext {
+    floats: Vec<PlacedFloat>,
+    // レイアウト中のボックスのボーダーボックスの上端の位置
+    origin: f64,
+}
+
+// 高さのない帯は上端の位置だけで判定する
+fn overlaps(rect: &Rect, y: f64, height: f64) -> bool {
+    if height <= 0.0 {
+        rect.y <= y && y < rect.bottom()
+    } else {
+        rect.y < y + height && y < rect.bottom()
+    }
+}
+
+impl FloatContext {
+    pub fn origin(&self) -> f64 {
+        self.origin
+    }
+
+    pub fn set_origin(&mut self, origin: f64) {
+        self.origin = origin;
+    }
+
+    // [y, y + height)の帯でフロートを避けた左端と右端
+    pub fn available(&self, y: f64, height: f64, left: f64, right: f64) -> (f64, f64) {
+        let mut left = left;
+        let mut right = right;
+        for float in self.floats.iter().filter(|f| overlaps(&f.rect, y, height)) {
+            match float.side {
+                Float::Left => left = left.max(float.rect.right()),
+                Float::Right => right = right.min(float.rect.x),
+                Float::None => {}
+            }
+        }
+        (left, right)
+    }
+
+    // y以降で幅widthが収まる最初の帯の位置と、その帯の左端と右端
+    pub fn find_band(
+        &self,
+        y: f64,
+        height: f64,
+        width: f64,
+        left: f64,
+        right: f64,
+    ) -> (f64, f64, f64) {
+        let mut y = y;
+        loop {
+            let (band_left, band_right) = self.available(y, height, left, right);
+            // 重なるフロートの下端のうち最も上にあるものまで下げて試す
+            let next = self
+                .floats
+                .iter()
+                .filter(|f| overlaps(&f.rect, y, height))
+                .map(|f| f.rect.bottom())
+                .fold(None, |next: Option<f64>, bottom| {
+                    Some(next.map_or(bottom, |n| n.min(bottom)))
+                });
+            match next {
+                Some(next) if band_right - band_left < width => y = next,
+                _ => return (y, band_left, band_right),
+            }
+        }
+    }
+
+    // 指定した側のフロートの下端。CSS 2.1 9.5.2
+    pub fn clearance(&self, clear: Clear) -> Option<f64> {
+        self.floats
+            .iter()
+            .filter(|f| match clear {
+                Clear::None => false,
+                Clear::Left => f.side == Float::Left,
+                Clear::Right => f.side == Float::Right,
+                Clear::Both => true,
+            })
+            .map(|f| f.rect.bottom())
+            .fold(None, |bottom: Option<f64>, b| {
+                Some(bottom.map_or(b, |c| c.max(b)))
+            })
+    }
+
+    // すべてのフロートの下端
+    pub fn bottom(&self) -> Option<f64> {
+        self.clearance(Clear::Both)
+    }
+
+    // マージンボックスの大きさが(width, height)のフロートをyより下に置き、左上の位置を返す。CSS 2.1 9.5.1
+    #[allow(clippy::too_many_arguments)]
+    pub fn place(
+        &mut self,
+        side: Float,
+        clear: Clear,
+        width: f64,
+        height: f64,
+        y: f64,
+        left: f64,
+        right: f64,
+    ) -> (f64, f64) {
+        // 先に置かれたフロートより上には置かない
+        let mut y = self.floats.iter().map(|f| f.rect.y).fold(y, f64::max);
+        if let Some(bottom) = self.clearance(clear) {
+            y = y.max(bottom);
+        }
+        let (y, band_left, band_right) = self.find_band(y, height, width, left, right);
+        let x = match side {
+            Float::Right => band_right - width,
+            _ => band_left,
+        };
+        if height > 0.0 {
+            self.floats.push(PlacedFloat {
+                side,
+                rect: Rect::new(x, y, width, height),
+            });
+        }
+        (x, y)
+    }
+}
+
+impl LayoutBox {
+    pub fn is_float(&self) -> bool {
+        self.style.float != Float::None
+    }
+
+    // 通常フローから取り除かれるボックス
+    pub fn is_out_of_flow(&self) -> bool {
+        self.is_float() || self.is_absolutely_positioned()
+    }
+
+    // フロートをレイアウトし、整形文脈のルートから見た位置yより下でほかのフロートを避けて置く
+    pub(super) fn layout_float(
+        &mut self,
+        containing_block: &ContainingBlock,
+        y: f64,
+        context: &LayoutContext,
+    ) {
+        self.layout_block(containing_block, context);
+        let margin_box = self.dimensions.margin_box();
+        let mut floats = context.floats.borrow_mut();
+        let (x, top) = floats.place(
+            self.style.float,
+            self.style.clear,
+            margin_box.width,
+            margin_box.height,
+            y,
+            containing_block.x,
+            containing_block.x + containing_block.width,
+        );
+        let origin = floats.origin();
+        drop(floats);
+        self.translate(x - margin_box.x, top - origin - margin_box.y);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::layout::{layout_document, layout_view::LayoutView};
+    use alloc::vec;
+
+    fn body(view: &LayoutView) -> &LayoutBox {
+        &view.root().expect("failed to get root box").children()[0]
+    }
+
+    #[test]
+    fn test_place_floats() {
+        let mut floats = FloatContext::default();
+        assert_eq!(
+            (0.0, 0.0),
+            floats.place(Float::Left, Clear::None, 100.0, 50.0, 0.0, 0.0, 300.0)
+        );
+        assert_eq!(
+            (250.0, 0.0),
+            floats.place(Float::Right, Clear::None, 50.0, 20.0, 0.0, 0.0, 300.0)
+        );
+        // 左右のフロートの間に収まらなければ右のフロートの下に置く
+        assert_eq!(
+            (100.0, 20.0),
+            floats.place(Float::Left, Clear::None, 180.0, 10.0, 0.0, 0.0, 300.0)
+        );
+        assert_eq!(
+            (0.0, 50.0),
+            floats.place(Float::Left, Clear::Left, 10.0, 10.0, 0.0, 0.0, 300.0)
+        );
+        assert_eq!((280.0, 300.0), floats.available(25.0, 10.0, 0.0, 300.0));
+        assert_eq!(Some(60.0), floats.bottom());
+    }
+
+    #[test]
+    fn test_float_shrinks_line_boxes() {
+        let (_, view) = layout_document(
+            "<html><body><p><a class=\"left\">L</a>aaaaaa bbbbbb cccccc dddddd</p></body></html>",
+            "body, p { margin: 0 } p { width: 100px; font-size: 10px; line-height: 20px } .left { float: left; width: 40px; height: 30px }",
+        );
+        let p = &body(&view).children()[0];
+        let left = p.children()[0].dimensions().margin_box();
+        assert_eq!(Rect::new(0.0, 0.0, 40.0, 30.0), left);
+        // フロートと重なる2行は幅が60pxになる
+        let lines: Vec<(f64, f64)> = p.lines().iter().map(|l| (l.rect.x, l.rect.width)).collect();
+        assert_eq!(vec![(40.0, 60.0), (40.0, 60.0), (0.0, 100.0)], lines);
+        let texts: Vec<&str> = p.children()[1]
+            .fragments()
+            .iter()
+            .map(|f| f.text.as_str())
+            .collect();
+        assert_eq!(vec!["aaaaaa", "bbbbbb", "cccccc dddddd"], texts);
+        // フロートはブロックの高さに含まれない
+        assert_eq!(60.0, p.dimensions().content.height);
+    }
+
+    #[test]
+    fn test_floats_between_blocks() {
+        let (_, view) = layout_document(
+            "<html><body><p class=\"right\">R</p><p class=\"text\">aaaa</p><p class=\"clear\">c</p><p class=\"root\">r</p></body></html>",
+            "body, p { margin: 0 } p { font-size: 10px; line-height: 20px } .right { float: right; width: 100px; height: 50px; margin: 5px } .clear { clear: right; margin-top: 10px } .root { display: flow-root; float: left; clear: both }",
+        );
+        let body = body(&view);
+        let right = body.children()[0].dimensions().margin_box();
+        assert_eq!(Rect::new(490.0, 0.0, 110.0, 60.0), right);
+        // 後続のブロックはフロートの横を流れ、行だけが短くなる
+        let text = &body.children()[1];
+        assert_eq!(0.0, text.dimensions().border_box().y);
+        assert_eq!(600.0, text.dimensions().content.width);
+        assert_eq!(490.0, text.lines()[0].rect.width);
+        // クリアランスでフロートの下に移動する
+        let clear = body.children()[2].dimensions().border_box();
+        assert_eq!(60.0, clear.y);
+        let root = body.children()[3].dimensions().margin_box();
+        assert_eq!((0.0, 80.0), (root.x, root.y));
+        assert_eq!(5.0, root.width);
+    }
+
+    #[test]
+    fn test_block_formatting_context_contains_floats() {
+        let (_, view) = layout_document(
+            "<html><body><p class=\"left\">L</p><p class=\"root\">aa</p><p class=\"outer\"><a class=\"inner\">b</a></p></body></html>",
+            "body, p { margin: 0 } p { font-size: 10px; line-height: 20px } .left { float: left; width: 100px; height: 30px } .root { display: flow-root } .outer { display: flow-root; clear: left } .inner { float: left; height: 50px }",
+        );
+        let body = body(&view);
+        // 新しいブロック整形文脈はフロートと重ならないように狭くなる
+        let root = body.children()[1].dimensions();
+        assert_eq!((100.0, 500.0), (root.content.x, root.content.width));
+        // 中のフロートの下端までが高さになる
+        let outer = body.children()[2].dimensions();
+        assert_eq!(30.0, outer.border_box().y);
+        assert_eq!(50.0, outer.content.height);
+    }
+}
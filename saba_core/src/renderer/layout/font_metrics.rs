@@ -0,0 +1,106 @@
+use crate::renderer::css::computed_style::ComputedStyle;
+
+// インラインレイアウトが文字の大きさを知るためのインターフェース。フォントの実装ごとに用意する
+pub trait FontMetrics {
+    // 文字の送り幅
+    fn advance(&self, c: char, style: &ComputedStyle) -> f64;
+
+    // ベースラインから上端までの高さ
+    fn ascent(&self, style: &ComputedStyle) -> f64;
+
+    // ベースラインから下端までの深さ
+    fn descent(&self, style: &ComputedStyle) -> f64;
+
+    // 2つの文字の間に加える調整量
+    fn kerning(&self, _left: char, _right: char, _style: &ComputedStyle) -> f64 {
+        0.0
+    }
+
+    fn text_width(&self, text: &str, style: &ComputedStyle) -> f64 {
+        let mut width = 0.0;
+        let mut previous = None;
+        for c in text.chars() {
+            if let Some(previous) = previous {
+                width += self.kerning(previous, c, style);
+            }
+            width += self.advance(c, style);
+            previous = Some(c);
+        }
+        width
+    }
+}
+
+// 幅を持たない結合文字やゼロ幅の文字
+pub fn is_zero_width(c: char) -> bool {
+    matches!(
+        c,
+        '\u{200b}' | '\u{200c}' | '\u{200d}' | '\u{2060}' | '\u{feff}' | '\u{0300}'..='\u{036f}'
+    )
+}
+
+// すべての文字がフォントサイズの半分の幅を持つとみなす等幅フォント
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FixedWidthFont;
+
+impl FontMetrics for FixedWidthFont {
+    fn advance(&self, c: char, style: &ComputedStyle) -> f64 {
+        match c {
+            c if is_zero_width(c) => 0.0,
+            // 全角の文字
+            '\u{1100}'..='\u{115f}'
+            | '\u{2e80}'..='\u{a4cf}'
+            | '\u{ac00}'..='\u{d7a3}'
+            | '\u{f900}'..='\u{faff}'
+            | '\u{fe30}'..='\u{fe4f}'
+            | '\u{ff00}'..='\u{ff60}'
+            | '\u{ffe0}'..='\u{ffe6}' => style.font_size,
+            _ => style.font_size / 2.0,
+        }
+    }
+
+    fn ascent(&self, style: &ComputedStyle) -> f64 {
+        style.font_size * 0.8
+    }
+
+    fn descent(&self, style: &ComputedStyle) -> f64 {
+        style.font_size * 0.2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Kerned;
+
+    impl FontMetrics for Kerned {
+        fn advance(&self, _c: char, _style: &ComputedStyle) -> f64 {
+            10.0
+        }
+
+        fn ascent(&self, _style: &ComputedStyle) -> f64 {
+            8.0
+        }
+
+        fn descent(&self, _style: &ComputedStyle) -> f64 {
+            2.0
+        }
+
+        fn kerning(&self, left: char, right: char, _style: &ComputedStyle) -> f64 {
+            if left == 'A' && right == 'V' {
+                -2.0
+            } else {
+                0.0
+            }
+        }
+    }
+
+    #[test]
+    fn test_text_width() {
+        let style = ComputedStyle::default();
+        assert_eq!(24.0, FixedWidthFont.text_width("abc", &style));
+        assert_eq!(40.0, FixedWidthFont.text_width("日本a", &style));
+        assert_eq!(28.0, Kerned.text_width("AVA", &style));
+        assert_eq!(0.0, Kerned.text_width("", &style));
+    }
+}
@@ -0,0 +1,107 @@
+use crate::renderer::css::computed_style::Sides;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Rect {
+    pub fn new(x: f64, y: f64, width: f64, height: f64) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn right(&self) -> f64 {
+        self.x + self.width
+    }
+
+    pub fn bottom(&self) -> f64 {
+        self.y + self.height
+    }
+
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        self.x <= x && x < self.right() && self.y <= y && y < self.bottom()
+    }
+
+    pub fn expanded_by(&self, edge: &Sides<f64>) -> Rect {
+        Rect {
+            x: self.x - edge.left,
+            y: self.y - edge.top,
+            width: self.width + edge.left + edge.right,
+            height: self.height + edge.top + edge.bottom,
+        }
+    }
+
+    pub fn translate(&mut self, dx: f64, dy: f64) {
+        self.x += dx;
+        self.y += dy;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width <= 0.0 || self.height <= 0.0
+    }
+
+    // 両方に含まれる部分。重ならなければ大きさ0の矩形になる
+    pub fn intersection(&self, other: &Rect) -> Rect {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        Rect {
+            x,
+            y,
+            width: (self.right().min(other.right()) - x).max(0.0),
+            height: (self.bottom().min(other.bottom()) - y).max(0.0),
+        }
+    }
+
+    pub fn intersects(&self, other: &Rect) -> bool {
+        !self.intersection(other).is_empty()
+    }
+
+    // 両方を含む最小の矩形。大きさのない矩形は無視する
+    pub fn union(&self, other: &Rect) -> Rect {
+        if other.is_empty() {
+            return *self;
+        }
+        if self.is_empty() {
+            return *other;
+        }
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        Rect {
+            x,
+            y,
+            width: self.right().max(other.right()) - x,
+            height: self.bottom().max(other.bottom()) - y,
+        }
+    }
+}
+
+// CSSボックスモデルの各領域。座標はページの左上を原点とする
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Dimensions {
+    pub content: Rect,
+    pub padding: Sides<f64>,
+    pub border: Sides<f64>,
+    pub margin: Sides<f64>,
+}
+
+impl Dimensions {
+    pub fn padding_box(&self) -> Rect {
+        self.content.expanded_by(&self.padding)
+    }
+
+    pub fn border_box(&self) -> Rect {
+        self.padding_box().expanded_by(&self.border)
+    }
+
+    pub fn margin_box(&self) -> Rect {
+        self.border_box().expanded_by(&self.margin)
+    }
+}
@@ -0,0 +1,1224 @@
+use crate::renderer::{
+    css::{
+        computed_style::{ComputedStyle, ContentAlign, ItemAlign},
+        grid::{
+            GridAutoFlow, GridLine, GridTemplateAreas, RepeatCount, TrackBreadth, TrackListItem,
+            TrackSize,
+        },
+        value::Length,
+    },
+    layout::{
+        block::{clamp_size, ContainingBlock, SizeOverride},
+        flex::{distribute, item_offset},
+        layout_box::LayoutBox,
+        layout_view::LayoutContext,
+    },
+};
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::ops::Range;
+
+// 軸ごとの値を[行, 列]の配列で持つ
+const ROW: usize = 0;
+const COLUMN: usize = 1;
+
+// 暗黙のトラックを含めた線の番号で表したグリッド領域。終わりの線は含まない
+type GridArea = [(usize, usize); 2];
+
+// 線の番号は明示的なグリッドの最初の線を0とし、負の値はそれより前の暗黙の線
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Placement {
+    Definite(i32, i32),
+    // 自動配置するアイテムが占めるトラックの数
+    Auto(usize),
+}
+
+impl Placement {
+    fn span(&self) -> usize {
+        match self {
+            Placement::Definite(start, end) => (end - start) as usize,
+            Placement::Auto(span) => *span,
+        }
+    }
+}
+
+// 明示的なグリッドの1方向分
+#[derive(Clone, Debug)]
+struct ExplicitGrid {
+    tracks: Vec<TrackSize>,
+    // 各線の名前。線の数はトラックの数より1つ多い
+    names: Vec<Vec<String>>,
+    // auto-fitで繰り返したトラックの位置
+    auto_fit: Vec<usize>,
+}
+
+impl ExplicitGrid {
+    fn new(list: &[TrackListItem], available: Option<f64>, gap: f64) -> Self {
+        let repetitions = repeat_count(list, available, gap);
+        let mut grid = Self {
+            tracks: Vec::new(),
+            names: vec![Vec::new()],
+            auto_fit: Vec::new(),
+        };
+        for item in list {
+            match item {
+                TrackListItem::Repeat(count, items) => {
+                    let times = match count {
+                        RepeatCount::Count(n) => *n,
+                        _ => repetitions,
+                    };
+                    for _ in 0..times {
+                        let start = grid.tracks.len();
+                        grid.push(items);
+                        if *count == RepeatCount::AutoFit {
+                            grid.auto_fit.extend(start..grid.tracks.len());
+                        }
+                    }
+                }
+                item => grid.push(core::slice::from_ref(item)),
+            }
+        }
+        grid
+    }
+
+    fn push(&mut self, items: &[TrackListItem]) {
+        for item in items {
+            match item {
+                TrackListItem::LineNames(names) => {
+                    if let Some(last) = self.names.last_mut() {
+                        last.extend(names.iter().cloned());
+                    }
+                }
+                TrackListItem::Track(size) => {
+                    self.tracks.push(size.clone());
+                    self.names.push(Vec::new());
+                }
+                // repeat()は入れ子にできない
+                TrackListItem::Repeat(_, _) => {}
+            }
+        }
+    }
+
+    // grid-template-areasの大きさまで明示的なグリッドを広げ、領域の名前から暗黙の線の名前を作る
+    fn add_areas(&mut self, areas: &GridTemplateAreas, column: bool) {
+        let count = if column { areas.columns } else { areas.rows };
+        while self.names.len() < count + 1 {
+            self.names.push(Vec::new());
+        }
+        for area in &areas.areas {
+            let (start, end) = if column { area.columns } else { area.rows };
+            self.names[start].push(area.name.clone() + "-start");
+            self.names[end].push(area.name.clone() + "-end");
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.names.len() - 1
+    }
+
+    fn named_lines(&self, name: &str) -> Vec<i32> {
+        self.names
+            .iter()
+            .enumerate()
+            .filter(|(_, names)| names.iter().any(|n| n == name))
+            .map(|(i, _)| i as i32)
+            .collect()
+    }
+
+    // n番目の線。名前の付いた線が足りなければ明示的なグリッドの外の暗黙の線を数える
+    fn line(&self, n: i32, name: Option<&str>) -> i32 {
+        let explicit = self.len() as i32;
+        let name = match name {
+            Some(name) => name,
+            None if n > 0 => return n - 1,
+            None => return explicit + 1 + n,
+        };
+        let lines = self.named_lines(name);
+        let count = lines.len() as i32;
+        if n > 0 {
+            if n <= count {
+                lines[(n - 1) as usize]
+            } else {
+                explicit + n - count
+            }
+        } else if -n <= count {
+            lines[(count + n) as usize]
+        } else {
+            -(-n - count)
+        }
+    }
+
+    // 始まりの線では"名前-start"、終わりの線では"名前-end"の線を優先する
+    fn line_by_name(&self, name: &str, suffix: &str) -> i32 {
+        let implicit = name.to_string() + suffix;
+        if let Some(line) = self.named_lines(&implicit).first() {
+            return *line;
+        }
+        self.line(1, Some(name))
+    }
+
+    // fromから線をn本数えた位置
+    fn span_from(&self, from: i32, n: u32, name: Option<&str>, forward: bool) -> i32 {
+        let n = n as i32;
+        let name = match name {
+            Some(name) => name,
+            None if forward => return from + n,
+            None => return from - n,
+        };
+        let mut lines = self.named_lines(name);
+        if forward {
+            lines.retain(|line| *line > from);
+        } else {
+            lines.retain(|line| *line < from);
+            lines.reverse();
+        }
+        let count = lines.len() as i32;
+        if n <= count {
+            lines[(n - 1) as usize]
+        } else if forward {
+            from.max(self.len() as i32) + n - count
+        } else {
+            from.min(0) - (n - count)
+        }
+    }
+
+    fn definite_line(&self, line: &GridLine, suffix: &str) -> Option<i32> {
+        match line {
+            GridLine::Line(n, name) => Some(self.line(*n, name.as_deref())),
+            GridLine::Name(name) => Some(self.line_by_name(name, suffix)),
+            GridLine::Auto | GridLine::Span(_, _) => None,
+        }
+    }
+
+    // CSS Grid 8.3.1 配置の衝突の解決
+    fn resolve(&self, start: &GridLine, end: &GridLine) -> Placement {
+        let start_line = self.definite_line(start, "-start");
+        let end_line = self.definite_line(end, "-end");
+        match (start_line, end_line) {
+            (Some(s), Some(e)) if e < s => Placement::Definite(e, s),
+            (Some(s), Some(e)) if e == s => Placement::Definite(s, s + 1),
+            (Some(s), Some(e)) => Placement::Definite(s, e),
+            (Some(s), None) => match end {
+                GridLine::Span(n, name) => {
+                    Placement::Definite(s, self.span_from(s, *n, name.as_deref(), true))
+                }
+                _ => Placement::Definite(s, s + 1),
+            },
+            (None, Some(e)) => match start {
+                GridLine::Span(n, name) => {
+                    Placement::Definite(self.span_from(e, *n, name.as_deref(), false), e)
+                }
+                _ => Placement::Definite(e - 1, e),
+            },
+            // 自動配置では名前の付いたspanは1として扱う
+            (None, None) => match (start, end) {
+                (GridLine::Span(n, None), _) | (_, GridLine::Span(n, None)) => {
+                    Placement::Auto(*n as usize)
+                }
+                _ => Placement::Auto(1),
+            },
+        }
+    }
+}
+
+// 大きさの決まったトラックだけで計算する
+fn fixed_track_size(size: &TrackSize, available: Option<f64>) -> f64 {
+    let fixed = |breadth: &TrackBreadth| match breadth {
+        TrackBreadth::Length(length) => resolve_length(length, available),
+        _ => None,
+    };
+    fixed(&size.max).or_else(|| fixed(&size.min)).unwrap_or(0.0)
+}
+
+// 自動の繰り返しの回数。CSS Grid 7.2.3.2
+fn repeat_count(list: &[TrackListItem], available: Option<f64>, gap: f64) -> usize {
+    let mut other = (0.0, 0);
+    let mut repeated = (0.0, 0);
+    for item in list {
+        let (target, times, items) = match item {
+            TrackListItem::Repeat(RepeatCount::Count(n), items) => {
+                (&mut other, *n, items.as_slice())
+            }
+            TrackListItem::Repeat(_, items) => (&mut repeated, 1, items.as_slice()),
+            item => (&mut other, 1, core::slice::from_ref(item)),
+        };
+        for item in items {
+            if let TrackListItem::Track(size) = item {
+                target.0 += fixed_track_size(size, available) * times as f64;
+                target.1 += times;
+            }
+        }
+    }
+    let available = match available {
+        Some(available) => available,
+        None => return 1,
+    };
+    // 大きさが0の繰り返しはいくらでも入るので1回にする
+    if repeated.1 == 0 || repeated.0 + gap * repeated.1 as f64 <= 0.0 {
+        return 1;
+    }
+    let total = |n: usize| {
+        let count = other.1 + repeated.1 * n;
+        other.0 + repeated.0 * n as f64 + gap * count.saturating_sub(1) as f64
+    };
+    let mut n = 1;
+    while total(n + 1) <= available {
+        n += 1;
+    }
+    n
+}
+
+// 明示的なグリッドの外のトラックはgrid-auto-rows/columnsを繰り返す
+fn track_size(explicit: &[TrackSize], auto: &[TrackSize], index: isize) -> TrackSize {
+    if index >= 0 && (index as usize) < explicit.len() {
+        return explicit[index as usize].clone();
+    }
+    if auto.is_empty() {
+        return TrackSize::auto();
+    }
+    let len = auto.len() as isize;
+    let i = if index >= 0 {
+        (index - explicit.len() as isize) % len
+    } else {
+        len - 1 - (-index - 1) % len
+    };
+    auto[i as usize].clone()
+}
+
+fn overlaps(placed: &[GridArea], area: &GridArea) -> bool {
+    placed
+        .iter()
+        .any(|p| (0..2).all(|axis| p[axis].0 < area[axis].1 && area[axis].0 < p[axis].1))
+}
+
+fn with_axes(major: usize, major_range: (usize, usize), minor_range: (usize, usize)) -> GridArea {
+    let mut area = [(0, 0); 2];
+    area[major] = major_range;
+    area[1 - major] = minor_range;
+    area
+}
+
+// CSS Grid 8.5 グリッドアイテムの配置アルゴリズム。
+// 暗黙のグリッドの最初の線を0とした領域と、明示的なグリッドの始まりの位置、トラックの数を返す
+fn place_items(
+    placements: &[[Placement; 2]],
+    explicit: [usize; 2],
+    flow: GridAutoFlow,
+) -> (Vec<GridArea>, [usize; 2], [usize; 2]) {
+    // 行方向に流す場合は列を埋めてから次の行に進む
+    let major = if flow.column { COLUMN } else { ROW };
+    let minor = 1 - major;
+
+    // 負の線番号は明示的なグリッドより前に暗黙のトラックを作る
+    let mut offset: [usize; 2] = [0; 2];
+    for placement in placements {
+        for axis in [ROW, COLUMN] {
+            if let Placement::Definite(start, _) = placement[axis] {
+                offset[axis] = offset[axis].max((-start).max(0) as usize);
+            }
+        }
+    }
+    let definite = |placement: Placement, axis: usize| match placement {
+        Placement::Definite(start, end) => Some((
+            (start + offset[axis] as i32) as usize,
+            (end + offset[axis] as i32) as usize,
+        )),
+        Placement::Auto(_) => None,
+    };
+
+    let mut areas: Vec<Option<GridArea>> = vec![None; placements.len()];
+    let mut placed: Vec<GridArea> = Vec::new();
+    // 1. 両方向の位置が決まっているアイテム
+    for (i, placement) in placements.iter().enumerate() {
+        if let (Some(rows), Some(columns)) = (
+            definite(placement[ROW], ROW),
+            definite(placement[COLUMN], COLUMN),
+        ) {
+            areas[i] = Some([rows, columns]);
+            placed.push([rows, columns]);
+        }
+    }
+
+    // 2. 主軸方向の位置だけが決まっているアイテム。denseでなければ同じ行で前に置いたアイテムより後ろに置く
+    let mut row_cursors: Vec<(usize, usize)> = Vec::new();
+    for (i, placement) in placements.iter().enumerate() {
+        let major_range = match (
+            definite(placement[major], major),
+            definite(placement[minor], minor),
+        ) {
+            (Some(range), None) => range,
+            _ => continue,
+        };
+        let span = placement[minor].span();
+        let cursor = row_cursors
+            .iter()
+            .position(|(line, _)| *line == major_range.0);
+        let mut start = match cursor {
+            Some(c) if !flow.dense => row_cursors[c].1,
+            _ => 0,
+        };
+        while overlaps(
+            &placed,
+            &with_axes(major, major_range, (start, start + span)),
+        ) {
+            start += 1;
+        }
+        let area = with_axes(major, major_range, (start, start + span));
+        match cursor {
+            Some(c) => row_cursors[c].1 = start + span,
+            None => row_cursors.push((major_range.0, start + span)),
+        }
+        areas[i] = Some(area);
+        placed.push(area);
+    }
+
+    // 3. 副軸方向のトラックの数を決める
+    let mut minor_count = explicit[minor] + offset[minor];
+    for (placement, area) in placements.iter().zip(&areas) {
+        let end = match (area, definite(placement[minor], minor)) {
+            (Some(area), _) => area[minor].1,
+            (None, Some(range)) => range.1,
+            (None, None) => placement[minor].span(),
+        };
+        minor_count = minor_count.max(end);
+    }
+
+    // 4. 残りのアイテムをカーソルを進めながら空いている場所に置く
+    let mut cursor = (0, 0);
+    for (i, placement) in placements.iter().enumerate() {
+        if areas[i].is_some() {
+            continue;
+        }
+        if flow.dense {
+            cursor = (0, 0);
+        }
+        let major_span = placement[major].span();
+        let area = match definite(placement[minor], minor) {
+            Some(minor_range) => {
+                if !flow.dense && minor_range.0 < cursor.1 {
+                    cursor.0 += 1;
+                }
+                cursor.1 = minor_range.0;
+                while overlaps(
+                    &placed,
+                    &with_axes(major, (cursor.0, cursor.0 + major_span), minor_range),
+                ) {
+                    cursor.0 += 1;
+                }
+                with_axes(major, (cursor.0, cursor.0 + major_span), minor_range)
+            }
+            None => {
+                let minor_span = placement[minor].span();
+                loop {
+                    if cursor.1 + minor_span > minor_count {
+                        cursor.0 += 1;
+                        cursor.1 = 0;
+                    }
+                    let area = with_axes(
+                        major,
+                        (cursor.0, cursor.0 + major_span),
+                        (cursor.1, cursor.1 + minor_span),
+                    );
+                    if !overlaps(&placed, &area) {
+                        break area;
+                    }
+                    cursor.1 += 1;
+                }
+            }
+        };
+        areas[i] = Some(area);
+        placed.push(area);
+    }
+
+    let mut counts = [
+        explicit[ROW] + offset[ROW],
+        explicit[COLUMN] + offset[COLUMN],
+    ];
+    for area in &placed {
+        for axis in [ROW, COLUMN] {
+            counts[axis] = counts[axis].max(area[axis].1);
+        }
+    }
+    let areas = areas.into_iter().map(|a| a.unwrap_or_default()).collect();
+    (areas, offset, counts)
+}
+
+fn resolve_length(length: &Length, available: Option<f64>) -> Option<f64> {
+    match (length, available) {
+        (Length::Px(px), _) => Some(*px),
+        (length, Some(available)) => length.resolve(available),
+        _ => None,
+    }
+}
+
+// 解決できないパーセントはautoとして扱う
+fn resolve_breadth(breadth: &TrackBreadth, available: Option<f64>) -> TrackBreadth {
+    match breadth {
+        TrackBreadth::Length(length) => match resolve_length(length, available) {
+            Some(px) => TrackBreadth::Length(Length::Px(px)),
+            None => TrackBreadth::Auto,
+        },
+        breadth => breadth.clone(),
+    }
+}
+
+fn fixed_breadth(breadth: &TrackBreadth) -> Option<f64> {
+    match breadth {
+        TrackBreadth::Length(length) => Some(length.resolve_or_zero(0.0)),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Debug)]
+struct GridTrack {
+    size: TrackSize,
+    base: f64,
+    // 成長の上限。無限大はまだ決まっていないことを表す
+    limit: f64,
+    // auto-fitで繰り返した空のトラック
+    collapsed: bool,
+}
+
+impl GridTrack {
+    fn new(size: TrackSize, collapsed: bool) -> Self {
+        Self {
+            size,
+            base: 0.0,
+            limit: 0.0,
+            collapsed,
+        }
+    }
+}
+
+// トラックの大きさを決めるためのアイテムの寄与。大きさはマージンボックスのもの
+struct TrackItem {
+    span: (usize, usize),
+    min: f64,
+    max: f64,
+}
+
+fn spans_flexible(tracks: &[GridTrack], span: (usize, usize)) -> bool {
+    tracks[span.0..span.1]
+        .iter()
+        .any(|t| t.size.flex_factor().is_some())
+}
+
+fn total_gaps(tracks: &[GridTrack], gap: f64) -> f64 {
+    gap * tracks
+        .iter()
+        .filter(|t| !t.collapsed)
+        .count()
+        .saturating_sub(1) as f64
+}
+
+fn total_size(tracks: &[GridTrack], gap: f64) -> f64 {
+    tracks.iter().map(|t| t.base).sum::<f64>() + total_gaps(tracks, gap)
+}
+
+// 1frの大きさ。CSS Grid 12.7.1
+fn fr_size(tracks: &[GridTrack], range: Range<usize>, space: f64) -> f64 {
+    let mut inflexible: Vec<usize> = Vec::new();
+    loop {
+        let mut leftover = space;
+        let mut factors = 0.0;
+        for t in range.clone() {
+            match tracks[t].size.flex_factor() {
+                Some(factor) if !inflexible.contains(&t) => factors += factor,
+                _ => leftover -= tracks[t].base,
+            }
+        }
+        let fr = leftover / f64::max(factors, 1.0);
+        // 基本サイズより小さくなるトラックは柔軟でないものとして計算し直す
+        let violating: Vec<usize> = range
+            .clone()
+            .filter(|t| {
+                !inflexible.contains(t)
+                    && tracks[*t]
+                        .size
+                        .flex_factor()
+                        .is_some_and(|factor| fr * factor < tracks[*t].base)
+            })
+            .collect();
+        if violating.is_empty() {
+            return fr;
+        }
+        inflexible.extend(violating);
+    }
+}
+
+// CSS Grid 12 トラックの大きさを決めるアルゴリズム。availableがNoneの場合は最大内容の大きさにする
+fn size_tracks(
+    tracks: &mut [GridTrack],
+    items: &[TrackItem],
+    available: Option<f64>,
+    gap: f64,
+    stretch: bool,
+) {
+    // 12.4 初期化
+    for track in tracks.iter_mut() {
+        if track.collapsed {
+            track.size = TrackSize::new(TrackBreadth::Length(Length::Px(0.0)));
+        }
+        track.size.min = resolve_breadth(&track.size.min, available);
+        track.size.max = resolve_breadth(&track.size.max, available);
+        track.base = fixed_breadth(&track.size.min).unwrap_or(0.0);
+        track.limit = fixed_breadth(&track.size.max).map_or(f64::INFINITY, |l| l.max(track.base));
+    }
+    let gaps = |span: (usize, usize)| gap * (span.1 - span.0).saturating_sub(1) as f64;
+
+    // 12.5 内在的な大きさのトラック。またがるトラックの少ないアイテムから処理する
+    let (flexible_items, mut sorted): (Vec<&TrackItem>, Vec<&TrackItem>) = items
+        .iter()
+        .partition(|item| spans_flexible(tracks, item.span));
+    sorted.sort_by_key(|item| item.span.1 - item.span.0);
+    let limit_or_base = |track: &GridTrack| {
+        if track.limit.is_infinite() {
+            track.base
+        } else {
+            track.limit
+        }
+    };
+    for item in sorted {
+        let range = item.span.0..item.span.1;
+        if range.len() == 1 {
+            let track = &mut tracks[item.span.0];
+            match track.size.min {
+                TrackBreadth::MinContent | TrackBreadth::Auto => {
+                    track.base = track.base.max(item.min)
+                }
+                TrackBreadth::MaxContent => track.base = track.base.max(item.max),
+                _ => {}
+            }
+            let contribution = match track.size.max {
+                TrackBreadth::MinContent => item.min,
+                TrackBreadth::MaxContent | TrackBreadth::Auto => item.max,
+                _ => continue,
+            };
+            track.limit = if track.limit.is_infinite() {
+                contribution
+            } else {
+                track.limit.max(contribution)
+            };
+            continue;
+        }
+
+        // 複数のトラックにまたがるアイテムは足りない分を内在的なトラックに均等に配る
+        let targets: Vec<usize> = range
+            .clone()
+            .filter(|t| tracks[*t].size.min.is_intrinsic())
+            .collect();
+        let extra = item.min - range.clone().map(|t| tracks[t].base).sum::<f64>() - gaps(item.span);
+        if extra > 0.0 {
+            for t in &targets {
+                tracks[*t].base += extra / targets.len() as f64;
+            }
+        }
+        let targets: Vec<usize> = range
+            .clone()
+            .filter(|t| tracks[*t].size.max.is_intrinsic())
+            .collect();
+        let extra = item.max
+            - range
+                .clone()
+                .map(|t| limit_or_base(&tracks[t]))
+                .sum::<f64>()
+            - gaps(item.span);
+        for t in &targets {
+            let track = &mut tracks[*t];
+            track.limit = limit_or_base(track) + extra.max(0.0) / targets.len() as f64;
+        }
+    }
+    for track in tracks.iter_mut() {
+        track.limit = limit_or_base(track).max(track.base);
+    }
+
+    // 柔軟なトラックにまたがるアイテムの最小の寄与はfrの比で配る
+    for item in &flexible_items {
+        let range = item.span.0..item.span.1;
+        let targets: Vec<usize> = range
+            .clone()
+            .filter(|t| {
+                tracks[*t].size.flex_factor().is_some() && tracks[*t].size.min.is_intrinsic()
+            })
+            .collect();
+        let extra = item.min - range.map(|t| tracks[t].base).sum::<f64>() - gaps(item.span);
+        if extra <= 0.0 || targets.is_empty() {
+            continue;
+        }
+        let factors: f64 = targets
+            .iter()
+            .filter_map(|t| tracks[*t].size.flex_factor())
+            .sum();
+        for t in &targets {
+            let factor = tracks[*t].size.flex_factor().unwrap_or(0.0);
+            let track = &mut tracks[*t];
+            track.base += if factors > 0.0 {
+                extra * factor / factors
+            } else {
+                extra / targets.len() as f64
+            };
+            track.limit = track.limit.max(track.base);
+        }
+    }
+
+    // 12.6 空きがあれば成長の上限まで広げる
+    match available {
+        Some(available) => {
+            let mut free = available - total_size(tracks, gap);
+            while free > 0.0 {
+                let growable: Vec<usize> = (0..tracks.len())
+                    .filter(|t| {
+                        tracks[*t].size.flex_factor().is_none()
+                            && tracks[*t].base < tracks[*t].limit
+                    })
+                    .collect();
+                if growable.is_empty() {
+                    break;
+                }
+                let share = free / growable.len() as f64;
+                let mut capped = false;
+                for t in growable {
+                    let track = &mut tracks[t];
+                    let grow = share.min(track.limit - track.base);
+                    capped |= grow < share;
+                    track.base += grow;
+                    free -= grow;
+                }
+                if !capped {
+                    break;
+                }
+            }
+        }
+        None => {
+            for track in tracks.iter_mut() {
+                if track.size.flex_factor().is_none() {
+                    track.base = track.limit;
+                }
+            }
+        }
+    }
+
+    // 12.7 柔軟なトラックを広げる
+    let flexible: Vec<usize> = (0..tracks.len())
+        .filter(|t| tracks[*t].size.flex_factor().is_some())
+        .collect();
+    if !flexible.is_empty() {
+        let fr = match available {
+            Some(available) => {
+                fr_size(tracks, 0..tracks.len(), available - total_gaps(tracks, gap))
+            }
+            None => {
+                let mut fr: f64 = 0.0;
+                for t in &flexible {
+                    let factor = tracks[*t].size.flex_factor().unwrap_or(0.0);
+                    let base = tracks[*t].base;
+                    fr = fr.max(if factor > 1.0 { base / factor } else { base });
+                }
+                for item in &flexible_items {
+                    let space = item.max - gaps(item.span);
+                    fr = fr.max(fr_size(tracks, item.span.0..item.span.1, space));
+                }
+                fr
+            }
+        };
+        for t in flexible {
+            let track = &mut tracks[t];
+            let factor = track.size.flex_factor().unwrap_or(0.0);
+            track.base = track.base.max(fr * factor);
+        }
+    }
+
+    // 12.8 autoトラックを伸ばす
+    if let (true, Some(available)) = (stretch, available) {
+        let free = available - total_size(tracks, gap);
+        let autos: Vec<usize> = (0..tracks.len())
+            .filter(|t| !tracks[*t].collapsed && tracks[*t].size.max == TrackBreadth::Auto)
+            .collect();
+        if free > 0.0 && !autos.is_empty() {
+            for t in &autos {
+                tracks[*t].base += free / autos.len() as f64;
+            }
+        }
+    }
+}
+
+// 各トラックの始まりの位置。justify-contentやalign-contentによる余白を含める
+fn track_positions(
+    tracks: &[GridTrack],
+    gap: f64,
+    size: Option<f64>,
+    align: ContentAlign,
+) -> Vec<f64> {
+    let count = tracks.iter().filter(|t| !t.collapsed).count();
+    let (mut position, between) = match size {
+        Some(size) => distribute(align, size - total_size(tracks, gap), count),
+        None => (0.0, 0.0),
+    };
+    let mut positions = Vec::new();
+    for (i, track) in tracks.iter().enumerate() {
+        positions.push(position);
+        position += track.base;
+        // 折りたたまれたトラックの両側の溝は1つになる
+        if !track.collapsed && tracks[i + 1..].iter().any(|t| !t.collapsed) {
+            position += gap + between;
+        }
+    }
+    positions
+}
+
+fn area_extent(tracks: &[GridTrack], positions: &[f64], span: (usize, usize)) -> (f64, f64) {
+    let start = positions[span.0];
+    let end = positions[span.1 - 1] + tracks[span.1 - 1].base;
+    (start, end - start)
+}
+
+// autoはコンテナのjustify-itemsやalign-itemsを使い、normalはstretchとして扱う
+fn self_alignment(value: ItemAlign, container: ItemAlign) -> ItemAlign {
+    match value {
+        ItemAlign::Auto => self_alignment(container, ItemAlign::Normal),
+        ItemAlign::Normal => ItemAlign::Stretch,
+        value => value,
+    }
+}
+
+fn is_stretch(align: ContentAlign) -> bool {
+    matches!(align, ContentAlign::Normal | ContentAlign::Stretch)
+}
+
+struct Grid {
+    // 子ボックスの位置とグリッド領域
+    items: Vec<(usize, GridArea)>,
+    tracks: [Vec<GridTrack>; 2],
+}
+
+impl LayoutBox {
+    // 明示的なグリッドを作り、アイテムを配置する
+    fn build_grid(&self, available: [Option<f64>; 2], gaps: [f64; 2]) -> Grid {
+        let style = &self.style;
+        let mut explicit = [
+            ExplicitGrid::new(&style.grid_template_rows, available[ROW], gaps[ROW]),
+            ExplicitGrid::new(
+                &style.grid_template_columns,
+                available[COLUMN],
+                gaps[COLUMN],
+            ),
+        ];
+        explicit[ROW].add_areas(&style.grid_template_areas, false);
+        explicit[COLUMN].add_areas(&style.grid_template_areas, true);
+
+        // orderの順に配置する。同じ値であれば文書順を保つ。絶対配置の子はアイテムにならない
+        let mut order: Vec<usize> = (0..self.children.len())
+            .filter(|i| !self.children[*i].is_absolutely_positioned())
+            .collect();
+        order.sort_by_key(|i| self.children[*i].style.order);
+        let placements: Vec<[Placement; 2]> = order
+            .iter()
+            .map(|i| {
+                let item = &self.children[*i].style;
+                [
+                    explicit[ROW].resolve(&item.grid_row_start, &item.grid_row_end),
+                    explicit[COLUMN].resolve(&item.grid_column_start, &item.grid_column_end),
+                ]
+            })
+            .collect();
+        let (areas, offset, counts) = place_items(
+            &placements,
+            [explicit[ROW].len(), explicit[COLUMN].len()],
+            style.grid_auto_flow,
+        );
+
+        let auto = [&style.grid_auto_rows, &style.grid_auto_columns];
+        let tracks = [ROW, COLUMN].map(|axis| {
+            (0..counts[axis])
+                .map(|i| {
+                    let index = i as isize - offset[axis] as isize;
+                    let size = track_size(&explicit[axis].tracks, auto[axis], index);
+                    let collapsed = index >= 0
+                        && explicit[axis].auto_fit.contains(&(index as usize))
+                        && !areas
+                            .iter()
+                            .any(|area| area[axis].0 <= i && i < area[axis].1);
+                    GridTrack::new(size, collapsed)
+                })
+                .collect()
+        });
+        Grid {
+            items: order.into_iter().zip(areas).collect(),
+            tracks,
+        }
+    }
+
+    // グリッド領域の中でアイテムをレイアウトし、マージンボックスの大きさを返す
+    fn layout_grid_item(
+        &mut self,
+        container_style: &ComputedStyle,
+        width: f64,
+        height: Option<f64>,
+        context: &LayoutContext,
+    ) -> (f64, f64) {
+        let containing_block = ContainingBlock {
+            x: 0.0,
+            width,
+            height,
+        };
+        self.resolve_edges(width);
+        self.dimensions.margin.left = self.style.margin.left.resolve_or_zero(width);
+        self.dimensions.margin.right = self.style.margin.right.resolve_or_zero(width);
+        let d = self.dimensions;
+        let horizontal_edges =
+            d.margin.horizontal() + d.padding.horizontal() + d.border.horizontal();
+        let vertical_edges = d.margin.vertical() + d.padding.vertical() + d.border.vertical();
+        let margin = &self.style.margin;
+        let justify = self_alignment(self.style.justify_self, container_style.justify_items);
+        let align = self_alignment(self.style.align_self, container_style.align_items);
+
+        let available = (width - horizontal_edges).max(0.0);
+        let (min_width, max_width) = self.min_max_width(width);
+        let content_width = match self.style.width.resolve(width) {
+            Some(w) => self.content_size(w, true),
+            None if justify == ItemAlign::Stretch
+                && !margin.left.is_auto()
+                && !margin.right.is_auto() =>
+            {
+                available
+            }
+            None => self.shrink_to_fit_width(available, context),
+        };
+        let content_height = match height {
+            Some(h)
+                if align == ItemAlign::Stretch
+                    && self.style.height.is_auto()
+                    && !margin.top.is_auto()
+                    && !margin.bottom.is_auto() =>
+            {
+                let (min, max) = self.min_max_height(&containing_block);
+                Some(clamp_size((h - vertical_edges).max(0.0), min, max))
+            }
+            _ => None,
+        };
+        self.layout_block_with(
+            &containing_block,
+            SizeOverride {
+                width: Some(clamp_size(content_width, min_width, max_width)),
+                height: content_height,
+            },
+            context,
+        );
+        let border_box = self.dimensions.border_box();
+        let margin = self.dimensions.margin;
+        (
+            border_box.width + margin.horizontal(),
+            border_box.height + margin.vertical(),
+        )
+    }
+
+    // グリッドアイテムを配置し、内容の高さとアイテムがあるかどうかを返す。CSS Grid 11
+    pub(super) fn layout_grid_children(
+        &mut self,
+        containing_block: &ContainingBlock,
+        context: &LayoutContext,
+    ) -> (f64, bool) {
+        let style = self.style.clone();
+        let width = containing_block.width;
+        let height = containing_block.height;
+        let column_gap = style.column_gap.resolve_or_zero(width);
+        let row_gap = style.row_gap.resolve_or_zero(height.unwrap_or(0.0));
+        self.place_absolute_children(containing_block, context);
+        let Grid { items, mut tracks } =
+            self.build_grid([height, Some(width)], [row_gap, column_gap]);
+
+        // 列の大きさを先に決める
+        let column_items: Vec<TrackItem> = items
+            .iter()
+            .map(|(index, area)| {
+                let (min, max) = self.children[*index].intrinsic_contribution(context);
+                TrackItem {
+                    span: area[COLUMN],
+                    min,
+                    max,
+                }
+            })
+            .collect();
+        size_tracks(
+            &mut tracks[COLUMN],
+            &column_items,
+            Some(width),
+            column_gap,
+            is_stretch(style.justify_content),
+        );
+        let column_positions = track_positions(
+            &tracks[COLUMN],
+            column_gap,
+            Some(width),
+            style.justify_content,
+        );
+
+        // 列の幅でレイアウトした高さから行の大きさを決める
+        let row_items: Vec<TrackItem> = items
+            .iter()
+            .map(|(index, area)| {
+                let (_, area_width) = area_extent(&tracks[COLUMN], &column_positions, area[COLUMN]);
+                let (_, outer_height) =
+                    self.children[*index].layout_grid_item(&style, area_width, None, context);
+                TrackItem {
+                    span: area[ROW],
+                    min: outer_height,
+                    max: outer_height,
+                }
+            })
+            .collect();
+        size_tracks(
+            &mut tracks[ROW],
+            &row_items,
+            height,
+            row_gap,
+            is_stretch(style.align_content),
+        );
+        let content_height = height.unwrap_or_else(|| total_size(&tracks[ROW], row_gap));
+        let row_positions = track_positions(
+            &tracks[ROW],
+            row_gap,
+            Some(content_height),
+            style.align_content,
+        );
+
+        // グリッド領域の中でアイテムを揃える
+        let content_x = containing_block.x;
+        let content_y = self.dimensions.content.y;
+        for (index, area) in &items {
+            let (x, area_width) = area_extent(&tracks[COLUMN], &column_positions, area[COLUMN]);
+            let (y, area_height) = area_extent(&tracks[ROW], &row_positions, area[ROW]);
+            let child = &mut self.children[*index];
+            let (outer_width, outer_height) =
+                child.layout_grid_item(&style, area_width, Some(area_height), context);
+            let margin = child.style.margin.clone();
+            let free_x = area_width - outer_width;
+            let free_y = area_height - outer_height;
+            let offset_x = match (margin.left.is_auto(), margin.right.is_auto()) {
+                (true, true) if free_x > 0.0 => free_x / 2.0,
+                (true, false) if free_x > 0.0 => free_x,
+                (false, true) => 0.0,
+                _ => item_offset(
+                    self_alignment(child.style.justify_self, style.justify_items),
+                    free_x,
+                    false,
+                ),
+            };
+            let offset_y = match (margin.top.is_auto(), margin.bottom.is_auto()) {
+                (true, true) if free_y > 0.0 => free_y / 2.0,
+                (true, false) if free_y > 0.0 => free_y,
+                (false, true) => 0.0,
+                _ => item_offset(
+                    self_alignment(child.style.align_self, style.align_items),
+                    free_y,
+                    false,
+                ),
+            };
+            let border_box = child.dimensions.border_box();
+            child.translate(
+                content_x + x + offset_x + child.dimensions.margin.left - border_box.x,
+                content_y + y + offset_y + child.dimensions.margin.top - border_box.y,
+            );
+        }
+
+        (total_size(&tracks[ROW], row_gap), !items.is_empty())
+    }
+
+    // グリッドコンテナの最小内容幅と最大内容幅
+    pub(super) fn grid_intrinsic_widths(&self, context: &LayoutContext) -> (f64, f64) {
+        let gap = self.style.column_gap.resolve_or_zero(0.0);
+        let grid = self.build_grid([None, None], [0.0, gap]);
+        let items: Vec<TrackItem> = grid
+            .items
+            .iter()
+            .map(|(index, area)| {
+                let (min, max) = self.children[*index].intrinsic_contribution(context);
+                TrackItem {
+                    span: area[COLUMN],
+                    min,
+                    max,
+                }
+            })
+            .collect();
+        // 空きのない状態で決めた大きさが最小内容幅になる
+        let mut min_tracks = grid.tracks[COLUMN].clone();
+        size_tracks(&mut min_tracks, &items, Some(0.0), gap, false);
+        let mut max_tracks = grid.tracks[COLUMN].clone();
+        size_tracks(&mut max_tracks, &items, None, gap, false);
+        (total_size(&min_tracks, gap), total_size(&max_tracks, gap))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::layout::{geometry::Rect, layout_document};
+
+    // クラスa, b, c, ...を持つcount個のアイテムを持つグリッドコンテナのアイテムのボーダーボックス
+    fn item_boxes(count: usize, css: &str) -> Vec<Rect> {
+        let items: String = ["a", "b", "c", "d", "e"][..count]
+            .iter()
+            .map(|class| "<a class=\"".to_string() + class + "\">x</a>")
+            .collect();
+        let (_, view) = layout_document(
+            &("<html><body><p>".to_string() + &items + "</p></body></html>"),
+            &("body, p { margin: 0 } p { display: grid; font-size: 10px; line-height: 20px } "
+                .to_string()
+                + css),
+        );
+        let body = &view.root().unwrap().children()[0];
+        let p = &body.children()[0];
+        p.children()
+            .iter()
+            .map(|item| item.dimensions().border_box())
+            .collect()
+    }
+
+    fn widths(boxes: &[Rect]) -> Vec<f64> {
+        boxes.iter().map(|b| b.width).collect()
+    }
+
+    fn positions(boxes: &[Rect]) -> Vec<(f64, f64)> {
+        boxes.iter().map(|b| (b.x, b.y)).collect()
+    }
+
+    #[test]
+    fn test_track_sizing() {
+        let boxes = item_boxes(
+            3,
+            "p { width: 400px; grid-template-columns: 100px 1fr 2fr }",
+        );
+        assert_eq!(vec![100.0, 100.0, 200.0], widths(&boxes));
+        assert_eq!(
+            vec![(0.0, 0.0), (100.0, 0.0), (200.0, 0.0)],
+            positions(&boxes)
+        );
+        assert_eq!(20.0, boxes[0].height);
+
+        let boxes = item_boxes(
+            3,
+            "p { width: 400px; grid-template-columns: minmax(50px, 100px) repeat(2, 1fr); column-gap: 10px }",
+        );
+        assert_eq!(vec![100.0, 140.0, 140.0], widths(&boxes));
+        assert_eq!(
+            vec![(0.0, 0.0), (110.0, 0.0), (260.0, 0.0)],
+            positions(&boxes)
+        );
+
+        // autoトラックは内容に合わせた後、残りの空きで伸びる
+        let boxes = item_boxes(
+            3,
+            "p { width: 100px; grid-template-columns: auto 30px auto } .a { width: 20px }",
+        );
+        assert_eq!(vec![20.0, 30.0, 27.5], widths(&boxes));
+        assert_eq!(
+            vec![(0.0, 0.0), (42.5, 0.0), (72.5, 0.0)],
+            positions(&boxes)
+        );
+    }
+
+    #[test]
+    fn test_auto_fill_and_auto_fit() {
+        let boxes = item_boxes(
+            4,
+            "p { width: 350px; grid-template-columns: repeat(auto-fill, 100px); gap: 5px 10px }",
+        );
+        assert_eq!(
+            vec![(0.0, 0.0), (110.0, 0.0), (220.0, 0.0), (0.0, 25.0)],
+            positions(&boxes)
+        );
+
+        let css = "p { width: 350px; column-gap: 10px; grid-template-columns: repeat(auto-fill, minmax(100px, 1fr)) }";
+        assert_eq!(vec![110.0, 110.0], widths(&item_boxes(2, css)));
+        // auto-fitでは空のトラックが折りたたまれる
+        let boxes = item_boxes(2, &css.replace("auto-fill", "auto-fit"));
+        assert_eq!(vec![170.0, 170.0], widths(&boxes));
+        assert_eq!(vec![(0.0, 0.0), (180.0, 0.0)], positions(&boxes));
+    }
+
+    #[test]
+    fn test_placement() {
+        let css = "p { grid-template-columns: repeat(3, 10px); grid-auto-rows: 10px } .a { grid-column: 2 / span 2 } .b { grid-column: span 2 }";
+        let boxes = item_boxes(3, css);
+        assert_eq!(
+            vec![(10.0, 0.0), (0.0, 10.0), (20.0, 10.0)],
+            positions(&boxes)
+        );
+        assert_eq!(vec![20.0, 20.0, 10.0], widths(&boxes));
+
+        let boxes = item_boxes(3, &(css.to_string() + " p { grid-auto-flow: row dense }"));
+        assert_eq!(
+            vec![(10.0, 0.0), (0.0, 10.0), (0.0, 0.0)],
+            positions(&boxes)
+        );
+
+        let boxes = item_boxes(
+            3,
+            "p { grid-auto-flow: column; grid-template-rows: 10px 10px; grid-auto-columns: 10px } .c { grid-row: 1 }",
+        );
+        assert_eq!(
+            vec![(0.0, 0.0), (0.0, 10.0), (10.0, 0.0)],
+            positions(&boxes)
+        );
+
+        // 負の線番号は明示的なグリッドの前に暗黙のトラックを作る
+        let boxes = item_boxes(
+            3,
+            "p { grid-template-columns: 10px 10px; grid-auto-columns: 5px; grid-auto-rows: 10px } .a { grid-column: -4 } .b { grid-row: 2; grid-column-end: -1 }",
+        );
+        assert_eq!(
+            vec![(0.0, 0.0), (15.0, 10.0), (5.0, 0.0)],
+            positions(&boxes)
+        );
+    }
+
+    #[test]
+    fn test_named_lines_and_areas() {
+        let boxes = item_boxes(
+            3,
+            "p { grid-template-areas: \"head head\" \"side main\"; grid-template-columns: [left] 50px [mid] 100px [right]; grid-auto-rows: 20px } .a { grid-area: main } .b { grid-area: head } .c { grid-column: mid / right; grid-row: side }",
+        );
+        assert_eq!(
+            vec![(50.0, 20.0), (0.0, 0.0), (50.0, 20.0)],
+            positions(&boxes)
+        );
+        assert_eq!(vec![100.0, 150.0, 100.0], widths(&boxes));
+    }
+
+    #[test]
+    fn test_alignment() {
+        let boxes = item_boxes(
+            2,
+            "p { grid-template-columns: 100px; grid-template-rows: 50px 50px; justify-items: center; align-items: end } .b { justify-self: stretch; align-self: start; margin-left: auto }",
+        );
+        assert_eq!(vec![(47.5, 30.0), (95.0, 50.0)], positions(&boxes));
+        assert_eq!(vec![5.0, 5.0], widths(&boxes));
+
+        let boxes = item_boxes(
+            2,
+            "p { width: 100px; height: 100px; grid-template-columns: 20px 20px; grid-template-rows: 20px; justify-content: space-between; align-content: center }",
+        );
+        assert_eq!(vec![(0.0, 40.0), (80.0, 40.0)], positions(&boxes));
+    }
+
+    #[test]
+    fn test_inline_grid_intrinsic_width() {
+        let (_, view) = layout_document(
+            "<html><body><p><a><h1>aa bb</h1><h2>c</h2></a></p></body></html>",
+            "body, p, h1, h2 { margin: 0 } p, h1, h2 { font-size: 10px } a { display: inline-grid; grid-template-columns: auto 20px; column-gap: 5px } h1, h2 { display: block }",
+        );
+        let body = &view.root().unwrap().children()[0];
+        let a = &body.children()[0].children()[0];
+        assert_eq!(50.0, a.dimensions().content.width);
+        assert_eq!(
+            vec![25.0, 20.0],
+            a.children()
+                .iter()
+                .map(|item| item.dimensions().border_box().width)
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,1435 @@
+use crate::renderer::{
+    css::computed_style::{
+        ComputedStyle, OverflowWrap, TextAlign, VerticalAlign, WhiteSpace, WordBreak,
+    },
+    layout::{
+        block::ContainingBlock,
+        font_metrics::FontMetrics,
+        geometry::Rect,
+        layout_box::{BoxKind, Fragment, LayoutBox, LineBox},
+        layout_view::LayoutContext,
+        line_break::{break_opportunities, line_break_class, BreakOpportunity, LineBreakClass},
+    },
+};
+use alloc::{collections::BTreeMap, string::String, vec, vec::Vec};
+
+// オブジェクト置換文字。インラインブロックなどを改行の判定で1文字として扱う
+const OBJECT_REPLACEMENT: char = '\u{fffc}';
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ItemKind {
+    // 空白処理済みのテキストの文字の範囲
+    Text {
+        text: usize,
+        start: usize,
+        end: usize,
+    },
+    // インラインボックスの開始と終了
+    Start,
+    End,
+    Atomic,
+    ForcedBreak,
+    // 行の中の位置だけを持つフロートや絶対配置のボックス
+    OutOfFlow,
+}
+
+#[derive(Clone, Debug)]
+struct InlineItem {
+    // インライン整形文脈のルートから見たボックスの位置
+    path: Vec<usize>,
+    kind: ItemKind,
+    width: f64,
+    // この項目の直後で改行できる
+    break_after: bool,
+}
+
+#[derive(Clone, Debug)]
+struct ProcessedText {
+    chars: Vec<char>,
+    // 各文字の元のテキストでの位置
+    offsets: Vec<usize>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct InlineContent {
+    items: Vec<InlineItem>,
+    texts: Vec<ProcessedText>,
+}
+
+fn is_space(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\n' | '\r' | '\u{c}')
+}
+
+// 行末で取り除かれる空白かどうか
+fn is_collapsible(white_space: WhiteSpace) -> bool {
+    matches!(
+        white_space,
+        WhiteSpace::Normal | WhiteSpace::Nowrap | WhiteSpace::PreLine
+    )
+}
+
+// CSS Text 3 4.1.1 空白の処理。直前が空白であれば先頭の空白も取り除く
+fn process_whitespace(
+    text: &str,
+    white_space: WhiteSpace,
+    after_space: &mut bool,
+) -> ProcessedText {
+    let mut chars = Vec::new();
+    let mut offsets = Vec::new();
+    for (i, c) in text.chars().enumerate() {
+        let collapse = match white_space {
+            WhiteSpace::Normal | WhiteSpace::Nowrap => is_space(c),
+            WhiteSpace::PreLine => {
+                if c == '\n' {
+                    // 改行の前後の空白は取り除く
+                    while chars.last() == Some(&' ') {
+                        chars.pop();
+                        offsets.pop();
+                    }
+                    chars.push('\n');
+                    offsets.push(i);
+                    *after_space = true;
+                    continue;
+                }
+                is_space(c)
+            }
+            WhiteSpace::Pre | WhiteSpace::PreWrap | WhiteSpace::BreakSpaces => {
+                if c != '\r' {
+                    chars.push(c);
+                    offsets.push(i);
+                }
+                *after_space = false;
+                continue;
+            }
+        };
+        if collapse {
+            if !*after_space {
+                chars.push(' ');
+                offsets.push(i);
+            }
+            *after_space = true;
+        } else {
+            chars.push(c);
+            offsets.push(i);
+            *after_space = false;
+        }
+    }
+    ProcessedText { chars, offsets }
+}
+
+fn segment_width(chars: &[char], style: &ComputedStyle, metrics: &dyn FontMetrics) -> f64 {
+    let mut width = 0.0;
+    let mut previous = None;
+    for c in chars {
+        if *c == '\t' {
+            // タブは空白8文字分とする
+            width += metrics.advance(' ', style) * 8.0;
+        } else {
+            if let Some(previous) = previous {
+                width += metrics.kerning(previous, *c, style);
+            }
+            width += metrics.advance(*c, style);
+        }
+        previous = Some(*c);
+    }
+    width
+}
+
+fn start_edge(style: &ComputedStyle, containing_width: f64) -> f64 {
+    style.margin.left.resolve_or_zero(containing_width)
+        + style.border_width.left
+        + style.padding.left.resolve_or_zero(containing_width)
+}
+
+fn end_edge(style: &ComputedStyle, containing_width: f64) -> f64 {
+    style.margin.right.resolve_or_zero(containing_width)
+        + style.border_width.right
+        + style.padding.right.resolve_or_zero(containing_width)
+}
+
+// 単語の途中でも改行できるかどうか
+fn can_break_anywhere(style: &ComputedStyle) -> bool {
+    style.overflow_wrap != OverflowWrap::Normal || style.word_break == WordBreak::BreakWord
+}
+
+fn is_letter(class: LineBreakClass) -> bool {
+    matches!(
+        class,
+        LineBreakClass::AL | LineBreakClass::NU | LineBreakClass::ID
+    )
+}
+
+enum RawItem {
+    Text(usize, Vec<usize>),
+    Start(Vec<usize>),
+    End(Vec<usize>),
+    Atomic(Vec<usize>),
+    OutOfFlow(Vec<usize>),
+}
+
+fn collect_raw_items(
+    b: &LayoutBox,
+    path: &mut Vec<usize>,
+    after_space: &mut bool,
+    raw: &mut Vec<RawItem>,
+    texts: &mut Vec<ProcessedText>,
+) {
+    for (i, child) in b.children.iter().enumerate() {
+        path.push(i);
+        match &child.kind {
+            BoxKind::Text(text) => {
+                texts.push(process_whitespace(
+                    text,
+                    child.style.white_space,
+                    after_space,
+                ));
+                raw.push(RawItem::Text(texts.len() - 1, path.clone()));
+            }
+            BoxKind::Inline => {
+                raw.push(RawItem::Start(path.clone()));
+                collect_raw_items(child, path, after_space, raw, texts);
+                raw.push(RawItem::End(path.clone()));
+            }
+            BoxKind::InlineBlock => {
+                *after_space = false;
+                raw.push(RawItem::Atomic(path.clone()));
+            }
+            BoxKind::Block | BoxKind::AnonymousBlock | BoxKind::Table => {
+                if child.is_out_of_flow() {
+                    raw.push(RawItem::OutOfFlow(path.clone()));
+                }
+            }
+        }
+        path.pop();
+    }
+}
+
+impl LayoutBox {
+    fn descendant(&self, path: &[usize]) -> &LayoutBox {
+        path.iter().fold(self, |b, i| &b.children[*i])
+    }
+
+    pub(super) fn descendant_mut(&mut self, path: &[usize]) -> &mut LayoutBox {
+        path.iter().fold(self, |b, i| &mut b.children[*i])
+    }
+
+    // インライン整形文脈の中身を改行できる位置で区切った項目の列にする
+    fn collect_inline_content(
+        &self,
+        metrics: &dyn FontMetrics,
+        containing_width: f64,
+    ) -> InlineContent {
+        let mut raw = Vec::new();
+        let mut texts = Vec::new();
+        let mut after_space = true;
+        collect_raw_items(
+            self,
+            &mut Vec::new(),
+            &mut after_space,
+            &mut raw,
+            &mut texts,
+        );
+
+        // 段落全体の文字列で改行位置を求める
+        let mut paragraph = Vec::new();
+        let mut styles: Vec<(WhiteSpace, WordBreak)> = Vec::new();
+        let mut text_starts = vec![0; texts.len()];
+        let mut atomic_positions = Vec::new();
+        for item in &raw {
+            match item {
+                RawItem::Text(index, path) => {
+                    let style = &self.descendant(path).style;
+                    text_starts[*index] = paragraph.len();
+                    for c in &texts[*index].chars {
+                        paragraph.push(*c);
+                        styles.push((style.white_space, style.word_break));
+                    }
+                }
+                RawItem::Atomic(_) => {
+                    atomic_positions.push(paragraph.len());
+                    paragraph.push(OBJECT_REPLACEMENT);
+                    styles.push((self.style.white_space, self.style.word_break));
+                }
+                RawItem::Start(_) | RawItem::End(_) | RawItem::OutOfFlow(_) => {}
+            }
+        }
+        let mut opportunities = break_opportunities(&paragraph);
+        for i in 1..paragraph.len() {
+            let (white_space, word_break) = styles[i - 1];
+            let before = line_break_class(paragraph[i - 1]);
+            let after = line_break_class(paragraph[i]);
+            let opportunity = &mut opportunities[i];
+            if *opportunity == BreakOpportunity::Mandatory {
+                continue;
+            }
+            let letters = is_letter(before) && is_letter(after);
+            if matches!(white_space, WhiteSpace::Nowrap | WhiteSpace::Pre) {
+                *opportunity = BreakOpportunity::Prohibited;
+            } else if (white_space == WhiteSpace::BreakSpaces && paragraph[i - 1] == ' ')
+                || (word_break == WordBreak::BreakAll && letters)
+            {
+                *opportunity = BreakOpportunity::Allowed;
+            } else if word_break == WordBreak::KeepAll && letters {
+                *opportunity = BreakOpportunity::Prohibited;
+            }
+        }
+        let can_break_before = |index: usize| {
+            opportunities
+                .get(index)
+                .is_some_and(|o| *o != BreakOpportunity::Prohibited)
+        };
+
+        let mut items = Vec::new();
+        let mut atomic_index = 0;
+        for item in raw {
+            match item {
+                RawItem::Text(index, path) => {
+                    let style = &self.descendant(&path).style;
+                    let chars = &texts[index].chars;
+                    let global = text_starts[index];
+                    let segment = |start: usize, end: usize, break_after: bool| InlineItem {
+                        path: path.clone(),
+                        kind: ItemKind::Text {
+                            text: index,
+                            start,
+                            end,
+                        },
+                        width: segment_width(&chars[start..end], style, metrics),
+                        break_after,
+                    };
+                    let mut start = 0;
+                    for (j, c) in chars.iter().enumerate() {
+                        if *c == '\n' {
+                            if j > start {
+                                items.push(segment(start, j, false));
+                            }
+                            start = j + 1;
+                            items.push(InlineItem {
+                                path: path.clone(),
+                                kind: ItemKind::ForcedBreak,
+                                width: 0.0,
+                                break_after: true,
+                            });
+                            continue;
+                        }
+                        if j > start && can_break_before(global + j) {
+                            items.push(segment(start, j, true));
+                            start = j;
+                        }
+                    }
+                    if chars.len() > start {
+                        let break_after = can_break_before(global + chars.len());
+                        items.push(segment(start, chars.len(), break_after));
+                    }
+                }
+                RawItem::Start(path) => {
+                    let style = &self.descendant(&path).style;
+                    items.push(InlineItem {
+                        width: start_edge(style, containing_width),
+                        path,
+                        kind: ItemKind::Start,
+                        break_after: false,
+                    });
+                }
+                RawItem::End(path) => {
+                    let style = &self.descendant(&path).style;
+                    items.push(InlineItem {
+                        width: end_edge(style, containing_width),
+                        path,
+                        kind: ItemKind::End,
+                        break_after: false,
+                    });
+                }
+                RawItem::Atomic(path) => {
+                    let global = atomic_positions[atomic_index];
+                    atomic_index += 1;
+                    items.push(InlineItem {
+                        path,
+                        kind: ItemKind::Atomic,
+                        width: 0.0,
+                        break_after: can_break_before(global + 1),
+                    });
+                }
+                RawItem::OutOfFlow(path) => items.push(InlineItem {
+                    path,
+                    kind: ItemKind::OutOfFlow,
+                    width: 0.0,
+                    break_after: false,
+                }),
+            }
+        }
+
+        // 改行位置の直後にあるインラインボックスの終了は前の行に含める
+        for i in 0..items.len().saturating_sub(1) {
+            if items[i].break_after && items[i + 1].kind == ItemKind::End {
+                items[i].break_after = false;
+                items[i + 1].break_after = true;
+            }
+        }
+
+        InlineContent { items, texts }
+    }
+}
+
+// iから始まり、改行できる位置で終わる項目の範囲の終端(その位置を含む)
+fn unit_end(items: &[InlineItem], i: usize) -> usize {
+    let mut end = i;
+    while end + 1 < items.len()
+        && !items[end].break_after
+        && items[end + 1].kind != ItemKind::ForcedBreak
+    {
+        end += 1;
+    }
+    end
+}
+
+// 垂直方向の配置に使う、ベースラインより上と下の大きさ
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct VerticalBounds {
+    above: f64,
+    below: f64,
+}
+
+fn text_bounds(style: &ComputedStyle, metrics: &dyn FontMetrics) -> VerticalBounds {
+    let ascent = metrics.ascent(style);
+    let descent = metrics.descent(style);
+    let half_leading = (style.line_height.resolve(style.font_size) - ascent - descent) / 2.0;
+    VerticalBounds {
+        above: ascent + half_leading,
+        below: descent + half_leading,
+    }
+}
+
+// 行分割の途中の状態
+struct LineBreaker {
+    items: Vec<InlineItem>,
+    // 次の行の先頭の項目
+    position: usize,
+}
+
+struct LineState {
+    items: Vec<InlineItem>,
+    // 強制改行か段落の最後で終わる行は両端揃えにしない
+    last: bool,
+    // 行末にぶら下がる空白の幅
+    hang: f64,
+}
+
+impl LayoutBox {
+    fn item_style(&self, item: &InlineItem) -> &ComputedStyle {
+        &self.descendant(&item.path).style
+    }
+
+    // 行末の空白の幅
+    fn trailing_space_width(
+        &self,
+        content: &InlineContent,
+        items: &[InlineItem],
+        metrics: &dyn FontMetrics,
+    ) -> f64 {
+        for item in items.iter().rev() {
+            match item.kind {
+                ItemKind::Text { text, start, end } => {
+                    let style = self.item_style(item);
+                    if matches!(style.white_space, WhiteSpace::Pre | WhiteSpace::BreakSpaces) {
+                        return 0.0;
+                    }
+                    let chars = &content.texts[text].chars[start..end];
+                    let spaces = chars.iter().rev().take_while(|c| **c == ' ').count();
+                    return segment_width(&chars[chars.len() - spaces..], style, metrics);
+                }
+                ItemKind::End | ItemKind::Start | ItemKind::OutOfFlow => continue,
+                _ => return 0.0,
+            }
+        }
+        0.0
+    }
+
+    // 行末の折りたたみ可能な空白を取り除き、ぶら下がる空白の幅を返す
+    fn trim_line_end(
+        &self,
+        content: &InlineContent,
+        items: &mut Vec<InlineItem>,
+        metrics: &dyn FontMetrics,
+    ) -> f64 {
+        let mut index = items.len();
+        while index > 0 {
+            index -= 1;
+            let (text, start, end) = match items[index].kind {
+                ItemKind::Text { text, start, end } => (text, start, end),
+                ItemKind::End | ItemKind::Start | ItemKind::OutOfFlow => continue,
+                _ => return 0.0,
+            };
+            let style = self.item_style(&items[index]);
+            if !is_collapsible(style.white_space) {
+                return self.trailing_space_width(content, &items[..=index], metrics);
+            }
+            let chars = &content.texts[text].chars;
+            let mut new_end = end;
+            while new_end > start && chars[new_end - 1] == ' ' {
+                new_end -= 1;
+            }
+            if new_end == start {
+                items.remove(index);
+                continue;
+            }
+            let width = segment_width(&chars[start..new_end], style, metrics);
+            items[index].kind = ItemKind::Text {
+                text,
+                start,
+                end: new_end,
+            };
+            items[index].width = width;
+            return 0.0;
+        }
+        0.0
+    }
+
+    // 1文字ずつ改行できる項目に分ける。分けられなければNone
+    fn split_anywhere(
+        &self,
+        content: &InlineContent,
+        items: &[InlineItem],
+        metrics: &dyn FontMetrics,
+    ) -> Option<Vec<InlineItem>> {
+        let mut result = Vec::new();
+        let mut split = false;
+        for (i, item) in items.iter().enumerate() {
+            let (text, start, end) = match item.kind {
+                ItemKind::Text { text, start, end } => (text, start, end),
+                _ => {
+                    result.push(item.clone());
+                    continue;
+                }
+            };
+            let style = self.item_style(item);
+            if !can_break_anywhere(style) || end - start < 2 {
+                result.push(item.clone());
+                continue;
+            }
+            split = true;
+            for j in start..end {
+                let is_last = j + 1 == end;
+                result.push(InlineItem {
+                    path: item.path.clone(),
+                    kind: ItemKind::Text {
+                        text,
+                        start: j,
+                        end: j + 1,
+                    },
+                    width: segment_width(&content.texts[text].chars[j..j + 1], style, metrics),
+                    break_after: if is_last && i + 1 == items.len() {
+                        item.break_after
+                    } else {
+                        true
+                    },
+                });
+            }
+        }
+        if split {
+            Some(result)
+        } else {
+            None
+        }
+    }
+
+    // 次の項目の並びのうち、行の先頭に置く改行できない部分の幅
+    fn next_unit_width(
+        &self,
+        breaker: &LineBreaker,
+        content: &InlineContent,
+        metrics: &dyn FontMetrics,
+    ) -> f64 {
+        let items = &breaker.items;
+        let i = breaker.position;
+        if i >= items.len() || items[i].kind == ItemKind::ForcedBreak {
+            return 0.0;
+        }
+        let end = unit_end(items, i);
+        let width: f64 = items[i..=end].iter().map(|item| item.width).sum();
+        width - self.trailing_space_width(content, &items[i..=end], metrics)
+    }
+
+    // 貪欲法で項目を1行に詰める。行に入ったフロートはplace_floatで置き、同じ行に置けた分だけ行を狭める
+    fn next_line(
+        &self,
+        breaker: &mut LineBreaker,
+        content: &InlineContent,
+        available: f64,
+        metrics: &dyn FontMetrics,
+        place_float: &mut dyn FnMut(&InlineItem, f64) -> f64,
+    ) -> Option<LineState> {
+        let items = &mut breaker.items;
+        let mut i = breaker.position;
+        if i >= items.len() {
+            return None;
+        }
+        let mut available = available;
+        let mut line: Vec<InlineItem> = Vec::new();
+        let mut x = 0.0;
+        let mut last = false;
+        while i < items.len() {
+            if items[i].kind == ItemKind::ForcedBreak {
+                line.push(items[i].clone());
+                i += 1;
+                last = true;
+                break;
+            }
+
+            let end = unit_end(items, i);
+            let width: f64 = items[i..=end].iter().map(|item| item.width).sum();
+            let hang = self.trailing_space_width(content, &items[i..=end], metrics);
+            if !line.is_empty() && x + width - hang > available {
+                break;
+            }
+            if line.is_empty() && width - hang > available {
+                // 収まらない単語はoverflow-wrapが許せば任意の位置で分割する
+                if let Some(split) = self.split_anywhere(content, &items[i..=end], metrics) {
+                    items.splice(i..=end, split);
+                    continue;
+                }
+            }
+            line.extend(items[i..=end].iter().cloned());
+            x += width;
+            for item in &items[i..=end] {
+                if item.kind == ItemKind::OutOfFlow {
+                    available -= place_float(item, available - x);
+                }
+            }
+            i = end + 1;
+        }
+        breaker.position = i;
+        let hang = self.trim_line_end(content, &mut line, metrics);
+        Some(LineState {
+            items: line,
+            last: last || i >= items.len(),
+            hang,
+        })
+    }
+
+    fn vertical_bounds(
+        &self,
+        path: &[usize],
+        atomics: &BTreeMap<Vec<usize>, VerticalBounds>,
+        metrics: &dyn FontMetrics,
+    ) -> VerticalBounds {
+        match atomics.get(path) {
+            Some(bounds) => *bounds,
+            None => text_bounds(&self.descendant(path).style, metrics),
+        }
+    }
+
+    // 親のベースラインからの持ち上げ量を、ルートのベースラインからの値として求める
+    fn baseline_shift(
+        &self,
+        path: &[usize],
+        atomics: &BTreeMap<Vec<usize>, VerticalBounds>,
+        metrics: &dyn FontMetrics,
+        shifts: &mut BTreeMap<Vec<usize>, f64>,
+    ) -> f64 {
+        if path.is_empty() {
+            return 0.0;
+        }
+        if let Some(shift) = shifts.get(path) {
+            return *shift;
+        }
+        let parent_path = &path[..path.len() - 1];
+        let parent_shift = self.baseline_shift(parent_path, atomics, metrics, shifts);
+        let style = &self.descendant(path).style;
+        let parent = &self.descendant(parent_path).style;
+        let bounds = self.vertical_bounds(path, atomics, metrics);
+        let offset = match &style.vertical_align {
+            VerticalAlign::Baseline | VerticalAlign::Top | VerticalAlign::Bottom => 0.0,
+            VerticalAlign::Sub => -parent.font_size / 5.0,
+            VerticalAlign::Super => parent.font_size / 3.0,
+            VerticalAlign::TextTop => metrics.ascent(parent) - bounds.above,
+            VerticalAlign::TextBottom => bounds.below - metrics.descent(parent),
+            // 中央を親のベースラインからx-heightの半分だけ上に揃える
+            VerticalAlign::Middle => parent.font_size / 4.0 - (bounds.above - bounds.below) / 2.0,
+            VerticalAlign::Length(length) => {
+                length.resolve_or_zero(style.line_height.resolve(style.font_size))
+            }
+        };
+        let shift = parent_shift + offset;
+        shifts.insert(path.to_vec(), shift);
+        shift
+    }
+
+    // topまたはbottomで揃えるもっとも外側のボックス
+    fn line_relative_anchor(&self, path: &[usize]) -> Option<Vec<usize>> {
+        (1..=path.len())
+            .map(|len| &path[..len])
+            .find(|p| {
+                matches!(
+                    self.descendant(p).style.vertical_align,
+                    VerticalAlign::Top | VerticalAlign::Bottom
+                )
+            })
+            .map(|p| p.to_vec())
+    }
+
+    // インラインボックスの行ごとのボーダーボックス。分割された側の余白は含めない
+    #[allow(clippy::too_many_arguments)]
+    fn inline_box_rect(
+        &self,
+        path: &[usize],
+        start_x: f64,
+        end_x: f64,
+        (has_start, has_end): (bool, bool),
+        baseline: f64,
+        metrics: &dyn FontMetrics,
+        containing_width: f64,
+    ) -> Rect {
+        let style = &self.descendant(path).style;
+        let margin_left = if has_start {
+            style.margin.left.resolve_or_zero(containing_width)
+        } else {
+            0.0
+        };
+        let margin_right = if has_end {
+            style.margin.right.resolve_or_zero(containing_width)
+        } else {
+            0.0
+        };
+        let top = metrics.ascent(style)
+            + style.padding.top.resolve_or_zero(containing_width)
+            + style.border_width.top;
+        let bottom = metrics.descent(style)
+            + style.padding.bottom.resolve_or_zero(containing_width)
+            + style.border_width.bottom;
+        Rect::new(
+            start_x + margin_left,
+            baseline - top,
+            (end_x - margin_right - start_x - margin_left).max(0.0),
+            top + bottom,
+        )
+    }
+
+    fn set_inline_dimensions(&mut self, border_box: Rect, containing_width: f64) {
+        let style = &self.style;
+        let dimensions = &mut self.dimensions;
+        dimensions.margin.left = style.margin.left.resolve_or_zero(containing_width);
+        dimensions.margin.right = style.margin.right.resolve_or_zero(containing_width);
+        dimensions.padding.top = style.padding.top.resolve_or_zero(containing_width);
+        dimensions.padding.right = style.padding.right.resolve_or_zero(containing_width);
+        dimensions.padding.bottom = style.padding.bottom.resolve_or_zero(containing_width);
+        dimensions.padding.left = style.padding.left.resolve_or_zero(containing_width);
+        dimensions.border = style.border_width;
+        dimensions.content = Rect::new(
+            border_box.x + dimensions.border.left + dimensions.padding.left,
+            border_box.y + dimensions.border.top + dimensions.padding.top,
+            (border_box.width - dimensions.border.horizontal() - dimensions.padding.horizontal())
+                .max(0.0),
+            border_box.height - dimensions.border.vertical() - dimensions.padding.vertical(),
+        );
+    }
+
+    // 最初の行のベースライン。行を持たなければNone
+    pub fn first_baseline(&self) -> Option<f64> {
+        if let Some(line) = self.lines.first() {
+            return Some(line.baseline);
+        }
+        self.children
+            .iter()
+            .filter(|child| {
+                matches!(
+                    child.kind,
+                    BoxKind::Block | BoxKind::AnonymousBlock | BoxKind::Table
+                ) && !child.is_out_of_flow()
+            })
+            .find_map(|child| child.first_baseline())
+    }
+
+    // 最後の行のベースライン。行を持たなければNone
+    pub fn last_baseline(&self) -> Option<f64> {
+        if let Some(line) = self.lines.last() {
+            return Some(line.baseline);
+        }
+        self.children
+            .iter()
+            .rev()
+            .filter(|child| {
+                matches!(
+                    child.kind,
+                    BoxKind::Block | BoxKind::AnonymousBlock | BoxKind::Table
+                ) && !child.is_out_of_flow()
+            })
+            .find_map(|child| child.last_baseline())
+    }
+
+    // インライン整形文脈の子を行に分けて配置し、内容の高さと行があるかどうかを返す
+    pub(super) fn layout_inline_children(&mut self, context: &LayoutContext) -> (f64, bool) {
+        let metrics = context.font_metrics;
+        let content_rect = self.dimensions.content;
+        let available = content_rect.width;
+        let mut content = self.collect_inline_content(metrics, available);
+        self.lines.clear();
+
+        // インラインブロックを先にレイアウトして幅とベースラインを求める
+        let mut atomics = BTreeMap::new();
+        for item in &mut content.items {
+            if item.kind != ItemKind::Atomic {
+                continue;
+            }
+            let atomic = self.descendant_mut(&item.path);
+            atomic.layout_block(
+                &ContainingBlock {
+                    x: 0.0,
+                    width: available,
+                    height: None,
+                },
+                context,
+            );
+            let margin_box = atomic.dimensions.margin_box();
+            let above = match atomic.last_baseline() {
+                Some(baseline) => baseline - margin_box.y,
+                None => margin_box.height,
+            };
+            item.width = margin_box.width;
+            atomics.insert(
+                item.path.clone(),
+                VerticalBounds {
+                    above,
+                    below: margin_box.height - above,
+                },
+            );
+        }
+
+        // フロートを先にレイアウトして大きさを求める。絶対配置のボックスは静的位置を求めるために仮にレイアウトする
+        let mut float_sizes = BTreeMap::new();
+        for item in &content.items {
+            if item.kind != ItemKind::OutOfFlow {
+                continue;
+            }
+            let out_of_flow = self.descendant_mut(&item.path);
+            out_of_flow.layout_block(
+                &ContainingBlock {
+                    x: content_rect.x,
+                    width: available,
+                    height: None,
+                },
+                context,
+            );
+            if out_of_flow.is_float() {
+                let margin_box = out_of_flow.dimensions.margin_box();
+                float_sizes.insert(item.path.clone(), (margin_box.width, margin_box.height));
+            }
+        }
+
+        // ブロック整形文脈のルートから見たボックスの上端
+        let origin = context.floats.borrow().origin();
+        let strut = text_bounds(&self.style, metrics);
+        let strut_height = strut.above + strut.below;
+        let mut breaker = LineBreaker {
+            items: content.items.clone(),
+            position: 0,
+        };
+        let mut float_positions: Vec<(Vec<usize>, f64, f64)> = Vec::new();
+        let mut shifts = BTreeMap::new();
+        let mut open: Vec<Vec<usize>> = Vec::new();
+        let mut text_fragments: Vec<(Vec<usize>, Fragment)> = Vec::new();
+        let mut box_fragments: Vec<(Vec<usize>, Rect, bool)> = Vec::new();
+        let mut atomic_offsets: Vec<(Vec<usize>, f64, f64)> = Vec::new();
+        let mut line_boxes = Vec::new();
+        let mut y = content_rect.y;
+        let mut has_content = false;
+
+        loop {
+            // フロートと重なって次の単語が収まらなければ行を下げる
+            let unit_width = self.next_unit_width(&breaker, &content, metrics);
+            let (line_top, band_left, band_right) = context.floats.borrow().find_band(
+                origin + y,
+                strut_height,
+                unit_width,
+                content_rect.x,
+                content_rect.right(),
+            );
+            y = line_top - origin;
+            // 行に入ったフロートは行の残りに収まればこの行の横に置き、収まらなければ行の下に置く
+            let mut deferred = Vec::new();
+            let mut place_float = |item: &InlineItem, remaining: f64| -> f64 {
+                let Some(size) = float_sizes.get(&item.path) else {
+                    return 0.0;
+                };
+                if size.0 > remaining && remaining < band_right - band_left {
+                    deferred.push(item.path.clone());
+                    return 0.0;
+                }
+                let (x, top) = self.place_inline_float(&item.path, *size, line_top, context);
+                float_positions.push((item.path.clone(), x, top));
+                if top < line_top + strut_height {
+                    size.0
+                } else {
+                    0.0
+                }
+            };
+            let Some(line) = self.next_line(
+                &mut breaker,
+                &content,
+                band_right - band_left,
+                metrics,
+                &mut place_float,
+            ) else {
+                break;
+            };
+            let items = &line.items;
+            // テキストや余白を持たない行は高さを持たない
+            let phantom = !items.iter().any(|item| match item.kind {
+                ItemKind::Text { .. } | ItemKind::Atomic | ItemKind::ForcedBreak => true,
+                ItemKind::Start | ItemKind::End => item.width != 0.0,
+                ItemKind::OutOfFlow => false,
+            });
+
+            // この行に現れるボックス
+            let mut boxes: Vec<Vec<usize>> = open.clone();
+            for item in items {
+                for len in 1..=item.path.len() {
+                    let path = &item.path[..len];
+                    if !boxes.iter().any(|b| b == path) {
+                        boxes.push(path.to_vec());
+                    }
+                }
+            }
+
+            let mut top: f64 = 0.0;
+            let mut bottom: f64 = 0.0;
+            let mut anchored: BTreeMap<Vec<usize>, (f64, f64)> = BTreeMap::new();
+            if !phantom {
+                let strut = text_bounds(&self.style, metrics);
+                top = -strut.above;
+                bottom = strut.below;
+                for path in &boxes {
+                    let bounds = self.vertical_bounds(path, &atomics, metrics);
+                    let shift = self.baseline_shift(path, &atomics, metrics, &mut shifts);
+                    match self.line_relative_anchor(path) {
+                        Some(anchor) => {
+                            let anchor_shift =
+                                self.baseline_shift(&anchor, &atomics, metrics, &mut shifts);
+                            let relative = shift - anchor_shift;
+                            let extent = anchored.entry(anchor).or_insert((0.0, 0.0));
+                            extent.0 = extent.0.min(-relative - bounds.above);
+                            extent.1 = extent.1.max(-relative + bounds.below);
+                        }
+                        None => {
+                            top = top.min(-shift - bounds.above);
+                            bottom = bottom.max(-shift + bounds.below);
+                        }
+                    }
+                }
+                for (extent_top, extent_bottom) in anchored.values() {
+                    let height = extent_bottom - extent_top;
+                    if height > bottom - top {
+                        bottom = top + height;
+                    }
+                }
+                has_content = true;
+            }
+            let line_height = bottom - top;
+            let baseline = y - top;
+            let mut anchor_baselines = BTreeMap::new();
+            for (anchor, (extent_top, extent_bottom)) in &anchored {
+                let anchor_baseline = match self.descendant(anchor).style.vertical_align {
+                    VerticalAlign::Top => y - extent_top,
+                    _ => y + line_height - extent_bottom,
+                };
+                anchor_baselines.insert(anchor.clone(), anchor_baseline);
+            }
+            let mut baseline_of = |b: &LayoutBox, path: &[usize]| -> f64 {
+                let shift = b.baseline_shift(path, &atomics, metrics, &mut shifts);
+                match b.line_relative_anchor(path) {
+                    Some(anchor) => {
+                        let anchor_shift =
+                            b.baseline_shift(&anchor, &atomics, metrics, &mut shifts);
+                        anchor_baselines.get(&anchor).copied().unwrap_or(baseline)
+                            - (shift - anchor_shift)
+                    }
+                    None => baseline - shift,
+                }
+            };
+
+            // 水平方向の揃え。行はフロートを避けた範囲に置く
+            let (line_left, line_right) = context.floats.borrow().available(
+                origin + y,
+                strut_height,
+                content_rect.x,
+                content_rect.right(),
+            );
+            let width: f64 = items.iter().map(|item| item.width).sum::<f64>() - line.hang;
+            let free = (line_right - line_left - width).max(0.0);
+            let (mut x, extra_space) = match self.style.text_align {
+                TextAlign::Start | TextAlign::Left => (0.0, 0.0),
+                TextAlign::End | TextAlign::Right => (free, 0.0),
+                TextAlign::Center => (free / 2.0, 0.0),
+                TextAlign::Justify => {
+                    let spaces = items
+                        .iter()
+                        .map(|item| match item.kind {
+                            ItemKind::Text { text, start, end } => content.texts[text].chars
+                                [start..end]
+                                .iter()
+                                .filter(|c| **c == ' ')
+                                .count(),
+                            _ => 0,
+                        })
+                        .sum::<usize>();
+                    if line.last || spaces == 0 {
+                        (0.0, 0.0)
+                    } else {
+                        (0.0, free / spaces as f64)
+                    }
+                }
+            };
+            x += line_left;
+
+            let mut starts: BTreeMap<Vec<usize>, (f64, bool)> =
+                open.iter().map(|path| (path.clone(), (x, false))).collect();
+            for item in items {
+                match item.kind {
+                    ItemKind::Text { text, start, end } => {
+                        let style = self.item_style(item);
+                        let chars = &content.texts[text].chars[start..end];
+                        let spaces = chars.iter().filter(|c| **c == ' ').count();
+                        let width = item.width + extra_space * spaces as f64;
+                        let item_baseline = baseline_of(self, &item.path);
+                        let ascent = metrics.ascent(style);
+                        let descent = metrics.descent(style);
+                        text_fragments.push((
+                            item.path.clone(),
+                            Fragment {
+                                rect: Rect::new(x, item_baseline - ascent, width, ascent + descent),
+                                text: chars.iter().collect(),
+                                offset: content.texts[text].offsets[start],
+                            },
+                        ));
+                        x += width;
+                    }
+                    ItemKind::Start => {
+                        starts.insert(item.path.clone(), (x, true));
+                        open.push(item.path.clone());
+                        x += item.width;
+                    }
+                    ItemKind::End => {
+                        x += item.width;
+                        open.retain(|p| *p != item.path);
+                        let (start_x, has_start) = starts.remove(&item.path).unwrap_or((x, false));
+                        let rect = self.inline_box_rect(
+                            &item.path,
+                            start_x,
+                            x,
+                            (has_start, true),
+                            baseline_of(self, &item.path),
+                            metrics,
+                            available,
+                        );
+                        box_fragments.push((item.path.clone(), rect, has_start));
+                    }
+                    ItemKind::Atomic => {
+                        let bounds = atomics[&item.path];
+                        let item_baseline = baseline_of(self, &item.path);
+                        let margin_box = self.descendant(&item.path).dimensions.margin_box();
+                        atomic_offsets.push((
+                            item.path.clone(),
+                            x - margin_box.x,
+                            item_baseline - bounds.above - margin_box.y,
+                        ));
+                        x += item.width;
+                    }
+                    ItemKind::OutOfFlow => {
+                        // 絶対配置のボックスの静的位置は行の中の位置になる
+                        if !float_sizes.contains_key(&item.path) {
+                            let margin_box = self.descendant(&item.path).dimensions.margin_box();
+                            atomic_offsets.push((
+                                item.path.clone(),
+                                x - margin_box.x,
+                                y - margin_box.y,
+                            ));
+                        }
+                    }
+                    ItemKind::ForcedBreak => {}
+                }
+            }
+            // 次の行に続くインラインボックス
+            for path in &open {
+                if let Some((start_x, has_start)) = starts.get(path) {
+                    let rect = self.inline_box_rect(
+                        path,
+                        *start_x,
+                        x,
+                        (*has_start, false),
+                        baseline_of(self, path),
+                        metrics,
+                        available,
+                    );
+                    box_fragments.push((path.clone(), rect, *has_start));
+                }
+            }
+
+            line_boxes.push(LineBox {
+                rect: Rect::new(line_left, y, line_right - line_left, line_height),
+                baseline,
+            });
+            y += line_height;
+
+            for path in deferred {
+                let (x, top) =
+                    self.place_inline_float(&path, float_sizes[&path], origin + y, context);
+                float_positions.push((path, x, top));
+            }
+        }
+
+        // 求めた位置をボックスに反映する
+        for (path, dx, dy) in atomic_offsets {
+            self.descendant_mut(&path).translate(dx, dy);
+        }
+        for (path, x, top) in float_positions {
+            let float = self.descendant_mut(&path);
+            let margin_box = float.dimensions.margin_box();
+            float.translate(x - margin_box.x, top - origin - margin_box.y);
+        }
+        for path in content
+            .items
+            .iter()
+            .filter(|item| matches!(item.kind, ItemKind::Text { .. } | ItemKind::Start))
+            .map(|item| item.path.clone())
+        {
+            self.descendant_mut(&path).fragments.clear();
+        }
+        // 両端揃えでなければ同じ行で連続する断片はまとめる
+        let merge = self.style.text_align != TextAlign::Justify;
+        for (path, fragment) in text_fragments {
+            let fragments = &mut self.descendant_mut(&path).fragments;
+            match fragments.last_mut() {
+                Some(last)
+                    if merge
+                        && last.rect.y == fragment.rect.y
+                        && last.rect.right() == fragment.rect.x =>
+                {
+                    last.rect.width += fragment.rect.width;
+                    last.text.push_str(&fragment.text);
+                }
+                _ => fragments.push(fragment),
+            }
+        }
+        for (path, rect, has_start) in box_fragments {
+            let inline = self.descendant_mut(&path);
+            if inline.fragments.is_empty() || has_start {
+                inline.set_inline_dimensions(rect, available);
+            }
+            inline.fragments.push(Fragment {
+                rect,
+                text: String::new(),
+                offset: 0,
+            });
+        }
+        self.lines = line_boxes;
+
+        (y - content_rect.y, has_content)
+    }
+
+    // 大きさがsizeのフロートを整形文脈のルートから見た位置yより下に置き、マージンボックスの左上を返す
+    fn place_inline_float(
+        &self,
+        path: &[usize],
+        size: (f64, f64),
+        y: f64,
+        context: &LayoutContext,
+    ) -> (f64, f64) {
+        let style = &self.descendant(path).style;
+        let content_rect = self.dimensions.content;
+        context.floats.borrow_mut().place(
+            style.float,
+            style.clear,
+            size.0,
+            size.1,
+            y,
+            content_rect.x,
+            content_rect.right(),
+        )
+    }
+
+    // 最小内容幅と最大内容幅
+    pub(super) fn inline_intrinsic_widths(&self, context: &LayoutContext) -> (f64, f64) {
+        let metrics = context.font_metrics;
+        let mut content = self.collect_inline_content(metrics, 0.0);
+        let mut atomic_min_widths = BTreeMap::new();
+        for item in &mut content.items {
+            // フロートはインラインブロックと同じように幅に寄与する
+            let contributes = match item.kind {
+                ItemKind::Atomic => true,
+                ItemKind::OutOfFlow => self.descendant(&item.path).is_float(),
+                _ => false,
+            };
+            if !contributes {
+                continue;
+            }
+            let (min, max) = self.descendant(&item.path).intrinsic_contribution(context);
+            item.width = max;
+            atomic_min_widths.insert(item.path.clone(), min);
+        }
+
+        // 強制改行の間で最も長い部分が最大内容幅になる
+        let mut max_content: f64 = 0.0;
+        let mut start = 0;
+        for end in 0..=content.items.len() {
+            if end < content.items.len() && content.items[end].kind != ItemKind::ForcedBreak {
+                continue;
+            }
+            let run = &content.items[start..end];
+            let width: f64 = run.iter().map(|item| item.width).sum();
+            let hang = self.trailing_space_width(&content, run, metrics);
+            max_content = max_content.max(width - hang);
+            start = end + 1;
+        }
+
+        // 改行できる位置の間で最も長い部分が最小内容幅になる
+        let mut min_content: f64 = 0.0;
+        let mut i = 0;
+        while i < content.items.len() {
+            let end = unit_end(&content.items, i);
+            let unit = &content.items[i..=end];
+            let anywhere = unit.iter().any(|item| {
+                let style = self.item_style(item);
+                matches!(item.kind, ItemKind::Text { .. })
+                    && (style.overflow_wrap == OverflowWrap::Anywhere
+                        || style.word_break == WordBreak::BreakWord)
+            });
+            let width = if anywhere {
+                // どの文字の間でも改行できるので最も広い文字の幅になる
+                unit.iter()
+                    .map(|item| match item.kind {
+                        ItemKind::Text { text, start, end } => content.texts[text].chars
+                            [start..end]
+                            .iter()
+                            .map(|c| segment_width(&[*c], self.item_style(item), metrics))
+                            .fold(0.0, f64::max),
+                        ItemKind::Atomic | ItemKind::OutOfFlow => {
+                            atomic_min_widths.get(&item.path).copied().unwrap_or(0.0)
+                        }
+                        _ => item.width,
+                    })
+                    .fold(0.0, f64::max)
+            } else {
+                unit.iter()
+                    .map(|item| match item.kind {
+                        ItemKind::Atomic | ItemKind::OutOfFlow => {
+                            atomic_min_widths.get(&item.path).copied().unwrap_or(0.0)
+                        }
+                        _ => item.width,
+                    })
+                    .sum::<f64>()
+                    - self.trailing_space_width(&content, unit, metrics)
+            };
+            min_content = min_content.max(width);
+            i = end + 1;
+        }
+
+        (min_content, max_content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::layout::{layout_document, layout_view::LayoutView};
+    use alloc::string::ToString;
+
+    fn paragraph(view: &LayoutView) -> &LayoutBox {
+        let root = view.root().expect("failed to get root box");
+        &root.children()[0].children()[0]
+    }
+
+    fn texts(b: &LayoutBox) -> Vec<(String, f64, f64)> {
+        b.fragments()
+            .iter()
+            .map(|f| (f.text.clone(), f.rect.x, f.rect.y))
+            .collect()
+    }
+
+    #[test]
+    fn test_process_whitespace() {
+        let collapse = |text: &str, white_space: WhiteSpace| -> String {
+            let mut after_space = false;
+            process_whitespace(text, white_space, &mut after_space)
+                .chars
+                .iter()
+                .collect()
+        };
+        assert_eq!(" a b ", collapse(" \n a \t b  ", WhiteSpace::Normal));
+        assert_eq!("a b", collapse("a   b", WhiteSpace::Nowrap));
+        assert_eq!(" a\nb c", collapse("  a  \n  b  c", WhiteSpace::PreLine));
+        assert_eq!("a  b\n", collapse("a  b\r\n", WhiteSpace::Pre));
+
+        let mut after_space = true;
+        let text = process_whitespace("  a", WhiteSpace::Normal, &mut after_space);
+        assert_eq!(vec!['a'], text.chars);
+        assert_eq!(vec![2], text.offsets);
+    }
+
+    #[test]
+    fn test_line_breaking() {
+        let (_, view) = layout_document(
+            "<html><body><p>aaa bbb ccc dd</p></body></html>",
+            "body, p { margin: 0 } p { width: 60px; font-size: 10px; line-height: 20px }",
+        );
+        let p = paragraph(&view);
+        assert_eq!(2, p.lines().len());
+        assert_eq!(Rect::new(0.0, 0.0, 60.0, 20.0), p.lines()[0].rect);
+        assert_eq!(13.0, p.lines()[0].baseline);
+        assert_eq!(40.0, p.dimensions().content.height);
+
+        let text = &p.children()[0];
+        assert_eq!(
+            vec![
+                ("aaa bbb ccc".to_string(), 0.0, 5.0),
+                ("dd".to_string(), 0.0, 25.0)
+            ],
+            texts(text)
+        );
+        // 行末の空白は幅に含めない
+        assert_eq!(55.0, text.fragments()[0].rect.width);
+        assert_eq!(12, text.fragments()[1].offset);
+    }
+
+    #[test]
+    fn test_text_align() {
+        let css = "body, p { margin: 0 } p { width: 60px; font-size: 10px; line-height: 10px }";
+        let html = "<html><body><p>aa bb cc dd ee ff gg</p></body></html>";
+        let xs = |text_align: &str| -> Vec<(String, f64, f64)> {
+            let (_, view) = layout_document(
+                html,
+                &(css.to_string() + "p { text-align: " + text_align + " }"),
+            );
+            texts(&paragraph(&view).children()[0])
+        };
+        assert_eq!(
+            vec![
+                ("aa bb cc dd".to_string(), 2.5, 0.0),
+                ("ee ff gg".to_string(), 10.0, 10.0)
+            ],
+            xs("center")
+        );
+        assert_eq!(
+            vec![
+                ("aa bb cc dd".to_string(), 5.0, 0.0),
+                ("ee ff gg".to_string(), 20.0, 10.0)
+            ],
+            xs("right")
+        );
+        // 最後の行は両端揃えにしない
+        let justified = xs("justify");
+        assert_eq!(("aa ".to_string(), 0.0, 0.0), justified[0]);
+        assert_eq!(("bb ".to_string(), 16.0 + 2.0 / 3.0, 0.0), justified[1]);
+        assert_eq!(("ee ".to_string(), 0.0, 10.0), justified[4]);
+        assert_eq!(("ff ".to_string(), 15.0, 10.0), justified[5]);
+    }
+
+    #[test]
+    fn test_white_space() {
+        let css = "body, p { margin: 0 } p { width: 20px; font-size: 10px; line-height: 10px }";
+        let lines = |white_space: &str| -> Vec<String> {
+            let (_, view) = layout_document(
+                "<html><body><p>a  b\n  c d e</p></body></html>",
+                &(css.to_string() + "p { white-space: " + white_space + " }"),
+            );
+            texts(&paragraph(&view).children()[0])
+                .into_iter()
+                .map(|(text, _, _)| text)
+                .collect()
+        };
+        assert_eq!(vec!["a b", "c d", "e"], lines("normal"));
+        assert_eq!(vec!["a b c d e"], lines("nowrap"));
+        assert_eq!(vec!["a  b", "  c d e"], lines("pre"));
+        assert_eq!(vec!["a  b", "  c ", "d e"], lines("pre-wrap"));
+        assert_eq!(vec!["a b", "c d", "e"], lines("pre-line"));
+    }
+
+    #[test]
+    fn test_whitespace_around_elements() {
+        // 要素の前後の空白や、ブロックの先頭の空白もテキストとして残す
+        let (_, view) = layout_document(
+            "<html><body><p>Hello <a>link</a> world</p><p class=\"pre\">    indented</p></body></html>",
+            "body, p { margin: 0 } p { font-size: 10px; line-height: 10px } .pre { white-space: pre }",
+        );
+        let body = &view.root().expect("failed to get root box").children()[0];
+        let p = &body.children()[0];
+        assert_eq!(
+            vec![("Hello ".to_string(), 0.0, 0.0)],
+            texts(&p.children()[0])
+        );
+        assert_eq!(
+            vec![("link".to_string(), 30.0, 0.0)],
+            texts(&p.children()[1].children()[0])
+        );
+        assert_eq!(
+            vec![(" world".to_string(), 50.0, 0.0)],
+            texts(&p.children()[2])
+        );
+        assert_eq!(
+            vec![("    indented".to_string(), 0.0, 10.0)],
+            texts(&body.children()[1].children()[0])
+        );
+    }
+
+    #[test]
+    fn test_word_break() {
+        let css = "body, p { margin: 0 } p { width: 30px; font-size: 10px; line-height: 10px }";
+        let lines = |declaration: &str| -> Vec<String> {
+            let (_, view) = layout_document(
+                "<html><body><p>abcdefgh ij</p></body></html>",
+                &(css.to_string() + "p { " + declaration + " }"),
+            );
+            texts(&paragraph(&view).children()[0])
+                .into_iter()
+                .map(|(text, _, _)| text)
+                .collect()
+        };
+        assert_eq!(vec!["abcdefgh", "ij"], lines("word-break: normal"));
+        assert_eq!(vec!["abcdef", "gh ij"], lines("word-break: break-all"));
+        assert_eq!(vec!["abcdef", "gh ij"], lines("overflow-wrap: anywhere"));
+        assert_eq!(vec!["abcdef", "gh ij"], lines("word-wrap: break-word"));
+    }
+
+    #[test]
+    fn test_vertical_align() {
+        let (_, view) = layout_document(
+            "<html><body><p>x<a>y</a></p></body></html>",
+            "body, p { margin: 0 } p { font-size: 10px; line-height: 20px } a { vertical-align: 10px }",
+        );
+        let p = paragraph(&view);
+        assert_eq!(30.0, p.dimensions().content.height);
+        assert_eq!(23.0, p.lines()[0].baseline);
+        assert_eq!(vec![("x".to_string(), 0.0, 15.0)], texts(&p.children()[0]));
+        let a = &p.children()[1];
+        assert_eq!(Rect::new(5.0, 5.0, 5.0, 10.0), a.fragments()[0].rect);
+        assert_eq!(vec![("y".to_string(), 5.0, 5.0)], texts(&a.children()[0]));
+
+        // インラインブロックは最後の行のベースラインで揃える
+        let (_, view) = layout_document(
+            "<html><body><p>x<a>y</a></p></body></html>",
+            "body, p { margin: 0 } p { font-size: 10px; line-height: 10px } a { display: inline-block; padding-bottom: 10px }",
+        );
+        let p = paragraph(&view);
+        assert_eq!(20.0, p.dimensions().content.height);
+        let a = &p.children()[1];
+        assert_eq!(Rect::new(5.0, 0.0, 5.0, 20.0), a.dimensions().border_box());
+        assert_eq!(8.0, a.last_baseline().unwrap());
+    }
+
+    #[test]
+    fn test_intrinsic_widths() {
+        let width = |css: &str| -> f64 {
+            let (_, view) = layout_document(
+                "<html><body><p><a>aa bbb</a></p></body></html>",
+                &("body, p { margin: 0 } p { font-size: 10px } a { display: inline-block } "
+                    .to_string()
+                    + css),
+            );
+            paragraph(&view).children()[0].dimensions().content.width
+        };
+        assert_eq!(30.0, width(""));
+        assert_eq!(20.0, width("p { width: 20px }"));
+        assert_eq!(15.0, width("p { width: 5px }"));
+        assert_eq!(5.0, width("p { width: 5px } a { overflow-wrap: anywhere }"));
+        assert_eq!(30.0, width("p { width: 5px } a { white-space: nowrap }"));
+    }
+}
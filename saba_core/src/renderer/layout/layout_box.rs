@@ -0,0 +1,642 @@
+use crate::renderer::{
+    css::{
+        computed_style::{
+            BorderStyle, CaptionSide, ComputedStyle, Display, Float, Overflow, Position, Sides,
+            WhiteSpace,
+        },
+        value::{Color, Length},
+    },
+    dom::{
+        api::children,
+        node::{ElementKind, Node, NodeKind},
+    },
+    layout::{
+        geometry::{Dimensions, Rect},
+        overflow::ScrollOffset,
+        replaced::{build_image_boxes, ReplacedContent},
+    },
+};
+use alloc::{rc::Rc, string::String, vec, vec::Vec};
+use core::cell::RefCell;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BoxKind {
+    // ブロックレベルの要素のボックス
+    Block,
+    // インラインレベルで中身がインライン整形文脈に参加するボックス
+    Inline,
+    // inline-blockのように中身を独立してレイアウトするインラインレベルのボックス
+    InlineBlock,
+    AnonymousBlock,
+    // セルを格子状に並べるテーブルボックス。テーブル要素の余白やキャプションは外側のラッパーボックスが持つ
+    Table,
+    Text(String),
+}
+
+// インラインレベルのボックスが行ごとに分割された断片
+#[derive(Clone, Debug, PartialEq)]
+pub struct Fragment {
+    pub rect: Rect,
+    // テキストの場合は断片に含まれる文字列。インラインボックスの場合は空
+    pub text: String,
+    // 元のテキストの中での開始位置(文字数)
+    pub offset: usize,
+}
+
+// インライン整形文脈の1行
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LineBox {
+    pub rect: Rect,
+    // 行のベースラインのy座標
+    pub baseline: f64,
+}
+
+#[derive(Clone, Debug)]
+pub struct LayoutBox {
+    pub(super) kind: BoxKind,
+    pub(super) node: Option<Rc<RefCell<Node>>>,
+    pub(super) style: ComputedStyle,
+    pub(super) dimensions: Dimensions,
+    pub(super) fragments: Vec<Fragment>,
+    pub(super) lines: Vec<LineBox>,
+    pub(super) children: Vec<LayoutBox>,
+    // パディングボックスと子孫のボーダーボックスを合わせた領域
+    pub(super) scrollable_overflow: Rect,
+    // スクロールコンテナの場合のスクロール位置
+    pub(super) scroll_offset: ScrollOffset,
+    // stickyの位置指定でずらしている量
+    pub(super) sticky_shift: (f64, f64),
+    // 画像のような置換要素の内容
+    pub(super) replaced: Option<ReplacedContent>,
+}
+
+impl LayoutBox {
+    pub fn new(kind: BoxKind, node: Option<Rc<RefCell<Node>>>, style: ComputedStyle) -> Self {
+        Self {
+            kind,
+            node,
+            style,
+            dimensions: Dimensions::default(),
+            fragments: Vec::new(),
+            lines: Vec::new(),
+            children: Vec::new(),
+            scrollable_overflow: Rect::default(),
+            scroll_offset: ScrollOffset::default(),
+            sticky_shift: (0.0, 0.0),
+            replaced: None,
+        }
+    }
+
+    fn anonymous_block(parent_style: &ComputedStyle, children: Vec<LayoutBox>) -> Self {
+        let mut style = ComputedStyle::inherit_from(parent_style);
+        style.display = Display::Block;
+        // border-styleがnoneなのでボーダーの幅は0になる
+        style.finalize(false);
+        let mut anonymous = LayoutBox::new(BoxKind::AnonymousBlock, None, style);
+        anonymous.children = children;
+        anonymous
+    }
+
+    // 匿名のテーブル要素。CSS 2.1 17.2.1
+    fn anonymous_table_box(
+        parent_style: &ComputedStyle,
+        display: Display,
+        children: Vec<LayoutBox>,
+    ) -> Self {
+        let mut style = ComputedStyle::inherit_from(parent_style);
+        style.display = display;
+        style.finalize(false);
+        let children = fix_table_structure(&style, children);
+        if matches!(display, Display::Table | Display::InlineTable) {
+            return LayoutBox::table_wrapper(None, style, children);
+        }
+        let mut anonymous = LayoutBox::new(BoxKind::AnonymousBlock, None, style);
+        anonymous.children = if display == Display::TableCell {
+            fix_block_children(&anonymous.style, children)
+        } else {
+            children
+        };
+        anonymous
+    }
+
+    // テーブル要素のボックス。マージンとキャプションを持つラッパーボックスの中にテーブルボックスを置く。CSS 2.1 17.4
+    fn table_wrapper(
+        node: Option<Rc<RefCell<Node>>>,
+        style: ComputedStyle,
+        children: Vec<LayoutBox>,
+    ) -> Self {
+        let (captions, children): (Vec<LayoutBox>, Vec<LayoutBox>) = children
+            .into_iter()
+            .partition(|child| child.style.display == Display::TableCaption);
+
+        let mut table_style = style.clone();
+        table_style.display = Display::Table;
+        table_style.margin = Sides::all(Length::Px(0.0));
+        table_style.position = Position::Static;
+        table_style.float = Float::None;
+        table_style.z_index = None;
+        table_style.overflow_x = Overflow::Visible;
+        table_style.overflow_y = Overflow::Visible;
+        table_style.opacity = 1.0;
+        let mut table = LayoutBox::new(BoxKind::Table, node.clone(), table_style);
+        table.children = children;
+        table.collapse_borders();
+
+        let mut wrapper_style = style;
+        wrapper_style.padding = Sides::all(Length::Px(0.0));
+        wrapper_style.border_width = Sides::all(0.0);
+        wrapper_style.border_style = Sides::all(BorderStyle::None);
+        wrapper_style.background_color = Color::TRANSPARENT;
+        wrapper_style.width = Length::Auto;
+        wrapper_style.height = Length::Auto;
+        wrapper_style.min_width = Length::Auto;
+        wrapper_style.min_height = Length::Auto;
+        wrapper_style.max_width = None;
+        wrapper_style.max_height = None;
+        let kind = if wrapper_style.display.is_inline_level() {
+            BoxKind::InlineBlock
+        } else {
+            BoxKind::Block
+        };
+        let (top, bottom): (Vec<LayoutBox>, Vec<LayoutBox>) = captions
+            .into_iter()
+            .partition(|caption| caption.style.caption_side == CaptionSide::Top);
+        let mut wrapper = LayoutBox::new(kind, node, wrapper_style);
+        wrapper.children = top;
+        wrapper.children.push(table);
+        wrapper.children.extend(bottom);
+        wrapper
+    }
+
+    pub fn kind(&self) -> BoxKind {
+        self.kind.clone()
+    }
+
+    pub fn node(&self) -> Option<Rc<RefCell<Node>>> {
+        self.node.clone()
+    }
+
+    pub fn style(&self) -> &ComputedStyle {
+        &self.style
+    }
+
+    pub fn dimensions(&self) -> Dimensions {
+        self.dimensions
+    }
+
+    pub fn fragments(&self) -> &[Fragment] {
+        &self.fragments
+    }
+
+    pub fn lines(&self) -> &[LineBox] {
+        &self.lines
+    }
+
+    pub fn children(&self) -> &[LayoutBox] {
+        &self.children
+    }
+
+    pub fn is_inline_level(&self) -> bool {
+        matches!(
+            self.kind,
+            BoxKind::Inline | BoxKind::InlineBlock | BoxKind::Text(_)
+        )
+    }
+
+    // 通常フローの子がすべてインラインレベルであればインライン整形文脈を持つ
+    pub fn has_inline_children(&self) -> bool {
+        self.children.iter().any(|c| c.is_inline_level())
+            && self
+                .children
+                .iter()
+                .all(|c| c.is_inline_level() || c.is_out_of_flow())
+    }
+
+    pub fn is_flex_container(&self) -> bool {
+        matches!(self.style.display, Display::Flex | Display::InlineFlex)
+    }
+
+    pub fn is_grid_container(&self) -> bool {
+        matches!(self.style.display, Display::Grid | Display::InlineGrid)
+    }
+
+    pub fn is_table_wrapper(&self) -> bool {
+        self.kind != BoxKind::Table
+            && matches!(self.style.display, Display::Table | Display::InlineTable)
+    }
+
+    // 折りたたまれて消える空白だけのテキスト
+    fn is_collapsible_whitespace(&self) -> bool {
+        match &self.kind {
+            BoxKind::Text(text) => {
+                matches!(
+                    self.style.white_space,
+                    WhiteSpace::Normal | WhiteSpace::Nowrap
+                ) && text.chars().all(|c| matches!(c, ' ' | '\t' | '\n' | '\r'))
+            }
+            _ => false,
+        }
+    }
+
+    pub fn translate(&mut self, dx: f64, dy: f64) {
+        self.dimensions.content.translate(dx, dy);
+        self.scrollable_overflow.translate(dx, dy);
+        for fragment in &mut self.fragments {
+            fragment.rect.translate(dx, dy);
+        }
+        for line in &mut self.lines {
+            line.rect.translate(dx, dy);
+            line.baseline += dy;
+        }
+        for child in &mut self.children {
+            child.translate(dx, dy);
+        }
+    }
+}
+
+// ブロックコンテナの子がブロックレベルとインラインレベルの混在であれば、インラインレベルの並びを匿名ブロックで包む
+fn fix_block_children(style: &ComputedStyle, boxes: Vec<LayoutBox>) -> Vec<LayoutBox> {
+    if boxes
+        .iter()
+        .all(|b| b.is_inline_level() || b.is_out_of_flow())
+    {
+        return boxes;
+    }
+
+    let mut result = Vec::new();
+    let mut inlines: Vec<LayoutBox> = Vec::new();
+    for b in boxes {
+        if b.is_inline_level() {
+            inlines.push(b);
+            continue;
+        }
+        // 通常フローの外にあるボックスはインラインレベルの並びの途中であればその中に置く
+        if b.is_out_of_flow() {
+            if inlines.iter().all(|i| i.is_collapsible_whitespace()) {
+                result.push(b);
+            } else {
+                inlines.push(b);
+            }
+            continue;
+        }
+        if !inlines.iter().all(|i| i.is_collapsible_whitespace()) {
+            result.push(LayoutBox::anonymous_block(style, inlines));
+        }
+        inlines = Vec::new();
+        result.push(b);
+    }
+    if !inlines.iter().all(|i| i.is_collapsible_whitespace()) {
+        result.push(LayoutBox::anonymous_block(style, inlines));
+    }
+    result
+}
+
+// フレックスコンテナやグリッドコンテナの子はそれぞれがブロック化されたアイテムになる。連続するテキストは匿名ブロックで包む
+fn fix_container_items(style: &ComputedStyle, boxes: Vec<LayoutBox>) -> Vec<LayoutBox> {
+    let mut result = Vec::new();
+    let mut texts: Vec<LayoutBox> = Vec::new();
+    for mut b in boxes {
+        if let BoxKind::Text(_) = b.kind {
+            texts.push(b);
+            continue;
+        }
+        if !texts.iter().all(|t| t.is_collapsible_whitespace()) {
+            result.push(LayoutBox::anonymous_block(style, texts));
+        }
+        texts = Vec::new();
+        if b.kind == BoxKind::Inline {
+            b.children = fix_block_children(&b.style, core::mem::take(&mut b.children));
+        }
+        b.kind = BoxKind::Block;
+        b.style.display = b.style.display.blockify();
+        // フレックスアイテムやグリッドアイテムはフロートにならない
+        b.style.float = Float::None;
+        result.push(b);
+    }
+    if !texts.iter().all(|t| t.is_collapsible_whitespace()) {
+        result.push(LayoutBox::anonymous_block(style, texts));
+    }
+    result
+}
+
+fn is_table_internal(display: Display) -> bool {
+    matches!(
+        display,
+        Display::TableRowGroup
+            | Display::TableHeaderGroup
+            | Display::TableFooterGroup
+            | Display::TableRow
+            | Display::TableCell
+            | Display::TableColumnGroup
+            | Display::TableColumn
+            | Display::TableCaption
+    )
+}
+
+// テーブルボックスの直下に置ける子
+fn is_proper_table_child(display: Display) -> bool {
+    is_table_internal(display) && display != Display::TableCell
+}
+
+// keepを満たさないボックスの連続をwrapで1つのボックスにまとめる
+fn wrap_runs(
+    boxes: Vec<LayoutBox>,
+    keep: impl Fn(&LayoutBox) -> bool,
+    wrap: impl Fn(Vec<LayoutBox>) -> LayoutBox,
+) -> Vec<LayoutBox> {
+    let mut result = Vec::new();
+    let mut run = Vec::new();
+    for b in boxes {
+        if keep(&b) {
+            if !run.is_empty() {
+                result.push(wrap(core::mem::take(&mut run)));
+            }
+            result.push(b);
+        } else {
+            run.push(b);
+        }
+    }
+    if !run.is_empty() {
+        result.push(wrap(run));
+    }
+    result
+}
+
+// CSS 2.1 17.2.1 テーブルの構造に合わない子を取り除くか、匿名のテーブル要素で包む
+fn fix_table_structure(style: &ComputedStyle, boxes: Vec<LayoutBox>) -> Vec<LayoutBox> {
+    let without_whitespace = |boxes: Vec<LayoutBox>| {
+        boxes
+            .into_iter()
+            .filter(|b| !b.is_collapsible_whitespace())
+            .collect::<Vec<_>>()
+    };
+    match style.display {
+        Display::Table | Display::InlineTable => wrap_runs(
+            without_whitespace(boxes),
+            |b| is_proper_table_child(b.style.display),
+            |run| LayoutBox::anonymous_table_box(style, Display::TableRow, run),
+        ),
+        Display::TableRowGroup | Display::TableHeaderGroup | Display::TableFooterGroup => {
+            wrap_runs(
+                without_whitespace(boxes),
+                |b| b.style.display == Display::TableRow,
+                |run| LayoutBox::anonymous_table_box(style, Display::TableRow, run),
+            )
+        }
+        Display::TableRow => wrap_runs(
+            without_whitespace(boxes),
+            |b| b.style.display == Display::TableCell,
+            |run| LayoutBox::anonymous_table_box(style, Display::TableCell, run),
+        ),
+        Display::TableColumnGroup => boxes
+            .into_iter()
+            .filter(|b| b.style.display == Display::TableColumn)
+            .collect(),
+        Display::TableColumn => Vec::new(),
+        _ => {
+            if !boxes.iter().any(|b| is_table_internal(b.style.display)) {
+                return boxes;
+            }
+            // テーブルの内部要素に挟まれた空白は取り除く
+            let internal = |i: Option<usize>| {
+                i.and_then(|i| boxes.get(i))
+                    .is_some_and(|b| is_table_internal(b.style.display))
+            };
+            let removed = (0..boxes.len())
+                .map(|i| {
+                    boxes[i].is_collapsible_whitespace()
+                        && internal(i.checked_sub(1))
+                        && internal(Some(i + 1))
+                })
+                .collect::<Vec<_>>();
+            let boxes = boxes
+                .into_iter()
+                .zip(removed)
+                .filter(|(_, removed)| !removed)
+                .map(|(b, _)| b)
+                .collect();
+            // 行の外にあるセルを匿名の行で、テーブルの外にある行などを匿名のテーブルで包む
+            let boxes = wrap_runs(
+                boxes,
+                |b| b.style.display != Display::TableCell,
+                |run| LayoutBox::anonymous_table_box(style, Display::TableRow, run),
+            );
+            let table = if style.display == Display::Inline {
+                Display::InlineTable
+            } else {
+                Display::Table
+            };
+            wrap_runs(
+                boxes,
+                |b| !is_proper_table_child(b.style.display),
+                |run| LayoutBox::anonymous_table_box(style, table, run),
+            )
+        }
+    }
+}
+
+fn build_children(node: &Rc<RefCell<Node>>, style: &ComputedStyle) -> Vec<LayoutBox> {
+    children(node)
+        .iter()
+        .flat_map(|child| build_boxes(child, style))
+        .collect()
+}
+
+// ノードから生成されるボックスの列。display: contentsやブロックを含むインラインボックスは複数のボックスになる
+pub fn build_boxes(node: &Rc<RefCell<Node>>, parent_style: &ComputedStyle) -> Vec<LayoutBox> {
+    let kind = node.borrow().kind();
+    let style = match kind {
+        NodeKind::Text(text) => {
+            return vec![LayoutBox::new(
+                BoxKind::Text(text),
+                Some(node.clone()),
+                ComputedStyle::inherit_from(parent_style),
+            )];
+        }
+        NodeKind::Element(_) => match node.borrow().computed_style() {
+            Some(style) => style,
+            None => return Vec::new(),
+        },
+        _ => return Vec::new(),
+    };
+    if node.borrow().element_kind() == Some(ElementKind::Img) {
+        return build_image_boxes(node, style);
+    }
+
+    match style.display {
+        Display::None => Vec::new(),
+        Display::Contents => build_children(node, &style),
+        Display::Inline => {
+            let children = fix_table_structure(&style, build_children(node, &style));
+            if children
+                .iter()
+                .all(|c| c.is_inline_level() || c.is_out_of_flow())
+            {
+                let mut inline = LayoutBox::new(BoxKind::Inline, Some(node.clone()), style);
+                inline.children = children;
+                return vec![inline];
+            }
+
+            // ブロックレベルの子の前後でインラインボックスを分割する
+            let mut result = Vec::new();
+            let mut inlines = Vec::new();
+            for child in children {
+                if child.is_inline_level() || child.is_out_of_flow() {
+                    inlines.push(child);
+                    continue;
+                }
+                if !inlines.is_empty() {
+                    let mut inline =
+                        LayoutBox::new(BoxKind::Inline, Some(node.clone()), style.clone());
+                    inline.children = core::mem::take(&mut inlines);
+                    result.push(inline);
+                }
+                result.push(child);
+            }
+            if !inlines.is_empty() {
+                let mut inline = LayoutBox::new(BoxKind::Inline, Some(node.clone()), style);
+                inline.children = inlines;
+                result.push(inline);
+            }
+            result
+        }
+        display => {
+            let kind = if display.is_inline_level() {
+                BoxKind::InlineBlock
+            } else {
+                BoxKind::Block
+            };
+            let children = fix_table_structure(&style, build_children(node, &style));
+            if matches!(display, Display::Table | Display::InlineTable) {
+                return vec![LayoutBox::table_wrapper(
+                    Some(node.clone()),
+                    style,
+                    children,
+                )];
+            }
+            let mut block = LayoutBox::new(kind, Some(node.clone()), style);
+            block.children = if block.is_flex_container() || block.is_grid_container() {
+                fix_container_items(&block.style, children)
+            } else {
+                fix_block_children(&block.style, children)
+            };
+            vec![block]
+        }
+    }
+}
+
+// ルート要素のボックスを生成する。ルートが複数のボックスになる場合は匿名ブロックで包む
+pub fn build_box_tree(document: &Rc<RefCell<Node>>) -> Option<LayoutBox> {
+    let root = children(document)
+        .into_iter()
+        .find(|child| child.borrow().get_element().is_some())?;
+    let default_style = ComputedStyle::default();
+    let mut boxes = build_boxes(&root, &default_style);
+    if boxes.len() == 1 && boxes[0].kind == BoxKind::Block {
+        return boxes.pop();
+    }
+    if boxes.is_empty() {
+        return None;
+    }
+    let children = fix_block_children(&default_style, boxes);
+    Some(LayoutBox::anonymous_block(&default_style, children))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::layout::style_document;
+    use alloc::string::ToString;
+
+    fn box_tree(html: &str, css: &str) -> LayoutBox {
+        let document = style_document(html, css);
+        build_box_tree(&document).expect("failed to build box tree")
+    }
+
+    fn kinds(b: &LayoutBox) -> Vec<BoxKind> {
+        b.children().iter().map(|c| c.kind()).collect()
+    }
+
+    #[test]
+    fn test_display_none() {
+        let root = box_tree(
+            "<html><head><style></style></head><body><p>a</p><p class=\"hidden\">b</p></body></html>",
+            ".hidden { display: none }",
+        );
+        assert_eq!(BoxKind::Block, root.kind());
+        // headはUAスタイルシートでdisplay: none
+        assert_eq!(vec![BoxKind::Block], kinds(&root));
+        let body = &root.children()[0];
+        assert_eq!(vec![BoxKind::Block], kinds(body));
+        assert_eq!(
+            vec![BoxKind::Text("a".to_string())],
+            kinds(&body.children()[0])
+        );
+    }
+
+    #[test]
+    fn test_anonymous_blocks() {
+        let root = box_tree(
+            "<html><body><p>a<a class=\"block\">b</a>c <a>d</a></p></body></html>",
+            ".block { display: block }",
+        );
+        let p = &root.children()[0].children()[0];
+        assert_eq!(
+            vec![
+                BoxKind::AnonymousBlock,
+                BoxKind::Block,
+                BoxKind::AnonymousBlock
+            ],
+            kinds(p)
+        );
+        assert!(p.children()[0].has_inline_children());
+        assert_eq!(
+            vec![BoxKind::Text("c ".to_string()), BoxKind::Inline],
+            kinds(&p.children()[2])
+        );
+    }
+
+    #[test]
+    fn test_split_inline() {
+        let root = box_tree(
+            "<html><body><p><a>a<h1>b</h1>c</a></p></body></html>",
+            "h1 { display: block }",
+        );
+        let p = &root.children()[0].children()[0];
+        assert_eq!(
+            vec![
+                BoxKind::AnonymousBlock,
+                BoxKind::Block,
+                BoxKind::AnonymousBlock
+            ],
+            kinds(p)
+        );
+        assert_eq!(vec![BoxKind::Inline], kinds(&p.children()[0]));
+        assert_eq!(vec![BoxKind::Inline], kinds(&p.children()[2]));
+    }
+
+    #[test]
+    fn test_display_contents() {
+        let root = box_tree(
+            "<html><body><p><a class=\"contents\">a<h1>b</h1></a></p></body></html>",
+            ".contents { display: contents }",
+        );
+        let p = &root.children()[0].children()[0];
+        assert_eq!(vec![BoxKind::AnonymousBlock, BoxKind::Block], kinds(p));
+    }
+
+    #[test]
+    fn test_flex_items() {
+        let root = box_tree(
+            "<html><body><p>a <a>b</a> <a class=\"inline-block\">c</a></p></body></html>",
+            "p { display: flex } .inline-block { display: inline-block }",
+        );
+        let p = &root.children()[0].children()[0];
+        assert_eq!(
+            vec![BoxKind::AnonymousBlock, BoxKind::Block, BoxKind::Block],
+            kinds(p)
+        );
+        assert_eq!(Display::Block, p.children()[1].style().display);
+        assert!(p.children()[1].has_inline_children());
+    }
+}
@@ -0,0 +1,212 @@
+use crate::renderer::{
+    dom::node::Node,
+    layout::{
+        block::ContainingBlock,
+        float::FloatContext,
+        font_metrics::{FixedWidthFont, FontMetrics},
+        geometry::Rect,
+        layout_box::{build_box_tree, LayoutBox},
+        overflow::{max_scroll_offset, reveal_offset, ScrollOffset},
+    },
+};
+use alloc::{rc::Rc, vec::Vec};
+use core::{cell::RefCell, fmt};
+
+// レイアウト中に各ボックスから参照する情報
+pub struct LayoutContext<'a> {
+    pub font_metrics: &'a dyn FontMetrics,
+    pub viewport_width: f64,
+    pub viewport_height: f64,
+    // レイアウト中のブロック整形文脈に置かれたフロート
+    pub floats: RefCell<FloatContext>,
+}
+
+#[derive(Clone)]
+pub struct LayoutView {
+    root: Option<LayoutBox>,
+    viewport_width: f64,
+    viewport_height: f64,
+    // ビューポートのスクロール位置
+    scroll_offset: ScrollOffset,
+    font_metrics: Rc<dyn FontMetrics>,
+}
+
+impl LayoutView {
+    // スタイルが計算済みのDOMツリーからボックスツリーを作り、ビューポートの幅でレイアウトする
+    pub fn new(document: &Rc<RefCell<Node>>, viewport_width: f64, viewport_height: f64) -> Self {
+        Self::with_font_metrics(
+            document,
+            viewport_width,
+            viewport_height,
+            Rc::new(FixedWidthFont),
+        )
+    }
+
+    pub fn with_font_metrics(
+        document: &Rc<RefCell<Node>>,
+        viewport_width: f64,
+        viewport_height: f64,
+        font_metrics: Rc<dyn FontMetrics>,
+    ) -> Self {
+        let mut view = Self {
+            root: build_box_tree(document),
+            viewport_width,
+            viewport_height,
+            scroll_offset: ScrollOffset::default(),
+            font_metrics,
+        };
+        view.layout();
+        view
+    }
+
+    fn layout(&mut self) {
+        let root = match &mut self.root {
+            Some(root) => root,
+            None => return,
+        };
+        let context = LayoutContext {
+            font_metrics: self.font_metrics.as_ref(),
+            viewport_width: self.viewport_width,
+            viewport_height: self.viewport_height,
+            floats: RefCell::new(FloatContext::default()),
+        };
+        // 初期包含ブロックはビューポートの大きさを持つ
+        root.layout_block(
+            &ContainingBlock {
+                x: 0.0,
+                width: self.viewport_width,
+                height: Some(self.viewport_height),
+            },
+            &context,
+        );
+        let margin_top = root.dimensions().margin.top;
+        root.translate(0.0, margin_top);
+        // 位置指定されたボックスは通常フローの位置が決まってから置く
+        root.layout_positioned(
+            &Rect::new(0.0, 0.0, self.viewport_width, self.viewport_height),
+            &context,
+        );
+        root.compute_overflow();
+        self.update_sticky_positions();
+    }
+
+    fn viewport(&self) -> Rect {
+        Rect::new(0.0, 0.0, self.viewport_width, self.viewport_height)
+    }
+
+    // 文書全体のスクロール可能な領域。初期包含ブロックより小さくはならない
+    pub fn scrollable_overflow(&self) -> Rect {
+        let viewport = self.viewport();
+        match &self.root {
+            Some(root) if root.clips_overflow() => viewport.union(&root.dimensions().border_box()),
+            Some(root) => viewport
+                .union(&root.dimensions().border_box())
+                .union(&root.scrollable_overflow()),
+            None => viewport,
+        }
+    }
+
+    pub fn scroll_offset(&self) -> ScrollOffset {
+        self.scroll_offset
+    }
+
+    pub fn max_scroll_offset(&self) -> ScrollOffset {
+        max_scroll_offset(&self.scrollable_overflow(), &self.viewport())
+    }
+
+    // ビューポートをスクロールし、スクロールできる範囲に収めた位置を返す
+    pub fn scroll_to(&mut self, offset: &ScrollOffset) -> ScrollOffset {
+        self.scroll_offset = offset.clamp(&self.max_scroll_offset());
+        self.update_sticky_positions();
+        self.scroll_offset
+    }
+
+    pub fn element_scroll_offset(&self, node: &Rc<RefCell<Node>>) -> Option<ScrollOffset> {
+        let root = self.root.as_ref()?;
+        root.find_scroll_container(node).map(|b| b.scroll_offset())
+    }
+
+    // 要素のスクロールコンテナをスクロールする。要素がスクロールコンテナでなければNone
+    pub fn scroll_element_to(
+        &mut self,
+        node: &Rc<RefCell<Node>>,
+        offset: &ScrollOffset,
+    ) -> Option<ScrollOffset> {
+        let root = self.root.as_mut()?;
+        let offset = root
+            .find_scroll_container_mut(node)?
+            .set_scroll_offset(offset);
+        self.update_sticky_positions();
+        Some(offset)
+    }
+
+    // 0でないスクロール位置を持つ要素とその位置
+    pub fn element_scroll_offsets(&self) -> Vec<(Rc<RefCell<Node>>, ScrollOffset)> {
+        let mut offsets = Vec::new();
+        if let Some(root) = &self.root {
+            root.collect_scroll_offsets(&mut offsets);
+        }
+        offsets
+    }
+
+    // 要素が見えるように、内側のスクロールコンテナから順にビューポートまでスクロールする
+    pub fn scroll_into_view(&mut self, node: &Rc<RefCell<Node>>) -> bool {
+        let root = match &mut self.root {
+            Some(root) => root,
+            None => return false,
+        };
+        let (path, mut rect) = match (root.path_to(node), root.border_box_of(node)) {
+            (Some(path), Some(rect)) => (path, rect),
+            _ => return false,
+        };
+        for depth in (0..path.len()).rev() {
+            let container = root.descendant_mut(&path[..depth]);
+            if !container.is_scroll_container() {
+                continue;
+            }
+            let scrollport = container.dimensions().padding_box();
+            let desired = reveal_offset(&rect, &scrollport, &container.scroll_offset());
+            let offset = container.set_scroll_offset(&desired);
+            // 外側から見た要素の位置は内側のスクロールの分だけずれる
+            rect.translate(-offset.x, -offset.y);
+        }
+        let desired = reveal_offset(&rect, &self.viewport(), &self.scroll_offset);
+        self.scroll_to(&desired);
+        true
+    }
+
+    fn update_sticky_positions(&mut self) {
+        let mut scrollport = self.viewport();
+        scrollport.translate(self.scroll_offset.x, self.scroll_offset.y);
+        if let Some(root) = &mut self.root {
+            root.update_sticky_positions(&scrollport);
+        }
+    }
+
+    pub fn root(&self) -> Option<&LayoutBox> {
+        self.root.as_ref()
+    }
+
+    pub fn viewport_width(&self) -> f64 {
+        self.viewport_width
+    }
+
+    pub fn viewport_height(&self) -> f64 {
+        self.viewport_height
+    }
+
+    pub fn font_metrics(&self) -> Rc<dyn FontMetrics> {
+        self.font_metrics.clone()
+    }
+}
+
+impl fmt::Debug for LayoutView {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LayoutView")
+            .field("root", &self.root)
+            .field("viewport_width", &self.viewport_width)
+            .field("viewport_height", &self.viewport_height)
+            .field("scroll_offset", &self.scroll_offset)
+            .finish()
+    }
+}
@@ -0,0 +1,284 @@
+use alloc::{vec, vec::Vec};
+
+// UAX #14の改行クラスのうち、この実装で区別するもの
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineBreakClass {
+    // 必須の改行
+    BK,
+    CR,
+    LF,
+    NL,
+    SP,
+    ZW,
+    ZWJ,
+    WJ,
+    GL,
+    CM,
+    // 開き括弧、閉じ括弧
+    OP,
+    CL,
+    CP,
+    QU,
+    EX,
+    IS,
+    SY,
+    NU,
+    PR,
+    PO,
+    HY,
+    BA,
+    BB,
+    B2,
+    IN,
+    NS,
+    // 表意文字
+    ID,
+    // 置換される要素などのオブジェクト
+    CB,
+    AL,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BreakOpportunity {
+    Prohibited,
+    Allowed,
+    Mandatory,
+}
+
+pub fn line_break_class(c: char) -> LineBreakClass {
+    use LineBreakClass::*;
+    match c {
+        '\u{b}' | '\u{c}' | '\u{2028}' | '\u{2029}' => BK,
+        '\r' => CR,
+        '\n' => LF,
+        '\u{85}' => NL,
+        ' ' => SP,
+        '\u{200b}' => ZW,
+        '\u{200d}' => ZWJ,
+        '\u{2060}' | '\u{feff}' => WJ,
+        '\u{a0}' | '\u{202f}' | '\u{2007}' | '\u{2011}' | '\u{34f}' => GL,
+        '\t' | '|' | '\u{ad}' | '\u{2010}' | '\u{2012}' | '\u{2013}' | '\u{1680}' | '\u{205f}' => {
+            BA
+        }
+        '\u{2000}'..='\u{2006}' | '\u{2008}'..='\u{200a}' => BA,
+        '\u{0}'..='\u{1f}' | '\u{7f}'..='\u{9f}' => CM,
+        '\u{300}'..='\u{36f}' | '\u{20d0}'..='\u{20ff}' | '\u{fe00}'..='\u{fe0f}' => CM,
+        '\u{b4}' | '\u{2c8}' | '\u{2cc}' | '\u{2df}' => BB,
+        '\u{2014}' => B2,
+        '-' => HY,
+        '(' | '[' | '{' | '\u{a1}' | '\u{bf}' | '\u{2018}' | '\u{201c}' => OP,
+        '\u{3008}' | '\u{300a}' | '\u{300c}' | '\u{300e}' | '\u{3010}' | '\u{3014}'
+        | '\u{3016}' | '\u{3018}' | '\u{301a}' | '\u{ff08}' | '\u{ff3b}' | '\u{ff5b}' => OP,
+        ')' | ']' => CP,
+        '}' | '\u{3001}' | '\u{3002}' | '\u{3009}' | '\u{300b}' | '\u{300d}' | '\u{300f}'
+        | '\u{3011}' | '\u{3015}' | '\u{3017}' | '\u{3019}' | '\u{301b}' | '\u{ff09}'
+        | '\u{ff0c}' | '\u{ff0e}' | '\u{ff3d}' | '\u{ff5d}' | '\u{2019}' | '\u{201d}' => CL,
+        '"' | '\'' | '\u{ab}' | '\u{bb}' => QU,
+        '!' | '?' | '\u{ff01}' | '\u{ff1f}' => EX,
+        ',' | '.' | ':' | ';' | '\u{37e}' | '\u{589}' => IS,
+        '/' => SY,
+        '0'..='9' => NU,
+        '$' | '+' | '\\' | '\u{a3}' | '\u{a5}' | '\u{20ac}' | '\u{20a9}' | '\u{2116}' => PR,
+        '%' | '\u{a2}' | '\u{b0}' | '\u{2030}' | '\u{2032}' | '\u{2033}' | '\u{2103}' => PO,
+        '\u{2024}'..='\u{2026}' => IN,
+        // 小書きの仮名や長音記号は行頭に置かない
+        '\u{3005}' | '\u{303b}' | '\u{309d}' | '\u{309e}' | '\u{30fb}'..='\u{30fe}' => NS,
+        '\u{3041}' | '\u{3043}' | '\u{3045}' | '\u{3047}' | '\u{3049}' | '\u{3063}'
+        | '\u{3083}' | '\u{3085}' | '\u{3087}' | '\u{308e}' | '\u{3095}' | '\u{3096}' => NS,
+        '\u{30a1}' | '\u{30a3}' | '\u{30a5}' | '\u{30a7}' | '\u{30a9}' | '\u{30c3}'
+        | '\u{30e3}' | '\u{30e5}' | '\u{30e7}' | '\u{30ee}' | '\u{30f5}' | '\u{30f6}' => NS,
+        '\u{fffc}' => CB,
+        '\u{1100}'..='\u{115f}'
+        | '\u{2e80}'..='\u{2fff}'
+        | '\u{3000}'..='\u{303f}'
+        | '\u{3040}'..='\u{30ff}'
+        | '\u{3130}'..='\u{318f}'
+        | '\u{31c0}'..='\u{31ff}'
+        | '\u{3200}'..='\u{4dbf}'
+        | '\u{4e00}'..='\u{9fff}'
+        | '\u{a000}'..='\u{a4cf}'
+        | '\u{ac00}'..='\u{d7a3}'
+        | '\u{f900}'..='\u{faff}'
+        | '\u{fe30}'..='\u{fe4f}'
+        | '\u{ff00}'..='\u{ff60}'
+        | '\u{ffe0}'..='\u{ffe6}'
+        | '\u{1f000}'..='\u{1faff}'
+        | '\u{20000}'..='\u{3fffd}' => ID,
+        _ => AL,
+    }
+}
+
+// 2つのクラスの間で改行できるかどうか。空白を挟む規則のためにbefore_spacesも受け取る
+fn pair_opportunity(
+    before: LineBreakClass,
+    after: LineBreakClass,
+    before_spaces: LineBreakClass,
+) -> BreakOpportunity {
+    use BreakOpportunity::*;
+    use LineBreakClass::*;
+
+    // LB4, LB5
+    if before == BK || before == LF || before == NL || (before == CR && after != LF) {
+        return Mandatory;
+    }
+    if before == CR {
+        return Prohibited;
+    }
+    // LB6, LB7
+    if matches!(after, BK | CR | LF | NL | SP | ZW) {
+        return Prohibited;
+    }
+    // LB8
+    if before_spaces == ZW {
+        return Allowed;
+    }
+    // LB8a, LB11, LB12, LB12a
+    if before == ZWJ || before == WJ || after == WJ || before == GL {
+        return Prohibited;
+    }
+    if after == GL && !matches!(before, SP | BA | HY) {
+        return Prohibited;
+    }
+    // LB13
+    if matches!(after, CL | CP | EX | IS | SY) {
+        return Prohibited;
+    }
+    // LB14 - LB17
+    if before_spaces == OP
+        || (before_spaces == QU && after == OP)
+        || (matches!(before_spaces, CL | CP) && after == NS)
+        || (before_spaces == B2 && after == B2)
+    {
+        return Prohibited;
+    }
+    // LB18
+    if before == SP {
+        return Allowed;
+    }
+    // LB19 - LB22
+    if before == QU || after == QU {
+        return Prohibited;
+    }
+    if before == CB || after == CB {
+        return Allowed;
+    }
+    if matches!(after, BA | HY | NS | IN) || before == BB {
+        return Prohibited;
+    }
+    // LB23 - LB25
+    let prohibited = matches!(
+        (before, after),
+        (AL, NU)
+            | (NU, AL)
+            | (PR, ID)
+            | (ID, PO)
+            | (PR | PO, AL)
+            | (AL, PR | PO)
+            | (CL | CP | NU, PO | PR)
+            | (PO | PR, OP | NU)
+            | (HY | IS | NU | SY, NU)
+            | (AL, AL)
+            | (IS, AL)
+            | (AL | NU, OP)
+            | (CP, AL | NU)
+    );
+    if prohibited {
+        return Prohibited;
+    }
+    // LB31
+    Allowed
+}
+
+// 各文字の直前で改行できるかどうか。先頭の要素は常にProhibited
+pub fn break_opportunities(text: &[char]) -> Vec<BreakOpportunity> {
+    use LineBreakClass::*;
+
+    let mut result = vec![BreakOpportunity::Prohibited; text.len()];
+    let mut classes: Vec<LineBreakClass> = Vec::with_capacity(text.len());
+    // LB9, LB10: 結合文字は直前の文字のクラスを引き継ぐ
+    let mut attached = vec![false; text.len()];
+    for (i, c) in text.iter().enumerate() {
+        let class = line_break_class(*c);
+        if matches!(class, CM | ZWJ) {
+            match classes.last() {
+                Some(previous) if !matches!(previous, BK | CR | LF | NL | SP | ZW) => {
+                    attached[i] = true;
+                    // ZWJの直後では改行しない
+                    classes.push(if class == ZWJ { ZWJ } else { *previous });
+                    continue;
+                }
+                _ => {
+                    classes.push(AL);
+                    continue;
+                }
+            }
+        }
+        classes.push(class);
+    }
+
+    let mut before_spaces = classes.first().copied().unwrap_or(AL);
+    for i in 1..text.len() {
+        let before = classes[i - 1];
+        if before != SP {
+            before_spaces = before;
+        }
+        if attached[i] {
+            continue;
+        }
+        result[i] = pair_opportunity(before, classes[i], before_spaces);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{string::String, vec::Vec};
+
+    // 改行できる位置で区切った文字列の列にする
+    fn segments(text: &str) -> Vec<String> {
+        let chars: Vec<char> = text.chars().collect();
+        let opportunities = break_opportunities(&chars);
+        let mut result = Vec::new();
+        let mut current = String::new();
+        for (c, opportunity) in chars.iter().zip(opportunities) {
+            if opportunity != BreakOpportunity::Prohibited && !current.is_empty() {
+                result.push(core::mem::take(&mut current));
+            }
+            current.push(*c);
+        }
+        if !current.is_empty() {
+            result.push(current);
+        }
+        result
+    }
+
+    #[test]
+    fn test_spaces_and_punctuation() {
+        assert_eq!(vec!["Hello, ", "world!"], segments("Hello, world!"));
+        assert_eq!(vec!["a  ", "(b) ", "c."], segments("a  (b) c."));
+        assert_eq!(vec!["well-", "known"], segments("well-known"));
+        assert_eq!(vec!["-5 ", "$10 ", "20% "], segments("-5 $10 20% "));
+        assert_eq!(vec!["a\u{a0}b ", "c"], segments("a\u{a0}b c"));
+        assert_eq!(vec!["\"quoted\" ", "text"], segments("\"quoted\" text"));
+        assert_eq!(vec!["and/", "or"], segments("and/or"));
+    }
+
+    #[test]
+    fn test_ideographs() {
+        assert_eq!(vec!["日", "本", "語"], segments("日本語"));
+        assert_eq!(vec!["「あ", "い」", "う"], segments("「あい」う"));
+        assert_eq!(vec!["ちょっ", "と"], segments("ちょっと"));
+        assert_eq!(vec!["a", "\u{fffc}", "b"], segments("a\u{fffc}b"));
+    }
+
+    #[test]
+    fn test_mandatory_and_combining() {
+        let chars: Vec<char> = "a\nb\u{301}\u{200b}c".chars().collect();
+        let opportunities = break_opportunities(&chars);
+        assert_eq!(BreakOpportunity::Mandatory, opportunities[2]);
+        assert_eq!(BreakOpportunity::Prohibited, opportunities[3]);
+        assert_eq!(BreakOpportunity::Prohibited, opportunities[4]);
+        assert_eq!(BreakOpportunity::Allowed, opportunities[5]);
+    }
+}
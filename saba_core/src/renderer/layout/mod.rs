@@ -0,0 +1,46 @@
+pub mod block;
+pub mod flex;
+pub mod float;
+pub mod font_metrics;
+pub mod geometry;
+pub mod grid;
+pub mod inline;
+pub mod layout_box;
+pub mod layout_view;
+pub mod line_break;
+pub mod overflow;
+pub mod positioned;
+pub mod replaced;
+pub mod table;
+
+#[cfg(test)]
+use crate::renderer::{
+    css::{
+        cascade::StyleResolver, media::MediaEnvironment, parser::CssParser, token::CssTokenizer,
+    },
+    dom::node::Node,
+    html::{parser::HtmlParser, token::HtmlTokenizer},
+    layout::layout_view::LayoutView,
+};
+#[cfg(test)]
+use alloc::{rc::Rc, string::ToString, vec::Vec};
+#[cfg(test)]
+use core::cell::RefCell;
+
+// テスト用に、HTMLから文書を作りCSSでスタイルを計算する
+#[cfg(test)]
+pub(crate) fn style_document(html: &str, css: &str) -> Rc<RefCell<Node>> {
+    let window = HtmlParser::new(HtmlTokenizer::new(html.to_string())).construct_tree();
+    let style_sheet = CssParser::new(CssTokenizer::new(css.to_string())).parse_stylesheet();
+    let document = window.borrow().document();
+    StyleResolver::new(&[style_sheet], Vec::new(), &MediaEnvironment::default()).resolve(&document);
+    document
+}
+
+// テスト用に、スタイルを計算した文書を600x400のビューポートでレイアウトする
+#[cfg(test)]
+pub(crate) fn layout_document(html: &str, css: &str) -> (Rc<RefCell<Node>>, LayoutView) {
+    let document = style_document(html, css);
+    let view = LayoutView::new(&document, 600.0, 400.0);
+    (document, view)
+}
@@ -0,0 +1,253 @@
+use crate::renderer::{
+    css::computed_style::Overflow,
+    dom::node::Node,
+    layout::{
+        geometry::Rect,
+        layout_box::{BoxKind, LayoutBox},
+    },
+};
+use alloc::{rc::Rc, vec::Vec};
+use core::cell::RefCell;
+
+// スクロールコンテナの内容をどれだけスクロールしたか
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ScrollOffset {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl ScrollOffset {
+    pub fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+
+    // 0からmaxまでの範囲に収める
+    pub fn clamp(&self, max: &ScrollOffset) -> ScrollOffset {
+        ScrollOffset {
+            x: self.x.min(max.x).max(0.0),
+            y: self.y.min(max.y).max(0.0),
+        }
+    }
+}
+
+// 大きさscrollportの窓でoverflowの領域を見るときにスクロールできる最大の量。左と上にはみ出した部分には届かない
+pub(super) fn max_scroll_offset(overflow: &Rect, scrollport: &Rect) -> ScrollOffset {
+    ScrollOffset {
+        x: (overflow.right() - scrollport.right()).max(0.0),
+        y: (overflow.bottom() - scrollport.bottom()).max(0.0),
+    }
+}
+
+// rectがscrollportに入るようにスクロール位置を決める。縦は上端に揃え、横は最小限だけ動かす
+pub(super) fn reveal_offset(
+    rect: &Rect,
+    scrollport: &Rect,
+    current: &ScrollOffset,
+) -> ScrollOffset {
+    let visible_x = rect.x - current.x;
+    let x = if visible_x < scrollport.x || rect.width > scrollport.width {
+        rect.x - scrollport.x
+    } else if visible_x + rect.width > scrollport.right() {
+        rect.right() - scrollport.right()
+    } else {
+        current.x
+    };
+    ScrollOffset {
+        x,
+        y: rect.y - scrollport.y,
+    }
+}
+
+impl LayoutBox {
+    // スクロールで内容を動かせるボックス。CSS Overflow 3 3.1
+    pub fn is_scroll_container(&self) -> bool {
+        self.style.overflow_x.is_scrollable() || self.style.overflow_y.is_scrollable()
+    }
+
+    // はみ出した内容をパディングボックスで切り取るボックス
+    pub fn clips_overflow(&self) -> bool {
+        self.style.overflow_x != Overflow::Visible || self.style.overflow_y != Overflow::Visible
+    }
+
+    pub fn scrollable_overflow(&self) -> Rect {
+        self.scrollable_overflow
+    }
+
+    pub fn scroll_offset(&self) -> ScrollOffset {
+        self.scroll_offset
+    }
+
+    pub fn max_scroll_offset(&self) -> ScrollOffset {
+        if !self.is_scroll_container() {
+            return ScrollOffset::default();
+        }
+        max_scroll_offset(&self.scrollable_overflow, &self.dimensions.padding_box())
+    }
+
+    // スクロールできる範囲に収めたスクロール位置を設定し、その値を返す
+    pub(super) fn set_scroll_offset(&mut self, offset: &ScrollOffset) -> ScrollOffset {
+        self.scroll_offset = offset.clamp(&self.max_scroll_offset());
+        self.scroll_offset
+    }
+
+    // 自身と子孫のスクロール可能なはみ出し領域を求める。CSS Overflow 3 2.2
+    pub(super) fn compute_overflow(&mut self) -> Rect {
+        // インラインボックスとテキストは行ごとの断片が領域になる
+        let mut overflow = match self.kind {
+            BoxKind::Inline | BoxKind::Text(_) => Rect::default(),
+            _ => self.dimensions.padding_box(),
+        };
+        for fragment in &self.fragments {
+            overflow = overflow.union(&fragment.rect);
+        }
+        for child in &mut self.children {
+            let child_overflow = child.compute_overflow();
+            let border_box = match child.kind {
+                BoxKind::Inline | BoxKind::Text(_) => child_overflow,
+                _ => child.dimensions.border_box(),
+            };
+            // 切り取られる内容は外側に伝わらない
+            overflow = if child.clips_overflow() {
+                overflow.union(&border_box)
+            } else {
+                overflow.union(&border_box).union(&child_overflow)
+            };
+        }
+        self.scrollable_overflow = overflow;
+        overflow
+    }
+
+    // ボックスツリーの中で要素のボックスまでの子の位置の列
+    pub(super) fn path_to(&self, node: &Rc<RefCell<Node>>) -> Option<Vec<usize>> {
+        if self.node.as_ref().is_some_and(|n| Rc::ptr_eq(n, node)) {
+            return Some(Vec::new());
+        }
+        self.children.iter().enumerate().find_map(|(i, child)| {
+            let mut path = child.path_to(node)?;
+            path.insert(0, i);
+            Some(path)
+        })
+    }
+
+    // 要素のボックスのうちスクロールコンテナになっているもの
+    pub(super) fn find_scroll_container(&self, node: &Rc<RefCell<Node>>) -> Option<&LayoutBox> {
+        if self.is_scroll_container() && self.node.as_ref().is_some_and(|n| Rc::ptr_eq(n, node)) {
+            return Some(self);
+        }
+        self.children
+            .iter()
+            .find_map(|child| child.find_scroll_container(node))
+    }
+
+    pub(super) fn find_scroll_container_mut(
+        &mut self,
+        node: &Rc<RefCell<Node>>,
+    ) -> Option<&mut LayoutBox> {
+        if self.is_scroll_container() && self.node.as_ref().is_some_and(|n| Rc::ptr_eq(n, node)) {
+            return Some(self);
+        }
+        self.children
+            .iter_mut()
+            .find_map(|child| child.find_scroll_container_mut(node))
+    }
+
+    // スクロールされている子孫の要素とそのスクロール位置
+    pub(super) fn collect_scroll_offsets(
+        &self,
+        offsets: &mut Vec<(Rc<RefCell<Node>>, ScrollOffset)>,
+    ) {
+        if let Some(node) = &self.node {
+            if self.scroll_offset != ScrollOffset::default() {
+                offsets.push((node.clone(), self.scroll_offset));
+            }
+        }
+        for child in &self.children {
+            child.collect_scroll_offsets(offsets);
+        }
+    }
+
+    // 要素のボーダーボックス。インラインボックスは断片をすべて含む矩形
+    pub(super) fn border_box_of(&self, node: &Rc<RefCell<Node>>) -> Option<Rect> {
+        if self.node.as_ref().is_some_and(|n| Rc::ptr_eq(n, node)) {
+            return Some(match self.kind {
+                BoxKind::Inline | BoxKind::Text(_) => self
+                    .fragments
+                    .iter()
+                    .fold(Rect::default(), |rect, f| rect.union(&f.rect)),
+                _ => self.dimensions.border_box(),
+            });
+        }
+        self.children
+            .iter()
+            .find_map(|child| child.border_box_of(node))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::layout::{layout_document, layout_view::LayoutView};
+
+    fn body(view: &LayoutView) -> &LayoutBox {
+        &view.root().expect("failed to get root box").children()[0]
+    }
+
+    #[test]
+    fn test_scrollable_overflow() {
+        let (_, view) = layout_document(
+            "<html><body><p class=\"box\"><a class=\"wide\">a</a></p><p class=\"clip\"><a class=\"tall\">b</a></p></body></html>",
+            "body, p { margin: 0 } p { line-height: 20px } a { display: block } .box { width: 100px; height: 50px; padding: 5px; overflow: auto } .wide { width: 300px; height: 80px; margin-left: 20px } .clip { height: 30px; overflow: clip } .tall { height: 1000px }",
+        );
+        let body = body(&view);
+        let scroller = &body.children()[0];
+        assert!(scroller.is_scroll_container());
+        assert_eq!(
+            Rect::new(0.0, 0.0, 325.0, 85.0),
+            scroller.scrollable_overflow()
+        );
+        assert_eq!(ScrollOffset::new(215.0, 25.0), scroller.max_scroll_offset());
+        // clipはスクロールコンテナではないが、内容を外に伝えない
+        let clip = &body.children()[1];
+        assert!(!clip.is_scroll_container() && clip.clips_overflow());
+        assert_eq!(0.0, clip.max_scroll_offset().y);
+        assert_eq!(90.0, body.scrollable_overflow().bottom());
+        // スクロールコンテナはブロック整形文脈を作る
+        assert!(scroller.establishes_block_formatting_context());
+    }
+
+    #[test]
+    fn test_scroll_element() {
+        let (document, mut view) = layout_document(
+            "<html><body><p id=\"scroller\"><a class=\"sticky\">s</a><a class=\"tall\">a</a><a id=\"target\">b</a></p><p class=\"page\">c</p></body></html>",
+            "body, p { margin: 0 } p { line-height: 20px } a { display: block } #scroller { height: 100px; overflow-y: scroll } .sticky { position: sticky; top: 5px } .tall { height: 300px } .page { height: 1000px }",
+        );
+        let html = document.borrow().first_child().unwrap();
+        let body_node = html.borrow().last_child().upgrade().unwrap();
+        let scroller = body_node.borrow().first_child().unwrap();
+        let target = scroller.borrow().last_child().upgrade().unwrap();
+
+        assert_eq!(
+            Some(ScrollOffset::new(0.0, 240.0)),
+            view.scroll_element_to(&scroller, &ScrollOffset::new(50.0, 1000.0))
+        );
+        assert_eq!(
+            None,
+            view.scroll_element_to(&target, &ScrollOffset::new(0.0, 10.0))
+        );
+        // stickyのボックスはスクロールポートの上端から5pxの位置に留まる
+        let sticky = body(&view).children()[0].children()[0].dimensions();
+        assert_eq!(245.0, sticky.border_box().y);
+
+        view.scroll_element_to(&scroller, &ScrollOffset::default());
+        view.scroll_to(&ScrollOffset::new(0.0, 20.0));
+        assert!(view.scroll_into_view(&target));
+        // 内側のスクロールコンテナで要素を上端に揃え、ビューポートでスクロールコンテナの中の要素を上端に揃える
+        assert_eq!(
+            Some(ScrollOffset::new(0.0, 240.0)),
+            view.element_scroll_offset(&scroller)
+        );
+        assert_eq!(ScrollOffset::new(0.0, 80.0), view.scroll_offset());
+        assert_eq!(1, view.element_scroll_offsets().len());
+        assert_eq!(ScrollOffset::new(0.0, 700.0), view.max_scroll_offset());
+    }
+}
@@ -0,0 +1,355 @@
+use crate::renderer::{
+    css::computed_style::Position,
+    layout::{
+        block::{clamp_size, resolve_height, ContainingBlock, SizeOverride},
+        geometry::Rect,
+        layout_box::LayoutBox,
+        layout_view::LayoutContext,
+    },
+};
+
+impl LayoutBox {
+    pub fn is_positioned(&self) -> bool {
+        self.style.position != Position::Static
+    }
+
+    pub fn is_absolutely_positioned(&self) -> bool {
+        self.style.position.is_absolute()
+    }
+
+    // 重ね合わせ文脈を作るボックス。CSS 2.1 9.9.1, CSS Color 4 15.2
+    pub fn creates_stacking_context(&self) -> bool {
+        self.is_root_element()
+            || matches!(self.style.position, Position::Fixed | Position::Sticky)
+            || self.style.opacity < 1.0
+            || (self.style.z_index.is_some() && (self.is_positioned() || self.is_container_item()))
+    }
+
+    // 重ね合わせ文脈の中での順序。autoは0と同じ層に描く
+    pub fn z_index(&self) -> i32 {
+        self.style.z_index.unwrap_or(0)
+    }
+
+    // 絶対配置のボックスを通常フローでの位置(静的位置)に仮に置く
+    pub(super) fn place_at_static_position(
+        &mut self,
+        containing_block: &ContainingBlock,
+        y: f64,
+        context: &LayoutContext,
+    ) {
+        self.layout_block(containing_block, context);
+        let margin_box = self.dimensions.margin_box();
+        self.translate(containing_block.x - margin_box.x, y - margin_box.y);
+    }
+
+    // フレックスコンテナやグリッドコンテナの絶対配置の子の静的位置は内容領域の左上になる
+    pub(super) fn place_absolute_children(
+        &mut self,
+        containing_block: &ContainingBlock,
+        context: &LayoutContext,
+    ) {
+        let content = self.dimensions.content;
+        let containing_block = ContainingBlock {
+            x: content.x,
+            ..*containing_block
+        };
+        for child in self
+            .children
+            .iter_mut()
+            .filter(|child| child.is_absolutely_positioned())
+        {
+            child.place_at_static_position(&containing_block, content.y, context);
+        }
+    }
+
+    // 相対位置指定でずらす量。CSS 2.1 9.4.3
+    fn relative_offset(&self, containing_block: &ContainingBlock) -> (f64, f64) {
+        let inset = &self.style.inset;
+        let dx = match (
+            inset.left.resolve(containing_block.width),
+            inset.right.resolve(containing_block.width),
+        ) {
+            (Some(left), _) => left,
+            (None, Some(right)) => -right,
+            (None, None) => 0.0,
+        };
+        let dy = match (
+            resolve_height(&inset.top, containing_block),
+            resolve_height(&inset.bottom, containing_block),
+        ) {
+            (Some(top), _) => top,
+            (None, Some(bottom)) => -bottom,
+            (None, None) => 0.0,
+        };
+        (dx, dy)
+    }
+
+    // スクロールポートからinsetだけ内側の範囲に留まるようにずらす量。ただし包含ブロックの外には出ない
+    fn sticky_offset(&self, scrollport: &Rect, containing: &Rect) -> (f64, f64) {
+        let inset = &self.style.inset;
+        let rect = self.dimensions.border_box();
+        let mut dx = 0.0;
+        if let Some(left) = inset.left.resolve(scrollport.width) {
+            let shift = scrollport.x + left - rect.x;
+            if shift > 0.0 {
+                dx = shift.min(containing.right() - rect.right()).max(0.0);
+            }
+        }
+        if let Some(right) = inset.right.resolve(scrollport.width) {
+            let shift = rect.right() - (scrollport.right() - right);
+            if shift > 0.0 {
+                dx = -shift.min(rect.x - containing.x).max(0.0);
+            }
+        }
+        let mut dy = 0.0;
+        if let Some(top) = inset.top.resolve(scrollport.height) {
+            let shift = scrollport.y + top - rect.y;
+            if shift > 0.0 {
+                dy = shift.min(containing.bottom() - rect.bottom()).max(0.0);
+            }
+        }
+        if let Some(bottom) = inset.bottom.resolve(scrollport.height) {
+            let shift = rect.bottom() - (scrollport.bottom() - bottom);
+            if shift > 0.0 {
+                dy = -shift.min(rect.y - containing.y).max(0.0);
+            }
+        }
+        (dx, dy)
+    }
+
+    // 絶対配置のボックスを包含ブロックに対してレイアウトする。CSS 2.1 10.3.7, 10.6.4
+    fn layout_absolute(&mut self, containing: &Rect, context: &LayoutContext) {
+        // 通常フローでのレイアウトで求めた静的位置
+        let static_position = self.dimensions.margin_box();
+        let containing_block = ContainingBlock {
+            x: containing.x,
+            width: containing.width,
+            height: Some(containing.height),
+        };
+        self.resolve_edges(containing.width);
+
+        let style = &self.style;
+        let left = style.inset.left.resolve(containing.width);
+        let right = style.inset.right.resolve(containing.width);
+        let top = resolve_height(&style.inset.top, &containing_block);
+        let bottom = resolve_height(&style.inset.bottom, &containing_block);
+        let margin_left = style.margin.left.resolve(containing.width);
+        let margin_right = style.margin.right.resolve(containing.width);
+        let margin_top = style.margin.top.resolve(containing.width);
+        let margin_bottom = style.margin.bottom.resolve(containing.width);
+        let horizontal = self.dimensions.padding.horizontal()
+            + self.dimensions.border.horizontal()
+            + margin_left.unwrap_or(0.0)
+            + margin_right.unwrap_or(0.0);
+        let vertical = self.dimensions.padding.vertical()
+            + self.dimensions.border.vertical()
+            + margin_top.unwrap_or(0.0)
+            + margin_bottom.unwrap_or(0.0);
+
+        // 左右の両方が決まっていればその間に広げ、そうでなければ内容に合わせる
+        let (min_width, max_width) = self.min_max_width(containing.width);
+        let available = containing.width - left.unwrap_or(0.0) - right.unwrap_or(0.0) - horizontal;
+        let width = match style.width.resolve(containing.width) {
+            Some(width) => self.content_size(width, true),
+            None if left.is_some() && right.is_some() => available.max(0.0),
+            None => self.shrink_to_fit_width(available.max(0.0), context),
+        };
+        let width = clamp_size(width, min_width, max_width);
+        let x = match (left, right) {
+            (Some(left), Some(right)) => {
+                // 余った幅をautoのマージンに割り当てる
+                let free = containing.width - left - right - width - horizontal;
+                let offset = match (margin_left, margin_right) {
+                    (None, None) => (free / 2.0).max(0.0),
+                    (None, Some(_)) => free,
+                    _ => 0.0,
+                };
+                containing.x + left + offset
+            }
+            (Some(left), None) => containing.x + left,
+            (None, Some(right)) => containing.right() - right - width - horizontal,
+            (None, None) => static_position.x,
+        };
+
+        let height = match (
+            top,
+            bottom,
+            resolve_height(&style.height, &containing_block),
+        ) {
+            (Some(top), Some(bottom), None) => {
+                Some((containing.height - top - bottom - vertical).max(0.0))
+            }
+            _ => None,
+        };
+        self.layout_block_with(
+            &ContainingBlock {
+                x,
+                ..containing_block
+            },
+            SizeOverride {
+                width: Some(width),
+                height,
+            },
+            context,
+        );
+
+        let margin_box = self.dimensions.margin_box();
+        let y = match (top, bottom) {
+            (Some(top), Some(bottom)) => {
+                let free = containing.height - top - bottom - margin_box.height;
+                let offset = match (margin_top, margin_bottom) {
+                    (None, None) => (free / 2.0).max(0.0),
+                    (None, Some(_)) => free,
+                    _ => 0.0,
+                };
+                containing.y + top + offset
+            }
+            (Some(top), None) => containing.y + top,
+            (None, Some(bottom)) => containing.bottom() - bottom - margin_box.height,
+            (None, None) => static_position.y,
+        };
+        self.translate(0.0, y - margin_box.y);
+    }
+
+    // 通常フローのレイアウトの後で、位置指定されたボックスをずらしたり包含ブロックに対して置き直したりする
+    pub(super) fn layout_positioned(&mut self, containing: &Rect, context: &LayoutContext) {
+        // 位置指定されたボックスのパディングボックスが絶対配置の子孫の包含ブロックになる
+        let containing = if self.is_positioned() {
+            self.dimensions.padding_box()
+        } else {
+            *containing
+        };
+        let viewport = Rect::new(0.0, 0.0, context.viewport_width, context.viewport_height);
+        let content = self.dimensions.content;
+        // 高さが内容に依存する場合は縦方向のパーセント値を解決しない
+        let content_block = ContainingBlock {
+            x: content.x,
+            width: content.width,
+            height: (!self.style.height.is_auto()).then_some(content.height),
+        };
+        for child in &mut self.children {
+            match child.style.position {
+                Position::Static => {}
+                Position::Relative => {
+                    let (dx, dy) = child.relative_offset(&content_block);
+                    child.translate(dx, dy);
+                }
+                // stickyはスクロール位置が決まってからずらす
+                Position::Sticky => {}
+                Position::Absolute => child.layout_absolute(&containing, context),
+                Position::Fixed => child.layout_absolute(&viewport, context),
+            }
+            child.layout_positioned(&containing, context);
+        }
+    }
+
+    // スクロール位置に合わせてstickyのボックスをずらし直す。scrollportはスクロールされた後に見えている範囲
+    pub(super) fn update_sticky_positions(&mut self, scrollport: &Rect) {
+        // スクロールコンテナの中ではそのスクロールポートが基準になる
+        let scrollport = if self.is_scroll_container() {
+            let mut port = self.dimensions.padding_box();
+            port.translate(self.scroll_offset.x, self.scroll_offset.y);
+            port
+        } else {
+            *scrollport
+        };
+        // スクロールコンテナの子はスクロールして見える内容全体の中で動ける
+        let content = if self.is_scroll_container() {
+            self.dimensions.content.union(&self.scrollable_overflow)
+        } else {
+            self.dimensions.content
+        };
+        for child in &mut self.children {
+            if child.style.position == Position::Sticky {
+                let (dx, dy) = child.sticky_shift;
+                child.translate(-dx, -dy);
+                let (dx, dy) = child.sticky_offset(&scrollport, &content);
+                child.translate(dx, dy);
+                child.sticky_shift = (dx, dy);
+            }
+            child.update_sticky_positions(&scrollport);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::layout::{layout_document, layout_view::LayoutView};
+
+    fn body(view: &LayoutView) -> &LayoutBox {
+        &view.root().expect("failed to get root box").children()[0]
+    }
+
+    #[test]
+    fn test_relative_position() {
+        let (_, view) = layout_document(
+            "<html><body><p class=\"a\">a</p><p>b</p></body></html>",
+            "body, p { margin: 0 } p { font-size: 10px; line-height: 20px } .a { position: relative; top: 5px; left: 10%; bottom: 100px }",
+        );
+        let body = body(&view);
+        let a = &body.children()[0];
+        assert_eq!(
+            Rect::new(60.0, 5.0, 600.0, 20.0),
+            a.dimensions().border_box()
+        );
+        assert_eq!(60.0, a.children()[0].fragments()[0].rect.x);
+        // 後続のボックスの位置は変わらない
+        assert_eq!(20.0, body.children()[1].dimensions().border_box().y);
+    }
+
+    #[test]
+    fn test_absolute_position() {
+        let (_, view) = layout_document(
+            "<html><body><p>a</p><p class=\"container\">b<a class=\"corner\">c</a><a class=\"fill\"></a><a class=\"static\">d</a></p></body></html>",
+            "body, p { margin: 0 } p { font-size: 10px; line-height: 20px } .container { position: relative; margin-left: 100px; width: 200px; height: 100px; padding: 10px } .corner { position: absolute; right: 0; bottom: 10% } .fill { position: absolute; top: 10px; bottom: 10px; left: 20px; right: 20px; margin: auto; width: 100px } .static { position: absolute }",
+        );
+        let container = &body(&view).children()[1];
+        // 包含ブロックはパディングボックス(100, 20, 220, 120)
+        let corner = container.children()[1].dimensions().border_box();
+        assert_eq!(Rect::new(315.0, 108.0, 5.0, 20.0), corner);
+        let fill = container.children()[2].dimensions();
+        assert_eq!(Rect::new(160.0, 30.0, 100.0, 100.0), fill.border_box());
+        // insetがすべてautoなら静的位置に置く
+        let static_box = container.children()[3].dimensions().border_box();
+        assert_eq!((115.0, 30.0), (static_box.x, static_box.y));
+        // 絶対配置のボックスは行に影響しない
+        assert_eq!(1, container.lines().len());
+        assert_eq!(5.0, container.children()[0].fragments()[0].rect.width);
+    }
+
+    #[test]
+    fn test_fixed_position_and_stacking_context() {
+        let (_, view) = layout_document(
+            "<html><body><p class=\"outer\"><a class=\"fixed\">a</a></p><p class=\"z\">b</p></body></html>",
+            "body, p { margin: 0 } p { font-size: 10px; line-height: 20px } .outer { position: relative; margin-top: 50px } .fixed { position: fixed; bottom: 0; left: 0; right: 0 } .z { position: absolute; z-index: -1 }",
+        );
+        let body = body(&view);
+        let fixed = &body.children()[0].children()[0];
+        assert_eq!(
+            Rect::new(0.0, 380.0, 600.0, 20.0),
+            fixed.dimensions().border_box()
+        );
+        assert!(fixed.creates_stacking_context());
+        assert!(!body.children()[0].creates_stacking_context());
+        let z = &body.children()[1];
+        assert!(z.creates_stacking_context());
+        assert_eq!(-1, z.z_index());
+    }
+
+    #[test]
+    fn test_sticky_offset() {
+        let (_, view) = layout_document(
+            "<html><body><p class=\"sticky\">a</p></body></html>",
+            "body, p { margin: 0 } body { height: 300px } p { line-height: 20px; margin-top: 100px } .sticky { position: sticky; top: 10px }",
+        );
+        let sticky = &body(&view).children()[0];
+        assert_eq!(100.0, sticky.dimensions().border_box().y);
+        // ビューポートが150pxスクロールされたときは包含ブロックの中で上端から10pxの位置に留まる
+        let scrollport = Rect::new(0.0, 150.0, 600.0, 400.0);
+        let containing = Rect::new(0.0, 100.0, 600.0, 300.0);
+        assert_eq!((0.0, 60.0), sticky.sticky_offset(&scrollport, &containing));
+        let scrollport = Rect::new(0.0, 500.0, 600.0, 400.0);
+        assert_eq!((0.0, 280.0), sticky.sticky_offset(&scrollport, &containing));
+    }
+}
@@ -0,0 +1,438 @@
+use crate::renderer::{
+    css::{
+        computed_style::{BoxSizing, ComputedStyle, Display, ObjectFit},
+        value::Length,
+    },
+    dom::node::Node,
+    image::{bitmap::Image, source::ImageState},
+    layout::{
+        block::clamp_size,
+        geometry::Rect,
+        layout_box::{BoxKind, LayoutBox},
+    },
+};
+use alloc::{rc::Rc, vec, vec::Vec};
+use core::cell::RefCell;
+
+// 置換要素の内容。大きさはCSSのpxで、分からない方向はNone
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReplacedContent {
+    image: Option<Rc<Image>>,
+    natural_width: Option<f64>,
+    natural_height: Option<f64>,
+    // 幅と高さの比
+    ratio: Option<f64>,
+}
+
+impl ReplacedContent {
+    pub fn image(&self) -> Option<Rc<Image>> {
+        self.image.clone()
+    }
+
+    pub fn natural_width(&self) -> Option<f64> {
+        self.natural_width
+    }
+
+    pub fn natural_height(&self) -> Option<f64> {
+        self.natural_height
+    }
+
+    pub fn ratio(&self) -> Option<f64> {
+        self.ratio
+    }
+}
+
+// widthやheight属性の値。HTMLの寸法の値として先頭の数値だけを読む
+fn dimension_attribute(node: &Rc<RefCell<Node>>, name: &str) -> Option<Length> {
+    let value = node.borrow().get_element()?.get_attribute(name)?;
+    let value = value.trim_start_matches(|c: char| c.is_ascii_whitespace());
+    let end = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let number = value[..end].parse::<f64>().ok()?;
+    if value[end..].starts_with('%') {
+        Some(Length::Percent(number))
+    } else {
+        Some(Length::Px(number))
+    }
+}
+
+// img要素のボックス。画像を表示できないときはalt属性の文字列を代わりに置く
+pub(super) fn build_image_boxes(node: &Rc<RefCell<Node>>, style: ComputedStyle) -> Vec<LayoutBox> {
+    // 置換要素のdisplay: contentsはnoneとして扱う
+    if matches!(style.display, Display::None | Display::Contents) {
+        return Vec::new();
+    }
+    let kind = if style.display.is_inline_level() {
+        BoxKind::InlineBlock
+    } else {
+        BoxKind::Block
+    };
+    let state = node.borrow().image_state().unwrap_or(ImageState::Pending);
+    let alt = node
+        .borrow()
+        .get_element()
+        .and_then(|element| element.get_attribute("alt"));
+
+    let (image, density) = match state {
+        ImageState::Loaded { image, density } => (image, density),
+        ImageState::Broken if alt.is_some() => {
+            let text_style = ComputedStyle::inherit_from(&style);
+            let kind = if style.display == Display::Inline {
+                BoxKind::Inline
+            } else {
+                kind
+            };
+            let mut fallback = LayoutBox::new(kind, Some(node.clone()), style);
+            fallback.children = alt
+                .filter(|alt| !alt.is_empty())
+                .map(|alt| {
+                    vec![LayoutBox::new(
+                        BoxKind::Text(alt),
+                        Some(node.clone()),
+                        text_style,
+                    )]
+                })
+                .unwrap_or_default();
+            return vec![fallback];
+        }
+        // 読み込まれるまでは属性で指定された大きさだけを持つ
+        _ => {
+            let ratio = match (
+                dimension_attribute(node, "width"),
+                dimension_attribute(node, "height"),
+            ) {
+                (Some(Length::Px(width)), Some(Length::Px(height))) if height > 0.0 => {
+                    Some(width / height)
+                }
+                _ => None,
+            };
+            let mut replaced = LayoutBox::new(kind, Some(node.clone()), style);
+            replaced.replaced = Some(ReplacedContent {
+                image: None,
+                natural_width: Some(0.0),
+                natural_height: Some(0.0),
+                ratio,
+            });
+            return vec![replaced];
+        }
+    };
+
+    let (width, height) = (image.width() as f64, image.height() as f64);
+    let mut replaced = LayoutBox::new(kind, Some(node.clone()), style);
+    replaced.replaced = Some(ReplacedContent {
+        image: Some(image),
+        natural_width: Some(width / density),
+        natural_height: Some(height / density),
+        ratio: (width > 0.0 && height > 0.0).then(|| width / height),
+    });
+    vec![replaced]
+}
+
+// 比を保ったままmin/maxの制限に収める。CSS 2.1 10.4の表
+fn constrain_with_ratio(
+    (width, height): (f64, f64),
+    (min_width, max_width): (f64, Option<f64>),
+    (min_height, max_height): (f64, Option<f64>),
+) -> (f64, f64) {
+    let max_width = max_width.unwrap_or(f64::INFINITY).max(min_width);
+    let max_height = max_height.unwrap_or(f64::INFINITY).max(min_height);
+    let (w, h) = (width, height);
+    if w > max_width && h > max_height {
+        if max_width / w <= max_height / h {
+            (max_width, min_height.max(max_width * h / w))
+        } else {
+            (min_width.max(max_height * w / h), max_height)
+        }
+    } else if w < min_width && h < min_height {
+        if min_width / w <= min_height / h {
+            (max_width.min(min_height * w / h), min_height)
+        } else {
+            (min_width, max_height.min(min_width * h / w))
+        }
+    } else if w < min_width && h > max_height {
+        (min_width, max_height)
+    } else if w > max_width && h < min_height {
+        (max_width, min_height)
+    } else if w > max_width {
+        (max_width, min_height.max(max_width * h / w))
+    } else if w < min_width {
+        (min_width, max_height.min(min_width * h / w))
+    } else if h > max_height {
+        (min_width.max(max_height * w / h), max_height)
+    } else if h < min_height {
+        (max_width.min(min_height * w / h), min_height)
+    } else {
+        (w, h)
+    }
+}
+
+impl LayoutBox {
+    pub fn replaced(&self) -> Option<&ReplacedContent> {
+        self.replaced.as_ref()
+    }
+
+    // 置換要素の内容領域の幅と高さ。CSS 2.1 10.3.2, 10.6.2。
+    // 包含ブロックの大きさが分からない方向のパーセントはautoとして扱う
+    pub(super) fn replaced_size(
+        &self,
+        containing_width: Option<f64>,
+        containing_height: Option<f64>,
+    ) -> (f64, f64) {
+        let Some(content) = &self.replaced else {
+            return (0.0, 0.0);
+        };
+        let style = &self.style;
+        let base = containing_width.unwrap_or(0.0);
+        let horizontal_edges = style.padding.left.resolve_or_zero(base)
+            + style.padding.right.resolve_or_zero(base)
+            + style.border_width.horizontal();
+        let vertical_edges = style.padding.top.resolve_or_zero(base)
+            + style.padding.bottom.resolve_or_zero(base)
+            + style.border_width.vertical();
+        // box-sizingに応じて内容領域の大きさにする
+        let content_size = |length: &Length, base: Option<f64>, edges: f64| {
+            let size = match length {
+                Length::Auto => None,
+                Length::Px(px) => Some(*px),
+                _ => base.and_then(|b| length.resolve(b)),
+            }?;
+            Some(match style.box_sizing {
+                BoxSizing::ContentBox => size,
+                BoxSizing::BorderBox => (size - edges).max(0.0),
+            })
+        };
+        // 属性の大きさはCSSで指定されていない場合に使う
+        let attribute = |name: &str| {
+            self.node
+                .as_ref()
+                .and_then(|node| dimension_attribute(node, name))
+                .unwrap_or(Length::Auto)
+        };
+        let width_length = if style.width.is_auto() {
+            attribute("width")
+        } else {
+            style.width.clone()
+        };
+        let height_length = if style.height.is_auto() {
+            attribute("height")
+        } else {
+            style.height.clone()
+        };
+        let specified_width = content_size(&width_length, containing_width, horizontal_edges);
+        let specified_height = content_size(&height_length, containing_height, vertical_edges);
+        let min_width =
+            content_size(&style.min_width, containing_width, horizontal_edges).unwrap_or(0.0);
+        let max_width = style
+            .max_width
+            .as_ref()
+            .and_then(|w| content_size(w, containing_width, horizontal_edges));
+        let min_height =
+            content_size(&style.min_height, containing_height, vertical_edges).unwrap_or(0.0);
+        let max_height = style
+            .max_height
+            .as_ref()
+            .and_then(|h| content_size(h, containing_height, vertical_edges));
+
+        let ratio = content.ratio.filter(|r| *r > 0.0);
+        let (width, height) = match (specified_width, specified_height) {
+            (Some(w), Some(h)) => (w, h),
+            (Some(w), None) => (
+                w,
+                ratio
+                    .map(|r| w / r)
+                    .or(content.natural_height)
+                    .unwrap_or(150.0),
+            ),
+            (None, Some(h)) => (
+                ratio
+                    .map(|r| h * r)
+                    .or(content.natural_width)
+                    .unwrap_or(300.0),
+                h,
+            ),
+            (None, None) => match (content.natural_width, content.natural_height, ratio) {
+                (Some(w), Some(h), _) => (w, h),
+                (Some(w), None, Some(r)) => (w, w / r),
+                (None, Some(h), Some(r)) => (h * r, h),
+                (None, None, Some(r)) => {
+                    let w = containing_width.unwrap_or(300.0);
+                    (w, w / r)
+                }
+                (w, h, _) => (w.unwrap_or(300.0), h.unwrap_or(150.0)),
+            },
+        };
+
+        if specified_width.is_none()
+            && specified_height.is_none()
+            && ratio.is_some()
+            && width > 0.0
+            && height > 0.0
+        {
+            return constrain_with_ratio(
+                (width, height),
+                (min_width, max_width),
+                (min_height, max_height),
+            );
+        }
+        (
+            clamp_size(width, min_width, max_width),
+            clamp_size(height, min_height, max_height),
+        )
+    }
+
+    // object-fitとobject-positionで内容領域に置いた画像の範囲。内容領域からはみ出すことがある
+    pub fn object_rect(&self) -> Option<Rect> {
+        let content = self.replaced.as_ref()?;
+        content.image.as_ref()?;
+        let (natural_width, natural_height) = (content.natural_width?, content.natural_height?);
+        let area = self.dimensions.content;
+        if natural_width <= 0.0 || natural_height <= 0.0 {
+            return None;
+        }
+        let scale = |cover: bool| {
+            let (x, y) = (area.width / natural_width, area.height / natural_height);
+            let scale = if cover { x.max(y) } else { x.min(y) };
+            (natural_width * scale, natural_height * scale)
+        };
+        let (width, height) = match self.style.object_fit {
+            ObjectFit::Fill => (area.width, area.height),
+            ObjectFit::Contain => scale(false),
+            ObjectFit::Cover => scale(true),
+            ObjectFit::None => (natural_width, natural_height),
+            ObjectFit::ScaleDown => {
+                if natural_width <= area.width && natural_height <= area.height {
+                    (natural_width, natural_height)
+                } else {
+                    scale(false)
+                }
+            }
+        };
+        let (x, y) = &self.style.object_position;
+        Some(Rect::new(
+            area.x + x.resolve_or_zero(area.width - width),
+            area.y + y.resolve_or_zero(area.height - height),
+            width,
+            height,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::{
+        dom::{api::children, node::ElementKind},
+        image::bitmap::Bitmap,
+        layout::{layout_view::LayoutView, style_document},
+    };
+    use alloc::string::ToString;
+
+    fn set_image_states(node: &Rc<RefCell<Node>>, states: &mut Vec<ImageState>) {
+        if node.borrow().element_kind() == Some(ElementKind::Img) && !states.is_empty() {
+            node.borrow_mut().set_image_state(Some(states.remove(0)));
+        }
+        for child in children(node) {
+            set_image_states(&child, states);
+        }
+    }
+
+    // img要素に文書順にstatesの状態を設定してからレイアウトする
+    fn layout(html: &str, css: &str, mut states: Vec<ImageState>) -> LayoutView {
+        let document = style_document(html, css);
+        set_image_states(&document, &mut states);
+        LayoutView::new(&document, 600.0, 400.0)
+    }
+
+    fn loaded(width: usize, height: usize, density: f64) -> ImageState {
+        ImageState::Loaded {
+            image: Rc::new(Image::still(Bitmap::new(width, height))),
+            density,
+        }
+    }
+
+    fn content_sizes(view: &LayoutView) -> Vec<(f64, f64)> {
+        let body = &view.root().unwrap().children()[0];
+        body.children()[0]
+            .children()
+            .iter()
+            .map(|b| (b.dimensions().content.width, b.dimensions().content.height))
+            .collect()
+    }
+
+    #[test]
+    fn test_replaced_size() {
+        let view = layout(
+            "<html><body><p><img><img><img class=\"w\"><img height=\"30\"><img class=\"max\"><img class=\"min\"><img width=\"100\" height=\"50\"><img><img class=\"percent\" width=\"50%\"></p></body></html>",
+            "body, p { margin: 0 } img { display: block } .w { width: 80px } .max { max-width: 20px } .min { min-width: 80px; max-height: 30px } .percent { box-sizing: border-box; padding: 0 10px }",
+            vec![
+                loaded(40, 20, 1.0),
+                // 2xの画像はCSSのpxで半分の大きさになる
+                loaded(40, 20, 2.0),
+                loaded(40, 20, 1.0),
+                loaded(40, 20, 1.0),
+                loaded(40, 20, 1.0),
+                loaded(40, 20, 1.0),
+                ImageState::Pending,
+                ImageState::Pending,
+                loaded(40, 20, 1.0),
+            ],
+        );
+        assert_eq!(
+            vec![
+                (40.0, 20.0),
+                (20.0, 10.0),
+                (80.0, 40.0),
+                (60.0, 30.0),
+                (20.0, 10.0),
+                // 比を保てない場合はmin-widthとmax-heightを両方満たす
+                (80.0, 30.0),
+                // 読み込まれるまでは属性の大きさか0になる
+                (100.0, 50.0),
+                (0.0, 0.0),
+                (280.0, 140.0),
+            ],
+            content_sizes(&view)
+        );
+    }
+
+    #[test]
+    fn test_inline_image_and_alt_text() {
+        let view = layout(
+            "<html><body><p>a<img alt=\"broken\">b<img src=\"x.png\" alt=\"\"><img class=\"fit\"></p></body></html>",
+            "body, p { margin: 0 } p { font-size: 10px; line-height: 20px } .fit { width: 100px; height: 100px; object-fit: contain; object-position: 0 100% }",
+            vec![ImageState::Broken, ImageState::Broken, loaded(40, 20, 1.0)],
+        );
+        let p = &view.root().unwrap().children()[0].children()[0];
+        let kinds: Vec<BoxKind> = p.children().iter().map(|b| b.kind()).collect();
+        assert_eq!(
+            vec![
+                BoxKind::Text("a".to_string()),
+                BoxKind::Inline,
+                BoxKind::Text("b".to_string()),
+                BoxKind::Inline,
+                BoxKind::InlineBlock,
+            ],
+            kinds
+        );
+        // 画像を表示できないときはalt属性の文字列を文字として並べる
+        assert_eq!(
+            vec![BoxKind::Text("broken".to_string())],
+            p.children()[1]
+                .children()
+                .iter()
+                .map(|b| b.kind())
+                .collect::<Vec<_>>()
+        );
+        assert!(p.children()[3].children().is_empty());
+
+        // 画像はベースラインに下端を揃える
+        let image = &p.children()[4];
+        let content = image.dimensions().content;
+        assert_eq!(Rect::new(40.0, 0.0, 100.0, 100.0), content);
+        assert_eq!(
+            Some(Rect::new(40.0, 50.0, 100.0, 50.0)),
+            image.object_rect()
+        );
+    }
+}
@@ -0,0 +1,1181 @@
+use crate::renderer::{
+    css::{
+        computed_style::{
+            BorderCollapse, BorderStyle, BoxSizing, Display, Sides, TableLayout, VerticalAlign,
+        },
+        value::{Color, Length},
+    },
+    dom::node::ElementKind,
+    layout::{
+        block::{ContainingBlock, SizeOverride},
+        geometry::{Dimensions, Rect},
+        layout_box::{BoxKind, LayoutBox},
+        layout_view::LayoutContext,
+    },
+};
+use alloc::{vec, vec::Vec};
+use core::ops::Range;
+
+// テーブルボックスの子から行を指す位置。行グループの中の行は(グループの番号, Some(行の番号))
+type RowPath = (usize, Option<usize>);
+
+// 格子に置かれたセル
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct GridCell {
+    row: usize,
+    // 行ボックスの中での子の番号
+    child: usize,
+    column: usize,
+    row_span: usize,
+    column_span: usize,
+}
+
+impl GridCell {
+    fn rows(&self) -> Range<usize> {
+        self.row..self.row + self.row_span
+    }
+
+    fn columns(&self) -> Range<usize> {
+        self.column..self.column + self.column_span
+    }
+}
+
+// 表示する順に並べた行と、それぞれのセルの位置
+#[derive(Clone, Debug, Default)]
+struct TableGrid {
+    rows: Vec<RowPath>,
+    cells: Vec<GridCell>,
+    columns: usize,
+}
+
+// 自動レイアウトで求める列の幅の範囲
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct ColumnWidth {
+    min: f64,
+    max: f64,
+    // 幅が指定された列は余った幅を受け取らない
+    fixed: bool,
+    percent: Option<f64>,
+}
+
+// border-collapse: collapseで隣り合うボックスの間で選ばれる境界線
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct CollapsedBorder {
+    width: f64,
+    style: BorderStyle,
+    color: Color,
+}
+
+impl CollapsedBorder {
+    // 幅が同じ場合に優先されるスタイルの順位
+    fn style_priority(&self) -> u8 {
+        match self.style {
+            BorderStyle::Hidden => 9,
+            BorderStyle::Double => 8,
+            BorderStyle::Solid => 7,
+            BorderStyle::Dashed => 6,
+            BorderStyle::Dotted => 5,
+            BorderStyle::Ridge => 4,
+            BorderStyle::Outset => 3,
+            BorderStyle::Groove => 2,
+            BorderStyle::Inset => 1,
+            BorderStyle::None => 0,
+        }
+    }
+
+    // CSS 2.1 17.6.2.1 hiddenが最優先、noneは最も弱い。それ以外は幅、スタイルの順で比べ、同じなら先に来たものを選ぶ
+    fn wins_over(&self, other: &CollapsedBorder) -> bool {
+        if self.style == BorderStyle::Hidden || other.style == BorderStyle::Hidden {
+            return self.style == BorderStyle::Hidden && other.style != BorderStyle::Hidden;
+        }
+        if self.style == BorderStyle::None || other.style == BorderStyle::None {
+            return other.style == BorderStyle::None && self.style != BorderStyle::None;
+        }
+        if self.width != other.width {
+            return self.width > other.width;
+        }
+        self.style_priority() > other.style_priority()
+    }
+
+    // 実際に描かれる幅。hiddenは境界線を消す
+    fn used_width(&self) -> f64 {
+        match self.style {
+            BorderStyle::Hidden | BorderStyle::None => 0.0,
+            _ => self.width,
+        }
+    }
+}
+
+fn border_of(b: &LayoutBox, side: usize) -> CollapsedBorder {
+    let style = &b.style;
+    let (width, border_style, color) = match side {
+        0 => (
+            style.border_width.top,
+            style.border_style.top,
+            style.border_color.top,
+        ),
+        1 => (
+            style.border_width.right,
+            style.border_style.right,
+            style.border_color.right,
+        ),
+        2 => (
+            style.border_width.bottom,
+            style.border_style.bottom,
+            style.border_color.bottom,
+        ),
+        _ => (
+            style.border_width.left,
+            style.border_style.left,
+            style.border_color.left,
+        ),
+    };
+    CollapsedBorder {
+        width,
+        style: border_style,
+        color,
+    }
+}
+
+fn add_candidate(edge: &mut Option<CollapsedBorder>, candidate: CollapsedBorder) {
+    match edge {
+        Some(current) if !candidate.wins_over(current) => {}
+        _ => *edge = Some(candidate),
+    }
+}
+
+// 複数の辺にまたがる場合は最も太い境界線を使う
+fn widest<'a>(edges: impl Iterator<Item = &'a Option<CollapsedBorder>>) -> Option<CollapsedBorder> {
+    edges
+        .flatten()
+        .fold(None, |widest: Option<CollapsedBorder>, edge| match widest {
+            Some(w) if w.used_width() >= edge.used_width() => Some(w),
+            _ => Some(*edge),
+        })
+}
+
+fn set_collapsed_border(b: &mut LayoutBox, side: usize, border: Option<CollapsedBorder>) {
+    let border = match border {
+        Some(border) => border,
+        None => return,
+    };
+    // 隣り合うボックスが境界線の半分ずつを持つ
+    let width = border.used_width() / 2.0;
+    let style = match border.style {
+        BorderStyle::Hidden => BorderStyle::None,
+        style => style,
+    };
+    let s = &mut b.style;
+    let (w, st, c) = match side {
+        0 => (
+            &mut s.border_width.top,
+            &mut s.border_style.top,
+            &mut s.border_color.top,
+        ),
+        1 => (
+            &mut s.border_width.right,
+            &mut s.border_style.right,
+            &mut s.border_color.right,
+        ),
+        2 => (
+            &mut s.border_width.bottom,
+            &mut s.border_style.bottom,
+            &mut s.border_color.bottom,
+        ),
+        _ => (
+            &mut s.border_width.left,
+            &mut s.border_style.left,
+            &mut s.border_color.left,
+        ),
+    };
+    *w = width;
+    *st = style;
+    *c = border.color;
+}
+
+// 列の幅の合計がwidthに満たなければ、幅が指定されていない列に最大内容幅の比で不足分を配る
+fn grow_columns(columns: &mut [ColumnWidth], width: f64, minimum: bool) {
+    let total: f64 = columns
+        .iter()
+        .map(|c| if minimum { c.min } else { c.max })
+        .sum();
+    if width <= total {
+        return;
+    }
+    let targets = targets_for_extra(columns);
+    let shares = shares(columns, &targets, width - total);
+    for (i, share) in targets.into_iter().zip(shares) {
+        let column = &mut columns[i];
+        if minimum {
+            column.min += share;
+            column.max = column.max.max(column.min);
+        } else {
+            column.max += share;
+        }
+    }
+}
+
+// 余った幅を受け取る列。すべての列の幅が指定されていればすべての列
+fn targets_for_extra(columns: &[ColumnWidth]) -> Vec<usize> {
+    let auto = (0..columns.len())
+        .filter(|&i| !columns[i].fixed)
+        .collect::<Vec<_>>();
+    if auto.is_empty() {
+        (0..columns.len()).collect()
+    } else {
+        auto
+    }
+}
+
+// extraを最大内容幅の比で分ける。すべて0なら均等に分ける
+fn shares(columns: &[ColumnWidth], targets: &[usize], extra: f64) -> Vec<f64> {
+    let total: f64 = targets.iter().map(|&i| columns[i].max).sum();
+    targets
+        .iter()
+        .map(|&i| {
+            if total > 0.0 {
+                extra * columns[i].max / total
+            } else {
+                extra / targets.len() as f64
+            }
+        })
+        .collect()
+}
+
+// CSS 2.1 17.5.2.2 自動レイアウトで列の幅の合計がwidthになるように各列の幅を決める
+fn distribute_column_widths(columns: &[ColumnWidth], width: f64) -> Vec<f64> {
+    let mut columns = columns.to_vec();
+    // パーセント指定の列は列の幅の合計に対する割合で固定する
+    for column in &mut columns {
+        if let Some(percent) = column.percent {
+            let w = column.min.max(width * percent / 100.0);
+            column.min = w;
+            column.max = w;
+            column.fixed = true;
+        }
+    }
+    let min: f64 = columns.iter().map(|c| c.min).sum();
+    let max: f64 = columns.iter().map(|c| c.max).sum();
+    if width <= min {
+        return columns.iter().map(|c| c.min).collect();
+    }
+    if width <= max {
+        // 最小内容幅と最大内容幅の間で線形に補間する
+        let ratio = (width - min) / (max - min);
+        return columns
+            .iter()
+            .map(|c| c.min + (c.max - c.min) * ratio)
+            .collect();
+    }
+    let mut widths = columns.iter().map(|c| c.max).collect::<Vec<_>>();
+    let targets = targets_for_extra(&columns);
+    let shares = shares(&columns, &targets, width - max);
+    for (i, share) in targets.into_iter().zip(shares) {
+        widths[i] += share;
+    }
+    widths
+}
+
+impl LayoutBox {
+    // colspanなどの属性の値。属性を持てる要素でなければNone
+    fn span_attribute(&self, name: &str, kinds: &[ElementKind]) -> Option<usize> {
+        let node = self.node.as_ref()?.borrow();
+        if !kinds.contains(&node.element_kind()?) {
+            return None;
+        }
+        node.get_element()?.get_attribute(name)?.trim().parse().ok()
+    }
+
+    // セルがまたがる(行数, 列数)。行数の0はグループの最後の行までを表す
+    fn cell_spans(&self) -> (usize, usize) {
+        let kinds = [ElementKind::Td, ElementKind::Th];
+        let rows = self
+            .span_attribute("rowspan", &kinds)
+            .unwrap_or(1)
+            .min(65534);
+        let columns = self
+            .span_attribute("colspan", &kinds)
+            .unwrap_or(1)
+            .clamp(1, 1000);
+        (rows, columns)
+    }
+
+    fn column_span(&self) -> usize {
+        self.span_attribute("span", &[ElementKind::Col, ElementKind::Colgroup])
+            .unwrap_or(1)
+            .clamp(1, 1000)
+    }
+
+    fn table_row(&self, (group, row): RowPath) -> &LayoutBox {
+        match row {
+            Some(row) => &self.children[group].children[row],
+            None => &self.children[group],
+        }
+    }
+
+    fn table_row_mut(&mut self, (group, row): RowPath) -> &mut LayoutBox {
+        match row {
+            Some(row) => &mut self.children[group].children[row],
+            None => &mut self.children[group],
+        }
+    }
+
+    fn grid_cell(&self, grid: &TableGrid, cell: &GridCell) -> &LayoutBox {
+        &self.table_row(grid.rows[cell.row]).children[cell.child]
+    }
+
+    fn grid_cell_mut(&mut self, grid: &TableGrid, cell: &GridCell) -> &mut LayoutBox {
+        &mut self.table_row_mut(grid.rows[cell.row]).children[cell.child]
+    }
+
+    // 行グループを表示する順に並べた子の番号。最初のヘッダーグループは先頭に、最初のフッターグループは末尾に置く
+    fn row_group_order(&self) -> Vec<usize> {
+        let position = |display: Display| {
+            self.children
+                .iter()
+                .position(|child| child.style.display == display)
+        };
+        let header = position(Display::TableHeaderGroup);
+        let footer = position(Display::TableFooterGroup);
+        let mut order = header.into_iter().collect::<Vec<_>>();
+        order.extend((0..self.children.len()).filter(|&i| {
+            Some(i) != header
+                && Some(i) != footer
+                && matches!(
+                    self.children[i].style.display,
+                    Display::TableRow
+                        | Display::TableRowGroup
+                        | Display::TableHeaderGroup
+                        | Display::TableFooterGroup
+                )
+        }));
+        order.extend(footer);
+        order
+    }
+
+    // 列要素と列グループの幅の指定。列ごとに1つ
+    fn column_lengths(&self) -> Vec<Length> {
+        let mut lengths = Vec::new();
+        for child in &self.children {
+            match child.style.display {
+                Display::TableColumn => {
+                    lengths.extend(vec![child.style.width.clone(); child.column_span()]);
+                }
+                Display::TableColumnGroup if child.children.is_empty() => {
+                    lengths.extend(vec![child.style.width.clone(); child.column_span()]);
+                }
+                Display::TableColumnGroup => {
+                    for column in &child.children {
+                        let width = if column.style.width.is_auto() {
+                            child.style.width.clone()
+                        } else {
+                            column.style.width.clone()
+                        };
+                        lengths.extend(vec![width; column.column_span()]);
+                    }
+                }
+                _ => {}
+            }
+        }
+        lengths
+    }
+
+    // 行とセルを格子に並べる。HTML 4.10.12 テーブルの処理モデル
+    fn build_table_grid(&self) -> TableGrid {
+        let mut rows: Vec<RowPath> = Vec::new();
+        // rowspanは行グループの外に伸びないので、行ごとに属するグループの終わりを記録する
+        let mut group_ends = Vec::new();
+        for i in self.row_group_order() {
+            let child = &self.children[i];
+            if child.style.display == Display::TableRow {
+                // テーブル直下で連続する行は1つのグループとして扱う
+                if rows.last().is_some_and(|(_, row)| row.is_none()) {
+                    rows.push((i, None));
+                    continue;
+                }
+                group_ends.resize(rows.len(), rows.len());
+                rows.push((i, None));
+                continue;
+            }
+            group_ends.resize(rows.len(), rows.len());
+            rows.extend((0..child.children.len()).map(|j| (i, Some(j))));
+        }
+        group_ends.resize(rows.len(), rows.len());
+
+        let mut occupied: Vec<Vec<bool>> = vec![Vec::new(); rows.len()];
+        let mut cells = Vec::new();
+        for (r, &path) in rows.iter().enumerate() {
+            let mut column = 0;
+            for (child, cell) in self.table_row(path).children.iter().enumerate() {
+                while occupied[r].get(column) == Some(&true) {
+                    column += 1;
+                }
+                let (row_span, column_span) = cell.cell_spans();
+                let remaining = group_ends[r] - r;
+                let row_span = if row_span == 0 {
+                    remaining
+                } else {
+                    row_span.min(remaining)
+                };
+                for slots in &mut occupied[r..r + row_span] {
+                    if slots.len() < column + column_span {
+                        slots.resize(column + column_span, false);
+                    }
+                    slots[column..column + column_span].fill(true);
+                }
+                cells.push(GridCell {
+                    row: r,
+                    child,
+                    column,
+                    row_span,
+                    column_span,
+                });
+                column += column_span;
+            }
+        }
+
+        let columns = occupied
+            .iter()
+            .map(Vec::len)
+            .max()
+            .unwrap_or(0)
+            .max(self.column_lengths().len());
+        TableGrid {
+            rows,
+            cells,
+            columns,
+        }
+    }
+
+    // 水平方向と垂直方向のセルの間隔。境界線を重ねる場合は0
+    fn cell_spacing(&self) -> (f64, f64) {
+        match self.style.border_collapse {
+            BorderCollapse::Separate => self.style.border_spacing,
+            BorderCollapse::Collapse => (0.0, 0.0),
+        }
+    }
+
+    // 列の間と両端の間隔の合計
+    fn total_column_spacing(&self, columns: usize) -> f64 {
+        if columns == 0 {
+            return 0.0;
+        }
+        self.cell_spacing().0 * (columns + 1) as f64
+    }
+
+    fn is_fixed_layout(&self) -> bool {
+        self.style.table_layout == TableLayout::Fixed && !self.style.width.is_auto()
+    }
+
+    pub(super) fn table_edges(&self, containing_width: f64) -> f64 {
+        self.style.padding.left.resolve_or_zero(containing_width)
+            + self.style.padding.right.resolve_or_zero(containing_width)
+            + self.style.border_width.horizontal()
+    }
+
+    // セルの最小内容幅と最大内容幅から列の幅の範囲を求める
+    fn column_widths(&self, grid: &TableGrid, context: &LayoutContext) -> Vec<ColumnWidth> {
+        let (spacing, _) = self.cell_spacing();
+        let mut columns = vec![ColumnWidth::default(); grid.columns];
+        let mut specified: Vec<Option<f64>> = vec![None; grid.columns];
+        for (i, length) in self.column_lengths().into_iter().enumerate() {
+            match length {
+                Length::Px(width) => specified[i] = Some(width),
+                Length::Percent(percent) => columns[i].percent = Some(percent),
+                _ => {}
+            }
+        }
+
+        let mut spanning = Vec::new();
+        for cell in &grid.cells {
+            if cell.column_span > 1 {
+                spanning.push(*cell);
+                continue;
+            }
+            let b = self.grid_cell(grid, cell);
+            let (min, max) = b.intrinsic_contribution(context);
+            let column = &mut columns[cell.column];
+            column.min = column.min.max(min);
+            column.max = column.max.max(max);
+            match b.style.width {
+                // 幅が指定されたセルの寄与はボーダーボックスの幅になる
+                Length::Px(_) => {
+                    specified[cell.column] = Some(specified[cell.column].unwrap_or(0.0).max(max))
+                }
+                Length::Percent(percent) => {
+                    column.percent = Some(column.percent.unwrap_or(0.0).max(percent))
+                }
+                _ => {}
+            }
+        }
+        for (column, specified) in columns.iter_mut().zip(specified) {
+            match specified {
+                Some(width) => {
+                    column.fixed = true;
+                    column.min = column.min.max(width);
+                    column.max = column.min;
+                }
+                None => column.max = column.max.max(column.min),
+            }
+        }
+
+        // 複数の列にまたがるセルは、またがる列の幅の合計が足りなければ広げる
+        spanning.sort_by_key(|cell| cell.column_span);
+        for cell in spanning {
+            let (min, max) = self.grid_cell(grid, &cell).intrinsic_contribution(context);
+            let inner_spacing = spacing * (cell.column_span - 1) as f64;
+            grow_columns(&mut columns[cell.columns()], min - inner_spacing, true);
+            grow_columns(&mut columns[cell.columns()], max - inner_spacing, false);
+        }
+        columns
+    }
+
+    // CSS 2.1 17.5.2.1 固定レイアウトでは列要素と最初の行のセルの指定だけで列の幅を決める
+    fn fixed_column_widths(&self, grid: &TableGrid, width: f64) -> Vec<Option<f64>> {
+        let (spacing, _) = self.cell_spacing();
+        let mut widths = self
+            .column_lengths()
+            .iter()
+            .map(|length| length.resolve(width))
+            .collect::<Vec<_>>();
+        widths.resize(grid.columns, None);
+        for cell in grid.cells.iter().filter(|cell| cell.row == 0) {
+            let b = self.grid_cell(grid, cell);
+            let specified = match b.style.width.resolve(width) {
+                Some(w) => w,
+                None => continue,
+            };
+            let specified = match b.style.box_sizing {
+                BoxSizing::BorderBox => specified,
+                BoxSizing::ContentBox => {
+                    specified
+                        + b.style.padding.left.resolve_or_zero(width)
+                        + b.style.padding.right.resolve_or_zero(width)
+                        + b.style.border_width.horizontal()
+                }
+            };
+            let inner_spacing = spacing * (cell.column_span - 1) as f64;
+            let each = ((specified - inner_spacing) / cell.column_span as f64).max(0.0);
+            for column in cell.columns() {
+                if widths[column].is_none() {
+                    widths[column] = Some(each);
+                }
+            }
+        }
+        widths
+    }
+
+    // 固定レイアウトで幅が決まっていない列に残りの幅を等しく分ける
+    fn distribute_fixed_column_widths(&self, grid: &TableGrid, width: f64) -> Vec<f64> {
+        let specified = self.fixed_column_widths(grid, width);
+        let used: f64 = specified.iter().flatten().sum();
+        let remaining = (width - used).max(0.0);
+        let auto = specified.iter().filter(|w| w.is_none()).count();
+        if auto > 0 {
+            return specified
+                .iter()
+                .map(|w| w.unwrap_or(remaining / auto as f64))
+                .collect();
+        }
+        // すべての列の幅が決まっていれば余りを幅の比で分ける
+        let widths = specified.into_iter().flatten().collect::<Vec<_>>();
+        if remaining == 0.0 || widths.is_empty() {
+            return widths;
+        }
+        let count = widths.len() as f64;
+        widths
+            .iter()
+            .map(|&w| {
+                if used > 0.0 {
+                    w + remaining * w / used
+                } else {
+                    w + remaining / count
+                }
+            })
+            .collect()
+    }
+
+    // テーブルボックスの最小幅と最大幅(ボーダーボックス)
+    fn table_intrinsic_widths(&self, containing_width: f64, context: &LayoutContext) -> (f64, f64) {
+        let grid = self.build_table_grid();
+        let extra = self.total_column_spacing(grid.columns) + self.table_edges(containing_width);
+        let (min, max) = if self.is_fixed_layout() {
+            let width: f64 = self.fixed_column_widths(&grid, 0.0).iter().flatten().sum();
+            (width, width)
+        } else {
+            let columns = self.column_widths(&grid, context);
+            (
+                columns.iter().map(|c| c.min).sum(),
+                columns.iter().map(|c| c.max).sum(),
+            )
+        };
+        (min + extra, max + extra)
+    }
+
+    // 指定された幅をボーダーボックスの幅にする
+    fn specified_table_width(&self, containing_width: f64) -> Option<f64> {
+        let width = self.style.width.resolve(containing_width)?;
+        Some(match self.style.box_sizing {
+            BoxSizing::BorderBox => width,
+            BoxSizing::ContentBox => width + self.table_edges(containing_width),
+        })
+    }
+
+    // 親の内容幅に対するテーブルボックスの最小幅と最大幅。幅の指定が最小幅より大きければその幅になる
+    fn table_contribution(&self, context: &LayoutContext) -> (f64, f64) {
+        let (min, max) = self.table_intrinsic_widths(0.0, context);
+        match self.style.width {
+            Length::Px(_) => {
+                let width = self.specified_table_width(0.0).unwrap_or(0.0).max(min);
+                (width, width)
+            }
+            _ => (min, max),
+        }
+    }
+
+    fn table_box(&self) -> &LayoutBox {
+        self.children
+            .iter()
+            .find(|child| child.kind == BoxKind::Table)
+            .expect("table wrapper box should have a table box")
+    }
+
+    // CSS 2.1 17.5.2 テーブルのラッパーボックスの幅。テーブルボックスの幅とキャプションの最小内容幅で決まる
+    pub(super) fn table_wrapper_width(
+        &self,
+        containing_width: f64,
+        context: &LayoutContext,
+    ) -> f64 {
+        let table = self.table_box();
+        let (min, max) = table.table_intrinsic_widths(containing_width, context);
+        let width = match table.specified_table_width(containing_width) {
+            Some(width) => width.max(min),
+            None => {
+                let available = containing_width
+                    - self.style.margin.left.resolve_or_zero(containing_width)
+                    - self.style.margin.right.resolve_or_zero(containing_width);
+                max.min(available).max(min)
+            }
+        };
+        self.children
+            .iter()
+            .filter(|child| child.kind != BoxKind::Table)
+            .map(|caption| caption.intrinsic_contribution(context).0)
+            .fold(width, f64::max)
+    }
+
+    pub(super) fn table_wrapper_intrinsic_widths(&self, context: &LayoutContext) -> (f64, f64) {
+        let (min, max) = self.table_box().table_contribution(context);
+        self.children
+            .iter()
+            .filter(|child| child.kind != BoxKind::Table)
+            .map(|caption| caption.intrinsic_contribution(context).0)
+            .fold((min, max), |(min, max), caption| {
+                (min.max(caption), max.max(caption))
+            })
+    }
+
+    // キャプションとテーブルボックスを上から順に積む
+    pub(super) fn layout_table_wrapper_children(
+        &mut self,
+        containing_block: &ContainingBlock,
+        context: &LayoutContext,
+    ) -> (f64, bool) {
+        let content_y = self.dimensions.content.y;
+        let width = containing_block.width;
+        let mut cursor = 0.0;
+        for child in &mut self.children {
+            if child.kind == BoxKind::Table {
+                let size = SizeOverride {
+                    width: Some((width - child.table_edges(width)).max(0.0)),
+                    height: None,
+                };
+                child.layout_block_with(containing_block, size, context);
+            } else {
+                child.layout_block(containing_block, context);
+            }
+            cursor += child.dimensions.margin.top;
+            child.translate(0.0, content_y + cursor);
+            cursor += child.dimensions.border_box().height + child.dimensions.margin.bottom;
+        }
+        (cursor, true)
+    }
+
+    // セルを左上が(x, 0)になるようにボーダーボックスの幅widthでレイアウトする
+    fn layout_table_cell(&mut self, x: f64, width: f64, context: &LayoutContext) {
+        let containing_block = ContainingBlock {
+            x,
+            width,
+            height: None,
+        };
+        self.resolve_edges(width);
+        let edges = self.dimensions.padding.horizontal() + self.dimensions.border.horizontal();
+        let size = SizeOverride {
+            width: Some((width - edges).max(0.0)),
+            height: None,
+        };
+        self.layout_block_with(&containing_block, size, context);
+    }
+
+    // セルのボーダーボックスの上端からベースラインまでの距離。行がなければ内容領域の下端
+    fn cell_baseline(&self) -> f64 {
+        let top = self.dimensions.border_box().y;
+        let content = self.dimensions.content;
+        self.first_baseline().unwrap_or(content.bottom()) - top
+    }
+
+    // セルの高さを行の高さに合わせ、vertical-alignに従って中身をoffsetだけ下げる
+    fn stretch_cell(&mut self, height: f64, offset: f64) {
+        let mut content = self.dimensions.content;
+        content.height += height - self.dimensions.border_box().height;
+        self.translate(0.0, offset);
+        self.dimensions.content = content;
+    }
+
+    // CSS 2.1 17.5 セルを格子に並べ、行や列のボックスの位置を決める
+    pub(super) fn layout_table_children(
+        &mut self,
+        containing_block: &ContainingBlock,
+        context: &LayoutContext,
+    ) -> (f64, bool) {
+        let grid = self.build_table_grid();
+        let (horizontal_spacing, vertical_spacing) = self.cell_spacing();
+        let content = self.dimensions.content;
+        let columns_width = (content.width - self.total_column_spacing(grid.columns)).max(0.0);
+        let widths = if self.is_fixed_layout() {
+            self.distribute_fixed_column_widths(&grid, columns_width)
+        } else {
+            distribute_column_widths(&self.column_widths(&grid, context), columns_width)
+        };
+
+        let mut xs = Vec::with_capacity(widths.len());
+        let mut x = content.x + horizontal_spacing;
+        for width in &widths {
+            xs.push(x);
+            x += width + horizontal_spacing;
+        }
+        let span_width = |range: Range<usize>| {
+            let count = range.len();
+            widths[range].iter().sum::<f64>() + horizontal_spacing * count.saturating_sub(1) as f64
+        };
+
+        for cell in &grid.cells {
+            let width = span_width(cell.columns());
+            self.grid_cell_mut(&grid, cell)
+                .layout_table_cell(xs[cell.column], width, context);
+        }
+
+        // 1行のセルから行の高さを求める。ベースラインで揃えるセルは上下に分けて考える
+        let row_count = grid.rows.len();
+        let mut heights = vec![0.0; row_count];
+        let mut ascents: Vec<f64> = vec![0.0; row_count];
+        let mut descents: Vec<f64> = vec![0.0; row_count];
+        for (r, &path) in grid.rows.iter().enumerate() {
+            if let Length::Px(height) = self.table_row(path).style.height {
+                heights[r] = height;
+            }
+        }
+        for cell in grid.cells.iter().filter(|cell| cell.row_span == 1) {
+            let b = self.grid_cell(&grid, cell);
+            let height = b.dimensions.border_box().height;
+            if is_baseline_aligned(&b.style.vertical_align) {
+                let baseline = b.cell_baseline();
+                ascents[cell.row] = ascents[cell.row].max(baseline);
+                descents[cell.row] = descents[cell.row].max(height - baseline);
+            } else {
+                heights[cell.row] = f64::max(heights[cell.row], height);
+            }
+        }
+        for r in 0..row_count {
+            heights[r] = f64::max(heights[r], ascents[r] + descents[r]);
+        }
+        // 複数の行にまたがるセルが収まらなければ最後の行を広げる
+        let mut spanning = grid
+            .cells
+            .iter()
+            .filter(|cell| cell.row_span > 1)
+            .collect::<Vec<_>>();
+        spanning.sort_by_key(|cell| cell.row_span);
+        for cell in spanning {
+            let height = self.grid_cell(&grid, cell).dimensions.border_box().height;
+            let total = heights[cell.rows()].iter().sum::<f64>()
+                + vertical_spacing * (cell.row_span - 1) as f64;
+            if height > total {
+                heights[cell.rows().end - 1] += height - total;
+            }
+        }
+        // テーブルの高さの指定が行の高さの合計より大きければ行の高さの比で広げる
+        if let Some(height) = containing_block.height {
+            let spacing = vertical_spacing * (row_count + 1) as f64;
+            let total: f64 = heights.iter().sum();
+            if row_count > 0 && height > total + spacing {
+                let extra = height - total - spacing;
+                for h in &mut heights {
+                    *h += if total > 0.0 {
+                        extra * *h / total
+                    } else {
+                        extra / row_count as f64
+                    };
+                }
+            }
+        }
+
+        let mut ys = Vec::with_capacity(row_count);
+        let mut y = content.y + vertical_spacing;
+        for height in &heights {
+            ys.push(y);
+            y += height + vertical_spacing;
+        }
+
+        for cell in &grid.cells {
+            let height = heights[cell.rows()].iter().sum::<f64>()
+                + vertical_spacing * (cell.row_span - 1) as f64;
+            let ascent = ascents[cell.row];
+            let b = self.grid_cell_mut(&grid, cell);
+            let extra = height - b.dimensions.border_box().height;
+            let offset = match b.style.vertical_align {
+                VerticalAlign::Top => 0.0,
+                VerticalAlign::Middle => extra / 2.0,
+                VerticalAlign::Bottom => extra,
+                _ if cell.row_span == 1 => ascent - b.cell_baseline(),
+                _ => 0.0,
+            };
+            b.stretch_cell(height, offset);
+            b.translate(0.0, ys[cell.row]);
+        }
+
+        // 行、行グループ、列、列グループのボックスはセルを囲む矩形にする
+        let left = content.x + horizontal_spacing;
+        let inner_width = span_width(0..widths.len());
+        for (r, &path) in grid.rows.iter().enumerate() {
+            self.table_row_mut(path).dimensions = Dimensions {
+                content: Rect::new(left, ys[r], inner_width, heights[r]),
+                ..Default::default()
+            };
+        }
+        let top = content.y + vertical_spacing;
+        let rows_height =
+            heights.iter().sum::<f64>() + vertical_spacing * row_count.saturating_sub(1) as f64;
+        let column_count = widths.len();
+        let column_rect = |start: usize, span: usize| {
+            let start = start.min(column_count);
+            let end = (start + span).min(column_count);
+            let x = xs.get(start).copied().unwrap_or(left + inner_width);
+            Rect::new(x, top, span_width(start..end), rows_height)
+        };
+        let mut column = 0;
+        for i in 0..self.children.len() {
+            let child = &mut self.children[i];
+            let rect = match child.style.display {
+                Display::TableRowGroup | Display::TableHeaderGroup | Display::TableFooterGroup => {
+                    let rows = (0..row_count)
+                        .filter(|&r| grid.rows[r].0 == i && grid.rows[r].1.is_some())
+                        .collect::<Vec<_>>();
+                    match (rows.first(), rows.last()) {
+                        (Some(&first), Some(&last)) => Rect::new(
+                            left,
+                            ys[first],
+                            inner_width,
+                            ys[last] + heights[last] - ys[first],
+                        ),
+                        _ => Rect::new(left, top, inner_width, 0.0),
+                    }
+                }
+                Display::TableColumnGroup if !child.children.is_empty() => {
+                    let start = column;
+                    for c in &mut child.children {
+                        let span = c.column_span();
+                        c.dimensions = Dimensions {
+                            content: column_rect(column, span),
+                            ..Default::default()
+                        };
+                        column += span;
+                    }
+                    column_rect(start, column - start)
+                }
+                Display::TableColumn | Display::TableColumnGroup => {
+                    let span = child.column_span();
+                    column += span;
+                    column_rect(column - span, span)
+                }
+                _ => continue,
+            };
+            child.dimensions = Dimensions {
+                content: rect,
+                ..Default::default()
+            };
+        }
+
+        let height = match row_count {
+            0 => containing_block.height.unwrap_or(0.0),
+            _ => y - content.y,
+        };
+        (height, row_count > 0)
+    }
+
+    // CSS 2.1 17.6.2 隣り合うセルと行とテーブルの境界線から優先されるものを選び、半分ずつをそれぞれのボックスの境界線にする
+    pub(super) fn collapse_borders(&mut self) {
+        if self.style.border_collapse != BorderCollapse::Collapse {
+            return;
+        }
+        // 境界線を重ねるテーブルはパディングを持たない
+        self.style.padding = Sides::all(Length::Px(0.0));
+        let grid = self.build_table_grid();
+        let rows = grid.rows.len();
+        let columns = grid.columns;
+        if rows == 0 || columns == 0 {
+            return;
+        }
+
+        // horizontal[r][c]はr行目の上側の辺、vertical[r][c]はc列目の左側の辺
+        let mut horizontal: Vec<Vec<Option<CollapsedBorder>>> = vec![vec![None; columns]; rows + 1];
+        let mut vertical: Vec<Vec<Option<CollapsedBorder>>> = vec![vec![None; columns + 1]; rows];
+        for cell in &grid.cells {
+            let b = self.grid_cell(&grid, cell);
+            for c in cell.columns() {
+                add_candidate(&mut horizontal[cell.row][c], border_of(b, 0));
+                add_candidate(&mut horizontal[cell.rows().end][c], border_of(b, 2));
+            }
+            for r in cell.rows() {
+                add_candidate(&mut vertical[r][cell.column], border_of(b, 3));
+                add_candidate(&mut vertical[r][cell.columns().end], border_of(b, 1));
+            }
+        }
+        for (r, &path) in grid.rows.iter().enumerate() {
+            let row = self.table_row(path);
+            for c in 0..columns {
+                add_candidate(&mut horizontal[r][c], border_of(row, 0));
+                add_candidate(&mut horizontal[r + 1][c], border_of(row, 2));
+            }
+        }
+        for c in 0..columns {
+            add_candidate(&mut horizontal[0][c], border_of(self, 0));
+            add_candidate(&mut horizontal[rows][c], border_of(self, 2));
+        }
+        for edges in vertical.iter_mut() {
+            add_candidate(&mut edges[0], border_of(self, 3));
+            add_candidate(&mut edges[columns], border_of(self, 1));
+        }
+
+        for cell in &grid.cells {
+            let top = widest(horizontal[cell.row][cell.columns()].iter());
+            let bottom = widest(horizontal[cell.rows().end][cell.columns()].iter());
+            let left = widest(
+                vertical[cell.rows()]
+                    .iter()
+                    .map(|edges| &edges[cell.column]),
+            );
+            let right = widest(
+                vertical[cell.rows()]
+                    .iter()
+                    .map(|edges| &edges[cell.columns().end]),
+            );
+            let b = self.grid_cell_mut(&grid, cell);
+            set_collapsed_border(b, 0, top);
+            set_collapsed_border(b, 1, right);
+            set_collapsed_border(b, 2, bottom);
+            set_collapsed_border(b, 3, left);
+        }
+        for &path in &grid.rows {
+            let row = self.table_row_mut(path);
+            row.style.border_width = Sides::all(0.0);
+        }
+        let top = widest(horizontal[0].iter());
+        let bottom = widest(horizontal[rows].iter());
+        let left = widest(vertical.iter().map(|edges| &edges[0]));
+        let right = widest(vertical.iter().map(|edges| &edges[columns]));
+        set_collapsed_border(self, 0, top);
+        set_collapsed_border(self, 1, right);
+        set_collapsed_border(self, 2, bottom);
+        set_collapsed_border(self, 3, left);
+    }
+}
+
+// sub, superなどもセルではbaselineとして扱う
+fn is_baseline_aligned(vertical_align: &VerticalAlign) -> bool {
+    !matches!(
+        vertical_align,
+        VerticalAlign::Top | VerticalAlign::Middle | VerticalAlign::Bottom
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::layout::layout_document;
+    use alloc::string::ToString;
+
+    // bodyの最初の子であるテーブルのラッパーボックス
+    fn table_wrapper(body: &str, css: &str) -> LayoutBox {
+        let (_, view) = layout_document(
+            &("<html><body>".to_string() + body + "</body></html>"),
+            &("body, p { margin: 0 } body { font-size: 10px; line-height: 20px } td { padding: 0 } "
+                .to_string() + css),
+        );
+        view.root().unwrap().children()[0].children()[0].clone()
+    }
+
+    // 行グループの中のセルのボーダーボックスを行ごとに並べる
+    fn cell_boxes(table: &LayoutBox) -> Vec<Vec<Rect>> {
+        table
+            .children()
+            .iter()
+            .flat_map(|group| group.children())
+            .map(|row| {
+                row.children()
+                    .iter()
+                    .map(|cell| cell.dimensions().border_box())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_auto_layout() {
+        let wrapper = table_wrapper(
+            "<table><tr><td>aa</td><td>bbbb bb</td></tr><tr><td colspan=\"2\">c</td></tr></table>",
+            "td { padding: 1px }",
+        );
+        assert_eq!(BoxKind::Block, wrapper.kind());
+        let table = &wrapper.children()[0];
+        assert_eq!(BoxKind::Table, table.kind());
+        // 最大内容幅: 2 + 12 + 2 + 37 + 2
+        assert_eq!(55.0, wrapper.dimensions().content.width);
+        assert_eq!(50.0, table.dimensions().content.height);
+        assert_eq!(
+            vec![
+                vec![
+                    Rect::new(2.0, 2.0, 12.0, 22.0),
+                    Rect::new(16.0, 2.0, 37.0, 22.0)
+                ],
+                vec![Rect::new(2.0, 26.0, 51.0, 22.0)],
+            ],
+            cell_boxes(table)
+        );
+
+        // 最小内容幅と最大内容幅の間で補間する
+        let wrapper = table_wrapper(
+            "<table><tr><td>a a a</td><td>bbbb bbb</td></tr></table>",
+            "body { width: 51px }",
+        );
+        let widths = cell_boxes(&wrapper.children()[0])[0]
+            .iter()
+            .map(|cell| cell.width)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![15.0, 30.0], widths);
+
+        // 余った幅は幅が指定されていない列に配る
+        let wrapper = table_wrapper(
+            "<table><tr><td class=\"fixed\">a a a</td><td>bbbb bbb</td></tr></table>",
+            "table { width: 101px } .fixed { width: 25px }",
+        );
+        let widths = cell_boxes(&wrapper.children()[0])[0]
+            .iter()
+            .map(|cell| cell.width)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![25.0, 70.0], widths);
+    }
+
+    #[test]
+    fn test_fixed_layout() {
+        let wrapper = table_wrapper(
+            "<table><tr><td class=\"first\">a</td><td>b</td><td>c</td></tr><tr><td>dddddddddddddddddddddddddddddd</td></tr></table>",
+            "table { table-layout: fixed; width: 206px } .first { width: 50px }",
+        );
+        let cells = cell_boxes(&wrapper.children()[0]);
+        assert_eq!(
+            vec![50.0, 74.0, 74.0],
+            cells[0].iter().map(|cell| cell.width).collect::<Vec<_>>()
+        );
+        assert_eq!(50.0, cells[1][0].width);
+    }
+
+    #[test]
+    fn test_rowspan_and_vertical_align() {
+        let wrapper = table_wrapper(
+            "<table><tr><td rowspan=\"2\">a</td><td class=\"tall\">b</td></tr><tr><td class=\"top\">c</td><td>d</td></tr></table>",
+            ".tall { height: 50px } .top { vertical-align: top; height: 10px }",
+        );
+        let table = &wrapper.children()[0];
+        let cells = cell_boxes(table);
+        assert_eq!(Rect::new(2.0, 2.0, 5.0, 72.0), cells[0][0]);
+        assert_eq!(Rect::new(9.0, 54.0, 5.0, 20.0), cells[1][0]);
+        // 3列目は1行目にセルがないので空になる
+        assert_eq!(16.0, cells[1][1].x);
+
+        let rows = &table.children()[0].children();
+        let a = &rows[0].children()[0];
+        // tbodyのvertical-align: middleを継承して中央に置かれる
+        assert_eq!(28.0, a.lines()[0].rect.y);
+        let c = &rows[1].children()[0];
+        assert_eq!(54.0, c.lines()[0].rect.y);
+        assert_eq!(
+            Rect::new(2.0, 54.0, 19.0, 20.0),
+            rows[1].dimensions().content
+        );
+    }
+
+    #[test]
+    fn test_border_collapse() {
+        let wrapper = table_wrapper(
+            "<table><tr><td>a</td><td class=\"thick\">b</td></tr></table>",
+            "table { border-collapse: collapse; border: 4px solid } td { border: 2px solid } .thick { border-left-width: 6px }",
+        );
+        let table = &wrapper.children()[0];
+        assert_eq!(Sides::all(2.0), table.dimensions().border);
+        let row = &table.children()[0].children()[0];
+        let a = row.children()[0].dimensions();
+        let b = row.children()[1].dimensions();
+        // セルの間の境界線は太い方の半分ずつになる
+        assert_eq!((2.0, 3.0), (a.border.left, a.border.right));
+        assert_eq!((3.0, 2.0), (b.border.left, b.border.right));
+        assert_eq!(Rect::new(2.0, 2.0, 10.0, 24.0), a.border_box());
+        assert_eq!(Rect::new(12.0, 2.0, 10.0, 24.0), b.border_box());
+        assert_eq!(24.0, wrapper.dimensions().content.width);
+    }
+
+    #[test]
+    fn test_anonymous_table_and_caption() {
+        let wrapper = table_wrapper(
+            "<p><a class=\"cell\">x</a> <a class=\"cell\">yy</a></p>",
+            ".cell { display: table-cell }",
+        );
+        let anonymous = &wrapper.children()[0];
+        assert_eq!(None, anonymous.node());
+        let table = &anonymous.children()[0];
+        assert_eq!(BoxKind::Table, table.kind());
+        let row = &table.children()[0];
+        assert_eq!(BoxKind::AnonymousBlock, row.kind());
+        assert_eq!(Display::TableRow, row.style().display);
+        assert_eq!(
+            vec![
+                Rect::new(0.0, 0.0, 5.0, 20.0),
+                Rect::new(5.0, 0.0, 10.0, 20.0)
+            ],
+            row.children()
+                .iter()
+                .map(|cell| cell.dimensions().border_box())
+                .collect::<Vec<_>>()
+        );
+
+        let wrapper = table_wrapper(
+            "<table><caption>caption</caption><tfoot><tr><td>f</td></tr></tfoot><tbody><tr><td>b</td></tr></tbody><thead><tr><td>h</td></tr></thead></table>",
+            "table { border-spacing: 0 }",
+        );
+        let caption = &wrapper.children()[0];
+        assert_eq!(Display::TableCaption, caption.style().display);
+        assert_eq!(
+            Rect::new(0.0, 0.0, 35.0, 20.0),
+            caption.dimensions().border_box()
+        );
+        let table = &wrapper.children()[1];
+        assert_eq!(20.0, table.dimensions().border_box().y);
+        // ヘッダーグループが先頭、フッターグループが末尾に置かれる
+        let ys = table
+            .children()
+            .iter()
+            .map(|group| group.dimensions().content.y)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![60.0, 40.0, 20.0], ys);
+    }
+}
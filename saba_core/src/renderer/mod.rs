@@ -1,3 +1,8 @@
+pub mod css;
 pub mod dom;
+pub mod font;
 pub mod html;
+pub mod image;
+pub mod layout;
 pub mod page;
+pub mod paint;
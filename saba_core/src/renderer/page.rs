@@ -1,24 +1,86 @@
 use super::{
-    dom::node::Window,
+    css::{
+        cascade::StyleResolver,
+        cssom::{ComponentValue, CssRule, Declaration, MediaRule, StyleSheet},
+        font_face::{collect_font_faces, FontFace},
+        media::{parse_media_query_list, MediaEnvironment},
+        parser::CssParser,
+        token::CssTokenizer,
+    },
+    dom::{
+        api::children,
+        node::{ElementKind, Node, NodeKind, Window},
+    },
+    font::{
+        font_system::{parse_font, FontSystem},
+        glyph::Font,
+    },
     html::{parser::HtmlParser, token::HtmlTokenizer},
+    image::{
+        bitmap::Image,
+        decoder::decode_image,
+        source::{select_source, ImageSource, ImageState},
+    },
+    layout::{layout_view::LayoutView, overflow::ScrollOffset},
+    paint::{
+        display_list::DisplayList,
+        hit_test::{hit_test, HitTestResult},
+        painter::build_display_list,
+        rasterizer::{rasterize, Framebuffer},
+        text_mode::{render_text, TextPage},
+    },
+};
+use crate::{
+    browser::Browser,
+    error::Error,
+    http::HttpResponse,
+    renderer::css::value::Color,
+    utils::{ceil, convert_dom_to_string},
 };
-use crate::{browser::Browser, http::HttpResponse, utils::convert_dom_to_string};
 use alloc::{
     rc::{Rc, Weak},
-    string::String,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
 };
 use core::cell::RefCell;
 
 pub struct Page {
     browser: Weak<RefCell<Browser>>,
     frame: Option<Rc<RefCell<Window>>>,
+    style_sheets: Vec<StyleSheet>,
+    inline_styles: Vec<(Rc<RefCell<Node>>, Vec<Declaration>)>,
+    // `<link rel=stylesheet>`のhref、media属性、style_sheets内の位置
+    style_sheet_links: Vec<(String, Vec<ComponentValue>, usize)>,
+    media_environment: MediaEnvironment,
+    // レイアウトと描画で使うフォント
+    fonts: Rc<FontSystem>,
+    // 取得した画像のURLとデコードした画像。取得やデコードに失敗したものはNone
+    images: Vec<(String, Option<Rc<Image>>)>,
+    layout_view: Option<LayoutView>,
+    // レイアウトし直しても保つスクロール位置。ビューポートと要素ごとに持つ
+    scroll_offset: ScrollOffset,
+    element_scroll_offsets: Vec<(Rc<RefCell<Node>>, ScrollOffset)>,
 }
 
 impl Page {
     pub fn new(browser: Weak<RefCell<Browser>>) -> Self {
+        let media_environment = match browser.upgrade() {
+            Some(browser) => browser.borrow().media_environment(),
+            None => MediaEnvironment::default(),
+        };
         Self {
             browser,
             frame: None,
+            style_sheets: Vec::new(),
+            inline_styles: Vec::new(),
+            style_sheet_links: Vec::new(),
+            media_environment,
+            fonts: Rc::new(FontSystem::default()),
+            images: Vec::new(),
+            layout_view: None,
+            scroll_offset: ScrollOffset::default(),
+            element_scroll_offsets: Vec::new(),
         }
     }
 
@@ -34,9 +96,609 @@ impl Page {
         String::new()
     }
 
+    pub fn style_sheets(&self) -> Vec<StyleSheet> {
+        self.style_sheets.clone()
+    }
+
+    pub fn inline_style(&self, node: &Rc<RefCell<Node>>) -> Option<Vec<Declaration>> {
+        self.inline_styles
+            .iter()
+            .find(|(n, _)| Rc::ptr_eq(n, node))
+            .map(|(_, declarations)| declarations.clone())
+    }
+
+    pub fn layout_view(&self) -> Option<&LayoutView> {
+        self.layout_view.as_ref()
+    }
+
+    // 現在のスクロール位置でビューポートに見える内容の描画命令
+    pub fn display_list(&self) -> DisplayList {
+        match &self.layout_view {
+            Some(view) => build_display_list(view),
+            None => DisplayList::new(),
+        }
+    }
+
+    // 見えている内容をビューポートの大きさの画像に描く。キャンバスの既定の色は白にする
+    pub fn render(&self) -> Framebuffer {
+        let mut framebuffer = Framebuffer::new(
+            ceil(self.media_environment.width) as usize,
+            ceil(self.media_environment.height) as usize,
+        );
+        framebuffer.clear(Color::WHITE);
+        rasterize(&self.display_list(), &self.fonts, &mut framebuffer);
+        framebuffer
+    }
+
+    // 端末向けに、レイアウトした文書全体を指定した桁数の文字セルにする
+    pub fn render_text(&self, columns: usize) -> Option<TextPage> {
+        Some(render_text(self.layout_view.as_ref()?, columns))
+    }
+
+    // 文書上の点(ページ座標)にある最も手前のノードと、テキストならその中の位置。リンクのクリックやホバー、テキストの選択に使う。
+    // ビューポート上の点はscroll_offsetを足してページ座標にする
+    pub fn hit_test(&self, x: f64, y: f64) -> Option<HitTestResult> {
+        hit_test(self.layout_view.as_ref()?, x, y)
+    }
+
+    pub fn scroll_offset(&self) -> ScrollOffset {
+        self.scroll_offset
+    }
+
+    // ビューポートをスクロールする。スクロールできる範囲を超える位置は範囲に収める
+    pub fn scroll_to(&mut self, x: f64, y: f64) {
+        if let Some(view) = &mut self.layout_view {
+            self.scroll_offset = view.scroll_to(&ScrollOffset::new(x, y));
+        }
+    }
+
+    pub fn scroll_by(&mut self, dx: f64, dy: f64) {
+        self.scroll_to(self.scroll_offset.x + dx, self.scroll_offset.y + dy);
+    }
+
+    pub fn element_scroll_offset(&self, node: &Rc<RefCell<Node>>) -> Option<ScrollOffset> {
+        self.layout_view.as_ref()?.element_scroll_offset(node)
+    }
+
+    // 要素の内容をスクロールする。要素がスクロールコンテナでなければfalseを返す
+    pub fn scroll_element_to(&mut self, node: &Rc<RefCell<Node>>, x: f64, y: f64) -> bool {
+        let scrolled = match &mut self.layout_view {
+            Some(view) => view
+                .scroll_element_to(node, &ScrollOffset::new(x, y))
+                .is_some(),
+            None => false,
+        };
+        self.save_scroll_offsets();
+        scrolled
+    }
+
+    pub fn scroll_element_by(&mut self, node: &Rc<RefCell<Node>>, dx: f64, dy: f64) -> bool {
+        match self.element_scroll_offset(node) {
+            Some(offset) => self.scroll_element_to(node, offset.x + dx, offset.y + dy),
+            None => false,
+        }
+    }
+
+    // 要素が見えるように祖先のスクロールコンテナとビューポートをスクロールする
+    pub fn scroll_into_view(&mut self, node: &Rc<RefCell<Node>>) -> bool {
+        let scrolled = match &mut self.layout_view {
+            Some(view) => view.scroll_into_view(node),
+            None => false,
+        };
+        self.save_scroll_offsets();
+        scrolled
+    }
+
+    pub fn fonts(&self) -> Rc<FontSystem> {
+        self.fonts.clone()
+    }
+
+    // font-familyで使えるフォントを加え、その寸法でレイアウトし直す
+    pub fn add_font(&mut self, family: &str, font: Rc<dyn Font>) {
+        self.fonts.add_font(family, font);
+        self.resolve_styles();
+    }
+
+    // 文書の@font-faceで宣言されたWebフォント。media条件が一致しないものは含まない
+    pub fn font_faces(&self) -> Vec<FontFace> {
+        collect_font_faces(&self.style_sheets, &self.media_environment)
+    }
+
+    // 取得したフォントファイルを@font-faceのfont-familyとして使えるようにする
+    pub fn load_font_face(&mut self, family: &str, data: Vec<u8>) -> Result<(), Error> {
+        let font = parse_font(data)?;
+        self.add_font(family, font);
+        Ok(())
+    }
+
+    pub fn media_environment(&self) -> MediaEnvironment {
+        self.media_environment
+    }
+
+    // ビューポートや設定が変わったらスタイルを計算し直す
+    pub fn set_media_environment(&mut self, media_environment: MediaEnvironment) {
+        self.media_environment = media_environment;
+        self.resolve_styles();
+    }
+
+    // 取得が必要なスタイルシートのhref。media属性が一致しないものは含まない
+    pub fn style_sheet_links(&self) -> Vec<String> {
+        self.style_sheet_links
+            .iter()
+            .filter(|(_, media, _)| parse_media_query_list(media).matches(&self.media_environment))
+            .map(|(href, _, _)| href.clone())
+            .collect()
+    }
+
+    // 取得したスタイルシートを文書内のlink要素の位置に適用する
+    pub fn set_linked_style_sheet(&mut self, href: &str, css: String) {
+        let mut parser = CssParser::new(CssTokenizer::new(css));
+        let style_sheet = parser.parse_stylesheet();
+        for (link_href, media, index) in &self.style_sheet_links {
+            if link_href == href {
+                self.style_sheets[*index] = with_media(style_sheet.clone(), media.clone());
+            }
+        }
+        self.resolve_styles();
+    }
+
+    // 取得が必要な画像のURL。srcsetとsizesから現在の環境に合わせて選んだもの
+    pub fn image_urls(&self) -> Vec<String> {
+        let mut urls: Vec<String> = Vec::new();
+        for source in self.image_sources() {
+            let loaded = self.images.iter().any(|(url, _)| *url == source.url);
+            if !loaded && !urls.contains(&source.url) {
+                urls.push(source.url);
+            }
+        }
+        urls
+    }
+
+    // 取得した画像をデコードして、そのURLを選んだimg要素に表示する。デコードできなければalt属性の文字列を表示する
+    pub fn set_image(&mut self, url: &str, data: &[u8]) -> Result<(), Error> {
+        let (image, result) = match decode_image(data) {
+            Ok(image) => (Some(Rc::new(image)), Ok(())),
+            Err(e) => (None, Err(e)),
+        };
+        self.images.retain(|(u, _)| u != url);
+        self.images.push((url.to_string(), image));
+        self.resolve_styles();
+        result
+    }
+
+    // 画像を取得できなかったことを記録する
+    pub fn set_image_error(&mut self, url: &str) {
+        self.images.retain(|(u, _)| u != url);
+        self.images.push((url.to_string(), None));
+        self.resolve_styles();
+    }
+
+    fn image_sources(&self) -> Vec<ImageSource> {
+        let mut images = Vec::new();
+        if let Some(frame) = &self.frame {
+            collect_images(&frame.borrow().document(), &mut images);
+        }
+        images
+            .iter()
+            .filter_map(|node| image_source(node, &self.media_environment))
+            .collect()
+    }
+
+    // img要素ごとに選んだ画像の読み込み状態を設定する
+    fn update_images(&self, document: &Rc<RefCell<Node>>) {
+        let mut images = Vec::new();
+        collect_images(document, &mut images);
+        for node in images {
+            let state = match image_source(&node, &self.media_environment) {
+                None => ImageState::Broken,
+                Some(source) => match self.images.iter().find(|(url, _)| *url == source.url) {
+                    None => ImageState::Pending,
+                    Some((_, None)) => ImageState::Broken,
+                    Some((_, Some(image))) => ImageState::Loaded {
+                        image: image.clone(),
+                        density: source.density,
+                    },
+                },
+            };
+            node.borrow_mut().set_image_state(Some(state));
+        }
+    }
+
     fn create_frame(&mut self, html: String) {
         let tokenizer = HtmlTokenizer::new(html);
         let frame = HtmlParser::new(tokenizer).construct_tree();
+
+        let document = frame.borrow().document();
+        self.style_sheets = Vec::new();
+        self.inline_styles = Vec::new();
+        self.style_sheet_links = Vec::new();
+        self.images = Vec::new();
+        self.scroll_offset = ScrollOffset::default();
+        self.element_scroll_offsets = Vec::new();
+        self.collect_styles(&document);
+
         self.frame = Some(frame);
+        self.resolve_styles();
+    }
+
+    // スタイルを計算し直し、その結果でレイアウトする
+    fn resolve_styles(&mut self) {
+        if let Some(frame) = &self.frame {
+            let document = frame.borrow().document();
+            self.update_images(&document);
+            StyleResolver::new(
+                &self.style_sheets,
+                self.inline_styles.clone(),
+                &self.media_environment,
+            )
+            .resolve(&document);
+            let mut view = LayoutView::with_font_metrics(
+                &document,
+                self.media_environment.width,
+                self.media_environment.height,
+                self.fonts.clone(),
+            );
+            // 新しいレイアウトでスクロールできる範囲に収めて前の位置を復元する
+            for (node, offset) in &self.element_scroll_offsets {
+                view.scroll_element_to(node, offset);
+            }
+            view.scroll_to(&self.scroll_offset);
+            self.layout_view = Some(view);
+            self.save_scroll_offsets();
+        }
+    }
+
+    fn save_scroll_offsets(&mut self) {
+        if let Some(view) = &self.layout_view {
+            self.scroll_offset = view.scroll_offset();
+            self.element_scroll_offsets = view.element_scroll_offsets();
+        }
+    }
+
+    fn collect_styles(&mut self, node: &Rc<RefCell<Node>>) {
+        if let NodeKind::Element(element) = node.borrow().kind() {
+            let media = element
+                .get_attribute("media")
+                .map(|media| CssParser::new(CssTokenizer::new(media)).parse_component_values())
+                .unwrap_or_default();
+
+            if element.kind() == ElementKind::Style {
+                let css = node.borrow().text_content().unwrap_or_default();
+                let mut parser = CssParser::new(CssTokenizer::new(css));
+                self.style_sheets
+                    .push(with_media(parser.parse_stylesheet(), media.clone()));
+            }
+
+            let is_style_sheet = element.get_attribute("rel").is_some_and(|rel| {
+                rel.split_ascii_whitespace()
+                    .any(|r| r.eq_ignore_ascii_case("stylesheet"))
+            });
+            if element.kind() == ElementKind::Link && is_style_sheet {
+                if let Some(href) = element.get_attribute("href") {
+                    // 取得されるまでは空のスタイルシートで順序だけを確保する
+                    self.style_sheet_links
+                        .push((href, media, self.style_sheets.len()));
+                    self.style_sheets.push(StyleSheet::new());
+                }
+            }
+
+            if let Some(style) = element.get_attribute("style") {
+                let mut parser = CssParser::new(CssTokenizer::new(style));
+                self.inline_styles
+                    .push((node.clone(), parser.parse_declaration_list()));
+            }
+        }
+
+        for child in children(node) {
+            self.collect_styles(&child);
+        }
+    }
+}
+
+fn collect_images(node: &Rc<RefCell<Node>>, images: &mut Vec<Rc<RefCell<Node>>>) {
+    if node.borrow().element_kind() == Some(ElementKind::Img) {
+        images.push(node.clone());
+    }
+    for child in children(node) {
+        collect_images(&child, images);
+    }
+}
+
+// img要素が環境に合わせて選んだ画像
+fn image_source(node: &Rc<RefCell<Node>>, environment: &MediaEnvironment) -> Option<ImageSource> {
+    let element = node.borrow().get_element()?;
+    select_source(
+        element.get_attribute("src").as_deref(),
+        element.get_attribute("srcset").as_deref(),
+        element.get_attribute("sizes").as_deref(),
+        environment,
+    )
+}
+
+// media属性を持つスタイルシートは全体を@mediaで囲む
+fn with_media(style_sheet: StyleSheet, media: Vec<ComponentValue>) -> StyleSheet {
+    if media.iter().all(|v| v.is_whitespace()) {
+        return style_sheet;
+    }
+    StyleSheet {
+        rules: vec![CssRule::Media(MediaRule {
+            media,
+            rules: style_sheet.rules,
+        })],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::css::{
+        computed_style::{ComputedStyle, Display},
+        media::ColorScheme,
+        value::Color,
+    };
+    use crate::renderer::layout::{font_metrics::FontMetrics, layout_box::BoxKind};
+    use crate::renderer::paint::encoder::encode_png;
+    use alloc::string::ToString;
+
+    fn create_page(html: &str) -> Page {
+        let mut page = Page::new(Weak::new());
+        let response = HttpResponse::try_from(
+            "HTTP/1.1 200 OK\nContent-Type: text/html\n\n".to_string() + html,
+        )
+        .expect("failed to parse http response");
+        page.receive_response(response);
+        page
+    }
+
+    #[test]
+    fn test_collect_styles() {
+        let page = create_page(
+            "<html><head><style>h1 { color: red; }</style></head><body><p style=\"color: blue; margin: 0\">text</p><style>p { color: green; }</style></body></html>",
+        );
+
+        let style_sheets = page.style_sheets();
+        assert_eq!(2, style_sheets.len());
+        assert_eq!(1, style_sheets[0].rules.len());
+        assert_eq!(1, style_sheets[1].rules.len());
+
+        let document = page.frame.as_ref().unwrap().borrow().document();
+        let html = document.borrow().first_child().unwrap();
+        let body = html.borrow().last_child().upgrade().unwrap();
+        let p = body.borrow().first_child().unwrap();
+        let declarations = page.inline_style(&p).expect("failed to get inline style");
+        assert_eq!(2, declarations.len());
+        assert_eq!("color", declarations[0].name);
+
+        assert!(page.inline_style(&body).is_none());
+    }
+
+    #[test]
+    fn test_computed_style() {
+        let page = create_page(
+            "<html><head><style>p { color: red; }</style></head><body><p style=\"font-size: 20px\">text</p></body></html>",
+        );
+
+        let document = page.frame.as_ref().unwrap().borrow().document();
+        assert!(document.borrow().computed_style().is_none());
+        let html = document.borrow().first_child().unwrap();
+        let body = html.borrow().last_child().upgrade().unwrap();
+        let p = body.borrow().first_child().unwrap();
+        let style = p
+            .borrow()
+            .computed_style()
+            .expect("failed to get computed style");
+        assert_eq!(Display::Block, style.display);
+        assert_eq!(Color::rgb(255, 0, 0), style.color);
+        assert_eq!(20.0, style.font_size);
+    }
+
+    fn paragraph_style(page: &Page) -> ComputedStyle {
+        let document = page.frame.as_ref().unwrap().borrow().document();
+        let html = document.borrow().first_child().unwrap();
+        let body = html.borrow().last_child().upgrade().unwrap();
+        let p = body.borrow().first_child().unwrap();
+        let style = p.borrow().computed_style();
+        style.expect("failed to get computed style")
+    }
+
+    #[test]
+    fn test_media_environment() {
+        let mut page = create_page(
+            "<html><head><style>p { color: red } @media (max-width: 500px) { p { color: blue } }</style><style media=\"(prefers-color-scheme: dark)\">p { font-size: 20px }</style></head><body><p>text</p></body></html>",
+        );
+        let style = paragraph_style(&page);
+        assert_eq!(Color::rgb(255, 0, 0), style.color);
+        assert_eq!(16.0, style.font_size);
+
+        let mut environment = MediaEnvironment::new(400.0, 800.0);
+        environment.color_scheme = ColorScheme::Dark;
+        page.set_media_environment(environment);
+        let style = paragraph_style(&page);
+        assert_eq!(Color::rgb(0, 0, 255), style.color);
+        assert_eq!(20.0, style.font_size);
+    }
+
+    #[test]
+    fn test_range_media_query() {
+        // styleの中身はタグとして読まないので、範囲の<や対応しない終了タグもそのままCSSに渡る
+        let mut page = create_page(
+            "<html><head><style>/* </p> */ @media (400px <= width <= 800px) { p { color: blue } } @media (width < 900px) { p { font-size: 20px } }</style></head><body><p>text</p></body></html>",
+        );
+        page.set_media_environment(MediaEnvironment::new(800.0, 600.0));
+        let style = paragraph_style(&page);
+        assert_eq!(Color::rgb(0, 0, 255), style.color);
+        assert_eq!(20.0, style.font_size);
+
+        page.set_media_environment(MediaEnvironment::new(900.0, 600.0));
+        let style = paragraph_style(&page);
+        assert_eq!(Color::BLACK, style.color);
+        assert_eq!(16.0, style.font_size);
+    }
+
+    #[test]
+    fn test_linked_style_sheets() {
+        let mut page = create_page(
+            "<html><head><link rel=stylesheet href=a.css><link rel=\"alternate stylesheet\" href=b.css media=print><link rel=icon href=c.png><style>p { color: red }</style></head><body><p>text</p></body></html>",
+        );
+        assert_eq!(vec!["a.css".to_string()], page.style_sheet_links());
+        assert_eq!(3, page.style_sheets().len());
+
+        page.set_linked_style_sheet("a.css", "p { color: blue; font-size: 20px }".to_string());
+        let style = paragraph_style(&page);
+        // 後に現れるstyle要素が優先される
+        assert_eq!(Color::rgb(255, 0, 0), style.color);
+        assert_eq!(20.0, style.font_size);
+    }
+
+    #[test]
+    fn test_font_faces() {
+        let mut page = create_page(
+            "<html><head><style>@font-face { font-family: Web; src: url(web.bdf) } @media print { @font-face { font-family: Print; src: url(print.ttf) } } p { font-family: Web }</style></head><body><p>text</p></body></html>",
+        );
+        let faces = page.font_faces();
+        assert_eq!(1, faces.len());
+        assert_eq!(vec!["web.bdf".to_string()], faces[0].urls());
+
+        assert!(page.load_font_face("Web", b"not a font".to_vec()).is_err());
+        let bdf = "STARTFONT 2.1\nFONTBOUNDINGBOX 3 4 0 -1\nSTARTCHAR t\nENCODING 116\nDWIDTH 3 0\nBBX 1 1 0 0\nBITMAP\n80\nENDCHAR\nENDFONT\n";
+        page.load_font_face("Web", bdf.as_bytes().to_vec()).unwrap();
+        assert_eq!(vec!["web".to_string()], page.fonts().families());
+        assert_eq!(12.0, page.fonts().advance('t', &paragraph_style(&page)));
+    }
+
+    #[test]
+    fn test_layout_view() {
+        let mut page = create_page("<html><body><p>text</p></body></html>");
+        let body_width = |page: &Page| {
+            let root = page.layout_view().unwrap().root().unwrap();
+            root.children()[0].dimensions().content.width
+        };
+        assert_eq!(584.0, body_width(&page));
+
+        page.set_media_environment(MediaEnvironment::new(320.0, 480.0));
+        assert_eq!(304.0, body_width(&page));
+    }
+
+    #[test]
+    fn test_render() {
+        let mut page = create_page(
+            "<html><head><style>body { margin: 0 } p { margin: 0; height: 10px; background-color: #00ff00 }</style></head><body><p></p></body></html>",
+        );
+        page.set_media_environment(MediaEnvironment::new(20.5, 30.0));
+        let framebuffer = page.render();
+        assert_eq!((21, 30), (framebuffer.width(), framebuffer.height()));
+        assert_eq!(Color::rgb(0, 255, 0), framebuffer.pixel(0, 9));
+        assert_eq!(Color::WHITE, framebuffer.pixel(0, 10));
+    }
+
+    #[test]
+    fn test_render_text() {
+        let mut page = create_page("<html><body><h1>Title</h1><p>text</p></body></html>");
+        page.set_media_environment(MediaEnvironment::new(400.0, 300.0));
+        let text = page.render_text(40).unwrap();
+        assert_eq!(40, text.columns());
+        assert_eq!("Title\n\ntext\n", text.to_plain_text());
+    }
+
+    #[test]
+    fn test_hit_test() {
+        let mut page = create_page(
+            "<html><head><style>body, p { margin: 0 } p { padding-top: 500px }</style></head><body><p><a href=\"a.html\">link</a></p></body></html>",
+        );
+        page.set_media_environment(MediaEnvironment::new(400.0, 300.0));
+        page.scroll_to(0.0, 100.0);
+        // ビューポートの下にある点もページ座標で指定できる
+        let result = page.hit_test(9.0, 505.0).unwrap();
+        assert_eq!(Some(1), result.offset);
+        let link = result.node.borrow().parent().upgrade().unwrap();
+        assert_eq!(
+            Some("a.html".to_string()),
+            link.borrow().get_element().unwrap().get_attribute("href")
+        );
+    }
+
+    #[test]
+    fn test_images() {
+        let mut page = create_page(
+            "<html><head><style>body, p { margin: 0 } p { font-size: 10px; line-height: 20px } img { vertical-align: top }</style></head><body><p><img src=\"a.png\" srcset=\"b.png 2x\"><img src=\"missing.png\" alt=\"x\"><img src=\"a.png\"></p></body></html>",
+        );
+        assert_eq!(
+            vec!["a.png".to_string(), "missing.png".to_string()],
+            page.image_urls()
+        );
+        let mut environment = MediaEnvironment::new(20.0, 30.0);
+        environment.resolution = 2.0;
+        page.set_media_environment(environment);
+        assert_eq!(
+            vec![
+                "b.png".to_string(),
+                "missing.png".to_string(),
+                "a.png".to_string()
+            ],
+            page.image_urls()
+        );
+
+        let mut image = Framebuffer::new(4, 2);
+        image.clear(Color::rgb(0, 255, 0));
+        page.set_image("b.png", &encode_png(&image)).unwrap();
+        page.set_image_error("missing.png");
+        assert!(page.set_image("a.png", b"not an image").is_err());
+        assert!(page.image_urls().is_empty());
+
+        let p = |page: &Page| {
+            let root = page.layout_view().unwrap().root().unwrap();
+            root.children()[0].children()[0].clone()
+        };
+        // 2xの画像は半分の大きさで表示し、読み込めなかった画像の代わりにalt属性を表示する
+        let children = p(&page).children().to_vec();
+        assert_eq!(2.0, children[0].dimensions().content.width);
+        assert!(children[0].replaced().unwrap().image().is_some());
+        assert_eq!(BoxKind::Inline, children[1].kind());
+        assert_eq!(0.0, children[2].dimensions().content.width);
+        let framebuffer = page.render();
+        assert_eq!(Color::rgb(0, 255, 0), framebuffer.pixel(1, 0));
+    }
+
+    #[test]
+    fn test_scroll() {
+        let mut page = create_page(
+            "<html><head><style>body { margin: 0 } p { margin: 0; height: 1000px } .scroller { height: 100px; overflow: auto } a { display: block; height: 300px }</style></head><body><p class=\"scroller\"><a>a</a><a>b</a></p><p>c</p></body></html>",
+        );
+        let document = page.frame.as_ref().unwrap().borrow().document();
+        let html = document.borrow().first_child().unwrap();
+        let body = html.borrow().last_child().upgrade().unwrap();
+        let scroller = body.borrow().first_child().unwrap();
+        let b = scroller.borrow().last_child().upgrade().unwrap();
+
+        page.scroll_by(0.0, 300.0);
+        page.scroll_by(10.0, 300.0);
+        assert_eq!(ScrollOffset::new(0.0, 600.0), page.scroll_offset());
+        // 文書の高さ1100pxからビューポートの高さを引いた位置までしかスクロールできない
+        page.scroll_to(0.0, 2000.0);
+        assert_eq!(ScrollOffset::new(0.0, 700.0), page.scroll_offset());
+
+        assert!(page.scroll_element_by(&scroller, 0.0, 50.0));
+        assert!(!page.scroll_element_by(&body, 0.0, 50.0));
+        assert_eq!(
+            Some(ScrollOffset::new(0.0, 50.0)),
+            page.element_scroll_offset(&scroller)
+        );
+
+        assert!(page.scroll_into_view(&b));
+        assert_eq!(
+            Some(ScrollOffset::new(0.0, 300.0)),
+            page.element_scroll_offset(&scroller)
+        );
+        assert_eq!(ScrollOffset::default(), page.scroll_offset());
+
+        // レイアウトし直してもスクロール位置は保たれ、新しい範囲に収められる
+        page.scroll_to(0.0, 700.0);
+        page.set_media_environment(MediaEnvironment::new(600.0, 800.0));
+        assert_eq!(ScrollOffset::new(0.0, 300.0), page.scroll_offset());
+        assert!(!page.display_list().items().is_empty());
+        assert_eq!(
+            Some(ScrollOffset::new(0.0, 300.0)),
+            page.element_scroll_offset(&scroller)
+        );
     }
 }
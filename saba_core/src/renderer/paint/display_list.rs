@@ -0,0 +1,130 @@
+use crate::renderer::{
+    css::{
+        computed_style::{BorderStyle, ComputedStyle, Corners, FontStyle, Sides},
+        value::Color,
+    },
+    image::bitmap::Image,
+    layout::geometry::Rect,
+};
+use alloc::{rc::Rc, string::String, vec::Vec};
+
+// 文字列を描くときのフォントと色
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextStyle {
+    pub color: Color,
+    pub font_family: Vec<String>,
+    pub font_size: f64,
+    pub font_weight: u16,
+    pub font_style: FontStyle,
+}
+
+impl TextStyle {
+    pub fn new(style: &ComputedStyle) -> Self {
+        Self {
+            color: style.color,
+            font_family: style.font_family.clone(),
+            font_size: style.font_size,
+            font_weight: style.font_weight,
+            font_style: style.font_style,
+        }
+    }
+
+    // フォントを選ぶためのスタイル。文字に関係するプロパティだけを持つ
+    pub fn to_computed_style(&self) -> ComputedStyle {
+        ComputedStyle {
+            color: self.color,
+            font_family: self.font_family.clone(),
+            font_size: self.font_size,
+            font_weight: self.font_weight,
+            font_style: self.font_style,
+            ..ComputedStyle::default()
+        }
+    }
+}
+
+// 位置を決めたグラデーション。stopsは線上の割合と色の組
+#[derive(Clone, Debug, PartialEq)]
+pub enum GradientPaint {
+    // startからendへ向かって色が変わる
+    Linear {
+        start: (f64, f64),
+        end: (f64, f64),
+        stops: Vec<(f64, Color)>,
+    },
+    // centerから横radius.0、縦radius.1の楕円が広がるように色が変わる
+    Radial {
+        center: (f64, f64),
+        radius: (f64, f64),
+        stops: Vec<(f64, Color)>,
+    },
+}
+
+// 描画バックエンドに渡す命令。座標はビューポートの左上を原点とする
+#[derive(Clone, Debug, PartialEq)]
+pub enum DisplayItem {
+    FillRect {
+        rect: Rect,
+        color: Color,
+    },
+    // 角ごとに横と縦の半径で丸めた矩形を塗る
+    FillRoundedRect {
+        rect: Rect,
+        radii: Corners<(f64, f64)>,
+        color: Color,
+    },
+    FillGradient {
+        rect: Rect,
+        radii: Corners<(f64, f64)>,
+        gradient: GradientPaint,
+    },
+    // rectの内側に辺ごとの太さと線種でボーダーを描く
+    DrawBorder {
+        rect: Rect,
+        widths: Sides<f64>,
+        colors: Sides<Color>,
+        styles: Sides<BorderStyle>,
+        radii: Corners<(f64, f64)>,
+    },
+    // ベースライン上の位置(x, baseline)から文字列を描く
+    DrawText {
+        x: f64,
+        baseline: f64,
+        text: String,
+        style: TextStyle,
+    },
+    // imageの最初のコマをrectに合わせて拡大縮小して描く
+    DrawImage {
+        rect: Rect,
+        image: Rc<Image>,
+    },
+    // 対応するPopClipまでの描画をrectの内側に制限する
+    PushClip {
+        rect: Rect,
+    },
+    PopClip,
+    // 対応するPopOpacityまでの描画をまとめてから不透明度opacityで合成する
+    PushOpacity {
+        opacity: f64,
+    },
+    PopOpacity,
+}
+
+// 描画順に並べた命令の列
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DisplayList {
+    items: Vec<DisplayItem>,
+}
+
+impl DisplayList {
+    pub fn new() -> Self {
+        Self { items: Vec::new() }
+    }
+
+    pub fn items(&self) -> &[DisplayItem] {
+        &self.items
+    }
+
+    pub fn push(&mut self, item: DisplayItem) {
+        self.items.push(item);
+    }
+}
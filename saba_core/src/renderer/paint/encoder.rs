@@ -0,0 +1,146 @@
+use crate::renderer::{css::value::Color, paint::rasterizer::Framebuffer};
+use alloc::{format, vec::Vec};
+
+pub const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+// deflateの無圧縮ブロックに入る最大のバイト数
+const MAX_STORED_BLOCK: usize = 65535;
+
+// PNGのチャンクに使うCRC-32。ISO 3309
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffff_u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+// zlibストリームの末尾に付けるチェックサム。RFC 1950
+pub fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1_u32, 0_u32);
+    for byte in data {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+fn push_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+// 圧縮しないdeflateブロックを並べたzlibストリーム。出力が入力だけで決まるようにする
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut stream = Vec::with_capacity(data.len() + data.len() / MAX_STORED_BLOCK * 5 + 11);
+    stream.extend_from_slice(&[0x78, 0x01]);
+    let mut blocks = data.chunks(MAX_STORED_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        stream.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let is_final = blocks.peek().is_none();
+        let length = block.len() as u16;
+        stream.push(is_final as u8);
+        stream.extend_from_slice(&length.to_le_bytes());
+        stream.extend_from_slice(&(!length).to_le_bytes());
+        stream.extend_from_slice(block);
+    }
+    stream.extend_from_slice(&adler32(data).to_be_bytes());
+    stream
+}
+
+// RGBA8のPNG画像にする。PNG Specification 11.2
+pub fn encode_png(framebuffer: &Framebuffer) -> Vec<u8> {
+    let (width, height) = (framebuffer.width(), framebuffer.height());
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(width as u32).to_be_bytes());
+    header.extend_from_slice(&(height as u32).to_be_bytes());
+    // ビット深度8、カラータイプ6(RGBA)、圧縮・フィルタ・インターレースは既定の方式
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    // 各行の先頭にフィルタなしを表す0を置く
+    let mut raw = Vec::with_capacity((width * 4 + 1) * height);
+    for row in framebuffer.pixels().chunks(width.max(1) * 4) {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+
+    let mut png = Vec::new();
+    png.extend_from_slice(&PNG_SIGNATURE);
+    push_chunk(&mut png, b"IHDR", &header);
+    push_chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+    push_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+// バイナリ形式(P6)のPPM画像にする。透明度は持てないためbackgroundに重ねる
+pub fn encode_ppm(framebuffer: &Framebuffer, background: Color) -> Vec<u8> {
+    let mut ppm = format!(
+        "P6\n{} {}\n255\n",
+        framebuffer.width(),
+        framebuffer.height()
+    )
+    .into_bytes();
+    for pixel in framebuffer.pixels().chunks_exact(4) {
+        let alpha = pixel[3] as u32;
+        let over = |source: u8, backdrop: u8| {
+            ((source as u32 * alpha + backdrop as u32 * (255 - alpha) + 127) / 255) as u8
+        };
+        ppm.push(over(pixel[0], background.r));
+        ppm.push(over(pixel[1], background.g));
+        ppm.push(over(pixel[2], background.b));
+    }
+    ppm
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(0xcbf4_3926, crc32(b"123456789"));
+        assert_eq!(0x11e6_0398, adler32(b"Wikipedia"));
+    }
+
+    #[test]
+    fn test_encode_png() {
+        let mut framebuffer = Framebuffer::new(2, 1);
+        framebuffer.clear(Color::rgb(255, 0, 0));
+        let png = encode_png(&framebuffer);
+        assert_eq!(&PNG_SIGNATURE, &png[..8]);
+        assert_eq!(
+            &[0, 0, 0, 13, b'I', b'H', b'D', b'R', 0, 0, 0, 2, 0, 0, 0, 1, 8, 6],
+            &png[8..26]
+        );
+        // 1行分の9バイトを1つの無圧縮ブロックに入れる
+        assert_eq!(&[0, 0, 0, 20, b'I', b'D', b'A', b'T'], &png[33..41]);
+        assert_eq!(&[0x78, 0x01, 1, 9, 0, 0xf6, 0xff, 0], &png[41..49]);
+        assert_eq!(&[255, 0, 0, 255, 255, 0, 0, 255], &png[49..57]);
+        assert_eq!(
+            &[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82],
+            &png[png.len() - 12..]
+        );
+    }
+
+    #[test]
+    fn test_encode_ppm() {
+        let mut framebuffer = Framebuffer::new(2, 1);
+        framebuffer.blend(0, 0, Color::rgb(0, 0, 255), 1.0);
+        let ppm = encode_ppm(&framebuffer, Color::WHITE);
+        assert_eq!(b"P6\n2 1\n255\n", &ppm[..11]);
+        assert_eq!(&[0, 0, 255, 255, 255, 255], &ppm[11..]);
+    }
+}
@@ -0,0 +1,177 @@
+use crate::renderer::{
+    css::computed_style::{ComputedStyle, PointerEvents},
+    dom::node::Node,
+    layout::{font_metrics::FontMetrics, layout_box::Fragment, layout_view::LayoutView},
+    paint::painter::hit_regions,
+};
+use alloc::rc::Rc;
+use core::cell::RefCell;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HitTestResult {
+    pub node: Rc<RefCell<Node>>,
+    // テキストノードに当たった場合、点に最も近い文字の境界の位置(文字数)
+    pub offset: Option<usize>,
+}
+
+// 断片の中で、左端からdxの位置に最も近い文字の境界
+fn text_offset(
+    font_metrics: &dyn FontMetrics,
+    fragment: &Fragment,
+    style: &ComputedStyle,
+    dx: f64,
+) -> usize {
+    let mut previous = 0.0;
+    for (i, (end, c)) in fragment.text.char_indices().enumerate() {
+        let width = font_metrics.text_width(&fragment.text[..end + c.len_utf8()], style);
+        if dx < (previous + width) / 2.0 {
+            return fragment.offset + i;
+        }
+        previous = width;
+    }
+    fragment.offset + fragment.text.chars().count()
+}
+
+// 文書上の点(ページ座標)にある最も手前のノード。描画と同じ重ね合わせの順序、overflowによる切り取り、スクロール位置に従う。
+// 固定位置のボックスは今のスクロール位置で判定する。pointer-eventsがnoneのボックスは対象にせず、その下にあるものを返す
+pub fn hit_test(view: &LayoutView, x: f64, y: f64) -> Option<HitTestResult> {
+    if !view.scrollable_overflow().contains(x, y) {
+        return None;
+    }
+    // 領域はビューポート上の座標なので、スクロール位置を引いて比べる
+    let scroll = view.scroll_offset();
+    let (x, y) = (x - scroll.x, y - scroll.y);
+    let regions = hit_regions(view);
+    let font_metrics = view.font_metrics();
+    for region in regions.iter().rev() {
+        let layout_box = region.layout_box;
+        if layout_box.style().pointer_events == PointerEvents::None
+            || !region.rect.contains(x, y)
+            || region.clip.is_some_and(|clip| !clip.contains(x, y))
+        {
+            continue;
+        }
+        // 匿名ボックスは親のボックスの中にあるので、親の領域で判定する
+        let Some(node) = layout_box.node() else {
+            continue;
+        };
+        let offset = region.fragment.map(|fragment| {
+            text_offset(
+                font_metrics.as_ref(),
+                fragment,
+                layout_box.style(),
+                x - region.rect.x,
+            )
+        });
+        return Some(HitTestResult { node, offset });
+    }
+
+    // キャンバスはルート要素の背景なので、どのボックスにも当たらなければルート要素とする
+    let root = view.root()?;
+    if root.style().pointer_events == PointerEvents::None {
+        return None;
+    }
+    Some(HitTestResult {
+        node: root.node()?,
+        offset: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::{
+        dom::node::NodeKind,
+        layout::{layout_document, overflow::ScrollOffset},
+    };
+    use alloc::{
+        format,
+        string::{String, ToString},
+    };
+
+    // 当たったノードを要素名とclass属性、またはテキストと位置で表す
+    fn hit(view: &LayoutView, x: f64, y: f64) -> String {
+        let Some(result) = hit_test(view, x, y) else {
+            return "none".to_string();
+        };
+        let kind = result.node.borrow().kind();
+        match kind {
+            NodeKind::Text(text) => format!("\"{}\" {:?}", text, result.offset),
+            NodeKind::Element(element) => format!(
+                "{}.{}",
+                element.kind(),
+                element.get_attribute("class").unwrap_or_default()
+            ),
+            _ => "other".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_text_offset() {
+        let (_, view) = layout_document(
+            "<html><body><p>hello world</p></body></html>",
+            "body, p { margin: 0 }",
+        );
+        assert_eq!("\"hello world\" Some(0)", hit(&view, 3.0, 5.0));
+        assert_eq!("\"hello world\" Some(3)", hit(&view, 21.0, 5.0));
+        assert_eq!("\"hello world\" Some(11)", hit(&view, 87.0, 5.0));
+        assert_eq!("p.", hit(&view, 100.0, 5.0));
+        // キャンバスはルート要素、ビューポートの外は何もない
+        assert_eq!("html.", hit(&view, 100.0, 300.0));
+        assert_eq!("none", hit(&view, 600.0, 5.0));
+    }
+
+    #[test]
+    fn test_stacking_and_pointer_events() {
+        let html = "<html><body><p class=\"a\"></p><p class=\"b\"></p>\
+                    <p class=\"pos\"><a class=\"auto\">x</a></p><p class=\"neg\"></p></body></html>";
+        let css = "body, p { margin: 0 } .a, .b { height: 20px }
+            .pos { position: absolute; top: 0; left: 100px; width: 50px; height: 50px; z-index: 1 }
+            .neg { position: absolute; top: 0; left: 200px; width: 50px; height: 50px; z-index: -1 }";
+        let (_, view) = layout_document(html, css);
+        assert_eq!("p.pos", hit(&view, 120.0, 30.0));
+        assert_eq!("\"x\" Some(0)", hit(&view, 101.0, 5.0));
+        assert_eq!("p.b", hit(&view, 220.0, 30.0));
+        assert_eq!("p.neg", hit(&view, 220.0, 45.0));
+
+        // pointer-events: noneのボックスは透過し、autoに戻した子孫には当たる
+        let css = format!(
+            "{} .pos {{ pointer-events: none }} .auto {{ pointer-events: auto }}",
+            css
+        );
+        let (_, view) = layout_document(html, &css);
+        assert_eq!("p.b", hit(&view, 120.0, 30.0));
+        assert_eq!("\"x\" Some(1)", hit(&view, 107.0, 5.0));
+    }
+
+    #[test]
+    fn test_clip_and_scroll() {
+        let html =
+            "<html><body><ul class=\"list\"><li class=\"one\"></li><li class=\"two\"></li></ul>\
+                    <p class=\"after\">below</p><p class=\"fixed\"></p></body></html>";
+        let css = "body, p, ul { margin: 0; padding: 0 } li { height: 20px }
+            .list { height: 20px; overflow: hidden } .after { height: 600px; padding-top: 550px }
+            .fixed { position: fixed; top: 0; left: 300px; width: 50px; height: 50px }";
+        let (_, mut view) = layout_document(html, css);
+        assert_eq!("li.one", hit(&view, 50.0, 10.0));
+        assert_eq!("p.after", hit(&view, 310.0, 55.0));
+        // 切り取られた部分には当たらない
+        assert_eq!("p.after", hit(&view, 50.0, 30.0));
+
+        let list = view.root().unwrap().children()[0].children()[0]
+            .node()
+            .unwrap();
+        view.scroll_element_to(&list, &ScrollOffset::new(0.0, 20.0));
+        assert_eq!("li.two", hit(&view, 50.0, 10.0));
+
+        // 座標は文書上の位置で、ビューポートの外にあるものにも当たる
+        view.scroll_to(&ScrollOffset::new(0.0, 10.0));
+        assert_eq!("li.two", hit(&view, 50.0, 15.0));
+        assert_eq!("p.after", hit(&view, 50.0, 25.0));
+        assert_eq!("\"below\" Some(1)", hit(&view, 9.0, 575.0));
+        assert_eq!("p.after", hit(&view, 50.0, 1100.0));
+        assert_eq!("none", hit(&view, 50.0, 1200.0));
+        // 固定位置のボックスはビューポートとともに動く
+        assert_eq!("p.fixed", hit(&view, 310.0, 55.0));
+    }
+}
@@ -0,0 +1,6 @@
+pub mod display_list;
+pub mod encoder;
+pub mod hit_test;
+pub mod painter;
+pub mod rasterizer;
+pub mod text_mode;
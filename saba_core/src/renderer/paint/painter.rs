@@ -0,0 +1,850 @@
+use crate::{
+    renderer::{
+        css::{
+            computed_style::{BorderStyle, Corners, Position, Sides, TextDecoration, Visibility},
+            gradient::{resolve_color_stops, Gradient, GradientDirection, RadialShape, RadialSize},
+            value::{Color, Length},
+        },
+        dom::node::{ElementKind, NodeKind},
+        layout::{
+            font_metrics::FontMetrics,
+            geometry::Rect,
+            layout_box::{BoxKind, Fragment, LayoutBox},
+            layout_view::LayoutView,
+        },
+        paint::display_list::{DisplayItem, DisplayList, GradientPaint, TextStyle},
+    },
+    utils::{cos, sin, sqrt},
+};
+use alloc::{rc::Rc, vec, vec::Vec};
+use core::{
+    f64::consts::{PI, SQRT_2},
+    ptr,
+};
+
+// ボックスを描くときに祖先から受け継ぐ状態
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct PaintState {
+    // スクロールによってずらす量
+    dx: f64,
+    dy: f64,
+    // 祖先のoverflowで切り取られる範囲
+    clip: Option<Rect>,
+}
+
+// 重ね合わせ文脈の中で描く順番ごとに分けた子孫
+#[derive(Default)]
+struct Layers<'a> {
+    negative: Vec<(&'a LayoutBox, PaintState)>,
+    floats: Vec<(&'a LayoutBox, PaintState)>,
+    // z-indexがautoか0の位置指定されたボックス
+    zero: Vec<(&'a LayoutBox, PaintState)>,
+    positive: Vec<(&'a LayoutBox, PaintState)>,
+}
+
+// 描いたボックスや文字の断片のビューポート上の領域
+#[derive(Clone, Debug)]
+pub(super) struct HitRegion<'a> {
+    pub(super) layout_box: &'a LayoutBox,
+    pub(super) rect: Rect,
+    // 祖先のoverflowで切り取られる範囲
+    pub(super) clip: Option<Rect>,
+    pub(super) fragment: Option<&'a Fragment>,
+}
+
+struct Painter<'a> {
+    font_metrics: Rc<dyn FontMetrics>,
+    viewport: Rect,
+    // 背景をキャンバスに描いたボックス
+    canvas_boxes: Vec<&'a LayoutBox>,
+    // 出力済みのPushClipで有効になっている範囲
+    clip: Option<Rect>,
+    list: DisplayList,
+    // 描いた順に並べた領域
+    regions: Vec<HitRegion<'a>>,
+}
+
+fn paint(view: &LayoutView) -> Option<Painter<'_>> {
+    let root = view.root()?;
+    let mut painter = Painter {
+        font_metrics: view.font_metrics(),
+        viewport: Rect::new(0.0, 0.0, view.viewport_width(), view.viewport_height()),
+        canvas_boxes: Vec::new(),
+        clip: None,
+        list: DisplayList::new(),
+        regions: Vec::new(),
+    };
+    painter.paint_canvas(root);
+    let scroll = view.scroll_offset();
+    let state = PaintState {
+        dx: -scroll.x,
+        dy: -scroll.y,
+        clip: None,
+    };
+    painter.paint_layer(root, &state);
+    painter.set_clip(None);
+    Some(painter)
+}
+
+// レイアウト済みのボックスをCSSの描画順に並べた描画命令にする。CSS 2.1 Appendix E
+pub fn build_display_list(view: &LayoutView) -> DisplayList {
+    match paint(view) {
+        Some(painter) => painter.list,
+        None => DisplayList::new(),
+    }
+}
+
+// 見えているボックスと文字の断片の領域を描画順に並べる。後ろにあるものほど手前に描かれる
+pub(super) fn hit_regions(view: &LayoutView) -> Vec<HitRegion<'_>> {
+    match paint(view) {
+        Some(painter) => painter.regions,
+        None => Vec::new(),
+    }
+}
+
+fn is_body(layout_box: &LayoutBox) -> bool {
+    layout_box
+        .node()
+        .is_some_and(|node| match node.borrow().kind() {
+            NodeKind::Element(element) => element.kind() == ElementKind::Body,
+            _ => false,
+        })
+}
+
+fn combine(a: TextDecoration, b: TextDecoration) -> TextDecoration {
+    TextDecoration {
+        underline: a.underline || b.underline,
+        overline: a.overline || b.overline,
+        line_through: a.line_through || b.line_through,
+    }
+}
+
+// 角の半径をボーダーボックスに対して求める。隣り合う角の半径の和が辺より長ければ全体を縮める。CSS Backgrounds 3 5.5
+fn resolve_radii(radius: &Corners<(Length, Length)>, rect: &Rect) -> Corners<(f64, f64)> {
+    let resolve = |(h, v): &(Length, Length)| {
+        let (h, v) = (
+            h.resolve_or_zero(rect.width),
+            v.resolve_or_zero(rect.height),
+        );
+        // どちらかが0なら角は丸くならない
+        if h > 0.0 && v > 0.0 {
+            (h, v)
+        } else {
+            (0.0, 0.0)
+        }
+    };
+    let radii = Corners {
+        top_left: resolve(&radius.top_left),
+        top_right: resolve(&radius.top_right),
+        bottom_right: resolve(&radius.bottom_right),
+        bottom_left: resolve(&radius.bottom_left),
+    };
+    let sides = [
+        (radii.top_left.0 + radii.top_right.0, rect.width),
+        (radii.bottom_left.0 + radii.bottom_right.0, rect.width),
+        (radii.top_left.1 + radii.bottom_left.1, rect.height),
+        (radii.top_right.1 + radii.bottom_right.1, rect.height),
+    ];
+    let f = sides
+        .iter()
+        .filter(|(sum, _)| *sum > 0.0)
+        .fold(1.0_f64, |f, (sum, side)| f.min(side / sum));
+    let scale = |(h, v): (f64, f64)| (h * f, v * f);
+    Corners {
+        top_left: scale(radii.top_left),
+        top_right: scale(radii.top_right),
+        bottom_right: scale(radii.bottom_right),
+        bottom_left: scale(radii.bottom_left),
+    }
+}
+
+// グラデーションの線や中心をareaに合わせて決める。CSS Images 3 3.1, 3.2
+fn gradient_paint(gradient: &Gradient, area: &Rect) -> GradientPaint {
+    let center = (area.x + area.width / 2.0, area.y + area.height / 2.0);
+    match gradient {
+        Gradient::Linear { direction, stops } => {
+            let (dx, dy) = match direction {
+                GradientDirection::Angle(angle) => {
+                    let radian = angle * PI / 180.0;
+                    (sin(radian), -cos(radian))
+                }
+                // 角に向かうときは隣の2つの角を結ぶ線に垂直な向きになる
+                GradientDirection::Corner(x, y) => {
+                    let (x, y) = (*x as f64 * area.height, *y as f64 * area.width);
+                    let length = sqrt(x * x + y * y);
+                    if length > 0.0 {
+                        (x / length, y / length)
+                    } else {
+                        (0.0, -1.0)
+                    }
+                }
+            };
+            // 線の両端で垂直な線が箱の角を通る長さ
+            let length = area.width * dx.max(-dx) + area.height * dy.max(-dy);
+            let half = length / 2.0;
+            GradientPaint::Linear {
+                start: (center.0 - dx * half, center.1 - dy * half),
+                end: (center.0 + dx * half, center.1 + dy * half),
+                stops: resolve_color_stops(stops, length),
+            }
+        }
+        Gradient::Radial {
+            shape,
+            size,
+            center: (x, y),
+            stops,
+        } => {
+            let cx = area.x + x.resolve_or_zero(area.width);
+            let cy = area.y + y.resolve_or_zero(area.height);
+            let (left, right) = (cx - area.x, area.right() - cx);
+            let (top, bottom) = (cy - area.y, area.bottom() - cy);
+            let (left, right, top, bottom) = (
+                left.max(-left),
+                right.max(-right),
+                top.max(-top),
+                bottom.max(-bottom),
+            );
+            let closest = (left.min(right), top.min(bottom));
+            let farthest = (left.max(right), top.max(bottom));
+            let radius = match (shape, size) {
+                (RadialShape::Circle, RadialSize::ClosestSide) => {
+                    let r = closest.0.min(closest.1);
+                    (r, r)
+                }
+                (RadialShape::Circle, RadialSize::FarthestSide) => {
+                    let r = farthest.0.max(farthest.1);
+                    (r, r)
+                }
+                (RadialShape::Circle, RadialSize::ClosestCorner) => {
+                    let r = sqrt(closest.0 * closest.0 + closest.1 * closest.1);
+                    (r, r)
+                }
+                (RadialShape::Circle, RadialSize::FarthestCorner) => {
+                    let r = sqrt(farthest.0 * farthest.0 + farthest.1 * farthest.1);
+                    (r, r)
+                }
+                (RadialShape::Ellipse, RadialSize::ClosestSide) => closest,
+                (RadialShape::Ellipse, RadialSize::FarthestSide) => farthest,
+                // 辺に接する楕円と同じ縦横比で角を通る
+                (RadialShape::Ellipse, RadialSize::ClosestCorner) => {
+                    (closest.0 * SQRT_2, closest.1 * SQRT_2)
+                }
+                (RadialShape::Ellipse, RadialSize::FarthestCorner) => {
+                    (farthest.0 * SQRT_2, farthest.1 * SQRT_2)
+                }
+            };
+            GradientPaint::Radial {
+                center: (cx, cy),
+                radius,
+                stops: resolve_color_stops(stops, radius.0),
+            }
+        }
+    }
+}
+
+// 重ね合わせ文脈やフロートのように、まとめて描くボックス
+fn is_layer(layout_box: &LayoutBox) -> bool {
+    layout_box.creates_stacking_context() || layout_box.is_positioned() || layout_box.is_float()
+}
+
+impl<'a> Painter<'a> {
+    // ルート要素の背景はキャンバス全体に描く。ルートの背景が透明ならbody要素の背景を使う。CSS 2.1 14.2
+    fn paint_canvas(&mut self, root: &'a LayoutBox) {
+        self.canvas_boxes.push(root);
+        let mut style = root.style();
+        if style.background_color.a == 0 && style.background_image.is_none() {
+            if let Some(body) = root.children().iter().find(|b| is_body(b)) {
+                self.canvas_boxes.push(body);
+                style = body.style();
+            }
+        }
+        self.fill_rect(self.viewport, style.background_color);
+        if let Some(gradient) = &style.background_image {
+            let radii = Corners::all((0.0, 0.0));
+            self.fill_gradient(self.viewport, self.viewport, radii, gradient);
+        }
+    }
+
+    fn set_clip(&mut self, clip: Option<Rect>) {
+        if self.clip == clip {
+            return;
+        }
+        if self.clip.is_some() {
+            self.list.push(DisplayItem::PopClip);
+        }
+        if let Some(rect) = clip {
+            self.list.push(DisplayItem::PushClip { rect });
+        }
+        self.clip = clip;
+    }
+
+    fn fill_rect(&mut self, rect: Rect, color: Color) {
+        if color.a == 0 || !rect.intersects(&self.viewport) {
+            return;
+        }
+        self.list.push(DisplayItem::FillRect { rect, color });
+    }
+
+    fn fill_rounded_rect(&mut self, rect: Rect, radii: Corners<(f64, f64)>, color: Color) {
+        if radii == Corners::all((0.0, 0.0)) {
+            self.fill_rect(rect, color);
+            return;
+        }
+        if color.a == 0 || !rect.intersects(&self.viewport) {
+            return;
+        }
+        self.list
+            .push(DisplayItem::FillRoundedRect { rect, radii, color });
+    }
+
+    // 背景画像は境界ボックスまで描き、位置と大きさはパディングボックスを基準にする
+    fn fill_gradient(
+        &mut self,
+        rect: Rect,
+        area: Rect,
+        radii: Corners<(f64, f64)>,
+        gradient: &Gradient,
+    ) {
+        if !rect.intersects(&self.viewport) {
+            return;
+        }
+        self.list.push(DisplayItem::FillGradient {
+            rect,
+            radii,
+            gradient: gradient_paint(gradient, &area),
+        });
+    }
+
+    // 子孫を描くときの状態。overflowで切り取るボックスとスクロールコンテナの中身が対象になる
+    fn child_state(&self, layout_box: &LayoutBox, state: &PaintState) -> PaintState {
+        let mut inner = *state;
+        if layout_box.clips_overflow() {
+            let mut clip = layout_box.dimensions().padding_box();
+            clip.translate(state.dx, state.dy);
+            inner.clip = Some(match state.clip {
+                Some(outer) => outer.intersection(&clip),
+                None => clip,
+            });
+        }
+        if layout_box.is_scroll_container() {
+            let offset = layout_box.scroll_offset();
+            inner.dx -= offset.x;
+            inner.dy -= offset.y;
+        }
+        inner
+    }
+
+    // 重ね合わせ文脈を描く。z-indexのない位置指定されたボックスやフロートもまとめて描く
+    fn paint_layer(&mut self, layout_box: &'a LayoutBox, state: &PaintState) {
+        let opacity = layout_box.style().opacity;
+        if opacity < 1.0 {
+            self.set_clip(None);
+            self.list.push(DisplayItem::PushOpacity { opacity });
+        }
+
+        self.paint_decorations(layout_box, state);
+        let inner = self.child_state(layout_box, state);
+        let mut layers = Layers::default();
+        self.collect_layers(layout_box, &inner, &mut layers);
+        // 同じz-indexの間では文書順を保つ
+        layers.negative.sort_by_key(|(b, _)| b.z_index());
+        layers.positive.sort_by_key(|(b, _)| b.z_index());
+
+        for (child, child_state) in layers.negative {
+            self.paint_layer(child, &child_state);
+        }
+        self.paint_blocks(layout_box, &inner);
+        for (child, child_state) in layers.floats {
+            self.paint_layer(child, &child_state);
+        }
+        self.paint_replaced(layout_box, state);
+        if layout_box.kind() == BoxKind::Inline {
+            self.paint_inlines(layout_box, state, layout_box.style().text_decoration);
+        } else {
+            self.paint_inlines(layout_box, &inner, layout_box.style().text_decoration);
+        }
+        for (child, child_state) in layers.zero.into_iter().chain(layers.positive) {
+            self.paint_layer(child, &child_state);
+        }
+
+        if opacity < 1.0 {
+            self.set_clip(None);
+            self.list.push(DisplayItem::PopOpacity);
+        }
+    }
+
+    fn collect_layers(
+        &self,
+        layout_box: &'a LayoutBox,
+        state: &PaintState,
+        layers: &mut Layers<'a>,
+    ) {
+        for child in layout_box.children() {
+            // 固定配置のボックスはスクロールに関係なくビューポートに対して描く
+            let child_state = if child.style().position == Position::Fixed {
+                PaintState {
+                    dx: 0.0,
+                    dy: 0.0,
+                    clip: None,
+                }
+            } else {
+                *state
+            };
+            if child.creates_stacking_context() {
+                match child.z_index() {
+                    z if z < 0 => layers.negative.push((child, child_state)),
+                    0 => layers.zero.push((child, child_state)),
+                    _ => layers.positive.push((child, child_state)),
+                }
+            } else if child.is_positioned() {
+                layers.zero.push((child, child_state));
+            } else if child.is_float() {
+                layers.floats.push((child, child_state));
+            } else if child.kind() != BoxKind::InlineBlock {
+                let inner = self.child_state(child, &child_state);
+                self.collect_layers(child, &inner, layers);
+            }
+        }
+    }
+
+    // 通常フローのブロックレベルの子孫の背景とボーダー
+    fn paint_blocks(&mut self, layout_box: &'a LayoutBox, state: &PaintState) {
+        for child in layout_box.children() {
+            if is_layer(child) || child.is_inline_level() {
+                continue;
+            }
+            self.paint_decorations(child, state);
+            let inner = self.child_state(child, state);
+            self.paint_blocks(child, &inner);
+        }
+    }
+
+    // 通常フローのインラインレベルの子孫。インラインブロックは重ね合わせ文脈と同じようにまとめて描く
+    fn paint_inlines(
+        &mut self,
+        layout_box: &'a LayoutBox,
+        state: &PaintState,
+        decoration: TextDecoration,
+    ) {
+        for child in layout_box.children() {
+            if is_layer(child) {
+                continue;
+            }
+            let decoration = combine(decoration, child.style().text_decoration);
+            match child.kind() {
+                BoxKind::Text(_) => self.paint_text(child, state, decoration),
+                BoxKind::Inline => {
+                    self.paint_decorations(child, state);
+                    self.paint_inlines(child, state, decoration);
+                }
+                BoxKind::InlineBlock => self.paint_layer(child, state),
+                _ => {
+                    self.paint_replaced(child, state);
+                    let inner = self.child_state(child, state);
+                    self.paint_inlines(child, &inner, decoration);
+                }
+            }
+        }
+    }
+
+    // 背景とボーダー。インラインボックスは行ごとの断片に描く
+    fn paint_decorations(&mut self, layout_box: &'a LayoutBox, state: &PaintState) {
+        let style = layout_box.style();
+        if style.visibility != Visibility::Visible {
+            return;
+        }
+        let dimensions = layout_box.dimensions();
+        let boxes = match layout_box.kind() {
+            BoxKind::Text(_) => return,
+            BoxKind::Inline => {
+                // 左右のボーダーは最初と最後の断片にだけ付く
+                let count = layout_box.fragments().len();
+                layout_box
+                    .fragments()
+                    .iter()
+                    .enumerate()
+                    .map(|(i, fragment)| {
+                        let mut border = dimensions.border;
+                        if i > 0 {
+                            border.left = 0.0;
+                        }
+                        if i + 1 < count {
+                            border.right = 0.0;
+                        }
+                        (fragment.rect, border)
+                    })
+                    .collect()
+            }
+            _ => vec![(dimensions.border_box(), dimensions.border)],
+        };
+        self.set_clip(state.clip);
+        let paints_background = !self.canvas_boxes.iter().any(|b| ptr::eq(*b, layout_box));
+        for (mut rect, border) in boxes {
+            rect.translate(state.dx, state.dy);
+            self.regions.push(HitRegion {
+                layout_box,
+                rect,
+                clip: state.clip,
+                fragment: None,
+            });
+            let radii = resolve_radii(&style.border_radius, &rect);
+            if paints_background {
+                self.fill_rounded_rect(rect, radii, style.background_color);
+                if let Some(gradient) = &style.background_image {
+                    let area = Rect::new(
+                        rect.x + border.left,
+                        rect.y + border.top,
+                        rect.width - border.left - border.right,
+                        rect.height - border.top - border.bottom,
+                    );
+                    self.fill_gradient(rect, area, radii, gradient);
+                }
+            }
+            self.paint_border(rect, border, radii, layout_box);
+        }
+    }
+
+    // 丸めた角や実線以外の線種があるときはまとめて描く命令にする
+    fn paint_border(
+        &mut self,
+        rect: Rect,
+        border: Sides<f64>,
+        radii: Corners<(f64, f64)>,
+        layout_box: &LayoutBox,
+    ) {
+        let style = layout_box.style();
+        let colors = style.border_color;
+        let styles = style.border_style;
+        let widths = [border.top, border.right, border.bottom, border.left];
+        if widths.iter().all(|w| *w <= 0.0) || !rect.intersects(&self.viewport) {
+            return;
+        }
+        let solid = [styles.top, styles.right, styles.bottom, styles.left]
+            .iter()
+            .zip(widths)
+            .all(|(style, width)| *style == BorderStyle::Solid || width <= 0.0);
+        if !solid || radii != Corners::all((0.0, 0.0)) {
+            self.list.push(DisplayItem::DrawBorder {
+                rect,
+                widths: border,
+                colors,
+                styles,
+                radii,
+            });
+            return;
+        }
+        let middle = rect.height - border.top - border.bottom;
+        let sides = [
+            (
+                Rect::new(rect.x, rect.y, rect.width, border.top),
+                colors.top,
+            ),
+            (
+                Rect::new(
+                    rect.right() - border.right,
+                    rect.y + border.top,
+                    border.right,
+                    middle,
+                ),
+                colors.right,
+            ),
+            (
+                Rect::new(
+                    rect.x,
+                    rect.bottom() - border.bottom,
+                    rect.width,
+                    border.bottom,
+                ),
+                colors.bottom,
+            ),
+            (
+                Rect::new(rect.x, rect.y + border.top, border.left, middle),
+                colors.left,
+            ),
+        ];
+        for (side, color) in sides {
+            if !side.is_empty() {
+                self.fill_rect(side, color);
+            }
+        }
+    }
+
+    // 置換要素の内容。内容領域からはみ出す部分は切り取る
+    fn paint_replaced(&mut self, layout_box: &'a LayoutBox, state: &PaintState) {
+        if layout_box.style().visibility != Visibility::Visible {
+            return;
+        }
+        let (Some(image), Some(mut rect)) = (
+            layout_box.replaced().and_then(|r| r.image()),
+            layout_box.object_rect(),
+        ) else {
+            return;
+        };
+        rect.translate(state.dx, state.dy);
+        let mut content = layout_box.dimensions().content;
+        content.translate(state.dx, state.dy);
+        if rect.is_empty() || !rect.intersects(&content) || !rect.intersects(&self.viewport) {
+            return;
+        }
+        let overflows = rect.x < content.x
+            || rect.y < content.y
+            || rect.right() > content.right()
+            || rect.bottom() > content.bottom();
+        let clip = match state.clip {
+            Some(clip) if overflows => Some(clip.intersection(&content)),
+            None if overflows => Some(content),
+            clip => clip,
+        };
+        self.set_clip(clip);
+        self.list.push(DisplayItem::DrawImage { rect, image });
+    }
+
+    fn paint_text(
+        &mut self,
+        layout_box: &'a LayoutBox,
+        state: &PaintState,
+        decoration: TextDecoration,
+    ) {
+        let style = layout_box.style();
+        if style.visibility != Visibility::Visible {
+            return;
+        }
+        self.set_clip(state.clip);
+        let ascent = self.font_metrics.ascent(style);
+        // 線の太さはフォントサイズに比例させる
+        let thickness = (style.font_size / 16.0).max(1.0);
+        for fragment in layout_box.fragments() {
+            let mut rect = fragment.rect;
+            rect.translate(state.dx, state.dy);
+            if fragment.text.is_empty() {
+                continue;
+            }
+            // ビューポートの外の断片も、スクロールせずに当たり判定できるよう領域には加える
+            self.regions.push(HitRegion {
+                layout_box,
+                rect,
+                clip: state.clip,
+                fragment: Some(fragment),
+            });
+            if !rect.intersects(&self.viewport) {
+                continue;
+            }
+            let baseline = rect.y + ascent;
+            self.list.push(DisplayItem::DrawText {
+                x: rect.x,
+                baseline,
+                text: fragment.text.clone(),
+                style: TextStyle::new(style),
+            });
+            let lines = [
+                (decoration.underline, baseline + thickness),
+                (decoration.overline, rect.y),
+                (
+                    decoration.line_through,
+                    rect.y + rect.height / 2.0 - thickness / 2.0,
+                ),
+            ];
+            for (_, y) in lines.iter().filter(|(enabled, _)| *enabled) {
+                self.fill_rect(Rect::new(rect.x, *y, rect.width, thickness), style.color);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::{
+        dom::api::children,
+        image::{
+            bitmap::{Bitmap, Image},
+            source::ImageState,
+        },
+        layout::{layout_document, overflow::ScrollOffset},
+    };
+    use alloc::{
+        format,
+        string::{String, ToString},
+        vec::Vec,
+    };
+
+    // 塗りつぶしの色と描いた文字列を描画順に並べる
+    fn summary(list: &DisplayList) -> Vec<String> {
+        list.items()
+            .iter()
+            .map(|item| match item {
+                DisplayItem::FillRect { rect, color } => format!(
+                    "rect {},{} {}x{} #{:02x}{:02x}{:02x}",
+                    rect.x, rect.y, rect.width, rect.height, color.r, color.g, color.b
+                ),
+                DisplayItem::FillRoundedRect { rect, radii, color } => format!(
+                    "rounded {},{} {}x{} #{:02x}{:02x}{:02x} {:?} {:?}",
+                    rect.x,
+                    rect.y,
+                    rect.width,
+                    rect.height,
+                    color.r,
+                    color.g,
+                    color.b,
+                    radii.top_left,
+                    radii.top_right
+                ),
+                DisplayItem::FillGradient { rect, gradient, .. } => match gradient {
+                    GradientPaint::Linear { start, end, stops } => format!(
+                        "linear {},{} {}x{} {:.1},{:.1}-{:.1},{:.1} {}",
+                        rect.x,
+                        rect.y,
+                        rect.width,
+                        rect.height,
+                        start.0,
+                        start.1,
+                        end.0,
+                        end.1,
+                        stops.len()
+                    ),
+                    GradientPaint::Radial {
+                        center,
+                        radius,
+                        stops,
+                    } => format!(
+                        "radial {},{} {}x{} {:?} {:?} {}",
+                        rect.x,
+                        rect.y,
+                        rect.width,
+                        rect.height,
+                        center,
+                        radius,
+                        stops.len()
+                    ),
+                },
+                DisplayItem::DrawBorder {
+                    rect,
+                    widths,
+                    styles,
+                    ..
+                } => format!(
+                    "border {},{} {}x{} {} {:?}",
+                    rect.x, rect.y, rect.width, rect.height, widths.top, styles.top
+                ),
+                DisplayItem::DrawText {
+                    x, baseline, text, ..
+                } => {
+                    format!("text {},{} {}", x, baseline, text)
+                }
+                DisplayItem::DrawImage { rect, image } => format!(
+                    "image {},{} {}x{} {}x{}",
+                    rect.x,
+                    rect.y,
+                    rect.width,
+                    rect.height,
+                    image.width(),
+                    image.height()
+                ),
+                DisplayItem::PushClip { rect } => {
+                    format!("clip {},{} {}x{}", rect.x, rect.y, rect.width, rect.height)
+                }
+                DisplayItem::PopClip => "pop clip".to_string(),
+                DisplayItem::PushOpacity { opacity } => format!("opacity {}", opacity),
+                DisplayItem::PopOpacity => "pop opacity".to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_painting_order() {
+        let (_, view) = layout_document(
+            "<html><body><p class=\"a\">a<a class=\"float\">f</a></p><p class=\"pos\">p</p><p class=\"neg\">n</p></body></html>",
+            "body, p { margin: 0 } body { background-color: #0000ff } p { font-size: 10px; line-height: 20px } .a { background-color: #ff0000; border-bottom: 2px solid #00ff00; text-decoration: underline } .float { float: right; background-color: #ffff00 } .pos { position: relative; left: 10px; background-color: #00ffff } .neg { position: absolute; top: 0; z-index: -1; background-color: #ff00ff }",
+        );
+        assert_eq!(
+            vec![
+                // bodyの背景はキャンバスに描く
+                "rect 0,0 600x400 #0000ff",
+                "rect 0,0 5x20 #ff00ff",
+                "text 0,13 n",
+                "rect 0,0 600x22 #ff0000",
+                "rect 0,20 600x2 #00ff00",
+                "rect 595,0 5x20 #ffff00",
+                // フロートには下線が伝わらない
+                "text 595,13 f",
+                "text 0,13 a",
+                "rect 0,14 5x1 #000000",
+                "rect 10,22 600x20 #00ffff",
+                "text 10,35 p",
+            ],
+            summary(&build_display_list(&view))
+        );
+    }
+
+    #[test]
+    fn test_clip_scroll_and_opacity() {
+        let (document, mut view) = layout_document(
+            "<html><body><p class=\"scroller\"><a class=\"tall\">a</a><a>b</a></p><p class=\"fixed\">f</p></body></html>",
+            "body, p { margin: 0 } p { font-size: 10px; line-height: 20px } a { display: block } .scroller { height: 30px; overflow: auto } .tall { height: 40px } .fixed { position: fixed; top: 0; right: 0; opacity: 0.5; background-color: #ff0000; width: 10px }",
+        );
+        let html = document.borrow().first_child().unwrap();
+        let body = html.borrow().last_child().upgrade().unwrap();
+        let scroller = body.borrow().first_child().unwrap();
+        view.scroll_element_to(&scroller, &ScrollOffset::new(0.0, 25.0));
+        assert_eq!(
+            vec![
+                // スクロールして見えなくなった文字列は描かない
+                "clip 0,0 600x30",
+                "text 0,28 b",
+                "pop clip",
+                "opacity 0.5",
+                "rect 590,0 10x20 #ff0000",
+                "text 590,13 f",
+                "pop opacity",
+            ],
+            summary(&build_display_list(&view))
+        );
+    }
+
+    #[test]
+    fn test_replaced_image() {
+        let (document, _) = layout_document(
+            "<html><body><p><img class=\"cover\"><img></p></body></html>",
+            "body, p { margin: 0 } img { display: block; width: 10px; height: 10px } .cover { object-fit: cover }",
+        );
+        let html = document.borrow().first_child().unwrap();
+        let body = html.borrow().last_child().upgrade().unwrap();
+        let p = body.borrow().first_child().unwrap();
+        for img in children(&p) {
+            img.borrow_mut().set_image_state(Some(ImageState::Loaded {
+                image: Rc::new(Image::still(Bitmap::new(40, 20))),
+                density: 1.0,
+            }));
+        }
+        let view = LayoutView::new(&document, 600.0, 400.0);
+        assert_eq!(
+            vec![
+                // 内容領域からはみ出す部分は切り取る
+                "clip 0,0 10x10",
+                "image -5,0 20x10 40x20",
+                "pop clip",
+                "image 0,10 10x10 40x20",
+            ],
+            summary(&build_display_list(&view))
+        );
+    }
+
+    #[test]
+    fn test_border_radius_and_gradient() {
+        let (_, view) = layout_document(
+            "<html><body><p class=\"a\"></p><p class=\"b\"></p></body></html>",
+            "body, p { margin: 0 } p { width: 100px; height: 40px } .a { border: 2px dashed #000000; border-radius: 10px 50%; background: linear-gradient(to right, red, blue) #00ff00 } .b { border-radius: 80px / 10px; background-image: radial-gradient(circle closest-side at 20% 50%, red, blue 20%, blue) }",
+        );
+        assert_eq!(
+            vec![
+                "rounded 0,0 104x44 #00ff00 (10.0, 10.0) (52.0, 22.0)",
+                // 線の位置はパディングボックスを基準にする
+                "linear 0,0 104x44 2.0,22.0-102.0,22.0 2",
+                "border 0,0 104x44 2 Dashed",
+                // 隣り合う角の半径の和が辺を超えるので縮める
+                "radial 0,44 100x40 (20.0, 64.0) (20.0, 20.0) 3",
+            ],
+            summary(&build_display_list(&view))
+        );
+    }
+}
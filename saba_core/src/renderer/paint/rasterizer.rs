@@ -0,0 +1,783 @@
+use crate::{
+    renderer::{
+        css::{
+            computed_style::{BorderStyle, Corners, Sides},
+            value::Color,
+        },
+        font::{font_system::FontSystem, glyph::Glyph},
+        image::bitmap::Bitmap,
+        layout::{font_metrics::FontMetrics, geometry::Rect},
+        paint::display_list::{DisplayItem, DisplayList, GradientPaint, TextStyle},
+    },
+    utils::{ceil, floor, sqrt},
+};
+use alloc::{vec, vec::Vec};
+
+// 1画素あたりの縦横の標本数。丸い角やグラデーションの境界を滑らかにする
+const SAMPLES: usize = 4;
+
+// 形を作って描く文字の大きさの上限。これより大きい文字は形を作るのに時間がかかりすぎるので描かない
+const MAX_GLYPH_SIZE: f64 = 4096.0;
+
+// 画素をRGBAの順に1バイトずつ並べた画像。色は乗算済みでない値を持つ
+#[derive(Clone, Debug, PartialEq)]
+pub struct Framebuffer {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Framebuffer {
+    // 透明な画素で埋めた画像を作る
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width * height * 4],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> Color {
+        let i = (y * self.width + x) * 4;
+        Color::rgba(
+            self.pixels[i],
+            self.pixels[i + 1],
+            self.pixels[i + 2],
+            self.pixels[i + 3],
+        )
+    }
+
+    pub fn clear(&mut self, color: Color) {
+        for pixel in self.pixels.chunks_exact_mut(4) {
+            pixel.copy_from_slice(&[color.r, color.g, color.b, color.a]);
+        }
+    }
+
+    // colorを画素のcoverageの割合だけ覆うように重ねる。Compositing 1 source-over
+    pub fn blend(&mut self, x: usize, y: usize, color: Color, coverage: f64) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let source_alpha = color.a as f64 / 255.0 * coverage.clamp(0.0, 1.0);
+        if source_alpha <= 0.0 {
+            return;
+        }
+        let i = (y * self.width + x) * 4;
+        let backdrop_alpha = self.pixels[i + 3] as f64 / 255.0;
+        let alpha = source_alpha + backdrop_alpha * (1.0 - source_alpha);
+        let mix = |source: u8, backdrop: u8| {
+            let value = (source as f64 * source_alpha
+                + backdrop as f64 * backdrop_alpha * (1.0 - source_alpha))
+                / alpha;
+            (value + 0.5) as u8
+        };
+        self.pixels[i] = mix(color.r, self.pixels[i]);
+        self.pixels[i + 1] = mix(color.g, self.pixels[i + 1]);
+        self.pixels[i + 2] = mix(color.b, self.pixels[i + 2]);
+        self.pixels[i + 3] = (alpha * 255.0 + 0.5) as u8;
+    }
+}
+
+// 描画命令を順に実行して画像に描く。文字はfontsから選んだフォントで描く
+pub fn rasterize(list: &DisplayList, fonts: &FontSystem, target: &mut Framebuffer) {
+    let bounds = Rect::new(0.0, 0.0, target.width() as f64, target.height() as f64);
+    let mut rasterizer = Rasterizer {
+        fonts,
+        target,
+        layers: Vec::new(),
+        clips: vec![bounds],
+    };
+    for item in list.items() {
+        rasterizer.execute(item);
+    }
+    // 閉じられていない層も合成しておく
+    while !rasterizer.layers.is_empty() {
+        rasterizer.pop_layer();
+    }
+}
+
+struct Rasterizer<'a> {
+    fonts: &'a FontSystem,
+    target: &'a mut Framebuffer,
+    // PushOpacityで積んだ透明な層と合成するときの不透明度
+    layers: Vec<(Framebuffer, f64)>,
+    // 外側の範囲と重ねた描画できる範囲
+    clips: Vec<Rect>,
+}
+
+// 乗算済みの色の成分。標本を平均するときに使う
+#[derive(Clone, Copy, Default)]
+struct Premultiplied {
+    r: f64,
+    g: f64,
+    b: f64,
+    a: f64,
+}
+
+impl Premultiplied {
+    fn new(color: Color) -> Self {
+        let a = color.a as f64 / 255.0;
+        Self {
+            r: color.r as f64 * a,
+            g: color.g as f64 * a,
+            b: color.b as f64 * a,
+            a,
+        }
+    }
+
+    fn add(&mut self, other: &Premultiplied, weight: f64) {
+        self.r += other.r * weight;
+        self.g += other.g * weight;
+        self.b += other.b * weight;
+        self.a += other.a * weight;
+    }
+
+    // 乗算済みでない色と不透明度に戻す
+    fn unpremultiply(&self) -> (Color, f64) {
+        if self.a <= 0.0 {
+            return (Color::TRANSPARENT, 0.0);
+        }
+        let channel = |value: f64| (value / self.a + 0.5).clamp(0.0, 255.0) as u8;
+        (
+            Color::rgb(channel(self.r), channel(self.g), channel(self.b)),
+            self.a,
+        )
+    }
+}
+
+// 角ごとの楕円で丸めた矩形に点が含まれるか
+fn in_rounded_rect(rect: &Rect, radii: &Corners<(f64, f64)>, x: f64, y: f64) -> bool {
+    if !rect.contains(x, y) {
+        return false;
+    }
+    let corners = [
+        (radii.top_left, rect.x, rect.y, 1.0, 1.0),
+        (radii.top_right, rect.right(), rect.y, -1.0, 1.0),
+        (radii.bottom_right, rect.right(), rect.bottom(), -1.0, -1.0),
+        (radii.bottom_left, rect.x, rect.bottom(), 1.0, -1.0),
+    ];
+    corners.iter().all(|((rx, ry), cx, cy, sx, sy)| {
+        if *rx <= 0.0 || *ry <= 0.0 {
+            return true;
+        }
+        // 楕円の中心から角の側にある点だけを調べる
+        let dx = (x - (cx + sx * rx)) / rx;
+        let dy = (y - (cy + sy * ry)) / ry;
+        dx * sx > 0.0 || dy * sy > 0.0 || dx * dx + dy * dy <= 1.0
+    })
+}
+
+// 位置tでの色。点の間は乗算済みの値で補間する。CSS Images 4 3.5.4
+fn gradient_color(stops: &[(f64, Color)], t: f64) -> Premultiplied {
+    let (first, last) = match (stops.first(), stops.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return Premultiplied::default(),
+    };
+    if t <= first.0 {
+        return Premultiplied::new(first.1);
+    }
+    for pair in stops.windows(2) {
+        let ((p0, c0), (p1, c1)) = (pair[0], pair[1]);
+        if t < p1 {
+            if p1 <= p0 {
+                return Premultiplied::new(c1);
+            }
+            let w = (t - p0) / (p1 - p0);
+            let mut color = Premultiplied::default();
+            color.add(&Premultiplied::new(c0), 1.0 - w);
+            color.add(&Premultiplied::new(c1), w);
+            return color;
+        }
+    }
+    Premultiplied::new(last.1)
+}
+
+// 画素の中心を整数の座標とした点(u, v)の色。範囲外は端の画素を伸ばす
+fn sample_bitmap(bitmap: &Bitmap, u: f64, v: f64) -> Premultiplied {
+    let u = u.clamp(0.0, (bitmap.width() - 1) as f64);
+    let v = v.clamp(0.0, (bitmap.height() - 1) as f64);
+    let (left, top) = (floor(u) as usize, floor(v) as usize);
+    let right = (left + 1).min(bitmap.width() - 1);
+    let bottom = (top + 1).min(bitmap.height() - 1);
+    let (fx, fy) = (u - left as f64, v - top as f64);
+    let mut color = Premultiplied::default();
+    for (x, y, weight) in [
+        (left, top, (1.0 - fx) * (1.0 - fy)),
+        (right, top, fx * (1.0 - fy)),
+        (left, bottom, (1.0 - fx) * fy),
+        (right, bottom, fx * fy),
+    ] {
+        color.add(&Premultiplied::new(bitmap.pixel(x, y)), weight);
+    }
+    color
+}
+
+fn shade(color: Color, dark: bool) -> Color {
+    if !dark {
+        return color;
+    }
+    let darken = |value: u8| (value as f64 * 0.6) as u8;
+    Color::rgba(darken(color.r), darken(color.g), darken(color.b), color.a)
+}
+
+// ボーダーの点(x, y)の色。辺の太さに対して最も近い辺の線種で決める。CSS Backgrounds 3 4.2
+fn border_color(
+    rect: &Rect,
+    widths: &Sides<f64>,
+    colors: &Sides<Color>,
+    styles: &Sides<BorderStyle>,
+    x: f64,
+    y: f64,
+) -> Option<Color> {
+    let sides = [
+        (y - rect.y, widths.top, colors.top, styles.top, x - rect.x),
+        (
+            rect.right() - x,
+            widths.right,
+            colors.right,
+            styles.right,
+            y - rect.y,
+        ),
+        (
+            rect.bottom() - y,
+            widths.bottom,
+            colors.bottom,
+            styles.bottom,
+            x - rect.x,
+        ),
+        (
+            x - rect.x,
+            widths.left,
+            colors.left,
+            styles.left,
+            y - rect.y,
+        ),
+    ];
+    let (i, (distance, width, color, style, along)) = sides
+        .iter()
+        .enumerate()
+        .filter(|(_, side)| side.1 > 0.0)
+        .min_by(|(_, a), (_, b)| (a.0 / a.1).total_cmp(&(b.0 / b.1)))?;
+    // 上と左の辺は影になる側
+    let top_left = i == 0 || i == 3;
+    let depth = distance / width;
+    match style {
+        BorderStyle::None | BorderStyle::Hidden => None,
+        BorderStyle::Solid => Some(*color),
+        BorderStyle::Dashed | BorderStyle::Dotted => {
+            let length = if *style == BorderStyle::Dashed {
+                3.0
+            } else {
+                1.0
+            };
+            let position = along / width / length;
+            if (floor(position) as i64) % 2 == 0 {
+                Some(*color)
+            } else {
+                None
+            }
+        }
+        BorderStyle::Double => {
+            if !(1.0 / 3.0..2.0 / 3.0).contains(&depth) {
+                Some(*color)
+            } else {
+                None
+            }
+        }
+        BorderStyle::Inset => Some(shade(*color, top_left)),
+        BorderStyle::Outset => Some(shade(*color, !top_left)),
+        BorderStyle::Groove => Some(shade(*color, top_left == (depth < 0.5))),
+        BorderStyle::Ridge => Some(shade(*color, top_left != (depth < 0.5))),
+    }
+}
+
+impl Rasterizer<'_> {
+    fn execute(&mut self, item: &DisplayItem) {
+        match item {
+            DisplayItem::FillRect { rect, color } => self.fill_rect(rect, *color),
+            DisplayItem::FillRoundedRect { rect, radii, color } => {
+                let color = Premultiplied::new(*color);
+                self.fill_samples(rect, |x, y| {
+                    in_rounded_rect(rect, radii, x, y).then_some(color)
+                });
+            }
+            DisplayItem::FillGradient {
+                rect,
+                radii,
+                gradient,
+            } => self.fill_gradient(rect, radii, gradient),
+            DisplayItem::DrawBorder {
+                rect,
+                widths,
+                colors,
+                styles,
+                radii,
+            } => {
+                let inner = Rect::new(
+                    rect.x + widths.left,
+                    rect.y + widths.top,
+                    rect.width - widths.left - widths.right,
+                    rect.height - widths.top - widths.bottom,
+                );
+                // 内側の角の半径は外側の半径からボーダーの太さを引いたもの
+                let shrink =
+                    |(rx, ry): (f64, f64), w: f64, h: f64| ((rx - w).max(0.0), (ry - h).max(0.0));
+                let inner_radii = Corners {
+                    top_left: shrink(radii.top_left, widths.left, widths.top),
+                    top_right: shrink(radii.top_right, widths.right, widths.top),
+                    bottom_right: shrink(radii.bottom_right, widths.right, widths.bottom),
+                    bottom_left: shrink(radii.bottom_left, widths.left, widths.bottom),
+                };
+                self.fill_samples(rect, |x, y| {
+                    if !in_rounded_rect(rect, radii, x, y)
+                        || in_rounded_rect(&inner, &inner_radii, x, y)
+                    {
+                        return None;
+                    }
+                    border_color(rect, widths, colors, styles, x, y).map(Premultiplied::new)
+                });
+            }
+            DisplayItem::DrawText {
+                x,
+                baseline,
+                text,
+                style,
+            } => self.draw_text(*x, *baseline, text, style),
+            DisplayItem::DrawImage { rect, image } => self.draw_image(rect, image.bitmap()),
+            DisplayItem::PushClip { rect } => {
+                let clip = self.clip().intersection(rect);
+                self.clips.push(clip);
+            }
+            DisplayItem::PopClip => {
+                if self.clips.len() > 1 {
+                    self.clips.pop();
+                }
+            }
+            DisplayItem::PushOpacity { opacity } => {
+                let layer = Framebuffer::new(self.target.width(), self.target.height());
+                self.layers.push((layer, *opacity));
+            }
+            DisplayItem::PopOpacity => self.pop_layer(),
+        }
+    }
+
+    fn clip(&self) -> Rect {
+        self.clips.last().copied().unwrap_or_default()
+    }
+
+    fn current(&mut self) -> &mut Framebuffer {
+        match self.layers.last_mut() {
+            Some((layer, _)) => layer,
+            None => self.target,
+        }
+    }
+
+    // 一番上の層を不透明度をかけて下の層に合成する
+    fn pop_layer(&mut self) {
+        let (layer, opacity) = match self.layers.pop() {
+            Some(layer) => layer,
+            None => return,
+        };
+        let target = self.current();
+        for y in 0..layer.height() {
+            for x in 0..layer.width() {
+                let color = layer.pixel(x, y);
+                if color.a > 0 {
+                    target.blend(x, y, color, opacity);
+                }
+            }
+        }
+    }
+
+    // rectと描画範囲が重なる画素の範囲
+    fn pixel_range(&self, rect: &Rect) -> Option<(usize, usize, usize, usize)> {
+        let area = self.clip().intersection(rect);
+        if area.is_empty() {
+            return None;
+        }
+        Some((
+            floor(area.x) as usize,
+            floor(area.y) as usize,
+            ceil(area.right()) as usize,
+            ceil(area.bottom()) as usize,
+        ))
+    }
+
+    // 文字の形は画素の境界に揃えて置く
+    fn draw_text(&mut self, x: f64, baseline: f64, text: &str, style: &TextStyle) {
+        let fonts = self.fonts;
+        let computed = style.to_computed_style();
+        let size = computed.font_size;
+        if size.is_nan() || size > MAX_GLYPH_SIZE {
+            return;
+        }
+        // 太字の形がないため、1画素ずらして重ねて太く見せる
+        let bold = style.font_weight >= 600;
+        let baseline = floor(baseline + 0.5);
+        let clip = self.clip();
+        let mut pen = x;
+        let mut previous = None;
+        for c in text.chars() {
+            if let Some(previous) = previous {
+                pen += fonts.kerning(previous, c, &computed);
+            }
+            let advance = fonts.advance(c, &computed);
+            // 描画範囲にかからない文字は形を作らずに飛ばす。整数の座標にするとあふれることもある。
+            // 文字の形は送り幅の外に文字の大きさほどはみ出すことがあり、太字はさらに1画素ずれる
+            let extent = Rect::new(
+                pen - size,
+                baseline - 2.0 * size,
+                advance + 2.0 * size + 1.0,
+                3.0 * size,
+            );
+            let visible = pen.is_finite() && baseline.is_finite() && clip.intersects(&extent);
+            if let Some(glyph) = visible.then(|| fonts.glyph(c, &computed)).flatten() {
+                let left = floor(pen + 0.5) as i64 + glyph.left as i64;
+                let top = baseline as i64 - glyph.top as i64;
+                self.draw_glyph(&glyph, left, top, style.color);
+                if bold {
+                    self.draw_glyph(&glyph, left + 1, top, style.color);
+                }
+            }
+            pen += advance;
+            previous = Some(c);
+        }
+    }
+
+    fn draw_glyph(&mut self, glyph: &Glyph, left: i64, top: i64, color: Color) {
+        let clip = self.clip();
+        let target = self.current();
+        for y in 0..glyph.height {
+            for x in 0..glyph.width {
+                let coverage = glyph.coverage_at(x, y);
+                let (px, py) = (left + x as i64, top + y as i64);
+                if coverage == 0 || px < 0 || py < 0 {
+                    continue;
+                }
+                let pixel = Rect::new(px as f64, py as f64, 1.0, 1.0).intersection(&clip);
+                let coverage = coverage as f64 / 255.0 * pixel.width * pixel.height;
+                target.blend(px as usize, py as usize, color, coverage);
+            }
+        }
+    }
+
+    // 矩形は画素と重なる面積をそのまま覆う割合にする
+    fn fill_rect(&mut self, rect: &Rect, color: Color) {
+        let (x0, y0, x1, y1) = match self.pixel_range(rect) {
+            Some(range) => range,
+            None => return,
+        };
+        let area = self.clip().intersection(rect);
+        let target = self.current();
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let pixel = Rect::new(x as f64, y as f64, 1.0, 1.0).intersection(&area);
+                target.blend(x, y, color, pixel.width * pixel.height);
+            }
+        }
+    }
+
+    // 画素の中心に対応する画像上の点の色を周りの4画素から補間する
+    fn draw_image(&mut self, rect: &Rect, bitmap: &Bitmap) {
+        let (x0, y0, x1, y1) = match self.pixel_range(rect) {
+            Some(range) => range,
+            None => return,
+        };
+        if bitmap.width() == 0 || bitmap.height() == 0 {
+            return;
+        }
+        let area = self.clip().intersection(rect);
+        let scale_x = bitmap.width() as f64 / rect.width;
+        let scale_y = bitmap.height() as f64 / rect.height;
+        let target = self.current();
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let pixel = Rect::new(x as f64, y as f64, 1.0, 1.0).intersection(&area);
+                let u = (x as f64 + 0.5 - rect.x) * scale_x - 0.5;
+                let v = (y as f64 + 0.5 - rect.y) * scale_y - 0.5;
+                let (color, alpha) = sample_bitmap(bitmap, u, v).unpremultiply();
+                target.blend(x, y, color, alpha * pixel.width * pixel.height);
+            }
+        }
+    }
+
+    // 画素ごとに標本を取って平均した色で塗る。sampleは点の色を返す
+    fn fill_samples(&mut self, rect: &Rect, sample: impl Fn(f64, f64) -> Option<Premultiplied>) {
+        let (x0, y0, x1, y1) = match self.pixel_range(rect) {
+            Some(range) => range,
+            None => return,
+        };
+        let clip = self.clip();
+        let weight = 1.0 / (SAMPLES * SAMPLES) as f64;
+        let target = self.current();
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let mut sum = Premultiplied::default();
+                for j in 0..SAMPLES {
+                    for i in 0..SAMPLES {
+                        let sx = x as f64 + (i as f64 + 0.5) / SAMPLES as f64;
+                        let sy = y as f64 + (j as f64 + 0.5) / SAMPLES as f64;
+                        if !clip.contains(sx, sy) {
+                            continue;
+                        }
+                        if let Some(color) = sample(sx, sy) {
+                            sum.add(&color, weight);
+                        }
+                    }
+                }
+                let (color, coverage) = sum.unpremultiply();
+                target.blend(x, y, color, coverage);
+            }
+        }
+    }
+
+    fn fill_gradient(
+        &mut self,
+        rect: &Rect,
+        radii: &Corners<(f64, f64)>,
+        gradient: &GradientPaint,
+    ) {
+        match gradient {
+            GradientPaint::Linear { start, end, stops } => {
+                let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+                let length = dx * dx + dy * dy;
+                self.fill_samples(rect, |x, y| {
+                    if !in_rounded_rect(rect, radii, x, y) {
+                        return None;
+                    }
+                    // 線の上に点を射影した位置
+                    let t = if length > 0.0 {
+                        ((x - start.0) * dx + (y - start.1) * dy) / length
+                    } else {
+                        0.0
+                    };
+                    Some(gradient_color(stops, t))
+                });
+            }
+            GradientPaint::Radial {
+                center,
+                radius,
+                stops,
+            } => {
+                self.fill_samples(rect, |x, y| {
+                    if !in_rounded_rect(rect, radii, x, y) {
+                        return None;
+                    }
+                    // 大きさのない楕円は最後の色で塗る
+                    let t = if radius.0 > 0.0 && radius.1 > 0.0 {
+                        let dx = (x - center.0) / radius.0;
+                        let dy = (y - center.1) / radius.1;
+                        sqrt(dx * dx + dy * dy)
+                    } else {
+                        f64::MAX
+                    };
+                    Some(gradient_color(stops, t))
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::image::bitmap::Image;
+    use alloc::{rc::Rc, string::ToString};
+
+    fn render(items: Vec<DisplayItem>) -> Framebuffer {
+        let mut list = DisplayList::new();
+        for item in items {
+            list.push(item);
+        }
+        let mut framebuffer = Framebuffer::new(20, 20);
+        framebuffer.clear(Color::WHITE);
+        rasterize(&list, &FontSystem::default(), &mut framebuffer);
+        framebuffer
+    }
+
+    #[test]
+    fn test_fill_and_clip() {
+        let red = Color::rgb(255, 0, 0);
+        let framebuffer = render(vec![
+            DisplayItem::PushClip {
+                rect: Rect::new(0.0, 0.0, 10.0, 20.0),
+            },
+            DisplayItem::FillRect {
+                rect: Rect::new(2.5, 2.0, 20.0, 4.0),
+                color: red,
+            },
+            DisplayItem::PopClip,
+            DisplayItem::FillRect {
+                rect: Rect::new(0.0, 10.0, 20.0, 2.0),
+                color: Color::rgba(0, 0, 255, 128),
+            },
+        ]);
+        assert_eq!(red, framebuffer.pixel(5, 3));
+        // 半分だけ覆われた画素は背景と混ざる
+        assert_eq!(Color::rgb(255, 128, 128), framebuffer.pixel(2, 3));
+        assert_eq!(Color::WHITE, framebuffer.pixel(10, 3));
+        assert_eq!(Color::rgb(127, 127, 255), framebuffer.pixel(15, 10));
+        assert_eq!(20 * 20 * 4, framebuffer.pixels().len());
+    }
+
+    #[test]
+    fn test_rounded_rect_and_border() {
+        let black = Color::BLACK;
+        let round = Corners::all((10.0, 10.0));
+        let framebuffer = render(vec![
+            DisplayItem::FillRoundedRect {
+                rect: Rect::new(0.0, 0.0, 20.0, 20.0),
+                radii: round,
+                color: black,
+            },
+            DisplayItem::DrawBorder {
+                rect: Rect::new(0.0, 0.0, 20.0, 20.0),
+                widths: Sides::all(2.0),
+                colors: Sides::all(Color::rgb(0, 255, 0)),
+                styles: Sides {
+                    top: BorderStyle::Dashed,
+                    right: BorderStyle::Solid,
+                    bottom: BorderStyle::Solid,
+                    left: BorderStyle::Solid,
+                },
+                radii: Corners::all((0.0, 0.0)),
+            },
+        ]);
+        // 丸めた角の外側は塗られない
+        assert_eq!(Color::WHITE, framebuffer.pixel(2, 17));
+        assert_eq!(black, framebuffer.pixel(10, 10));
+        let corner = framebuffer.pixel(3, 2);
+        assert!(corner != black && corner != Color::WHITE);
+        // 破線は太さの3倍ごとに途切れる
+        assert_eq!(Color::rgb(0, 255, 0), framebuffer.pixel(13, 0));
+        assert_ne!(Color::rgb(0, 255, 0), framebuffer.pixel(7, 0));
+        assert_eq!(Color::rgb(0, 255, 0), framebuffer.pixel(19, 9));
+    }
+
+    #[test]
+    fn test_gradient_and_opacity() {
+        let framebuffer = render(vec![
+            DisplayItem::PushOpacity { opacity: 0.5 },
+            DisplayItem::FillGradient {
+                rect: Rect::new(0.0, 0.0, 20.0, 10.0),
+                radii: Corners::all((0.0, 0.0)),
+                gradient: GradientPaint::Linear {
+                    start: (0.0, 0.0),
+                    end: (20.0, 0.0),
+                    stops: vec![(0.0, Color::BLACK), (1.0, Color::rgb(0, 0, 255))],
+                },
+            },
+            DisplayItem::FillRect {
+                rect: Rect::new(0.0, 0.0, 1.0, 1.0),
+                color: Color::BLACK,
+            },
+            DisplayItem::PopOpacity,
+            DisplayItem::FillGradient {
+                rect: Rect::new(0.0, 10.0, 20.0, 10.0),
+                radii: Corners::all((0.0, 0.0)),
+                gradient: GradientPaint::Radial {
+                    center: (10.0, 15.0),
+                    radius: (5.0, 5.0),
+                    stops: vec![(0.0, Color::rgb(255, 0, 0)), (1.0, Color::BLACK)],
+                },
+            },
+        ]);
+        // 層の中で重ねてから半分の不透明度で合成する
+        assert_eq!(Color::rgb(128, 128, 128), framebuffer.pixel(0, 0));
+        assert_eq!(Color::rgb(128, 128, 252), framebuffer.pixel(19, 5));
+        assert_eq!(Color::rgb(128, 128, 195), framebuffer.pixel(10, 5));
+        let center = framebuffer.pixel(10, 15);
+        assert!(center.r > 200 && center.g == 0);
+        assert_eq!(Color::BLACK, framebuffer.pixel(0, 19));
+    }
+
+    #[test]
+    fn test_draw_text() {
+        let mut style = TextStyle::new(&Default::default());
+        style.font_size = 16.0;
+        let framebuffer = render(vec![
+            DisplayItem::PushClip {
+                rect: Rect::new(0.0, 0.0, 9.0, 20.0),
+            },
+            DisplayItem::DrawText {
+                x: 1.0,
+                baseline: 13.0,
+                text: "AA".to_string(),
+                style,
+            },
+        ]);
+        // Aの横棒はベースラインから4行上にある
+        assert!((2..8).all(|x| framebuffer.pixel(x, 9) == Color::BLACK));
+        assert_eq!(Color::WHITE, framebuffer.pixel(1, 9));
+        // 2つ目の文字は切り取られる
+        assert_eq!(Color::WHITE, framebuffer.pixel(11, 9));
+    }
+
+    #[test]
+    fn test_draw_text_far_away() {
+        // 整数の座標にするとあふれる位置の文字や、形を作れないほど大きな文字は描かずに飛ばす
+        let mut style = TextStyle::new(&Default::default());
+        style.font_size = 16.0;
+        style.font_weight = 700;
+        let mut huge = style.clone();
+        huge.font_size = f64::INFINITY;
+        let mut items = vec![DisplayItem::DrawText {
+            x: 1.0,
+            baseline: 13.0,
+            text: "AA".to_string(),
+            style: huge,
+        }];
+        for (x, baseline) in [
+            (f64::MAX, 13.0),
+            (f64::INFINITY, 13.0),
+            (f64::NAN, 13.0),
+            (1.0, f64::MAX),
+            (f64::MIN, f64::MIN),
+        ] {
+            items.push(DisplayItem::DrawText {
+                x,
+                baseline,
+                text: "AA".to_string(),
+                style: style.clone(),
+            });
+        }
+        assert_eq!(render(Vec::new()).pixels(), render(items).pixels());
+    }
+
+    #[test]
+    fn test_draw_image() {
+        let mut bitmap = Bitmap::new(2, 2);
+        bitmap.set_pixel(0, 0, Color::rgb(255, 0, 0));
+        bitmap.set_pixel(1, 0, Color::rgb(0, 0, 255));
+        bitmap.set_pixel(0, 1, Color::rgb(255, 0, 0));
+        let framebuffer = render(vec![
+            DisplayItem::PushClip {
+                rect: Rect::new(0.0, 0.0, 20.0, 7.0),
+            },
+            DisplayItem::DrawImage {
+                rect: Rect::new(0.0, 0.0, 8.0, 8.0),
+                image: Rc::new(Image::still(bitmap)),
+            },
+        ]);
+        assert_eq!(Color::rgb(255, 0, 0), framebuffer.pixel(1, 1));
+        assert_eq!(Color::rgb(0, 0, 255), framebuffer.pixel(6, 1));
+        // 画素の間は補間する
+        assert_eq!(Color::rgb(96, 0, 159), framebuffer.pixel(4, 1));
+        // 透明な画素は背景が見える
+        assert_eq!(Color::WHITE, framebuffer.pixel(7, 6));
+        assert_eq!(Color::WHITE, framebuffer.pixel(1, 7));
+        assert_eq!(Color::WHITE, framebuffer.pixel(8, 1));
+    }
+}
@@ -0,0 +1,1265 @@
+use crate::{
+    renderer::{
+        css::{
+            computed_style::{
+                ComputedStyle, Display, FontStyle, ListStyleType, TextAlign, Visibility, WhiteSpace,
+            },
+            value::Color,
+        },
+        dom::node::{ElementKind, NodeKind},
+        layout::{
+            layout_box::{BoxKind, LayoutBox},
+            layout_view::LayoutView,
+            line_break::{break_opportunities, BreakOpportunity},
+        },
+    },
+    utils::{ceil, floor},
+};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+// 全角文字の右半分に置くセルの文字
+pub const WIDE_TAIL: char = '\0';
+
+// 罫線がどの方向に伸びるか
+const UP: u8 = 1;
+const DOWN: u8 = 2;
+const LEFT: u8 = 4;
+const RIGHT: u8 = 8;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CellStyle {
+    // 文書の既定の色と同じ場合はNoneにして端末の色を使う
+    pub foreground: Option<Color>,
+    pub background: Option<Color>,
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub line_through: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cell {
+    pub ch: char,
+    pub style: CellStyle,
+}
+
+impl Cell {
+    const BLANK: Cell = Cell {
+        ch: ' ',
+        style: CellStyle {
+            foreground: None,
+            background: None,
+            bold: false,
+            italic: false,
+            underline: false,
+            line_through: false,
+        },
+    };
+}
+
+// 端末に表示する文字セルの格子と、リンクの参照先の一覧
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextPage {
+    columns: usize,
+    lines: Vec<Vec<Cell>>,
+    links: Vec<String>,
+}
+
+impl TextPage {
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn lines(&self) -> &[Vec<Cell>] {
+        &self.lines
+    }
+
+    // [1]のような番号の順に並べたリンクのURL
+    pub fn links(&self) -> &[String] {
+        &self.links
+    }
+
+    // 装飾のない文字列。行末の空白は除く
+    pub fn to_plain_text(&self) -> String {
+        let mut text = String::new();
+        for line in &self.lines {
+            let end = line.iter().rposition(|c| c.ch != ' ').map_or(0, |i| i + 1);
+            text.extend(line[..end].iter().map(|c| c.ch).filter(|c| *c != WIDE_TAIL));
+            text.push('\n');
+        }
+        text
+    }
+
+    // 色と装飾をSGRのエスケープシーケンスで付けた文字列。色は24ビットカラーで指定する
+    pub fn to_ansi_text(&self) -> String {
+        let mut text = String::new();
+        for line in &self.lines {
+            let end = line
+                .iter()
+                .rposition(|c| c.ch != ' ' || c.style.background.is_some())
+                .map_or(0, |i| i + 1);
+            let mut current = CellStyle::default();
+            for cell in &line[..end] {
+                if cell.ch == WIDE_TAIL {
+                    continue;
+                }
+                if cell.style != current {
+                    text.push_str(&select_graphic_rendition(&cell.style));
+                    current = cell.style;
+                }
+                text.push(cell.ch);
+            }
+            if current != CellStyle::default() {
+                text.push_str("\x1b[0m");
+            }
+            text.push('\n');
+        }
+        text
+    }
+}
+
+fn select_graphic_rendition(style: &CellStyle) -> String {
+    let mut codes = vec!["0".to_string()];
+    if style.bold {
+        codes.push("1".to_string());
+    }
+    if style.italic {
+        codes.push("3".to_string());
+    }
+    if style.underline {
+        codes.push("4".to_string());
+    }
+    if style.line_through {
+        codes.push("9".to_string());
+    }
+    if let Some(color) = style.foreground {
+        codes.push(format!("38;2;{};{};{}", color.r, color.g, color.b));
+    }
+    if let Some(color) = style.background {
+        codes.push(format!("48;2;{};{};{}", color.r, color.g, color.b));
+    }
+    format!("\x1b[{}m", codes.join(";"))
+}
+
+// 端末で占める桁数。東アジアの全角文字は2桁、結合文字や制御文字は0桁とする
+fn char_columns(c: char) -> usize {
+    match c {
+        '\u{0}'..='\u{1f}' | '\u{7f}'..='\u{9f}' => 0,
+        '\u{300}'..='\u{36f}' | '\u{200b}'..='\u{200f}' | '\u{fe00}'..='\u{fe0f}' => 0,
+        '\u{1100}'..='\u{115f}'
+        | '\u{2e80}'..='\u{303e}'
+        | '\u{3041}'..='\u{33ff}'
+        | '\u{3400}'..='\u{4dbf}'
+        | '\u{4e00}'..='\u{9fff}'
+        | '\u{a000}'..='\u{a4cf}'
+        | '\u{ac00}'..='\u{d7a3}'
+        | '\u{f900}'..='\u{faff}'
+        | '\u{fe30}'..='\u{fe4f}'
+        | '\u{ff00}'..='\u{ff60}'
+        | '\u{ffe0}'..='\u{ffe6}'
+        | '\u{1f300}'..='\u{1f64f}'
+        | '\u{1f900}'..='\u{1f9ff}'
+        | '\u{20000}'..='\u{3fffd}' => 2,
+        _ => 1,
+    }
+}
+
+fn text_columns(text: &str) -> usize {
+    text.chars().map(char_columns).sum()
+}
+
+// 桁数や行数の合計。極端な長さのボックスでもあふれないよう飽和させる
+fn saturating_sum(values: &[usize]) -> usize {
+    values
+        .iter()
+        .fold(0, |sum, value| sum.saturating_add(*value))
+}
+
+// 罫線の伸びる方向から箱組み文字を選ぶ
+fn box_drawing(edges: u8) -> char {
+    match edges {
+        e if e == DOWN | RIGHT => '┌',
+        e if e == DOWN | LEFT => '┐',
+        e if e == UP | RIGHT => '└',
+        e if e == UP | LEFT => '┘',
+        e if e == LEFT | RIGHT | DOWN => '┬',
+        e if e == LEFT | RIGHT | UP => '┴',
+        e if e == UP | DOWN | RIGHT => '├',
+        e if e == UP | DOWN | LEFT => '┤',
+        e if e == UP | DOWN | LEFT | RIGHT => '┼',
+        e if e & (UP | DOWN) != 0 => '│',
+        _ => '─',
+    }
+}
+
+fn alphabetic(mut n: usize, first: u8) -> String {
+    let mut letters = Vec::new();
+    while n > 0 {
+        n -= 1;
+        letters.push((first + (n % 26) as u8) as char);
+        n /= 26;
+    }
+    letters.iter().rev().collect()
+}
+
+fn roman(mut n: usize) -> String {
+    const NUMERALS: [(usize, &str); 13] = [
+        (1000, "m"),
+        (900, "cm"),
+        (500, "d"),
+        (400, "cd"),
+        (100, "c"),
+        (90, "xc"),
+        (50, "l"),
+        (40, "xl"),
+        (10, "x"),
+        (9, "ix"),
+        (5, "v"),
+        (4, "iv"),
+        (1, "i"),
+    ];
+    let mut numeral = String::new();
+    for (value, symbol) in NUMERALS {
+        while n >= value {
+            numeral.push_str(symbol);
+            n -= value;
+        }
+    }
+    numeral
+}
+
+// リスト項目の先頭に置くマーカー。ordinalは1から数えた項目の番号
+fn list_marker(list_style_type: ListStyleType, ordinal: usize) -> Option<String> {
+    let marker = match list_style_type {
+        ListStyleType::None => return None,
+        ListStyleType::Disc => "•".to_string(),
+        ListStyleType::Circle => "◦".to_string(),
+        ListStyleType::Square => "▪".to_string(),
+        ListStyleType::Decimal => format!("{}.", ordinal),
+        ListStyleType::LowerAlpha => format!("{}.", alphabetic(ordinal, b'a')),
+        ListStyleType::UpperAlpha => format!("{}.", alphabetic(ordinal, b'A')),
+        ListStyleType::LowerRoman => format!("{}.", roman(ordinal)),
+        ListStyleType::UpperRoman => format!("{}.", roman(ordinal).to_uppercase()),
+    };
+    Some(marker)
+}
+
+// インライン整形文脈の1文字
+#[derive(Clone, Copy, Debug)]
+struct StyledChar {
+    ch: char,
+    style: CellStyle,
+    // white-spaceで連続する空白をまとめる文字
+    collapsible: bool,
+    // 直前で折り返せる文字
+    wrap: bool,
+}
+
+impl StyledChar {
+    fn is_collapsible_space(&self) -> bool {
+        self.collapsible && self.ch == ' '
+    }
+}
+
+fn push_str(content: &mut Vec<StyledChar>, text: &str, style: CellStyle) {
+    content.extend(text.chars().map(|ch| StyledChar {
+        ch,
+        style,
+        collapsible: false,
+        wrap: true,
+    }));
+}
+
+// 描いている表のセル
+struct TableCell {
+    // 占める列の境界と行の範囲
+    columns: (usize, usize),
+    rows: (usize, usize),
+    lines: Vec<Vec<Cell>>,
+}
+
+struct TextRenderer {
+    cell_width: f64,
+    default_foreground: Color,
+    canvas: Color,
+    // 描いている領域の左端のx座標と桁数
+    origin: f64,
+    columns: usize,
+    lines: Vec<Vec<Cell>>,
+    // 次の行の前に空行を入れる
+    blank_line: bool,
+    // 次に出力する行の左に置くリストマーカー
+    marker: Option<(String, CellStyle)>,
+    links: Vec<String>,
+    // ブロックレベルのリンクの番号。リンクの中の最初の段落の先頭に置く
+    link_label: Option<(String, CellStyle)>,
+    // 表の列の幅を決めるために中身の幅を測っている
+    measuring: bool,
+}
+
+// レイアウト済みのボックスを端末の文字セルにする。横方向の位置は桁に換算し、文字列は桁数に合わせて折り返す
+pub fn render_text(view: &LayoutView, columns: usize) -> TextPage {
+    let columns = columns.max(1);
+    let root = match view.root() {
+        Some(root) => root,
+        None => {
+            return TextPage {
+                columns,
+                lines: Vec::new(),
+                links: Vec::new(),
+            }
+        }
+    };
+
+    // ルートの背景が透明ならbody要素の背景をキャンバスの色とする。CSS 2.1 14.2
+    let mut canvas = root.style().background_color;
+    if canvas.a == 0 {
+        if let Some(body) = root
+            .children()
+            .iter()
+            .find(|b| is_element(b, ElementKind::Body))
+        {
+            canvas = body.style().background_color;
+        }
+    }
+    let mut renderer = TextRenderer {
+        cell_width: (view.viewport_width() / columns as f64).max(1.0),
+        default_foreground: root.style().color,
+        canvas,
+        origin: 0.0,
+        columns,
+        lines: Vec::new(),
+        blank_line: false,
+        marker: None,
+        links: Vec::new(),
+        link_label: None,
+        measuring: false,
+    };
+    renderer.render_box(root, CellStyle::default(), 0);
+    renderer.render_references();
+    while renderer.lines.last().is_some_and(|line| is_blank(line)) {
+        renderer.lines.pop();
+    }
+    TextPage {
+        columns,
+        lines: renderer.lines,
+        links: renderer.links,
+    }
+}
+
+fn is_element(layout_box: &LayoutBox, kind: ElementKind) -> bool {
+    layout_box
+        .node()
+        .is_some_and(|node| node.borrow().element_kind() == Some(kind))
+}
+
+fn attribute(layout_box: &LayoutBox, name: &str) -> Option<String> {
+    layout_box
+        .node()
+        .and_then(|node| match node.borrow().kind() {
+            NodeKind::Element(element) => element.get_attribute(name),
+            _ => None,
+        })
+}
+
+fn is_blank(line: &[Cell]) -> bool {
+    line.iter()
+        .all(|c| c.ch == ' ' && c.style.background.is_none())
+}
+
+// bがaの右に、縦の範囲が重なるように置かれている
+fn is_beside(a: &LayoutBox, b: &LayoutBox) -> bool {
+    let (a, b) = (a.dimensions().border_box(), b.dimensions().border_box());
+    b.x >= a.right() - 0.5 && b.y < a.bottom() && a.y < b.bottom()
+}
+
+// 表の行を上から順に集める
+fn collect_rows<'a>(layout_box: &'a LayoutBox, rows: &mut Vec<&'a LayoutBox>) {
+    for child in layout_box.children() {
+        match child.style().display {
+            Display::TableRow => rows.push(child),
+            Display::TableRowGroup | Display::TableHeaderGroup | Display::TableFooterGroup => {
+                collect_rows(child, rows)
+            }
+            _ => {}
+        }
+    }
+}
+
+impl TextRenderer {
+    // x座標を含む桁
+    fn column(&self, x: f64) -> usize {
+        self.clamp_column(floor((x - self.origin) / self.cell_width + 1e-6))
+    }
+
+    // 右端のx座標を含む桁の次の桁。左端と同じく、桁の途中にかかる場合はその桁まで含める
+    fn column_end(&self, x: f64) -> usize {
+        self.clamp_column(ceil((x - self.origin) / self.cell_width - 1e-6))
+    }
+
+    fn clamp_column(&self, column: f64) -> usize {
+        if column <= 0.0 {
+            0
+        } else {
+            (column as usize).min(self.columns)
+        }
+    }
+
+    fn foreground(&self, color: Color) -> Option<Color> {
+        (color.a != 0 && color != self.default_foreground).then_some(color)
+    }
+
+    fn background(&self, color: Color) -> Option<Color> {
+        (color.a != 0 && color != self.canvas).then_some(color)
+    }
+
+    // 文字の装飾はテキストのスタイルに祖先から伝わった下線などを加える
+    fn char_style(&self, style: &ComputedStyle, base: CellStyle) -> CellStyle {
+        CellStyle {
+            foreground: self.foreground(style.color),
+            bold: style.font_weight >= 600,
+            italic: style.font_style != FontStyle::Normal,
+            ..base
+        }
+    }
+
+    // 新しい行を追加する。リストマーカーが残っていればstartの列の左に置き、
+    // 左に1桁空けて置く余地がなければマーカーだけの行にする
+    fn new_line(&mut self, start: usize) -> usize {
+        if self.blank_line && self.lines.last().is_some_and(|line| !is_blank(line)) {
+            self.lines.push(vec![Cell::BLANK; self.columns]);
+        }
+        self.blank_line = false;
+        self.lines.push(vec![Cell::BLANK; self.columns]);
+        if let Some((marker, style)) = self.marker.take() {
+            let width = text_columns(&marker);
+            let mut column = start.saturating_sub(width + 1);
+            for ch in marker.chars() {
+                if column + char_columns(ch) > self.columns {
+                    self.lines.push(vec![Cell::BLANK; self.columns]);
+                    column = 0;
+                }
+                column += self.put(self.lines.len() - 1, column, ch, style);
+            }
+            if width >= start {
+                self.lines.push(vec![Cell::BLANK; self.columns]);
+            }
+        }
+        self.lines.len() - 1
+    }
+
+    // リストマーカーとその右の1桁が収まるように、startの列を右にずらす
+    fn marker_start(&self, start: usize) -> usize {
+        match &self.marker {
+            Some((marker, _)) if text_columns(marker) + 1 < self.columns => {
+                start.max(text_columns(marker) + 1)
+            }
+            _ => start,
+        }
+    }
+
+    // 1文字を置き、置いた桁数を返す。領域からはみ出す文字は捨てる
+    fn put(&mut self, row: usize, column: usize, ch: char, style: CellStyle) -> usize {
+        let width = char_columns(ch);
+        if width == 0 || column + width > self.columns {
+            return width;
+        }
+        let line = &mut self.lines[row];
+        line[column] = Cell { ch, style };
+        if width == 2 {
+            line[column + 1] = Cell {
+                ch: WIDE_TAIL,
+                style,
+            };
+        }
+        width
+    }
+
+    // hrefを持つリンクに番号を振る。幅を測っているときは番号の桁数だけ合わせて登録しない
+    fn link_number(&mut self, layout_box: &LayoutBox) -> Option<String> {
+        let href = attribute(layout_box, "href")?;
+        if !self.measuring {
+            self.links.push(href);
+            return Some(format!("[{}]", self.links.len()));
+        }
+        Some(format!("[{}]", self.links.len() + 1))
+    }
+
+    fn render_box(&mut self, layout_box: &LayoutBox, base: CellStyle, ordinal: usize) {
+        if layout_box.kind() == BoxKind::Table {
+            self.render_table(layout_box, base);
+            return;
+        }
+
+        let dimensions = layout_box.dimensions();
+        let style = layout_box.style();
+        // 文字の高さの半分以上のマージンは空行にする
+        let margin_lines = style.font_size / 2.0;
+        if dimensions.margin.top >= margin_lines {
+            self.blank_line = true;
+        }
+        if style.display == Display::ListItem {
+            if let Some(marker) = list_marker(style.list_style_type, ordinal) {
+                self.marker = Some((marker, self.char_style(style, base)));
+            }
+        }
+        let base = CellStyle {
+            underline: base.underline || style.text_decoration.underline,
+            line_through: base.line_through || style.text_decoration.line_through,
+            ..base
+        };
+        // マージンの空行は背景で塗らない
+        let separated = self.blank_line && self.lines.last().is_some_and(|line| !is_blank(line));
+        let first_line = self.lines.len() + separated as usize;
+
+        if layout_box.is_inline_level() || layout_box.replaced().is_some() {
+            self.render_paragraph(layout_box, core::slice::from_ref(layout_box), base);
+        } else {
+            // フレックスアイテムなどブロックレベルのリンク。インラインのリンクはcollect_inlineで番号を振る
+            if is_element(layout_box, ElementKind::A) {
+                if let Some(label) = self.link_number(layout_box) {
+                    self.link_label = Some((label, self.char_style(style, base)));
+                }
+            }
+            if layout_box.has_inline_children() {
+                self.render_paragraph(layout_box, layout_box.children(), base);
+            } else {
+                let mut ordinal = 0;
+                let mut row: Vec<(&LayoutBox, usize)> = Vec::new();
+                for child in layout_box.children() {
+                    if child.style().display == Display::ListItem {
+                        ordinal += 1;
+                    }
+                    // フレックスアイテムなど、前のボックスの右に並ぶボックスは同じ行にまとめる
+                    if row.last().is_some_and(|(last, _)| !is_beside(last, child)) {
+                        self.render_row(&row, base);
+                        row.clear();
+                    }
+                    row.push((child, ordinal));
+                }
+                self.render_row(&row, base);
+            }
+            // 文字のないリンクは番号だけの行にする
+            if let Some((label, style)) = self.link_label.take() {
+                let mut content = Vec::new();
+                push_str(&mut content, &label, style);
+                let start = self.column(dimensions.content.x);
+                self.emit_line(&content, start, self.columns - start, TextAlign::Start);
+            }
+        }
+        // 中身のないリスト項目もマーカーだけの行にする
+        if style.display == Display::ListItem && self.marker.is_some() {
+            let start = self.marker_start(self.column(dimensions.content.x));
+            self.new_line(start);
+        }
+
+        // 子孫が背景を塗っていない部分をボックスの背景で塗る
+        if let Some(background) = self.background(style.background_color) {
+            let border_box = dimensions.border_box();
+            let (start, end) = (
+                self.column(border_box.x),
+                self.column_end(border_box.right()),
+            );
+            let first_line = first_line.min(self.lines.len());
+            for line in &mut self.lines[first_line..] {
+                for cell in &mut line[start..end] {
+                    if cell.style.background.is_none() {
+                        cell.style.background = Some(background);
+                    }
+                }
+            }
+        }
+        if dimensions.margin.bottom >= margin_lines {
+            self.blank_line = true;
+        }
+    }
+
+    // インラインレベルのボックスの文字を集め、ブロックの内容の幅で折り返す
+    fn render_paragraph(&mut self, block: &LayoutBox, inlines: &[LayoutBox], base: CellStyle) {
+        let mut content = Vec::new();
+        for inline in inlines {
+            self.collect_inline(inline, base, &mut content);
+        }
+        while content.last().is_some_and(|c| c.is_collapsible_space()) {
+            content.pop();
+        }
+        if content.is_empty() {
+            return;
+        }
+        if let Some((label, style)) = self.link_label.take() {
+            let mut labeled = Vec::new();
+            push_str(&mut labeled, &label, style);
+            labeled.append(&mut content);
+            content = labeled;
+        }
+
+        let content_box = block.dimensions().content;
+        let start = self.marker_start(self.column(content_box.x).min(self.columns - 1));
+        let end = self.column_end(content_box.right()).max(start + 1);
+        let width = end - start;
+        let align = block.style().text_align;
+
+        let chars: Vec<char> = content.iter().map(|c| c.ch).collect();
+        let opportunities = break_opportunities(&chars);
+        let mut line: Vec<StyledChar> = Vec::new();
+        let mut line_width = 0;
+        let mut segment_start = 0;
+        for i in 1..=content.len() {
+            let boundary = i == content.len()
+                || opportunities[i] == BreakOpportunity::Mandatory
+                || (opportunities[i] == BreakOpportunity::Allowed && content[i].wrap);
+            if !boundary {
+                continue;
+            }
+            let segment = &content[segment_start..i];
+            segment_start = i;
+
+            // 行末の空白は行に収まるかどうかの判定に含めない
+            let visible = segment
+                .iter()
+                .rposition(|c| !c.is_collapsible_space() && c.ch != '\n')
+                .map_or(0, |end| end + 1);
+            let segment_width: usize = segment[..visible].iter().map(|c| char_columns(c.ch)).sum();
+            if line_width + segment_width > width && !line.is_empty() {
+                self.emit_line(&line, start, width, align);
+                line.clear();
+                line_width = 0;
+            }
+            for c in segment {
+                if c.ch == '\n' || (line.is_empty() && c.is_collapsible_space()) {
+                    continue;
+                }
+                let columns = char_columns(c.ch);
+                if line_width + columns > width && c.is_collapsible_space() {
+                    continue;
+                }
+                // 内容の幅に収まらない単語は右にはみ出させ、格子の端にも収まらない場合だけ途中で折り返す
+                if line_width + columns > width.max(self.columns - start) && !line.is_empty() {
+                    self.emit_line(&line, start, width, align);
+                    line.clear();
+                    line_width = 0;
+                }
+                line.push(*c);
+                line_width += columns;
+            }
+            if i < content.len() && opportunities[i] == BreakOpportunity::Mandatory {
+                self.emit_line(&line, start, width, align);
+                line.clear();
+                line_width = 0;
+            }
+        }
+        if !line.is_empty() {
+            self.emit_line(&line, start, width, align);
+        }
+    }
+
+    fn emit_line(&mut self, line: &[StyledChar], start: usize, width: usize, align: TextAlign) {
+        let end = line
+            .iter()
+            .rposition(|c| !c.is_collapsible_space())
+            .map_or(0, |end| end + 1);
+        let line = &line[..end];
+        let line_width: usize = line.iter().map(|c| char_columns(c.ch)).sum();
+        let offset = match align {
+            _ if self.measuring => 0,
+            TextAlign::Center => width.saturating_sub(line_width) / 2,
+            TextAlign::Right | TextAlign::End => width.saturating_sub(line_width),
+            _ => 0,
+        };
+        let row = self.new_line(start);
+        let mut column = start + offset;
+        for c in line {
+            column += self.put(row, column, c.ch, c.style);
+        }
+    }
+
+    fn collect_inline(
+        &mut self,
+        layout_box: &LayoutBox,
+        base: CellStyle,
+        content: &mut Vec<StyledChar>,
+    ) {
+        let style = layout_box.style();
+        if let BoxKind::Text(text) = layout_box.kind() {
+            self.collect_text(&text, style, self.char_style(style, base), content);
+            return;
+        }
+        if layout_box.replaced().is_some() {
+            // lynxと同じく、代替テキストを括弧で囲んで画像の代わりにする
+            let label = match attribute(layout_box, "alt") {
+                Some(alt) if alt.trim().is_empty() => return,
+                Some(alt) => format!("[{}]", alt.trim()),
+                None => "[IMG]".to_string(),
+            };
+            push_str(content, &label, self.char_style(style, base));
+            return;
+        }
+
+        let mut base = CellStyle {
+            underline: base.underline || style.text_decoration.underline,
+            line_through: base.line_through || style.text_decoration.line_through,
+            ..base
+        };
+        if let Some(background) = self.background(style.background_color) {
+            base.background = Some(background);
+        }
+        if is_element(layout_box, ElementKind::A) {
+            if let Some(label) = self.link_number(layout_box) {
+                push_str(content, &label, self.char_style(style, base));
+            }
+        }
+        for child in layout_box.children() {
+            self.collect_inline(child, base, content);
+        }
+    }
+
+    // white-spaceに従って空白をまとめ、文字を加える。CSS Text 4.1
+    fn collect_text(
+        &self,
+        text: &str,
+        style: &ComputedStyle,
+        char_style: CellStyle,
+        content: &mut Vec<StyledChar>,
+    ) {
+        let collapsible = matches!(
+            style.white_space,
+            WhiteSpace::Normal | WhiteSpace::Nowrap | WhiteSpace::PreLine
+        );
+        let keeps_newlines = !matches!(style.white_space, WhiteSpace::Normal | WhiteSpace::Nowrap);
+        let wrap = !matches!(style.white_space, WhiteSpace::Nowrap | WhiteSpace::Pre);
+        let visible = style.visibility == Visibility::Visible;
+        for ch in text.chars() {
+            let ch = match ch {
+                '\r' => continue,
+                '\n' if keeps_newlines => '\n',
+                '\n' | '\t' if collapsible => ' ',
+                ch => ch,
+            };
+            if ch == ' '
+                && collapsible
+                && content
+                    .last()
+                    .map_or(true, |last| last.is_collapsible_space() || last.ch == '\n')
+            {
+                continue;
+            }
+            // タブは8桁ごとの位置まで空白で埋める
+            let (ch, count) = if ch == '\t' {
+                let line_start = content
+                    .iter()
+                    .rposition(|c| c.ch == '\n')
+                    .map_or(0, |i| i + 1);
+                let column: usize = content[line_start..]
+                    .iter()
+                    .map(|c| char_columns(c.ch))
+                    .sum();
+                (' ', 8 - column % 8)
+            } else {
+                (ch, 1)
+            };
+            let ch = if visible || ch.is_whitespace() {
+                ch
+            } else {
+                ' '
+            };
+            for _ in 0..count {
+                content.push(StyledChar {
+                    ch,
+                    style: char_style,
+                    collapsible,
+                    wrap,
+                });
+            }
+        }
+    }
+
+    // ボックスを別の格子に描く。表のセルの中身を描くのに使い、パディングボックスの幅をcolumns桁に合わせる
+    fn render_detached(
+        &mut self,
+        layout_box: &LayoutBox,
+        columns: usize,
+        base: CellStyle,
+        ordinal: usize,
+    ) -> Vec<Vec<Cell>> {
+        let padding_box = layout_box.dimensions().padding_box();
+        let cell_width = if padding_box.width > 0.0 {
+            padding_box.width / columns as f64
+        } else {
+            self.cell_width
+        };
+        let saved = (
+            core::mem::take(&mut self.lines),
+            core::mem::replace(&mut self.origin, padding_box.x),
+            core::mem::replace(&mut self.columns, columns),
+            core::mem::replace(&mut self.cell_width, cell_width),
+            core::mem::take(&mut self.blank_line),
+            self.marker.take(),
+        );
+        self.render_box(layout_box, base, ordinal);
+        let mut lines = core::mem::replace(&mut self.lines, saved.0);
+        self.origin = saved.1;
+        self.columns = saved.2;
+        self.cell_width = saved.3;
+        self.blank_line = saved.4;
+        self.marker = saved.5;
+
+        while lines.last().is_some_and(|line| is_blank(line)) {
+            lines.pop();
+        }
+        let leading = lines.iter().take_while(|line| is_blank(line)).count();
+        lines.drain(..leading);
+        lines
+    }
+
+    // 横に並んだボックスをそれぞれ別の格子に描き、左から順に同じ行に重ねる。
+    // 桁数に収まらなくなったら、次のボックスから改めて行を始める
+    fn render_row(&mut self, row: &[(&LayoutBox, usize)], base: CellStyle) {
+        match row {
+            [] => return,
+            [(layout_box, ordinal)] => {
+                self.render_box(layout_box, base, *ordinal);
+                return;
+            }
+            _ => {}
+        }
+
+        let origin = self
+            .column(row[0].0.dimensions().padding_box().x)
+            .min(self.columns - 1);
+        let mut placed = Vec::new();
+        let mut next = 0;
+        for (layout_box, ordinal) in row {
+            let padding_box = layout_box.dimensions().padding_box();
+            let left = self.column(padding_box.x);
+            // 桁に丸めたせいで単語が収まらなくならないよう、折り返さない内容の幅まで広げる
+            let columns = self
+                .column_end(padding_box.right())
+                .saturating_sub(left)
+                .max(self.content_width(layout_box, base, self.columns - origin));
+            let mut start = left.max(next).min(self.columns - 1);
+            if start + columns > self.columns && !placed.is_empty() {
+                self.place_row(&placed);
+                placed.clear();
+                start = origin;
+            }
+            let columns = columns.clamp(1, self.columns - start);
+            let lines = self.render_detached(layout_box, columns, base, *ordinal);
+            let width = lines
+                .iter()
+                .map(|line| {
+                    line.iter()
+                        .rposition(|c| c.ch != ' ' || c.style.background.is_some())
+                        .map_or(0, |i| i + 1)
+                })
+                .max()
+                .unwrap_or(0);
+            // 隣のボックスとの間は少なくとも1桁空ける
+            next = start + width + 1;
+            if layout_box.dimensions().margin.top >= layout_box.style().font_size / 2.0 {
+                self.blank_line = true;
+            }
+            placed.push((start, lines));
+        }
+        self.place_row(&placed);
+        if row.iter().any(|(layout_box, _)| {
+            layout_box.dimensions().margin.bottom >= layout_box.style().font_size / 2.0
+        }) {
+            self.blank_line = true;
+        }
+    }
+
+    // 別々に描いたボックスの行を、それぞれの開始桁に置いて重ねる
+    fn place_row(&mut self, placed: &[(usize, Vec<Vec<Cell>>)]) {
+        let height = placed
+            .iter()
+            .map(|(_, lines)| lines.len())
+            .max()
+            .unwrap_or(0);
+        for i in 0..height {
+            let row = self.new_line(placed[0].0);
+            for (start, lines) in placed {
+                let Some(line) = lines.get(i) else {
+                    continue;
+                };
+                for (j, cell) in line.iter().enumerate() {
+                    if start + j < self.columns
+                        && (cell.ch != ' ' || cell.style.background.is_some())
+                    {
+                        self.lines[row][start + j] = *cell;
+                    }
+                }
+            }
+        }
+    }
+
+    // 折り返さずに描いたときのセルの中身の桁数。行揃えによる余白は含めない
+    fn content_width(&mut self, cell: &LayoutBox, base: CellStyle, available: usize) -> usize {
+        let measuring = core::mem::replace(&mut self.measuring, true);
+        let lines = self.render_detached(cell, available, base, 0);
+        self.measuring = measuring;
+        lines
+            .iter()
+            .map(|line| line.iter().rposition(|c| c.ch != ' ').map_or(0, |i| i + 1))
+            .max()
+            .unwrap_or(0)
+    }
+
+    // セルの境界を罫線で囲んだ表にする。列の幅はレイアウトしたセルの幅を桁に換算して決める
+    fn render_table(&mut self, table: &LayoutBox, base: CellStyle) {
+        let mut rows = Vec::new();
+        collect_rows(table, &mut rows);
+        rows.sort_by(|a, b| {
+            let (a, b) = (a.dimensions().border_box().y, b.dimensions().border_box().y);
+            a.partial_cmp(&b).unwrap_or(core::cmp::Ordering::Equal)
+        });
+        let mut edges: Vec<f64> = Vec::new();
+        for row in &rows {
+            for cell in row.children() {
+                let border_box = cell.dimensions().border_box();
+                edges.push(border_box.x);
+                edges.push(border_box.right());
+            }
+        }
+        edges.sort_by(|a, b| a.partial_cmp(b).unwrap_or(core::cmp::Ordering::Equal));
+        // セルの間隔のような半桁に満たない隙間は1本の境界にまとめる
+        let cell_width = self.cell_width;
+        edges.dedup_by(|a, b| *a - *b < cell_width / 2.0);
+        if edges.len() < 2 {
+            self.render_unboxed_table(&rows, base);
+            return;
+        }
+
+        let edge_index = |x: f64| edges.iter().rposition(|edge| *edge <= x + 0.5).unwrap_or(0);
+        let mut spans = Vec::new();
+        for (index, row) in rows.iter().enumerate() {
+            for cell in row.children() {
+                let border_box = cell.dimensions().border_box();
+                let columns = (edge_index(border_box.x), edge_index(border_box.right()));
+                if columns.1 <= columns.0 {
+                    // 桁に換算すると幅がなくなるセルに中身があれば、罫線で囲まずに描く
+                    if self.content_width(cell, base, self.columns) > 0 {
+                        self.render_unboxed_table(&rows, base);
+                        return;
+                    }
+                    continue;
+                }
+                let last_row = rows
+                    .iter()
+                    .rposition(|r| r.dimensions().border_box().y < border_box.bottom() - 0.5)
+                    .unwrap_or(index)
+                    .max(index);
+                spans.push((cell, columns, (index, last_row)));
+            }
+        }
+
+        // 列の幅は罫線の1桁を除いた桁数。折り返さずに中身が収まる幅を優先し、端末に収まらなければその比で縮める
+        let left = self.column(edges[0]);
+        let count = edges.len() - 1;
+        let available = self.columns.saturating_sub(left + count + 1).max(count);
+        let mut preferred = vec![1; count];
+        let mut spans_by_width = spans.clone();
+        spans_by_width.sort_by_key(|(_, columns, _)| columns.1 - columns.0);
+        for (cell, (first, end), _) in spans_by_width {
+            let needed = self.content_width(cell, base, available);
+            let width = saturating_sum(&preferred[first..end]) + end - first - 1;
+            if needed > width {
+                preferred[end - 1] += needed - width;
+            }
+        }
+        let widths: Vec<usize> = edges
+            .windows(2)
+            .zip(&preferred)
+            .map(|(pair, preferred)| {
+                // 無限に広いセルも格子の幅までに抑える
+                let width = floor((pair[1] - pair[0]) / self.cell_width + 0.5)
+                    .min(self.columns as f64) as usize;
+                width.saturating_sub(1).max(*preferred)
+            })
+            .collect();
+        let widths = if saturating_sum(&widths) <= available {
+            widths
+        } else {
+            let total = saturating_sum(&preferred);
+            preferred
+                .iter()
+                .map(|width| (width * available / total.max(available)).max(1))
+                .collect()
+        };
+        let mut positions = vec![left];
+        for width in widths {
+            positions.push(positions[positions.len() - 1] + width + 1);
+        }
+        if positions[positions.len() - 1] >= self.columns {
+            self.render_unboxed_table(&rows, base);
+            return;
+        }
+
+        let mut cells = Vec::new();
+        for (cell, columns, rows) in spans {
+            let width = positions[columns.1] - positions[columns.0] - 1;
+            let lines = self.render_detached(cell, width, base, 0);
+            cells.push(TableCell {
+                columns,
+                rows,
+                lines,
+            });
+        }
+
+        // 行の高さは1行に収まるセルの行数の最大値。複数の行にまたがるセルは最後の行を高くする
+        let mut heights = vec![1; rows.len()];
+        for cell in cells.iter().filter(|c| c.rows.0 == c.rows.1) {
+            heights[cell.rows.0] = heights[cell.rows.0].max(cell.lines.len());
+        }
+        for cell in cells.iter().filter(|c| c.rows.0 != c.rows.1) {
+            let (first, last) = cell.rows;
+            let height = saturating_sum(&heights[first..=last]) + last - first;
+            if cell.lines.len() > height {
+                heights[last] += cell.lines.len() - height;
+            }
+        }
+        let mut tops = vec![0];
+        for height in &heights {
+            tops.push(tops[tops.len() - 1] + height + 1);
+        }
+
+        let width = positions[positions.len() - 1] - left + 1;
+        let mut grid = vec![vec![0_u8; width]; tops[tops.len() - 1] + 1];
+        for cell in &cells {
+            let (x0, x1) = (
+                positions[cell.columns.0] - left,
+                positions[cell.columns.1] - left,
+            );
+            let (y0, y1) = (tops[cell.rows.0], tops[cell.rows.1 + 1]);
+            for y in [y0, y1] {
+                for x in x0..x1 {
+                    grid[y][x] |= RIGHT;
+                    grid[y][x + 1] |= LEFT;
+                }
+            }
+            for x in [x0, x1] {
+                for line in &mut grid[y0..y1] {
+                    line[x] |= DOWN;
+                }
+                for line in &mut grid[y0 + 1..=y1] {
+                    line[x] |= UP;
+                }
+            }
+        }
+
+        let border_style = CellStyle {
+            foreground: self.foreground(table.style().border_color.top),
+            ..CellStyle::default()
+        };
+        for line in &grid {
+            let row = self.new_line(left);
+            for (x, edges) in line.iter().enumerate() {
+                if *edges != 0 {
+                    self.put(row, left + x, box_drawing(*edges), border_style);
+                }
+            }
+        }
+        let first_row = self.lines.len() - grid.len();
+        for cell in &cells {
+            let column = positions[cell.columns.0] + 1;
+            for (i, line) in cell.lines.iter().enumerate() {
+                let row = first_row + tops[cell.rows.0] + 1 + i;
+                for (x, c) in line.iter().enumerate() {
+                    if column + x < self.columns {
+                        self.lines[row][column + x] = *c;
+                    }
+                }
+            }
+        }
+    }
+
+    // 罫線を引く桁の余裕がない表は、セルを上から順に罫線のないブロックとして描く
+    fn render_unboxed_table(&mut self, rows: &[&LayoutBox], base: CellStyle) {
+        for row in rows {
+            for cell in row.children() {
+                self.render_box(cell, base, 0);
+            }
+        }
+    }
+
+    // lynxのように、番号を付けたリンクの参照先を末尾に並べる
+    fn render_references(&mut self) {
+        if self.links.is_empty() {
+            return;
+        }
+        self.blank_line = true;
+        let heading = CellStyle {
+            bold: true,
+            ..CellStyle::default()
+        };
+        let row = self.new_line(0);
+        for (i, ch) in "References".chars().enumerate() {
+            self.put(row, i, ch, heading);
+        }
+        self.blank_line = true;
+        for (i, link) in self.links.clone().iter().enumerate() {
+            let mut row = self.new_line(0);
+            let mut column = 0;
+            for ch in format!("{:>4}. {}", i + 1, link).chars() {
+                if column + char_columns(ch) > self.columns {
+                    row = self.new_line(0);
+                    column = 0;
+                }
+                column += self.put(row, column, ch, CellStyle::default());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::layout::layout_document;
+
+    fn render(html: &str, css: &str, columns: usize) -> TextPage {
+        let (_, view) = layout_document(html, css);
+        render_text(&view, columns)
+    }
+
+    #[test]
+    fn test_paragraphs_and_lists() {
+        let page = render(
+            "<html><body><h1>Title</h1><p>The quick brown fox jumps over the lazy dog.</p>\
+             <ul><li>one</li><li>two<ol><li>nested</li><li>list</li></ol></li></ul>\
+             <p class=\"right\">right</p><p class=\"pre\">a\n  b</p></body></html>",
+            ".right { text-align: right } .pre { white-space: pre }",
+            30,
+        );
+        assert_eq!(
+            "Title\n\nThe quick brown fox jumps over\nthe lazy dog.\n\n• one\n• two\n 1. nested\n 2. list\n\n                         right\n\na\n  b\n",
+            page.to_plain_text()
+        );
+        assert_eq!(30, page.lines()[0].len());
+        assert!(page.lines()[0][0].style.bold);
+    }
+
+    #[test]
+    fn test_table() {
+        let page = render(
+            "<html><body><table><tr><th>Name</th><th>Value</th></tr><tr><td>alpha</td><td>1</td></tr>\
+             <tr><td colspan=\"2\">spanning cell</td></tr></table></body></html>",
+            "td, th { border: 1px solid; text-align: left }",
+            40,
+        );
+        assert_eq!(
+            "┌─────┬───────┐\n│Name │Value  │\n├─────┼───────┤\n│alpha│1      │\n├─────┴───────┤\n│spanning cell│\n└─────────────┘\n",
+            page.to_plain_text()
+        );
+    }
+
+    #[test]
+    fn test_links_and_colors() {
+        let page = render(
+            "<html><body><p>a <a href=\"http://example.com/\">link</a><a class=\"red\">red </a>日本語</p></body></html>",
+            ".red { color: #ff0000; background-color: #00ff00 }",
+            40,
+        );
+        assert_eq!(&["http://example.com/".to_string()], page.links());
+        assert_eq!(
+            "a [1]linkred 日本語\n\nReferences\n\n   1. http://example.com/\n",
+            page.to_plain_text()
+        );
+        assert_eq!(
+            "a \x1b[0;4;38;2;0;0;238m[1]link\x1b[0;38;2;255;0;0;48;2;0;255;0mred \x1b[0m日本語\n",
+            page.to_ansi_text().lines().next().unwrap().to_string() + "\n"
+        );
+        // 全角文字は2桁を占める
+        assert_eq!(WIDE_TAIL, page.lines()[0][14].ch);
+    }
+
+    #[test]
+    fn test_block_level_links() {
+        // フレックスアイテムのリンクや表の中のリンクにも1回だけ番号を振る
+        let page = render(
+            "<html><body><p class=\"nav\"><a href=\"/\">Home</a><a href=\"/about\">About</a><a href=\"/empty\"></a></p>\
+             <table><tr><td><a href=\"/cell\">cell</a></td></tr></table></body></html>",
+            ".nav { display: flex }",
+            60,
+        );
+        assert_eq!(
+            &["/", "/about", "/empty", "/cell"].map(|s| s.to_string()),
+            page.links()
+        );
+        let text = page.to_plain_text();
+        for label in ["[1]Home [2]About [3]", "[4]cell"] {
+            assert!(text.contains(label), "{} is not in {}", label, text);
+        }
+    }
+
+    #[test]
+    fn test_flex_row() {
+        // 桁に換算すると文字の幅より狭くなる短いリンクも、単語の途中で折り返さずに横に並べる
+        let html = "<html><body><p class=\"row\"><a href=\"/\">Home</a><a href=\"/about\">About us</a>\
+                    <a href=\"/contact\">Contact</a><a href=\"/x\">and</a></p><p>After</p></body></html>";
+        let css = ".row { display: flex } .row a { margin-right: 7px }";
+        let page = render(html, css, 60);
+        assert_eq!(
+            "[1]Home [2]About us [3]Contact [4]and\n\nAfter\n",
+            page.to_plain_text()
+                .split("\n\nReferences")
+                .next()
+                .unwrap()
+                .to_string()
+                + "\n"
+        );
+        // 桁数に収まらないボックスは次の行に送る
+        let page = render(html, css, 20);
+        assert_eq!(
+            "[1]Home [2]About us\n[3]Contact [4]and\n\nAfter\n",
+            page.to_plain_text()
+                .split("\n\nReferences")
+                .next()
+                .unwrap()
+                .to_string()
+                + "\n"
+        );
+    }
+
+    #[test]
+    fn test_infinite_lengths() {
+        // 無限に広いセルや余白も格子の幅に抑え、桁数の合計をあふれさせない
+        let html = "<html><body><p>hello world</p><table><tr><td>a</td><td>b</td></tr></table></body></html>";
+        let page = render(html, "td { width: 1e400px }", 80);
+        let text = page.to_plain_text();
+        let lines: Vec<&str> = text.lines().map(|line| line.trim()).collect();
+        assert_eq!(" hello world", text.lines().next().unwrap());
+        // 無限の位置にある2番目のセルも捨てずに描く
+        assert_eq!(vec!["hello world", "", "a", "b"], lines);
+        for columns in [2, 10] {
+            render(html, "p, td { padding: 1e400px }", columns);
+            render(html, "* { font-size: calc(infinity * 1px) }", columns);
+        }
+    }
+
+    #[test]
+    fn test_narrow_columns() {
+        // マーカーと本文の間は1桁空ける
+        let html = "<html><body><ol><li>first item</li><li>second</li></ol></body></html>";
+        assert_eq!(
+            "1. first item\n2. second\n",
+            render(html, "", 40).to_plain_text()
+        );
+        // 罫線やマーカーが収まらない桁数でも中身を捨てない
+        let html = "<html><body><ul><li>one</li></ul>\
+                    <table><tr><td>alpha</td><td>beta</td></tr></table></body></html>";
+        let text = render(html, "", 1).to_plain_text();
+        assert!(text.lines().all(|line| line.chars().count() <= 1));
+        assert_eq!(
+            "•onealphabeta",
+            text.split_whitespace().collect::<Vec<_>>().concat()
+        );
+        assert_eq!(
+            "alpha\nbeta\n",
+            render(html, "ul { display: none }", 6).to_plain_text()
+        );
+    }
+}
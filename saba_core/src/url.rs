@@ -50,19 +50,19 @@ impl Url {
         }
     }
 
-    fn host(&self) -> String {
+    pub fn host(&self) -> String {
         self.host.clone()
     }
 
-    fn port(&self) -> String {
+    pub fn port(&self) -> String {
         self.port.clone()
     }
 
-    fn path(&self) -> String {
+    pub fn path(&self) -> String {
         self.path.clone()
     }
 
-    fn params(&self) -> String {
+    pub fn params(&self) -> String {
         self.params.clone()
     }
 
@@ -4,7 +4,7 @@ use alloc::{
     rc::Rc,
     string::{String, ToString},
 };
-use core::cell::RefCell;
+use core::{cell::RefCell, f64::consts::PI};
 
 pub fn convert_dom_to_string(root: &Option<Rc<RefCell<Node>>>) -> String {
     let mut result = "\n".to_string();
@@ -28,3 +28,91 @@ fn convert_dom_to_string_internal(
         None => {}
     }
 }
+
+// no_stdではf64の数学関数が使えないため、描画に必要なものを用意する
+pub fn floor(x: f64) -> f64 {
+    // 2^52以上の値は小数部を持たないので、i64に収まらない値や無限大、NaNと同様にそのまま返す
+    if !(-4503599627370496.0..4503599627370496.0).contains(&x) {
+        return x;
+    }
+    let truncated = x as i64 as f64;
+    if truncated > x {
+        truncated - 1.0
+    } else {
+        truncated
+    }
+}
+
+pub fn ceil(x: f64) -> f64 {
+    -floor(-x)
+}
+
+// ニュートン法で求める平方根
+pub fn sqrt(x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    // 指数部を半分にした値を初期値にする
+    let mut y = f64::from_bits((x.to_bits() >> 1) + (1023 << 51));
+    for _ in 0..6 {
+        y = (y + x / y) / 2.0;
+    }
+    y
+}
+
+// [-π, π]に収めてからテイラー展開で求める
+pub fn sin(x: f64) -> f64 {
+    let tau = 2.0 * PI;
+    let mut x = x % tau;
+    if x > PI {
+        x -= tau;
+    } else if x < -PI {
+        x += tau;
+    }
+    let mut term = x;
+    let mut sum = x;
+    for n in 1..12 {
+        term *= -x * x / ((2 * n) as f64 * (2 * n + 1) as f64);
+        sum += term;
+    }
+    sum
+}
+
+pub fn cos(x: f64) -> f64 {
+    sin(x + PI / 2.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(expected: f64, actual: f64) {
+        assert!(
+            (expected - actual) < 1e-9 && (actual - expected) < 1e-9,
+            "expected {} but got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn test_math() {
+        assert_eq!(-2.0, floor(-1.5));
+        assert_eq!(1.0, floor(1.5));
+        assert_eq!(2.0, ceil(1.5));
+        assert_eq!(3.0, ceil(3.0));
+        assert_eq!(-0.0, ceil(-0.5));
+        assert!(floor(f64::NAN).is_nan());
+        assert_eq!(f64::INFINITY, floor(f64::INFINITY));
+        assert_eq!(f64::NEG_INFINITY, ceil(f64::NEG_INFINITY));
+        assert_eq!(1e19, floor(1e19));
+        assert_eq!(-1e300, ceil(-1e300));
+        assert_eq!(4503599627370497.0, floor(4503599627370497.0));
+        assert_near(1.5, sqrt(2.25));
+        assert_near(300.0, sqrt(90000.0));
+        assert_near(0.5, sin(PI / 6.0));
+        assert_near(-1.0, sin(-PI / 2.0 + 4.0 * PI));
+        assert_near(0.0, cos(PI / 2.0));
+        assert_near(-1.0, cos(PI));
+    }
+}
@@ -0,0 +1,322 @@
+use saba_core::{
+    browser::Browser,
+    error::Error,
+    http::HttpResponse,
+    renderer::{
+        css::{media::MediaEnvironment, value::Color},
+        paint::encoder::{encode_png, encode_ppm},
+    },
+    url::Url,
+};
+use std::{
+    env, fs,
+    io::{Read, Write},
+    net::TcpStream,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+// 画像の幅と高さの上限。大きすぎる画像のメモリを確保しようとして落ちないようにする
+const MAX_IMAGE_SIZE: f64 = 16384.0;
+
+const USAGE: &str = "usage: saba_headless <file or http url> <output.png|output.ppm|output.txt|-> [--width <px>] [--height <px>] [--scroll-y <px>] [--columns <n>]";
+
+struct Options {
+    input: String,
+    output: PathBuf,
+    width: f64,
+    height: f64,
+    scroll_y: f64,
+    // テキストで出力するときの1行の桁数
+    columns: usize,
+}
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut positional = Vec::new();
+    let mut width = 800.0;
+    let mut height = 600.0;
+    let mut scroll_y = 0.0;
+    let mut columns = 80;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let (target, max) = match arg.as_str() {
+            "--width" => (&mut width, Some(MAX_IMAGE_SIZE)),
+            "--height" => (&mut height, Some(MAX_IMAGE_SIZE)),
+            "--scroll-y" => (&mut scroll_y, None),
+            "--columns" => {
+                columns = args
+                    .next()
+                    .and_then(|value| value.parse::<usize>().ok())
+                    .filter(|value| *value >= 1)
+                    .ok_or_else(|| format!("{} needs a positive integer", arg))?;
+                continue;
+            }
+            _ => {
+                positional.push(arg.clone());
+                continue;
+            }
+        };
+        let value = args
+            .next()
+            .and_then(|value| value.parse::<f64>().ok())
+            .filter(|value| value.is_finite() && *value >= 0.0);
+        *target = match (value, max) {
+            (Some(value), Some(max)) if value <= max => value,
+            (Some(value), None) => value,
+            (_, Some(max)) => return Err(format!("{} needs a number from 0 to {}", arg, max)),
+            (_, None) => return Err(format!("{} needs a non-negative number", arg)),
+        };
+    }
+    match &positional[..] {
+        [input, output] => Ok(Options {
+            input: input.clone(),
+            output: PathBuf::from(output),
+            width,
+            height,
+            scroll_y,
+            columns,
+        }),
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+// 文書の取得元。相対的なhrefを解決するために使う
+enum Source {
+    File(PathBuf),
+    Http(Url),
+}
+
+impl Source {
+    fn new(input: &str) -> Result<Self, String> {
+        if input.starts_with("http://") {
+            Url::try_from(input).map(Source::Http)
+        } else {
+            Ok(Source::File(PathBuf::from(input)))
+        }
+    }
+
+    fn fetch(&self) -> Result<String, String> {
+        match self {
+            Source::File(path) => fs::read_to_string(path)
+                .map_err(|e| format!("failed to read {}: {}", path.display(), e)),
+            Source::Http(url) => {
+                let received = http_get(url)?;
+                let response = String::from_utf8(received)
+                    .map_err(|e| format!("failed to fetch: Invalid received response: {:#?}", e))?;
+                if response.split_whitespace().nth(1) != Some("200") {
+                    return Err(format!(
+                        "failed to fetch: {}",
+                        response.lines().next().unwrap_or_default()
+                    ));
+                }
+                let response = HttpResponse::try_from(response)
+                    .map_err(|e| format!("failed to fetch: {:?}", e))?;
+                Ok(response.body())
+            }
+        }
+    }
+
+    // フォントのようにテキストでないリソース。HTTPではヘッダの後をそのまま本文とする
+    fn fetch_bytes(&self) -> Result<Vec<u8>, String> {
+        match self {
+            Source::File(path) => {
+                fs::read(path).map_err(|e| format!("failed to read {}: {}", path.display(), e))
+            }
+            Source::Http(url) => {
+                let received = http_get(url)?;
+                let header_end = received
+                    .windows(4)
+                    .position(|w| w == b"\r\n\r\n")
+                    .map(|i| i + 4)
+                    .or_else(|| {
+                        received
+                            .windows(2)
+                            .position(|w| w == b"\n\n")
+                            .map(|i| i + 2)
+                    })
+                    .ok_or_else(|| "failed to fetch: no response header".to_string())?;
+                let status = String::from_utf8_lossy(&received[..header_end]);
+                if status.split_whitespace().nth(1) != Some("200") {
+                    return Err(format!(
+                        "failed to fetch: {}",
+                        status.lines().next().unwrap_or_default()
+                    ));
+                }
+                Ok(received[header_end..].to_vec())
+            }
+        }
+    }
+
+    // hrefが指すリソース。絶対URLでなければ文書と同じ場所からの相対パスとする
+    fn resolve(&self, href: &str) -> Result<Source, String> {
+        if href.starts_with("http://") {
+            return Source::new(href);
+        }
+        match self {
+            Source::File(path) => {
+                let base = path.parent().unwrap_or(Path::new(""));
+                Ok(Source::File(base.join(href)))
+            }
+            Source::Http(url) => {
+                // 空のhrefは文書自身、?から始まるhrefは文書のパスでクエリだけを置き換える
+                if href.is_empty() {
+                    return Ok(Source::Http(url.clone()));
+                }
+                let path = match href.strip_prefix('/') {
+                    None if href.starts_with('?') => format!("{}{}", url.path(), href),
+                    Some(absolute) => absolute.to_string(),
+                    None => match url.path().rsplit_once('/') {
+                        Some((directory, _)) => format!("{}/{}", directory, href),
+                        None => href.to_string(),
+                    },
+                };
+                Source::new(&format!("http://{}:{}/{}", url.host(), url.port(), path))
+            }
+        }
+    }
+}
+
+fn http_get(url: &Url) -> Result<Vec<u8>, String> {
+    let (host, path) = (url.host(), url.path());
+    let port = url
+        .port()
+        .parse::<u16>()
+        .map_err(|e| format!("invalid port {}: {}", url.port(), e))?;
+    let mut stream = TcpStream::connect((host.as_str(), port))
+        .map_err(|e| format!("Failed to connect to TCP stream: {:#?}", e))?;
+    let target = match url.params() {
+        params if params.is_empty() => path,
+        params => format!("{}?{}", path, params),
+    };
+    let request =
+        format!("GET /{target} HTTP/1.0\nHost: {host}\nAccept: */*\nConnection: close\n\n");
+    stream
+        .write_all(request.as_bytes())
+        .map_err(|e| format!("Failed to send a request to TCP stream: {:#?}", e))?;
+    let mut received = Vec::new();
+    stream
+        .read_to_end(&mut received)
+        .map_err(|e| format!("Failed to receive a request from TCP stream: {:#?}", e))?;
+    Ok(received)
+}
+
+// 解析、スタイル計算、レイアウト、描画を行い、画像ファイルに書き出す
+fn run(options: &Options) -> Result<(), String> {
+    let source = Source::new(&options.input)?;
+    let html = source.fetch()?;
+    let response = HttpResponse::try_from(format!(
+        "HTTP/1.1 200 OK\nContent-Type: text/html\n\n{}",
+        html
+    ))
+    .map_err(|e| format!("failed to load the document: {:?}", e))?;
+
+    let browser = Browser::new();
+    browser
+        .borrow_mut()
+        .set_media_environment(MediaEnvironment::new(options.width, options.height));
+    let page = browser.borrow().current_page();
+    page.borrow_mut().receive_response(response);
+
+    // 取得できなかったスタイルシートは無視して描く
+    let links = page.borrow().style_sheet_links();
+    for href in links {
+        match source.resolve(&href).and_then(|s| s.fetch()) {
+            Ok(css) => page.borrow_mut().set_linked_style_sheet(&href, css),
+            Err(e) => eprintln!("warning: {}", e),
+        }
+    }
+    // Webフォントはsrcの順に試し、最初に読み込めたものを使う
+    let faces = page.borrow().font_faces();
+    for face in faces {
+        let loaded = face.urls().iter().any(|url| {
+            match source
+                .resolve(url)
+                .and_then(|s| s.fetch_bytes())
+                .and_then(|data| {
+                    page.borrow_mut()
+                        .load_font_face(&face.family, data)
+                        .map_err(|e| format!("failed to load {}: {:?}", url, e))
+                }) {
+                Ok(()) => true,
+                Err(e) => {
+                    eprintln!("warning: {}", e);
+                    false
+                }
+            }
+        });
+        if !loaded {
+            eprintln!("warning: no usable font for {}", face.family);
+        }
+    }
+    // 取得やデコードに失敗した画像はalt属性の文字列で代わりに描く
+    let urls = page.borrow().image_urls();
+    for url in urls {
+        let result = source.resolve(&url).and_then(|s| s.fetch_bytes());
+        match result {
+            Ok(data) => {
+                if let Err(e) = page.borrow_mut().set_image(&url, &data) {
+                    eprintln!("warning: failed to decode {}: {:?}", url, e);
+                }
+            }
+            Err(e) => {
+                eprintln!("warning: {}", e);
+                page.borrow_mut().set_image_error(&url);
+            }
+        }
+    }
+    page.borrow_mut().scroll_to(0.0, options.scroll_y);
+
+    // -なら色付きで標準出力に、.txtならファイルに文字セルとして書き出す
+    let is_text = options
+        .output
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("txt"));
+    if is_text || options.output.as_os_str() == "-" {
+        let text = page
+            .borrow()
+            .render_text(options.columns)
+            .map(|text| {
+                if is_text {
+                    text.to_plain_text()
+                } else {
+                    text.to_ansi_text()
+                }
+            })
+            .unwrap_or_default();
+        return write_text(&options.output, &text);
+    }
+
+    let framebuffer = page.borrow().render();
+    let is_ppm = options
+        .output
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("ppm"));
+    let image = if is_ppm {
+        encode_ppm(&framebuffer, Color::WHITE)
+    } else {
+        encode_png(&framebuffer)
+    };
+    fs::write(&options.output, image)
+        .map_err(|e| format!("failed to write {}: {}", options.output.display(), e))
+}
+
+fn write_text(output: &Path, text: &str) -> Result<(), String> {
+    if output.as_os_str() == "-" {
+        return std::io::stdout()
+            .write_all(text.as_bytes())
+            .map_err(|e| format!("failed to write to stdout: {}", e));
+    }
+    fs::write(output, text).map_err(|e| format!("failed to write {}: {}", output.display(), e))
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = parse_options(&args).and_then(|options| run(&options));
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::FAILURE
+        }
+    }
+}